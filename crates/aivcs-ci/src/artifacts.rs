@@ -0,0 +1,156 @@
+//! Stage output kept as CAS artifacts
+//!
+//! The pipeline stores each stage's stdout and stderr as CAS blobs and
+//! records their digests in the stage's `tool_returned`/`tool_failed` event
+//! (`stdout_digest`, `stderr_digest`). The event itself keeps only the
+//! last [`LOG_PREVIEW_BYTES`] of each stream. [`stage_logs`] finds those
+//! digests again for a run and stage and reads the blobs back.
+
+use aivcs_core::cas::{CasStore, Digest};
+use anyhow::{Context, Result};
+use oxidized_state::{RunId, RunLedger};
+
+/// Largest stored log; longer output keeps its tail, where errors land
+pub const MAX_LOG_BYTES: usize = 4 * 1024 * 1024;
+
+/// Largest preview of a stored log kept inline in a stage's result event
+pub const LOG_PREVIEW_BYTES: usize = 2 * 1024;
+
+/// The last [`LOG_PREVIEW_BYTES`] of `output`
+pub fn log_preview(output: &str) -> &str {
+    let mut cut = output.len().saturating_sub(LOG_PREVIEW_BYTES);
+    while !output.is_char_boundary(cut) {
+        cut += 1;
+    }
+    &output[cut..]
+}
+
+/// A log written to CAS by [`store_log`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredLog {
+    /// CAS digest of the stored text
+    pub digest: Digest,
+    /// Size of the original output in bytes
+    pub original_bytes: usize,
+    /// Whether the leading part of the output was dropped
+    pub truncated: bool,
+}
+
+/// Store `output` in CAS, keeping only its last [`MAX_LOG_BYTES`] behind a
+/// note saying how much was dropped
+pub fn store_log(cas: &dyn CasStore, output: &str) -> Result<StoredLog> {
+    let truncated = output.len() > MAX_LOG_BYTES;
+    let digest = if truncated {
+        let mut cut = output.len() - MAX_LOG_BYTES;
+        while !output.is_char_boundary(cut) {
+            cut += 1;
+        }
+        let text = format!(
+            "[aivcs: output truncated, {} leading bytes dropped]\n{}",
+            cut,
+            &output[cut..]
+        );
+        cas.put(text.as_bytes())
+    } else {
+        cas.put(output.as_bytes())
+    }
+    .map_err(|e| anyhow::anyhow!("failed to store stage log: {e}"))?;
+    Ok(StoredLog {
+        digest,
+        original_bytes: output.len(),
+        truncated,
+    })
+}
+
+/// Output of one stage of a recorded CI run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageLogs {
+    /// Recorded stage name, e.g. `cargo_clippy`
+    pub stage: String,
+    pub stdout: String,
+    pub stderr: String,
+    /// Whether either stream was truncated before storing
+    pub truncated: bool,
+}
+
+/// Read back the stored output of `stage` in run `run_id`
+///
+/// `stage` matches the recorded stage name or its builtin short form, so
+/// `clippy` finds `cargo_clippy`.
+pub async fn stage_logs(
+    ledger: &dyn RunLedger,
+    cas: &dyn CasStore,
+    run_id: &str,
+    stage: &str,
+) -> Result<StageLogs> {
+    let events = ledger
+        .get_events(&RunId(run_id.to_string()))
+        .await
+        .with_context(|| format!("failed to load events of run {run_id}"))?;
+    let event = events
+        .iter()
+        .filter(|e| e.kind == "tool_returned" || e.kind == "tool_failed")
+        .find(|e| {
+            e.payload["tool_name"]
+                .as_str()
+                .is_some_and(|name| crate::gate::names_stage(stage, name))
+        })
+        .with_context(|| format!("run {run_id} has no completed stage '{stage}'"))?;
+
+    let read = |field: &str| -> Result<String> {
+        let hex = event.payload[field].as_str().with_context(|| {
+            format!("stage '{stage}' of run {run_id} has no stored output ({field} missing)")
+        })?;
+        let digest: Digest = hex
+            .parse()
+            .with_context(|| format!("invalid {field} '{hex}'"))?;
+        let blob = cas
+            .get(&digest)
+            .with_context(|| format!("log blob {hex} is not in the CAS store"))?;
+        Ok(String::from_utf8_lossy(&blob).into_owned())
+    };
+
+    Ok(StageLogs {
+        stage: event.payload["tool_name"]
+            .as_str()
+            .unwrap_or(stage)
+            .to_string(),
+        stdout: read("stdout_digest")?,
+        stderr: read("stderr_digest")?,
+        truncated: event.payload["stdout_truncated"].as_bool() == Some(true)
+            || event.payload["stderr_truncated"].as_bool() == Some(true),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aivcs_core::cas::memory::MemoryCasStore;
+
+    #[test]
+    fn test_store_log_keeps_tail_of_oversized_output() {
+        let cas = MemoryCasStore::new();
+        let output = format!("{}error: the real failure", "x".repeat(MAX_LOG_BYTES));
+
+        let stored = store_log(&cas, &output).unwrap();
+        assert!(stored.truncated);
+        assert_eq!(stored.original_bytes, output.len());
+        let text = String::from_utf8(cas.get(&stored.digest).unwrap()).unwrap();
+        assert!(text.starts_with("[aivcs: output truncated, 24 leading bytes dropped]\n"));
+        assert!(text.ends_with("error: the real failure"));
+
+        let small = store_log(&cas, "ok").unwrap();
+        assert!(!small.truncated);
+        assert_eq!(cas.get(&small.digest).unwrap(), b"ok");
+    }
+
+    #[test]
+    fn test_log_preview_keeps_tail_on_char_boundary() {
+        assert_eq!(log_preview("ok"), "ok");
+
+        let output = format!("{}é{}", "x".repeat(10), "y".repeat(LOG_PREVIEW_BYTES - 1));
+        let preview = log_preview(&output);
+        // The cut would split `é`, so the preview starts after it
+        assert_eq!(preview, "y".repeat(LOG_PREVIEW_BYTES - 1));
+    }
+}
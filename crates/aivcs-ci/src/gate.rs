@@ -1,8 +1,30 @@
 //! CI gate evaluation for pass/fail criteria.
+//!
+//! # Exit codes
+//!
+//! Commands that evaluate a gate (`aivcs ci run`, `aivcs gate eval`) exit with:
+//!
+//! - [`EXIT_PASS`] (`0`): the gate passed
+//! - [`EXIT_VIOLATIONS`] (`1`): the gate found violations
+//! - [`EXIT_ERROR`] (`2`): the gate could not be evaluated (malformed input,
+//!   I/O failure, or a pipeline that failed to run)
 
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::Context;
 use oxidized_state::RunEvent;
 use serde::{Deserialize, Serialize};
 
+/// Exit code when the gate passes.
+pub const EXIT_PASS: i32 = 0;
+
+/// Exit code when the gate finds violations.
+pub const EXIT_VIOLATIONS: i32 = 1;
+
+/// Exit code when the gate could not be evaluated.
+pub const EXIT_ERROR: i32 = 2;
+
 /// Gate evaluation verdict.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GateVerdict {
@@ -16,6 +38,141 @@ pub struct GateVerdict {
     pub message: String,
 }
 
+impl GateVerdict {
+    /// One-line summary: `PASS: ...` or `FAIL: ...` followed by the violations.
+    pub fn summary(&self) -> String {
+        if self.passed {
+            format!("PASS: {}", self.message)
+        } else {
+            format!("FAIL: {}: {}", self.message, self.violations.join("; "))
+        }
+    }
+
+    /// Exit code for this verdict: [`EXIT_PASS`] or [`EXIT_VIOLATIONS`].
+    pub fn exit_code(&self) -> i32 {
+        if self.passed {
+            EXIT_PASS
+        } else {
+            EXIT_VIOLATIONS
+        }
+    }
+}
+
+/// Customizable gate rules, loaded from a `--gate-rules` JSON or TOML file.
+///
+/// Every field is optional and missing fields keep their defaults; an empty
+/// file gives the default gate, where every called tool must succeed.
+/// Unknown keys are rejected so a misspelled rule is not silently ignored.
+///
+/// Tools are named as the stage reports them (`cargo_clippy`) or without
+/// the `cargo_` prefix (`clippy`), the way `aivcs ci logs --stage` accepts.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct GateRules {
+    /// Tools whose failure does not fail the gate.
+    pub allow_failure: Vec<String>,
+
+    /// Tools that must have been called for the gate to pass.
+    pub required_tools: Vec<String>,
+
+    /// Maximum `warning:` diagnostics allowed per tool; unlisted tools are
+    /// unlimited (`clippy = 0` requires clippy-clean output).
+    pub max_warnings: BTreeMap<String, usize>,
+
+    /// Thresholds for [`CiGate::evaluate_regression`].
+    pub regression: RegressionRules,
+}
+
+/// What counts as a regression when comparing a run against a baseline.
+///
+/// A tool that fails now but passed in the baseline is always a regression,
+/// unless it is listed in [`GateRules::allow_failure`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct RegressionRules {
+    /// Maximum slowdown per tool, as a percentage of its baseline duration;
+    /// durations are not compared when unset.
+    pub max_slowdown_pct: Option<u32>,
+
+    /// Tolerate tools that ran in the baseline but not in the current run.
+    pub allow_removed_tools: bool,
+}
+
+/// Whether rule name `rule` names tool `tool`, with or without its
+/// `cargo_` prefix.
+pub(crate) fn names_stage(rule: &str, tool: &str) -> bool {
+    tool == rule || tool.strip_prefix("cargo_") == Some(rule)
+}
+
+impl GateRules {
+    /// Load rules from `path`, parsed as TOML for a `.toml` extension and
+    /// as JSON otherwise.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read gate rules {}", path.display()))?;
+        let rules = if path.extension().is_some_and(|ext| ext == "toml") {
+            toml::from_str(&text).map_err(anyhow::Error::from)
+        } else {
+            serde_json::from_str(&text).map_err(anyhow::Error::from)
+        };
+        rules.with_context(|| format!("invalid gate rules {}", path.display()))
+    }
+
+    /// Whether `tool` is listed in [`GateRules::allow_failure`].
+    fn allows_failure(&self, tool: &str) -> bool {
+        self.allow_failure
+            .iter()
+            .any(|rule| names_stage(rule, tool))
+    }
+
+    /// The [`GateRules::max_warnings`] cap of `tool`, if it has one; an
+    /// entry under the exact name wins over one without the prefix.
+    fn max_warnings_of(&self, tool: &str) -> Option<usize> {
+        self.max_warnings.get(tool).copied().or_else(|| {
+            self.max_warnings
+                .iter()
+                .find(|(rule, _)| names_stage(rule, tool))
+                .map(|(_, &max)| max)
+        })
+    }
+}
+
+/// Final status of one tool in a run.
+#[derive(Debug, Default)]
+struct ToolOutcome {
+    failed: bool,
+    duration_ms: Option<u64>,
+}
+
+/// Final status of every tool that returned or failed in `events`.
+fn tool_outcomes(events: &[RunEvent]) -> BTreeMap<String, ToolOutcome> {
+    let mut outcomes: BTreeMap<String, ToolOutcome> = BTreeMap::new();
+    for event in events {
+        let failed = match event.kind.as_str() {
+            "tool_returned" => event.payload["exit_code"].as_i64().unwrap_or(-1) != 0,
+            "tool_failed" => true,
+            _ => continue,
+        };
+        let tool_name = event.payload["tool_name"].as_str().unwrap_or("unknown");
+        let outcome = outcomes.entry(tool_name.to_string()).or_default();
+        outcome.failed |= failed;
+        if let Some(ms) = event.payload["duration_ms"].as_u64() {
+            *outcome.duration_ms.get_or_insert(0) += ms;
+        }
+    }
+    outcomes
+}
+
+/// Count `warning:` diagnostics in tool output, excluding cargo's
+/// "generated N warnings" summary lines.
+pub(crate) fn count_warnings(output: &str) -> usize {
+    output
+        .lines()
+        .map(str::trim_start)
+        .filter(|line| line.starts_with("warning:") && !line.contains(" generated "))
+        .count()
+}
+
 /// CI gate evaluation rules.
 pub struct CiGate;
 
@@ -29,6 +186,15 @@ impl CiGate {
     ///   - A `ToolFailed` event (fail)
     /// - If any stage has a `ToolFailed` event or non-zero exit_code, gate fails
     pub fn evaluate(events: &[RunEvent]) -> GateVerdict {
+        Self::evaluate_with_rules(events, &GateRules::default())
+    }
+
+    /// Evaluate the gate as [`CiGate::evaluate`] does, adjusted by `rules`.
+    ///
+    /// Failures of tools in `rules.allow_failure` are tolerated, every tool
+    /// in `rules.required_tools` must appear in a `ToolCalled` event, and a
+    /// tool's stderr may hold at most `rules.max_warnings` warnings.
+    pub fn evaluate_with_rules(events: &[RunEvent], rules: &GateRules) -> GateVerdict {
         let mut violations = Vec::new();
 
         // Track which tools were called and their final status
@@ -51,12 +217,27 @@ impl CiGate {
                 let exit_code = event.payload["exit_code"].as_i64().unwrap_or(-1);
 
                 if exit_code == 0 {
+                    if let Some(max) = rules.max_warnings_of(&tool_name) {
+                        // Stored stages record the count; their stderr is a preview
+                        let warnings = match event.payload["warnings"].as_u64() {
+                            Some(count) => count as usize,
+                            None => count_warnings(event.payload["stderr"].as_str().unwrap_or("")),
+                        };
+                        if warnings > max {
+                            violations.push(format!(
+                                "Tool '{}' emitted {} warning(s), max allowed {}",
+                                tool_name, warnings, max
+                            ));
+                        }
+                    }
                     tools_completed.insert(tool_name);
                 } else {
-                    violations.push(format!(
-                        "Tool '{}' returned non-zero exit code: {}",
-                        tool_name, exit_code
-                    ));
+                    if !rules.allows_failure(&tool_name) {
+                        violations.push(format!(
+                            "Tool '{}' returned non-zero exit code: {}",
+                            tool_name, exit_code
+                        ));
+                    }
                     tools_failed.insert(tool_name);
                 }
             } else if event.kind == "tool_failed" {
@@ -68,7 +249,9 @@ impl CiGate {
                     .as_str()
                     .unwrap_or("Unknown error")
                     .to_string();
-                violations.push(format!("Tool '{}' failed: {}", tool_name, error));
+                if !rules.allows_failure(&tool_name) {
+                    violations.push(format!("Tool '{}' failed: {}", tool_name, error));
+                }
                 tools_failed.insert(tool_name);
             }
         }
@@ -81,6 +264,11 @@ impl CiGate {
                 violations.push(format!("Tool '{}' was called but never completed", tool));
             }
         }
+        for tool in &rules.required_tools {
+            if !tools_called.iter().any(|called| names_stage(tool, called)) {
+                violations.push(format!("Required tool '{}' was never called", tool));
+            }
+        }
 
         let passed = violations.is_empty();
         let message = if passed {
@@ -95,6 +283,72 @@ impl CiGate {
             message,
         }
     }
+
+    /// Compare `current` against a known-good `baseline` run.
+    ///
+    /// Tool calls are aligned with the LCS diff from `aivcs_core`. The gate
+    /// fails when a tool that passed in the baseline now fails, when a
+    /// baseline tool no longer runs, or when a tool slowed down by more than
+    /// `rules.regression.max_slowdown_pct`.
+    pub fn evaluate_regression(
+        current: &[RunEvent],
+        baseline: &[RunEvent],
+        rules: &GateRules,
+    ) -> GateVerdict {
+        let mut violations = Vec::new();
+
+        if !rules.regression.allow_removed_tools {
+            let diff = aivcs_core::diff_tool_calls(baseline, current);
+            for change in &diff.changes {
+                if let aivcs_core::ToolCallChange::Removed(call) = change {
+                    violations.push(format!(
+                        "Tool '{}' ran in the baseline but not in the current run",
+                        call.tool_name
+                    ));
+                }
+            }
+        }
+
+        let before = tool_outcomes(baseline);
+        let after = tool_outcomes(current);
+        for (tool, now) in &after {
+            let then = before.get(tool);
+            if now.failed && !then.is_some_and(|then| then.failed) && !rules.allows_failure(tool) {
+                violations.push(format!("Tool '{}' fails but passed in the baseline", tool));
+            }
+
+            let (Some(max_pct), Some(then_ms), Some(now_ms)) = (
+                rules.regression.max_slowdown_pct,
+                then.and_then(|then| then.duration_ms),
+                now.duration_ms,
+            ) else {
+                continue;
+            };
+            if then_ms > 0 && now_ms * 100 > then_ms * (100 + u64::from(max_pct)) {
+                violations.push(format!(
+                    "Tool '{}' took {}ms, {}% slower than the baseline's {}ms (max {}%)",
+                    tool,
+                    now_ms,
+                    (now_ms - then_ms) * 100 / then_ms,
+                    then_ms,
+                    max_pct
+                ));
+            }
+        }
+
+        let passed = violations.is_empty();
+        let message = if passed {
+            "No regressions against baseline".to_string()
+        } else {
+            format!("Gate failed with {} regression(s)", violations.len())
+        };
+
+        GateVerdict {
+            passed,
+            violations,
+            message,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -294,4 +548,248 @@ mod tests {
         assert!(!verdict.passed);
         assert!(verdict.violations[0].contains("127"));
     }
+
+    #[test]
+    fn test_summary_and_exit_code() {
+        let passed = CiGate::evaluate(&[]);
+        assert_eq!(passed.summary(), "PASS: All stages passed");
+        assert_eq!(passed.exit_code(), EXIT_PASS);
+
+        let events = vec![RunEvent {
+            seq: 1,
+            kind: "tool_failed".to_string(),
+            payload: json!({ "tool_name": "check", "error": "Build failed" }),
+            timestamp: Utc::now(),
+        }];
+        let failed = CiGate::evaluate(&events);
+        assert_eq!(
+            failed.summary(),
+            "FAIL: Gate failed with 1 violation(s): Tool 'check' failed: Build failed"
+        );
+        assert_eq!(failed.exit_code(), EXIT_VIOLATIONS);
+    }
+
+    #[test]
+    fn test_rules_allow_failure_and_require_tools() {
+        let events = vec![
+            RunEvent {
+                seq: 1,
+                kind: "tool_called".to_string(),
+                payload: json!({ "tool_name": "clippy" }),
+                timestamp: Utc::now(),
+            },
+            RunEvent {
+                seq: 2,
+                kind: "tool_returned".to_string(),
+                payload: json!({ "tool_name": "clippy", "exit_code": 101 }),
+                timestamp: Utc::now(),
+            },
+        ];
+        let rules: GateRules =
+            serde_json::from_value(json!({ "allow_failure": ["clippy"] })).unwrap();
+        assert!(CiGate::evaluate_with_rules(&events, &rules).passed);
+
+        let rules: GateRules = serde_json::from_value(json!({
+            "allow_failure": ["clippy"],
+            "required_tools": ["test"]
+        }))
+        .unwrap();
+        let verdict = CiGate::evaluate_with_rules(&events, &rules);
+        assert!(!verdict.passed);
+        assert_eq!(
+            verdict.violations,
+            vec!["Required tool 'test' was never called".to_string()]
+        );
+
+        assert!(serde_json::from_value::<GateRules>(json!({ "max_failures": 1 })).is_err());
+    }
+
+    #[test]
+    fn test_rules_name_pipeline_stages_without_cargo_prefix() {
+        let events = vec![
+            RunEvent {
+                seq: 1,
+                kind: "tool_called".to_string(),
+                payload: json!({ "tool_name": "cargo_fmt" }),
+                timestamp: Utc::now(),
+            },
+            RunEvent {
+                seq: 2,
+                kind: "tool_returned".to_string(),
+                payload: json!({ "tool_name": "cargo_fmt", "exit_code": 1 }),
+                timestamp: Utc::now(),
+            },
+            RunEvent {
+                seq: 3,
+                kind: "tool_called".to_string(),
+                payload: json!({ "tool_name": "cargo_clippy" }),
+                timestamp: Utc::now(),
+            },
+            RunEvent {
+                seq: 4,
+                kind: "tool_returned".to_string(),
+                payload: json!({
+                    "tool_name": "cargo_clippy",
+                    "exit_code": 0,
+                    "stderr": "warning: unused import\n"
+                }),
+                timestamp: Utc::now(),
+            },
+        ];
+        let rules: GateRules = serde_json::from_value(json!({
+            "allow_failure": ["fmt"],
+            "required_tools": ["clippy"],
+            "max_warnings": { "clippy": 0 }
+        }))
+        .unwrap();
+        let verdict = CiGate::evaluate_with_rules(&events, &rules);
+        assert_eq!(
+            verdict.violations,
+            vec!["Tool 'cargo_clippy' emitted 1 warning(s), max allowed 0".to_string()]
+        );
+
+        let prefixed: GateRules = serde_json::from_value(json!({
+            "allow_failure": ["cargo_fmt"],
+            "required_tools": ["cargo_clippy"]
+        }))
+        .unwrap();
+        assert!(CiGate::evaluate_with_rules(&events, &prefixed).passed);
+    }
+
+    #[test]
+    fn test_rules_file_requiring_clippy_clean_fails_on_warnings() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gate.toml");
+        std::fs::write(
+            &path,
+            "required_tools = [\"clippy\"]\n\n[max_warnings]\nclippy = 0\n",
+        )
+        .unwrap();
+        let rules = GateRules::load(&path).unwrap();
+        assert!(rules.allow_failure.is_empty());
+
+        let clippy_run = |stderr: &str| {
+            vec![
+                RunEvent {
+                    seq: 1,
+                    kind: "tool_called".to_string(),
+                    payload: json!({ "tool_name": "clippy" }),
+                    timestamp: Utc::now(),
+                },
+                RunEvent {
+                    seq: 2,
+                    kind: "tool_returned".to_string(),
+                    payload: json!({ "tool_name": "clippy", "exit_code": 0, "stderr": stderr }),
+                    timestamp: Utc::now(),
+                },
+            ]
+        };
+
+        let warned = clippy_run(
+            "warning: unused variable: `x`\n  --> src/lib.rs:3:9\n\
+             warning: `demo` (lib) generated 1 warning\n",
+        );
+        let verdict = CiGate::evaluate_with_rules(&warned, &rules);
+        assert!(!verdict.passed);
+        assert_eq!(
+            verdict.violations,
+            vec!["Tool 'clippy' emitted 1 warning(s), max allowed 0".to_string()]
+        );
+        assert!(CiGate::evaluate(&warned).passed);
+
+        let clean = clippy_run("    Finished `dev` profile\n");
+        assert!(CiGate::evaluate_with_rules(&clean, &rules).passed);
+    }
+
+    #[test]
+    fn test_rules_file_rejects_unknown_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gate.json");
+        std::fs::write(&path, r#"{ "max_warning": { "clippy": 0 } }"#).unwrap();
+
+        let err = GateRules::load(&path).unwrap_err();
+        assert!(format!("{:#}", err).contains("unknown field `max_warning`"));
+    }
+
+    fn stage_events(stages: &[(&str, i64, u64)]) -> Vec<RunEvent> {
+        let mut events = Vec::new();
+        for (tool, exit_code, duration_ms) in stages {
+            events.push(RunEvent {
+                seq: events.len() as u64 + 1,
+                kind: "tool_called".to_string(),
+                payload: json!({ "tool_name": tool }),
+                timestamp: Utc::now(),
+            });
+            events.push(RunEvent {
+                seq: events.len() as u64 + 1,
+                kind: if *exit_code == 0 {
+                    "tool_returned"
+                } else {
+                    "tool_failed"
+                }
+                .to_string(),
+                payload: json!({
+                    "tool_name": tool,
+                    "exit_code": exit_code,
+                    "duration_ms": duration_ms,
+                }),
+                timestamp: Utc::now(),
+            });
+        }
+        events
+    }
+
+    #[test]
+    fn test_regression_slower_and_worse_run_fails() {
+        let baseline = stage_events(&[("fmt", 0, 100), ("check", 0, 1000), ("test", 0, 2000)]);
+        let current = stage_events(&[("fmt", 0, 100), ("check", 0, 1600), ("test", 1, 1900)]);
+        let rules = GateRules {
+            regression: RegressionRules {
+                max_slowdown_pct: Some(50),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let verdict = CiGate::evaluate_regression(&current, &baseline, &rules);
+        assert!(!verdict.passed);
+        assert_eq!(
+            verdict.violations,
+            vec![
+                "Tool 'check' took 1600ms, 60% slower than the baseline's 1000ms (max 50%)"
+                    .to_string(),
+                "Tool 'test' fails but passed in the baseline".to_string(),
+            ]
+        );
+        assert_eq!(verdict.exit_code(), EXIT_VIOLATIONS);
+
+        let dropped = stage_events(&[("fmt", 0, 100), ("check", 0, 1000)]);
+        let verdict = CiGate::evaluate_regression(&dropped, &baseline, &rules);
+        assert_eq!(
+            verdict.violations,
+            vec!["Tool 'test' ran in the baseline but not in the current run".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_regression_improved_run_passes() {
+        let baseline = stage_events(&[("fmt", 0, 100), ("check", 0, 1000), ("test", 1, 2000)]);
+        let current = stage_events(&[
+            ("fmt", 0, 90),
+            ("check", 0, 1040),
+            ("test", 0, 1500),
+            ("clippy", 0, 800),
+        ]);
+        let rules = GateRules {
+            regression: RegressionRules {
+                max_slowdown_pct: Some(10),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let verdict = CiGate::evaluate_regression(&current, &baseline, &rules);
+        assert!(verdict.passed, "{:?}", verdict.violations);
+        assert_eq!(verdict.summary(), "PASS: No regressions against baseline");
+    }
 }
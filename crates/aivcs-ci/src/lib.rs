@@ -4,16 +4,28 @@
 //! - Executes Cargo stages (fmt, check, clippy, test)
 //! - Records all executions as AIVCS runs
 //! - Enables replay and gate evaluation
+//! - Reruns stages as sources change (watch mode)
+//! - Plans repairs from a failed run's diagnostics
 
+pub mod artifacts;
 pub mod gate;
 pub mod pipeline;
+pub mod repair;
 pub mod runner;
 pub mod spec;
 pub mod stage;
+pub mod watch;
 
 // Re-export key types
-pub use gate::{CiGate, GateVerdict};
+pub use artifacts::{
+    log_preview, stage_logs, store_log, StageLogs, StoredLog, LOG_PREVIEW_BYTES, MAX_LOG_BYTES,
+};
+pub use gate::{
+    CiGate, GateRules, GateVerdict, RegressionRules, EXIT_ERROR, EXIT_PASS, EXIT_VIOLATIONS,
+};
 pub use pipeline::{CiPipeline, PipelineResult};
+pub use repair::{apply_repair_plan, parse_diagnostics, plan_repair, run_diagnostics};
 pub use runner::{CiRunner, StageResult};
-pub use spec::CiSpec;
+pub use spec::{detect_active_toolchain, read_toolchain_file, CiSpec, UNKNOWN_TOOLCHAIN};
 pub use stage::{BuiltinStage, StageConfig};
+pub use watch::{WatchOptions, WorkspaceWatcher};
@@ -1,15 +1,18 @@
 //! CI pipeline orchestration and run recording.
 
+use crate::artifacts::{log_preview, store_log};
+use crate::gate::count_warnings;
 use crate::runner::{CiRunner, StageResult};
 use crate::spec::CiSpec;
 use crate::stage::StageConfig;
+use aivcs_core::cas::CasStore;
 use aivcs_core::domain::run::{Event, EventKind};
 use aivcs_core::recording::GraphRunRecorder;
 use oxidized_state::{ContentDigest, RunLedger, RunMetadata, RunSummary};
 use serde_json::json;
 use std::sync::Arc;
 use std::time::Instant;
-use tracing::info;
+use tracing::{info, warn};
 use uuid::Uuid;
 
 /// Result of a complete CI pipeline execution.
@@ -58,9 +61,38 @@ impl CiPipeline {
         ledger: Arc<dyn RunLedger>,
         ci_spec: &CiSpec,
         stages: Vec<StageConfig>,
+    ) -> anyhow::Result<PipelineResult> {
+        Self::run_with_artifacts(ledger, ci_spec, stages, None).await
+    }
+
+    /// [`run`](Self::run), additionally storing each completed stage's
+    /// stdout and stderr in `cas`
+    ///
+    /// The digests go into the stage's result event; see
+    /// [`crate::artifacts`] for reading them back.
+    pub async fn run_with_artifacts(
+        ledger: Arc<dyn RunLedger>,
+        ci_spec: &CiSpec,
+        stages: Vec<StageConfig>,
+        cas: Option<&dyn CasStore>,
     ) -> anyhow::Result<PipelineResult> {
         let start = Instant::now();
 
+        ci_spec.check_toolchain()?;
+        if !ci_spec.toolchain_checkable() {
+            warn!(
+                expected = ?ci_spec.expected_toolchain,
+                actual = %ci_spec.toolchain_hash,
+                "Active toolchain could not be checked; running anyway"
+            );
+        } else if !ci_spec.toolchain_matches() {
+            warn!(
+                expected = ?ci_spec.expected_toolchain,
+                actual = %ci_spec.toolchain_hash,
+                "Toolchain mismatch allowed; running anyway"
+            );
+        }
+
         // Create AgentSpec and get digest
         let agent_spec = ci_spec.to_agent_spec()?;
         let spec_digest = ContentDigest::from_bytes(agent_spec.spec_digest.as_bytes());
@@ -79,6 +111,8 @@ impl CiPipeline {
                 "stages": stages.iter().map(|s| &s.name).collect::<Vec<_>>(),
                 "workspace": ci_spec.workspace_path.to_string_lossy(),
                 "toolchain": &ci_spec.toolchain_hash,
+                "expected_toolchain": &ci_spec.expected_toolchain,
+                "toolchain_mismatch": !ci_spec.toolchain_matches(),
             }),
             evaluation: Default::default(),
         };
@@ -122,7 +156,7 @@ impl CiPipeline {
             let result = match CiRunner::execute_stage(&config).await {
                 Ok(r) => r,
                 Err(e) => {
-                    // Stage execution itself failed (e.g. timeout, spawn error).
+                    // Stage execution itself failed (e.g. spawn error).
                     // Record a ToolFailed event so the gate sees it.
                     all_passed = false;
                     let duration_ms_stage = stage_start.elapsed().as_millis() as u64;
@@ -150,6 +184,7 @@ impl CiPipeline {
                         stderr: e.to_string(),
                         duration_ms: duration_ms_stage,
                         success: false,
+                        timed_out: false,
                     });
                     continue;
                 }
@@ -163,29 +198,49 @@ impl CiPipeline {
                     EventKind::ToolReturned {
                         tool_name: tool_name.clone(),
                     },
-                    json!({
-                        "exit_code": result.exit_code,
-                        "stdout": &result.stdout,
-                        "stderr": &result.stderr,
-                        "duration_ms": result.duration_ms,
-                    }),
+                    with_log_artifacts(
+                        json!({
+                            "exit_code": result.exit_code,
+                            "stdout": &result.stdout,
+                            "stderr": &result.stderr,
+                            "duration_ms": result.duration_ms,
+                        }),
+                        cas,
+                        &result,
+                    )?,
                 );
                 recorder.record(&returned_event).await?;
             } else {
                 all_passed = false;
+                let error = if result.timed_out {
+                    format!(
+                        "Stage '{}' timed out after {}s",
+                        tool_name, config.timeout_secs
+                    )
+                } else {
+                    format!(
+                        "Stage '{}' exited with code {}",
+                        tool_name, result.exit_code
+                    )
+                };
                 let failed_event = Event::new(
                     Uuid::new_v4(),
                     seq,
                     EventKind::ToolFailed {
                         tool_name: tool_name.clone(),
                     },
-                    json!({
-                        "exit_code": result.exit_code,
-                        "stdout": &result.stdout,
-                        "stderr": &result.stderr,
-                        "duration_ms": result.duration_ms,
-                        "error": format!("Stage '{}' exited with code {}", tool_name, result.exit_code),
-                    }),
+                    with_log_artifacts(
+                        json!({
+                            "exit_code": result.exit_code,
+                            "stdout": &result.stdout,
+                            "stderr": &result.stderr,
+                            "duration_ms": result.duration_ms,
+                            "timed_out": result.timed_out,
+                            "error": error,
+                        }),
+                        cas,
+                        &result,
+                    )?,
                 );
                 recorder.record(&failed_event).await?;
             }
@@ -202,6 +257,7 @@ impl CiPipeline {
             final_state_digest: None,
             duration_ms,
             success: all_passed,
+            usage: None,
         };
 
         if all_passed {
@@ -222,6 +278,30 @@ impl CiPipeline {
     }
 }
 
+/// Store the stage's output in `cas` (if any) and add the digests and
+/// truncation flags to its result event payload
+///
+/// With the output stored, the payload's `stdout`/`stderr` shrink to a
+/// preview of their tail, and the stderr warning count the gate checks is
+/// recorded as `warnings` since the preview may not hold every warning.
+fn with_log_artifacts(
+    mut payload: serde_json::Value,
+    cas: Option<&dyn CasStore>,
+    result: &StageResult,
+) -> anyhow::Result<serde_json::Value> {
+    let Some(cas) = cas else {
+        return Ok(payload);
+    };
+    for (stream, output) in [("stdout", &result.stdout), ("stderr", &result.stderr)] {
+        let stored = store_log(cas, output)?;
+        payload[stream] = json!(log_preview(output));
+        payload[format!("{stream}_digest")] = json!(stored.digest.to_hex());
+        payload[format!("{stream}_truncated")] = json!(stored.truncated);
+    }
+    payload["warnings"] = json!(count_warnings(&result.stderr));
+    Ok(payload)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -239,6 +319,7 @@ mod tests {
                     stderr: "".to_string(),
                     duration_ms: 100,
                     success: true,
+                    timed_out: false,
                 },
                 StageResult {
                     stage_name: "check".to_string(),
@@ -247,6 +328,7 @@ mod tests {
                     stderr: "".to_string(),
                     duration_ms: 200,
                     success: true,
+                    timed_out: false,
                 },
             ],
             duration_ms: 300,
@@ -271,6 +353,7 @@ mod tests {
                     stderr: "".to_string(),
                     duration_ms: 100,
                     success: true,
+                    timed_out: false,
                 },
                 StageResult {
                     stage_name: "check".to_string(),
@@ -279,6 +362,7 @@ mod tests {
                     stderr: "error".to_string(),
                     duration_ms: 200,
                     success: false,
+                    timed_out: false,
                 },
             ],
             duration_ms: 300,
@@ -0,0 +1,390 @@
+//! Repair plans from a recorded run's diagnostics
+//!
+//! [`run_diagnostics`] parses the stored output of a run's failed stages
+//! into [`Diagnostic`]s, [`plan_repair`] turns them into a bounded
+//! [`RepairPlan`], and [`apply_repair_plan`] runs the plan's fix commands
+//! once the sandbox policy allows the `Fixer` role to write files.
+
+use std::path::Path;
+
+use aivcs_core::cas::CasStore;
+use aivcs_core::domain::ci::{
+    normalize_diagnostics, Diagnostic, DiagnosticSource, RankedDiagnostic, RepairAction,
+    RepairPlan, RepairPolicy, RepairStrategy, Severity,
+};
+use aivcs_core::role_orchestration::roles::AgentRole;
+use aivcs_core::sandbox::{evaluate_tool_request, PolicyVerdict, ToolPolicySet, ToolRequest};
+use aivcs_core::tooling::ToolCapability;
+use anyhow::{Context, Result};
+use oxidized_state::{RunId, RunLedger};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::artifacts::stage_logs;
+use crate::stage::BuiltinStage;
+
+/// Diagnostics in the stored stdout and stderr of every failed stage of
+/// run `run_id`
+///
+/// Stages that failed without storing output (the command could not be
+/// started, or the run was recorded without a CAS store) contribute none.
+pub async fn run_diagnostics(
+    ledger: &dyn RunLedger,
+    cas: &dyn CasStore,
+    run_id: &str,
+) -> Result<Vec<Diagnostic>> {
+    let events = ledger
+        .get_events(&RunId(run_id.to_string()))
+        .await
+        .with_context(|| format!("failed to load events of run {run_id}"))?;
+    let failed: Vec<&str> = events
+        .iter()
+        .filter(|e| e.kind == "tool_failed" && e.payload["stderr_digest"].is_string())
+        .filter_map(|e| e.payload["tool_name"].as_str())
+        .collect();
+
+    let mut diagnostics = Vec::new();
+    for stage in failed {
+        let logs = stage_logs(ledger, cas, run_id, stage).await?;
+        diagnostics.extend(parse_diagnostics(stage, &logs.stdout));
+        diagnostics.extend(parse_diagnostics(stage, &logs.stderr));
+    }
+    Ok(diagnostics)
+}
+
+/// Parse cargo, rustfmt and libtest output of `stage` into diagnostics
+///
+/// Understands rustc-style `error[CODE]: message` headers with their
+/// `--> file:line:col` span (clippy lint names are taken from the help
+/// link), rustfmt `Diff in` hunks and failed tests. Cargo's own summary
+/// lines are skipped.
+pub fn parse_diagnostics(stage: &str, output: &str) -> Vec<Diagnostic> {
+    let source = stage_source(stage);
+    let mut diagnostics = Vec::new();
+    let mut current: Option<Diagnostic> = None;
+
+    for line in output.lines() {
+        let trimmed = line.trim_start();
+        if let Some(diag) = parse_header(line, source) {
+            diagnostics.extend(current.replace(diag));
+        } else if let Some(span) = trimmed.strip_prefix("--> ") {
+            if let Some(diag) = current.as_mut().filter(|d| d.file.is_none()) {
+                if let Some((file, line, column)) = parse_span(span) {
+                    diag.file = Some(file);
+                    diag.line = Some(line);
+                    diag.column = Some(column);
+                }
+            }
+        } else if let Some((_, lint)) = trimmed.split_once("rust-clippy/master/index.html#") {
+            if let Some(diag) = current.as_mut().filter(|d| d.code.is_none()) {
+                diag.code = Some(format!("clippy::{}", lint.trim()));
+                diag.source = DiagnosticSource::Clippy;
+            }
+        } else if let Some(diag) = parse_fmt_diff(line) {
+            diagnostics.extend(current.take());
+            diagnostics.push(diag);
+        } else if let Some(diag) = parse_failed_test(line) {
+            diagnostics.extend(current.take());
+            diagnostics.push(diag);
+        }
+    }
+    diagnostics.extend(current);
+    diagnostics
+}
+
+/// Plan one action per distinct diagnostic, within `policy`'s bounds
+///
+/// Diagnostics are deduplicated and ranked by [`normalize_diagnostics`];
+/// those below `policy.min_severity` are dropped and at most
+/// `policy.max_actions` are kept. The plan is `AutoFix` when the policy
+/// allows it and some action has a fix command, `Suggest` when there are
+/// only manual actions and `Skip` when there are none.
+pub fn plan_repair(run_id: Uuid, diagnostics: &[Diagnostic], policy: &RepairPolicy) -> RepairPlan {
+    let actions: Vec<RepairAction> = normalize_diagnostics(diagnostics, false)
+        .ranked
+        .into_iter()
+        .filter(|r| r.diagnostic.severity >= policy.min_severity)
+        .take(policy.max_actions)
+        .map(repair_action)
+        .collect();
+
+    let strategy = if actions.is_empty() {
+        RepairStrategy::Skip
+    } else if policy.allow_auto_fix && actions.iter().any(|a| a.fix_command.is_some()) {
+        RepairStrategy::AutoFix
+    } else {
+        RepairStrategy::Suggest
+    };
+    actions.into_iter().fold(
+        RepairPlan::new(run_id, strategy, policy.max_attempts),
+        RepairPlan::with_action,
+    )
+}
+
+/// Run the distinct fix commands of an `AutoFix` plan in `workspace`
+///
+/// The commands write files, so they only run when `sandbox` allows the
+/// `Fixer` role the `FileWrite` capability. Returns the commands run; a
+/// plan with any other strategy runs nothing.
+pub async fn apply_repair_plan(
+    plan: &RepairPlan,
+    workspace: &Path,
+    sandbox: &ToolPolicySet,
+) -> Result<Vec<Vec<String>>> {
+    if plan.strategy != RepairStrategy::AutoFix {
+        return Ok(Vec::new());
+    }
+    let mut commands: Vec<Vec<String>> = Vec::new();
+    for command in plan.actions.iter().filter_map(|a| a.fix_command.as_ref()) {
+        if !commands.contains(command) {
+            commands.push(command.clone());
+        }
+    }
+
+    let request = ToolRequest {
+        tool_name: "ci_repair".to_string(),
+        capability: ToolCapability::FileWrite,
+        params: json!({
+            "workspace": workspace.to_string_lossy(),
+            "commands": &commands,
+        }),
+        requesting_role: AgentRole::Fixer,
+    };
+    match evaluate_tool_request(sandbox, &request) {
+        PolicyVerdict::Allowed => {}
+        PolicyVerdict::Denied { reason } => {
+            anyhow::bail!("sandbox policy denies applying the repair: {reason}")
+        }
+        PolicyVerdict::RequiresApproval { reason } => {
+            anyhow::bail!("sandbox policy requires approval to apply the repair: {reason}")
+        }
+    }
+
+    for command in &commands {
+        let status = tokio::process::Command::new(&command[0])
+            .args(&command[1..])
+            .current_dir(workspace)
+            .status()
+            .await
+            .with_context(|| format!("failed to run `{}`", command.join(" ")))?;
+        if !status.success() {
+            anyhow::bail!("`{}` failed with {}", command.join(" "), status);
+        }
+    }
+    Ok(commands)
+}
+
+fn repair_action(ranked: RankedDiagnostic) -> RepairAction {
+    let diag = ranked.diagnostic;
+    let (fix_command, rationale) = match diag.source {
+        DiagnosticSource::Fmt => (
+            BuiltinStage::CargoFmt.fix_command(),
+            "formatting differs from rustfmt; `cargo fmt --all` rewrites it".to_string(),
+        ),
+        _ => (
+            None,
+            match &diag.code {
+                Some(code) => format!("{code}: {}", diag.message),
+                None => diag.message.clone(),
+            },
+        ),
+    };
+    RepairAction {
+        file: diag.file,
+        line: diag.line,
+        column: diag.column,
+        code: diag.code,
+        rationale,
+        fix_command,
+        occurrences: ranked.occurrences,
+    }
+}
+
+fn stage_source(stage: &str) -> DiagnosticSource {
+    if stage.contains("clippy") {
+        DiagnosticSource::Clippy
+    } else if stage.contains("fmt") {
+        DiagnosticSource::Fmt
+    } else if stage.contains("test") {
+        DiagnosticSource::Test
+    } else {
+        DiagnosticSource::Rustc
+    }
+}
+
+/// `error[E0425]: message` or `warning: message`, excluding cargo summaries
+fn parse_header(line: &str, source: DiagnosticSource) -> Option<Diagnostic> {
+    let (severity, rest) = if let Some(rest) = line.strip_prefix("error") {
+        (Severity::Error, rest)
+    } else if let Some(rest) = line.strip_prefix("warning") {
+        (Severity::Warning, rest)
+    } else {
+        return None;
+    };
+    let (code, message) = match rest.strip_prefix('[') {
+        Some(rest) => {
+            let (code, message) = rest.split_once("]: ")?;
+            (Some(code.to_string()), message)
+        }
+        None => (None, rest.strip_prefix(": ")?),
+    };
+    let summary = [
+        "`",
+        "could not compile",
+        "aborting due to",
+        "build failed",
+        "test failed",
+    ];
+    if summary.iter().any(|prefix| message.starts_with(prefix)) {
+        return None;
+    }
+
+    let diag = Diagnostic::new(severity, message.to_string(), source);
+    Some(match code {
+        Some(code) => diag.with_code(code),
+        None => diag,
+    })
+}
+
+/// `src/lib.rs:2:5`
+fn parse_span(span: &str) -> Option<(String, u32, u32)> {
+    let mut parts = span.trim().rsplitn(3, ':');
+    let column = parts.next()?.parse().ok()?;
+    let line = parts.next()?.parse().ok()?;
+    let file = parts.next()?;
+    Some((file.to_string(), line, column))
+}
+
+/// `Diff in /path/src/lib.rs:3:` or the older `Diff in /path at line 3:`
+fn parse_fmt_diff(line: &str) -> Option<Diagnostic> {
+    let rest = line.strip_prefix("Diff in ")?.strip_suffix(':')?;
+    let (file, line_no) = match rest.split_once(" at line ") {
+        Some((file, line_no)) => (file, line_no),
+        None => rest.rsplit_once(':')?,
+    };
+    let diag = Diagnostic::new(
+        Severity::Error,
+        "file is not formatted".to_string(),
+        DiagnosticSource::Fmt,
+    )
+    .with_code("rustfmt".to_string());
+    Some(match line_no.parse() {
+        Ok(line_no) => diag.with_location(file.to_string(), line_no, 1),
+        Err(_) => diag,
+    })
+}
+
+/// `test tests::it_works ... FAILED`
+fn parse_failed_test(line: &str) -> Option<Diagnostic> {
+    let name = line
+        .strip_prefix("test ")?
+        .strip_suffix(" ... FAILED")?
+        .trim();
+    Some(Diagnostic::new(
+        Severity::Error,
+        format!("test {name} failed"),
+        DiagnosticSource::Test,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIPPY_OUTPUT: &str = "\
+    Checking demo v0.1.0 (/work/demo)
+error: unneeded `return` statement
+ --> src/lib.rs:3:5
+  |
+3 |     return x;
+  |     ^^^^^^^^^
+  |
+  = help: for further information visit https://rust-lang.github.io/rust-clippy/master/index.html#needless_return
+  = note: `-D clippy::needless-return` implied by `-D warnings`
+
+error[E0425]: cannot find value `y` in this scope
+ --> src/main.rs:7:13
+  |
+7 |     let z = y;
+  |             ^ not found in this scope
+
+error: could not compile `demo` (lib) due to 2 previous errors
+";
+
+    #[test]
+    fn test_parse_rustc_and_clippy_diagnostics() {
+        let diagnostics = parse_diagnostics("cargo_clippy", CLIPPY_OUTPUT);
+        assert_eq!(diagnostics.len(), 2);
+
+        assert_eq!(
+            diagnostics[0].code.as_deref(),
+            Some("clippy::needless_return")
+        );
+        assert_eq!(diagnostics[0].message, "unneeded `return` statement");
+        assert_eq!(diagnostics[0].file.as_deref(), Some("src/lib.rs"));
+        assert_eq!(
+            (diagnostics[0].line, diagnostics[0].column),
+            (Some(3), Some(5))
+        );
+
+        assert_eq!(diagnostics[1].code.as_deref(), Some("E0425"));
+        assert_eq!(diagnostics[1].severity, Severity::Error);
+        assert_eq!(diagnostics[1].file.as_deref(), Some("src/main.rs"));
+        assert_eq!(diagnostics[1].line, Some(7));
+    }
+
+    #[test]
+    fn test_fmt_diff_plans_an_automatic_fix() {
+        let output = "Diff in /work/demo/src/lib.rs:12:\n fn a() {\n-    1\n+  1\n";
+        let diagnostics = parse_diagnostics("cargo_fmt", output);
+        assert_eq!(diagnostics.len(), 1);
+
+        let plan = plan_repair(Uuid::nil(), &diagnostics, &RepairPolicy::default());
+        assert_eq!(plan.strategy, RepairStrategy::AutoFix);
+        assert_eq!(
+            plan.actions[0].fix_command,
+            BuiltinStage::CargoFmt.fix_command()
+        );
+        assert_eq!(plan.actions[0].line, Some(12));
+
+        let manual_only = RepairPolicy {
+            allow_auto_fix: false,
+            ..RepairPolicy::default()
+        };
+        let plan = plan_repair(Uuid::nil(), &diagnostics, &manual_only);
+        assert_eq!(plan.strategy, RepairStrategy::Suggest);
+    }
+
+    #[test]
+    fn test_plan_respects_severity_and_action_bounds() {
+        let diagnostics = parse_diagnostics(
+            "cargo_test",
+            "test a ... FAILED\ntest b ... FAILED\ntest c ... ok\nwarning: unused import\n",
+        );
+        assert_eq!(diagnostics.len(), 3);
+
+        let policy = RepairPolicy {
+            max_actions: 1,
+            min_severity: Severity::Error,
+            ..RepairPolicy::default()
+        };
+        let plan = plan_repair(Uuid::nil(), &diagnostics, &policy);
+        assert_eq!(plan.strategy, RepairStrategy::Suggest);
+        assert_eq!(plan.actions.len(), 1);
+        assert_eq!(plan.actions[0].rationale, "test a failed");
+
+        let plan = plan_repair(Uuid::nil(), &[], &policy);
+        assert_eq!(plan.strategy, RepairStrategy::Skip);
+    }
+
+    #[tokio::test]
+    async fn test_apply_requires_file_write_for_fixer() {
+        let diagnostics = parse_diagnostics("cargo_fmt", "Diff in /w/src/lib.rs:1:\n");
+        let plan = plan_repair(Uuid::nil(), &diagnostics, &RepairPolicy::default());
+
+        let err = apply_repair_plan(&plan, Path::new("."), &ToolPolicySet::empty())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("sandbox policy denies"), "{err}");
+    }
+}
@@ -2,8 +2,14 @@
 
 use crate::stage::StageConfig;
 use std::process::Stdio;
-use std::time::Instant;
-use tokio::process::Command;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::{Child, Command};
+use tokio::task::JoinHandle;
+
+/// How long a timed-out stage gets to exit after SIGTERM before SIGKILL
+const KILL_GRACE: Duration = Duration::from_secs(2);
 
 /// Result of a stage execution.
 #[derive(Debug, Clone)]
@@ -25,6 +31,10 @@ pub struct StageResult {
 
     /// Whether execution succeeded.
     pub success: bool,
+
+    /// Whether the stage was killed for exceeding its timeout; output is
+    /// whatever it wrote before then.
+    pub timed_out: bool,
 }
 
 impl StageResult {
@@ -43,6 +53,9 @@ impl CiRunner {
     /// Records two events:
     /// - `ToolCalled` when stage starts
     /// - `ToolReturned` (success) or event with error info (failure) when stage completes
+    ///
+    /// A stage running past `timeout_secs` is killed together with every
+    /// process it started and returned with `timed_out` set.
     pub async fn execute_stage(config: &StageConfig) -> anyhow::Result<StageResult> {
         let start = Instant::now();
 
@@ -54,48 +67,138 @@ impl CiRunner {
         let exe = &config.command[0];
         let args = &config.command[1..];
 
-        // Execute with timeout
-        let child = Command::new(exe)
+        // The stage leads its own process group, so a timeout can take down
+        // everything it spawned (test binaries, rustc) and not just the
+        // direct child
+        let mut command = Command::new(exe);
+        command
             .args(args)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
-            .spawn()?;
+            .kill_on_drop(true);
+        #[cfg(unix)]
+        command.process_group(0);
+        let mut child = command.spawn()?;
 
-        let output = if config.timeout_secs > 0 {
-            tokio::time::timeout(
-                std::time::Duration::from_secs(config.timeout_secs),
-                child.wait_with_output(),
-            )
-            .await
-            .map_err(|_| {
-                anyhow::anyhow!(
-                    "Stage {} timed out after {} seconds",
-                    config.name,
-                    config.timeout_secs
-                )
-            })??
+        // Read output as it arrives so a killed stage still reports it
+        let stdout = capture(child.stdout.take());
+        let stderr = capture(child.stderr.take());
+
+        let status = if config.timeout_secs > 0 {
+            let limit = Duration::from_secs(config.timeout_secs);
+            match tokio::time::timeout(limit, child.wait()).await {
+                Ok(status) => Some(status?),
+                Err(_) => {
+                    tracing::warn!(
+                        stage = %config.name,
+                        timeout_secs = config.timeout_secs,
+                        "Stage timed out; killing its process group"
+                    );
+                    terminate_tree(&mut child).await;
+                    None
+                }
+            }
         } else {
-            child.wait_with_output().await?
+            Some(child.wait().await?)
         };
 
         let duration_ms = start.elapsed().as_millis() as u64;
-        let exit_code = output.status.code().unwrap_or(-1);
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-
-        let success = output.status.success();
-
-        Ok(StageResult {
-            stage_name: config.name.clone(),
-            exit_code,
-            stdout,
-            stderr,
-            duration_ms,
-            success,
+        let stdout = collect(stdout).await;
+        let mut stderr = collect(stderr).await;
+
+        Ok(match status {
+            Some(status) => StageResult {
+                stage_name: config.name.clone(),
+                exit_code: status.code().unwrap_or(-1),
+                stdout,
+                stderr,
+                duration_ms,
+                success: status.success(),
+                timed_out: false,
+            },
+            None => {
+                if !stderr.is_empty() && !stderr.ends_with('\n') {
+                    stderr.push('\n');
+                }
+                stderr.push_str(&format!(
+                    "Stage {} timed out after {} seconds",
+                    config.name, config.timeout_secs
+                ));
+                StageResult {
+                    stage_name: config.name.clone(),
+                    exit_code: -1,
+                    stdout,
+                    stderr,
+                    duration_ms,
+                    success: false,
+                    timed_out: true,
+                }
+            }
         })
     }
 }
 
+type Captured = (Arc<Mutex<Vec<u8>>>, Option<JoinHandle<()>>);
+
+/// Drain `pipe` into a shared buffer in the background
+fn capture<R: AsyncRead + Unpin + Send + 'static>(pipe: Option<R>) -> Captured {
+    let buffer = Arc::new(Mutex::new(Vec::new()));
+    let task = pipe.map(|mut pipe| {
+        let buffer = buffer.clone();
+        tokio::spawn(async move {
+            let mut chunk = [0u8; 8192];
+            while let Ok(n @ 1..) = pipe.read(&mut chunk).await {
+                buffer
+                    .lock()
+                    .expect("output buffer")
+                    .extend_from_slice(&chunk[..n]);
+            }
+        })
+    });
+    (buffer, task)
+}
+
+/// Output captured so far, waiting briefly for the pipe to close in case a
+/// process outside the stage's group still holds it open
+async fn collect((buffer, task): Captured) -> String {
+    if let Some(mut task) = task {
+        if tokio::time::timeout(KILL_GRACE, &mut task).await.is_err() {
+            task.abort();
+        }
+    }
+    let bytes = buffer.lock().expect("output buffer");
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+/// SIGTERM the child's process group, then SIGKILL it if the child hasn't
+/// exited within [`KILL_GRACE`]
+async fn terminate_tree(child: &mut Child) {
+    #[cfg(unix)]
+    if let Some(pgid) = child.id() {
+        signal_group("TERM", pgid).await;
+        if tokio::time::timeout(KILL_GRACE, child.wait()).await.is_ok() {
+            // The leader is gone; make sure nothing it left behind survives
+            signal_group("KILL", pgid).await;
+            return;
+        }
+        signal_group("KILL", pgid).await;
+    }
+    let _ = child.kill().await;
+}
+
+#[cfg(unix)]
+async fn signal_group(signal: &str, pgid: u32) {
+    let sent = Command::new("kill")
+        .args([format!("-{signal}"), "--".to_string(), format!("-{pgid}")])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await;
+    if let Err(e) = sent {
+        tracing::warn!(pgid, signal, error = %e, "Failed to signal process group");
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -109,6 +212,7 @@ mod tests {
             stderr: "".to_string(),
             duration_ms: 100,
             success: true,
+            timed_out: false,
         };
         assert!(result.passed());
     }
@@ -122,6 +226,7 @@ mod tests {
             stderr: "error".to_string(),
             duration_ms: 100,
             success: false,
+            timed_out: false,
         };
         assert!(!result.passed());
     }
@@ -152,4 +257,48 @@ mod tests {
         assert!(!result.success);
         assert_ne!(result.exit_code, 0);
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_timed_out_stage_is_killed_with_its_children() {
+        let dir = tempfile::tempdir().unwrap();
+        let pid_file = dir.path().join("sleep.pid");
+        let config = StageConfig::custom(
+            "hang".to_string(),
+            vec![
+                "sh".to_string(),
+                "-c".to_string(),
+                "echo started; sleep 60 & echo $! > \"$0\"; wait".to_string(),
+                pid_file.display().to_string(),
+            ],
+            1,
+        );
+
+        let result =
+            tokio::time::timeout(Duration::from_secs(20), CiRunner::execute_stage(&config))
+                .await
+                .expect("stage must not hang past its timeout")
+                .expect("execute failed");
+
+        assert!(result.timed_out);
+        assert!(!result.passed());
+        assert_eq!(result.exit_code, -1);
+        assert!(result.stdout.contains("started"));
+        assert!(result.stderr.contains("timed out after 1 seconds"));
+
+        // The backgrounded grandchild went down with the group
+        let pid = std::fs::read_to_string(&pid_file).unwrap();
+        let alive = std::process::Command::new("kill")
+            .args(["-0", pid.trim()])
+            .status()
+            .unwrap()
+            .success();
+        let zombie = std::fs::read_to_string(format!("/proc/{}/stat", pid.trim()))
+            .is_ok_and(|stat| stat.contains(") Z"));
+        assert!(
+            !alive || zombie,
+            "sleep {} survived the timeout",
+            pid.trim()
+        );
+    }
 }
@@ -3,7 +3,7 @@
 use aivcs_core::domain::agent_spec::AgentSpec;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// CI pipeline specification.
 ///
@@ -20,8 +20,17 @@ pub struct CiSpec {
     /// Git commit SHA where execution occurred.
     pub git_sha: String,
 
-    /// Toolchain hash from `rustup show` output.
+    /// Toolchain hash from `rustup show` output, `rustc -V` output when
+    /// rustup is unavailable, or [`UNKNOWN_TOOLCHAIN`].
     pub toolchain_hash: String,
+
+    /// Toolchain the workspace expects, e.g. `1.78.0` or `stable`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_toolchain: Option<String>,
+
+    /// Run anyway when the active toolchain is not the expected one.
+    #[serde(default)]
+    pub allow_toolchain_mismatch: bool,
 }
 
 impl CiSpec {
@@ -38,7 +47,85 @@ impl CiSpec {
             stages_digest,
             git_sha,
             toolchain_hash,
+            expected_toolchain: None,
+            allow_toolchain_mismatch: false,
+        }
+    }
+
+    /// Require the active toolchain to be `expected`.
+    pub fn with_expected_toolchain(mut self, expected: impl Into<String>) -> Self {
+        self.expected_toolchain = Some(expected.into());
+        self
+    }
+
+    /// Downgrade a toolchain mismatch from an error to a warning.
+    pub fn allow_toolchain_mismatch(mut self, allow: bool) -> Self {
+        self.allow_toolchain_mismatch = allow;
+        self
+    }
+
+    /// Name of the active toolchain: the first word of `rustup` output, or
+    /// the release in `rustc -V` output. `None` when it was not detected.
+    fn active_toolchain(&self) -> Option<&str> {
+        let mut words = self.toolchain_hash.split_whitespace();
+        match words.next()? {
+            UNKNOWN_TOOLCHAIN => None,
+            "rustc" => words.next(),
+            name => Some(name),
+        }
+    }
+
+    /// Whether the active toolchain can be checked against the expected one.
+    ///
+    /// It cannot when it was not detected, or when only its `rustc -V`
+    /// release is known (no rustup, e.g. a nix-provided toolchain) and the
+    /// workspace pins a channel such as `stable` rather than a release.
+    pub fn toolchain_checkable(&self) -> bool {
+        let Some(expected) = self.expected_toolchain.as_deref() else {
+            return true;
+        };
+        match self.active_toolchain() {
+            None => false,
+            Some(_) if self.toolchain_hash.trim_start().starts_with("rustc ") => {
+                expected.starts_with(|c: char| c.is_ascii_digit())
+            }
+            Some(_) => true,
+        }
+    }
+
+    /// Whether the active toolchain is the expected one.
+    ///
+    /// The active toolchain's name matches when it equals the expected
+    /// toolchain or extends it with a host triple, so `stable` matches
+    /// `stable-x86_64-unknown-linux-gnu`. Always true when no toolchain is
+    /// expected or it cannot be checked; see
+    /// [`CiSpec::toolchain_checkable`].
+    pub fn toolchain_matches(&self) -> bool {
+        let Some(expected) = self.expected_toolchain.as_deref() else {
+            return true;
+        };
+        if !self.toolchain_checkable() {
+            return true;
+        }
+        let active = self.active_toolchain().unwrap_or("");
+        active == expected
+            || active
+                .strip_prefix(expected)
+                .is_some_and(|rest| rest.starts_with('-'))
+    }
+
+    /// Fail unless the active toolchain is the expected one or a mismatch
+    /// is allowed.
+    pub fn check_toolchain(&self) -> anyhow::Result<()> {
+        if self.toolchain_matches() || self.allow_toolchain_mismatch {
+            return Ok(());
         }
+        anyhow::bail!(
+            "toolchain mismatch: workspace expects '{}' but the active toolchain is '{}' \
+             (pass --allow-toolchain-mismatch to run anyway)",
+            self.expected_toolchain.as_deref().unwrap_or_default(),
+            self.toolchain_hash.trim()
+        )
     }
 
     /// Convert to an AIVCS AgentSpec for run identity.
@@ -60,14 +147,67 @@ impl CiSpec {
     }
 }
 
+/// `toolchain_hash` of a toolchain that could not be detected.
+pub const UNKNOWN_TOOLCHAIN: &str = "unknown";
+
+/// Describe the toolchain active in `workspace`: `rustup show
+/// active-toolchain`, or `rustc -V` where rustup is absent or fails, or
+/// [`UNKNOWN_TOOLCHAIN`]. Runs in the workspace so its toolchain file
+/// applies.
+pub fn detect_active_toolchain(workspace: &Path) -> String {
+    let run = |program: &str, args: &[&str]| {
+        std::process::Command::new(program)
+            .args(args)
+            .current_dir(workspace)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|stdout| !stdout.is_empty())
+    };
+    run("rustup", &["show", "active-toolchain"])
+        .or_else(|| run("rustc", &["-V"]))
+        .unwrap_or_else(|| UNKNOWN_TOOLCHAIN.to_string())
+}
+
+/// Toolchain channel pinned by the workspace's `rust-toolchain.toml` or
+/// legacy `rust-toolchain` file, if it has one.
+pub fn read_toolchain_file(workspace: &Path) -> anyhow::Result<Option<String>> {
+    #[derive(Deserialize)]
+    struct ToolchainFile {
+        toolchain: ToolchainSection,
+    }
+    #[derive(Deserialize)]
+    struct ToolchainSection {
+        channel: Option<String>,
+    }
+
+    for name in ["rust-toolchain.toml", "rust-toolchain"] {
+        let path = workspace.join(name);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        // The legacy file may hold just the channel name
+        let trimmed = text.trim();
+        if name == "rust-toolchain" && !trimmed.contains('[') {
+            return Ok(Some(trimmed.to_string()).filter(|c| !c.is_empty()));
+        }
+        let file: ToolchainFile = toml::from_str(&text)
+            .map_err(|e| anyhow::anyhow!("invalid {}: {}", path.display(), e))?;
+        return Ok(file.toolchain.channel);
+    }
+    Ok(None)
+}
+
 /// Compute deterministic digest of ordered stage names.
+///
+/// Hashes the canonical JSON array of names, so it agrees with every other
+/// JSON digest site for the same list.
 fn compute_stages_digest(stages: &[String]) -> String {
-    let mut hasher = Sha256::new();
-    for stage in stages {
-        hasher.update(stage.as_bytes());
-        hasher.update(b"\0");
-    }
-    hex::encode(hasher.finalize())
+    let value = serde_json::Value::from(stages.to_vec());
+    compute_component_digest(&oxidized_state::canonical_json(&value))
 }
 
 /// Compute digest of a component.
@@ -119,6 +259,17 @@ mod tests {
         assert_ne!(digest1, digest2);
     }
 
+    #[test]
+    fn test_stages_digest_matches_canonical_json_digest() {
+        let stages = vec!["fmt".to_string(), "check".to_string()];
+        let value = serde_json::json!(["fmt", "check"]);
+
+        assert_eq!(
+            compute_stages_digest(&stages),
+            aivcs_core::domain::digest::compute_digest(&value).unwrap()
+        );
+    }
+
     #[test]
     fn test_ci_spec_to_agent_spec() {
         let stages = vec!["fmt".to_string(), "check".to_string()];
@@ -133,4 +284,87 @@ mod tests {
         assert_eq!(agent_spec.git_sha, "abc123");
         assert!(!agent_spec.spec_digest.is_empty());
     }
+
+    fn spec_with_active(active: &str) -> CiSpec {
+        CiSpec::new(
+            PathBuf::from("."),
+            &["check".to_string()],
+            "abc123".to_string(),
+            active.to_string(),
+        )
+    }
+
+    #[test]
+    fn test_toolchain_matches_channel_with_host_triple() {
+        let spec = spec_with_active("1.78.0-x86_64-unknown-linux-gnu (overridden)");
+        assert!(spec.toolchain_matches());
+        assert!(spec
+            .clone()
+            .with_expected_toolchain("1.78.0")
+            .check_toolchain()
+            .is_ok());
+        assert!(!spec
+            .clone()
+            .with_expected_toolchain("1.7")
+            .toolchain_matches());
+        assert!(!spec.with_expected_toolchain("stable").toolchain_matches());
+    }
+
+    #[test]
+    fn test_toolchain_mismatch_fails_unless_allowed() {
+        let spec = spec_with_active("stable-x86_64-unknown-linux-gnu (default)")
+            .with_expected_toolchain("nightly-2024-05-01");
+        let err = spec.check_toolchain().unwrap_err().to_string();
+        assert!(err.contains("nightly-2024-05-01"), "{err}");
+        assert!(err.contains("--allow-toolchain-mismatch"), "{err}");
+        assert!(spec
+            .allow_toolchain_mismatch(true)
+            .check_toolchain()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_undetected_or_rustc_only_toolchain_is_not_a_mismatch() {
+        for active in [UNKNOWN_TOOLCHAIN, ""] {
+            let spec = spec_with_active(active).with_expected_toolchain("1.78.0");
+            assert!(!spec.toolchain_checkable(), "{active:?}");
+            assert!(spec.check_toolchain().is_ok(), "{active:?}");
+        }
+
+        // Without rustup only the release is known
+        let nix = spec_with_active("rustc 1.78.0 (9b00956e5 2024-04-29)");
+        let pinned_channel = nix.clone().with_expected_toolchain("stable");
+        assert!(!pinned_channel.toolchain_checkable());
+        assert!(pinned_channel.check_toolchain().is_ok());
+
+        let pinned_release = nix.clone().with_expected_toolchain("1.78.0");
+        assert!(pinned_release.toolchain_checkable());
+        assert!(pinned_release.toolchain_matches());
+        assert!(nix
+            .with_expected_toolchain("1.80.0")
+            .check_toolchain()
+            .is_err());
+    }
+
+    #[test]
+    fn test_read_toolchain_file() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(read_toolchain_file(dir.path()).unwrap(), None);
+
+        std::fs::write(dir.path().join("rust-toolchain"), "1.77.2\n").unwrap();
+        assert_eq!(
+            read_toolchain_file(dir.path()).unwrap().as_deref(),
+            Some("1.77.2")
+        );
+
+        std::fs::write(
+            dir.path().join("rust-toolchain.toml"),
+            "[toolchain]\nchannel = \"1.78.0\"\ncomponents = [\"clippy\"]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            read_toolchain_file(dir.path()).unwrap().as_deref(),
+            Some("1.78.0")
+        );
+    }
 }
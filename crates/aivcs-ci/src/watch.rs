@@ -0,0 +1,175 @@
+//! Watch mode: rerun the pipeline when Rust sources change
+//!
+//! [`WorkspaceWatcher`] polls the workspace for `.rs` files whose size or
+//! modification time changed, waiting until edits settle before reporting
+//! them. Polling keeps this dependency-free and behaves the same on every
+//! platform and filesystem.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+
+/// Timing of [`WorkspaceWatcher`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchOptions {
+    /// Pause between workspace scans
+    pub poll_interval: Duration,
+    /// How long sources must stay unchanged before a change is reported
+    pub debounce: Duration,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_millis(500),
+            debounce: Duration::from_millis(300),
+        }
+    }
+}
+
+/// Size and modification time of each `.rs` file, keyed by path
+type SourceStamps = BTreeMap<PathBuf, (u64, Option<SystemTime>)>;
+
+/// Polls a workspace for changed Rust sources
+#[derive(Debug)]
+pub struct WorkspaceWatcher {
+    root: PathBuf,
+    options: WatchOptions,
+    stamps: SourceStamps,
+}
+
+impl WorkspaceWatcher {
+    /// Start watching `root`; the current sources are the baseline
+    pub fn new(root: impl Into<PathBuf>, options: WatchOptions) -> Result<Self> {
+        let root = root.into();
+        let stamps = scan_sources(&root)?;
+        Ok(Self {
+            root,
+            options,
+            stamps,
+        })
+    }
+
+    /// Wait until `.rs` files are added, removed or modified and have then
+    /// stayed unchanged for the debounce period; returns the changed paths
+    pub async fn wait_for_change(&mut self) -> Result<Vec<PathBuf>> {
+        let mut current = loop {
+            tokio::time::sleep(self.options.poll_interval).await;
+            let scan = scan_sources(&self.root)?;
+            if scan != self.stamps {
+                break scan;
+            }
+        };
+        loop {
+            tokio::time::sleep(self.options.debounce).await;
+            let scan = scan_sources(&self.root)?;
+            if scan == current {
+                break;
+            }
+            current = scan;
+        }
+
+        let changed: BTreeSet<PathBuf> = current
+            .iter()
+            .filter(|(path, stamp)| self.stamps.get(*path) != Some(stamp))
+            .map(|(path, _)| path.clone())
+            .chain(
+                self.stamps
+                    .keys()
+                    .filter(|path| !current.contains_key(*path))
+                    .cloned(),
+            )
+            .collect();
+        self.stamps = current;
+        Ok(changed.into_iter().collect())
+    }
+}
+
+fn scan_sources(root: &Path) -> Result<SourceStamps> {
+    let mut stamps = SourceStamps::new();
+    scan_dir(root, &mut stamps)
+        .with_context(|| format!("failed to scan workspace {}", root.display()))?;
+    Ok(stamps)
+}
+
+fn scan_dir(dir: &Path, stamps: &mut SourceStamps) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        // Build output and hidden directories (.git, caches) never hold
+        // sources worth rerunning for
+        if name.starts_with('.') || name == "target" || name == "node_modules" {
+            continue;
+        }
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            scan_dir(&path, stamps)?;
+        } else if file_type.is_file() && path.extension().is_some_and(|e| e == "rs") {
+            let meta = entry.metadata()?;
+            stamps.insert(path, (meta.len(), meta.modified().ok()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fast() -> WatchOptions {
+        WatchOptions {
+            poll_interval: Duration::from_millis(20),
+            debounce: Duration::from_millis(40),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_source_change_is_reported_once_settled() {
+        let dir = tempfile::tempdir().unwrap();
+        let lib = dir.path().join("src/lib.rs");
+        std::fs::create_dir_all(lib.parent().unwrap()).unwrap();
+        std::fs::write(&lib, "pub fn a() {}\n").unwrap();
+        std::fs::create_dir_all(dir.path().join("target")).unwrap();
+
+        let mut watcher = WorkspaceWatcher::new(dir.path(), fast()).unwrap();
+        let edit = {
+            let root = dir.path().to_path_buf();
+            let lib = lib.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(60)).await;
+                // Neither of these is a source change
+                std::fs::write(root.join("README.md"), "notes").unwrap();
+                std::fs::write(root.join("target/gen.rs"), "fn x() {}").unwrap();
+                tokio::time::sleep(Duration::from_millis(60)).await;
+                std::fs::write(&lib, "pub fn a() {}\npub fn b() {}\n").unwrap();
+            })
+        };
+
+        let changed = tokio::time::timeout(Duration::from_secs(10), watcher.wait_for_change())
+            .await
+            .expect("change detected before timeout")
+            .unwrap();
+        edit.await.unwrap();
+        assert_eq!(changed, vec![lib]);
+    }
+
+    #[tokio::test]
+    async fn test_removed_source_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let gone = dir.path().join("old.rs");
+        std::fs::write(&gone, "fn old() {}").unwrap();
+        std::fs::write(dir.path().join("kept.rs"), "fn kept() {}").unwrap();
+
+        let mut watcher = WorkspaceWatcher::new(dir.path(), fast()).unwrap();
+        std::fs::remove_file(&gone).unwrap();
+        let changed = tokio::time::timeout(Duration::from_secs(10), watcher.wait_for_change())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(changed, vec![gone]);
+    }
+}
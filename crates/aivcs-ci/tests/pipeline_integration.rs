@@ -1,6 +1,6 @@
 //! Integration tests for CI pipeline with MemoryRunLedger.
 
-use aivcs_ci::{CiGate, CiPipeline, CiSpec, StageConfig};
+use aivcs_ci::{CiGate, CiPipeline, CiSpec, GateRules, StageConfig};
 use oxidized_state::fakes::MemoryRunLedger;
 use oxidized_state::{RunId, RunLedger};
 use std::path::PathBuf;
@@ -268,3 +268,189 @@ async fn test_pipeline_execution_error_recorded_as_tool_failed() {
         "tool_failed event should have exit_code -1"
     );
 }
+
+/// Test: a failing stage's output is kept in CAS and found by run and stage
+#[tokio::test]
+async fn test_failing_stage_stderr_is_stored_and_retrievable() {
+    let ledger = Arc::new(MemoryRunLedger::new());
+    let cas = aivcs_core::cas::memory::MemoryCasStore::new();
+
+    let stages = vec![StageConfig::custom(
+        "cargo_clippy".to_string(),
+        vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "echo linting; echo 'error: unused variable `x`' >&2; exit 1".to_string(),
+        ],
+        60,
+    )];
+    let ci_spec = CiSpec::new(
+        PathBuf::from("."),
+        &["cargo_clippy".to_string()],
+        "abc123".to_string(),
+        "rustc_hash".to_string(),
+    );
+
+    let result = CiPipeline::run_with_artifacts(ledger.clone(), &ci_spec, stages, Some(&cas))
+        .await
+        .expect("pipeline failed");
+    assert!(!result.success);
+
+    let logs = aivcs_ci::stage_logs(ledger.as_ref(), &cas, &result.run_id, "clippy")
+        .await
+        .expect("logs retrievable");
+    assert_eq!(logs.stage, "cargo_clippy");
+    assert_eq!(logs.stdout, "linting\n");
+    assert_eq!(logs.stderr, "error: unused variable `x`\n");
+    assert!(!logs.truncated);
+
+    let missing = aivcs_ci::stage_logs(ledger.as_ref(), &cas, &result.run_id, "test").await;
+    assert!(missing.is_err());
+}
+
+/// Test: with output stored in CAS, the result event keeps only a preview
+/// of it, and the gate still sees every warning
+#[tokio::test]
+async fn test_stored_stage_output_is_not_inlined_in_event() {
+    let ledger = Arc::new(MemoryRunLedger::new());
+    let cas = aivcs_core::cas::memory::MemoryCasStore::new();
+
+    let stages = vec![StageConfig::custom(
+        "cargo_check".to_string(),
+        vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "for i in $(seq 1 2000); do echo \"warning: unused $i\" >&2; done; echo done"
+                .to_string(),
+        ],
+        60,
+    )];
+    let ci_spec = CiSpec::new(
+        PathBuf::from("."),
+        &["cargo_check".to_string()],
+        "abc123".to_string(),
+        "rustc_hash".to_string(),
+    );
+
+    let result = CiPipeline::run_with_artifacts(ledger.clone(), &ci_spec, stages, Some(&cas))
+        .await
+        .expect("pipeline failed");
+    assert!(result.success);
+
+    let events = ledger
+        .get_events(&RunId(result.run_id.clone()))
+        .await
+        .unwrap();
+    let returned = events
+        .iter()
+        .find(|e| e.kind == "tool_returned")
+        .expect("tool_returned event");
+    let stderr = returned.payload["stderr"].as_str().unwrap();
+    assert!(stderr.len() <= aivcs_ci::LOG_PREVIEW_BYTES);
+    assert!(stderr.ends_with("warning: unused 2000\n"));
+    assert_eq!(returned.payload["warnings"], 2000);
+
+    let logs = aivcs_ci::stage_logs(ledger.as_ref(), &cas, &result.run_id, "check")
+        .await
+        .unwrap();
+    assert_eq!(logs.stderr.lines().count(), 2000);
+    assert_eq!(logs.stdout, "done\n");
+
+    let rules = GateRules {
+        max_warnings: [("check".to_string(), 10)].into(),
+        ..GateRules::default()
+    };
+    let verdict = CiGate::evaluate_with_rules(&events, &rules);
+    assert!(!verdict.passed);
+    assert!(verdict.violations[0].contains("2000 warning(s)"));
+}
+
+/// Test: a toolchain mismatch aborts before anything is recorded; a match
+/// runs and records expected and actual toolchains
+#[tokio::test]
+async fn test_toolchain_mismatch_aborts_and_match_proceeds() {
+    let ledger = Arc::new(MemoryRunLedger::new());
+    let stages = || {
+        vec![StageConfig::custom(
+            "echo_test".to_string(),
+            vec!["echo".to_string(), "hello".to_string()],
+            60,
+        )]
+    };
+    let spec = CiSpec::new(
+        PathBuf::from("."),
+        &["echo_test".to_string()],
+        "abc123".to_string(),
+        "stable-x86_64-unknown-linux-gnu (default)".to_string(),
+    );
+
+    let mismatched = spec.clone().with_expected_toolchain("1.78.0");
+    let err = CiPipeline::run(ledger.clone(), &mismatched, stages())
+        .await
+        .expect_err("mismatched toolchain must abort");
+    assert!(err.to_string().contains("toolchain mismatch"), "{err}");
+    assert!(ledger.list_runs(None).await.unwrap().is_empty());
+
+    let matched = spec.with_expected_toolchain("stable");
+    let result = CiPipeline::run(ledger.clone(), &matched, stages())
+        .await
+        .expect("matching toolchain runs");
+    assert!(result.success);
+    let run = ledger.get_run(&RunId(result.run_id)).await.unwrap();
+    assert_eq!(run.metadata.tags["expected_toolchain"], "stable");
+    assert_eq!(
+        run.metadata.tags["toolchain"],
+        "stable-x86_64-unknown-linux-gnu (default)"
+    );
+    assert_eq!(run.metadata.tags["toolchain_mismatch"], false);
+
+    let allowed = mismatched.allow_toolchain_mismatch(true);
+    let result = CiPipeline::run(ledger.clone(), &allowed, stages())
+        .await
+        .expect("allowed mismatch runs");
+    let run = ledger.get_run(&RunId(result.run_id)).await.unwrap();
+    assert_eq!(run.metadata.tags["toolchain_mismatch"], true);
+}
+
+/// Test: a failed run's stored diagnostics become a repair plan action
+#[tokio::test]
+async fn test_repair_plan_from_failed_run_diagnostics() {
+    let ledger = Arc::new(MemoryRunLedger::new());
+    let cas = aivcs_core::cas::memory::MemoryCasStore::new();
+
+    let stages = vec![StageConfig::custom(
+        "cargo_check".to_string(),
+        vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "printf 'error[E0425]: cannot find value `y` in this scope\\n --> src/main.rs:7:13\\n' >&2; exit 101"
+                .to_string(),
+        ],
+        60,
+    )];
+    let ci_spec = CiSpec::new(
+        PathBuf::from("."),
+        &["cargo_check".to_string()],
+        "abc123".to_string(),
+        "rustc_hash".to_string(),
+    );
+    let result = CiPipeline::run_with_artifacts(ledger.clone(), &ci_spec, stages, Some(&cas))
+        .await
+        .expect("pipeline failed");
+    assert!(!result.success);
+
+    let diagnostics = aivcs_ci::run_diagnostics(ledger.as_ref(), &cas, &result.run_id)
+        .await
+        .expect("diagnostics load");
+    let run_id = uuid::Uuid::parse_str(&result.run_id).unwrap();
+    let plan = aivcs_ci::plan_repair(run_id, &diagnostics, &Default::default());
+
+    assert_eq!(plan.run_id, run_id);
+    assert_eq!(plan.actions.len(), 1);
+    let action = &plan.actions[0];
+    assert_eq!(action.code.as_deref(), Some("E0425"));
+    assert_eq!(action.file.as_deref(), Some("src/main.rs"));
+    assert_eq!((action.line, action.column), (Some(7), Some(13)));
+    assert!(action.rationale.contains("cannot find value `y`"));
+    assert!(action.fix_command.is_none());
+}
@@ -0,0 +1,216 @@
+//! `aivcs branch` and `fork` — listing, creating, protecting and forking branches.
+
+use anyhow::Result;
+use oxidized_state::{BranchProtection, BranchRecord, SurrealHandle};
+use std::sync::Arc;
+
+use aivcs_core::commands::{self, branch_list_output};
+use aivcs_core::{fork_agent_parallel_with_progress, short_hash, Progress};
+
+/// List all branches
+pub(crate) async fn cmd_branch_list(handle: &SurrealHandle, json: bool) -> Result<()> {
+    let branches = handle.list_branches().await?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&branch_list_output(&branches))?
+        );
+        return Ok(());
+    }
+
+    if branches.is_empty() {
+        println!("No branches found. Run 'agent-git init' first.");
+        return Ok(());
+    }
+
+    for branch in &branches {
+        println!("{}", format_branch_line(branch));
+    }
+
+    Ok(())
+}
+
+/// Format a branch as `<marker><name> -> <short head>` for `branch list`.
+fn format_branch_line(branch: &BranchRecord) -> String {
+    let prefix = if branch.is_default { "* " } else { "  " };
+    format!(
+        "{}{} -> {}",
+        prefix,
+        branch.name,
+        short_hash(&branch.head_commit_id)
+    )
+}
+
+/// Create a new branch
+pub(crate) async fn cmd_branch_create(
+    handle: &SurrealHandle,
+    name: &str,
+    from: &str,
+) -> Result<()> {
+    let branch = commands::create_branch(handle, name, from).await?;
+
+    println!(
+        "Created branch '{}' at {}",
+        name,
+        short_hash(&branch.head_commit_id)
+    );
+
+    Ok(())
+}
+
+/// Delete a branch
+pub(crate) async fn cmd_branch_delete(handle: &SurrealHandle, name: &str) -> Result<()> {
+    commands::delete_branch(handle, name).await?;
+
+    println!("Deleted branch '{}'", name);
+
+    Ok(())
+}
+
+/// Set the protection rules of a branch
+pub(crate) async fn cmd_branch_protect(
+    handle: &SurrealHandle,
+    name: &str,
+    protection: BranchProtection,
+) -> Result<()> {
+    handle.set_protection(name, protection).await?;
+
+    if protection.is_empty() {
+        println!("Removed protection from branch '{}'", name);
+        return Ok(());
+    }
+    let rules: Vec<&str> = [
+        (protection.no_force_push, "no-force-push"),
+        (protection.no_delete, "no-delete"),
+        (protection.require_linear, "require-linear"),
+    ]
+    .into_iter()
+    .filter_map(|(enabled, rule)| enabled.then_some(rule))
+    .collect();
+    println!("Protected branch '{}': {}", name, rules.join(", "));
+
+    Ok(())
+}
+
+/// Fork multiple parallel branches for exploration
+pub(crate) async fn cmd_fork(
+    handle: &SurrealHandle,
+    parent: &str,
+    count: u8,
+    prefix: &str,
+    progress: &dyn Progress,
+) -> Result<()> {
+    // Resolve parent reference (branch name or commit ID)
+    let parent_commit = if let Ok(Some(branch)) = handle.get_branch(parent).await {
+        branch.head_commit_id
+    } else {
+        parent.to_string()
+    };
+
+    println!(
+        "Forking {} branches from {} with prefix '{}'",
+        count,
+        short_hash(&parent_commit),
+        prefix
+    );
+
+    let handle_arc = Arc::new(handle.clone());
+
+    let result =
+        fork_agent_parallel_with_progress(handle_arc, &parent_commit, count, prefix, progress)
+            .await?;
+
+    println!("\nCreated {} parallel branches:", result.branches.len());
+    for (i, branch) in result.branches.iter().enumerate() {
+        println!("  {} -> {}", branch, result.commit_ids[i].short());
+    }
+
+    println!("\nUse 'aivcs branch list' to see all branches");
+    println!("Use 'aivcs trace <commit>' to debug a branch's reasoning");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aivcs_core::NoProgress;
+    use serde_json::json;
+
+    use crate::snapshot::{cmd_init, cmd_snapshot};
+
+    #[tokio::test]
+    async fn test_cmd_fork_creates_branches_in_same_db() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+
+        // Hermetic init: see `test_pr_note_command` for the pattern.
+        let temp_dir = tempfile::tempdir().unwrap();
+        cmd_init(&handle, &temp_dir.path().to_path_buf())
+            .await
+            .unwrap();
+
+        // Create a snapshot to fork from in the same tempdir.
+        let state_path = temp_dir.path().join("state.json");
+        std::fs::write(&state_path, r#"{"step": 1, "value": "test"}"#).unwrap();
+        cmd_snapshot(
+            &handle,
+            &state_path,
+            "Base",
+            "agent",
+            "main",
+            Some("bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"),
+            Some(temp_dir.path().join("cas").as_path()),
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+
+        // Run fork command
+        let result = cmd_fork(&handle, "main", 2, "test-fork", &NoProgress).await;
+
+        assert!(result.is_ok(), "Fork failed: {:?}", result.err());
+
+        // Verify branches exist in the original handle
+        let branches = handle.list_branches().await.unwrap();
+        let fork_branches: Vec<_> = branches
+            .iter()
+            .filter(|b| b.name.starts_with("test-fork"))
+            .collect();
+
+        assert_eq!(
+            fork_branches.len(),
+            2,
+            "Should have created 2 branches in the same DB"
+        );
+    }
+
+    #[test]
+    fn test_branch_list_json_golden_is_sorted() {
+        let branches = vec![
+            BranchRecord::new("main", "aaa", true),
+            BranchRecord::new("feature", "bbb", false),
+        ];
+        let value = serde_json::to_value(branch_list_output(&branches)).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "branches": [
+                    { "name": "feature", "head_commit_id": "bbb", "is_default": false },
+                    { "name": "main", "head_commit_id": "aaa", "is_default": true }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_branch_list_renders_short_head_ids() {
+        let short = BranchRecord::new("main", "abc", true);
+        assert_eq!(format_branch_line(&short), "* main -> abc");
+
+        let long = BranchRecord::new("feature", "0123456789abcdef", false);
+        assert_eq!(format_branch_line(&long), "  feature -> 01234567");
+        assert_eq!(short_hash("ab🦀cdefghij"), "ab🦀cdefg");
+    }
+}
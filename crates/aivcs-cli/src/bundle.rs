@@ -0,0 +1,129 @@
+//! `aivcs bundle`, `cas` and `remote` — moving repository data and checking the content store.
+
+use anyhow::{Context, Result};
+use oxidized_state::SurrealHandle;
+
+use aivcs_core::commands::resolve_commit_ref;
+use aivcs_core::{short_hash, Progress};
+
+use crate::{open_cas, RemoteCasArgs};
+
+fn print_bundle_summary(verb: &str, path: &std::path::Path, summary: &aivcs_core::BundleSummary) {
+    println!("{} {}", verb, path.display());
+    println!("  commits:     {}", summary.commits);
+    println!("  snapshots:   {}", summary.snapshots);
+    println!("  branches:    {}", summary.branches);
+    println!("  memories:    {}", summary.memories);
+    println!("  graph edges: {}", summary.graph_edges);
+    println!("  blobs:       {}", summary.blobs);
+}
+
+/// Export the repository to a bundle file
+pub(crate) async fn cmd_bundle_export(
+    handle: &SurrealHandle,
+    out: &std::path::Path,
+    since: Option<&str>,
+    cas_dir: Option<&std::path::Path>,
+    progress: &dyn Progress,
+) -> Result<()> {
+    let cas = open_cas(cas_dir)?;
+    let base = match since {
+        Some(since) => Some(resolve_commit_ref(handle, since).await),
+        None => None,
+    };
+    let summary =
+        aivcs_core::export_bundle_with_progress(handle, &cas, out, base.as_deref(), progress)
+            .await?;
+    print_bundle_summary("Exported bundle", out, &summary);
+    Ok(())
+}
+
+/// Import a bundle file into the repository
+pub(crate) async fn cmd_bundle_import(
+    handle: &SurrealHandle,
+    input: &std::path::Path,
+    cas_dir: Option<&std::path::Path>,
+) -> Result<()> {
+    let cas = open_cas(cas_dir)?;
+    let summary = aivcs_core::import_bundle(handle, &cas, input).await?;
+    print_bundle_summary("Imported bundle", input, &summary);
+    Ok(())
+}
+
+/// Print the size summary of the CAS store
+pub(crate) fn cmd_cas_stats(cas_dir: Option<&std::path::Path>, json: bool) -> Result<()> {
+    let stats = open_cas(cas_dir)?
+        .stats()
+        .map_err(|e| anyhow::anyhow!("failed to read CAS stats: {e}"))?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+    println!("blobs:        {}", stats.blob_count);
+    println!("total bytes:  {}", stats.total_bytes);
+    println!("average size: {}", stats.average_blob_bytes);
+    Ok(())
+}
+
+/// Scan the CAS store for blobs that no longer match their digest
+pub(crate) fn cmd_cas_verify(cas_dir: Option<&std::path::Path>) -> Result<()> {
+    let cas = open_cas(cas_dir)?;
+    let corrupt = cas
+        .verify_all()
+        .map_err(|e| anyhow::anyhow!("CAS verification failed: {e}"))?;
+    if corrupt.is_empty() {
+        println!("CAS OK: no corrupt blobs");
+        return Ok(());
+    }
+    for digest in &corrupt {
+        println!("corrupt: {}", digest);
+    }
+    anyhow::bail!("{} corrupt blob(s) in CAS", corrupt.len())
+}
+
+/// Push or pull `branch` against the remote named by `AIVCS_REMOTE_*`
+pub(crate) async fn cmd_remote_sync(
+    handle: &SurrealHandle,
+    branch: &str,
+    force: bool,
+    cas: &RemoteCasArgs,
+    push: bool,
+) -> Result<()> {
+    let config = oxidized_state::CloudConfig::from_env_with_prefix("AIVCS_REMOTE")
+        .map_err(|e| anyhow::anyhow!("remote not configured: {e}"))?;
+    let remote = SurrealHandle::setup_cloud(config)
+        .await
+        .context("Failed to connect to remote")?;
+
+    let local_cas = open_cas(cas.cas_dir.as_deref())?;
+    let remote_cas = open_cas(cas.remote_cas_dir.as_deref().or(cas.cas_dir.as_deref()))?;
+
+    let summary = if push {
+        aivcs_core::push_branch(handle, &local_cas, &remote, &remote_cas, branch, force).await?
+    } else {
+        aivcs_core::pull_branch(handle, &local_cas, &remote, &remote_cas, branch, force).await?
+    };
+
+    if summary.is_up_to_date() {
+        println!("Branch '{}' is up to date", branch);
+        return Ok(());
+    }
+    let old = summary
+        .old_head
+        .as_deref()
+        .map(short_hash)
+        .unwrap_or("(new)");
+    println!(
+        "{} '{}': {} -> {}{}",
+        if push { "Pushed" } else { "Pulled" },
+        branch,
+        old,
+        short_hash(&summary.new_head),
+        if summary.forced { " (forced)" } else { "" }
+    );
+    println!(
+        "  {} commit(s), {} blob(s) transferred",
+        summary.transferred.commits, summary.transferred.blobs
+    );
+    Ok(())
+}
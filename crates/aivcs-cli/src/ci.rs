@@ -0,0 +1,585 @@
+//! `aivcs ci`, `gate` and `eval` — running local CI stages and gating on their results.
+
+use anyhow::{Context, Result};
+use oxidized_state::{RunEvent, RunLedger, SurrealHandle, SurrealRunLedger};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use aivcs_ci::{BuiltinStage, CiGate, CiPipeline, CiSpec, GateRules, GateVerdict, StageConfig};
+use aivcs_core::{short_hash, DecisionRecorder};
+
+use crate::{open_cas, read_json_file};
+
+/// Map a gate evaluation to its exit code (see `aivcs_ci::gate`), reporting
+/// evaluation errors on stderr
+pub(crate) fn gate_exit_code(verdict: Result<GateVerdict>) -> i32 {
+    match verdict {
+        Ok(verdict) => verdict.exit_code(),
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            aivcs_ci::EXIT_ERROR
+        }
+    }
+}
+
+fn load_gate_rules(path: Option<&PathBuf>) -> Result<GateRules> {
+    match path {
+        Some(path) => GateRules::load(path),
+        None => Ok(GateRules::default()),
+    }
+}
+
+pub(crate) fn cmd_gate_eval(events: &PathBuf, rules: &PathBuf, json: bool) -> Result<GateVerdict> {
+    let events: Vec<RunEvent> = read_json_file(events)?;
+    let rules = load_gate_rules(Some(rules))?;
+    let verdict = CiGate::evaluate_with_rules(&events, &rules);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&verdict)?);
+    } else {
+        println!("{}", verdict.summary());
+    }
+    Ok(verdict)
+}
+
+/// Run an eval suite over recorded outputs, store the report in CAS, and
+/// compare it with `baseline` if given. Returns the process exit code: 1
+/// when a case that passed in the baseline no longer does, else 0.
+pub(crate) async fn cmd_eval_run(
+    handle: &SurrealHandle,
+    suite: &PathBuf,
+    outputs: &PathBuf,
+    baseline: Option<&PathBuf>,
+    seed: u64,
+    cas_dir: Option<&std::path::Path>,
+    json: bool,
+) -> Result<i32> {
+    let suite: aivcs_core::EvalSuite = read_json_file(suite)?;
+    let outputs: std::collections::HashMap<_, Value> = read_json_file(outputs)?;
+    let baseline: Option<aivcs_core::EvalRunReport> = baseline.map(read_json_file).transpose()?;
+
+    let report =
+        aivcs_core::DeterministicEvalRunner::new(seed).run_with_outputs(&suite, &outputs)?;
+    let cas = open_cas(cas_dir)?;
+    let digest = aivcs_core::persist_eval_report(handle, &cas, &report).await?;
+    let regression = baseline
+        .as_ref()
+        .map(|b| aivcs_core::compare_eval_reports(b, &report));
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "report_digest": digest.to_hex(),
+                "report": report,
+                "regression": regression,
+            }))?
+        );
+    } else {
+        println!(
+            "Eval {} {}: {}/{} passed ({:.1}%) {}",
+            suite.name,
+            suite.version,
+            report.passed_cases,
+            report.total_cases,
+            report.pass_rate * 100.0,
+            if report.overall_pass { "PASS" } else { "FAIL" }
+        );
+        println!("Report: {}", digest);
+        if let Some(regression) = &regression {
+            for id in &regression.newly_passing {
+                println!("  newly passing: {}", id);
+            }
+            for id in &regression.newly_failing {
+                println!("  newly failing: {}", id);
+            }
+            if regression.has_regression() {
+                println!(
+                    "Regression: {} case(s) newly failing vs baseline",
+                    regression.newly_failing.len()
+                );
+            } else {
+                println!("No regression vs baseline");
+            }
+        }
+    }
+
+    Ok(i32::from(regression.is_some_and(|r| r.has_regression())))
+}
+
+pub(crate) fn cmd_gate_regression(
+    current: &PathBuf,
+    baseline: &PathBuf,
+    rules: Option<&PathBuf>,
+    json: bool,
+) -> Result<GateVerdict> {
+    let current: Vec<RunEvent> = read_json_file(current)?;
+    let baseline: Vec<RunEvent> = read_json_file(baseline)?;
+    let rules = load_gate_rules(rules)?;
+    let verdict = CiGate::evaluate_regression(&current, &baseline, &rules);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&verdict)?);
+    } else {
+        println!("{}", verdict.summary());
+    }
+    Ok(verdict)
+}
+
+/// Run CI, then rerun it whenever Rust sources in `workspace` change, until
+/// interrupted
+///
+/// Verdicts are remembered by workspace hash, so a change that brings the
+/// tree back to a state already run (an undo, a touch) reuses that verdict
+/// instead of running the stages again.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn cmd_ci_watch(
+    ledger: &SurrealRunLedger,
+    workspace: &PathBuf,
+    stages: &str,
+    no_cache: bool,
+    fix: bool,
+    gate_rules: Option<&PathBuf>,
+    cas_dir: Option<&std::path::Path>,
+    allow_toolchain_mismatch: bool,
+    recorder: Option<&DecisionRecorder>,
+) -> Result<()> {
+    let mut watcher =
+        aivcs_ci::WorkspaceWatcher::new(workspace, aivcs_ci::WatchOptions::default())?;
+    let mut verdicts: std::collections::HashMap<String, bool> = std::collections::HashMap::new();
+    let mut changed: Vec<PathBuf> = Vec::new();
+    let mut hasher = aivcs_core::WorkspaceHasher::new();
+
+    for iteration in 1.. {
+        let digest = hasher.hash(workspace)?;
+        let trigger = match changed.as_slice() {
+            [] => "initial run".to_string(),
+            [path] => format!("{} changed", path.display()),
+            paths => format!("{} files changed", paths.len()),
+        };
+        let (passed, cached) = match verdicts.get(&digest) {
+            Some(&passed) => (passed, true),
+            None => {
+                let passed = match cmd_ci_run(
+                    ledger,
+                    workspace,
+                    stages,
+                    no_cache,
+                    fix,
+                    gate_rules,
+                    cas_dir,
+                    allow_toolchain_mismatch,
+                    recorder,
+                )
+                .await
+                {
+                    Ok(verdict) => verdict.passed,
+                    Err(e) => {
+                        eprintln!("Error: {:?}", e);
+                        false
+                    }
+                };
+                verdicts.insert(digest.clone(), passed);
+                (passed, false)
+            }
+        };
+        println!(
+            "[watch #{}] {} -> {}{} (workspace {})",
+            iteration,
+            trigger,
+            if passed { "PASS" } else { "FAIL" },
+            if cached { ", cached" } else { "" },
+            short_hash(&digest)
+        );
+        println!(
+            "Watching {} for changes (Ctrl-C to stop)",
+            workspace.display()
+        );
+        changed = watcher.wait_for_change().await?;
+    }
+    Ok(())
+}
+
+/// Run CI stages and record execution
+///
+/// The gate verdict is recorded through `recorder`, when given, as a
+/// decision under `gate:ci` against the workspace's git SHA.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn cmd_ci_run(
+    ledger: &SurrealRunLedger,
+    workspace: &PathBuf,
+    stages_str: &str,
+    no_cache: bool,
+    fix: bool,
+    gate_rules: Option<&PathBuf>,
+    cas_dir: Option<&std::path::Path>,
+    allow_toolchain_mismatch: bool,
+    recorder: Option<&DecisionRecorder>,
+) -> Result<GateVerdict> {
+    let rules = load_gate_rules(gate_rules)?;
+    if no_cache {
+        eprintln!("warning: --no-cache is not yet implemented; proceeding without caching changes");
+    }
+    if fix {
+        eprintln!("warning: --fix is not yet implemented; stages will run in check-only mode");
+    }
+
+    // Get git SHA
+    let git_sha = if let Ok(output) = std::process::Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+    {
+        String::from_utf8(output.stdout)
+            .unwrap_or_default()
+            .trim()
+            .to_string()
+    } else {
+        "unknown".to_string()
+    };
+
+    let toolchain_hash = aivcs_ci::detect_active_toolchain(workspace);
+
+    // Parse stages
+    let stage_names: Vec<String> = stages_str
+        .split(',')
+        .map(|s| s.trim().to_lowercase())
+        .collect();
+
+    let mut stage_configs = Vec::new();
+    for stage_name in &stage_names {
+        let config = match stage_name.as_str() {
+            "fmt" => StageConfig::from_builtin(BuiltinStage::CargoFmt, 300),
+            "check" => StageConfig::from_builtin(BuiltinStage::CargoCheck, 300),
+            "clippy" => StageConfig::from_builtin(BuiltinStage::CargoClippy, 600),
+            "test" => StageConfig::from_builtin(BuiltinStage::CargoTest, 1200),
+            _ => anyhow::bail!("Unknown stage: {}", stage_name),
+        };
+        stage_configs.push(config);
+    }
+
+    // Create CI spec
+    let mut ci_spec = CiSpec::new(
+        workspace.clone(),
+        &stage_names,
+        git_sha.clone(),
+        toolchain_hash.clone(),
+    )
+    .allow_toolchain_mismatch(allow_toolchain_mismatch);
+    if let Some(expected) = aivcs_ci::read_toolchain_file(workspace)? {
+        ci_spec = ci_spec.with_expected_toolchain(expected);
+    }
+    if !ci_spec.toolchain_checkable() {
+        eprintln!(
+            "warning: cannot check the active toolchain ('{}') against the workspace's '{}'",
+            toolchain_hash,
+            ci_spec.expected_toolchain.as_deref().unwrap_or_default()
+        );
+    } else if !ci_spec.toolchain_matches() && allow_toolchain_mismatch {
+        eprintln!(
+            "warning: workspace expects toolchain '{}' but '{}' is active",
+            ci_spec.expected_toolchain.as_deref().unwrap_or_default(),
+            toolchain_hash
+        );
+    }
+
+    println!("Running CI pipeline for workspace: {:?}", workspace);
+    println!("Stages: {}", stages_str);
+    println!("Git SHA: {}", git_sha);
+    println!();
+
+    // Run pipeline
+    let ledger_arc = Arc::new(ledger.clone());
+    let cas = open_cas(cas_dir)?;
+    let result =
+        CiPipeline::run_with_artifacts(ledger_arc.clone(), &ci_spec, stage_configs, Some(&cas))
+            .await
+            .context("CI pipeline failed to run")?;
+
+    // Print results
+    println!("Run ID: {}", result.run_id);
+    println!(
+        "Status: {}",
+        if result.success {
+            "✓ PASSED"
+        } else {
+            "✗ FAILED"
+        }
+    );
+    println!("Duration: {}ms", result.duration_ms);
+    println!();
+
+    for stage_result in &result.stages {
+        let status = if stage_result.passed() { "✓" } else { "✗" };
+        if stage_result.timed_out {
+            println!(
+                "  {} {} ({}ms, timed out)",
+                status, stage_result.stage_name, stage_result.duration_ms
+            );
+        } else {
+            println!(
+                "  {} {} ({}ms, exit code: {})",
+                status, stage_result.stage_name, stage_result.duration_ms, stage_result.exit_code
+            );
+        }
+    }
+
+    println!();
+    println!(
+        "Summary: {}/{} stages passed",
+        result.passed_count(),
+        result.stages.len()
+    );
+
+    // Evaluate gate
+    let events = ledger_arc
+        .get_events(&oxidized_state::RunId(result.run_id.clone()))
+        .await?;
+
+    let verdict = CiGate::evaluate_with_rules(&events, &rules);
+    println!(
+        "Gate: {}",
+        if verdict.passed {
+            "✓ PASSED"
+        } else {
+            "✗ FAILED"
+        }
+    );
+
+    if !verdict.violations.is_empty() {
+        println!("Violations:");
+        for violation in &verdict.violations {
+            println!("  - {}", violation);
+        }
+    }
+
+    println!("\n{}", verdict.summary());
+
+    if let Some(recorder) = recorder {
+        let violations: Vec<&str> = verdict.violations.iter().map(String::as_str).collect();
+        recorder
+            .record_gate_outcome(
+                &git_sha,
+                "ci",
+                verdict.passed,
+                &violations,
+                serde_json::json!({
+                    "run_id": result.run_id,
+                    "stages": stages_str,
+                    "verdict": verdict,
+                }),
+            )
+            .await?;
+    }
+    Ok(verdict)
+}
+
+pub(crate) async fn cmd_ci_logs(
+    ledger: &SurrealRunLedger,
+    run_id: &str,
+    stage: &str,
+    cas_dir: Option<&std::path::Path>,
+) -> Result<()> {
+    let cas = open_cas(cas_dir)?;
+    let logs = aivcs_ci::stage_logs(ledger, &cas, run_id, stage).await?;
+
+    println!("==> {} stdout <==", logs.stage);
+    print!("{}", logs.stdout);
+    println!("==> {} stderr <==", logs.stage);
+    print!("{}", logs.stderr);
+    if logs.truncated {
+        println!("(output was truncated before storing; only its tail is kept)");
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn cmd_ci_repair(
+    ledger: &SurrealRunLedger,
+    run_id: &str,
+    workspace: &std::path::Path,
+    apply: bool,
+    sandbox_policy: Option<&PathBuf>,
+    max_actions: usize,
+    cas_dir: Option<&std::path::Path>,
+    json: bool,
+) -> Result<()> {
+    let cas = open_cas(cas_dir)?;
+    let diagnostics = aivcs_ci::run_diagnostics(ledger, &cas, run_id).await?;
+    let policy = aivcs_core::domain::ci::RepairPolicy {
+        max_actions,
+        ..Default::default()
+    };
+    let plan = aivcs_ci::plan_repair(
+        run_id
+            .parse()
+            .with_context(|| format!("invalid run id '{}'", run_id))?,
+        &diagnostics,
+        &policy,
+    );
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&plan)?);
+    } else {
+        println!(
+            "Repair plan for run {} ({:?}, {} action(s))",
+            run_id,
+            plan.strategy,
+            plan.actions.len()
+        );
+        for action in &plan.actions {
+            let location = match (&action.file, action.line, action.column) {
+                (Some(file), Some(line), Some(column)) => format!("{}:{}:{}", file, line, column),
+                (Some(file), _, _) => file.clone(),
+                _ => "(no location)".to_string(),
+            };
+            let times = if action.occurrences > 1 {
+                format!(" (x{})", action.occurrences)
+            } else {
+                String::new()
+            };
+            println!("  {}{}", location, times);
+            println!("    {}", action.rationale);
+            if let Some(command) = &action.fix_command {
+                println!("    fix: {}", command.join(" "));
+            }
+        }
+    }
+
+    if !apply {
+        return Ok(());
+    }
+    let sandbox = match sandbox_policy {
+        Some(path) => read_json_file(path)?,
+        None => aivcs_core::ToolPolicySet::standard_dev(),
+    };
+    let ran = aivcs_ci::apply_repair_plan(&plan, workspace, &sandbox).await?;
+    if ran.is_empty() {
+        eprintln!("Nothing to apply: no action has an automatic fix");
+    }
+    for command in ran {
+        eprintln!("Applied: {}", command.join(" "));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn gate_eval_exit_code(events: &str, rules: &str) -> i32 {
+        let dir = tempfile::tempdir().unwrap();
+        let events_path = dir.path().join("events.json");
+        let rules_path = dir.path().join("rules.json");
+        std::fs::write(&events_path, events).unwrap();
+        std::fs::write(&rules_path, rules).unwrap();
+        gate_exit_code(cmd_gate_eval(&events_path, &rules_path, false))
+    }
+
+    #[test]
+    fn test_gate_eval_exit_codes() {
+        let events = json!([
+            {
+                "seq": 1,
+                "kind": "tool_called",
+                "payload": { "tool_name": "clippy" },
+                "timestamp": "2026-01-01T00:00:00Z"
+            },
+            {
+                "seq": 2,
+                "kind": "tool_returned",
+                "payload": { "tool_name": "clippy", "exit_code": 101 },
+                "timestamp": "2026-01-01T00:00:01Z"
+            }
+        ])
+        .to_string();
+
+        assert_eq!(
+            gate_eval_exit_code(&events, r#"{"allow_failure": ["clippy"]}"#),
+            aivcs_ci::EXIT_PASS
+        );
+        assert_eq!(
+            gate_eval_exit_code(&events, "{}"),
+            aivcs_ci::EXIT_VIOLATIONS
+        );
+        assert_eq!(
+            gate_eval_exit_code("[{\"seq\": 1}]", "{}"),
+            aivcs_ci::EXIT_ERROR
+        );
+        assert_eq!(
+            gate_eval_exit_code(&events, r#"{"max_failures": 1}"#),
+            aivcs_ci::EXIT_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_eval_run_reports_regression_against_baseline() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let cases = [
+            aivcs_core::EvalTestCase::new(json!({ "q": "2+2" }), Some(json!(4))),
+            aivcs_core::EvalTestCase::new(json!({ "q": "3*3" }), Some(json!(9))),
+        ];
+        let suite = cases
+            .iter()
+            .fold(
+                aivcs_core::EvalSuite::new("arith".to_string(), "1.0.0".to_string()),
+                |suite, case| suite.add_test_case(case.clone()),
+            )
+            .finalize()
+            .unwrap();
+        let write = |name: &str, value: &Value| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, serde_json::to_string(value).unwrap()).unwrap();
+            path
+        };
+        let suite_path = write("suite.json", &serde_json::to_value(&suite).unwrap());
+        let good = json!({ cases[0].case_id.to_string(): 4, cases[1].case_id.to_string(): 9 });
+        let bad = json!({ cases[0].case_id.to_string(): 4, cases[1].case_id.to_string(): 8 });
+        let good_path = write("good.json", &good);
+        let bad_path = write("bad.json", &bad);
+        let cas_dir = dir.path().join("cas");
+
+        let code = cmd_eval_run(
+            &handle,
+            &suite_path,
+            &good_path,
+            None,
+            0,
+            Some(cas_dir.as_path()),
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(code, 0);
+        let cas = open_cas(Some(cas_dir.as_path())).unwrap();
+        let baseline = aivcs_core::load_eval_report(&handle, &cas, &suite.suite_digest, 0)
+            .await
+            .unwrap()
+            .expect("report persisted under suite digest and seed");
+        assert_eq!(baseline.passed_cases, 2);
+        let baseline_path = write("baseline.json", &serde_json::to_value(&baseline).unwrap());
+
+        let code = cmd_eval_run(
+            &handle,
+            &suite_path,
+            &bad_path,
+            Some(&baseline_path),
+            0,
+            Some(cas_dir.as_path()),
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(code, 1);
+
+        // The regressed run does not become the next baseline
+        let indexed = aivcs_core::load_eval_report(&handle, &cas, &suite.suite_digest, 0)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(indexed.passed_cases, 2);
+        assert!(!aivcs_core::compare_eval_reports(&baseline, &indexed).has_regression());
+    }
+}
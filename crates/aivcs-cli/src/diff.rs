@@ -0,0 +1,742 @@
+//! `aivcs diff` and `diff-runs` — comparing specs, runs, snapshots and branches.
+
+use anyhow::{Context, Result};
+use oxidized_state::{RunEvent, RunLedger, SurrealHandle, SurrealRunLedger};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use aivcs_core::commands::resolve_commit_ref;
+use aivcs_core::{
+    diff_node_paths, diff_tool_calls, NodePathDiff, NodeStep, TermStyle, ToolCallChange,
+    ToolCallDiff,
+};
+
+use crate::{read_json_file, truncate, DiffAction};
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+struct SpecDiffOutput {
+    changed_paths: Vec<String>,
+    only_in_a: Vec<String>,
+    only_in_b: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+struct RunDiffOutput {
+    events_a: usize,
+    events_b: usize,
+    tool_call_changes: usize,
+    added: usize,
+    removed: usize,
+    reordered: usize,
+    param_changed: usize,
+}
+
+pub(crate) async fn cmd_diff(handle: &SurrealHandle, action: DiffAction) -> Result<()> {
+    match action {
+        DiffAction::Spec { a, b, json } => cmd_diff_spec(&a, &b, json),
+        DiffAction::Run { a, b, json } => cmd_diff_run(&a, &b, json),
+        DiffAction::Branches { a, b, json } => cmd_diff_branches(handle, &a, &b, json).await,
+        DiffAction::Snapshot { a, b, scope, json } => {
+            cmd_diff_snapshot(handle, &a, &b, &scope, json).await
+        }
+        DiffAction::Specs { a, b, input_tag } => {
+            let ledger = SurrealRunLedger::from_handle(handle);
+            cmd_diff_specs(&ledger, &a, &b, input_tag.as_deref()).await
+        }
+    }
+}
+
+fn cmd_diff_spec(a: &PathBuf, b: &PathBuf, json: bool) -> Result<()> {
+    let left: Value = read_json_file(a)?;
+    let right: Value = read_json_file(b)?;
+    let diff = build_spec_diff(&left, &right);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        println!("{}", render_spec_diff_text(&diff, &TermStyle::stdout()));
+    }
+    Ok(())
+}
+
+fn cmd_diff_run(a: &PathBuf, b: &PathBuf, json: bool) -> Result<()> {
+    let left: Vec<RunEvent> = read_json_file(a)?;
+    let right: Vec<RunEvent> = read_json_file(b)?;
+    let diff = build_run_diff(&left, &right);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        println!("{}", render_run_diff_text(&diff));
+    }
+    Ok(())
+}
+
+async fn cmd_diff_branches(handle: &SurrealHandle, a: &str, b: &str, json: bool) -> Result<()> {
+    let delta = branch_memory_delta(handle, a, b).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&delta)?);
+    } else {
+        println!(
+            "{}",
+            render_branch_diff_text(a, b, &delta, &TermStyle::stdout())
+        );
+    }
+    Ok(())
+}
+
+async fn cmd_diff_snapshot(
+    handle: &SurrealHandle,
+    a: &str,
+    b: &str,
+    scope: &str,
+    json: bool,
+) -> Result<()> {
+    let diff = snapshot_scope_diff(handle, a, b, scope).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff.deltas)?);
+    } else {
+        println!(
+            "{}",
+            render_snapshot_diff_text(a, b, scope, &diff, &TermStyle::stdout())
+        );
+    }
+    Ok(())
+}
+
+/// Snapshot states of `a` and `b` (branches or commits) and their leaf
+/// differences below `scope`
+async fn snapshot_scope_diff(
+    handle: &SurrealHandle,
+    a: &str,
+    b: &str,
+    scope: &str,
+) -> Result<SnapshotScopeDiff> {
+    if !scope.is_empty() && !scope.starts_with('/') {
+        anyhow::bail!("Scope must be a JSON pointer starting with '/': {}", scope);
+    }
+    let mut states = Vec::with_capacity(2);
+    for reference in [a, b] {
+        let commit = resolve_commit_ref(handle, reference).await;
+        let snapshot = handle
+            .load_snapshot(&commit)
+            .await
+            .with_context(|| format!("No snapshot for {}", reference))?;
+        states.push(snapshot.state);
+    }
+    Ok(SnapshotScopeDiff {
+        missing_in_a: states[0].pointer(scope).is_none(),
+        missing_in_b: states[1].pointer(scope).is_none(),
+        deltas: aivcs_core::diff_state_subtree(&states[0], &states[1], scope).deltas,
+    })
+}
+
+struct SnapshotScopeDiff {
+    missing_in_a: bool,
+    missing_in_b: bool,
+    deltas: Vec<aivcs_core::StateDelta>,
+}
+
+fn render_snapshot_diff_text(
+    a: &str,
+    b: &str,
+    scope: &str,
+    diff: &SnapshotScopeDiff,
+    style: &TermStyle,
+) -> String {
+    let mut out = String::new();
+    let shown = if scope.is_empty() { "/" } else { scope };
+    out.push_str(&format!(
+        "Snapshot Diff: {} .. {} (scope {})\n",
+        a, b, shown
+    ));
+    out.push_str("=============\n");
+    for (side, missing) in [(a, diff.missing_in_a), (b, diff.missing_in_b)] {
+        if missing {
+            out.push_str(&format!("note: {} is absent in {}\n", shown, side));
+        }
+    }
+    if diff.deltas.is_empty() {
+        out.push_str("No changes\n");
+    }
+    for delta in &diff.deltas {
+        out.push_str(&format!("{}\n", format_state_delta(delta, style)));
+    }
+
+    out.trim_end().to_string()
+}
+
+/// One `+`/`-`/`~` line for a state delta; `Null` on a side means absent
+pub(crate) fn format_state_delta(delta: &aivcs_core::StateDelta, style: &TermStyle) -> String {
+    if delta.before.is_null() {
+        style.added(&style.fit(&format!("  + {}: {}", delta.pointer, delta.after)))
+    } else if delta.after.is_null() {
+        style.removed(&style.fit(&format!("  - {}: {}", delta.pointer, delta.before)))
+    } else {
+        style.conflict(&style.fit(&format!(
+            "  ~ {}: {} -> {}",
+            delta.pointer, delta.before, delta.after
+        )))
+    }
+}
+
+/// Diff the memories at the heads of branches `a` and `b`
+async fn branch_memory_delta(
+    handle: &SurrealHandle,
+    a: &str,
+    b: &str,
+) -> Result<semantic_rag_merge::VectorStoreDelta> {
+    let head_a = handle
+        .get_branch_head(a)
+        .await
+        .context(format!("Branch not found: {}", a))?;
+    let head_b = handle
+        .get_branch_head(b)
+        .await
+        .context(format!("Branch not found: {}", b))?;
+
+    Ok(semantic_rag_merge::diff_memory_vectors(handle, &head_a, &head_b).await?)
+}
+
+fn render_branch_diff_text(
+    a: &str,
+    b: &str,
+    delta: &semantic_rag_merge::VectorStoreDelta,
+    style: &TermStyle,
+) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("Branch Diff: {} .. {}\n", a, b));
+    out.push_str("===========\n");
+    out.push_str(&format!("only_in_a: {}\n", delta.only_in_a.len()));
+    out.push_str(&format!("only_in_b: {}\n", delta.only_in_b.len()));
+    out.push_str(&format!("conflicts: {}\n", delta.conflicts.len()));
+    out.push_str(&format!("identical: {}\n", delta.identical.len()));
+
+    if !delta.only_in_a.is_empty() {
+        out.push_str(&format!("\nOnly in {}:\n", a));
+        for m in &delta.only_in_a {
+            let line = style.fit(&format!("  - {}: {}", m.key, truncate(&m.content, 60)));
+            out.push_str(&format!("{}\n", style.removed(&line)));
+        }
+    }
+    if !delta.only_in_b.is_empty() {
+        out.push_str(&format!("\nOnly in {}:\n", b));
+        for m in &delta.only_in_b {
+            let line = style.fit(&format!("  + {}: {}", m.key, truncate(&m.content, 60)));
+            out.push_str(&format!("{}\n", style.added(&line)));
+        }
+    }
+    if !delta.conflicts.is_empty() {
+        out.push_str("\nConflicts:\n");
+        for c in &delta.conflicts {
+            let key = style.fit(&format!("  ~ {}", c.key));
+            out.push_str(&format!("{}\n", style.conflict(&key)));
+            for (side, memory) in [(a, &c.memory_a), (b, &c.memory_b)] {
+                let line = format!("      {}: {}", side, truncate(&memory.content, 60));
+                out.push_str(&format!("{}\n", style.fit(&line)));
+            }
+        }
+    }
+
+    out.trim_end().to_string()
+}
+
+fn collect_leaf_paths(prefix: &str, value: &Value, out: &mut BTreeMap<String, Value>) {
+    if let Some(obj) = value.as_object() {
+        for (k, v) in obj {
+            let next = if prefix.is_empty() {
+                format!("/{}", k.replace('~', "~0").replace('/', "~1"))
+            } else {
+                format!("{}/{}", prefix, k.replace('~', "~0").replace('/', "~1"))
+            };
+            collect_leaf_paths(&next, v, out);
+        }
+        return;
+    }
+
+    if let Some(arr) = value.as_array() {
+        for (idx, v) in arr.iter().enumerate() {
+            let next = if prefix.is_empty() {
+                format!("/{}", idx)
+            } else {
+                format!("{}/{}", prefix, idx)
+            };
+            collect_leaf_paths(&next, v, out);
+        }
+        return;
+    }
+
+    let path = if prefix.is_empty() {
+        "/".to_string()
+    } else {
+        prefix.to_string()
+    };
+    out.insert(path, value.clone());
+}
+
+fn build_spec_diff(a: &Value, b: &Value) -> SpecDiffOutput {
+    let mut left = BTreeMap::new();
+    let mut right = BTreeMap::new();
+    collect_leaf_paths("", a, &mut left);
+    collect_leaf_paths("", b, &mut right);
+
+    let mut changed_paths = Vec::new();
+    let mut only_in_a = Vec::new();
+    let mut only_in_b = Vec::new();
+
+    for (path, val_a) in &left {
+        match right.get(path) {
+            Some(val_b) if val_a != val_b => changed_paths.push(path.clone()),
+            None => only_in_a.push(path.clone()),
+            _ => {}
+        }
+    }
+
+    for path in right.keys() {
+        if !left.contains_key(path) {
+            only_in_b.push(path.clone());
+        }
+    }
+
+    SpecDiffOutput {
+        changed_paths,
+        only_in_a,
+        only_in_b,
+    }
+}
+
+fn build_run_diff(a: &[RunEvent], b: &[RunEvent]) -> RunDiffOutput {
+    let tool_diff = diff_tool_calls(a, b);
+    let mut added = 0usize;
+    let mut removed = 0usize;
+    let mut reordered = 0usize;
+    let mut param_changed = 0usize;
+
+    for change in &tool_diff.changes {
+        match change {
+            ToolCallChange::Added(_) => added += 1,
+            ToolCallChange::Removed(_) => removed += 1,
+            ToolCallChange::Reordered { .. } => reordered += 1,
+            ToolCallChange::ParamChanged { .. } => param_changed += 1,
+        }
+    }
+
+    RunDiffOutput {
+        events_a: a.len(),
+        events_b: b.len(),
+        tool_call_changes: tool_diff.changes.len(),
+        added,
+        removed,
+        reordered,
+        param_changed,
+    }
+}
+
+fn render_spec_diff_text(diff: &SpecDiffOutput, style: &TermStyle) -> String {
+    let mut out = String::new();
+    out.push_str("Spec Diff\n");
+    out.push_str("=========\n");
+    out.push_str(&format!("changed_paths: {}\n", diff.changed_paths.len()));
+    out.push_str(&format!("only_in_a: {}\n", diff.only_in_a.len()));
+    out.push_str(&format!("only_in_b: {}\n", diff.only_in_b.len()));
+
+    if !diff.changed_paths.is_empty() {
+        out.push_str("\nChanged:\n");
+        for p in &diff.changed_paths {
+            let line = style.fit(&format!("  ~ {}", p));
+            out.push_str(&format!("{}\n", style.conflict(&line)));
+        }
+    }
+    if !diff.only_in_a.is_empty() {
+        out.push_str("\nOnly in A:\n");
+        for p in &diff.only_in_a {
+            let line = style.fit(&format!("  - {}", p));
+            out.push_str(&format!("{}\n", style.removed(&line)));
+        }
+    }
+    if !diff.only_in_b.is_empty() {
+        out.push_str("\nOnly in B:\n");
+        for p in &diff.only_in_b {
+            let line = style.fit(&format!("  + {}", p));
+            out.push_str(&format!("{}\n", style.added(&line)));
+        }
+    }
+
+    out.trim_end().to_string()
+}
+
+fn render_run_diff_text(diff: &RunDiffOutput) -> String {
+    format!(
+        "Run Diff\n========\nevents_a: {}\nevents_b: {}\ntool_call_changes: {}\n  added: {}\n  removed: {}\n  reordered: {}\n  param_changed: {}",
+        diff.events_a,
+        diff.events_b,
+        diff.tool_call_changes,
+        diff.added,
+        diff.removed,
+        diff.reordered,
+        diff.param_changed
+    )
+}
+
+/// Diff the tool-call sequences of two runs, reading archived runs from `cas`
+pub(crate) async fn cmd_diff_runs(
+    ledger: &dyn RunLedger,
+    cas: &dyn aivcs_core::CasStore,
+    id_a: &str,
+    id_b: &str,
+    node_paths: bool,
+) -> Result<()> {
+    let (events_a, summary_a) = aivcs_core::replay_run_with_archive(ledger, cas, id_a)
+        .await
+        .with_context(|| format!("replay failed for run: {}", id_a))?;
+    let (events_b, summary_b) = aivcs_core::replay_run_with_archive(ledger, cas, id_b)
+        .await
+        .with_context(|| format!("replay failed for run: {}", id_b))?;
+
+    let diff = diff_tool_calls(&events_a, &events_b);
+
+    println!("A: {} ({})", summary_a.run_id, summary_a.agent_name);
+    println!("B: {} ({})", summary_b.run_id, summary_b.agent_name);
+    println!();
+
+    if node_paths {
+        let path_diff = diff_node_paths(&events_a, &events_b);
+        for line in format_node_path_divergence(&path_diff) {
+            println!("{}", line);
+        }
+        println!();
+    }
+
+    if diff.is_empty() {
+        println!("Tool-call sequences are identical.");
+        return Ok(());
+    }
+
+    print_tool_call_changes(&diff);
+    Ok(())
+}
+
+async fn cmd_diff_specs(
+    ledger: &dyn RunLedger,
+    a: &str,
+    b: &str,
+    input_tag: Option<&str>,
+) -> Result<()> {
+    let spec_a =
+        oxidized_state::ContentDigest::try_from(a.to_string()).context("Invalid spec digest A")?;
+    let spec_b =
+        oxidized_state::ContentDigest::try_from(b.to_string()).context("Invalid spec digest B")?;
+    let diff = aivcs_core::compare_specs_behavior(ledger, &spec_a, &spec_b, input_tag).await?;
+
+    println!("A: spec {} run {}", spec_a.short(), diff.run_a);
+    println!("B: spec {} run {}", spec_b.short(), diff.run_b);
+    println!();
+
+    for line in format_node_path_divergence(&diff.node_paths) {
+        println!("{}", line);
+    }
+    println!();
+
+    if diff.tool_calls.is_empty() {
+        println!("Tool-call sequences are identical.");
+    } else {
+        print_tool_call_changes(&diff.tool_calls);
+    }
+    Ok(())
+}
+
+/// Print each tool-call change on its own line, followed by the total.
+fn print_tool_call_changes(diff: &ToolCallDiff) {
+    let style = TermStyle::stdout();
+    for change in &diff.changes {
+        match change {
+            ToolCallChange::Added(call) => {
+                let line = style.fit(&format!("  + [{}] {}", call.seq, call.tool_name));
+                println!("{}", style.added(&line));
+            }
+            ToolCallChange::Removed(call) => {
+                let line = style.fit(&format!("  - [{}] {}", call.seq, call.tool_name));
+                println!("{}", style.removed(&line));
+            }
+            ToolCallChange::Reordered {
+                call,
+                from_index,
+                to_index,
+            } => {
+                println!(
+                    "{}",
+                    style.fit(&format!(
+                        "  ~ {} (pos {} -> {})",
+                        call.tool_name, from_index, to_index
+                    ))
+                );
+            }
+            ToolCallChange::ParamChanged {
+                tool_name,
+                seq_a,
+                seq_b,
+                deltas,
+            } => {
+                let line = style.fit(&format!(
+                    "  Δ {} (A:[{}] / B:[{}])",
+                    tool_name, seq_a, seq_b
+                ));
+                println!("{}", style.conflict(&line));
+                for d in deltas {
+                    println!(
+                        "{}",
+                        style.fit(&format!("      {} : {} -> {}", d.key, d.before, d.after))
+                    );
+                }
+            }
+        }
+    }
+
+    println!("\nChanges: {}", diff.changes.len());
+}
+
+/// Render a node-path divergence as "agreed until X, then A went to Y and B to Z".
+fn format_node_path_divergence(diff: &NodePathDiff) -> Vec<String> {
+    let Some(div) = &diff.divergence else {
+        return vec!["Node paths are identical.".to_string()];
+    };
+
+    let first = |tail: &[NodeStep]| {
+        tail.first()
+            .map(|s| format!("{} (seq {})", s.node_id, s.seq))
+            .unwrap_or_else(|| "<end of run>".to_string())
+    };
+
+    let mut lines = vec![format!(
+        "Node paths diverge after {} common node(s)",
+        div.common_prefix.len()
+    )];
+    if let Some(last) = div.common_prefix.last() {
+        lines.push(format!("  agreed until: {}", last));
+    }
+    lines.push(format!("  A next: {}", first(&div.tail_a)));
+    lines.push(format!("  B next: {}", first(&div.tail_b)));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidized_state::{BranchRecord, CommitId, CommitRecord};
+    use serde_json::json;
+
+    #[test]
+    fn test_spec_diff_json_output_stability() {
+        let a = json!({
+            "model": "gpt-4",
+            "routing": {"strategy": "math"},
+            "threshold": 0.9
+        });
+        let b = json!({
+            "model": "gpt-4o",
+            "routing": {"strategy": "search"},
+            "new_flag": true
+        });
+
+        let diff = build_spec_diff(&a, &b);
+        let actual = serde_json::to_string_pretty(&diff).unwrap();
+        let expected = r#"{
+  "changed_paths": [
+    "/model",
+    "/routing/strategy"
+  ],
+  "only_in_a": [
+    "/threshold"
+  ],
+  "only_in_b": [
+    "/new_flag"
+  ]
+}"#;
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_run_diff_json_output_stability() {
+        let a: Vec<RunEvent> = serde_json::from_value(json!([{
+            "seq": 1,
+            "kind": "tool_called",
+            "payload": {"tool_name":"search","query":"rust"},
+            "timestamp": "2026-01-01T00:00:00Z"
+        }]))
+        .unwrap();
+        let b: Vec<RunEvent> = serde_json::from_value(json!([{
+            "seq": 1,
+            "kind": "tool_called",
+            "payload": {"tool_name":"search","query":"python"},
+            "timestamp": "2026-01-01T00:00:00Z"
+        }]))
+        .unwrap();
+
+        let diff = build_run_diff(&a, &b);
+        let actual = serde_json::to_string_pretty(&diff).unwrap();
+        let expected = r#"{
+  "events_a": 1,
+  "events_b": 1,
+  "tool_call_changes": 1,
+  "added": 0,
+  "removed": 0,
+  "reordered": 0,
+  "param_changed": 1
+}"#;
+
+        assert_eq!(actual, expected);
+    }
+
+    fn node_event(seq: u64, node_id: &str) -> RunEvent {
+        RunEvent {
+            seq,
+            kind: "node_entered".to_string(),
+            payload: json!({ "node_id": node_id }),
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_node_path_divergence_reports_first_differing_nodes() {
+        let a = vec![
+            node_event(1, "planner"),
+            node_event(2, "retriever"),
+            node_event(3, "writer"),
+        ];
+        let b = vec![
+            node_event(1, "planner"),
+            node_event(2, "retriever"),
+            node_event(3, "critic"),
+            node_event(4, "writer"),
+        ];
+
+        let diff = diff_node_paths(&a, &b);
+        let div = diff.divergence.as_ref().expect("paths should diverge");
+        assert_eq!(div.common_prefix, vec!["planner", "retriever"]);
+
+        let lines = format_node_path_divergence(&diff);
+        assert_eq!(
+            lines,
+            vec![
+                "Node paths diverge after 2 common node(s)",
+                "  agreed until: retriever",
+                "  A next: writer (seq 3)",
+                "  B next: critic (seq 3)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_node_path_divergence_identical_paths() {
+        let a = vec![node_event(1, "planner")];
+        let lines = format_node_path_divergence(&diff_node_paths(&a, &a));
+        assert_eq!(lines, vec!["Node paths are identical."]);
+    }
+
+    #[tokio::test]
+    async fn test_diff_snapshot_reports_only_in_scope_changes() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+        let states = [
+            (
+                "scope-a",
+                json!({"model": "gpt-4", "routing": {"default": "planner", "retries": 1}}),
+            ),
+            (
+                "scope-b",
+                json!({"model": "gpt-5", "routing": {"default": "coder", "retries": 1}}),
+            ),
+            ("scope-c", json!({"model": "gpt-4"})),
+        ];
+        let mut commits = Vec::new();
+        for (label, state) in states {
+            let id = CommitId::from_state(label.as_bytes());
+            handle.save_snapshot(&id, state).await.unwrap();
+            commits.push(id.hash);
+        }
+
+        let diff = snapshot_scope_diff(&handle, &commits[0], &commits[1], "/routing")
+            .await
+            .unwrap();
+        assert_eq!(diff.deltas.len(), 1);
+        assert_eq!(diff.deltas[0].pointer, "/routing/default");
+        assert_eq!(diff.deltas[0].after, json!("coder"));
+        let text = render_snapshot_diff_text("a", "b", "/routing", &diff, &TermStyle::PLAIN);
+        assert!(text.contains("  ~ /routing/default: \"planner\" -> \"coder\""));
+        assert!(!text.contains("model"));
+
+        let diff = snapshot_scope_diff(&handle, &commits[0], &commits[2], "/routing")
+            .await
+            .unwrap();
+        assert!(diff.missing_in_b && !diff.missing_in_a);
+        assert_eq!(diff.deltas.len(), 2);
+        assert!(diff.deltas.iter().all(|d| d.after.is_null()));
+        let text = render_snapshot_diff_text("a", "c", "/routing", &diff, &TermStyle::PLAIN);
+        assert!(text.contains("note: /routing is absent in c"));
+
+        assert!(
+            snapshot_scope_diff(&handle, &commits[0], &commits[1], "routing")
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_diff_branches_reports_divergent_memories() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+
+        let memories: [(&str, &[(&str, &str)]); 2] = [
+            (
+                "left",
+                &[
+                    ("goal", "ship it"),
+                    ("plan", "plan A"),
+                    ("note", "left only"),
+                ],
+            ),
+            (
+                "right",
+                &[
+                    ("goal", "ship it"),
+                    ("plan", "plan B"),
+                    ("todo", "right only"),
+                    ("risk", "also right only"),
+                ],
+            ),
+        ];
+        for (branch, entries) in memories {
+            let id = CommitId::from_state(branch.as_bytes());
+            let commit = CommitRecord::new(id.clone(), vec![], branch, "agent");
+            handle.save_commit(&commit).await.unwrap();
+            handle
+                .save_branch(&BranchRecord::new(branch, &id.hash, false))
+                .await
+                .unwrap();
+            for (key, content) in entries {
+                handle
+                    .save_memory(&oxidized_state::MemoryRecord::new(&id.hash, key, content))
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let delta = branch_memory_delta(&handle, "left", "right").await.unwrap();
+        assert_eq!(delta.only_in_a.len(), 1);
+        assert_eq!(delta.only_in_b.len(), 2);
+        assert_eq!(delta.conflicts.len(), 1);
+        assert_eq!(delta.identical.len(), 1);
+        assert_eq!(delta.conflicts[0].key, "plan");
+
+        let text = render_branch_diff_text("left", "right", &delta, &TermStyle::PLAIN);
+        assert!(text.contains("  - note: left only"));
+        assert!(text.contains("  ~ plan"));
+
+        assert!(branch_memory_delta(&handle, "left", "missing")
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,229 @@
+//! `aivcs env` — Nix environment hashes, the Attic cache and toolchain checks.
+
+use anyhow::{Context, Result};
+use nix_env_manager::{
+    generate_environment_hash, generate_logic_hash_with, is_attic_available, is_nix_available,
+    logic_hash_breakdown, AtticClient, LogicHashConfig, NixHash,
+};
+use oxidized_state::SurrealHandle;
+use std::path::PathBuf;
+
+use aivcs_core::commands;
+use aivcs_core::short_hash;
+
+/// Generate and display environment hash
+pub(crate) async fn cmd_env_hash(path: &PathBuf) -> Result<()> {
+    let hash = generate_environment_hash(path).context(format!(
+        "Failed to generate environment hash for {:?}",
+        path
+    ))?;
+
+    println!("Environment Hash: {}", hash.hash);
+    println!("Source: {:?}", hash.source);
+    println!("Short: {}", hash.short());
+
+    Ok(())
+}
+
+/// Generate and display logic hash (Rust source)
+pub(crate) async fn cmd_logic_hash(
+    path: &PathBuf,
+    include_ext: &[String],
+    breakdown: bool,
+    json: bool,
+) -> Result<()> {
+    let config = LogicHashConfig::default().with_extensions(include_ext);
+    let hash = generate_logic_hash_with(path, &config)
+        .context(format!("Failed to generate logic hash for {:?}", path))?;
+    let files = if breakdown {
+        Some(
+            logic_hash_breakdown(path, &config)
+                .context(format!("Failed to list logic files in {:?}", path))?,
+        )
+    } else {
+        None
+    };
+
+    if json {
+        let mut output = serde_json::json!({ "logic_hash": hash });
+        if let Some(files) = &files {
+            output["files"] = serde_json::to_value(files)?;
+        }
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!("Logic Hash: {}", hash);
+    println!("Short: {}", short_hash(&hash));
+    if let Some(files) = files {
+        println!();
+        println!("{:<12}  {:>10}  PATH", "DIGEST", "BYTES");
+        for file in &files {
+            println!(
+                "{:<12}  {:>10}  {}",
+                short_hash(&file.digest),
+                file.size_bytes,
+                file.path
+            );
+        }
+        println!("{} file(s)", files.len());
+    }
+
+    Ok(())
+}
+
+/// Show Attic cache information
+pub(crate) async fn cmd_cache_info() -> Result<()> {
+    let client = AtticClient::from_env();
+    let info = client
+        .get_cache_info()
+        .await
+        .context("Failed to get cache info")?;
+
+    println!("Cache Name: {}", info.name);
+    println!("Server: {}", info.server);
+    println!("Available: {}", if info.available { "yes" } else { "no" });
+
+    if let Some(details) = info.info {
+        println!("\nDetails:");
+        println!("{}", details);
+    }
+
+    Ok(())
+}
+
+/// Preflight the Attic settings and server; fails if the cache is unusable
+pub(crate) async fn cmd_env_check() -> Result<()> {
+    let client = AtticClient::from_env();
+    let info = client
+        .check_connectivity()
+        .await
+        .context("Attic connectivity check failed")?;
+    println!("Attic cache '{}' on {}: OK", info.name, info.server);
+    Ok(())
+}
+
+/// Check if environment is cached
+pub(crate) async fn cmd_is_cached(hash: &str) -> Result<()> {
+    let client = AtticClient::from_env();
+    let nix_hash = NixHash::new(hash.to_string(), nix_env_manager::HashSource::FlakeLock);
+
+    let cached = client.is_environment_cached(&nix_hash).await;
+
+    if cached {
+        println!("Environment {} is CACHED", short_hash(hash));
+    } else {
+        println!("Environment {} is NOT cached", short_hash(hash));
+    }
+
+    Ok(())
+}
+
+/// Pull the environment recorded for a commit from Attic
+pub(crate) async fn cmd_env_reproduce(
+    handle: &SurrealHandle,
+    reference: &str,
+    json: bool,
+) -> Result<()> {
+    let client = AtticClient::from_env();
+    let reproduced = commands::reproduce_environment(handle, &client, reference).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&reproduced)?);
+    } else {
+        println!(
+            "Environment {} of {}: {}",
+            short_hash(&reproduced.env_hash),
+            short_hash(&reproduced.commit_id),
+            reproduced.store_path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Show environment system info
+pub(crate) async fn cmd_env_info() -> Result<()> {
+    use aivcs_core::domain::EnvValidation;
+
+    let validation = EnvValidation::check();
+
+    println!("AIVCS Environment Info");
+    println!("======================");
+    println!();
+    println!("Platform: {}", validation.platform);
+    println!(
+        "Nix shell: {}",
+        if validation.is_nix_shell { "yes" } else { "no" }
+    );
+    println!();
+
+    // Nix availability
+    let nix = is_nix_available();
+    println!("Nix installed: {}", if nix { "yes" } else { "no" });
+
+    if nix {
+        // Get Nix version
+        if let Ok(output) = std::process::Command::new("nix").arg("--version").output() {
+            if output.status.success() {
+                let version = String::from_utf8_lossy(&output.stdout);
+                println!("Nix version: {}", version.trim());
+            }
+        }
+    }
+
+    // Attic availability
+    let attic = is_attic_available();
+    println!("Attic installed: {}", if attic { "yes" } else { "no" });
+
+    if attic {
+        if let Ok(output) = std::process::Command::new("attic")
+            .arg("--version")
+            .output()
+        {
+            if output.status.success() {
+                let version = String::from_utf8_lossy(&output.stdout);
+                println!("Attic version: {}", version.trim());
+            }
+        }
+    }
+
+    println!();
+
+    // Environment variables
+    println!("Environment Variables:");
+    if let Ok(server) = std::env::var("ATTIC_SERVER") {
+        println!("  ATTIC_SERVER: {}", server);
+    } else {
+        println!("  ATTIC_SERVER: (not set)");
+    }
+    if let Ok(cache) = std::env::var("ATTIC_CACHE") {
+        println!("  ATTIC_CACHE: {}", cache);
+    } else {
+        println!("  ATTIC_CACHE: (not set)");
+    }
+    if std::env::var("ATTIC_TOKEN").is_ok() {
+        println!("  ATTIC_TOKEN: (set)");
+    } else {
+        println!("  ATTIC_TOKEN: (not set)");
+    }
+    if let Some(tmp) = &validation.tmpdir {
+        println!("  TMPDIR: {}", tmp);
+    }
+
+    println!();
+    match AtticClient::from_env().check_connectivity().await {
+        Ok(info) => println!("Attic cache: reachable ({} on {})", info.name, info.server),
+        Err(e) => println!("Attic cache: unavailable ({})", e),
+    }
+
+    let tips = validation.recommendations();
+    if !tips.is_empty() {
+        println!();
+        println!("Recommendations:");
+        for tip in tips {
+            println!("  - {}", tip);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+//! User-facing command failures: exit code, stable machine code, and a hint
+//! at what to try next.
+
+use std::io::Write;
+
+use aivcs_core::commands::CommandConflict;
+use oxidized_state::StateError;
+use serde::Serialize;
+
+/// Exit code for failures without a more specific code (clap uses 2 for
+/// usage errors).
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_BRANCH_NOT_FOUND: i32 = 3;
+pub const EXIT_COMMIT_NOT_FOUND: i32 = 4;
+pub const EXIT_DB_UNREACHABLE: i32 = 5;
+pub const EXIT_CONFLICT: i32 = 6;
+
+/// A failed command, classified for reporting. Each variant carries the
+/// full error message, context included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliError {
+    BranchNotFound(String),
+    CommitNotFound(String),
+    DbUnreachable(String),
+    /// Refused by a branch protection rule, or lost a race with another writer
+    Conflict(String),
+    Other(String),
+}
+
+/// `--json` form of a [`CliError`]
+#[derive(Debug, Serialize)]
+struct CliErrorOutput<'a> {
+    code: &'static str,
+    message: &'a str,
+    hint: Option<&'static str>,
+}
+
+impl CliError {
+    /// Classify `err` by the first recognised error in its chain.
+    pub fn classify(err: &anyhow::Error) -> Self {
+        let message = format!("{err:#}");
+        for cause in err.chain() {
+            if let Some(state) = cause.downcast_ref::<StateError>() {
+                match state {
+                    StateError::BranchNotFound(_) => return Self::BranchNotFound(message),
+                    StateError::CommitNotFound(_) => return Self::CommitNotFound(message),
+                    StateError::Connection(_) => return Self::DbUnreachable(message),
+                    StateError::BranchProtected { .. } => return Self::Conflict(message),
+                    _ => {}
+                }
+            }
+            if cause.downcast_ref::<CommandConflict>().is_some() {
+                return Self::Conflict(message);
+            }
+        }
+        Self::Other(message)
+    }
+
+    /// Stable identifier for scripts, e.g. `branch_not_found`
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::BranchNotFound(_) => "branch_not_found",
+            Self::CommitNotFound(_) => "commit_not_found",
+            Self::DbUnreachable(_) => "db_unreachable",
+            Self::Conflict(_) => "conflict",
+            Self::Other(_) => "error",
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::BranchNotFound(_) => EXIT_BRANCH_NOT_FOUND,
+            Self::CommitNotFound(_) => EXIT_COMMIT_NOT_FOUND,
+            Self::DbUnreachable(_) => EXIT_DB_UNREACHABLE,
+            Self::Conflict(_) => EXIT_CONFLICT,
+            Self::Other(_) => EXIT_FAILURE,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::BranchNotFound(m)
+            | Self::CommitNotFound(m)
+            | Self::DbUnreachable(m)
+            | Self::Conflict(m)
+            | Self::Other(m) => m,
+        }
+    }
+
+    /// Suggested next step, if there is an obvious one
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::BranchNotFound(_) => Some(
+                "run `aivcs branch list` to see existing branches; \
+                 in a new repository, did you run `aivcs init`?",
+            ),
+            Self::CommitNotFound(_) => {
+                Some("run `aivcs log` to see commit ids; did you run `aivcs init`?")
+            }
+            Self::DbUnreachable(_) => {
+                Some("check --db-url, SURREALDB_URL, or db_url in aivcs.toml")
+            }
+            Self::Conflict(_) => Some("run `aivcs branch list` and retry against the current head"),
+            Self::Other(_) => None,
+        }
+    }
+
+    /// Write the error to `out`: a `{code, message, hint}` object when
+    /// `json`, otherwise `Error:` and `hint:` lines.
+    pub fn report(&self, json: bool, out: &mut dyn Write) -> std::io::Result<()> {
+        if json {
+            let output = CliErrorOutput {
+                code: self.code(),
+                message: self.message(),
+                hint: self.hint(),
+            };
+            let line = serde_json::to_string(&output).map_err(std::io::Error::other)?;
+            return writeln!(out, "{line}");
+        }
+        writeln!(out, "Error: {}", self.message())?;
+        if let Some(hint) = self.hint() {
+            writeln!(out, "hint: {hint}")?;
+        }
+        Ok(())
+    }
+}
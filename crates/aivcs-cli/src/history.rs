@@ -0,0 +1,482 @@
+//! `aivcs show`, `log`, `trace`, `blame` and `verify-commit` — reading commit history.
+
+use anyhow::{Context, Result};
+use oxidized_state::{CommitRecord, StateError, SurrealHandle};
+use serde::Serialize;
+use serde_json::Value;
+
+use aivcs_core::commands::{
+    self, commit_output, log_output, parse_log_range, resolve_commit_ref, CommitOutput, LogWindow,
+};
+use aivcs_core::{render_commit_graph_ascii, short_hash, CommitGraphNode, TermStyle};
+
+use crate::truncate;
+
+/// Show the metadata of the commit at `reference`
+pub(crate) async fn cmd_show(handle: &SurrealHandle, reference: &str, json: bool) -> Result<()> {
+    let shown = commands::show(handle, reference).await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&shown)?);
+        return Ok(());
+    }
+
+    let optional = |hash: &Option<String>| hash.clone().unwrap_or_else(|| "-".to_string());
+    println!("commit  {}", shown.commit_id);
+    println!("  logic {}", optional(&shown.logic_hash));
+    println!("  state {}", shown.state_hash);
+    println!("  env   {}", optional(&shown.env_hash));
+    for parent in &shown.parent_ids {
+        println!("parent  {}", parent);
+    }
+    println!("author  {}", shown.author);
+    println!("date    {}", shown.created_at);
+    if let Some(branch) = &shown.branch {
+        println!("branch  {}", branch);
+    }
+    if shown.signed {
+        println!("signed  yes");
+    }
+    if let Some(meta) = &shown.meta {
+        if !meta.git_sha.is_empty() {
+            println!("git     {}", meta.git_sha);
+        }
+        if let Some(toolchain) = &meta.toolchain {
+            println!("rust    {}", toolchain);
+        }
+    }
+    match &shown.snapshot.delta_parent {
+        Some(parent) => println!(
+            "size    {} bytes (delta against {}, depth {})",
+            shown.snapshot.size_bytes,
+            short_hash(parent),
+            shown.snapshot.chain_depth
+        ),
+        None => println!("size    {} bytes", shown.snapshot.size_bytes),
+    }
+    println!();
+    println!("    {}", shown.message);
+    Ok(())
+}
+
+/// Show commit history
+pub(crate) async fn cmd_log(
+    handle: &SurrealHandle,
+    reference: &str,
+    limit: usize,
+    window: LogWindow,
+    oneline: bool,
+    graph: bool,
+    json: bool,
+) -> Result<()> {
+    let range = parse_log_range(reference);
+    if let (Some((from, to)), false) = (range, json) {
+        let from_commit = resolve_commit_ref(handle, from).await;
+        let to_commit = resolve_commit_ref(handle, to).await;
+        match handle.find_merge_base(&from_commit, &to_commit).await? {
+            Some(base) => println!("Merge base: {}\n", short_hash(&base)),
+            None => println!("No common ancestor between '{}' and '{}'\n", from, to),
+        }
+    }
+
+    let mut history = if graph && !json && range.is_none() {
+        let start_commit = resolve_commit_ref(handle, reference).await;
+        if window.is_unbounded() {
+            collect_commit_graph(handle, &start_commit, limit).await?
+        } else {
+            let all = collect_commit_graph(handle, &start_commit, usize::MAX).await?;
+            all.into_iter()
+                .filter(|c| window.contains(c.created_at))
+                .take(limit)
+                .collect()
+        }
+    } else {
+        commands::log_window(handle, reference, limit, window).await?
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&log_output(&history))?);
+        return Ok(());
+    }
+
+    if history.is_empty() {
+        println!("No commits found for '{}'", reference);
+        return Ok(());
+    }
+
+    if graph {
+        // Newest first keeps children ahead of their parents.
+        history.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        let nodes: Vec<CommitGraphNode> = history
+            .iter()
+            .map(|c| CommitGraphNode {
+                id: c.commit_id.hash.clone(),
+                parents: c.parent_ids.clone(),
+                label: format_commit_oneline(c),
+            })
+            .collect();
+        print!("{}", render_commit_graph_ascii(&nodes));
+        return Ok(());
+    }
+
+    let style = TermStyle::stdout();
+    if oneline {
+        for commit in &history {
+            println!("{}", style.fit(&format_commit_oneline(commit)));
+        }
+        return Ok(());
+    }
+
+    for commit in history {
+        println!("{}", style.fit(&format!("commit {}", commit.commit_id)));
+        println!("Author: {}", commit.author);
+        println!(
+            "Date:   {}",
+            commit.created_at.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+        println!();
+        for line in commit.message.lines() {
+            println!("{}", style.wrap(&format!("    {}", line)));
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+/// Format a commit as `<short hash> <first line of message>`.
+fn format_commit_oneline(commit: &CommitRecord) -> String {
+    let subject = commit.message.lines().next().unwrap_or("");
+    format!("{} {}", short_hash(&commit.commit_id.hash), subject)
+}
+
+/// Collect up to `limit` commits reachable from `start`, following every parent.
+async fn collect_commit_graph(
+    handle: &SurrealHandle,
+    start: &str,
+    limit: usize,
+) -> Result<Vec<CommitRecord>> {
+    let mut commits = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    let mut queue = std::collections::VecDeque::from([start.to_string()]);
+
+    while let Some(hash) = queue.pop_front() {
+        if commits.len() >= limit {
+            break;
+        }
+        if !seen.insert(hash.clone()) {
+            continue;
+        }
+        if let Some(commit) = handle.get_commit(&hash).await? {
+            queue.extend(commit.parent_ids.iter().cloned());
+            commits.push(commit);
+        }
+    }
+
+    Ok(commits)
+}
+
+// Stable shapes for `--json` on read commands. Field names are snake_case and
+// list order is deterministic; add fields rather than renaming or removing.
+// `log` and `branch list` shapes are shared with `aivcsd` via
+// `aivcs_core::commands`.
+
+/// `aivcs trace --json`: steps from HEAD (`step` 0) backwards.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+struct TraceOutput {
+    commit_id: String,
+    steps: Vec<TraceStepOutput>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+struct TraceStepOutput {
+    step: usize,
+    commit: CommitOutput,
+    /// Snapshot state, or `null` when no snapshot is stored for the commit
+    state: Option<Value>,
+}
+
+fn trace_output(
+    commit_id: &str,
+    history: &[CommitRecord],
+    states: Vec<Option<Value>>,
+) -> TraceOutput {
+    TraceOutput {
+        commit_id: commit_id.to_string(),
+        steps: history
+            .iter()
+            .zip(states)
+            .enumerate()
+            .map(|(step, (commit, state))| TraceStepOutput {
+                step,
+                commit: commit_output(commit),
+                state,
+            })
+            .collect(),
+    }
+}
+
+/// Show the commit that last changed `key` as of `reference`
+pub(crate) async fn cmd_blame(
+    handle: &SurrealHandle,
+    reference: &str,
+    key: &str,
+    json: bool,
+) -> Result<()> {
+    let commit_hash = resolve_commit_ref(handle, reference).await;
+    let entry = aivcs_core::blame_memory(handle, &commit_hash, key).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entry)?);
+        return Ok(());
+    }
+
+    match entry {
+        Some(entry) => {
+            println!(
+                "{} ({} {}) {}",
+                short_hash(&entry.commit_id),
+                entry.author,
+                entry.created_at.format("%Y-%m-%d %H:%M:%S"),
+                key
+            );
+            println!("{}", entry.content);
+        }
+        None => println!(
+            "Memory key '{}' not found at {}",
+            key,
+            short_hash(&commit_hash)
+        ),
+    }
+    Ok(())
+}
+
+/// Check a commit's signature; unsigned commits pass, bad or untrusted
+/// signatures fail
+pub(crate) async fn cmd_verify_commit(
+    handle: &SurrealHandle,
+    reference: &str,
+    trusted: &aivcs_core::TrustedKeys,
+) -> Result<()> {
+    use aivcs_core::SignatureStatus;
+
+    let commit_hash = resolve_commit_ref(handle, reference).await;
+    let commit = handle
+        .get_commit(&commit_hash)
+        .await?
+        .ok_or_else(|| StateError::CommitNotFound(reference.to_string()))?;
+
+    match aivcs_core::verify_commit(&commit, trusted) {
+        SignatureStatus::Unsigned => {
+            println!("{}: unsigned", short_hash(&commit_hash));
+            Ok(())
+        }
+        SignatureStatus::Valid { name, .. } => {
+            println!("{}: good signature from {}", short_hash(&commit_hash), name);
+            Ok(())
+        }
+        SignatureStatus::UntrustedSigner { signer } => anyhow::bail!(
+            "{}: signed by untrusted key {}",
+            short_hash(&commit_hash),
+            signer
+        ),
+        SignatureStatus::Invalid { reason } => {
+            anyhow::bail!("{}: BAD signature ({})", short_hash(&commit_hash), reason)
+        }
+    }
+}
+
+/// Show reasoning trace for time-travel debugging
+pub(crate) async fn cmd_trace(
+    handle: &SurrealHandle,
+    reference: &str,
+    depth: usize,
+    json: bool,
+) -> Result<()> {
+    // Resolve reference
+    let commit_hash = if let Ok(Some(branch)) = handle.get_branch(reference).await {
+        branch.head_commit_id
+    } else {
+        reference.to_string()
+    };
+
+    if json {
+        let history = handle.get_commit_history(&commit_hash, depth).await?;
+        let mut states = Vec::with_capacity(history.len());
+        for commit in &history {
+            let state = handle
+                .load_snapshot(&commit.commit_id.hash)
+                .await
+                .ok()
+                .map(|s| s.state);
+            states.push(state);
+        }
+        let output = trace_output(&commit_hash, &history, states);
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!("Reasoning Trace for {}", short_hash(&commit_hash));
+    println!("=========================================\n");
+
+    // Get commit history (limited by depth)
+    let history = handle.get_commit_history(&commit_hash, depth).await?;
+
+    if history.is_empty() {
+        println!("No commits found for '{}'", reference);
+        return Ok(());
+    }
+
+    // Load snapshots and display trace
+    let style = TermStyle::stdout();
+    for (i, commit) in history.iter().enumerate() {
+        let step_marker = if i == 0 { "HEAD" } else { &format!("~{}", i) };
+
+        println!(
+            "{}",
+            style.wrap(&format!(
+                "[{}] {} - {}",
+                step_marker,
+                commit.commit_id.short(),
+                commit.message
+            ))
+        );
+        println!(
+            "    Author: {} | {}",
+            commit.author,
+            commit.created_at.format("%Y-%m-%d %H:%M:%S")
+        );
+
+        // Try to load and display state summary
+        if let Ok(snapshot) = handle.load_snapshot(&commit.commit_id.hash).await {
+            if let Some(obj) = snapshot.state.as_object() {
+                // Show key state fields
+                let keys: Vec<_> = obj.keys().take(5).collect();
+                for key in keys {
+                    if let Some(value) = obj.get(key) {
+                        let value_str = match value {
+                            serde_json::Value::String(s) => truncate(s, 40),
+                            serde_json::Value::Number(n) => n.to_string(),
+                            serde_json::Value::Bool(b) => b.to_string(),
+                            _ => format!("{}", value).chars().take(40).collect(),
+                        };
+                        println!("{}", style.fit(&format!("    {}: {}", key, value_str)));
+                    }
+                }
+            }
+        }
+        println!();
+    }
+
+    println!(
+        "Showing {} of {} commits (use --depth to see more)",
+        history.len(),
+        depth
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidized_state::CommitId;
+    use serde_json::json;
+
+    use crate::error::{self, CliError};
+
+    #[tokio::test]
+    async fn test_show_of_missing_commit_exits_commit_not_found() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+
+        for json in [false, true] {
+            let err = cmd_show(&handle, "no-such-commit", json).await.unwrap_err();
+            let err = CliError::classify(&err);
+            assert_eq!(err.code(), "commit_not_found");
+            assert_eq!(err.exit_code(), error::EXIT_COMMIT_NOT_FOUND);
+        }
+    }
+
+    #[test]
+    fn test_parse_log_range() {
+        assert_eq!(parse_log_range("main..feature"), Some(("main", "feature")));
+        assert_eq!(parse_log_range("main"), None);
+        assert_eq!(parse_log_range("..feature"), None);
+        assert_eq!(parse_log_range("main.."), None);
+    }
+
+    #[test]
+    fn test_format_commit_oneline() {
+        let id = CommitId::from_state(b"oneline");
+        let short = short_hash(&id.hash).to_string();
+        let commit = CommitRecord::new(id, vec![], "Add planner\n\nLonger body", "test");
+        assert_eq!(
+            format_commit_oneline(&commit),
+            format!("{} Add planner", short)
+        );
+    }
+
+    fn fixed_commit(label: &str, parents: Vec<String>) -> CommitRecord {
+        let mut commit = CommitRecord::new(
+            CommitId::from_state(label.as_bytes()),
+            parents,
+            label,
+            "alice",
+        );
+        commit.created_at = chrono::DateTime::parse_from_rfc3339("2026-01-02T03:04:05Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        commit
+    }
+
+    #[test]
+    fn test_log_json_golden() {
+        let root = fixed_commit("root", vec![]);
+        let child = fixed_commit("child", vec![root.commit_id.hash.clone()]);
+
+        let value = serde_json::to_value(log_output(&[child.clone(), root.clone()])).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "commits": [
+                    {
+                        "commit_id": child.commit_id.hash,
+                        "parent_ids": [root.commit_id.hash],
+                        "author": "alice",
+                        "message": "child",
+                        "created_at": "2026-01-02T03:04:05Z",
+                        "branch": null
+                    },
+                    {
+                        "commit_id": root.commit_id.hash,
+                        "parent_ids": [],
+                        "author": "alice",
+                        "message": "root",
+                        "created_at": "2026-01-02T03:04:05Z",
+                        "branch": null
+                    }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_trace_json_golden() {
+        let root = fixed_commit("root", vec![]);
+        let head = fixed_commit("head", vec![root.commit_id.hash.clone()]);
+        let output = trace_output(
+            &head.commit_id.hash,
+            &[head.clone(), root.clone()],
+            vec![Some(json!({ "step": 2 })), None],
+        );
+
+        let value = serde_json::to_value(output).unwrap();
+        assert_eq!(value["commit_id"], json!(head.commit_id.hash));
+        assert_eq!(value["steps"][0]["step"], json!(0));
+        assert_eq!(value["steps"][0]["commit"]["message"], json!("head"));
+        assert_eq!(value["steps"][0]["state"], json!({ "step": 2 }));
+        assert_eq!(value["steps"][1]["step"], json!(1));
+        assert_eq!(value["steps"][1]["state"], Value::Null);
+        let keys: Vec<&String> = value["steps"][0].as_object().unwrap().keys().collect();
+        assert_eq!(keys, vec!["step", "commit", "state"]);
+    }
+}
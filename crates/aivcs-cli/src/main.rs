@@ -9,29 +9,68 @@
 //! - `branch`: Create or list branches
 //! - `merge`: Merge two branches with semantic resolution
 //! - `log`: Show commit history
-
+//! - `show`: Show a commit's metadata
+
+mod branch;
+mod bundle;
+mod ci;
+mod diff;
+mod env;
+mod error;
+mod history;
 mod infra;
+mod memory;
+mod merge;
 mod oci;
+mod pr;
+mod release;
+mod runs;
+mod snapshot;
+mod stash;
 
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
-use nix_env_manager::{
-    generate_environment_hash, generate_logic_hash, is_attic_available, is_nix_available,
-    AtticClient, NixHash,
-};
-use oxidized_state::{
-    BranchRecord, CommitId, CommitRecord, ReleaseRegistry, RunEvent, RunLedger,
-    SurrealDbReleaseRegistry, SurrealHandle, SurrealRunLedger,
-};
-use serde::Serialize;
-use serde_json::Value;
-use std::collections::BTreeMap;
+use clap::{CommandFactory, Parser, Subcommand};
+use oxidized_state::{BranchProtection, DecisionFilter, SurrealRunLedger};
+use std::io::IsTerminal;
 use std::path::PathBuf;
 use std::sync::Arc;
-use tracing::{info, warn, Level};
+use tracing::Level;
+
+use aivcs_core::commands::{parse_log_time, LogWindow};
+use aivcs_core::config::{Config, ConfigOverrides};
+use aivcs_core::{DecisionRecorder, NoProgress, Progress, TermProgress};
 
-use aivcs_ci::{BuiltinStage, CiGate, CiPipeline, CiSpec, StageConfig};
-use aivcs_core::{diff_tool_calls, fork_agent_parallel, ToolCallChange};
+use branch::{cmd_branch_create, cmd_branch_delete, cmd_branch_list, cmd_branch_protect, cmd_fork};
+use bundle::{
+    cmd_bundle_export, cmd_bundle_import, cmd_cas_stats, cmd_cas_verify, cmd_remote_sync,
+};
+use ci::{
+    cmd_ci_logs, cmd_ci_repair, cmd_ci_run, cmd_ci_watch, cmd_eval_run, cmd_gate_eval,
+    cmd_gate_regression, gate_exit_code,
+};
+use diff::{cmd_diff, cmd_diff_runs};
+use env::{
+    cmd_cache_info, cmd_env_check, cmd_env_hash, cmd_env_info, cmd_env_reproduce, cmd_is_cached,
+    cmd_logic_hash,
+};
+use error::CliError;
+use history::{cmd_blame, cmd_log, cmd_show, cmd_trace, cmd_verify_commit};
+use memory::{
+    cmd_decisions_list, cmd_memory_cluster, cmd_memory_dedup, cmd_memory_export, cmd_memory_import,
+    cmd_memory_log, cmd_memory_provenance,
+};
+use merge::{cmd_merge, cmd_merge_dry_run, cmd_merge_resolve};
+use pr::{
+    cmd_pr_branch, cmd_pr_commit, cmd_pr_note, cmd_pr_open, cmd_pr_pipeline,
+    cmd_pr_semantic_summary, cmd_pr_verify_reproducibility, cmd_pr_verify_snapshot,
+    cmd_report_cross_org, PrOpenArgs, PrPipelineArgs,
+};
+use release::{
+    cmd_release_current, cmd_release_history, cmd_release_promote, cmd_release_rollback,
+};
+use runs::{cmd_cost_report, cmd_run_checkpoint_diff, cmd_run_cost, cmd_run_tail};
+use snapshot::{cmd_init, cmd_replay_artifact, cmd_restore, cmd_snapshot};
+use stash::{cmd_stash_list, cmd_stash_pop, cmd_stash_save};
 
 // clap `value_parser` for `--owner` / `--repo` flags. Rejects names that
 // downstream sites would splice into REST URL paths, A2A event payloads, or
@@ -59,10 +98,20 @@ struct Cli {
     #[arg(short, long, global = true)]
     verbose: bool,
 
-    /// Emit JSON-formatted log lines
+    /// Emit JSON-formatted log lines, and JSON output from read commands
+    /// (`log`, `branch list`, `trace`)
     #[arg(long, global = true)]
     json: bool,
 
+    /// SurrealDB URL, overriding SURREALDB_URL and aivcs.toml
+    #[arg(long, global = true)]
+    db_url: Option<String>,
+
+    /// Show a progress bar for `fork`, `merge`, and `bundle export` (only on
+    /// a terminal, and never with --json)
+    #[arg(long, global = true)]
+    progress: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -80,7 +129,7 @@ enum Commands {
     ///
     /// # TDD: test_agent_git_snapshot_cli_returns_valid_id
     Snapshot {
-        /// Path to agent state file (JSON)
+        /// Path to agent state file (JSON), or `-` to read it from stdin
         #[arg(short, long)]
         state: PathBuf,
 
@@ -88,9 +137,9 @@ enum Commands {
         #[arg(short, long, default_value = "Auto-snapshot")]
         message: String,
 
-        /// Author/agent name
-        #[arg(short, long, default_value = "agent")]
-        author: String,
+        /// Author/agent name [default: $AIVCS_AUTHOR, then git user.name/email, then "agent"]
+        #[arg(short, long)]
+        author: Option<String>,
 
         /// Branch to commit to
         #[arg(short, long, default_value = "main")]
@@ -100,9 +149,17 @@ enum Commands {
         #[arg(long)]
         git_sha: Option<String>,
 
-        /// CAS storage directory (default: .aivcs/cas in current directory)
+        /// CAS storage directory (default: AIVCS_CAS_DIR, aivcs.toml, or .aivcs/cas)
         #[arg(long)]
         cas_dir: Option<PathBuf>,
+
+        /// Don't create a commit when the state matches the branch head
+        #[arg(long)]
+        skip_if_unchanged: bool,
+
+        /// Sign the commit with the Ed25519 seed (hex) in this file
+        #[arg(long)]
+        sign_key: Option<PathBuf>,
     },
 
     /// Restore agent to a previous state
@@ -113,6 +170,19 @@ enum Commands {
         /// Output path for restored state
         #[arg(short, long)]
         output: Option<PathBuf>,
+
+        /// Shell command to receive the restored state on stdin
+        /// (also exported as `AIVCS_RESTORED_COMMIT` / `AIVCS_RESTORED_STATE`)
+        #[arg(long)]
+        exec: Option<String>,
+
+        /// Check the state against its commit's state hash before restoring
+        #[arg(long)]
+        verify: bool,
+
+        /// CAS storage directory (default: AIVCS_CAS_DIR, aivcs.toml, or .aivcs/cas)
+        #[arg(long)]
+        cas_dir: Option<PathBuf>,
     },
 
     /// Replay a recorded run artifact from disk by run ID
@@ -138,19 +208,49 @@ enum Commands {
 
     /// Show commit history
     Log {
-        /// Branch or commit to show history for
+        /// Branch or commit to show history for, or a `<from>..<to>` range
         #[arg(default_value = "main")]
         reference: String,
 
         /// Maximum number of commits to show
         #[arg(short, long, default_value = "10")]
         limit: usize,
+
+        /// Only commits created at or after this time: RFC 3339, or a
+        /// duration ago such as `7d` or `24h`
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only commits created at or before this time: RFC 3339, or a
+        /// duration ago such as `7d` or `24h`
+        #[arg(long)]
+        until: Option<String>,
+
+        /// Print one line per commit: `<short hash> <message>`
+        #[arg(long)]
+        oneline: bool,
+
+        /// Draw the commit graph, following all parents of merge commits
+        #[arg(long)]
+        graph: bool,
+    },
+
+    /// Show a commit's metadata without restoring its state
+    Show {
+        /// Commit ID or branch to show
+        #[arg(default_value = "main")]
+        commit: String,
     },
 
     /// Merge two branches
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Merge {
+        #[command(subcommand)]
+        action: Option<MergeAction>,
+
         /// Source branch to merge from
-        source: String,
+        #[arg(required = true)]
+        source: Option<String>,
 
         /// Target branch to merge into (default: current branch)
         #[arg(short, long, default_value = "main")]
@@ -159,9 +259,21 @@ enum Commands {
         /// Merge commit message
         #[arg(short, long)]
         message: Option<String>,
+
+        /// Always create a merge commit, even when a fast-forward is possible
+        #[arg(long)]
+        no_ff: bool,
+
+        /// Show what the merge would do without writing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Author of the merge commit [default: $AIVCS_AUTHOR, then git user.name/email, then "agent"]
+        #[arg(long)]
+        author: Option<String>,
     },
 
-    /// Show differences for specs or runs
+    /// Show differences for specs, runs, or branch memories
     Diff {
         #[command(subcommand)]
         action: DiffAction,
@@ -204,6 +316,25 @@ enum Commands {
         depth: usize,
     },
 
+    /// Show which commit last changed a memory key
+    Blame {
+        /// Commit ID or branch to start from
+        commit: String,
+
+        /// Memory key to attribute
+        key: String,
+    },
+
+    /// Verify a commit's Ed25519 signature against the trusted key set
+    VerifyCommit {
+        /// Commit ID or branch to verify
+        commit: String,
+
+        /// Trusted public keys, one `<hex key> [name]` per line
+        #[arg(long, env = "AIVCS_TRUSTED_KEYS")]
+        keys: Option<PathBuf>,
+    },
+
     /// Diff the tool-call sequences of two runs
     DiffRuns {
         /// First run ID
@@ -213,6 +344,62 @@ enum Commands {
         /// Second run ID
         #[arg(long)]
         run_b: String,
+
+        /// Also show where the runs' node execution paths diverged
+        #[arg(long)]
+        node_paths: bool,
+
+        /// CAS storage directory holding archived runs (default: AIVCS_CAS_DIR, aivcs.toml, or .aivcs/cas)
+        #[arg(long)]
+        cas_dir: Option<PathBuf>,
+    },
+
+    /// Run ledger operations
+    Run {
+        #[command(subcommand)]
+        action: RunAction,
+    },
+
+    /// Token and cost accounting across runs
+    Cost {
+        #[command(subcommand)]
+        action: CostAction,
+    },
+
+    /// Export or import the repository as a single archive
+    Bundle {
+        #[command(subcommand)]
+        action: BundleAction,
+    },
+
+    /// Inspect and check the content-addressed store
+    Cas {
+        #[command(subcommand)]
+        action: CasAction,
+    },
+
+    /// Inspect and maintain a commit's memories
+    Memory {
+        #[command(subcommand)]
+        action: MemoryAction,
+    },
+
+    /// Query recorded agent decisions
+    Decisions {
+        #[command(subcommand)]
+        action: DecisionsAction,
+    },
+
+    /// Set aside work-in-progress state without committing it
+    Stash {
+        #[command(subcommand)]
+        action: StashAction,
+    },
+
+    /// Sync branches with a remote SurrealDB configured via AIVCS_REMOTE_* variables
+    Remote {
+        #[command(subcommand)]
+        action: RemoteAction,
     },
 
     /// CI pipeline operations
@@ -221,6 +408,18 @@ enum Commands {
         action: CiAction,
     },
 
+    /// CI gate evaluation (exit code 0 pass, 1 violations, 2 evaluation error)
+    Gate {
+        #[command(subcommand)]
+        action: GateAction,
+    },
+
+    /// Eval suite runs (exit code 1 when a baseline-passing case regresses)
+    Eval {
+        #[command(subcommand)]
+        action: EvalAction,
+    },
+
     /// Git forge change-request operations (GitHub PR or GitLab MR)
     Pr {
         #[command(subcommand)]
@@ -271,166 +470,546 @@ enum Commands {
         #[arg(long, default_value = "main")]
         base: String,
     },
+
+    /// Print a shell completion script to stdout
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
 }
 
 #[derive(Subcommand)]
-enum CiAction {
-    /// Run CI stages and record execution
-    Run {
-        /// Workspace path (default: current directory)
-        #[arg(short, long, default_value = ".")]
-        workspace: PathBuf,
+enum MergeAction {
+    /// Interactively resolve a merge commit's outstanding manual conflicts
+    Resolve {
+        /// Merge commit ID
+        merge_commit: String,
+    },
+}
 
-        /// Stages to run (comma-separated: fmt,check,clippy,test)
-        #[arg(short, long, default_value = "fmt,check")]
-        stages: String,
+#[derive(Subcommand)]
+enum RunAction {
+    /// Print a run's events as they are appended, until the run finishes
+    Tail {
+        /// Run ID to follow
+        run_id: String,
+    },
 
-        /// Skip caching
+    /// Diff the state of the last checkpoint saved by each of two runs
+    CheckpointDiff {
+        /// First run ID
         #[arg(long)]
-        no_cache: bool,
+        run_a: String,
 
-        /// Auto-repair (use fix commands)
+        /// Second run ID
         #[arg(long)]
-        fix: bool,
+        run_b: String,
+
+        /// Emit the deltas as JSON instead of terminal text
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show the tokens a run used and their cost, priced with the
+    /// `[pricing]` table of aivcs.toml
+    Cost {
+        /// Run ID
+        run_id: String,
     },
 }
 
 #[derive(Subcommand)]
-enum ReportAction {
-    /// Cross-organizational integration health & performance audit (Issue #184)
-    CrossOrg {
-        /// Optional path to cross-repo dependency graph (JSON)
-        #[arg(short, long)]
-        graph: Option<PathBuf>,
-
-        /// Objective identifier to aggregate health for
-        #[arg(short, long, default_value = "main")]
-        objective: String,
+enum CostAction {
+    /// Sum tokens and cost over runs, priced with the `[pricing]` table of
+    /// aivcs.toml
+    Report {
+        /// Only runs of this agent
+        #[arg(long)]
+        agent: Option<String>,
 
-        /// Output path for the Markdown report
-        #[arg(short, long)]
-        output: Option<PathBuf>,
+        /// Only runs started at or after this time: RFC 3339, or a duration
+        /// ago such as `30d`
+        #[arg(long)]
+        since: Option<String>,
     },
 }
 
 #[derive(Subcommand)]
-enum PrAction {
-    /// Open a new Pull Request for a branch
-    Open {
-        /// PR title
-        #[arg(short, long)]
-        title: String,
-
-        /// PR body (markdown)
-        #[arg(short, long)]
-        body: String,
+enum BundleAction {
+    /// Write commits, snapshots, branches, memories and CAS blobs to a tarball
+    Export {
+        /// Output bundle path
+        out: PathBuf,
 
-        /// Head branch (source of changes)
-        #[arg(short, long)]
-        head: String,
-
-        /// Base branch (target for changes, default: main)
-        #[arg(short, long, default_value = "main")]
-        base: String,
-
-        /// GitHub organization/owner
-        #[arg(long, value_parser = github_name_arg)]
-        owner: String,
+        /// Only include commits not reachable from this commit (or branch)
+        #[arg(long)]
+        since: Option<String>,
 
-        /// GitHub repository name
-        #[arg(long, value_parser = github_name_arg)]
-        repo: String,
+        /// CAS storage directory (default: AIVCS_CAS_DIR, aivcs.toml, or .aivcs/cas)
+        #[arg(long)]
+        cas_dir: Option<PathBuf>,
+    },
+    /// Restore a bundle, skipping records that already exist
+    Import {
+        /// Bundle to import
+        input: PathBuf,
 
-        /// Request review from the Librarian Agent
-        #[arg(long, default_value_t = true)]
-        librarian: bool,
+        /// CAS storage directory (default: AIVCS_CAS_DIR, aivcs.toml, or .aivcs/cas)
+        #[arg(long)]
+        cas_dir: Option<PathBuf>,
+    },
+}
 
-        /// Require local-ci validation before opening PR
-        #[arg(long, default_value_t = true)]
-        require_local_ci: bool,
+#[derive(Subcommand)]
+enum CasAction {
+    /// Re-hash every blob and report any whose content no longer matches
+    /// its digest; exits non-zero if corruption is found
+    Verify {
+        /// CAS storage directory (default: AIVCS_CAS_DIR, aivcs.toml, or .aivcs/cas)
+        #[arg(long)]
+        cas_dir: Option<PathBuf>,
     },
 
-    /// Create a GitHub branch from a base ref
-    Branch {
-        /// Branch name to create
-        #[arg(short, long)]
-        name: String,
+    /// Report blob count and size of the store
+    Stats {
+        /// CAS storage directory (default: AIVCS_CAS_DIR, aivcs.toml, or .aivcs/cas)
+        #[arg(long)]
+        cas_dir: Option<PathBuf>,
+    },
+}
 
-        /// Base branch or ref
-        #[arg(short, long, default_value = "main")]
-        base: String,
+#[derive(Subcommand)]
+enum MemoryAction {
+    /// Find near-duplicate memories by embedding cosine similarity and
+    /// delete the newer memory of each pair
+    Dedup {
+        /// Commit ID or branch whose memories to scan
+        #[arg(long, default_value = "main")]
+        commit: String,
 
-        /// GitHub organization/owner
-        #[arg(long, value_parser = github_name_arg)]
-        owner: String,
+        /// Minimum cosine similarity for two memories to count as duplicates
+        #[arg(long, default_value = "0.95")]
+        threshold: f32,
 
-        /// GitHub repository name
-        #[arg(long, value_parser = github_name_arg)]
-        repo: String,
+        /// Only report duplicate pairs; delete nothing
+        #[arg(long)]
+        dry_run: bool,
     },
 
-    /// Commit a file to a GitHub branch via the Contents API
-    Commit {
-        /// Target branch
-        #[arg(short, long)]
+    /// Show how a memory key changed along a branch, oldest first
+    Log {
+        /// Branch whose history to walk
         branch: String,
 
-        /// Repository-relative file path
-        #[arg(short, long)]
-        path: String,
+        /// Memory key to follow
+        key: String,
+    },
 
-        /// Commit message
-        #[arg(short, long)]
-        message: String,
+    /// Show which run events produced a memory
+    Provenance {
+        /// Memory key
+        key: String,
 
-        /// Local file to upload
-        #[arg(short, long)]
-        file: PathBuf,
+        /// Commit ID or branch holding the memory
+        #[arg(long, default_value = "main")]
+        commit: String,
+    },
 
-        /// GitHub organization/owner
-        #[arg(long, value_parser = github_name_arg)]
-        owner: String,
+    /// Group a commit's memories into clusters of related content by
+    /// embedding similarity
+    Cluster {
+        /// Commit ID or branch whose memories to cluster
+        #[arg(long, default_value = "main")]
+        commit: String,
 
-        /// GitHub repository name
-        #[arg(long, value_parser = github_name_arg)]
-        repo: String,
+        /// Minimum cosine similarity linking two memories into a cluster
+        #[arg(long, default_value = "0.85")]
+        threshold: f32,
     },
 
-    /// Zero-touch pipeline: branch → commit → CODE_COMMITTED → open PR
-    ///
-    /// Runs the full autonomous builder flow in one invocation. Intended for
-    /// ephemeral ADK Agent Jobs that invoke `aivcs` via `uv`.
-    Pipeline {
-        /// Feature branch to create and commit to
+    /// Write a commit's memories as JSON Lines, one MemoryRecord per line
+    Export {
+        /// Commit ID or branch whose memories to export
+        #[arg(long, default_value = "main")]
+        commit: String,
+
+        /// Output JSONL file
         #[arg(long)]
-        branch: String,
+        out: PathBuf,
+    },
 
-        /// Base branch or ref
-        #[arg(long, default_value = "develop")]
-        base: String,
+    /// Save memories from a JSON Lines file under a commit
+    ///
+    /// Every line is validated before anything is saved.
+    Import {
+        /// Commit ID or branch to attach the memories to
+        #[arg(long, default_value = "main")]
+        commit: String,
 
-        /// Repository-relative file path
-        #[arg(long)]
-        path: String,
+        /// Input JSONL file
+        #[arg(long = "in")]
+        input: PathBuf,
+    },
+}
 
-        /// Local file to upload
+#[derive(Subcommand)]
+enum DecisionsAction {
+    /// List decisions, newest first
+    List {
+        /// Only decisions about this task
         #[arg(long)]
-        file: PathBuf,
+        task: Option<String>,
 
-        /// Commit message
+        /// Only decisions with this outcome: success, failure, partial or
+        /// skipped
         #[arg(long)]
-        message: String,
+        outcome: Option<aivcs_core::RationaleOutcome>,
 
-        /// Pull request title
+        /// Only decisions made at or after this time: RFC 3339, or a
+        /// duration ago such as `7d` or `24h`
         #[arg(long)]
-        title: String,
+        since: Option<String>,
 
-        /// Pull request body (markdown)
+        /// Only decisions made at or before this time: RFC 3339, or a
+        /// duration ago such as `7d` or `24h`
         #[arg(long)]
-        body: String,
+        until: Option<String>,
 
-        /// GitHub organization/owner
-        #[arg(long, value_parser = github_name_arg)]
+        /// Maximum number of decisions to show
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum StashAction {
+    /// Store state in CAS under a named stash entry
+    Save {
+        /// Path to agent state file (JSON), or `-` to read it from stdin
+        #[arg(short, long)]
+        state: PathBuf,
+
+        /// Stash name (default: `stash-<UTC timestamp>`)
+        #[arg(short, long)]
+        name: Option<String>,
+
+        /// Description of the stashed work
+        #[arg(short, long, default_value = "WIP")]
+        message: String,
+
+        /// CAS storage directory (default: AIVCS_CAS_DIR, aivcs.toml, or .aivcs/cas)
+        #[arg(long)]
+        cas_dir: Option<PathBuf>,
+    },
+    /// List stashes, newest first
+    List,
+    /// Restore a stash's state and remove the stash
+    Pop {
+        /// Stash name
+        name: String,
+
+        /// Output path for restored state (default: print to stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// CAS storage directory (default: AIVCS_CAS_DIR, aivcs.toml, or .aivcs/cas)
+        #[arg(long)]
+        cas_dir: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum RemoteAction {
+    /// Send missing commits to the remote and move its branch head
+    Push {
+        /// Branch to push
+        branch: String,
+
+        /// Overwrite the remote branch even if it is not a fast-forward
+        #[arg(long)]
+        force: bool,
+
+        #[command(flatten)]
+        cas: RemoteCasArgs,
+    },
+    /// Fetch missing commits from the remote and move the local branch head
+    Pull {
+        /// Branch to pull
+        branch: String,
+
+        /// Overwrite the local branch even if it is not a fast-forward
+        #[arg(long)]
+        force: bool,
+
+        #[command(flatten)]
+        cas: RemoteCasArgs,
+    },
+}
+
+#[derive(clap::Args)]
+struct RemoteCasArgs {
+    /// Local CAS storage directory (default: AIVCS_CAS_DIR, aivcs.toml, or .aivcs/cas)
+    #[arg(long)]
+    cas_dir: Option<PathBuf>,
+
+    /// CAS directory backing the remote (default: same as the local CAS)
+    #[arg(long, env = "AIVCS_REMOTE_CAS_DIR")]
+    remote_cas_dir: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum CiAction {
+    /// Run CI stages and record execution
+    Run {
+        /// Workspace path (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        workspace: PathBuf,
+
+        /// Stages to run (comma-separated: fmt,check,clippy,test)
+        #[arg(short, long, default_value = "fmt,check")]
+        stages: String,
+
+        /// Skip caching
+        #[arg(long)]
+        no_cache: bool,
+
+        /// Auto-repair (use fix commands)
+        #[arg(long)]
+        fix: bool,
+
+        /// JSON or TOML file of gate rules (allow_failure, required_tools, max_warnings)
+        #[arg(long)]
+        gate_rules: Option<PathBuf>,
+
+        /// Keep running, rerunning the stages whenever `.rs` files change
+        #[arg(long)]
+        watch: bool,
+
+        /// CAS directory for stage stdout/stderr
+        #[arg(long)]
+        cas_dir: Option<PathBuf>,
+
+        /// Run even if the active toolchain differs from the workspace's
+        /// rust-toolchain file
+        #[arg(long)]
+        allow_toolchain_mismatch: bool,
+    },
+    /// Print the stored stdout/stderr of one stage of a recorded run
+    Logs {
+        /// Run ID printed by `ci run`
+        run_id: String,
+        /// Stage name, e.g. `clippy` or `cargo_clippy`
+        #[arg(long)]
+        stage: String,
+        /// CAS directory the run stored its output in
+        #[arg(long)]
+        cas_dir: Option<PathBuf>,
+    },
+    /// Plan repairs for the diagnostics of a failed run
+    Repair {
+        /// Run ID printed by `ci run`
+        run_id: String,
+        /// Workspace the fix commands run in
+        #[arg(short, long, default_value = ".")]
+        workspace: PathBuf,
+        /// Run the plan's fix commands instead of only printing the plan
+        #[arg(long)]
+        apply: bool,
+        /// Sandbox policy JSON (a ToolPolicySet); defaults to the standard
+        /// developer policy
+        #[arg(long)]
+        sandbox_policy: Option<PathBuf>,
+        /// Most actions to propose
+        #[arg(long, default_value_t = 20)]
+        max_actions: usize,
+        /// CAS directory the run stored its output in
+        #[arg(long)]
+        cas_dir: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum EvalAction {
+    /// Score recorded outputs against an eval suite and store the report
+    Run {
+        /// Eval suite JSON file (a finalized EvalSuite)
+        #[arg(long)]
+        suite: PathBuf,
+        /// JSON object mapping test case ids to the agent's output
+        #[arg(long)]
+        outputs: PathBuf,
+        /// Known-good report JSON to check for regressions against
+        #[arg(long)]
+        baseline: Option<PathBuf>,
+        /// Seed recorded in the report
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// CAS directory for the stored report
+        #[arg(long)]
+        cas_dir: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum GateAction {
+    /// Evaluate the CI gate over a recorded run's events
+    Eval {
+        /// Run events JSON file (array of RunEvent)
+        events: PathBuf,
+        /// Gate rules JSON or TOML file (allow_failure, required_tools, max_warnings)
+        rules: PathBuf,
+    },
+    /// Compare a run's events against a known-good baseline run
+    Regression {
+        /// Current run events JSON file (array of RunEvent)
+        #[arg(long)]
+        current: PathBuf,
+        /// Baseline run events JSON file (array of RunEvent)
+        #[arg(long)]
+        baseline: PathBuf,
+        /// Gate rules JSON or TOML file; thresholds live under `regression`
+        #[arg(long)]
+        rules: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ReportAction {
+    /// Cross-organizational integration health & performance audit (Issue #184)
+    CrossOrg {
+        /// Optional path to cross-repo dependency graph (JSON)
+        #[arg(short, long)]
+        graph: Option<PathBuf>,
+
+        /// Objective identifier to aggregate health for
+        #[arg(short, long, default_value = "main")]
+        objective: String,
+
+        /// Output path for the Markdown report
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum PrAction {
+    /// Open a new Pull Request for a branch
+    Open {
+        /// PR title
+        #[arg(short, long)]
+        title: String,
+
+        /// PR body (markdown)
+        #[arg(short, long)]
+        body: String,
+
+        /// Head branch (source of changes)
+        #[arg(short, long)]
+        head: String,
+
+        /// Base branch (target for changes, default: main)
+        #[arg(short, long, default_value = "main")]
+        base: String,
+
+        /// GitHub organization/owner
+        #[arg(long, value_parser = github_name_arg)]
+        owner: String,
+
+        /// GitHub repository name
+        #[arg(long, value_parser = github_name_arg)]
+        repo: String,
+
+        /// Request review from the Librarian Agent
+        #[arg(long, default_value_t = true)]
+        librarian: bool,
+
+        /// Require local-ci validation before opening PR
+        #[arg(long, default_value_t = true)]
+        require_local_ci: bool,
+    },
+
+    /// Create a GitHub branch from a base ref
+    Branch {
+        /// Branch name to create
+        #[arg(short, long)]
+        name: String,
+
+        /// Base branch or ref
+        #[arg(short, long, default_value = "main")]
+        base: String,
+
+        /// GitHub organization/owner
+        #[arg(long, value_parser = github_name_arg)]
+        owner: String,
+
+        /// GitHub repository name
+        #[arg(long, value_parser = github_name_arg)]
+        repo: String,
+    },
+
+    /// Commit a file to a GitHub branch via the Contents API
+    Commit {
+        /// Target branch
+        #[arg(short, long)]
+        branch: String,
+
+        /// Repository-relative file path
+        #[arg(short, long)]
+        path: String,
+
+        /// Commit message
+        #[arg(short, long)]
+        message: String,
+
+        /// Local file to upload
+        #[arg(short, long)]
+        file: PathBuf,
+
+        /// GitHub organization/owner
+        #[arg(long, value_parser = github_name_arg)]
+        owner: String,
+
+        /// GitHub repository name
+        #[arg(long, value_parser = github_name_arg)]
+        repo: String,
+    },
+
+    /// Zero-touch pipeline: branch → commit → CODE_COMMITTED → open PR
+    ///
+    /// Runs the full autonomous builder flow in one invocation. Intended for
+    /// ephemeral ADK Agent Jobs that invoke `aivcs` via `uv`.
+    Pipeline {
+        /// Feature branch to create and commit to
+        #[arg(long)]
+        branch: String,
+
+        /// Base branch or ref
+        #[arg(long, default_value = "develop")]
+        base: String,
+
+        /// Repository-relative file path
+        #[arg(long)]
+        path: String,
+
+        /// Local file to upload
+        #[arg(long)]
+        file: PathBuf,
+
+        /// Commit message
+        #[arg(long)]
+        message: String,
+
+        /// Pull request title
+        #[arg(long)]
+        title: String,
+
+        /// Pull request body (markdown)
+        #[arg(long)]
+        body: String,
+
+        /// GitHub organization/owner
+        #[arg(long, value_parser = github_name_arg)]
         owner: String,
 
         /// GitHub repository name
@@ -485,6 +1064,24 @@ enum BranchAction {
         /// Branch name
         name: String,
     },
+
+    /// Set protection rules for a branch (replaces any existing rules)
+    Protect {
+        /// Branch name
+        name: String,
+
+        /// Reject non-fast-forward pushes, even with --force
+        #[arg(long)]
+        no_force_push: bool,
+
+        /// Reject deleting the branch
+        #[arg(long)]
+        no_delete: bool,
+
+        /// Reject merges that would create a merge commit
+        #[arg(long)]
+        require_linear: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -501,17 +1098,35 @@ enum EnvAction {
         /// Path to source directory
         #[arg(default_value = "src")]
         path: PathBuf,
+
+        /// Also hash files with these extensions, e.g. `md,toml,py`
+        #[arg(long = "include-ext", value_delimiter = ',')]
+        include_ext: Vec<String>,
+
+        /// List each hashed file with its own digest and size
+        #[arg(long)]
+        breakdown: bool,
     },
 
     /// Check Attic cache status
     CacheInfo,
 
+    /// Verify the Attic server is reachable and accepts the token
+    Check,
+
     /// Check if environment is cached
     IsCached {
         /// Environment hash to check
         hash: String,
     },
 
+    /// Pull the Nix environment a commit was snapshotted in from Attic
+    Reproduce {
+        /// Commit ID or branch whose environment to pull
+        #[arg(default_value = "main")]
+        commit: String,
+    },
+
     /// Show system info (Nix/Attic availability)
     Info,
 }
@@ -538,6 +1153,41 @@ enum DiffAction {
         #[arg(long)]
         json: bool,
     },
+    /// Diff the memories at the heads of two branches
+    Branches {
+        /// First branch
+        a: String,
+        /// Second branch
+        b: String,
+        /// Emit the VectorStoreDelta as JSON instead of terminal text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Diff the snapshot states of two commits or branches
+    Snapshot {
+        /// First commit ID or branch
+        a: String,
+        /// Second commit ID or branch
+        b: String,
+        /// Only report changes below this JSON pointer, e.g. `/routing`
+        #[arg(long, default_value = "")]
+        scope: String,
+        /// Emit the deltas as JSON instead of terminal text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Compare the behavior of two agent specs on their latest runs
+    Specs {
+        /// Spec digest A (64-char hex)
+        #[arg(long)]
+        a: String,
+        /// Spec digest B (64-char hex)
+        #[arg(long)]
+        b: String,
+        /// Only compare runs whose metadata tags hold the same value for this key
+        #[arg(long)]
+        input_tag: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -570,6 +1220,22 @@ enum ReleaseAction {
         /// Optional release notes
         #[arg(long)]
         notes: Option<String>,
+        /// JSON compat rule set to check before promoting
+        #[arg(long)]
+        compat_rules: Option<PathBuf>,
+        /// Tool or config fields exposed by this spec, e.g. `model,max_tokens`
+        #[arg(long = "field", value_delimiter = ',')]
+        fields: Vec<String>,
+        /// Target environment (dev, staging or production); compat rules
+        /// compare against its current release
+        #[arg(long = "env", default_value = "production")]
+        environment: aivcs_core::ReleaseEnvironment,
+        /// CAS digest of the candidate's eval report (printed by `eval run`)
+        #[arg(long)]
+        eval_report: Option<String>,
+        /// CAS storage directory (default: AIVCS_CAS_DIR, aivcs.toml, or .aivcs/cas)
+        #[arg(long)]
+        cas_dir: Option<PathBuf>,
     },
     /// Roll back the agent to the previous release (append-only history)
     Rollback {
@@ -589,9 +1255,17 @@ enum ReleaseAction {
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() {
     let cli = Cli::parse();
+    let json = cli.json;
+    if let Err(err) = run(cli).await {
+        let err = CliError::classify(&err);
+        let _ = err.report(json, &mut std::io::stderr());
+        std::process::exit(err.exit_code());
+    }
+}
 
+async fn run(cli: Cli) -> Result<()> {
     // Setup logging
     let level = if cli.verbose {
         Level::DEBUG
@@ -600,8 +1274,26 @@ async fn main() -> Result<()> {
     };
     aivcs_core::init_tracing(cli.json, level);
 
+    // Needs no repository, so don't require a database connection
+    if let Commands::Completions { shell } = cli.command {
+        cmd_completions(shell, &mut std::io::stdout());
+        return Ok(());
+    }
+
+    let config = Config::resolve(&ConfigOverrides {
+        db_url: cli.db_url.clone(),
+    })?;
+
+    let progress: Box<dyn Progress> =
+        if cli.progress && !cli.json && std::io::stderr().is_terminal() {
+            Box::new(TermProgress::stderr())
+        } else {
+            Box::new(NoProgress)
+        };
+
     // Initialize database connection
-    let handle = SurrealHandle::setup_from_env()
+    let handle = config
+        .connect()
         .await
         .context("Failed to connect to AIVCS database")?;
 
@@ -614,20 +1306,43 @@ async fn main() -> Result<()> {
             branch,
             git_sha,
             cas_dir,
+            skip_if_unchanged,
+            sign_key,
         } => {
+            let signer = sign_key
+                .as_deref()
+                .map(aivcs_core::CommitSigner::from_file)
+                .transpose()?;
             cmd_snapshot(
                 &handle,
                 &state,
                 &message,
-                &author,
+                &resolve_author_here(author.as_deref())?,
                 &branch,
                 git_sha.as_deref(),
                 cas_dir.as_deref(),
+                skip_if_unchanged,
+                signer.as_ref(),
             )
             .await
+            .map(|_| ())
         }
-        Commands::Restore { commit, output } => {
-            cmd_restore(&handle, &commit, output.as_deref()).await
+        Commands::Restore {
+            commit,
+            output,
+            exec,
+            verify,
+            cas_dir,
+        } => {
+            let cas = verify.then(|| open_cas(cas_dir.as_deref())).transpose()?;
+            cmd_restore(
+                &handle,
+                &commit,
+                output.as_deref(),
+                exec.as_deref(),
+                cas.as_ref().map(|c| c as &dyn aivcs_core::CasStore),
+            )
+            .await
         }
         Commands::ReplayArtifact {
             run,
@@ -635,22 +1350,90 @@ async fn main() -> Result<()> {
             output,
         } => cmd_replay_artifact(&run, artifacts_dir.as_deref(), output.as_deref()),
         Commands::Branch { action } => match action {
-            BranchAction::List => cmd_branch_list(&handle).await,
+            BranchAction::List => cmd_branch_list(&handle, cli.json).await,
             BranchAction::Create { name, from } => cmd_branch_create(&handle, &name, &from).await,
             BranchAction::Delete { name } => cmd_branch_delete(&handle, &name).await,
+            BranchAction::Protect {
+                name,
+                no_force_push,
+                no_delete,
+                require_linear,
+            } => {
+                let protection = BranchProtection {
+                    no_force_push,
+                    no_delete,
+                    require_linear,
+                };
+                cmd_branch_protect(&handle, &name, protection).await
+            }
         },
-        Commands::Log { reference, limit } => cmd_log(&handle, &reference, limit).await,
+        Commands::Log {
+            reference,
+            limit,
+            since,
+            until,
+            oneline,
+            graph,
+        } => {
+            let now = chrono::Utc::now();
+            let window = LogWindow {
+                since: since.map(|t| parse_log_time(&t, now)).transpose()?,
+                until: until.map(|t| parse_log_time(&t, now)).transpose()?,
+            };
+            cmd_log(&handle, &reference, limit, window, oneline, graph, cli.json).await
+        }
+        Commands::Show { commit } => cmd_show(&handle, &commit, cli.json).await,
         Commands::Merge {
+            action,
             source,
             target,
             message,
-        } => cmd_merge(&handle, &source, &target, message.as_deref()).await,
-        Commands::Diff { action } => cmd_diff(action).await,
+            no_ff,
+            dry_run,
+            author,
+        } => match action {
+            Some(MergeAction::Resolve { merge_commit }) => {
+                let stdin = std::io::stdin();
+                cmd_merge_resolve(
+                    &handle,
+                    &merge_commit,
+                    &mut stdin.lock(),
+                    &mut std::io::stdout(),
+                )
+                .await
+            }
+            None => {
+                let source = source.context("source branch is required")?;
+                if dry_run {
+                    cmd_merge_dry_run(&handle, &source, &target, no_ff, cli.json).await
+                } else {
+                    let author = resolve_author_here(author.as_deref())?;
+                    cmd_merge(
+                        &handle,
+                        &source,
+                        &target,
+                        message.as_deref(),
+                        no_ff,
+                        &author,
+                        progress.as_ref(),
+                    )
+                    .await
+                    .map(|_| ())
+                }
+            }
+        },
+        Commands::Diff { action } => cmd_diff(&handle, action).await,
         Commands::Env { action } => match action {
             EnvAction::Hash { path } => cmd_env_hash(&path).await,
-            EnvAction::LogicHash { path } => cmd_logic_hash(&path).await,
+            EnvAction::LogicHash {
+                path,
+                include_ext,
+                breakdown,
+            } => cmd_logic_hash(&path, &include_ext, breakdown, cli.json).await,
             EnvAction::CacheInfo => cmd_cache_info().await,
+            EnvAction::Check => cmd_env_check().await,
             EnvAction::IsCached { hash } => cmd_is_cached(&hash).await,
+            EnvAction::Reproduce { commit } => cmd_env_reproduce(&handle, &commit, cli.json).await,
             EnvAction::Info => cmd_env_info().await,
         },
         Commands::Release { action } => match action {
@@ -664,6 +1447,11 @@ async fn main() -> Result<()> {
                 promoted_by,
                 version,
                 notes,
+                compat_rules,
+                fields,
+                environment,
+                eval_report,
+                cas_dir,
             } => {
                 cmd_release_promote(
                     &handle,
@@ -676,6 +1464,11 @@ async fn main() -> Result<()> {
                     &promoted_by,
                     version.as_deref(),
                     notes.as_deref(),
+                    compat_rules.as_deref(),
+                    &fields,
+                    environment,
+                    eval_report.as_deref(),
+                    cas_dir.as_deref(),
                 )
                 .await
             }
@@ -687,21 +1480,245 @@ async fn main() -> Result<()> {
             parent,
             count,
             prefix,
-        } => cmd_fork(&handle, &parent, count, &prefix).await,
-        Commands::Trace { commit, depth } => cmd_trace(&handle, &commit, depth).await,
-        Commands::DiffRuns { run_a, run_b } => {
-            let ledger = SurrealRunLedger::from_env()
+        } => cmd_fork(&handle, &parent, count, &prefix, progress.as_ref()).await,
+        Commands::Trace { commit, depth } => cmd_trace(&handle, &commit, depth, cli.json).await,
+        Commands::Blame { commit, key } => cmd_blame(&handle, &commit, &key, cli.json).await,
+        Commands::VerifyCommit { commit, keys } => {
+            let trusted = match keys {
+                Some(path) => aivcs_core::TrustedKeys::from_file(&path)?,
+                None => aivcs_core::TrustedKeys::new(),
+            };
+            cmd_verify_commit(&handle, &commit, &trusted).await
+        }
+        Commands::DiffRuns {
+            run_a,
+            run_b,
+            node_paths,
+            cas_dir,
+        } => {
+            let ledger = SurrealRunLedger::from_handle(&handle);
+            let cas = open_cas(cas_dir.as_deref())?;
+            cmd_diff_runs(&ledger, &cas, &run_a, &run_b, node_paths).await
+        }
+        Commands::Run { action } => match action {
+            RunAction::Tail { run_id } => {
+                let ledger = SurrealRunLedger::from_handle(&handle);
+                cmd_run_tail(&ledger, &run_id).await
+            }
+            RunAction::CheckpointDiff { run_a, run_b, json } => {
+                let ledger = SurrealRunLedger::from_handle(&handle);
+                cmd_run_checkpoint_diff(&ledger, &run_a, &run_b, json).await
+            }
+            RunAction::Cost { run_id } => {
+                let ledger = SurrealRunLedger::from_handle(&handle);
+                cmd_run_cost(&ledger, &run_id, &config.pricing, cli.json).await
+            }
+        },
+        Commands::Cost { action } => match action {
+            CostAction::Report { agent, since } => {
+                let since = since
+                    .map(|t| parse_log_time(&t, chrono::Utc::now()))
+                    .transpose()?;
+                let ledger = SurrealRunLedger::from_handle(&handle);
+                cmd_cost_report(&ledger, agent.as_deref(), since, &config.pricing, cli.json).await
+            }
+        },
+        Commands::Memory { action } => match action {
+            MemoryAction::Dedup {
+                commit,
+                threshold,
+                dry_run,
+            } => cmd_memory_dedup(&handle, &commit, threshold, dry_run).await,
+            MemoryAction::Log { branch, key } => {
+                cmd_memory_log(&handle, &branch, &key, cli.json).await
+            }
+            MemoryAction::Provenance { key, commit } => {
+                cmd_memory_provenance(&handle, &key, &commit, cli.json).await
+            }
+            MemoryAction::Cluster { commit, threshold } => {
+                cmd_memory_cluster(&handle, &commit, threshold, cli.json).await
+            }
+            MemoryAction::Export { commit, out } => cmd_memory_export(&handle, &commit, &out).await,
+            MemoryAction::Import { commit, input } => {
+                cmd_memory_import(&handle, &commit, &input).await
+            }
+        },
+        Commands::Decisions { action } => match action {
+            DecisionsAction::List {
+                task,
+                outcome,
+                since,
+                until,
+                limit,
+            } => {
+                let now = chrono::Utc::now();
+                let filter = DecisionFilter {
+                    task,
+                    outcome: outcome.map(|o| o.as_str().to_string()),
+                    since: since.map(|t| parse_log_time(&t, now)).transpose()?,
+                    until: until.map(|t| parse_log_time(&t, now)).transpose()?,
+                    limit: Some(limit),
+                };
+                cmd_decisions_list(&handle, filter, cli.json).await
+            }
+        },
+        Commands::Stash { action } => match action {
+            StashAction::Save {
+                state,
+                name,
+                message,
+                cas_dir,
+            } => {
+                cmd_stash_save(
+                    &handle,
+                    &state,
+                    name.as_deref(),
+                    &message,
+                    cas_dir.as_deref(),
+                )
                 .await
-                .context("Failed to connect to run ledger")?;
-            cmd_diff_runs(&ledger, &run_a, &run_b).await
+            }
+            StashAction::List => cmd_stash_list(&handle).await,
+            StashAction::Pop {
+                name,
+                output,
+                cas_dir,
+            } => cmd_stash_pop(&handle, &name, output.as_deref(), cas_dir.as_deref()).await,
+        },
+        Commands::Remote { action } => {
+            let (branch, force, cas, push) = match action {
+                RemoteAction::Push { branch, force, cas } => (branch, force, cas, true),
+                RemoteAction::Pull { branch, force, cas } => (branch, force, cas, false),
+            };
+            cmd_remote_sync(&handle, &branch, force, &cas, push).await
         }
+        Commands::Bundle { action } => match action {
+            BundleAction::Export {
+                out,
+                since,
+                cas_dir,
+            } => {
+                cmd_bundle_export(
+                    &handle,
+                    &out,
+                    since.as_deref(),
+                    cas_dir.as_deref(),
+                    progress.as_ref(),
+                )
+                .await
+            }
+            BundleAction::Import { input, cas_dir } => {
+                cmd_bundle_import(&handle, &input, cas_dir.as_deref()).await
+            }
+        },
+        Commands::Cas { action } => match action {
+            CasAction::Verify { cas_dir } => cmd_cas_verify(cas_dir.as_deref()),
+            CasAction::Stats { cas_dir } => cmd_cas_stats(cas_dir.as_deref(), cli.json),
+        },
         Commands::Ci { action } => match action {
             CiAction::Run {
                 workspace,
                 stages,
                 no_cache,
                 fix,
-            } => cmd_ci_run(&workspace, &stages, no_cache, fix).await,
+                gate_rules,
+                watch,
+                cas_dir,
+                allow_toolchain_mismatch,
+            } => {
+                let ledger = SurrealRunLedger::from_handle(&handle);
+                let recorder = DecisionRecorder::with_default_config(Arc::new(handle.clone()));
+                if watch {
+                    return cmd_ci_watch(
+                        &ledger,
+                        &workspace,
+                        &stages,
+                        no_cache,
+                        fix,
+                        gate_rules.as_ref(),
+                        cas_dir.as_deref(),
+                        allow_toolchain_mismatch,
+                        Some(&recorder),
+                    )
+                    .await;
+                }
+                let verdict = cmd_ci_run(
+                    &ledger,
+                    &workspace,
+                    &stages,
+                    no_cache,
+                    fix,
+                    gate_rules.as_ref(),
+                    cas_dir.as_deref(),
+                    allow_toolchain_mismatch,
+                    Some(&recorder),
+                )
+                .await;
+                std::process::exit(gate_exit_code(verdict))
+            }
+            CiAction::Logs {
+                run_id,
+                stage,
+                cas_dir,
+            } => {
+                let ledger = SurrealRunLedger::from_handle(&handle);
+                cmd_ci_logs(&ledger, &run_id, &stage, cas_dir.as_deref()).await
+            }
+            CiAction::Repair {
+                run_id,
+                workspace,
+                apply,
+                sandbox_policy,
+                max_actions,
+                cas_dir,
+            } => {
+                cmd_ci_repair(
+                    &SurrealRunLedger::from_handle(&handle),
+                    &run_id,
+                    &workspace,
+                    apply,
+                    sandbox_policy.as_ref(),
+                    max_actions,
+                    cas_dir.as_deref(),
+                    cli.json,
+                )
+                .await
+            }
+        },
+        Commands::Eval { action } => match action {
+            EvalAction::Run {
+                suite,
+                outputs,
+                baseline,
+                seed,
+                cas_dir,
+            } => {
+                let code = cmd_eval_run(
+                    &handle,
+                    &suite,
+                    &outputs,
+                    baseline.as_ref(),
+                    seed,
+                    cas_dir.as_deref(),
+                    cli.json,
+                )
+                .await?;
+                std::process::exit(code)
+            }
+        },
+        Commands::Gate { action } => match action {
+            GateAction::Eval { events, rules } => {
+                let verdict = cmd_gate_eval(&events, &rules, cli.json);
+                std::process::exit(gate_exit_code(verdict))
+            }
+            GateAction::Regression {
+                current,
+                baseline,
+                rules,
+            } => {
+                let verdict = cmd_gate_regression(&current, &baseline, rules.as_ref(), cli.json);
+                std::process::exit(gate_exit_code(verdict))
+            }
         },
         Commands::Pr { action } => match action {
             PrAction::Open {
@@ -791,2097 +1808,107 @@ async fn main() -> Result<()> {
         }
         Commands::Infra { action } => infra::run(action).await,
         Commands::Oci { action } => oci::run(action),
+        Commands::Completions { .. } => unreachable!("completions are generated before connecting"),
     }
 }
 
-async fn cmd_report_cross_org(
-    graph_path: Option<PathBuf>,
-    _objective: &str,
-    output: Option<PathBuf>,
-) -> Result<()> {
-    use aivcs_core::{
-        multi_repo::{audit::AuditEngine, model::CrossRepoGraph},
-        reporting::{render_cross_org_audit_md, CrossOrgAuditArtifact},
-    };
-
-    let graph = if let Some(path) = graph_path {
-        let content = std::fs::read_to_string(&path)?;
-        serde_json::from_str(&content)?
-    } else {
-        // Default minimal graph for demonstration if no path provided
-        CrossRepoGraph::default()
-    };
-
-    let engine = AuditEngine::new(&graph);
-    let audit = engine.audit();
-
-    let artifact = CrossOrgAuditArtifact {
-        generated_at: chrono::Utc::now(),
-        coupling: audit.coupling,
-        critical_spofs: audit.critical_spofs,
-        health: None,
-    };
-
-    let report = render_cross_org_audit_md(&artifact);
-
-    if let Some(path) = output {
-        std::fs::write(&path, report)?;
-        println!("Report written to {:?}", path);
-    } else {
-        println!("{}", report);
-    }
-
-    Ok(())
-}
-
-struct PrOpenArgs {
-    title: String,
-    body: String,
-    head: String,
-    base: String,
-    owner: String,
-    repo: String,
-    librarian: bool,
-    require_local_ci: bool,
-}
-
-async fn cmd_pr_open(args: PrOpenArgs) -> Result<()> {
-    let PrOpenArgs {
-        title,
-        mut body,
-        head,
-        base,
-        owner,
-        repo,
-        librarian,
-        require_local_ci,
-    } = args;
-
-    let repo_root = aivcs_core::find_repo_root();
-
-    if require_local_ci {
-        aivcs_core::run_local_ci(&repo_root)?;
-    }
-
-    // Always generate snapshot and embed in body
-    let snapshot = aivcs_core::build_ci_snapshot(&repo_root)?;
-    let digest = snapshot.digest();
-    body = format!(
-        "{}\n\n<!-- aivcs-ci-snapshot: sha256:{} config-hash:{} -->",
-        body, digest, snapshot.local_ci_config_hash
-    );
-
-    let client = forge_client_from_env(owner, repo)?;
-
-    let pr_number = client
-        .open_change_request(&title, &body, &head, &base, librarian)
-        .await?;
-
-    let label = match client.host() {
-        aivcs_core::GitHost::GitHub => "PR",
-        aivcs_core::GitHost::GitLab => "MR",
-    };
-    println!("Successfully opened {label} #{pr_number}");
-
-    Ok(())
-}
-
-async fn cmd_pr_branch(name: String, base: String, owner: String, repo: String) -> Result<()> {
-    let client = forge_client_from_env(owner, repo)?;
-    let sha = client.create_branch(&name, &base).await?;
-    println!("Created branch '{}' from '{}' ({})", name, base, sha);
-    Ok(())
-}
-
-async fn read_file_for_github_commit(file: &PathBuf) -> Result<Vec<u8>> {
-    tokio::fs::read(file)
-        .await
-        .with_context(|| format!("Failed to read file for commit: {:?}", file))
-}
-
-async fn cmd_pr_commit(
-    handle: &SurrealHandle,
-    branch: String,
-    path: String,
-    message: String,
-    file: PathBuf,
-    owner: String,
-    repo: String,
-) -> Result<()> {
-    let content = read_file_for_github_commit(&file).await?;
-    let forge_repo = format!("{owner}/{repo}");
-
-    let client = forge_client_from_env(owner, repo)?;
-    let sha = client
-        .commit_file(&branch, &path, &content, &message)
-        .await?;
-    println!("Committed '{path}' to branch '{branch}' ({sha})");
-
-    let aivcs_commit_id = match handle.get_branch(&branch).await {
-        Ok(Some(b)) => Some(b.head_commit_id),
-        _ => None,
-    };
-
-    aivcs_core::maybe_emit_code_committed_from_env(
-        &branch,
-        &sha,
-        vec![path.clone()],
-        "forge-pr-commit",
-        Some(&forge_repo),
-        aivcs_commit_id.as_deref(),
-    )
-    .await;
-
-    Ok(())
-}
-
-struct PrPipelineArgs {
-    branch: String,
-    base: String,
-    path: String,
-    file: PathBuf,
-    message: String,
-    title: String,
-    body: String,
-    owner: String,
-    repo: String,
-    librarian: bool,
-    skip_branch: bool,
-    require_local_ci: bool,
-}
-
-async fn cmd_pr_pipeline(handle: &SurrealHandle, args: PrPipelineArgs) -> Result<()> {
-    let PrPipelineArgs {
-        branch,
-        base,
-        path,
-        file,
-        message,
-        title,
-        mut body,
-        owner,
-        repo,
-        librarian,
-        skip_branch,
-        require_local_ci,
-    } = args;
-
-    let repo_root = aivcs_core::find_repo_root();
-
-    if require_local_ci {
-        aivcs_core::run_local_ci(&repo_root)?;
-    }
-
-    // Always generate snapshot and embed in body
-    let snapshot = aivcs_core::build_ci_snapshot(&repo_root)?;
-    let digest = snapshot.digest();
-    body = format!(
-        "{}\n\n<!-- aivcs-ci-snapshot: sha256:{} config-hash:{} -->",
-        body, digest, snapshot.local_ci_config_hash
-    );
-
-    let forge_repo = format!("{owner}/{repo}");
-    let client = forge_client_from_env(owner.clone(), repo.clone())?;
-
-    if !skip_branch {
-        let sha = client.create_branch(&branch, &base).await?;
-        println!("Created branch '{branch}' from '{base}' ({sha})");
-    }
-
-    let content = read_file_for_github_commit(&file).await?;
-
-    let sha = client
-        .commit_file(&branch, &path, &content, &message)
-        .await?;
-    println!("Committed '{path}' to branch '{branch}' ({sha})");
-
-    let aivcs_commit_id = match handle.get_branch(&branch).await {
-        Ok(Some(b)) => Some(b.head_commit_id),
-        _ => None,
-    };
-
-    aivcs_core::maybe_emit_code_committed_from_env(
-        &branch,
-        &sha,
-        vec![path.clone()],
-        "forge-pr-pipeline",
-        Some(&forge_repo),
-        aivcs_commit_id.as_deref(),
-    )
-    .await;
-
-    let pr_number = client
-        .open_change_request(&title, &body, &branch, &base, librarian)
-        .await?;
-    let label = match client.host() {
-        aivcs_core::GitHost::GitHub => "PR",
-        aivcs_core::GitHost::GitLab => "MR",
-    };
-    println!("Successfully opened {label} #{pr_number}");
-
-    Ok(())
-}
-
-async fn cmd_pr_verify_snapshot(pr_body: Option<String>) -> Result<()> {
-    let repo_root = aivcs_core::find_repo_root();
-
-    // 1. Get PR body
-    let body = if let Some(b) = pr_body {
-        b
-    } else if let Ok(event_path) = std::env::var("GITHUB_EVENT_PATH") {
-        let content =
-            std::fs::read_to_string(event_path).context("Failed to read GITHUB_EVENT_PATH")?;
-        let event: serde_json::Value =
-            serde_json::from_str(&content).context("Failed to parse GITHUB_EVENT_PATH JSON")?;
-
-        if let Some(body_val) = event.pointer("/pull_request/body") {
-            body_val.as_str().unwrap_or_default().to_string()
-        } else {
-            anyhow::bail!("Could not find pull_request.body in GITHUB_EVENT_PATH");
-        }
-    } else {
-        anyhow::bail!("No PR body provided and GITHUB_EVENT_PATH is not set");
-    };
-
-    // 2. Extract snapshot digest from PR body
-    // Look for: <!-- aivcs-ci-snapshot: sha256:<digest> config-hash:<hash> -->
-    let pattern = "<!-- aivcs-ci-snapshot: sha256:";
-    let idx = body.find(pattern).context("No aivcs-ci-snapshot digest found in PR body. Did you open this PR using 'aivcs pr pipeline'?")?;
-    let start = idx + pattern.len();
-    let rest = &body[start..];
-    let end_idx = rest
-        .find(' ')
-        .or_else(|| rest.find("-->"))
-        .context("Malformed aivcs-ci-snapshot in PR body")?;
-    let expected_digest = rest[..end_idx].trim().to_string();
-
-    println!(
-        "Extracted snapshot digest from PR body: {}",
-        expected_digest
-    );
-
-    // 3. Recompute snapshot
-    let snapshot = aivcs_core::build_ci_snapshot(&repo_root)?;
-    let computed_digest = snapshot.digest();
-    println!("Computed snapshot digest from HEAD: {}", computed_digest);
-
-    if expected_digest == computed_digest {
-        println!("✓ Snapshot verification successful!");
-        Ok(())
-    } else {
-        println!("Expected snapshot details:");
-        println!("  repo_sha: {}", snapshot.repo_sha);
-        println!("  workspace_hash: {}", snapshot.workspace_hash);
-        println!("  local_ci_config_hash: {}", snapshot.local_ci_config_hash);
-        println!("  env_hash: {}", snapshot.env_hash);
-        anyhow::bail!("✗ Snapshot verification failed! The current HEAD state does not match the local-ci snapshot in the PR body. Did you push unstaged/untested changes without using aivcs pr pipeline?")
-    }
-}
-
-/// PRs that only change code/docs/tests without capturing an agent cognitive
-/// snapshot may declare an exempt sentinel instead of a real CommitId hash.
-/// Format: `code-only-<scope>-no-cognitive-snapshot` (see `.github/pull_request_template.md`).
-fn is_no_cognitive_snapshot_exempt(commit_id: &str) -> bool {
-    commit_id.starts_with("code-only-") && commit_id.ends_with("-no-cognitive-snapshot")
-}
-
-async fn cmd_pr_verify_reproducibility(pr_body: Option<String>) -> Result<()> {
-    let repo_root = aivcs_core::find_repo_root();
-    println!("cmd_pr_verify_reproducibility: repo_root = {:?}", repo_root);
-
-    // 1. Get PR body
-    let body = if let Some(b) = pr_body {
-        b
-    } else if let Ok(event_path) = std::env::var("GITHUB_EVENT_PATH") {
-        let content =
-            std::fs::read_to_string(event_path).context("Failed to read GITHUB_EVENT_PATH")?;
-        let event: serde_json::Value =
-            serde_json::from_str(&content).context("Failed to parse GITHUB_EVENT_PATH JSON")?;
-
-        if let Some(body_val) = event.pointer("/pull_request/body") {
-            body_val.as_str().unwrap_or_default().to_string()
-        } else {
-            anyhow::bail!("Could not find pull_request.body in GITHUB_EVENT_PATH");
-        }
-    } else {
-        anyhow::bail!("No PR body provided and GITHUB_EVENT_PATH is not set");
-    };
-
-    // 2. Extract aivcs-commit: <CommitId>
-    let commit_pattern = "aivcs-commit:";
-    let idx = body.find(commit_pattern).with_context(|| {
-        "No 'aivcs-commit' field found in PR body. Run `aivcs pr-note` and paste the output, \
-         or for code/docs-only changes without a cognitive snapshot add e.g. \
-         `aivcs-commit: code-only-docs-change-no-cognitive-snapshot` (see .github/pull_request_template.md)."
-    })?;
-    let start = idx + commit_pattern.len();
-    let rest = &body[start..];
-    let end_idx = rest.find('\n').unwrap_or(rest.len());
-    let expected_commit_id = rest[..end_idx].trim().to_string();
-
-    println!(
-        "Extracted aivcs-commit from PR body: {}",
-        expected_commit_id
-    );
-
-    if is_no_cognitive_snapshot_exempt(&expected_commit_id) {
-        println!(
-            "✓ Exempt sentinel '{expected_commit_id}' — skipping cognitive snapshot / CAS verification."
-        );
-        println!("✓ aivcs reproducibility check successful!");
-        return Ok(());
-    }
-
-    // 3. Extract Env Hash (optional / if present)
-    let env_pattern = "- **Env Hash**:";
-    let env_hash_opt = if let Some(env_idx) = body.find(env_pattern) {
-        let start = env_idx + env_pattern.len();
-        let rest = &body[start..];
-        let end_idx = rest.find('\n').unwrap_or(rest.len());
-        Some(rest[..end_idx].trim().to_string())
-    } else {
-        None
-    };
-
-    // 4. Extract State Hash (optional / if present)
-    let state_pattern = "- **State Hash**:";
-    let state_hash_opt = if let Some(state_idx) = body.find(state_pattern) {
-        let start = state_idx + state_pattern.len();
-        let rest = &body[start..];
-        let end_idx = rest.find('\n').unwrap_or(rest.len());
-        Some(rest[..end_idx].trim().to_string())
-    } else {
-        None
-    };
-
-    // 5. Extract Logic Hash (optional / if present)
-    let logic_pattern = "- **Logic Hash**:";
-    let logic_hash_opt = if let Some(logic_idx) = body.find(logic_pattern) {
-        let start = logic_idx + logic_pattern.len();
-        let rest = &body[start..];
-        let end_idx = rest.find('\n').unwrap_or(rest.len());
-        Some(rest[..end_idx].trim().to_string())
-    } else {
-        None
-    };
-
-    // 6. Validate composite CommitId match
-    if let (Some(state_hash), env_hash, logic_hash) = (
-        state_hash_opt.clone(),
-        env_hash_opt.clone(),
-        logic_hash_opt.clone(),
-    ) {
-        let computed_commit =
-            CommitId::new(logic_hash.as_deref(), &state_hash, env_hash.as_deref());
-        if computed_commit.hash != expected_commit_id {
-            anyhow::bail!(
-                "✗ Commit ID validation failed! The composite CommitId in the PR body ({}) does not match the computed hash ({}) from the listed components.",
-                expected_commit_id,
-                computed_commit.hash
-            );
-        }
-        println!("✓ Composite CommitId integrity validated.");
-    }
-
-    // 7. Verify CAS file exists locally (validates the commit data exists in repository)
-    if let Some(state_hash) = state_hash_opt {
-        let cas_path = repo_root
-            .join(".aivcs")
-            .join("cas")
-            .join("objects")
-            .join(&state_hash[..2])
-            .join(&state_hash[2..]);
-        if !cas_path.exists() {
-            anyhow::bail!(
-                "✗ CAS file not found at {:?}! Please ensure you have committed and pushed the CAS objects in '.aivcs/cas/objects/' to your PR.",
-                cas_path
-            );
-        }
-        println!("✓ CAS object verified: {:?}", cas_path);
-    }
-
-    // 8. Generate workspace Nix environment hash and verify against the Env Hash
-    let workspace_env = nix_env_manager::generate_environment_hash(&repo_root)
-        .context("Failed to generate Nix environment hash for the workspace")?;
-    println!("Computed workspace environment: {:?}", workspace_env);
-    println!(
-        "Computed workspace environment hash: {}",
-        workspace_env.hash
-    );
-
-    if let Some(ref env_hash) = env_hash_opt {
-        if env_hash != &workspace_env.hash {
-            anyhow::bail!(
-                "✗ Nix environment hash mismatch! PR body has '{}', but workspace has '{}'. Please make sure your Nix flake and lock file match the snapshot environment.",
-                env_hash,
-                workspace_env.hash
-            );
-        }
-        println!("✓ Nix environment hash matches!");
-    } else {
-        println!("⚠ No Env Hash found in PR body summary. Skipping env hash comparison.");
-    }
-
-    println!("✓ aivcs reproducibility check successful!");
-    Ok(())
+/// Write the completion script for `shell`, generated from the `Cli` definition
+fn cmd_completions(shell: clap_complete::Shell, out: &mut dyn std::io::Write) {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, out);
 }
 
-fn forge_client_from_env(owner: String, repo: String) -> Result<aivcs_core::ForgeClient> {
-    aivcs_core::ForgeClient::from_env(owner, repo)
+/// Resolve the commit author relative to the current directory
+fn resolve_author_here(explicit: Option<&str>) -> Result<String> {
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    Ok(aivcs_core::resolve_author(explicit, &cwd))
 }
 
-/// Initialize a new AIVCS repository
-async fn cmd_init(handle: &SurrealHandle, path: &PathBuf) -> Result<()> {
-    info!("Initializing AIVCS repository at {:?}", path);
-
-    // Create initial commit
-    let initial_state = serde_json::json!({
-        "initialized": true,
-        "version": env!("CARGO_PKG_VERSION"),
-    });
-
-    let commit_id = CommitId::from_state(serde_json::to_vec(&initial_state)?.as_slice());
-
-    // Save initial snapshot
-    handle.save_snapshot(&commit_id, initial_state).await?;
-
-    // Create initial commit record
-    let commit = CommitRecord::new(commit_id.clone(), vec![], "Initial commit", "system");
-    handle.save_commit(&commit).await?;
-
-    // Create main branch
-    let main_branch = BranchRecord::new("main", &commit_id.hash, true);
-    handle.save_branch(&main_branch).await?;
-
-    println!("Initialized AIVCS repository at {:?}", path);
-    println!("Initial commit: {}", commit_id.short());
-
-    Ok(())
+fn read_json_file<T: serde::de::DeserializeOwned>(path: &PathBuf) -> Result<T> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read JSON file: {:?}", path))?;
+    serde_json::from_str(&content).with_context(|| format!("Invalid JSON in {:?}", path))
 }
 
-/// Create a snapshot of agent state, linked to the current git HEAD
-async fn cmd_snapshot(
-    handle: &SurrealHandle,
-    state_path: &PathBuf,
-    message: &str,
-    author: &str,
-    branch: &str,
-    git_sha_override: Option<&str>,
-    cas_dir: Option<&std::path::Path>,
-) -> Result<()> {
-    // Read state file
-    let state_content = std::fs::read_to_string(state_path)
-        .context(format!("Failed to read state file: {:?}", state_path))?;
-
-    let state: serde_json::Value =
-        serde_json::from_str(&state_content).context("Failed to parse state as JSON")?;
-
-    // Resolve git SHA: use override, or auto-detect from cwd. Sentinel
-    // `None` means "no git context" — used by the local-print path and the
-    // A2A emit gate below. See the matching block on the emit site.
-    let cwd = std::env::current_dir().context("Failed to get current directory")?;
-    let git_sha: Option<String> = match git_sha_override {
-        Some(sha) => Some(sha.to_string()),
-        None => aivcs_core::capture_head_sha(&cwd).ok(),
-    };
-
-    // Generate logic and environment hashes for composite CommitId
-    let logic_hash = generate_logic_hash(&cwd.join("src")).ok();
-    let env_hash = generate_environment_hash(&cwd).ok();
-
-    // Store state in CAS
-    let cas_root = cas_dir
-        .map(|p| p.to_path_buf())
-        .unwrap_or_else(|| PathBuf::from(".aivcs/cas"));
-    let cas = aivcs_core::FsCasStore::new(&cas_root)
-        .map_err(|e| anyhow::anyhow!("failed to open CAS store: {e}"))?;
-    let cas_digest = aivcs_core::CasStore::put(&cas, state_content.as_bytes())
-        .map_err(|e| anyhow::anyhow!("CAS put failed: {e}"))?;
-
-    // Get parent commit from branch
-    let parent_ids = handle
-        .get_branch(branch)
-        .await?
-        .map(|b| vec![b.head_commit_id])
-        .unwrap_or_default();
-
-    // Create composite commit ID
-    let commit_id = CommitId::new(
-        logic_hash.as_deref(),
-        &cas_digest.to_hex(),
-        env_hash.as_ref().map(|h| h.hash.as_str()),
-    );
-
-    // Save snapshot to SurrealDB
-    handle.save_snapshot(&commit_id, state).await?;
-
-    // Create commit record
-    let commit = CommitRecord::new(commit_id.clone(), parent_ids.clone(), message, author);
-    handle.save_commit(&commit).await?;
-
-    // Create graph edges for all parents
-    for pid in &parent_ids {
-        handle.save_commit_graph_edge(&commit_id.hash, pid).await?;
-    }
-
-    // Update branch head
-    let branch_record = BranchRecord::new(branch, &commit_id.hash, branch == "main");
-    handle.save_branch(&branch_record).await?;
-
-    // Same A2A gate as `cmd_merge`: only emit CODE_COMMITTED when we have
-    // a real git SHA. Emitting with a placeholder hash would be a lie to
-    // any consumer that joins on `commit_sha`.
-    if let Some(sha) = &git_sha {
-        aivcs_core::maybe_emit_code_committed_from_env(
-            branch,
-            sha,
-            vec![state_path.display().to_string()],
-            author,
-            None,
-            Some(&commit_id.hash),
-        )
-        .await;
+/// Truncate a string for display (with ellipsis)
+fn truncate(s: &str, max_len: usize) -> String {
+    let truncated: String = s.chars().take(max_len).collect();
+    if s.chars().count() > max_len {
+        format!("{}...", truncated)
     } else {
-        warn!(
-            aivcs_commit_id = %commit_id.hash,
-            "skipping CODE_COMMITTED emission for snapshot: git SHA unavailable"
-        );
+        truncated
     }
-
-    info!(
-        cas_digest = %cas_digest,
-        git_sha = %git_sha.as_deref().unwrap_or("(unavailable)"),
-        "snapshot stored in CAS"
-    );
-
-    println!("[{}] {} ({})", branch, message, commit_id.short());
-    println!("Commit:    {}", commit_id);
-    println!(
-        "Git SHA:   {}",
-        git_sha.as_deref().unwrap_or("(unavailable)")
-    );
-    println!("CAS digest: {}", cas_digest);
-
-    Ok(())
 }
 
-/// Restore agent to a previous state
-async fn cmd_restore(
-    handle: &SurrealHandle,
-    reference: &str,
-    output: Option<&std::path::Path>,
-) -> Result<()> {
-    // Try to resolve reference as branch first, then as commit ID
-    let commit_hash = if let Ok(Some(branch)) = handle.get_branch(reference).await {
-        branch.head_commit_id
-    } else {
-        reference.to_string()
+/// Open the CAS store at `cas_dir`, defaulting to the configured
+/// [`Config::cas_dir`]
+fn open_cas(cas_dir: Option<&std::path::Path>) -> Result<aivcs_core::FsCasStore> {
+    let root = match cas_dir {
+        Some(dir) => dir.to_path_buf(),
+        None => Config::resolve(&ConfigOverrides::default())?.cas_dir,
     };
-
-    // Load snapshot
-    let snapshot = handle
-        .load_snapshot(&commit_hash)
-        .await
-        .context(format!("Commit not found: {}", reference))?;
-
-    let state_json = serde_json::to_string_pretty(&snapshot.state)?;
-
-    if let Some(path) = output {
-        std::fs::write(path, &state_json).context(format!("Failed to write to {:?}", path))?;
-        println!("Restored state to {:?}", path);
-    } else {
-        println!("{}", state_json);
-    }
-
-    Ok(())
+    aivcs_core::FsCasStore::new(&root).map_err(|e| anyhow::anyhow!("failed to open CAS store: {e}"))
 }
 
-/// Replay a recorded run artifact from disk.
-///
-/// Expected layout:
-/// - `<artifacts_dir>/<run_id>/output.json`
-/// - `<artifacts_dir>/<run_id>/output.digest`
-fn cmd_replay_artifact(
-    run_id: &str,
-    artifacts_dir: Option<&std::path::Path>,
-    output: Option<&std::path::Path>,
-) -> Result<()> {
-    let root = artifacts_dir
-        .map(|p| p.to_path_buf())
-        .unwrap_or_else(|| PathBuf::from(".aivcs/runs"));
-    let run_dir = root.join(run_id);
-    let output_path = run_dir.join("output.json");
-    let digest_path = run_dir.join("output.digest");
-
-    if !output_path.exists() {
-        anyhow::bail!("Recorded artifact not found: {:?}", output_path);
-    }
-    if !digest_path.exists() {
-        anyhow::bail!("Recorded digest not found: {:?}", digest_path);
-    }
-
-    let artifact_bytes = std::fs::read(&output_path)
-        .with_context(|| format!("Failed to read recorded artifact: {:?}", output_path))?;
-
-    // Validate artifact is JSON for replayability.
-    let _: serde_json::Value = serde_json::from_slice(&artifact_bytes)
-        .with_context(|| format!("Recorded artifact is not valid JSON: {:?}", output_path))?;
-
-    let expected_digest = std::fs::read_to_string(&digest_path)
-        .with_context(|| format!("Failed to read recorded digest: {:?}", digest_path))?
-        .trim()
-        .to_string();
-
-    let actual_digest = aivcs_core::Digest::compute(&artifact_bytes).to_hex();
-    if actual_digest != expected_digest {
-        anyhow::bail!(
-            "Replay digest mismatch for run {}: expected {}, got {}",
-            run_id,
-            expected_digest,
-            actual_digest
-        );
-    }
-
-    if let Some(path) = output {
-        std::fs::write(path, &artifact_bytes)
-            .with_context(|| format!("Failed to write replay output to {:?}", path))?;
-        println!("Replayed run {} to {:?}", run_id, path);
-    } else {
-        println!("{}", String::from_utf8_lossy(&artifact_bytes));
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    println!("Replay digest verified: {}", actual_digest);
-    Ok(())
-}
+    #[tokio::test]
+    async fn test_unreachable_db_error_has_code_and_hint() {
+        // Nothing listens on port 1
+        let config = Config {
+            db_url: Some("ws://127.0.0.1:1".to_string()),
+            namespace: "aivcs".to_string(),
+            database: "main".to_string(),
+            cas_dir: PathBuf::from(".aivcs/cas"),
+            pricing: Default::default(),
+            file: None,
+        };
 
-/// List all branches
-async fn cmd_branch_list(handle: &SurrealHandle) -> Result<()> {
-    let branches = handle.list_branches().await?;
+        let err = config
+            .connect()
+            .await
+            .context("Failed to connect to AIVCS database")
+            .unwrap_err();
+        let err = CliError::classify(&err);
 
-    if branches.is_empty() {
-        println!("No branches found. Run 'agent-git init' first.");
-        return Ok(());
-    }
+        assert_eq!(err.code(), "db_unreachable");
+        assert_eq!(err.exit_code(), error::EXIT_DB_UNREACHABLE);
+        assert!(err.hint().unwrap().contains("--db-url"));
 
-    for branch in branches {
-        let prefix = if branch.is_default { "* " } else { "  " };
-        println!(
-            "{}{} -> {}",
-            prefix,
-            branch.name,
-            truncate_id(&branch.head_commit_id, 8)
-        );
-    }
-
-    Ok(())
-}
-
-/// Create a new branch
-async fn cmd_branch_create(handle: &SurrealHandle, name: &str, from: &str) -> Result<()> {
-    // Resolve starting point
-    let head_commit = if let Ok(Some(branch)) = handle.get_branch(from).await {
-        branch.head_commit_id
-    } else {
-        from.to_string()
-    };
-
-    // Create branch
-    let branch = BranchRecord::new(name, &head_commit, false);
-    handle.save_branch(&branch).await?;
-
-    println!(
-        "Created branch '{}' at {}",
-        name,
-        truncate_id(&head_commit, 8)
-    );
-
-    Ok(())
-}
-
-/// Delete a branch
-async fn cmd_branch_delete(handle: &SurrealHandle, name: &str) -> Result<()> {
-    handle
-        .delete_branch(name)
-        .await
-        .context(format!("Failed to delete branch '{}'", name))?;
-
-    println!("Deleted branch '{}'", name);
-
-    Ok(())
-}
-
-/// Show commit history
-async fn cmd_log(handle: &SurrealHandle, reference: &str, limit: usize) -> Result<()> {
-    // Resolve reference
-    let start_commit = if let Ok(Some(branch)) = handle.get_branch(reference).await {
-        branch.head_commit_id
-    } else {
-        reference.to_string()
-    };
-
-    let history = handle.get_commit_history(&start_commit, limit).await?;
-
-    if history.is_empty() {
-        println!("No commits found for '{}'", reference);
-        return Ok(());
-    }
-
-    for commit in history {
-        println!("commit {}", commit.commit_id);
-        println!("Author: {}", commit.author);
-        println!(
-            "Date:   {}",
-            commit.created_at.format("%Y-%m-%d %H:%M:%S UTC")
-        );
-        println!();
-        println!("    {}", commit.message);
-        println!();
-    }
-
-    Ok(())
-}
-
-/// Merge two branches
-async fn cmd_merge(
-    handle: &SurrealHandle,
-    source: &str,
-    target: &str,
-    message: Option<&str>,
-) -> Result<()> {
-    // Resolve branch heads
-    let source_commit = handle
-        .get_branch_head(source)
-        .await
-        .context(format!("Source branch not found: {}", source))?;
-
-    let target_commit = handle
-        .get_branch_head(target)
-        .await
-        .context(format!("Target branch not found: {}", target))?;
-
-    let merge_message = message
-        .map(String::from)
-        .unwrap_or_else(|| format!("Merge branch '{}' into '{}'", source, target));
-
-    // Perform semantic merge
-    let result = semantic_rag_merge::semantic_merge(
-        handle,
-        &source_commit,
-        &target_commit,
-        &merge_message,
-        "agent-git",
-    )
-    .await?;
-
-    // Update target branch head
-    let branch = BranchRecord::new(target, &result.merge_commit_id.hash, target == "main");
-    handle.save_branch(&branch).await?;
-
-    // CODE_COMMITTED carries a `commit_sha` (git) AND an optional
-    // `aivcs_commit_id` (this merge's aivcs hash). The git side has no
-    // meaningful value if git isn't available, and emitting a 40-char
-    // string of zeros is indistinguishable from a real SHA to downstream
-    // consumers — strictly worse than not emitting at all. Skip the A2A
-    // side-effect and log; the aivcs merge itself is already durable.
-    let cwd = std::env::current_dir().context("Failed to get current directory")?;
-    match aivcs_core::capture_head_sha(&cwd) {
-        Ok(git_sha) => {
-            aivcs_core::maybe_emit_code_committed_from_env(
-                target,
-                &git_sha,
-                Vec::new(),
-                "agent-git",
-                None,
-                Some(&result.merge_commit_id.hash),
-            )
-            .await;
-        }
-        Err(e) => {
-            warn!(
-                aivcs_commit_id = %result.merge_commit_id.hash,
-                error = %e,
-                "skipping CODE_COMMITTED emission for merge: git SHA unavailable"
-            );
-        }
-    }
-
-    println!("Merge complete: {}", result.merge_commit_id.short());
-    println!("{}", result.summary);
-
-    if !result.manual_conflicts.is_empty() {
-        println!("\nUnresolved conflicts:");
-        for conflict in &result.manual_conflicts {
-            println!("  - {}", conflict.key);
-        }
-    }
-
-    Ok(())
-}
-
-#[derive(Debug, Clone, Serialize, PartialEq)]
-struct SpecDiffOutput {
-    changed_paths: Vec<String>,
-    only_in_a: Vec<String>,
-    only_in_b: Vec<String>,
-}
-
-#[derive(Debug, Clone, Serialize, PartialEq)]
-struct RunDiffOutput {
-    events_a: usize,
-    events_b: usize,
-    tool_call_changes: usize,
-    added: usize,
-    removed: usize,
-    reordered: usize,
-    param_changed: usize,
-}
-
-async fn cmd_diff(action: DiffAction) -> Result<()> {
-    match action {
-        DiffAction::Spec { a, b, json } => cmd_diff_spec(&a, &b, json),
-        DiffAction::Run { a, b, json } => cmd_diff_run(&a, &b, json),
-    }
-}
-
-fn cmd_diff_spec(a: &PathBuf, b: &PathBuf, json: bool) -> Result<()> {
-    let left: Value = read_json_file(a)?;
-    let right: Value = read_json_file(b)?;
-    let diff = build_spec_diff(&left, &right);
-
-    if json {
-        println!("{}", serde_json::to_string_pretty(&diff)?);
-    } else {
-        println!("{}", render_spec_diff_text(&diff));
-    }
-    Ok(())
-}
-
-fn cmd_diff_run(a: &PathBuf, b: &PathBuf, json: bool) -> Result<()> {
-    let left: Vec<RunEvent> = read_json_file(a)?;
-    let right: Vec<RunEvent> = read_json_file(b)?;
-    let diff = build_run_diff(&left, &right);
-
-    if json {
-        println!("{}", serde_json::to_string_pretty(&diff)?);
-    } else {
-        println!("{}", render_run_diff_text(&diff));
-    }
-    Ok(())
-}
-
-fn read_json_file<T: serde::de::DeserializeOwned>(path: &PathBuf) -> Result<T> {
-    let content = std::fs::read_to_string(path)
-        .with_context(|| format!("Failed to read JSON file: {:?}", path))?;
-    serde_json::from_str(&content).with_context(|| format!("Invalid JSON in {:?}", path))
-}
-
-fn collect_leaf_paths(prefix: &str, value: &Value, out: &mut BTreeMap<String, Value>) {
-    if let Some(obj) = value.as_object() {
-        for (k, v) in obj {
-            let next = if prefix.is_empty() {
-                format!("/{}", k.replace('~', "~0").replace('/', "~1"))
-            } else {
-                format!("{}/{}", prefix, k.replace('~', "~0").replace('/', "~1"))
-            };
-            collect_leaf_paths(&next, v, out);
-        }
-        return;
-    }
-
-    if let Some(arr) = value.as_array() {
-        for (idx, v) in arr.iter().enumerate() {
-            let next = if prefix.is_empty() {
-                format!("/{}", idx)
-            } else {
-                format!("{}/{}", prefix, idx)
-            };
-            collect_leaf_paths(&next, v, out);
-        }
-        return;
-    }
-
-    let path = if prefix.is_empty() {
-        "/".to_string()
-    } else {
-        prefix.to_string()
-    };
-    out.insert(path, value.clone());
-}
-
-fn build_spec_diff(a: &Value, b: &Value) -> SpecDiffOutput {
-    let mut left = BTreeMap::new();
-    let mut right = BTreeMap::new();
-    collect_leaf_paths("", a, &mut left);
-    collect_leaf_paths("", b, &mut right);
-
-    let mut changed_paths = Vec::new();
-    let mut only_in_a = Vec::new();
-    let mut only_in_b = Vec::new();
-
-    for (path, val_a) in &left {
-        match right.get(path) {
-            Some(val_b) if val_a != val_b => changed_paths.push(path.clone()),
-            None => only_in_a.push(path.clone()),
-            _ => {}
-        }
-    }
-
-    for path in right.keys() {
-        if !left.contains_key(path) {
-            only_in_b.push(path.clone());
-        }
-    }
-
-    SpecDiffOutput {
-        changed_paths,
-        only_in_a,
-        only_in_b,
-    }
-}
-
-fn build_run_diff(a: &[RunEvent], b: &[RunEvent]) -> RunDiffOutput {
-    let tool_diff = diff_tool_calls(a, b);
-    let mut added = 0usize;
-    let mut removed = 0usize;
-    let mut reordered = 0usize;
-    let mut param_changed = 0usize;
-
-    for change in &tool_diff.changes {
-        match change {
-            ToolCallChange::Added(_) => added += 1,
-            ToolCallChange::Removed(_) => removed += 1,
-            ToolCallChange::Reordered { .. } => reordered += 1,
-            ToolCallChange::ParamChanged { .. } => param_changed += 1,
-        }
-    }
-
-    RunDiffOutput {
-        events_a: a.len(),
-        events_b: b.len(),
-        tool_call_changes: tool_diff.changes.len(),
-        added,
-        removed,
-        reordered,
-        param_changed,
-    }
-}
-
-fn render_spec_diff_text(diff: &SpecDiffOutput) -> String {
-    let mut out = String::new();
-    out.push_str("Spec Diff\n");
-    out.push_str("=========\n");
-    out.push_str(&format!("changed_paths: {}\n", diff.changed_paths.len()));
-    out.push_str(&format!("only_in_a: {}\n", diff.only_in_a.len()));
-    out.push_str(&format!("only_in_b: {}\n", diff.only_in_b.len()));
-
-    if !diff.changed_paths.is_empty() {
-        out.push_str("\nChanged:\n");
-        for p in &diff.changed_paths {
-            out.push_str(&format!("  ~ {}\n", p));
-        }
-    }
-    if !diff.only_in_a.is_empty() {
-        out.push_str("\nOnly in A:\n");
-        for p in &diff.only_in_a {
-            out.push_str(&format!("  - {}\n", p));
-        }
-    }
-    if !diff.only_in_b.is_empty() {
-        out.push_str("\nOnly in B:\n");
-        for p in &diff.only_in_b {
-            out.push_str(&format!("  + {}\n", p));
-        }
-    }
-
-    out.trim_end().to_string()
-}
-
-fn render_run_diff_text(diff: &RunDiffOutput) -> String {
-    format!(
-        "Run Diff\n========\nevents_a: {}\nevents_b: {}\ntool_call_changes: {}\n  added: {}\n  removed: {}\n  reordered: {}\n  param_changed: {}",
-        diff.events_a,
-        diff.events_b,
-        diff.tool_call_changes,
-        diff.added,
-        diff.removed,
-        diff.reordered,
-        diff.param_changed
-    )
-}
-
-/// Truncate a string for display (with ellipsis)
-fn truncate(s: &str, max_len: usize) -> String {
-    let truncated: String = s.chars().take(max_len).collect();
-    if s.chars().count() > max_len {
-        format!("{}...", truncated)
-    } else {
-        truncated
-    }
-}
-
-/// Truncate an ID/hash for display (no ellipsis)
-fn truncate_id(s: &str, max_len: usize) -> String {
-    s.chars().take(max_len).collect()
-}
-
-// ========== Environment Commands (Phase 2) ==========
-
-/// Generate and display environment hash
-async fn cmd_env_hash(path: &PathBuf) -> Result<()> {
-    let hash = generate_environment_hash(path).context(format!(
-        "Failed to generate environment hash for {:?}",
-        path
-    ))?;
-
-    println!("Environment Hash: {}", hash.hash);
-    println!("Source: {:?}", hash.source);
-    println!("Short: {}", hash.short());
-
-    Ok(())
-}
-
-/// Generate and display logic hash (Rust source)
-async fn cmd_logic_hash(path: &PathBuf) -> Result<()> {
-    let hash = generate_logic_hash(path)
-        .context(format!("Failed to generate logic hash for {:?}", path))?;
-
-    println!("Logic Hash: {}", hash);
-    println!("Short: {}", truncate_id(&hash, 12));
-
-    Ok(())
-}
-
-/// Show Attic cache information
-async fn cmd_cache_info() -> Result<()> {
-    let client = AtticClient::from_env();
-    let info = client
-        .get_cache_info()
-        .await
-        .context("Failed to get cache info")?;
-
-    println!("Cache Name: {}", info.name);
-    println!("Server: {}", info.server);
-    println!("Available: {}", if info.available { "yes" } else { "no" });
-
-    if let Some(details) = info.info {
-        println!("\nDetails:");
-        println!("{}", details);
-    }
-
-    Ok(())
-}
-
-/// Check if environment is cached
-async fn cmd_is_cached(hash: &str) -> Result<()> {
-    let client = AtticClient::from_env();
-    let nix_hash = NixHash::new(hash.to_string(), nix_env_manager::HashSource::FlakeLock);
-
-    let cached = client.is_environment_cached(&nix_hash).await;
-
-    if cached {
-        println!("Environment {} is CACHED", truncate_id(hash, 12));
-    } else {
-        println!("Environment {} is NOT cached", truncate_id(hash, 12));
-    }
-
-    Ok(())
-}
-
-/// Show environment system info
-async fn cmd_env_info() -> Result<()> {
-    use aivcs_core::domain::EnvValidation;
-
-    let validation = EnvValidation::check();
-
-    println!("AIVCS Environment Info");
-    println!("======================");
-    println!();
-    println!("Platform: {}", validation.platform);
-    println!(
-        "Nix shell: {}",
-        if validation.is_nix_shell { "yes" } else { "no" }
-    );
-    println!();
-
-    // Nix availability
-    let nix = is_nix_available();
-    println!("Nix installed: {}", if nix { "yes" } else { "no" });
-
-    if nix {
-        // Get Nix version
-        if let Ok(output) = std::process::Command::new("nix").arg("--version").output() {
-            if output.status.success() {
-                let version = String::from_utf8_lossy(&output.stdout);
-                println!("Nix version: {}", version.trim());
-            }
-        }
-    }
-
-    // Attic availability
-    let attic = is_attic_available();
-    println!("Attic installed: {}", if attic { "yes" } else { "no" });
-
-    if attic {
-        if let Ok(output) = std::process::Command::new("attic")
-            .arg("--version")
-            .output()
-        {
-            if output.status.success() {
-                let version = String::from_utf8_lossy(&output.stdout);
-                println!("Attic version: {}", version.trim());
-            }
-        }
-    }
-
-    println!();
-
-    // Environment variables
-    println!("Environment Variables:");
-    if let Ok(server) = std::env::var("ATTIC_SERVER") {
-        println!("  ATTIC_SERVER: {}", server);
-    } else {
-        println!("  ATTIC_SERVER: (not set)");
-    }
-    if let Ok(cache) = std::env::var("ATTIC_CACHE") {
-        println!("  ATTIC_CACHE: {}", cache);
-    } else {
-        println!("  ATTIC_CACHE: (not set)");
-    }
-    if std::env::var("ATTIC_TOKEN").is_ok() {
-        println!("  ATTIC_TOKEN: (set)");
-    } else {
-        println!("  ATTIC_TOKEN: (not set)");
-    }
-    if let Some(tmp) = &validation.tmpdir {
-        println!("  TMPDIR: {}", tmp);
-    }
-
-    let tips = validation.recommendations();
-    if !tips.is_empty() {
-        println!();
-        println!("Recommendations:");
-        for tip in tips {
-            println!("  - {}", tip);
-        }
-    }
-
-    Ok(())
-}
-
-// ========== Release Registry Commands (Phase 4) ==========
-
-#[allow(clippy::too_many_arguments)]
-async fn cmd_release_promote(
-    handle: &SurrealHandle,
-    name: &str,
-    git_sha: &str,
-    graph_digest: &str,
-    prompts_digest: &str,
-    tools_digest: &str,
-    config_digest: &str,
-    promoted_by: &str,
-    version: Option<&str>,
-    notes: Option<&str>,
-) -> Result<()> {
-    let spec = aivcs_core::AgentSpec::new(
-        git_sha.to_string(),
-        graph_digest.to_string(),
-        prompts_digest.to_string(),
-        tools_digest.to_string(),
-        config_digest.to_string(),
-    )
-    .context("failed to build AgentSpec")?;
-
-    let registry = SurrealDbReleaseRegistry::new(Arc::new(handle.clone()));
-    let api = aivcs_core::ReleaseRegistryApi::new(registry);
-
-    let release = api
-        .promote(
-            name,
-            &spec,
-            promoted_by,
-            version.map(ToString::to_string),
-            notes.map(ToString::to_string),
-        )
-        .await
-        .context("promote failed")?;
-
-    println!(
-        "Promoted {} -> {}",
-        release.name,
-        release.spec_digest.as_str()
-    );
-    Ok(())
-}
-
-async fn cmd_release_rollback(handle: &SurrealHandle, name: &str) -> Result<()> {
-    let registry = SurrealDbReleaseRegistry::new(Arc::new(handle.clone()));
-    let release = registry.rollback(name).await?;
-    println!(
-        "Rolled back {} -> {}",
-        release.name,
-        release.spec_digest.as_str()
-    );
-    Ok(())
-}
-
-async fn cmd_release_current(handle: &SurrealHandle, name: &str) -> Result<()> {
-    let registry = SurrealDbReleaseRegistry::new(Arc::new(handle.clone()));
-    let current = registry.current(name).await?;
-    match current {
-        Some(release) => {
-            println!(
-                "Current {} -> {}",
-                release.name,
-                release.spec_digest.as_str()
-            );
-        }
-        None => println!("No release found for {}", name),
-    }
-    Ok(())
-}
-
-async fn cmd_release_history(handle: &SurrealHandle, name: &str) -> Result<()> {
-    let registry = SurrealDbReleaseRegistry::new(Arc::new(handle.clone()));
-    let history = registry.history(name).await?;
-
-    if history.is_empty() {
-        println!("No release history for {}", name);
-        return Ok(());
-    }
-
-    for release in history {
-        println!(
-            "{} {} {}",
-            release.created_at.to_rfc3339(),
-            release.name,
-            release.spec_digest.as_str()
-        );
-    }
-    Ok(())
-}
-
-// ========== Parallel Simulation Commands (Phase 4) ==========
-
-/// Fork multiple parallel branches for exploration
-async fn cmd_fork(handle: &SurrealHandle, parent: &str, count: u8, prefix: &str) -> Result<()> {
-    // Resolve parent reference (branch name or commit ID)
-    let parent_commit = if let Ok(Some(branch)) = handle.get_branch(parent).await {
-        branch.head_commit_id
-    } else {
-        parent.to_string()
-    };
-
-    println!(
-        "Forking {} branches from {} with prefix '{}'",
-        count,
-        truncate_id(&parent_commit, 8),
-        prefix
-    );
-
-    let handle_arc = Arc::new(handle.clone());
-
-    let result = fork_agent_parallel(handle_arc, &parent_commit, count, prefix).await?;
-
-    println!("\nCreated {} parallel branches:", result.branches.len());
-    for (i, branch) in result.branches.iter().enumerate() {
-        println!("  {} -> {}", branch, result.commit_ids[i].short());
-    }
-
-    println!("\nUse 'aivcs branch list' to see all branches");
-    println!("Use 'aivcs trace <commit>' to debug a branch's reasoning");
-
-    Ok(())
-}
-
-/// Show reasoning trace for time-travel debugging
-async fn cmd_trace(handle: &SurrealHandle, reference: &str, depth: usize) -> Result<()> {
-    // Resolve reference
-    let commit_hash = if let Ok(Some(branch)) = handle.get_branch(reference).await {
-        branch.head_commit_id
-    } else {
-        reference.to_string()
-    };
-
-    println!("Reasoning Trace for {}", truncate_id(&commit_hash, 12));
-    println!("=========================================\n");
-
-    // Get commit history (limited by depth)
-    let history = handle.get_commit_history(&commit_hash, depth).await?;
-
-    if history.is_empty() {
-        println!("No commits found for '{}'", reference);
-        return Ok(());
-    }
-
-    // Load snapshots and display trace
-    for (i, commit) in history.iter().enumerate() {
-        let step_marker = if i == 0 { "HEAD" } else { &format!("~{}", i) };
-
-        println!(
-            "[{}] {} - {}",
-            step_marker,
-            commit.commit_id.short(),
-            commit.message
-        );
-        println!(
-            "    Author: {} | {}",
-            commit.author,
-            commit.created_at.format("%Y-%m-%d %H:%M:%S")
-        );
-
-        // Try to load and display state summary
-        if let Ok(snapshot) = handle.load_snapshot(&commit.commit_id.hash).await {
-            if let Some(obj) = snapshot.state.as_object() {
-                // Show key state fields
-                let keys: Vec<_> = obj.keys().take(5).collect();
-                for key in keys {
-                    if let Some(value) = obj.get(key) {
-                        let value_str = match value {
-                            serde_json::Value::String(s) => truncate(s, 40),
-                            serde_json::Value::Number(n) => n.to_string(),
-                            serde_json::Value::Bool(b) => b.to_string(),
-                            _ => format!("{}", value).chars().take(40).collect(),
-                        };
-                        println!("    {}: {}", key, value_str);
-                    }
-                }
-            }
-        }
-        println!();
-    }
-
-    println!(
-        "Showing {} of {} commits (use --depth to see more)",
-        history.len(),
-        depth
-    );
-
-    Ok(())
-}
-
-/// Diff the tool-call sequences of two runs
-async fn cmd_diff_runs(ledger: &dyn RunLedger, id_a: &str, id_b: &str) -> Result<()> {
-    let (events_a, summary_a) = aivcs_core::replay_run(ledger, id_a)
-        .await
-        .with_context(|| format!("replay failed for run: {}", id_a))?;
-    let (events_b, summary_b) = aivcs_core::replay_run(ledger, id_b)
-        .await
-        .with_context(|| format!("replay failed for run: {}", id_b))?;
-
-    let diff = diff_tool_calls(&events_a, &events_b);
-
-    println!("A: {} ({})", summary_a.run_id, summary_a.agent_name);
-    println!("B: {} ({})", summary_b.run_id, summary_b.agent_name);
-    println!();
-
-    if diff.is_empty() {
-        println!("Tool-call sequences are identical.");
-        return Ok(());
-    }
-
-    for change in &diff.changes {
-        match change {
-            ToolCallChange::Added(call) => {
-                println!("  + [{}] {}", call.seq, call.tool_name);
-            }
-            ToolCallChange::Removed(call) => {
-                println!("  - [{}] {}", call.seq, call.tool_name);
-            }
-            ToolCallChange::Reordered {
-                call,
-                from_index,
-                to_index,
-            } => {
-                println!(
-                    "  ~ {} (pos {} -> {})",
-                    call.tool_name, from_index, to_index
-                );
-            }
-            ToolCallChange::ParamChanged {
-                tool_name,
-                seq_a,
-                seq_b,
-                deltas,
-            } => {
-                println!("  Δ {} (A:[{}] / B:[{}])", tool_name, seq_a, seq_b);
-                for d in deltas {
-                    println!("      {} : {} -> {}", d.key, d.before, d.after);
-                }
-            }
-        }
-    }
-
-    println!("\nChanges: {}", diff.changes.len());
-    Ok(())
-}
-
-/// Run CI stages and record execution
-async fn cmd_ci_run(
-    workspace: &PathBuf,
-    stages_str: &str,
-    no_cache: bool,
-    fix: bool,
-) -> Result<()> {
-    if no_cache {
-        eprintln!("warning: --no-cache is not yet implemented; proceeding without caching changes");
-    }
-    if fix {
-        eprintln!("warning: --fix is not yet implemented; stages will run in check-only mode");
-    }
-
-    // Get git SHA
-    let git_sha = if let Ok(output) = std::process::Command::new("git")
-        .args(["rev-parse", "HEAD"])
-        .output()
-    {
-        String::from_utf8(output.stdout)
-            .unwrap_or_default()
-            .trim()
-            .to_string()
-    } else {
-        "unknown".to_string()
-    };
-
-    // Get toolchain hash
-    let toolchain_hash = if let Ok(output) = std::process::Command::new("rustup")
-        .args(["show", "active-toolchain"])
-        .output()
-    {
-        String::from_utf8(output.stdout)
-            .unwrap_or_default()
-            .trim()
-            .to_string()
-    } else {
-        "unknown".to_string()
-    };
-
-    // Parse stages
-    let stage_names: Vec<String> = stages_str
-        .split(',')
-        .map(|s| s.trim().to_lowercase())
-        .collect();
-
-    let mut stage_configs = Vec::new();
-    for stage_name in &stage_names {
-        let config = match stage_name.as_str() {
-            "fmt" => StageConfig::from_builtin(BuiltinStage::CargoFmt, 300),
-            "check" => StageConfig::from_builtin(BuiltinStage::CargoCheck, 300),
-            "clippy" => StageConfig::from_builtin(BuiltinStage::CargoClippy, 600),
-            "test" => StageConfig::from_builtin(BuiltinStage::CargoTest, 1200),
-            _ => anyhow::bail!("Unknown stage: {}", stage_name),
-        };
-        stage_configs.push(config);
-    }
-
-    // Create CI spec
-    let ci_spec = CiSpec::new(
-        workspace.clone(),
-        &stage_names,
-        git_sha.clone(),
-        toolchain_hash.clone(),
-    );
-
-    println!("Running CI pipeline for workspace: {:?}", workspace);
-    println!("Stages: {}", stages_str);
-    println!("Git SHA: {}", git_sha);
-    println!();
-
-    // Run pipeline
-    let ledger_arc = std::sync::Arc::new(oxidized_state::SurrealRunLedger::from_env().await?);
-    let result = CiPipeline::run(ledger_arc.clone(), &ci_spec, stage_configs)
-        .await
-        .context("CI pipeline failed to run")?;
-
-    // Print results
-    println!("Run ID: {}", result.run_id);
-    println!(
-        "Status: {}",
-        if result.success {
-            "✓ PASSED"
-        } else {
-            "✗ FAILED"
-        }
-    );
-    println!("Duration: {}ms", result.duration_ms);
-    println!();
-
-    for stage_result in &result.stages {
-        let status = if stage_result.passed() { "✓" } else { "✗" };
-        println!(
-            "  {} {} ({}ms, exit code: {})",
-            status, stage_result.stage_name, stage_result.duration_ms, stage_result.exit_code
-        );
-    }
-
-    println!();
-    println!(
-        "Summary: {}/{} stages passed",
-        result.passed_count(),
-        result.stages.len()
-    );
-
-    // Evaluate gate
-    let events = ledger_arc
-        .get_events(&oxidized_state::RunId(result.run_id.clone()))
-        .await?;
-
-    let verdict = CiGate::evaluate(&events);
-    println!(
-        "Gate: {}",
-        if verdict.passed {
-            "✓ PASSED"
-        } else {
-            "✗ FAILED"
-        }
-    );
-
-    if !verdict.violations.is_empty() {
-        println!("Violations:");
-        for violation in &verdict.violations {
-            println!("  - {}", violation);
-        }
-    }
-
-    if result.success && verdict.passed {
-        println!("\n✓ All checks passed!");
-        Ok(())
-    } else {
-        anyhow::bail!("CI checks failed")
-    }
-}
-
-async fn cmd_pr_note(handle: &SurrealHandle, branch_name_opt: Option<&str>) -> Result<()> {
-    let branch_name = match branch_name_opt {
-        Some(name) => name.to_string(),
-        None => {
-            let cwd = std::env::current_dir().context("Failed to get current directory")?;
-            aivcs_core::detect_current_branch(&cwd)
-                .context("Failed to auto-detect current git branch")?
-        }
-    };
-
-    let commit = resolve_branch_commit(handle, &branch_name).await?;
-
-    // Format output matching acceptance criteria.
-    //
-    // The `<!-- aivcs-linkage -->` HTML comment is a *visible section
-    // header* for the metadata that follows. The lines underneath
-    // (`aivcs-commit:`, `intent-id:`) are human-readable plain text and
-    // are the durable index into the aivcs ledger — the comment marker
-    // is just an anchor that reviewers (and the Phase 1 verifier) can
-    // locate without parsing markdown headings. This is a different
-    // pattern from `<!-- aivcs-ci-snapshot: sha256:... -->` over in
-    // `ci_snapshot`, where the comment itself buries an opaque
-    // workspace digest as the payload. Here the comment is metadata
-    // *about* the section, not the metadata payload.
-    // See stevedores-org/aivcs#231 (M2).
-    println!("<!-- aivcs-linkage -->");
-    println!("aivcs-commit: {}", commit.commit_id.hash);
-    if let Some(intent_id) = read_objective_id() {
-        println!("intent-id: {}", intent_id);
-    }
-    println!();
-    println!("### AIVCS Commit Summary");
-    println!("- **Message**: {}", commit.message);
-    println!("- **Author**: {}", commit.author);
-    println!("- **Created**: {}", commit.created_at);
-    println!("- **State Hash**: {}", commit.commit_id.state_hash);
-    if let Some(logic) = &commit.commit_id.logic_hash {
-        println!("- **Logic Hash**: {}", logic);
-    }
-    if let Some(env) = &commit.commit_id.env_hash {
-        println!("- **Env Hash**: {}", env);
-    }
-
-    Ok(())
-}
-
-async fn resolve_branch_commit(handle: &SurrealHandle, branch_name: &str) -> Result<CommitRecord> {
-    let branch = handle
-        .get_branch(branch_name)
-        .await?
-        .ok_or_else(|| anyhow::anyhow!("Branch '{}' not found in AIVCS database", branch_name))?;
-
-    handle
-        .get_commit(&branch.head_commit_id)
-        .await?
-        .ok_or_else(|| {
-            anyhow::anyhow!(
-                "Commit '{}' not found in AIVCS database",
-                branch.head_commit_id
-            )
-        })
-}
-
-async fn cmd_pr_semantic_summary(
-    handle: &SurrealHandle,
-    branch_name_opt: Option<&str>,
-    base_name: &str,
-) -> Result<()> {
-    use aivcs_core::{diff_graph_snapshots, extract_graph_snapshot, format_semantic_diff_markdown};
-
-    let branch_name = match branch_name_opt {
-        Some(name) => name.to_string(),
-        None => {
-            let cwd = std::env::current_dir().context("Failed to get current directory")?;
-            aivcs_core::detect_current_branch(&cwd)
-                .context("Failed to auto-detect current git branch")?
-        }
-    };
-
-    let _head_commit = resolve_branch_commit(handle, &branch_name).await?;
-    let _base_commit = resolve_branch_commit(handle, base_name).await?;
-
-    cmd_pr_note(handle, Some(&branch_name)).await?;
-
-    let head_snapshot = handle
-        .load_snapshot(&_head_commit.commit_id.hash)
-        .await
-        .context("Failed to load head branch snapshot")?;
-    let base_snapshot = handle
-        .load_snapshot(&_base_commit.commit_id.hash)
-        .await
-        .context("Failed to load base branch snapshot")?;
-
-    let head_graph = extract_graph_snapshot(&head_snapshot.state);
-    let base_graph = extract_graph_snapshot(&base_snapshot.state);
-
-    println!();
-    println!("### Semantic diff (`{base_name}` → `{branch_name}`)");
-    println!();
-
-    if !head_graph.has_semantic_content() && !base_graph.has_semantic_content() {
-        println!("_No semantic delta — only code/infra changes._");
-        return Ok(());
-    }
-
-    let diff = diff_graph_snapshots(&base_graph, &head_graph);
-    println!("{}", format_semantic_diff_markdown(&diff));
-
-    Ok(())
-}
-
-fn read_objective_id() -> Option<String> {
-    let content = std::fs::read_to_string("intent/current.yaml").ok()?;
-    for line in content.lines() {
-        if let Some(rest) = line.trim().strip_prefix("objective_id:") {
-            return Some(rest.trim().trim_matches('"').trim_matches('\'').to_string());
-        }
-    }
-    None
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serde_json::json;
-
-    #[tokio::test]
-    async fn test_agent_git_snapshot_cli_returns_valid_id() {
-        let handle = SurrealHandle::setup_db().await.unwrap();
-
-        // Hermetic init: use tempdir so the test doesn't write into whatever
-        // cwd `cargo test` happened to run from. Matches the pattern in
-        // `test_pr_note_command`.
-        let temp_dir = tempfile::tempdir().unwrap();
-        cmd_init(&handle, &temp_dir.path().to_path_buf())
-            .await
-            .unwrap();
-
-        // Create a temp state file in the same tempdir.
-        let state_path = temp_dir.path().join("state.json");
-        std::fs::write(&state_path, r#"{"step": 1, "value": "test"}"#).unwrap();
-
-        // Run snapshot command (with explicit git SHA since test isn't in a git repo)
-        let result = cmd_snapshot(
-            &handle,
-            &state_path,
-            "Test snapshot",
-            "test-agent",
-            "main",
-            Some("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
-            Some(temp_dir.path().join("cas").as_path()),
-        )
-        .await;
-
-        assert!(result.is_ok(), "Snapshot failed: {:?}", result.err());
-
-        // Verify we can get the branch head
-        let head = handle.get_branch_head("main").await.unwrap();
-        assert!(!head.is_empty());
-    }
-
-    #[tokio::test]
-    async fn test_pr_note_command() {
-        let handle = SurrealHandle::setup_db().await.unwrap();
-        // Use a temp dir for cmd_init so the test is hermetic. Previously
-        // this called `cmd_init(&handle, &PathBuf::from("."))`, which leaked
-        // init artifacts into whatever cwd `cargo test` ran from.
-        let temp_dir = tempfile::tempdir().unwrap();
-        cmd_init(&handle, &temp_dir.path().to_path_buf())
-            .await
-            .unwrap();
-
-        // Create a snapshot to produce a commit and update the branch.
-        let state_path = temp_dir.path().join("state.json");
-        std::fs::write(&state_path, r#"{"step": 1, "value": "test"}"#).unwrap();
-
-        cmd_snapshot(
-            &handle,
-            &state_path,
-            "Initial commit",
-            "test-author",
-            "main",
-            Some("1234567890abcdef1234567890abcdef12345678"),
-            Some(&temp_dir.path().join("cas")),
-        )
-        .await
-        .unwrap();
-
-        // Run the pr-note command
-        let res = cmd_pr_note(&handle, Some("main")).await;
-        assert!(res.is_ok());
-    }
-
-    #[tokio::test]
-    async fn test_pr_semantic_summary_command() {
-        let handle = SurrealHandle::setup_db().await.unwrap();
-        let temp_dir = tempfile::tempdir().unwrap();
-        cmd_init(&handle, &temp_dir.path().to_path_buf())
-            .await
-            .unwrap();
-
-        let base_state = temp_dir.path().join("base.json");
-        std::fs::write(
-            &base_state,
-            r#"{
-                "graph": {
-                    "entry": "start",
-                    "exits": ["end"],
-                    "nodes": ["start", "plan"],
-                    "edges": [{"from": "start", "to": "plan"}]
-                },
-                "prompts": {"plan": "Plan v1"}
-            }"#,
-        )
-        .unwrap();
-        cmd_snapshot(
-            &handle,
-            &base_state,
-            "base graph",
-            "agent",
-            "main",
-            Some("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
-            Some(temp_dir.path().join("cas").as_path()),
-        )
-        .await
-        .unwrap();
-
-        let main_head = handle.get_branch_head("main").await.unwrap();
-        handle
-            .save_branch(&BranchRecord::new("feat", &main_head, false))
-            .await
-            .unwrap();
-
-        let head_state = temp_dir.path().join("head.json");
-        std::fs::write(
-            &head_state,
-            r#"{
-                "graph": {
-                    "entry": "start",
-                    "exits": ["end"],
-                    "nodes": ["start", "plan", "review"],
-                    "edges": [
-                        {"from": "start", "to": "plan"},
-                        {"from": "plan", "to": "review"}
-                    ]
-                },
-                "prompts": {"plan": "Plan v2", "review": "Review output"}
-            }"#,
-        )
-        .unwrap();
-        cmd_snapshot(
-            &handle,
-            &head_state,
-            "feature graph",
-            "agent",
-            "feat",
-            Some("bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"),
-            Some(temp_dir.path().join("cas").as_path()),
-        )
-        .await
-        .unwrap();
-
-        let res = cmd_pr_semantic_summary(&handle, Some("feat"), "main").await;
-        assert!(res.is_ok(), "pr-semantic-summary failed: {:?}", res.err());
-    }
-
-    #[tokio::test]
-    async fn test_pr_verify_reproducibility() {
-        let repo_root = aivcs_core::find_repo_root();
-        println!(
-            "test_pr_verify_reproducibility: repo_root = {:?}",
-            repo_root
-        );
-        let state_hash = "1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef";
-
-        // Mock the CAS file location locally just for this test
-        let cas_dir = repo_root
-            .join(".aivcs")
-            .join("cas")
-            .join("objects")
-            .join(&state_hash[..2]);
-        std::fs::create_dir_all(&cas_dir).unwrap();
-        let cas_file = cas_dir.join(&state_hash[2..]);
-        std::fs::write(&cas_file, b"test").unwrap();
-
-        let workspace_env = nix_env_manager::generate_environment_hash(&repo_root).unwrap();
-        println!(
-            "test_pr_verify_reproducibility: workspace_env = {:?}",
-            workspace_env
-        );
-        let computed = CommitId::new(None, state_hash, Some(&workspace_env.hash));
-
-        let pr_body = format!(
-            "aivcs-commit: {}\n- **State Hash**: {}\n- **Env Hash**: {}\n",
-            computed.hash, state_hash, workspace_env.hash
-        );
-
-        let res = cmd_pr_verify_reproducibility(Some(pr_body)).await;
-
-        // Cleanup mock CAS file
-        let _ = std::fs::remove_file(&cas_file);
-
-        assert!(res.is_ok(), "reproducibility check failed: {:?}", res.err());
-    }
-
-    #[tokio::test]
-    async fn test_pr_verify_reproducibility_docs_only_exempt() {
-        let pr_body = "aivcs-commit: code-only-docs-change-no-cognitive-snapshot\n".to_string();
-        let res = cmd_pr_verify_reproducibility(Some(pr_body)).await;
-        assert!(
-            res.is_ok(),
-            "docs-only exempt sentinel should pass: {:?}",
-            res.err()
-        );
-    }
-
-    #[tokio::test]
-    async fn test_cmd_fork_creates_branches_in_same_db() {
-        let handle = SurrealHandle::setup_db().await.unwrap();
-
-        // Hermetic init: see `test_pr_note_command` for the pattern.
-        let temp_dir = tempfile::tempdir().unwrap();
-        cmd_init(&handle, &temp_dir.path().to_path_buf())
-            .await
-            .unwrap();
-
-        // Create a snapshot to fork from in the same tempdir.
-        let state_path = temp_dir.path().join("state.json");
-        std::fs::write(&state_path, r#"{"step": 1, "value": "test"}"#).unwrap();
-        cmd_snapshot(
-            &handle,
-            &state_path,
-            "Base",
-            "agent",
-            "main",
-            Some("bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"),
-            Some(temp_dir.path().join("cas").as_path()),
-        )
-        .await
-        .unwrap();
-
-        // Run fork command
-        let result = cmd_fork(&handle, "main", 2, "test-fork").await;
-
-        assert!(result.is_ok(), "Fork failed: {:?}", result.err());
-
-        // Verify branches exist in the original handle
-        let branches = handle.list_branches().await.unwrap();
-        let fork_branches: Vec<_> = branches
-            .iter()
-            .filter(|b| b.name.starts_with("test-fork"))
-            .collect();
-
-        assert_eq!(
-            fork_branches.len(),
-            2,
-            "Should have created 2 branches in the same DB"
-        );
-    }
-
-    #[tokio::test]
-    async fn test_snapshot_linked_to_git_sha() {
-        let handle = SurrealHandle::setup_db().await.unwrap();
-        // Hermetic init: see `test_pr_note_command` for the pattern.
-        let temp_dir = tempfile::tempdir().unwrap();
-        cmd_init(&handle, &temp_dir.path().to_path_buf())
-            .await
-            .unwrap();
-
-        let state_path = temp_dir.path().join("state.json");
-        std::fs::write(&state_path, r#"{"model": "gpt-4", "step": 42}"#).unwrap();
-
-        let git_sha = "deadbeefdeadbeefdeadbeefdeadbeefdeadbeef";
-        let cas_dir = temp_dir.path().join("cas");
-
-        // Create snapshot with explicit git SHA
-        cmd_snapshot(
-            &handle,
-            &state_path,
-            "Linked to git commit",
-            "agent-v1",
-            "main",
-            Some(git_sha),
-            Some(cas_dir.as_path()),
-        )
-        .await
-        .unwrap();
-
-        // Verify commit exists in DB
-        let head = handle.get_branch_head("main").await.unwrap();
-        assert!(!head.is_empty());
-
-        // Verify state was stored in CAS
-        let store = aivcs_core::FsCasStore::new(&cas_dir).unwrap();
-        let state_bytes = r#"{"model": "gpt-4", "step": 42}"#.as_bytes();
-        let digest = aivcs_core::Digest::compute(state_bytes);
-        assert!(
-            aivcs_core::CasStore::exists(&store, &digest).unwrap(),
-            "State should exist in CAS after snapshot"
-        );
-
-        // Verify CAS roundtrip
-        let retrieved = aivcs_core::CasStore::get(&store, &digest).unwrap();
-        assert_eq!(retrieved, state_bytes);
-    }
-
-    #[tokio::test]
-    async fn test_snapshot_auto_detects_git_sha_in_repo() {
-        // Create a temporary git repo
-        let temp_dir = tempfile::tempdir().unwrap();
-        std::process::Command::new("git")
-            .args(["init"])
-            .current_dir(temp_dir.path())
-            .output()
-            .unwrap();
-        std::process::Command::new("git")
-            .args(["config", "user.name", "test-user"])
-            .current_dir(temp_dir.path())
-            .output()
-            .unwrap();
-        std::process::Command::new("git")
-            .args(["config", "user.email", "test@example.com"])
-            .current_dir(temp_dir.path())
-            .output()
-            .unwrap();
-        std::process::Command::new("git")
-            .args(["commit", "--allow-empty", "-m", "initial"])
-            .current_dir(temp_dir.path())
-            .output()
-            .unwrap();
-
-        // Capture the actual HEAD SHA
-        let expected_sha = aivcs_core::capture_head_sha(temp_dir.path()).unwrap();
-
-        assert_eq!(expected_sha.len(), 40);
-        assert!(expected_sha.chars().all(|c| c.is_ascii_hexdigit()));
-    }
-
-    #[test]
-    fn test_replay_golden_digest_equality() {
-        let temp_dir = tempfile::tempdir().unwrap();
-        let run_id = "run-golden-1";
-        let run_dir = temp_dir.path().join(run_id);
-        std::fs::create_dir_all(&run_dir).unwrap();
-
-        let output_bytes = br#"{"result":"ok","tokens":42}"#;
-        std::fs::write(run_dir.join("output.json"), output_bytes).unwrap();
-        let digest = aivcs_core::Digest::compute(output_bytes).to_hex();
-        std::fs::write(run_dir.join("output.digest"), format!("{}\n", digest)).unwrap();
-
-        let replayed = temp_dir.path().join("replayed.json");
-        let result = cmd_replay_artifact(run_id, Some(temp_dir.path()), Some(replayed.as_path()));
-        assert!(result.is_ok(), "replay failed: {:?}", result.err());
-
-        let written = std::fs::read(replayed).unwrap();
-        assert_eq!(written, output_bytes);
+        let mut out = Vec::new();
+        err.report(false, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("Error: Failed to connect to AIVCS database"));
+        assert!(text.contains("\nhint: check --db-url"));
     }
 
     #[test]
-    fn test_replay_missing_artifact_rejected() {
-        let temp_dir = tempfile::tempdir().unwrap();
-        let run_id = "run-missing-1";
-
-        let err = cmd_replay_artifact(run_id, Some(temp_dir.path()), None).unwrap_err();
-        let msg = format!("{err:#}");
-        assert!(
-            msg.contains("Recorded artifact not found"),
-            "unexpected error: {msg}"
-        );
-    }
-
-    #[test]
-    fn test_spec_diff_json_output_stability() {
-        let a = json!({
-            "model": "gpt-4",
-            "routing": {"strategy": "math"},
-            "threshold": 0.9
-        });
-        let b = json!({
-            "model": "gpt-4o",
-            "routing": {"strategy": "search"},
-            "new_flag": true
-        });
-
-        let diff = build_spec_diff(&a, &b);
-        let actual = serde_json::to_string_pretty(&diff).unwrap();
-        let expected = r#"{
-  "changed_paths": [
-    "/model",
-    "/routing/strategy"
-  ],
-  "only_in_a": [
-    "/threshold"
-  ],
-  "only_in_b": [
-    "/new_flag"
-  ]
-}"#;
-
-        assert_eq!(actual, expected);
+    fn test_bash_completions_list_top_level_commands() {
+        let mut out = Vec::new();
+        cmd_completions(clap_complete::Shell::Bash, &mut out);
+        let script = String::from_utf8(out).unwrap();
+
+        assert!(script.contains("_aivcs"), "{script}");
+        for name in [
+            "init",
+            "snapshot",
+            "restore",
+            "branch",
+            "merge",
+            "completions",
+        ] {
+            assert!(script.contains(name), "missing subcommand {name}");
+        }
     }
 
     #[test]
-    fn test_run_diff_json_output_stability() {
-        let a: Vec<RunEvent> = serde_json::from_value(json!([{
-            "seq": 1,
-            "kind": "tool_called",
-            "payload": {"tool_name":"search","query":"rust"},
-            "timestamp": "2026-01-01T00:00:00Z"
-        }]))
-        .unwrap();
-        let b: Vec<RunEvent> = serde_json::from_value(json!([{
-            "seq": 1,
-            "kind": "tool_called",
-            "payload": {"tool_name":"search","query":"python"},
-            "timestamp": "2026-01-01T00:00:00Z"
-        }]))
-        .unwrap();
-
-        let diff = build_run_diff(&a, &b);
-        let actual = serde_json::to_string_pretty(&diff).unwrap();
-        let expected = r#"{
-  "events_a": 1,
-  "events_b": 1,
-  "tool_call_changes": 1,
-  "added": 0,
-  "removed": 0,
-  "reordered": 0,
-  "param_changed": 1
-}"#;
-
-        assert_eq!(actual, expected);
+    fn test_truncate_does_not_split_multibyte_chars() {
+        // Byte 5 falls inside the second "é" and inside the emoji
+        assert_eq!(truncate("caféé latte", 5), "caféé...");
+        assert_eq!(truncate("ab🦀cd", 3), "ab🦀...");
+        assert_eq!(truncate("日本語", 3), "日本語");
     }
 }
@@ -0,0 +1,374 @@
+//! `aivcs memory` and `decisions` — inspecting, compacting and moving agent memories.
+
+use anyhow::{Context, Result};
+use oxidized_state::{DecisionFilter, StateError, SurrealHandle};
+
+use aivcs_core::commands::resolve_commit_ref;
+use aivcs_core::short_hash;
+
+use crate::truncate;
+
+/// Print each change of memory `key` along `branch`
+pub(crate) async fn cmd_memory_log(
+    handle: &SurrealHandle,
+    branch: &str,
+    key: &str,
+    json: bool,
+) -> Result<()> {
+    let history = handle.get_memory_history(branch, key).await?;
+
+    if json {
+        let entries: Vec<_> = history
+            .iter()
+            .map(|(commit, memory)| {
+                serde_json::json!({
+                    "commit": commit,
+                    "content": memory.content,
+                    "created_at": memory.created_at,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    if history.is_empty() {
+        println!("Memory key '{}' not found on '{}'", key, branch);
+        return Ok(());
+    }
+    for (commit, memory) in &history {
+        println!("{} {}", short_hash(commit), memory.content);
+    }
+    Ok(())
+}
+
+pub(crate) async fn cmd_memory_provenance(
+    handle: &SurrealHandle,
+    key: &str,
+    reference: &str,
+    json: bool,
+) -> Result<()> {
+    let commit = resolve_commit_ref(handle, reference).await;
+    let origins = aivcs_core::memory_provenance(handle, &commit, key).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&origins)?);
+        return Ok(());
+    }
+
+    if origins.is_empty() {
+        println!(
+            "No run provenance recorded for '{}' at {}",
+            key,
+            short_hash(&commit)
+        );
+        return Ok(());
+    }
+    for origin in &origins {
+        println!(
+            "run {} seq {} ({}) recorded {}",
+            origin.run_id,
+            origin.seq,
+            origin.source_type,
+            origin.recorded_at.format("%Y-%m-%d %H:%M:%S")
+        );
+    }
+    Ok(())
+}
+
+/// List decisions matching `filter`
+pub(crate) async fn cmd_decisions_list(
+    handle: &SurrealHandle,
+    filter: DecisionFilter,
+    json: bool,
+) -> Result<()> {
+    let decisions = handle.query_decisions(filter).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&decisions)?);
+        return Ok(());
+    }
+
+    if decisions.is_empty() {
+        println!("No matching decisions");
+        return Ok(());
+    }
+    for decision in &decisions {
+        println!(
+            "{} {} [{}] {} -> {}",
+            decision.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            short_hash(&decision.commit_id),
+            decision.task,
+            decision.action,
+            decision.outcome_status.as_deref().unwrap_or("pending")
+        );
+        println!("    {}", decision.rationale);
+    }
+    Ok(())
+}
+
+/// Report (and unless `dry_run`, delete) near-duplicate memories of a commit
+pub(crate) async fn cmd_memory_dedup(
+    handle: &SurrealHandle,
+    reference: &str,
+    threshold: f32,
+    dry_run: bool,
+) -> Result<()> {
+    let commit = resolve_commit_ref(handle, reference).await;
+    let pairs = aivcs_core::find_duplicate_memories(handle, &commit, threshold).await?;
+    if pairs.is_empty() {
+        println!(
+            "No duplicate memories in {} at threshold {}",
+            short_hash(&commit),
+            threshold
+        );
+        return Ok(());
+    }
+
+    // Keep the older memory of each pair; a key already removed as a
+    // duplicate doesn't get to remove anything else.
+    let mut removed = std::collections::BTreeSet::new();
+    for (keep, duplicate, similarity) in &pairs {
+        println!("{:.4}  {} ~ {}", similarity, keep, duplicate);
+        if removed.contains(keep) || removed.contains(duplicate) {
+            continue;
+        }
+        if !dry_run {
+            handle.delete_commit_memory(&commit, duplicate).await?;
+        }
+        removed.insert(duplicate.clone());
+    }
+
+    if dry_run {
+        println!(
+            "{} duplicate pair(s); would remove {} memor{} (dry run)",
+            pairs.len(),
+            removed.len(),
+            if removed.len() == 1 { "y" } else { "ies" }
+        );
+    } else {
+        println!(
+            "{} duplicate pair(s); removed {} memor{}",
+            pairs.len(),
+            removed.len(),
+            if removed.len() == 1 { "y" } else { "ies" }
+        );
+    }
+    Ok(())
+}
+
+/// Print the memory clusters of `reference`, largest first
+pub(crate) async fn cmd_memory_cluster(
+    handle: &SurrealHandle,
+    reference: &str,
+    threshold: f32,
+    json: bool,
+) -> Result<()> {
+    let commit = resolve_commit_ref(handle, reference).await;
+    let memories = handle.get_memories(&commit).await?;
+    let mut clusters = aivcs_core::cluster_memories(memories, threshold);
+    clusters.sort_by_key(|cluster| std::cmp::Reverse(cluster.len()));
+
+    if json {
+        let output: Vec<Vec<_>> = clusters
+            .iter()
+            .map(|cluster| {
+                cluster
+                    .iter()
+                    .map(|m| serde_json::json!({ "key": m.key, "content": m.content }))
+                    .collect()
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    if clusters.is_empty() {
+        println!("No memories in {}", short_hash(&commit));
+        return Ok(());
+    }
+    for (i, cluster) in clusters.iter().enumerate() {
+        println!("cluster {} ({} memories)", i + 1, cluster.len());
+        for memory in cluster {
+            println!("  {}: {}", memory.key, truncate(&memory.content, 60));
+        }
+    }
+    Ok(())
+}
+
+/// Write the memories of `reference` to `out`, one JSON record per line
+pub(crate) async fn cmd_memory_export(
+    handle: &SurrealHandle,
+    reference: &str,
+    out: &std::path::Path,
+) -> Result<()> {
+    let commit = resolve_commit_ref(handle, reference).await;
+    let memories = handle.get_memories(&commit).await?;
+
+    let mut text = String::new();
+    for mut memory in memories.iter().cloned() {
+        // Record ids are local to this database
+        memory.id = None;
+        text.push_str(&serde_json::to_string(&memory)?);
+        text.push('\n');
+    }
+    std::fs::write(out, text).with_context(|| format!("Failed to write {}", out.display()))?;
+
+    println!(
+        "Exported {} memor{} from {} to {}",
+        memories.len(),
+        if memories.len() == 1 { "y" } else { "ies" },
+        short_hash(&commit),
+        out.display()
+    );
+    Ok(())
+}
+
+/// Parse JSON Lines memories, skipping blank lines; fails on the first
+/// invalid line, naming it
+fn parse_memory_jsonl(text: &str) -> Result<Vec<oxidized_state::MemoryRecord>> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("Invalid memory record on line {}", index + 1))
+        })
+        .collect()
+}
+
+/// Save the memories in `input` under `reference`, all or none
+///
+/// Memories the commit already holds with the same key and content are
+/// skipped, so importing a file twice stores it once.
+pub(crate) async fn cmd_memory_import(
+    handle: &SurrealHandle,
+    reference: &str,
+    input: &std::path::Path,
+) -> Result<()> {
+    let commit = resolve_commit_ref(handle, reference).await;
+    handle
+        .get_commit(&commit)
+        .await?
+        .ok_or_else(|| StateError::CommitNotFound(reference.to_string()))?;
+    let text = std::fs::read_to_string(input)
+        .with_context(|| format!("Failed to read {}", input.display()))?;
+    let existing: std::collections::HashSet<(String, String)> = handle
+        .get_memories(&commit)
+        .await?
+        .into_iter()
+        .map(|m| (m.key, m.content))
+        .collect();
+
+    let mut skipped = 0;
+    let mut memories = Vec::new();
+    for mut memory in parse_memory_jsonl(&text)? {
+        if existing.contains(&(memory.key.clone(), memory.content.clone())) {
+            skipped += 1;
+            continue;
+        }
+        memory.id = None;
+        memory.commit_id = commit.clone();
+        memories.push(memory);
+    }
+
+    handle.save_memories_batch(&memories).await?;
+
+    print!(
+        "Imported {} memor{} into {}",
+        memories.len(),
+        if memories.len() == 1 { "y" } else { "ies" },
+        short_hash(&commit)
+    );
+    if skipped > 0 {
+        print!(" ({} already present)", skipped);
+    }
+    println!();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidized_state::{CommitId, CommitRecord};
+
+    #[tokio::test]
+    async fn test_memory_export_import_roundtrip() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("memories.jsonl");
+        let source = CommitId::from_state(b"cli-export-source");
+        let target = CommitId::from_state(b"cli-export-target");
+        for id in [&source, &target] {
+            let commit = CommitRecord::new(id.clone(), vec![], "export", "agent");
+            handle.save_commit(&commit).await.unwrap();
+        }
+        for (key, content) in [("goal", "ship it"), ("plan", "line one\nline two")] {
+            handle
+                .save_memory(&oxidized_state::MemoryRecord::new(
+                    &source.hash,
+                    key,
+                    content,
+                ))
+                .await
+                .unwrap();
+        }
+
+        cmd_memory_export(&handle, &source.hash, &path)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 2);
+        cmd_memory_import(&handle, &target.hash, &path)
+            .await
+            .unwrap();
+        // Importing the same file again adds nothing
+        cmd_memory_import(&handle, &target.hash, &path)
+            .await
+            .unwrap();
+
+        let key_content = |memories: Vec<oxidized_state::MemoryRecord>| {
+            let mut pairs: Vec<_> = memories.into_iter().map(|m| (m.key, m.content)).collect();
+            pairs.sort();
+            pairs
+        };
+        let exported = key_content(handle.get_memories(&source.hash).await.unwrap());
+        let imported = handle.get_memories(&target.hash).await.unwrap();
+        assert!(imported.iter().all(|m| m.commit_id == target.hash));
+        assert_eq!(key_content(imported), exported);
+    }
+
+    #[tokio::test]
+    async fn test_memory_import_rejects_file_with_any_invalid_line() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("memories.jsonl");
+        let target = CommitId::from_state(b"cli-import-target");
+        handle
+            .save_commit(&CommitRecord::new(
+                target.clone(),
+                vec![],
+                "import",
+                "agent",
+            ))
+            .await
+            .unwrap();
+        let valid = serde_json::to_string(&oxidized_state::MemoryRecord::new(
+            "elsewhere",
+            "goal",
+            "ship it",
+        ))
+        .unwrap();
+        std::fs::write(&path, format!("{valid}\n{{\"key\": \"broken\"}}\n")).unwrap();
+
+        let err = cmd_memory_import(&handle, &target.hash, &path)
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("line 2"), "{err:#}");
+        assert!(handle.get_memories(&target.hash).await.unwrap().is_empty());
+
+        let err = cmd_memory_import(&handle, "no-such-commit", &path)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Commit not found"), "{err}");
+    }
+}
@@ -0,0 +1,544 @@
+//! `aivcs merge` — merging branches, previewing a merge and resolving its conflicts.
+
+use anyhow::{Context, Result};
+use oxidized_state::SurrealHandle;
+use std::sync::Arc;
+use tracing::warn;
+
+use aivcs_core::commands::{self, MergePath};
+use aivcs_core::{short_hash, DecisionCaptureSource, DecisionRecorder, Progress, TermStyle};
+
+/// Merge two branches
+pub(crate) async fn cmd_merge(
+    handle: &SurrealHandle,
+    source: &str,
+    target: &str,
+    message: Option<&str>,
+    no_ff: bool,
+    author: &str,
+    progress: &dyn Progress,
+) -> Result<MergePath> {
+    let outcome = commands::merge_with_progress(
+        handle, source, target, message, no_ff, author, None, progress,
+    )
+    .await?;
+
+    match outcome.path {
+        MergePath::UpToDate => {
+            println!("Already up to date: '{}' contains '{}'", target, source);
+            return Ok(outcome.path);
+        }
+        MergePath::FastForward => {
+            println!(
+                "Fast-forward: {} {}..{}",
+                target,
+                short_hash(&outcome.previous_head),
+                short_hash(&outcome.head)
+            );
+            return Ok(outcome.path);
+        }
+        MergePath::MergeCommit => {}
+    }
+
+    // CODE_COMMITTED carries a `commit_sha` (git) AND an optional
+    // `aivcs_commit_id` (this merge's aivcs hash). The git side has no
+    // meaningful value if git isn't available, and emitting a 40-char
+    // string of zeros is indistinguishable from a real SHA to downstream
+    // consumers — strictly worse than not emitting at all. Skip the A2A
+    // side-effect and log; the aivcs merge itself is already durable.
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    match aivcs_core::capture_head_sha(&cwd) {
+        Ok(git_sha) => {
+            aivcs_core::maybe_emit_code_committed_from_env(
+                target,
+                &git_sha,
+                Vec::new(),
+                author,
+                None,
+                Some(&outcome.head),
+            )
+            .await;
+        }
+        Err(e) => {
+            warn!(
+                aivcs_commit_id = %outcome.head,
+                error = %e,
+                "skipping CODE_COMMITTED emission for merge: git SHA unavailable"
+            );
+        }
+    }
+
+    println!(
+        "Merge commit created: {}{}",
+        short_hash(&outcome.head),
+        if no_ff { " (--no-ff)" } else { "" }
+    );
+    if let Some(summary) = &outcome.summary {
+        println!("{}", summary);
+    }
+    for line in format_confidence_histogram(&outcome.confidence_histogram) {
+        println!("{}", line);
+    }
+
+    if !outcome.conflicts.is_empty() {
+        let style = TermStyle::stdout();
+        println!("\nUnresolved conflicts:");
+        for key in &outcome.conflicts {
+            println!("{}", style.conflict(&style.fit(&format!("  - {}", key))));
+        }
+    }
+
+    Ok(outcome.path)
+}
+
+/// Render the arbiter's confidence histogram as one bar per 0.1 bucket,
+/// followed by the share of resolutions below the manual-review threshold.
+/// Empty when there were no conflicts.
+fn format_confidence_histogram(histogram: &[usize; 10]) -> Vec<String> {
+    let total: usize = histogram.iter().sum();
+    if total == 0 {
+        return Vec::new();
+    }
+    let widest = histogram.iter().copied().max().unwrap_or(0);
+    let mut lines = vec!["\nArbiter confidence:".to_string()];
+    for (i, &count) in histogram.iter().enumerate() {
+        let bar = "#".repeat((count * 20).div_ceil(widest));
+        lines.push(format!(
+            "  {:.1}-{:.1} | {:<20} {}",
+            i as f32 / 10.0,
+            (i + 1) as f32 / 10.0,
+            bar,
+            count
+        ));
+    }
+    let review_buckets = (semantic_rag_merge::MANUAL_REVIEW_CONFIDENCE * 10.0) as usize;
+    let low: usize = histogram[..review_buckets].iter().sum();
+    lines.push(format!(
+        "  {}% of {} resolutions below {:.1} confidence",
+        low * 100 / total,
+        total,
+        semantic_rag_merge::MANUAL_REVIEW_CONFIDENCE
+    ));
+    lines
+}
+
+/// Print what merging `source` into `target` would do, writing nothing
+pub(crate) async fn cmd_merge_dry_run(
+    handle: &SurrealHandle,
+    source: &str,
+    target: &str,
+    no_ff: bool,
+    json: bool,
+) -> Result<()> {
+    let preview = commands::preview_merge(handle, source, target, no_ff).await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&preview)?);
+        return Ok(());
+    }
+
+    match preview.path {
+        MergePath::UpToDate => {
+            println!("Already up to date: '{}' contains '{}'", target, source);
+        }
+        MergePath::FastForward => println!(
+            "Would fast-forward: {} {}..{}",
+            target,
+            short_hash(&preview.previous_head),
+            short_hash(&preview.head)
+        ),
+        MergePath::MergeCommit => {
+            println!(
+                "Would create merge commit {}{}",
+                short_hash(&preview.head),
+                if no_ff { " (--no-ff)" } else { "" }
+            );
+            if let Some(summary) = &preview.summary {
+                println!("{}", summary);
+            }
+            println!("Auto-resolved conflicts: {}", preview.auto_resolved);
+            for line in format_confidence_histogram(&preview.confidence_histogram) {
+                println!("{}", line);
+            }
+            if !preview.conflicts.is_empty() {
+                let style = TermStyle::stdout();
+                println!("\nConflicts needing manual resolution:");
+                for key in &preview.conflicts {
+                    println!("{}", style.conflict(&style.fit(&format!("  - {}", key))));
+                }
+            }
+        }
+    }
+    println!("(dry run: nothing was written)");
+    Ok(())
+}
+
+/// Walk the unresolved conflicts of `merge_commit`, prompting for each one.
+///
+/// Answers are read line by line: `a` or `b` picks a side, `v` reads the next
+/// line as a custom value, `s` skips. Every answer is stored immediately, so
+/// stopping early (or hitting EOF) leaves a resumable partial resolution.
+pub(crate) async fn cmd_merge_resolve(
+    handle: &SurrealHandle,
+    merge_commit: &str,
+    input: &mut dyn std::io::BufRead,
+    output: &mut dyn std::io::Write,
+) -> Result<()> {
+    use std::io::Write as _;
+
+    let conflicts = semantic_rag_merge::unresolved_conflicts(handle, merge_commit).await?;
+    if conflicts.is_empty() {
+        writeln!(
+            output,
+            "No unresolved conflicts for {}",
+            short_hash(merge_commit)
+        )?;
+        return Ok(());
+    }
+
+    writeln!(output, "{} unresolved conflict(s)", conflicts.len())?;
+    let recorder = DecisionRecorder::with_default_config(Arc::new(handle.clone()));
+    let mut resolved = 0;
+    'conflicts: for conflict in &conflicts {
+        writeln!(output, "\nConflict: {}", conflict.key)?;
+        writeln!(output, "  [a] {}", conflict.memory_a.content)?;
+        writeln!(output, "  [b] {}", conflict.memory_b.content)?;
+
+        let choice = loop {
+            write!(output, "Resolve with (a/b/v=value/s=skip): ")?;
+            output.flush()?;
+            let Some(answer) = read_answer(input)? else {
+                break 'conflicts;
+            };
+            match answer.as_str() {
+                "a" => break Some(semantic_rag_merge::ConflictChoice::SideA),
+                "b" => break Some(semantic_rag_merge::ConflictChoice::SideB),
+                "v" => {
+                    write!(output, "Value: ")?;
+                    output.flush()?;
+                    let Some(value) = read_answer(input)? else {
+                        break 'conflicts;
+                    };
+                    break Some(semantic_rag_merge::ConflictChoice::Value(value));
+                }
+                "s" => break None,
+                other => writeln!(output, "Unrecognised answer '{}'", other)?,
+            }
+        };
+
+        if let Some(choice) = choice {
+            let resolution =
+                semantic_rag_merge::resolve_merge_conflict(handle, merge_commit, conflict, choice)
+                    .await?;
+            recorder
+                .record(resolution.decision, DecisionCaptureSource::Manual)
+                .await?;
+            resolved += 1;
+        }
+    }
+
+    writeln!(
+        output,
+        "\nResolved {} of {} conflict(s)",
+        resolved,
+        conflicts.len()
+    )?;
+    Ok(())
+}
+
+/// Read one trimmed line, or `None` at EOF.
+fn read_answer(input: &mut dyn std::io::BufRead) -> Result<Option<String>> {
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aivcs_core::NoProgress;
+    use oxidized_state::{BranchProtection, BranchRecord, CommitId, CommitRecord};
+    use serde_json::{json, Value};
+
+    use crate::error::{self, CliError};
+
+    #[tokio::test]
+    async fn test_missing_branch_error_has_code_and_hint() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+
+        let err = cmd_merge(&handle, "nope", "main", None, false, "agent", &NoProgress)
+            .await
+            .unwrap_err();
+        let err = CliError::classify(&err);
+
+        assert_eq!(err.code(), "branch_not_found");
+        assert_eq!(err.exit_code(), error::EXIT_BRANCH_NOT_FOUND);
+        assert!(err.hint().unwrap().contains("aivcs branch list"));
+
+        let mut out = Vec::new();
+        err.report(true, &mut out).unwrap();
+        let reported: Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(reported["code"], "branch_not_found");
+        assert!(reported["message"]
+            .as_str()
+            .unwrap()
+            .contains("Source branch not found: nope"));
+        assert_eq!(reported["hint"], err.hint().unwrap());
+    }
+
+    #[test]
+    fn test_format_confidence_histogram() {
+        assert!(format_confidence_histogram(&[0; 10]).is_empty());
+
+        let lines = format_confidence_histogram(&[0, 0, 1, 0, 0, 2, 0, 0, 4, 3]);
+        assert_eq!(lines.len(), 12);
+        assert_eq!(lines[3], format!("  0.2-0.3 | {:<20} 1", "#".repeat(5)));
+        assert_eq!(lines[9], format!("  0.8-0.9 | {} 4", "#".repeat(20)));
+        assert_eq!(lines[11], "  30% of 10 resolutions below 0.6 confidence");
+    }
+
+    #[tokio::test]
+    async fn test_merge_resolve_picks_side_b() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+
+        let commit_a = CommitId::from_state(b"cli-resolve-a");
+        let commit_b = CommitId::from_state(b"cli-resolve-b");
+        for id in [&commit_a, &commit_b] {
+            let commit = CommitRecord::new(id.clone(), vec![], "parent", "agent");
+            handle.save_commit(&commit).await.unwrap();
+        }
+        handle
+            .save_memory(&oxidized_state::MemoryRecord::new(
+                &commit_a.hash,
+                "goal",
+                "ship A",
+            ))
+            .await
+            .unwrap();
+        handle
+            .save_memory(&oxidized_state::MemoryRecord::new(
+                &commit_b.hash,
+                "goal",
+                "ship B",
+            ))
+            .await
+            .unwrap();
+
+        let merge = semantic_rag_merge::semantic_merge(
+            &handle,
+            &commit_a.hash,
+            &commit_b.hash,
+            "Merge",
+            "agent-git",
+        )
+        .await
+        .unwrap();
+        let merge_commit = merge.merge_commit_id.hash;
+
+        let mut input = std::io::Cursor::new(b"b\n".to_vec());
+        let mut output = Vec::new();
+        cmd_merge_resolve(&handle, &merge_commit, &mut input, &mut output)
+            .await
+            .unwrap();
+
+        let printed = String::from_utf8(output).unwrap();
+        assert!(printed.contains("Resolved 1 of 1 conflict(s)"), "{printed}");
+
+        let memories = handle.get_memories(&merge_commit).await.unwrap();
+        let goal: Vec<_> = memories.iter().filter(|m| m.key == "goal").collect();
+        assert_eq!(goal.len(), 1);
+        assert_eq!(goal[0].content, "ship B");
+
+        let audit = handle
+            .get_decision_history(
+                &format!(
+                    "{}:{}",
+                    semantic_rag_merge::MERGE_RESOLUTION_TASK_PREFIX,
+                    merge_commit
+                ),
+                10,
+            )
+            .await
+            .unwrap();
+        assert_eq!(audit.len(), 1);
+        assert_eq!(audit[0].action, "goal: chose side B");
+        assert!(!audit[0].alternatives.iter().any(|a| a.contains("ship")));
+
+        // Re-running finds nothing left to resolve.
+        let mut input = std::io::Cursor::new(Vec::new());
+        let mut output = Vec::new();
+        cmd_merge_resolve(&handle, &merge_commit, &mut input, &mut output)
+            .await
+            .unwrap();
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .starts_with("No unresolved conflicts"));
+    }
+
+    async fn merge_fixture_commit(handle: &SurrealHandle, label: &str, parent: &str) -> String {
+        let id = CommitId::from_state(label.as_bytes());
+        let parents = if parent.is_empty() {
+            vec![]
+        } else {
+            vec![parent.to_string()]
+        };
+        handle
+            .save_commit(&CommitRecord::new(id.clone(), parents, label, "agent"))
+            .await
+            .unwrap();
+        handle
+            .save_snapshot(&id, json!({ "label": label }))
+            .await
+            .unwrap();
+        id.hash
+    }
+
+    #[tokio::test]
+    async fn test_merge_fast_forwards_when_target_is_behind() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+        let root = merge_fixture_commit(&handle, "ff-root", "").await;
+        let ahead = merge_fixture_commit(&handle, "ff-ahead", &root).await;
+        handle
+            .save_branch(&BranchRecord::new("main", &root, true))
+            .await
+            .unwrap();
+        handle
+            .save_branch(&BranchRecord::new("feature", &ahead, false))
+            .await
+            .unwrap();
+        let before = handle.list_commits().await.unwrap().len();
+
+        let path = cmd_merge(
+            &handle,
+            "feature",
+            "main",
+            None,
+            false,
+            "agent",
+            &NoProgress,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(path, MergePath::FastForward);
+        assert_eq!(handle.get_branch_head("main").await.unwrap(), ahead);
+        assert_eq!(handle.list_commits().await.unwrap().len(), before);
+
+        // Merging again is a no-op
+        let path = cmd_merge(
+            &handle,
+            "feature",
+            "main",
+            None,
+            false,
+            "agent",
+            &NoProgress,
+        )
+        .await
+        .unwrap();
+        assert_eq!(path, MergePath::UpToDate);
+    }
+
+    #[tokio::test]
+    async fn test_merge_creates_commit_when_diverged_or_no_ff() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+        let root = merge_fixture_commit(&handle, "div-root", "").await;
+        let left = merge_fixture_commit(&handle, "div-left", &root).await;
+        let right = merge_fixture_commit(&handle, "div-right", &root).await;
+        handle
+            .save_branch(&BranchRecord::new("main", &left, true))
+            .await
+            .unwrap();
+        handle
+            .save_branch(&BranchRecord::new("feature", &right, false))
+            .await
+            .unwrap();
+        let before = handle.list_commits().await.unwrap().len();
+
+        let path = cmd_merge(
+            &handle,
+            "feature",
+            "main",
+            None,
+            false,
+            "agent",
+            &NoProgress,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(path, MergePath::MergeCommit);
+        let head = handle.get_branch_head("main").await.unwrap();
+        assert_ne!(head, left);
+        assert_ne!(head, right);
+        assert_eq!(handle.list_commits().await.unwrap().len(), before + 1);
+
+        // --no-ff forces a merge commit even when fast-forward is possible
+        let ahead = merge_fixture_commit(&handle, "div-ahead", &head).await;
+        handle
+            .save_branch(&BranchRecord::new("topic", &ahead, false))
+            .await
+            .unwrap();
+        let path = cmd_merge(&handle, "topic", "main", None, true, "agent", &NoProgress)
+            .await
+            .unwrap();
+        assert_eq!(path, MergePath::MergeCommit);
+        assert_ne!(handle.get_branch_head("main").await.unwrap(), ahead);
+    }
+
+    #[tokio::test]
+    async fn test_require_linear_rejects_merge_commit_but_allows_fast_forward() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+        let root = merge_fixture_commit(&handle, "lin-root", "").await;
+        let left = merge_fixture_commit(&handle, "lin-left", &root).await;
+        let right = merge_fixture_commit(&handle, "lin-right", &root).await;
+        handle
+            .save_branch(&BranchRecord::new("main", &left, true))
+            .await
+            .unwrap();
+        handle
+            .save_branch(&BranchRecord::new("feature", &right, false))
+            .await
+            .unwrap();
+        handle
+            .set_protection(
+                "main",
+                BranchProtection {
+                    require_linear: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let err = cmd_merge(
+            &handle,
+            "feature",
+            "main",
+            None,
+            false,
+            "agent",
+            &NoProgress,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("require-linear"), "{err}");
+        assert_eq!(handle.get_branch_head("main").await.unwrap(), left);
+
+        let ahead = merge_fixture_commit(&handle, "lin-ahead", &left).await;
+        handle
+            .save_branch(&BranchRecord::new("topic", &ahead, false))
+            .await
+            .unwrap();
+        assert!(
+            cmd_merge(&handle, "topic", "main", None, true, "agent", &NoProgress)
+                .await
+                .is_err()
+        );
+        let path = cmd_merge(&handle, "topic", "main", None, false, "agent", &NoProgress)
+            .await
+            .unwrap();
+        assert_eq!(path, MergePath::FastForward);
+    }
+}
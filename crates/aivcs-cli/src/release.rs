@@ -0,0 +1,324 @@
+//! `aivcs release` — promoting, rolling back and inspecting agent releases.
+
+use anyhow::{Context, Result};
+use oxidized_state::{ReleaseRegistry, SurrealDbReleaseRegistry, SurrealHandle};
+use std::sync::Arc;
+
+use crate::open_cas;
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn cmd_release_promote(
+    handle: &SurrealHandle,
+    name: &str,
+    git_sha: &str,
+    graph_digest: &str,
+    prompts_digest: &str,
+    tools_digest: &str,
+    config_digest: &str,
+    promoted_by: &str,
+    version: Option<&str>,
+    notes: Option<&str>,
+    compat_rules: Option<&std::path::Path>,
+    fields: &[String],
+    environment: aivcs_core::ReleaseEnvironment,
+    eval_report: Option<&str>,
+    cas_dir: Option<&std::path::Path>,
+) -> Result<()> {
+    let spec = aivcs_core::AgentSpec::new(
+        git_sha.to_string(),
+        graph_digest.to_string(),
+        prompts_digest.to_string(),
+        tools_digest.to_string(),
+        config_digest.to_string(),
+    )
+    .context("failed to build AgentSpec")?;
+
+    let mut candidate = aivcs_core::domain::release::Release::new(
+        name.to_string(),
+        spec.spec_digest.clone(),
+        spec.tools_digest.clone(),
+        spec.graph_digest.clone(),
+        version.unwrap_or_default().to_string(),
+        environment,
+        promoted_by.to_string(),
+    );
+    if !fields.is_empty() {
+        candidate.metadata = serde_json::json!({ "fields": fields });
+    }
+
+    let registry = SurrealDbReleaseRegistry::new(Arc::new(handle.clone()));
+    let api = aivcs_core::ReleaseRegistryApi::new(registry);
+
+    if let Some(path) = compat_rules {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read compat rules {}", path.display()))?;
+        let rules: aivcs_core::CompatRuleSet =
+            serde_json::from_str(&raw).context("invalid compat rule set")?;
+        let current = api
+            .current_release(name, environment)
+            .await
+            .context("failed to load the current release")?;
+        let report = eval_report
+            .map(|digest| -> Result<_> {
+                let parsed: aivcs_core::Digest = digest
+                    .parse()
+                    .with_context(|| format!("invalid eval report digest {}", digest))?;
+                aivcs_core::load_eval_report_by_digest(&open_cas(cas_dir)?, &parsed)
+                    .with_context(|| format!("failed to load eval report {}", digest))
+            })
+            .transpose()?;
+        let verdict = aivcs_core::evaluate_compat(
+            &rules,
+            &aivcs_core::PromoteContext {
+                candidate: &candidate,
+                current: current.as_ref(),
+                eval_report: report.as_ref(),
+                eval_report_digest: eval_report,
+            },
+        );
+        for warning in &verdict.warnings {
+            eprintln!("warning: {}", warning.reason);
+        }
+        if !verdict.passed() {
+            let reasons: Vec<&str> = verdict
+                .violations
+                .iter()
+                .map(|v| v.reason.as_str())
+                .collect();
+            anyhow::bail!("compat check failed: {}", reasons.join("; "));
+        }
+    }
+
+    let release = api
+        .promote_release(&spec, &candidate, notes.map(ToString::to_string))
+        .await
+        .context("promote failed")?;
+
+    println!(
+        "Promoted {} -> {}",
+        release.name,
+        release.spec_digest.as_str()
+    );
+    Ok(())
+}
+
+pub(crate) async fn cmd_release_rollback(handle: &SurrealHandle, name: &str) -> Result<()> {
+    let registry = SurrealDbReleaseRegistry::new(Arc::new(handle.clone()));
+    let release = registry.rollback(name).await?;
+    println!(
+        "Rolled back {} -> {}",
+        release.name,
+        release.spec_digest.as_str()
+    );
+    Ok(())
+}
+
+pub(crate) async fn cmd_release_current(handle: &SurrealHandle, name: &str) -> Result<()> {
+    let registry = SurrealDbReleaseRegistry::new(Arc::new(handle.clone()));
+    let current = registry.current(name).await?;
+    match current {
+        Some(release) => {
+            println!(
+                "Current {} -> {}",
+                release.name,
+                release.spec_digest.as_str()
+            );
+        }
+        None => println!("No release found for {}", name),
+    }
+    Ok(())
+}
+
+pub(crate) async fn cmd_release_history(handle: &SurrealHandle, name: &str) -> Result<()> {
+    let registry = SurrealDbReleaseRegistry::new(Arc::new(handle.clone()));
+    let history = registry.history(name).await?;
+
+    if history.is_empty() {
+        println!("No release history for {}", name);
+        return Ok(());
+    }
+
+    for release in history {
+        println!(
+            "{} {} {}",
+            release.created_at.to_rfc3339(),
+            release.name,
+            release.spec_digest.as_str()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRODUCTION: aivcs_core::ReleaseEnvironment = aivcs_core::ReleaseEnvironment::Production;
+
+    /// Promote agent `gated` with every spec digest set to `digest`
+    async fn promote_with_rules(
+        handle: &SurrealHandle,
+        digest: &str,
+        rules: &std::path::Path,
+        fields: &[String],
+        environment: aivcs_core::ReleaseEnvironment,
+        eval_report: Option<&str>,
+        cas_dir: &std::path::Path,
+    ) -> Result<()> {
+        cmd_release_promote(
+            handle,
+            "gated",
+            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            digest,
+            digest,
+            digest,
+            digest,
+            "ci",
+            None,
+            None,
+            Some(rules),
+            fields,
+            environment,
+            eval_report,
+            Some(cas_dir),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_release_promote_gates_on_eval_report_digest() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let cas_dir = temp_dir.path().join("cas");
+        let cas = aivcs_core::FsCasStore::new(&cas_dir).unwrap();
+        let rules = temp_dir.path().join("compat.json");
+        std::fs::write(
+            &rules,
+            r#"{"rules": [{"type": "min_eval_pass_rate", "threshold": 0.9}]}"#,
+        )
+        .unwrap();
+
+        let mut persisted = Vec::new();
+        for (seed, passed_cases, total_cases) in [(1, 7, 10), (2, 19, 20)] {
+            let report = aivcs_core::EvalRunReport {
+                suite_digest: "suite".to_string(),
+                seed,
+                total_cases,
+                passed_cases,
+                pass_rate: passed_cases as f32 / total_cases as f32,
+                overall_pass: true,
+                case_results: vec![],
+            };
+            let digest = aivcs_core::persist_eval_report(&handle, &cas, &report)
+                .await
+                .unwrap();
+            persisted.push(digest.to_hex());
+        }
+        let digest = "b".repeat(64);
+
+        let err = promote_with_rules(&handle, &digest, &rules, &[], PRODUCTION, None, &cas_dir)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no eval run report"), "{err}");
+
+        let err = promote_with_rules(
+            &handle,
+            "gated",
+            &digest,
+            &rules,
+            &[],
+            Some(&persisted[0]),
+            &cas_dir,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("0.70"), "{err}");
+        let registry = SurrealDbReleaseRegistry::new(Arc::new(handle.clone()));
+        assert!(registry.current("gated").await.unwrap().is_none());
+
+        promote_with_rules(
+            &handle,
+            &digest,
+            &rules,
+            &[],
+            PRODUCTION,
+            Some(&persisted[1]),
+            &cas_dir,
+        )
+        .await
+        .unwrap();
+        assert!(registry.current("gated").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_release_promote_checks_fields_and_digests_against_live_release() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let cas_dir = temp_dir.path().join("cas");
+        let rules = temp_dir.path().join("compat.json");
+        std::fs::write(
+            &rules,
+            r#"{"rules": [
+                {"type": "require_field", "field": "model"},
+                {"type": "no_tools_change"}
+            ]}"#,
+        )
+        .unwrap();
+        let fields = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        let live = "b".repeat(64);
+
+        promote_with_rules(
+            &handle,
+            &live,
+            &rules,
+            &fields(&["model", "max_tokens"]),
+            PRODUCTION,
+            None,
+            &cas_dir,
+        )
+        .await
+        .unwrap();
+
+        // Dropping `model` relative to the live production release blocks
+        let err = promote_with_rules(
+            &handle,
+            &live,
+            &rules,
+            &fields(&["max_tokens"]),
+            PRODUCTION,
+            None,
+            &cas_dir,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("'model' was removed"), "{err}");
+
+        // New tools differ from the live production release
+        let changed = "c".repeat(64);
+        let err = promote_with_rules(
+            &handle,
+            &changed,
+            &rules,
+            &fields(&["model"]),
+            PRODUCTION,
+            None,
+            &cas_dir,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("tools_digest changed"), "{err}");
+
+        // Staging has no live release yet, so only the candidate is checked
+        promote_with_rules(
+            &handle,
+            &changed,
+            &rules,
+            &fields(&["model"]),
+            aivcs_core::ReleaseEnvironment::Staging,
+            None,
+            &cas_dir,
+        )
+        .await
+        .unwrap();
+    }
+}
@@ -0,0 +1,217 @@
+//! `aivcs run` and `cost` — following, comparing and pricing recorded runs.
+
+use anyhow::{Context, Result};
+use oxidized_state::{RunId, RunLedger, SurrealRunLedger};
+
+use aivcs_core::TermStyle;
+
+use crate::diff::format_state_delta;
+
+/// Follow a run's events until it reaches a terminal state
+pub(crate) async fn cmd_run_tail(ledger: &SurrealRunLedger, run_id: &str) -> Result<()> {
+    use futures::StreamExt;
+
+    let id = RunId(run_id.to_string());
+    let run = ledger
+        .get_run(&id)
+        .await
+        .with_context(|| format!("run not found: {}", run_id))?;
+    println!("Tailing run {} ({})", run_id, run.metadata.agent_name);
+
+    let mut events = std::pin::pin!(ledger.subscribe(&id));
+    while let Some(event) = events.next().await {
+        println!("[{}] {} {}", event.seq, event.kind, event.payload);
+    }
+
+    let run = ledger
+        .get_run(&id)
+        .await
+        .with_context(|| format!("run not found: {}", run_id))?;
+    println!("Run finished: {:?}", run.status);
+    Ok(())
+}
+
+/// Show the tokens and cost of one run
+pub(crate) async fn cmd_run_cost(
+    ledger: &SurrealRunLedger,
+    run_id: &str,
+    pricing: &aivcs_core::PricingTable,
+    json: bool,
+) -> Result<()> {
+    let cost = aivcs_core::run_cost(ledger, run_id, pricing).await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&cost)?);
+    } else {
+        println!("Run {} ({})", cost.run_id, cost.agent_name);
+        print_cost_summary(&cost.cost);
+    }
+    Ok(())
+}
+
+/// Show the summed cost of the runs of `agent` (or of all agents) since `since`
+pub(crate) async fn cmd_cost_report(
+    ledger: &SurrealRunLedger,
+    agent: Option<&str>,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    pricing: &aivcs_core::PricingTable,
+    json: bool,
+) -> Result<()> {
+    let report = aivcs_core::cost_report(ledger, agent, since, pricing).await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!(
+            "{} run(s) of {}{}",
+            report.runs,
+            report.agent.as_deref().unwrap_or("all agents"),
+            report
+                .since
+                .map(|t| format!(" since {}", t.format("%Y-%m-%d %H:%M")))
+                .unwrap_or_default()
+        );
+        print_cost_summary(&report.cost);
+    }
+    Ok(())
+}
+
+/// Print per-model tokens and cost, then the totals
+fn print_cost_summary(cost: &aivcs_core::CostSummary) {
+    for model in &cost.models {
+        let price = match model.cost_usd {
+            Some(usd) => format!("${usd:.4}"),
+            None => "unpriced".to_string(),
+        };
+        println!(
+            "  {:<24} {:>6} call(s) {:>12} in {:>12} out  {}",
+            model.model, model.calls, model.input_tokens, model.output_tokens, price
+        );
+    }
+    println!(
+        "Total: {} input + {} output tokens, ${:.4}",
+        cost.input_tokens, cost.output_tokens, cost.cost_usd
+    );
+    if !cost.unpriced_models.is_empty() {
+        println!(
+            "No price configured for: {} (add [pricing.<model>] to aivcs.toml)",
+            cost.unpriced_models.join(", ")
+        );
+    }
+}
+
+pub(crate) async fn cmd_run_checkpoint_diff(
+    ledger: &dyn RunLedger,
+    id_a: &str,
+    id_b: &str,
+    json: bool,
+) -> Result<()> {
+    let diff = run_checkpoint_diff(ledger, id_a, id_b).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff.deltas)?);
+        return Ok(());
+    }
+    println!("A: {}", id_a);
+    println!("B: {}", id_b);
+    println!();
+    if diff.is_empty() {
+        println!("Final checkpoint states are identical.");
+        return Ok(());
+    }
+    let style = TermStyle::stdout();
+    for delta in &diff.deltas {
+        println!("{}", format_state_delta(delta, &style));
+    }
+    Ok(())
+}
+
+/// Diff the last `checkpoint_saved` state of runs `id_a` and `id_b`,
+/// failing if either run saved no checkpoint
+async fn run_checkpoint_diff(
+    ledger: &dyn RunLedger,
+    id_a: &str,
+    id_b: &str,
+) -> Result<aivcs_core::ScopedStateDiff> {
+    let mut states = Vec::with_capacity(2);
+    for id in [id_a, id_b] {
+        let (events, _) = aivcs_core::replay_run(ledger, id)
+            .await
+            .with_context(|| format!("replay failed for run: {}", id))?;
+        let state = aivcs_core::extract_last_checkpoint(&events).ok_or_else(|| {
+            anyhow::anyhow!(
+                "run {} saved no checkpoint ({} events); nothing to compare",
+                id,
+                aivcs_core::CHECKPOINT_SAVED_KIND
+            )
+        })?;
+        states.push(state);
+    }
+    Ok(aivcs_core::diff_state_subtree(&states[0], &states[1], ""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidized_state::RunEvent;
+    use serde_json::{json, Value};
+
+    #[tokio::test]
+    async fn test_run_checkpoint_diff_compares_final_checkpoints() {
+        use oxidized_state::fakes::MemoryRunLedger;
+        use oxidized_state::{ContentDigest, RunMetadata};
+
+        let ledger = MemoryRunLedger::new();
+        let spec = ContentDigest::from_bytes(b"checkpoint-spec");
+        let metadata = RunMetadata {
+            git_sha: None,
+            agent_name: "agent".to_string(),
+            tags: json!({}),
+            evaluation: Default::default(),
+        };
+        let event = |seq: u64, kind: &str, payload: Value| RunEvent {
+            seq,
+            kind: kind.to_string(),
+            payload,
+            timestamp: chrono::Utc::now(),
+        };
+
+        let mut runs = Vec::new();
+        for (first_tool, final_plan) in [("search", "ship"), ("browse", "hold")] {
+            let id = ledger.create_run(&spec, metadata.clone()).await.unwrap();
+            let events = [
+                event(1, "tool_called", json!({ "tool_name": first_tool })),
+                event(
+                    2,
+                    aivcs_core::CHECKPOINT_SAVED_KIND,
+                    json!({ "node_id": "plan", "plan": "draft", "step": 1 }),
+                ),
+                event(
+                    3,
+                    aivcs_core::CHECKPOINT_SAVED_KIND,
+                    json!({ "node_id": "plan", "plan": final_plan, "step": 2 }),
+                ),
+            ];
+            for e in events {
+                ledger.append_event(&id, e).await.unwrap();
+            }
+            runs.push(id.0);
+        }
+        let bare = ledger.create_run(&spec, metadata.clone()).await.unwrap();
+        ledger
+            .append_event(&bare, event(1, "tool_called", json!({})))
+            .await
+            .unwrap();
+
+        let diff = run_checkpoint_diff(&ledger, &runs[0], &runs[1])
+            .await
+            .unwrap();
+        assert_eq!(diff.deltas.len(), 1);
+        assert_eq!(diff.deltas[0].pointer, "/plan");
+        assert_eq!(diff.deltas[0].before, json!("ship"));
+        assert_eq!(diff.deltas[0].after, json!("hold"));
+
+        let err = run_checkpoint_diff(&ledger, &runs[0], &bare.0)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("saved no checkpoint"), "{err:#}");
+    }
+}
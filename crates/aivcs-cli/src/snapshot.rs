@@ -0,0 +1,675 @@
+//! `aivcs init`, `snapshot`, `restore` and `replay-artifact` — recording agent state and getting it back.
+
+use anyhow::{Context, Result};
+use nix_env_manager::{generate_environment_hash, generate_logic_hash, rust_toolchain_version};
+use oxidized_state::SurrealHandle;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+use aivcs_core::commands::{self, snapshot_keyframe_interval, SnapshotOutcome, SnapshotRequest};
+use aivcs_core::short_hash;
+
+use crate::open_cas;
+
+/// Initialize a new AIVCS repository
+pub(crate) async fn cmd_init(handle: &SurrealHandle, path: &PathBuf) -> Result<()> {
+    info!("Initializing AIVCS repository at {:?}", path);
+
+    let commit_id = commands::init(handle).await?;
+
+    println!("Initialized AIVCS repository at {:?}", path);
+    println!("Initial commit: {}", commit_id.short());
+
+    Ok(())
+}
+
+/// Create a snapshot of agent state, linked to the current git HEAD
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn cmd_snapshot(
+    handle: &SurrealHandle,
+    state_path: &PathBuf,
+    message: &str,
+    author: &str,
+    branch: &str,
+    git_sha_override: Option<&str>,
+    cas_dir: Option<&std::path::Path>,
+    skip_if_unchanged: bool,
+    signer: Option<&aivcs_core::CommitSigner>,
+) -> Result<SnapshotOutcome> {
+    let state_content = read_state_input(state_path, &mut std::io::stdin().lock())?;
+    let source = if state_path.as_os_str() == "-" {
+        "<stdin>".to_string()
+    } else {
+        state_path.display().to_string()
+    };
+
+    snapshot_state_content(
+        handle,
+        &state_content,
+        &source,
+        message,
+        author,
+        branch,
+        git_sha_override,
+        cas_dir,
+        skip_if_unchanged,
+        signer,
+    )
+    .await
+}
+
+/// Read snapshot state from `state_path`, or from `stdin` when the path is `-`.
+pub(crate) fn read_state_input(
+    state_path: &std::path::Path,
+    stdin: &mut dyn std::io::Read,
+) -> Result<String> {
+    if state_path.as_os_str() == "-" {
+        let mut content = String::new();
+        stdin
+            .read_to_string(&mut content)
+            .context("Failed to read state from stdin")?;
+        Ok(content)
+    } else {
+        std::fs::read_to_string(state_path)
+            .context(format!("Failed to read state file: {:?}", state_path))
+    }
+}
+
+/// Commit already-read state JSON; `source` names where it came from.
+#[allow(clippy::too_many_arguments)]
+async fn snapshot_state_content(
+    handle: &SurrealHandle,
+    state_content: &str,
+    source: &str,
+    message: &str,
+    author: &str,
+    branch: &str,
+    git_sha_override: Option<&str>,
+    cas_dir: Option<&std::path::Path>,
+    skip_if_unchanged: bool,
+    signer: Option<&aivcs_core::CommitSigner>,
+) -> Result<SnapshotOutcome> {
+    // Resolve git SHA: use override, or auto-detect from cwd. Sentinel
+    // `None` means "no git context" — used by the local-print path and the
+    // A2A emit gate below. See the matching block on the emit site.
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    let git_sha: Option<String> = match git_sha_override {
+        Some(sha) => Some(sha.to_string()),
+        None => aivcs_core::capture_head_sha(&cwd).ok(),
+    };
+
+    // Generate logic and environment hashes for composite CommitId
+    let logic_hash = generate_logic_hash(&cwd.join("src")).ok();
+    let env_hash = generate_environment_hash(&cwd).ok();
+
+    let cas = open_cas(cas_dir)?;
+
+    let request = SnapshotRequest {
+        state: state_content.to_string(),
+        message: message.to_string(),
+        author: author.to_string(),
+        branch: branch.to_string(),
+        logic_hash,
+        env_hash: env_hash.map(|h| h.hash),
+        git_sha: git_sha.clone(),
+        toolchain: rust_toolchain_version(),
+        skip_if_unchanged,
+        keyframe_interval: snapshot_keyframe_interval(),
+    };
+    let outcome = commands::snapshot(handle, &cas, &request, signer).await?;
+
+    if let Some(head) = &outcome.unchanged_from {
+        println!("state unchanged from {}", short_hash(head));
+    }
+    let Some(commit_hash) = &outcome.commit else {
+        println!("Skipped snapshot on '{}' (--skip-if-unchanged)", branch);
+        return Ok(outcome);
+    };
+
+    // Same A2A gate as `cmd_merge`: only emit CODE_COMMITTED when we have
+    // a real git SHA. Emitting with a placeholder hash would be a lie to
+    // any consumer that joins on `commit_sha`.
+    if let Some(sha) = &git_sha {
+        aivcs_core::maybe_emit_code_committed_from_env(
+            branch,
+            sha,
+            vec![source.to_string()],
+            author,
+            None,
+            Some(commit_hash),
+        )
+        .await;
+    } else {
+        warn!(
+            aivcs_commit_id = %commit_hash,
+            "skipping CODE_COMMITTED emission for snapshot: git SHA unavailable"
+        );
+    }
+
+    info!(
+        cas_digest = %outcome.cas_digest,
+        git_sha = %git_sha.as_deref().unwrap_or("(unavailable)"),
+        "snapshot stored in CAS"
+    );
+
+    println!("[{}] {} ({})", branch, message, short_hash(commit_hash));
+    println!("Commit:    {}", commit_hash);
+    println!(
+        "Git SHA:   {}",
+        git_sha.as_deref().unwrap_or("(unavailable)")
+    );
+    println!("CAS digest: {}", outcome.cas_digest);
+
+    Ok(outcome)
+}
+
+/// Restore agent to a previous state
+pub(crate) async fn cmd_restore(
+    handle: &SurrealHandle,
+    reference: &str,
+    output: Option<&std::path::Path>,
+    exec: Option<&str>,
+    verify: Option<&dyn aivcs_core::CasStore>,
+) -> Result<()> {
+    let restored = commands::restore(handle, reference, verify).await?;
+    let commit_hash = restored.commit;
+    let state_json = serde_json::to_string_pretty(&restored.state)?;
+
+    if let Some(path) = output {
+        std::fs::write(path, &state_json).context(format!("Failed to write to {:?}", path))?;
+        println!("Restored state to {:?}", path);
+    } else if exec.is_none() {
+        println!("{}", state_json);
+    }
+
+    if let Some(command) = exec {
+        exec_restore_hook(command, &commit_hash, &state_json, output)?;
+        println!(
+            "Handed state of {} to `{}`",
+            short_hash(&commit_hash),
+            command
+        );
+    }
+
+    Ok(())
+}
+
+/// Run `command` via `sh -c`, piping the restored state to its stdin.
+///
+/// Fails if the command cannot be spawned or exits non-zero.
+fn exec_restore_hook(
+    command: &str,
+    commit_hash: &str,
+    state_json: &str,
+    state_path: Option<&std::path::Path>,
+) -> Result<()> {
+    use std::io::Write as _;
+    use std::process::{Command, Stdio};
+
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c")
+        .arg(command)
+        .env("AIVCS_RESTORED_COMMIT", commit_hash)
+        .stdin(Stdio::piped());
+    if let Some(path) = state_path {
+        cmd.env("AIVCS_RESTORED_STATE", path);
+    }
+
+    let mut child = cmd
+        .spawn()
+        .with_context(|| format!("Failed to spawn restore hook: {}", command))?;
+    {
+        let mut stdin = child
+            .stdin
+            .take()
+            .context("restore hook stdin unavailable")?;
+        // A hook that ignores its input may close stdin early; only the exit
+        // status decides success.
+        let _ = stdin.write_all(state_json.as_bytes());
+    }
+
+    let status = child.wait().context("Failed to wait for restore hook")?;
+    if !status.success() {
+        anyhow::bail!("restore hook `{}` failed with {}", command, status);
+    }
+    Ok(())
+}
+
+/// Replay a recorded run artifact from disk.
+///
+/// Expected layout:
+/// - `<artifacts_dir>/<run_id>/output.json`
+/// - `<artifacts_dir>/<run_id>/output.digest`
+pub(crate) fn cmd_replay_artifact(
+    run_id: &str,
+    artifacts_dir: Option<&std::path::Path>,
+    output: Option<&std::path::Path>,
+) -> Result<()> {
+    let root = artifacts_dir
+        .map(|p| p.to_path_buf())
+        .unwrap_or_else(|| PathBuf::from(".aivcs/runs"));
+    let run_dir = root.join(run_id);
+    let output_path = run_dir.join("output.json");
+    let digest_path = run_dir.join("output.digest");
+
+    if !output_path.exists() {
+        anyhow::bail!("Recorded artifact not found: {:?}", output_path);
+    }
+    if !digest_path.exists() {
+        anyhow::bail!("Recorded digest not found: {:?}", digest_path);
+    }
+
+    let artifact_bytes = std::fs::read(&output_path)
+        .with_context(|| format!("Failed to read recorded artifact: {:?}", output_path))?;
+
+    // Validate artifact is JSON for replayability.
+    let _: serde_json::Value = serde_json::from_slice(&artifact_bytes)
+        .with_context(|| format!("Recorded artifact is not valid JSON: {:?}", output_path))?;
+
+    let expected_digest = std::fs::read_to_string(&digest_path)
+        .with_context(|| format!("Failed to read recorded digest: {:?}", digest_path))?
+        .trim()
+        .to_string();
+
+    let actual_digest = aivcs_core::Digest::compute(&artifact_bytes).to_hex();
+    if actual_digest != expected_digest {
+        anyhow::bail!(
+            "Replay digest mismatch for run {}: expected {}, got {}",
+            run_id,
+            expected_digest,
+            actual_digest
+        );
+    }
+
+    if let Some(path) = output {
+        std::fs::write(path, &artifact_bytes)
+            .with_context(|| format!("Failed to write replay output to {:?}", path))?;
+        println!("Replayed run {} to {:?}", run_id, path);
+    } else {
+        println!("{}", String::from_utf8_lossy(&artifact_bytes));
+    }
+
+    println!("Replay digest verified: {}", actual_digest);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidized_state::CommitId;
+    use serde_json::{json, Value};
+
+    use crate::error::{self, CliError};
+
+    #[tokio::test]
+    async fn test_agent_git_snapshot_cli_returns_valid_id() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+
+        // Hermetic init: use tempdir so the test doesn't write into whatever
+        // cwd `cargo test` happened to run from. Matches the pattern in
+        // `test_pr_note_command`.
+        let temp_dir = tempfile::tempdir().unwrap();
+        cmd_init(&handle, &temp_dir.path().to_path_buf())
+            .await
+            .unwrap();
+
+        // Create a temp state file in the same tempdir.
+        let state_path = temp_dir.path().join("state.json");
+        std::fs::write(&state_path, r#"{"step": 1, "value": "test"}"#).unwrap();
+
+        // Run snapshot command (with explicit git SHA since test isn't in a git repo)
+        let result = cmd_snapshot(
+            &handle,
+            &state_path,
+            "Test snapshot",
+            "test-agent",
+            "main",
+            Some("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+            Some(temp_dir.path().join("cas").as_path()),
+            false,
+            None,
+        )
+        .await;
+
+        assert!(result.is_ok(), "Snapshot failed: {:?}", result.err());
+
+        // Verify we can get the branch head
+        let head = handle.get_branch_head("main").await.unwrap();
+        assert!(!head.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_linked_to_git_sha() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+        // Hermetic init: see `test_pr_note_command` for the pattern.
+        let temp_dir = tempfile::tempdir().unwrap();
+        cmd_init(&handle, &temp_dir.path().to_path_buf())
+            .await
+            .unwrap();
+
+        let state_path = temp_dir.path().join("state.json");
+        std::fs::write(&state_path, r#"{"model": "gpt-4", "step": 42}"#).unwrap();
+
+        let git_sha = "deadbeefdeadbeefdeadbeefdeadbeefdeadbeef";
+        let cas_dir = temp_dir.path().join("cas");
+
+        // Create snapshot with explicit git SHA
+        cmd_snapshot(
+            &handle,
+            &state_path,
+            "Linked to git commit",
+            "agent-v1",
+            "main",
+            Some(git_sha),
+            Some(cas_dir.as_path()),
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+
+        // Verify commit exists in DB
+        let head = handle.get_branch_head("main").await.unwrap();
+        assert!(!head.is_empty());
+
+        // Verify state was stored in CAS
+        let store = aivcs_core::FsCasStore::new(&cas_dir).unwrap();
+        let state_bytes = r#"{"model": "gpt-4", "step": 42}"#.as_bytes();
+        let digest = aivcs_core::Digest::compute(state_bytes);
+        assert!(
+            aivcs_core::CasStore::exists(&store, &digest).unwrap(),
+            "State should exist in CAS after snapshot"
+        );
+
+        // Verify CAS roundtrip
+        let retrieved = aivcs_core::CasStore::get(&store, &digest).unwrap();
+        assert_eq!(retrieved, state_bytes);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_auto_detects_git_sha_in_repo() {
+        // Create a temporary git repo
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::process::Command::new("git")
+            .args(["init"])
+            .current_dir(temp_dir.path())
+            .output()
+            .unwrap();
+        std::process::Command::new("git")
+            .args(["config", "user.name", "test-user"])
+            .current_dir(temp_dir.path())
+            .output()
+            .unwrap();
+        std::process::Command::new("git")
+            .args(["config", "user.email", "test@example.com"])
+            .current_dir(temp_dir.path())
+            .output()
+            .unwrap();
+        std::process::Command::new("git")
+            .args(["commit", "--allow-empty", "-m", "initial"])
+            .current_dir(temp_dir.path())
+            .output()
+            .unwrap();
+
+        // Capture the actual HEAD SHA
+        let expected_sha = aivcs_core::capture_head_sha(temp_dir.path()).unwrap();
+
+        assert_eq!(expected_sha.len(), 40);
+        assert!(expected_sha.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn test_replay_golden_digest_equality() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let run_id = "run-golden-1";
+        let run_dir = temp_dir.path().join(run_id);
+        std::fs::create_dir_all(&run_dir).unwrap();
+
+        let output_bytes = br#"{"result":"ok","tokens":42}"#;
+        std::fs::write(run_dir.join("output.json"), output_bytes).unwrap();
+        let digest = aivcs_core::Digest::compute(output_bytes).to_hex();
+        std::fs::write(run_dir.join("output.digest"), format!("{}\n", digest)).unwrap();
+
+        let replayed = temp_dir.path().join("replayed.json");
+        let result = cmd_replay_artifact(run_id, Some(temp_dir.path()), Some(replayed.as_path()));
+        assert!(result.is_ok(), "replay failed: {:?}", result.err());
+
+        let written = std::fs::read(replayed).unwrap();
+        assert_eq!(written, output_bytes);
+    }
+
+    #[test]
+    fn test_replay_missing_artifact_rejected() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let run_id = "run-missing-1";
+
+        let err = cmd_replay_artifact(run_id, Some(temp_dir.path()), None).unwrap_err();
+        let msg = format!("{err:#}");
+        assert!(
+            msg.contains("Recorded artifact not found"),
+            "unexpected error: {msg}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_restore_of_missing_commit_exits_commit_not_found() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+
+        let err = cmd_restore(&handle, "no-such-commit", None, None, None)
+            .await
+            .unwrap_err();
+        let err = CliError::classify(&err);
+        assert_eq!(err.code(), "commit_not_found");
+        assert_eq!(err.exit_code(), error::EXIT_COMMIT_NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_restore_exec_pipes_state_to_subprocess() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+        let commit_id = CommitId::from_state(b"restore-exec");
+        let state = json!({ "step": 7, "goal": "ship" });
+        handle
+            .save_snapshot(&commit_id, state.clone())
+            .await
+            .unwrap();
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let received = temp_dir.path().join("received.json");
+        let command = format!("cat > '{}'", received.display());
+
+        cmd_restore(&handle, &commit_id.hash, None, Some(&command), None)
+            .await
+            .unwrap();
+
+        let got: Value =
+            serde_json::from_str(&std::fs::read_to_string(&received).unwrap()).unwrap();
+        assert_eq!(got, state);
+    }
+
+    #[test]
+    fn test_restore_exec_fails_on_nonzero_exit() {
+        let err = exec_restore_hook("cat > /dev/null; exit 3", "abc", "{}", None).unwrap_err();
+        assert!(err.to_string().contains("failed"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_from_stdin_matches_file_snapshot() {
+        let content = r#"{"step": 3, "value": "piped"}"#;
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        // File path
+        let file_handle = SurrealHandle::setup_db().await.unwrap();
+        let state_path = temp_dir.path().join("state.json");
+        std::fs::write(&state_path, content).unwrap();
+        let file_cas = temp_dir.path().join("cas-file");
+        cmd_snapshot(
+            &file_handle,
+            &state_path,
+            "snap",
+            "agent",
+            "main",
+            Some("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+            Some(&file_cas),
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+
+        // `--state -`
+        let stdin_handle = SurrealHandle::setup_db().await.unwrap();
+        let mut stdin = std::io::Cursor::new(content.as_bytes().to_vec());
+        let piped = read_state_input(std::path::Path::new("-"), &mut stdin).unwrap();
+        let stdin_cas = temp_dir.path().join("cas-stdin");
+        snapshot_state_content(
+            &stdin_handle,
+            &piped,
+            "<stdin>",
+            "snap",
+            "agent",
+            "main",
+            Some("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+            Some(&stdin_cas),
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let file_head = file_handle.get_branch_head("main").await.unwrap();
+        let stdin_head = stdin_handle.get_branch_head("main").await.unwrap();
+        assert_eq!(file_head, stdin_head);
+
+        let digest = aivcs_core::Digest::compute(content.as_bytes());
+        for cas_root in [&file_cas, &stdin_cas] {
+            let cas = aivcs_core::FsCasStore::new(cas_root).unwrap();
+            let blob = aivcs_core::CasStore::get(&cas, &digest).unwrap();
+            assert_eq!(blob, content.as_bytes());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_identical_snapshot_reports_dedup_and_can_be_skipped() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let state_path = temp_dir.path().join("state.json");
+        std::fs::write(&state_path, r#"{"step": 1}"#).unwrap();
+        let cas_dir = temp_dir.path().join("cas");
+        let snapshot = |skip| {
+            cmd_snapshot(
+                &handle,
+                &state_path,
+                "snap",
+                "agent",
+                "main",
+                Some("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+                Some(&cas_dir),
+                skip,
+                None,
+            )
+        };
+
+        let first = snapshot(false).await.unwrap();
+        assert!(!first.deduplicated);
+        assert_eq!(first.unchanged_from, None);
+        let head = first.commit.clone().unwrap();
+
+        let second = snapshot(true).await.unwrap();
+        assert!(second.deduplicated);
+        assert_eq!(second.unchanged_from.as_deref(), Some(head.as_str()));
+        assert_eq!(second.commit, None);
+        assert_eq!(handle.get_branch_head("main").await.unwrap(), head);
+        assert_eq!(handle.list_commits().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_records_its_branch() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let outcome = snapshot_state_content(
+            &handle,
+            r#"{"step": "feature work"}"#,
+            "<stdin>",
+            "snap",
+            "agent",
+            "feature",
+            Some("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+            Some(&temp_dir.path().join("cas")),
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+        let hash = outcome.commit.unwrap();
+
+        let commit = handle.get_commit(&hash).await.unwrap().unwrap();
+        assert_eq!(commit.branch.as_deref(), Some("feature"));
+
+        let on_feature = handle.list_commits_on_branch("feature").await.unwrap();
+        assert_eq!(on_feature.len(), 1);
+        assert_eq!(on_feature[0].commit_id.hash, hash);
+        assert!(handle
+            .list_commits_on_branch("main")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_snapshots_keep_both_commits() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let cas_dir = temp_dir.path().join("cas");
+        let snapshot = |content: &'static str| {
+            snapshot_state_content(
+                &handle,
+                content,
+                "<stdin>",
+                "snap",
+                "agent",
+                "main",
+                Some("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+                Some(&cas_dir),
+                false,
+                None,
+            )
+        };
+
+        let base = snapshot(r#"{"step": 0}"#).await.unwrap().commit.unwrap();
+        let (a, b) = tokio::join!(snapshot(r#"{"step": "a"}"#), snapshot(r#"{"step": "b"}"#));
+        let (a, b) = (a.unwrap().commit.unwrap(), b.unwrap().commit.unwrap());
+
+        let head = handle.get_branch_head("main").await.unwrap();
+        let history: Vec<String> = handle
+            .get_commit_history(&head, 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| c.commit_id.hash)
+            .collect();
+        assert_eq!(history.len(), 3, "{history:?}");
+        assert!(history.contains(&a) && history.contains(&b));
+        assert_eq!(history.last(), Some(&base));
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_rejects_invalid_json_before_commit() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let result = snapshot_state_content(
+            &handle,
+            "not json",
+            "<stdin>",
+            "snap",
+            "agent",
+            "main",
+            Some("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+            Some(&temp_dir.path().join("cas")),
+            false,
+            None,
+        )
+        .await;
+        assert!(result.is_err());
+        assert!(handle.get_branch("main").await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,73 @@
+//! `aivcs stash` — setting agent state aside without committing it.
+
+use anyhow::{Context, Result};
+use oxidized_state::SurrealHandle;
+
+use aivcs_core::short_hash;
+
+use crate::open_cas;
+use crate::snapshot::read_state_input;
+
+/// Stash state from `state_path` (or stdin for `-`) under `name`
+pub(crate) async fn cmd_stash_save(
+    handle: &SurrealHandle,
+    state_path: &std::path::Path,
+    name: Option<&str>,
+    message: &str,
+    cas_dir: Option<&std::path::Path>,
+) -> Result<()> {
+    let state_content = read_state_input(state_path, &mut std::io::stdin().lock())?;
+    let name = name
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("stash-{}", chrono::Utc::now().format("%Y%m%d%H%M%S")));
+    let cas = open_cas(cas_dir)?;
+    let record = aivcs_core::stash_save(handle, &cas, &name, &state_content, message).await?;
+
+    println!(
+        "Saved stash '{}': {} ({})",
+        record.name,
+        record.message,
+        short_hash(&record.cas_digest)
+    );
+    Ok(())
+}
+
+/// List stashes, newest first
+pub(crate) async fn cmd_stash_list(handle: &SurrealHandle) -> Result<()> {
+    let stashes = handle.list_stashes().await?;
+    if stashes.is_empty() {
+        println!("No stashes.");
+        return Ok(());
+    }
+
+    for stash in stashes {
+        println!(
+            "{}  {}  {}  {}",
+            stash.name,
+            stash.created_at.format("%Y-%m-%d %H:%M:%S"),
+            short_hash(&stash.cas_digest),
+            stash.message
+        );
+    }
+    Ok(())
+}
+
+/// Restore the stash `name` to `output` (or stdout) and drop it
+pub(crate) async fn cmd_stash_pop(
+    handle: &SurrealHandle,
+    name: &str,
+    output: Option<&std::path::Path>,
+    cas_dir: Option<&std::path::Path>,
+) -> Result<()> {
+    let cas = open_cas(cas_dir)?;
+    let (record, state) = aivcs_core::stash_pop(handle, &cas, name).await?;
+    let state_json = serde_json::to_string_pretty(&state)?;
+
+    if let Some(path) = output {
+        std::fs::write(path, &state_json).context(format!("Failed to write to {:?}", path))?;
+        println!("Popped stash '{}' to {:?}", record.name, path);
+    } else {
+        println!("{}", state_json);
+    }
+    Ok(())
+}
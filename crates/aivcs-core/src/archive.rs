@@ -0,0 +1,109 @@
+//! Run retention: archiving old runs from the ledger into CAS
+//!
+//! An archived run is serialized as a [`RunArchive`] blob (record plus
+//! events) into CAS. The ledger records the blob's digest in its
+//! `archived_runs` index and drops the run from its hot tables, so long-lived
+//! ledgers stay small while old runs remain replayable through
+//! [`crate::replay::replay_run_with_archive`].
+
+use chrono::{DateTime, Utc};
+use oxidized_state::{RunEvent, RunId, RunLedger, RunRecord, RunStatus};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::cas::{CasStore, Digest};
+use crate::domain::{AivcsError, Result};
+
+/// A run as stored in CAS once archived
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunArchive {
+    pub record: RunRecord,
+    pub events: Vec<RunEvent>,
+}
+
+/// One run moved out of the ledger
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchivedRun {
+    pub run_id: String,
+    /// CAS digest of the run's [`RunArchive`] blob, as the store prints it
+    pub digest: String,
+}
+
+/// Outcome of [`archive_runs`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArchiveReport {
+    /// Runs archived, oldest first
+    pub archived: Vec<ArchivedRun>,
+    /// Runs older than the cutoff left in place because they are still running
+    pub skipped_running: usize,
+}
+
+/// Archive every terminal run that ended before `older_than`.
+///
+/// A run's age is taken from `completed_at`, or `created_at` if it has none.
+/// Each run is written to CAS before it is removed from the ledger, so a
+/// failure part-way leaves every run either in the ledger or archived.
+pub async fn archive_runs(
+    ledger: &dyn RunLedger,
+    cas: &dyn CasStore,
+    older_than: DateTime<Utc>,
+) -> Result<ArchiveReport> {
+    let mut runs = ledger.list_runs(None).await.map_err(storage_err)?;
+    runs.sort_by_key(|run| run.completed_at.unwrap_or(run.created_at));
+
+    let mut report = ArchiveReport::default();
+    for record in runs {
+        if record.completed_at.unwrap_or(record.created_at) >= older_than {
+            continue;
+        }
+        if record.status == RunStatus::Running {
+            report.skipped_running += 1;
+            continue;
+        }
+
+        let events = ledger
+            .get_events(&record.run_id)
+            .await
+            .map_err(storage_err)?;
+        let run_id = record.run_id.clone();
+        let blob = serde_json::to_vec(&RunArchive { record, events })?;
+        let digest = cas.put(&blob).map_err(cas_err)?.to_string();
+
+        ledger
+            .archive_run(&run_id, &digest)
+            .await
+            .map_err(storage_err)?;
+        info!("archived run {} ({})", run_id, digest);
+        report.archived.push(ArchivedRun {
+            run_id: run_id.0,
+            digest,
+        });
+    }
+    Ok(report)
+}
+
+/// Load an archived run from CAS, or `None` if `run_id` was never archived.
+pub async fn load_archived_run(
+    ledger: &dyn RunLedger,
+    cas: &dyn CasStore,
+    run_id: &RunId,
+) -> Result<Option<RunArchive>> {
+    let Some(digest) = ledger
+        .archived_run_digest(run_id)
+        .await
+        .map_err(storage_err)?
+    else {
+        return Ok(None);
+    };
+    let digest: Digest = digest.parse().map_err(cas_err)?;
+    let blob = cas.get(&digest).map_err(cas_err)?;
+    Ok(Some(serde_json::from_slice(&blob)?))
+}
+
+fn storage_err(e: oxidized_state::StorageError) -> AivcsError {
+    AivcsError::StorageError(e.to_string())
+}
+
+fn cas_err(e: crate::cas::CasError) -> AivcsError {
+    AivcsError::StorageError(format!("run archive: {e}"))
+}
@@ -0,0 +1,362 @@
+//! Repository bundles for offline transfer and backup
+//!
+//! A bundle is a tar archive holding every commit, snapshot, branch, memory
+//! and graph edge of a repository plus the CAS blobs its commits reference,
+//! similar to `git bundle`:
+//!
+//! ```text
+//! manifest.json
+//! commits.json
+//! snapshots.json
+//! branches.json
+//! memories.json
+//! graph_edges.json
+//! cas/<sha256-hex>
+//! ```
+//!
+//! Importing skips records that already exist in the target (commits and
+//! snapshots by commit hash, memories by commit and key, edges by endpoints,
+//! blobs by digest), so re-importing a bundle is a no-op. Existing branches
+//! are only moved when the bundled head fast-forwards them.
+//!
+//! [`export_bundle_since`] writes an incremental bundle holding only commits
+//! not reachable from a base commit; importing it requires the target to
+//! already contain that base.
+
+use std::collections::{BTreeSet, HashSet};
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use oxidized_state::{BranchRecord, CommitRecord, GraphEdge, MemoryRecord, SnapshotRecord};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tracing::{info, instrument, warn};
+
+use crate::cas::{CasStore, Digest};
+use crate::progress::{NoProgress, Progress};
+use crate::SurrealHandle;
+
+/// Format version written to `manifest.json`
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// Archive entries other than CAS blobs: the manifest and five record files
+const RECORD_ENTRIES: usize = 6;
+
+/// Header entry describing a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BundleManifest {
+    format_version: u32,
+    created_at: chrono::DateTime<chrono::Utc>,
+    /// Base commit of an incremental bundle
+    #[serde(default)]
+    since: Option<String>,
+}
+
+/// Record counts for an export, or records actually written by an import
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleSummary {
+    pub commits: usize,
+    pub snapshots: usize,
+    pub branches: usize,
+    pub memories: usize,
+    pub graph_edges: usize,
+    pub blobs: usize,
+}
+
+/// Write the whole repository in `handle` (and referenced blobs in `cas`) to `out`
+#[instrument(skip(handle, cas))]
+pub async fn export_bundle(
+    handle: &SurrealHandle,
+    cas: &dyn CasStore,
+    out: &Path,
+) -> Result<BundleSummary> {
+    write_bundle(handle, cas, out, None, None, &NoProgress).await
+}
+
+/// Write only the commits reachable from branch heads but not from `since`
+///
+/// Snapshots, memories, edges and blobs are limited to those commits, and
+/// only branches whose head is among them are included.
+#[instrument(skip(handle, cas))]
+pub async fn export_bundle_since(
+    handle: &SurrealHandle,
+    cas: &dyn CasStore,
+    out: &Path,
+    since: &str,
+) -> Result<BundleSummary> {
+    export_bundle_with_progress(handle, cas, out, Some(since), &NoProgress).await
+}
+
+/// [`export_bundle`], or [`export_bundle_since`] when `since` is given,
+/// reporting each archive entry written to `progress`
+#[instrument(skip(handle, cas, progress))]
+pub async fn export_bundle_with_progress(
+    handle: &SurrealHandle,
+    cas: &dyn CasStore,
+    out: &Path,
+    since: Option<&str>,
+    progress: &dyn Progress,
+) -> Result<BundleSummary> {
+    if let Some(since) = since {
+        if handle.get_commit(since).await?.is_none() {
+            bail!("commit not found: {since}");
+        }
+    }
+    write_bundle(handle, cas, out, since, None, progress).await
+}
+
+/// Write a bundle limited to commits reachable from `branch` (and not from
+/// `since`, if given)
+///
+/// Branch-scoped bundles carry no branch records; the caller decides how to
+/// move the ref after import (see [`crate::remote`]).
+pub(crate) async fn export_branch_bundle(
+    handle: &SurrealHandle,
+    cas: &dyn CasStore,
+    out: &Path,
+    branch: &str,
+    since: Option<&str>,
+) -> Result<BundleSummary> {
+    write_bundle(handle, cas, out, since, Some(branch), &NoProgress).await
+}
+
+async fn write_bundle(
+    handle: &SurrealHandle,
+    cas: &dyn CasStore,
+    out: &Path,
+    since: Option<&str>,
+    only_branch: Option<&str>,
+    progress: &dyn Progress,
+) -> Result<BundleSummary> {
+    let mut commits = handle.list_commits().await?;
+    let mut snapshots = handle.list_snapshot_records().await?;
+    let mut branches = handle.list_branches().await?;
+    let mut memories = handle.list_all_memories().await?;
+    let mut edges = handle.list_graph_edges().await?;
+
+    if since.is_some() || only_branch.is_some() {
+        if let Some(name) = only_branch {
+            branches.retain(|b| b.name == name);
+        }
+        let mut wanted = HashSet::new();
+        for branch in &branches {
+            wanted.extend(handle.get_ancestors(&branch.head_commit_id).await?);
+        }
+        if let Some(since) = since {
+            let base = handle.get_ancestors(since).await?;
+            wanted.retain(|id| !base.contains(id));
+        }
+
+        commits.retain(|c| wanted.contains(&c.commit_id.hash));
+        snapshots.retain(|s| wanted.contains(&s.commit_id));
+        branches.retain(|b| only_branch.is_none() && wanted.contains(&b.head_commit_id));
+        memories.retain(|m| wanted.contains(&m.commit_id));
+        edges.retain(|e| wanted.contains(&e.child_id));
+    }
+
+    // Record ids are local to the source database
+    commits.iter_mut().for_each(|r| r.id = None);
+    snapshots.iter_mut().for_each(|r| r.id = None);
+    branches.iter_mut().for_each(|r| r.id = None);
+    memories.iter_mut().for_each(|r| r.id = None);
+
+    // Commit state hashes double as CAS digests for snapshots taken via the CLI
+    let mut blobs = BTreeSet::new();
+    for commit in &commits {
+        if let Ok(digest) = commit.commit_id.state_hash.parse::<Digest>() {
+            if cas.exists(&digest)? {
+                blobs.insert(digest);
+            }
+        }
+    }
+
+    let file = std::fs::File::create(out)
+        .with_context(|| format!("failed to create bundle {}", out.display()))?;
+    let mut tar = tar::Builder::new(file);
+
+    let manifest = BundleManifest {
+        format_version: BUNDLE_FORMAT_VERSION,
+        created_at: chrono::Utc::now(),
+        since: since.map(String::from),
+    };
+    progress.start("bundle export", RECORD_ENTRIES + blobs.len());
+    let written = (|| -> Result<()> {
+        append_json(&mut tar, "manifest.json", &manifest)?;
+        append_json(&mut tar, "commits.json", &commits)?;
+        append_json(&mut tar, "snapshots.json", &snapshots)?;
+        append_json(&mut tar, "branches.json", &branches)?;
+        append_json(&mut tar, "memories.json", &memories)?;
+        append_json(&mut tar, "graph_edges.json", &edges)?;
+        progress.inc(RECORD_ENTRIES);
+        for digest in &blobs {
+            let data = cas.get(digest)?;
+            append_bytes(&mut tar, &format!("cas/{}", digest.to_hex()), &data)?;
+            progress.inc(1);
+        }
+        tar.finish()?;
+        Ok(())
+    })();
+    progress.finish();
+    written?;
+
+    let summary = BundleSummary {
+        commits: commits.len(),
+        snapshots: snapshots.len(),
+        branches: branches.len(),
+        memories: memories.len(),
+        graph_edges: edges.len(),
+        blobs: blobs.len(),
+    };
+    info!(?summary, "bundle exported to {}", out.display());
+    Ok(summary)
+}
+
+/// Restore a bundle written by [`export_bundle`] into `handle` and `cas`
+///
+/// Fails without writing anything if a bundled commit's parent is neither in
+/// the bundle nor in the target. Returns counts of newly written records;
+/// existing ones are left untouched apart from fast-forwarded branches.
+#[instrument(skip(handle, cas))]
+pub async fn import_bundle(
+    handle: &SurrealHandle,
+    cas: &dyn CasStore,
+    input: &Path,
+) -> Result<BundleSummary> {
+    let file = std::fs::File::open(input)
+        .with_context(|| format!("failed to open bundle {}", input.display()))?;
+    let mut archive = tar::Archive::new(file);
+
+    let mut manifest: Option<BundleManifest> = None;
+    let mut commits: Vec<CommitRecord> = Vec::new();
+    let mut snapshots: Vec<SnapshotRecord> = Vec::new();
+    let mut branches: Vec<BranchRecord> = Vec::new();
+    let mut memories: Vec<MemoryRecord> = Vec::new();
+    let mut edges: Vec<GraphEdge> = Vec::new();
+    let mut blobs: Vec<(Digest, Vec<u8>)> = Vec::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.to_string_lossy().into_owned();
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+
+        match path.as_str() {
+            "manifest.json" => manifest = Some(parse_json(&path, &data)?),
+            "commits.json" => commits = parse_json(&path, &data)?,
+            "snapshots.json" => snapshots = parse_json(&path, &data)?,
+            "branches.json" => branches = parse_json(&path, &data)?,
+            "memories.json" => memories = parse_json(&path, &data)?,
+            "graph_edges.json" => edges = parse_json(&path, &data)?,
+            other => {
+                let Some(hex) = other.strip_prefix("cas/") else {
+                    bail!("unexpected bundle entry: {other}");
+                };
+                let expected: Digest = hex.parse()?;
+                if Digest::compute_with(expected.algo(), &data) != expected {
+                    bail!("bundle blob {hex} does not match its digest");
+                }
+                blobs.push((expected, data));
+            }
+        }
+    }
+
+    let manifest = manifest.context("bundle is missing manifest.json")?;
+    if manifest.format_version != BUNDLE_FORMAT_VERSION {
+        bail!(
+            "unsupported bundle format version {} (expected {})",
+            manifest.format_version,
+            BUNDLE_FORMAT_VERSION
+        );
+    }
+
+    // Every parent must be bundled or already present before anything is written
+    let bundled: HashSet<&str> = commits.iter().map(|c| c.commit_id.hash.as_str()).collect();
+    for commit in &commits {
+        for parent in &commit.parent_ids {
+            if !bundled.contains(parent.as_str()) && handle.get_commit(parent).await?.is_none() {
+                bail!(
+                    "bundle commit {} references parent {} missing from the target{}",
+                    commit.commit_id.short(),
+                    parent,
+                    manifest
+                        .since
+                        .as_deref()
+                        .map(|s| format!(" (bundle was exported --since {s})"))
+                        .unwrap_or_default()
+                );
+            }
+        }
+    }
+
+    let mut summary = BundleSummary::default();
+    for (digest, data) in &blobs {
+        if !cas.exists(digest)? {
+            cas.put(data)?;
+            summary.blobs += 1;
+        }
+    }
+    for commit in &commits {
+        summary.commits += handle.import_commit(commit).await? as usize;
+    }
+    for snapshot in &snapshots {
+        summary.snapshots += handle.import_snapshot_record(snapshot).await? as usize;
+    }
+    for edge in &edges {
+        summary.graph_edges += handle.import_graph_edge(edge).await? as usize;
+    }
+    for memory in &memories {
+        summary.memories += handle.import_memory(memory).await? as usize;
+    }
+    for branch in &branches {
+        let update = match handle.get_branch(&branch.name).await? {
+            None => true,
+            Some(existing) if existing.head_commit_id == branch.head_commit_id => false,
+            Some(existing) => {
+                let fast_forward = handle
+                    .get_ancestors(&branch.head_commit_id)
+                    .await?
+                    .contains(&existing.head_commit_id);
+                if !fast_forward {
+                    warn!(
+                        "not moving diverged branch '{}' ({} -> {})",
+                        branch.name, existing.head_commit_id, branch.head_commit_id
+                    );
+                }
+                fast_forward
+            }
+        };
+        if update {
+            handle.save_branch(branch).await?;
+            summary.branches += 1;
+        }
+    }
+
+    info!(?summary, "bundle imported from {}", input.display());
+    Ok(summary)
+}
+
+fn append_json<W: std::io::Write, T: Serialize>(
+    tar: &mut tar::Builder<W>,
+    name: &str,
+    value: &T,
+) -> Result<()> {
+    let data = serde_json::to_vec_pretty(value)?;
+    append_bytes(tar, name, &data)
+}
+
+fn append_bytes<W: std::io::Write>(
+    tar: &mut tar::Builder<W>,
+    name: &str,
+    data: &[u8],
+) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    tar.append_data(&mut header, name, data)?;
+    Ok(())
+}
+
+fn parse_json<T: DeserializeOwned>(name: &str, data: &[u8]) -> Result<T> {
+    serde_json::from_slice(data).with_context(|| format!("invalid {name} in bundle"))
+}
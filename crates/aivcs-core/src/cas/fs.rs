@@ -2,15 +2,45 @@ use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 
+use serde::Serialize;
 use tempfile::NamedTempFile;
 
-use super::{CasError, CasStore, Digest, Result};
+use super::{CasError, CasStore, Digest, DigestAlgo, Result};
+
+/// Size summary of an [`FsCasStore`].
+///
+/// Blobs are stored uncompressed as loose objects, so on-disk bytes equal
+/// content bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CasStats {
+    /// Number of distinct blobs; deduplicated puts count once
+    pub blob_count: u64,
+    /// Total size of all blobs on disk, in bytes
+    pub total_bytes: u64,
+    /// Mean blob size in bytes (0 for an empty store)
+    pub average_blob_bytes: u64,
+}
+
+/// Default number of 2-hex-char directory levels above each blob.
+pub const DEFAULT_SHARD_DEPTH: usize = 1;
+
+/// Deepest supported sharding; 4 levels already allow 2^32 leaf directories.
+pub const MAX_SHARD_DEPTH: usize = 4;
 
 /// Filesystem-backed content-addressed store with git-style 2-char sharding.
 ///
-/// Layout: `<root>/objects/<first 2 hex chars>/<remaining hex chars>`
+/// Layout: `<root>/objects/<first 2 hex chars>/<remaining hex chars>` for
+/// SHA-256 blobs; other algorithms live under `<root>/objects/<prefix>/...`
+/// so a store can hold blobs of mixed algorithms.
+///
+/// [`FsCasStore::with_shard_depth`] sets how many 2-char levels precede the
+/// blob: 0 is flat (`objects/<hex>`), 2 is `objects/ab/cd/<rest>`. The depth
+/// is not recorded on disk, so every handle on a store must use the same
+/// one; [`FsCasStore::reshard_from`] moves blobs between layouts.
 pub struct FsCasStore {
     objects_dir: PathBuf,
+    algo: DigestAlgo,
+    shard_depth: usize,
 }
 
 impl FsCasStore {
@@ -18,18 +48,172 @@ impl FsCasStore {
     pub fn new(root: impl AsRef<Path>) -> Result<Self> {
         let objects_dir = root.as_ref().join("objects");
         fs::create_dir_all(&objects_dir)?;
-        Ok(Self { objects_dir })
+        Ok(Self {
+            objects_dir,
+            algo: DigestAlgo::default(),
+            shard_depth: DEFAULT_SHARD_DEPTH,
+        })
+    }
+
+    /// Store and look up blobs under `depth` levels of 2-char directories.
+    ///
+    /// # Panics
+    ///
+    /// If `depth` exceeds [`MAX_SHARD_DEPTH`].
+    pub fn with_shard_depth(mut self, depth: usize) -> Self {
+        assert!(
+            depth <= MAX_SHARD_DEPTH,
+            "shard depth {depth} exceeds {MAX_SHARD_DEPTH}"
+        );
+        self.shard_depth = depth;
+        self
+    }
+
+    /// Move blobs laid out with `from_depth` levels of sharding into this
+    /// store's layout, returning how many blobs were migrated.
+    ///
+    /// Blobs already present in the new layout are kept and their old copy
+    /// removed. Directories the move empties are deleted. Run it once, with
+    /// no other writers, after changing a store's shard depth.
+    pub fn reshard_from(&self, from_depth: usize) -> Result<usize> {
+        if from_depth == self.shard_depth {
+            return Ok(0);
+        }
+        let blobs = self.blobs_at(from_depth)?;
+        for (digest, old_path) in &blobs {
+            let new_path = self.blob_path(digest);
+            if new_path.exists() {
+                fs::remove_file(old_path)?;
+            } else {
+                if let Some(dir) = new_path.parent() {
+                    fs::create_dir_all(dir)?;
+                }
+                fs::rename(old_path, &new_path)?;
+            }
+        }
+        for (_, old_path) in &blobs {
+            // Stops at the first directory that still holds something
+            for dir in old_path.ancestors().skip(1) {
+                if dir == self.objects_dir || fs::remove_dir(dir).is_err() {
+                    break;
+                }
+            }
+        }
+        Ok(blobs.len())
+    }
+
+    /// Use `algo` for digests of newly stored blobs. Lookups always follow
+    /// the algorithm of the digest being looked up.
+    pub fn with_algo(mut self, algo: DigestAlgo) -> Self {
+        self.algo = algo;
+        self
+    }
+
+    /// Re-hash every stored blob and return the digests whose content no
+    /// longer matches their address.
+    ///
+    /// The store only holds loose objects, so this covers all of it.
+    pub fn verify_all(&self) -> Result<Vec<Digest>> {
+        let mut corrupt = Vec::new();
+        for (digest, path) in self.blobs()? {
+            let data = fs::read(&path)?;
+            if Digest::compute_with(digest.algo(), &data) != digest {
+                corrupt.push(digest);
+            }
+        }
+        Ok(corrupt)
+    }
+
+    /// Count the stored blobs and their size on disk.
+    pub fn stats(&self) -> Result<CasStats> {
+        let mut stats = CasStats::default();
+        for (_, path) in self.blobs()? {
+            stats.blob_count += 1;
+            stats.total_bytes += fs::metadata(&path)?.len();
+        }
+        if stats.blob_count > 0 {
+            stats.average_blob_bytes = stats.total_bytes / stats.blob_count;
+        }
+        Ok(stats)
+    }
+
+    /// Every stored blob with its path, in no particular order.
+    fn blobs(&self) -> Result<Vec<(Digest, PathBuf)>> {
+        self.blobs_at(self.shard_depth)
+    }
+
+    /// Every blob stored with `depth` levels of sharding. Entries whose path
+    /// doesn't spell a digest (e.g. leftover temp files) are skipped.
+    fn blobs_at(&self, depth: usize) -> Result<Vec<(Digest, PathBuf)>> {
+        let mut blobs = Vec::new();
+        collect_blobs(&self.objects_dir, depth, None, "", &mut blobs)?;
+        for entry in fs::read_dir(&self.objects_dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if entry.file_type()?.is_dir() && DigestAlgo::from_prefix(&name).is_some() {
+                collect_blobs(&entry.path(), depth, Some(&name), "", &mut blobs)?;
+            }
+        }
+        Ok(blobs)
     }
 
     fn blob_path(&self, digest: &Digest) -> PathBuf {
-        let hex = digest.to_hex();
-        self.objects_dir.join(&hex[..2]).join(&hex[2..])
+        let hex = hex::encode(digest.as_bytes());
+        let mut path = match digest.algo().prefix() {
+            Some(prefix) => self.objects_dir.join(prefix),
+            None => self.objects_dir.clone(),
+        };
+        for level in 0..self.shard_depth {
+            path.push(&hex[2 * level..2 * level + 2]);
+        }
+        path.push(&hex[2 * self.shard_depth..]);
+        path
     }
 }
 
+/// Add the blobs below `dir` to `blobs`, descending `depth` levels of
+/// `<2 hex chars>` shard directories. `hex` is the digest text spelled by
+/// the shards above `dir`.
+fn collect_blobs(
+    dir: &Path,
+    depth: usize,
+    prefix: Option<&str>,
+    hex: &str,
+    blobs: &mut Vec<(Digest, PathBuf)>,
+) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let is_dir = entry.file_type()?.is_dir();
+        if depth > 0 {
+            if is_dir && name.len() == 2 {
+                collect_blobs(
+                    &entry.path(),
+                    depth - 1,
+                    prefix,
+                    &format!("{hex}{name}"),
+                    blobs,
+                )?;
+            }
+            continue;
+        }
+        if is_dir {
+            continue;
+        }
+        let text = match prefix {
+            Some(prefix) => format!("{prefix}:{hex}{name}"),
+            None => format!("{hex}{name}"),
+        };
+        if let Ok(digest) = text.parse::<Digest>() {
+            blobs.push((digest, entry.path()));
+        }
+    }
+    Ok(())
+}
+
 impl CasStore for FsCasStore {
     fn put(&self, data: &[u8]) -> Result<Digest> {
-        let digest = Digest::compute(data);
+        let digest = Digest::compute_with(self.algo, data);
         let path = self.blob_path(&digest);
 
         if path.exists() {
@@ -45,6 +229,9 @@ impl CasStore for FsCasStore {
         fs::create_dir_all(shard_dir)?;
 
         // Atomic write: write to temp file in the same directory, then rename.
+        // Readers and concurrent writers only ever see a missing or complete
+        // blob; a rename racing another put of the same content replaces one
+        // complete copy with an identical one.
         let mut tmp = NamedTempFile::new_in(shard_dir)?;
         tmp.write_all(data)?;
 
@@ -53,6 +240,9 @@ impl CasStore for FsCasStore {
         loop {
             match tmp.persist(&path) {
                 Ok(_) => break,
+                // Another writer stored the blob first (Windows refuses to
+                // rename over it); dropping the temp file removes it.
+                Err(_) if path.exists() => break,
                 Err(e) if attempts < 3 => {
                     attempts += 1;
                     // Check if it's a known transient error in WSL/9p
@@ -93,6 +283,14 @@ impl CasStore for FsCasStore {
         let path = self.blob_path(digest);
         Ok(path.exists())
     }
+
+    fn delete(&self, digest: &Digest) -> Result<bool> {
+        match fs::remove_file(self.blob_path(digest)) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(CasError::Io(e)),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -184,4 +382,155 @@ mod tests {
         let got = store.get(&digest).unwrap();
         assert_eq!(got, data);
     }
+
+    #[test]
+    fn stats_count_deduplicated_blobs_once() {
+        let (_dir, store) = make_store();
+        assert_eq!(store.stats().unwrap(), CasStats::default());
+
+        store.put(b"aaaa").unwrap();
+        store.put(b"bb").unwrap();
+        store.put(b"aaaa").unwrap();
+        store.put(b"").unwrap();
+
+        let stats = store.stats().unwrap();
+        assert_eq!(stats.blob_count, 3);
+        assert_eq!(stats.total_bytes, 6);
+        assert_eq!(stats.average_blob_bytes, 2);
+    }
+
+    #[test]
+    fn verify_all_flags_corrupted_blobs() {
+        let (_dir, store) = make_store();
+        let intact = store.put(b"intact").unwrap();
+        let damaged = store.put(b"will rot").unwrap();
+        assert!(store.verify_all().unwrap().is_empty());
+
+        std::fs::write(store.blob_path(&damaged), b"bit rot").unwrap();
+        // Stray files in the store are not mistaken for blobs
+        std::fs::write(store.blob_path(&intact).with_file_name(".tmpXYZ"), b"x").unwrap();
+
+        assert_eq!(store.verify_all().unwrap(), vec![damaged]);
+        assert_eq!(store.get(&intact).unwrap(), b"intact");
+    }
+
+    #[test]
+    fn concurrent_puts_of_the_same_blob_leave_one_complete_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let data: Vec<u8> = (0..4_000_000u32).map(|i| (i % 251) as u8).collect();
+        let threads = 8;
+        let barrier = std::sync::Barrier::new(threads);
+
+        let digests: Vec<Digest> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        // Separate handles, as separate processes would have
+                        let store = FsCasStore::new(dir.path()).unwrap();
+                        barrier.wait();
+                        store.put(&data).unwrap()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        let expected = Digest::compute(&data);
+        assert!(digests.iter().all(|d| *d == expected));
+        let store = FsCasStore::new(dir.path()).unwrap();
+        let shard = store.blob_path(&expected).parent().unwrap().to_path_buf();
+        let entries: Vec<_> = std::fs::read_dir(shard).unwrap().collect();
+        assert_eq!(entries.len(), 1, "temp files left behind");
+        assert_eq!(store.get(&expected).unwrap(), data);
+        assert!(store.verify_all().unwrap().is_empty());
+    }
+
+    #[test]
+    fn deeper_sharding_nests_blobs_and_keeps_lookups_consistent() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FsCasStore::new(dir.path()).unwrap().with_shard_depth(2);
+
+        let digest = store.put(b"sharded twice").unwrap();
+        let hex = digest.to_hex();
+        let expected = dir
+            .path()
+            .join("objects")
+            .join(&hex[..2])
+            .join(&hex[2..4])
+            .join(&hex[4..]);
+        assert!(expected.is_file());
+        assert_eq!(store.get(&digest).unwrap(), b"sharded twice");
+        assert!(store.exists(&digest).unwrap());
+        assert_eq!(store.stats().unwrap().blob_count, 1);
+        assert!(store.verify_all().unwrap().is_empty());
+
+        // A handle with a different depth doesn't see the blob
+        let default_depth = FsCasStore::new(dir.path()).unwrap();
+        assert!(!default_depth.exists(&digest).unwrap());
+    }
+
+    #[test]
+    fn flat_store_reshards_into_nested_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let flat = FsCasStore::new(dir.path()).unwrap().with_shard_depth(0);
+        let digests: Vec<Digest> = [&b"one"[..], b"two", b"three"]
+            .iter()
+            .map(|data| flat.put(data).unwrap())
+            .collect();
+        let objects = dir.path().join("objects");
+        assert!(objects.join(digests[0].to_hex()).is_file());
+
+        let sharded = FsCasStore::new(dir.path()).unwrap().with_shard_depth(2);
+        // One blob was already written in the new layout
+        sharded.put(b"two").unwrap();
+        assert_eq!(sharded.reshard_from(0).unwrap(), 3);
+        assert_eq!(sharded.reshard_from(2).unwrap(), 0);
+
+        for (digest, data) in digests.iter().zip([&b"one"[..], b"two", b"three"]) {
+            assert_eq!(sharded.get(digest).unwrap(), data);
+            assert!(!flat.exists(digest).unwrap());
+        }
+        assert_eq!(sharded.stats().unwrap().blob_count, 3);
+        assert!(std::fs::read_dir(&objects).unwrap().all(|e| e
+            .unwrap()
+            .file_type()
+            .unwrap()
+            .is_dir()));
+
+        // And back to the default layout, leaving no empty shard dirs behind
+        let default_depth = FsCasStore::new(dir.path()).unwrap();
+        assert_eq!(default_depth.reshard_from(2).unwrap(), 3);
+        assert_eq!(default_depth.get(&digests[2]).unwrap(), b"three");
+        for entry in std::fs::read_dir(&objects).unwrap() {
+            let shard = entry.unwrap().path();
+            assert!(std::fs::read_dir(&shard).unwrap().all(|e| e
+                .unwrap()
+                .file_type()
+                .unwrap()
+                .is_file()));
+        }
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn mixed_algorithm_lookups_find_the_right_blob() {
+        let dir = tempfile::tempdir().unwrap();
+        let sha_store = FsCasStore::new(dir.path()).unwrap();
+        let b3_store = FsCasStore::new(dir.path())
+            .unwrap()
+            .with_algo(DigestAlgo::Blake3);
+
+        let sha = sha_store.put(b"shared content").unwrap();
+        let b3 = b3_store.put(b"blake3 only").unwrap();
+        assert_eq!(sha.algo(), DigestAlgo::Sha256);
+        assert_eq!(b3.algo(), DigestAlgo::Blake3);
+
+        // Either handle resolves digests of either algorithm.
+        for store in [&sha_store, &b3_store] {
+            assert_eq!(store.get(&sha).unwrap(), b"shared content");
+            assert_eq!(store.get(&b3).unwrap(), b"blake3 only");
+        }
+        let b3_missing = Digest::compute_with(DigestAlgo::Blake3, b"shared content");
+        assert!(!sha_store.exists(&b3_missing).unwrap());
+    }
 }
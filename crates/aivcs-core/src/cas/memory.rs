@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use super::{CasError, CasStore, Digest, Result};
+
+/// In-memory content-addressed store, for tests and ephemeral use.
+///
+/// Same digests and dedup semantics as [`FsCasStore`](super::fs::FsCasStore),
+/// without touching the filesystem.
+#[derive(Debug, Default)]
+pub struct MemoryCasStore {
+    blobs: Mutex<HashMap<Digest, Vec<u8>>>,
+}
+
+impl MemoryCasStore {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of distinct blobs stored.
+    pub fn len(&self) -> usize {
+        self.blobs.lock().unwrap().len()
+    }
+
+    /// True if no blobs are stored.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl CasStore for MemoryCasStore {
+    fn put(&self, data: &[u8]) -> Result<Digest> {
+        let digest = Digest::compute(data);
+        self.blobs
+            .lock()
+            .unwrap()
+            .entry(digest)
+            .or_insert_with(|| data.to_vec());
+        Ok(digest)
+    }
+
+    fn get(&self, digest: &Digest) -> Result<Vec<u8>> {
+        self.blobs
+            .lock()
+            .unwrap()
+            .get(digest)
+            .cloned()
+            .ok_or(CasError::NotFound(*digest))
+    }
+
+    fn exists(&self, digest: &Digest) -> Result<bool> {
+        Ok(self.blobs.lock().unwrap().contains_key(digest))
+    }
+
+    fn delete(&self, digest: &Digest) -> Result<bool> {
+        Ok(self.blobs.lock().unwrap().remove(digest).is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cas::fs::FsCasStore;
+
+    #[test]
+    fn blob_roundtrip() {
+        let store = MemoryCasStore::new();
+        let digest = store.put(b"hello world").unwrap();
+        assert_eq!(store.get(&digest).unwrap(), b"hello world");
+    }
+
+    #[test]
+    fn dedupe_invariant() {
+        let store = MemoryCasStore::new();
+        let d1 = store.put(b"duplicate me").unwrap();
+        let d2 = store.put(b"duplicate me").unwrap();
+        assert_eq!(d1, d2);
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn exists_tracks_puts() {
+        let store = MemoryCasStore::new();
+        let missing = Digest::compute(b"missing");
+        assert!(!store.exists(&missing).unwrap());
+        let digest = store.put(b"exists check").unwrap();
+        assert!(store.exists(&digest).unwrap());
+    }
+
+    #[test]
+    fn get_nonexistent_returns_not_found() {
+        let store = MemoryCasStore::new();
+        let fake = Digest::compute(b"no such blob");
+        match store.get(&fake) {
+            Err(CasError::NotFound(d)) => assert_eq!(d, fake),
+            other => panic!("expected NotFound, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn digests_match_fs_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let fs_store = FsCasStore::new(dir.path()).unwrap();
+        let mem_store = MemoryCasStore::new();
+
+        let inputs: [&[u8]; 4] = [b"", b"a", b"{\"step\":1}", &[0xAB; 4096]];
+        for data in inputs {
+            let from_fs = fs_store.put(data).unwrap();
+            let from_mem = mem_store.put(data).unwrap();
+            assert_eq!(from_fs, from_mem);
+            assert_eq!(
+                mem_store.get(&from_fs).unwrap(),
+                fs_store.get(&from_mem).unwrap()
+            );
+        }
+    }
+}
@@ -1,33 +1,87 @@
 pub mod fs;
+pub mod memory;
 
 use std::fmt;
 use std::str::FromStr;
 
+use oxidized_state::ContentDigest;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest as Sha2Digest, Sha256};
 use thiserror::Error;
 
-/// SHA-256 digest used as a content address.
+/// Hash algorithm behind a [`Digest`].
+///
+/// SHA-256 is the default and keeps the bare-hex representation used by
+/// existing stores; other algorithms are written as `<prefix>:<hex>`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DigestAlgo {
+    #[default]
+    Sha256,
+    /// BLAKE3 (requires the `blake3` feature)
+    #[cfg(feature = "blake3")]
+    Blake3,
+}
+
+impl DigestAlgo {
+    /// Prefix used in the textual form, `None` for the SHA-256 default.
+    pub fn prefix(self) -> Option<&'static str> {
+        match self {
+            DigestAlgo::Sha256 => None,
+            #[cfg(feature = "blake3")]
+            DigestAlgo::Blake3 => Some("b3"),
+        }
+    }
+
+    fn from_prefix(prefix: &str) -> Option<Self> {
+        match prefix {
+            #[cfg(feature = "blake3")]
+            "b3" => Some(DigestAlgo::Blake3),
+            _ => None,
+        }
+    }
+}
+
+/// 32-byte digest used as a content address, tagged with its algorithm.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct Digest([u8; 32]);
+pub struct Digest {
+    algo: DigestAlgo,
+    bytes: [u8; 32],
+}
 
 impl Digest {
     /// Compute the SHA-256 digest of `data`.
     pub fn compute(data: &[u8]) -> Self {
-        let hash = Sha256::digest(data);
+        Self::compute_with(DigestAlgo::Sha256, data)
+    }
+
+    /// Compute the digest of `data` with `algo`.
+    pub fn compute_with(algo: DigestAlgo, data: &[u8]) -> Self {
         let mut bytes = [0u8; 32];
-        bytes.copy_from_slice(&hash);
-        Self(bytes)
+        match algo {
+            DigestAlgo::Sha256 => bytes.copy_from_slice(&Sha256::digest(data)),
+            #[cfg(feature = "blake3")]
+            DigestAlgo::Blake3 => bytes = *blake3::hash(data).as_bytes(),
+        }
+        Self { algo, bytes }
+    }
+
+    /// Algorithm that produced this digest.
+    pub fn algo(&self) -> DigestAlgo {
+        self.algo
     }
 
     /// Return the raw bytes.
     pub fn as_bytes(&self) -> &[u8; 32] {
-        &self.0
+        &self.bytes
     }
 
-    /// Hex-encoded string.
+    /// Hex-encoded string, prefixed with the algorithm unless it is SHA-256.
     pub fn to_hex(&self) -> String {
-        hex::encode(self.0)
+        match self.algo.prefix() {
+            None => hex::encode(self.bytes),
+            Some(prefix) => format!("{prefix}:{}", hex::encode(self.bytes)),
+        }
     }
 }
 
@@ -48,13 +102,37 @@ impl FromStr for Digest {
     type Err = CasError;
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        let bytes = hex::decode(s).map_err(|_| CasError::InvalidDigest(s.to_string()))?;
-        if bytes.len() != 32 {
-            return Err(CasError::InvalidDigest(s.to_string()));
+        let invalid = || CasError::InvalidDigest(s.to_string());
+        let (algo, hex_part) = match s.split_once(':') {
+            Some((prefix, rest)) => (DigestAlgo::from_prefix(prefix).ok_or_else(invalid)?, rest),
+            None => (DigestAlgo::Sha256, s),
+        };
+        let bytes = hex::decode(hex_part).map_err(|_| invalid())?;
+        let bytes: [u8; 32] = bytes.try_into().map_err(|_| invalid())?;
+        Ok(Self { algo, bytes })
+    }
+}
+
+impl TryFrom<Digest> for ContentDigest {
+    type Error = CasError;
+
+    /// Fails with [`CasError::InvalidDigest`] for non-SHA-256 digests, which
+    /// the ledger's `ContentDigest` cannot represent.
+    fn try_from(digest: Digest) -> std::result::Result<Self, Self::Error> {
+        if digest.algo != DigestAlgo::Sha256 {
+            return Err(CasError::InvalidDigest(digest.to_hex()));
         }
-        let mut arr = [0u8; 32];
-        arr.copy_from_slice(&bytes);
-        Ok(Self(arr))
+        ContentDigest::try_from(digest.to_hex())
+            .map_err(|_| CasError::InvalidDigest(digest.to_hex()))
+    }
+}
+
+impl TryFrom<ContentDigest> for Digest {
+    type Error = CasError;
+
+    /// Fails with [`CasError::InvalidDigest`] unless the digest is 32 bytes of hex.
+    fn try_from(digest: ContentDigest) -> std::result::Result<Self, Self::Error> {
+        digest.as_str().parse()
     }
 }
 
@@ -75,7 +153,8 @@ pub type Result<T> = std::result::Result<T, CasError>;
 
 /// Content-addressed store interface.
 pub trait CasStore: Send + Sync {
-    /// Store `data` and return its digest. Deduplicates automatically.
+    /// Store `data` and return its digest under the store's algorithm.
+    /// Deduplicates automatically.
     fn put(&self, data: &[u8]) -> Result<Digest>;
 
     /// Retrieve the blob for `digest`.
@@ -83,6 +162,9 @@ pub trait CasStore: Send + Sync {
 
     /// Check whether `digest` exists without reading the blob.
     fn exists(&self, digest: &Digest) -> Result<bool>;
+
+    /// Remove the blob for `digest`. Returns `false` if it wasn't stored.
+    fn delete(&self, digest: &Digest) -> Result<bool>;
 }
 
 #[cfg(test)]
@@ -115,6 +197,60 @@ mod tests {
         assert_eq!(a, b);
     }
 
+    #[test]
+    fn content_digest_roundtrip_preserves_bytes() {
+        let d = Digest::compute(b"ledger to cas");
+        let content = ContentDigest::try_from(d).unwrap();
+        assert_eq!(content, ContentDigest::from_bytes(b"ledger to cas"));
+        let back = Digest::try_from(content).unwrap();
+        assert_eq!(back.as_bytes(), d.as_bytes());
+    }
+
+    #[test]
+    fn content_digest_wrong_length_fails_conversion() {
+        // Deserialization bypasses ContentDigest's own validation
+        let short: ContentDigest = serde_json::from_str("\"abcd\"").unwrap();
+        assert!(matches!(
+            Digest::try_from(short),
+            Err(CasError::InvalidDigest(s)) if s == "abcd"
+        ));
+    }
+
+    #[test]
+    fn sha256_keeps_bare_hex() {
+        let d = Digest::compute(b"compat");
+        assert_eq!(d.algo(), DigestAlgo::Sha256);
+        assert_eq!(d, Digest::compute_with(DigestAlgo::default(), b"compat"));
+        assert!(!d.to_hex().contains(':'));
+    }
+
+    #[test]
+    fn unknown_algorithm_prefix_is_rejected() {
+        let hex = Digest::compute(b"x").to_hex();
+        assert!(format!("md5:{hex}").parse::<Digest>().is_err());
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn blake3_display_fromstr_roundtrip() {
+        let d = Digest::compute_with(DigestAlgo::Blake3, b"hello world");
+        let text = d.to_string();
+        assert!(text.starts_with("b3:"));
+        let parsed: Digest = text.parse().unwrap();
+        assert_eq!(parsed, d);
+        assert_eq!(parsed.algo(), DigestAlgo::Blake3);
+        assert_eq!(d.as_bytes(), blake3::hash(b"hello world").as_bytes());
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn algorithms_do_not_collide() {
+        let sha = Digest::compute(b"same bytes");
+        let b3 = Digest::compute_with(DigestAlgo::Blake3, b"same bytes");
+        assert_ne!(sha, b3);
+        assert!(ContentDigest::try_from(b3).is_err());
+    }
+
     #[test]
     fn digest_different_data_different_hash() {
         let a = Digest::compute(b"data a");
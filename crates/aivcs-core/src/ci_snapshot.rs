@@ -2,26 +2,174 @@
 
 use anyhow::{Context, Result};
 use oxidized_state::CiSnapshot;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-/// Compute deterministic hash of all files in the directory recursively.
-/// Skips common non-source directories (e.g. .git, target, node_modules, .local-ci-cache, .direnv).
+/// Where [`build_ci_snapshot`] keeps per-file digests between runs,
+/// relative to the repo root
+pub const WORKSPACE_HASH_CACHE: &str = ".aivcs/workspace-hashes.json";
+
+/// Files modified this recently are always re-read: a second write within
+/// the filesystem's timestamp granularity could leave size and mtime as
+/// they were
+const RACY_WINDOW: Duration = Duration::from_secs(2);
+
+/// Compute deterministic hash of the workspace's files.
+///
+/// See [`WorkspaceHasher::hash`] for which files count; this variant
+/// starts with an empty digest cache.
 pub fn compute_workspace_hash(dir: &Path) -> Result<String> {
-    let mut files = Vec::new();
-    collect_files_recursive(dir, dir, &mut files)?;
-    files.sort_by(|a, b| a.0.cmp(&b.0)); // sort relative paths deterministically
-
-    let mut hasher = Sha256::new();
-    for (rel_path, abs_path) in files {
-        hasher.update(rel_path.as_bytes());
-        hasher.update(b"\0");
-        if let Ok(content) = std::fs::read(&abs_path) {
-            hasher.update(&content);
+    WorkspaceHasher::new().hash(dir)
+}
+
+/// Digest of one file, reusable while its size and mtime are unchanged
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CachedDigest {
+    len: u64,
+    mtime_nanos: u128,
+    digest: String,
+}
+
+/// Workspace tree hasher that caches per-file digests by size and mtime
+///
+/// Only files whose size or modification time changed since the previous
+/// [`hash`](Self::hash) are read again, so repeated hashing of a large,
+/// mostly unchanged workspace costs one `stat` per file. The cache can be
+/// kept across processes with [`load`](Self::load) and [`save`](Self::save).
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct WorkspaceHasher {
+    files: BTreeMap<String, CachedDigest>,
+    #[serde(skip)]
+    files_read: usize,
+}
+
+impl WorkspaceHasher {
+    /// Hasher with an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hasher with the cache saved at `path`; a missing or unreadable cache
+    /// file gives an empty one
+    pub fn load(path: &Path) -> Self {
+        std::fs::read(path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the cache to `path`, creating its directory
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
         }
-        hasher.update(b"\0");
+        std::fs::write(path, serde_json::to_vec(self)?)
+            .with_context(|| format!("failed to write {}", path.display()))
     }
-    Ok(hex::encode(hasher.finalize()))
+
+    /// Number of files whose contents the last [`hash`](Self::hash) read
+    pub fn files_read(&self) -> usize {
+        self.files_read
+    }
+
+    /// Hash the files of `dir` into one stable tree hash.
+    ///
+    /// In a git work tree the files are those git tracks plus untracked
+    /// ones it doesn't ignore; elsewhere every file except hidden ones and
+    /// common build directories (target, node_modules, dist, .direnv). Paths
+    /// under `target/` or `.aivcs/` never count. The hash covers each
+    /// file's relative path and content digest, in path order.
+    pub fn hash(&mut self, dir: &Path) -> Result<String> {
+        let mut files = match git_listed_files(dir) {
+            Some(files) => files,
+            None => {
+                let mut files = Vec::new();
+                collect_files_recursive(dir, dir, &mut files)?;
+                files
+            }
+        };
+        files.retain(|(rel, _)| {
+            !rel.split('/')
+                .any(|part| part == "target" || part == ".aivcs")
+        });
+        files.sort_by(|a, b| a.0.cmp(&b.0)); // sort relative paths deterministically
+
+        let racy_after = SystemTime::now()
+            .checked_sub(RACY_WINDOW)
+            .unwrap_or(UNIX_EPOCH);
+        let mut cache = BTreeMap::new();
+        let mut hasher = Sha256::new();
+        self.files_read = 0;
+        for (rel_path, abs_path) in files {
+            // Deleted but still in the index
+            let Ok(meta) = std::fs::metadata(&abs_path) else {
+                continue;
+            };
+            if !meta.is_file() {
+                continue;
+            }
+            let mtime = meta.modified().unwrap_or(UNIX_EPOCH);
+            let mtime_nanos = mtime
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or(0);
+            let cached = self.files.get(&rel_path).filter(|c| {
+                c.len == meta.len() && c.mtime_nanos == mtime_nanos && mtime < racy_after
+            });
+            let entry = match cached {
+                Some(entry) => entry.clone(),
+                None => {
+                    let content = std::fs::read(&abs_path)
+                        .with_context(|| format!("failed to read {}", abs_path.display()))?;
+                    self.files_read += 1;
+                    CachedDigest {
+                        len: meta.len(),
+                        mtime_nanos,
+                        digest: hex::encode(Sha256::digest(&content)),
+                    }
+                }
+            };
+            hasher.update(rel_path.as_bytes());
+            hasher.update(b"\0");
+            hasher.update(entry.digest.as_bytes());
+            hasher.update(b"\0");
+            cache.insert(rel_path, entry);
+        }
+        self.files = cache;
+        Ok(hex::encode(hasher.finalize()))
+    }
+}
+
+/// Files git tracks under `dir` plus untracked ones it doesn't ignore, as
+/// `/`-separated paths relative to `dir`; `None` outside a git work tree or
+/// without git
+fn git_listed_files(dir: &Path) -> Option<Vec<(String, PathBuf)>> {
+    let output = std::process::Command::new("git")
+        .args([
+            "ls-files",
+            "-z",
+            "--cached",
+            "--others",
+            "--exclude-standard",
+        ])
+        .current_dir(dir)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let mut files: Vec<(String, PathBuf)> = String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|rel| !rel.is_empty())
+        .map(|rel| (rel.to_string(), dir.join(rel)))
+        .collect();
+    // Unmerged paths are listed once per conflict stage
+    files.dedup_by(|a, b| a.0 == b.0);
+    Some(files)
 }
 
 fn collect_files_recursive(
@@ -53,7 +201,7 @@ fn collect_files_recursive(
             collect_files_recursive(root, &path, files)?;
         } else if path.is_file() {
             if let Ok(rel) = path.strip_prefix(root) {
-                files.push((rel.to_string_lossy().to_string(), path));
+                files.push((rel.to_string_lossy().replace('\\', "/"), path));
             }
         }
     }
@@ -111,8 +259,13 @@ pub fn build_ci_snapshot(repo_root: &Path) -> Result<CiSnapshot> {
         "unknown".to_string()
     };
 
-    // 2. Compute workspace hash
-    let workspace_hash = compute_workspace_hash(repo_root)?;
+    // 2. Compute workspace hash, reusing digests of unchanged files
+    let cache_path = repo_root.join(WORKSPACE_HASH_CACHE);
+    let mut hasher = WorkspaceHasher::load(&cache_path);
+    let workspace_hash = hasher.hash(repo_root)?;
+    if let Err(e) = hasher.save(&cache_path) {
+        tracing::warn!("failed to save workspace hash cache: {:#}", e);
+    }
 
     // 3. Compute local-ci config hash
     let local_ci_config_hash = {
@@ -0,0 +1,963 @@
+//! Repository operations shared by the `aivcs` CLI and the `aivcsd` API
+//!
+//! Each operation takes its storage explicitly and returns a structured
+//! outcome. Reporting, and side effects tied to the caller's working
+//! directory (git SHA capture, A2A events), stay with the caller.
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use nix_env_manager::{EnvironmentCache, HashSource, NixHash};
+use oxidized_state::{BranchRecord, CommitId, CommitRecord, StateError};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{info, instrument};
+
+use crate::cas::{CasError, CasStore, Digest};
+use crate::domain::SnapshotMeta;
+use crate::memory::{DecisionCaptureSource, DecisionRecorder};
+use crate::progress::{NoProgress, Progress};
+use crate::signing::CommitSigner;
+use crate::SurrealHandle;
+
+/// How many times a snapshot re-reads a moving branch head before giving up
+pub const BRANCH_HEAD_CAS_RETRIES: usize = 5;
+
+/// A command refused because it conflicts with the repository's current state
+#[derive(Debug, thiserror::Error)]
+pub enum CommandConflict {
+    /// Concurrent writers kept moving the branch head
+    #[error("branch '{branch}' kept moving; gave up after {attempts} attempts")]
+    BranchMoving { branch: String, attempts: usize },
+
+    /// A merge would create a merge commit on a `require-linear` branch
+    #[error(
+        "merging '{from}' into '{into}' would create a merge commit, but '{into}' is protected (require-linear); only fast-forward merges are allowed"
+    )]
+    LinearHistoryRequired { from: String, into: String },
+
+    /// The destination branch moved while a push or pull was transferring
+    #[error("branch '{branch}' changed on the destination during the sync; sync again")]
+    BranchMovedDuringSync { branch: String },
+}
+
+/// Keyframe interval for delta-encoded snapshots, if enabled via
+/// `AIVCS_SNAPSHOT_KEYFRAME_INTERVAL`.
+pub fn snapshot_keyframe_interval() -> Option<u32> {
+    std::env::var("AIVCS_SNAPSHOT_KEYFRAME_INTERVAL")
+        .ok()?
+        .parse()
+        .ok()
+        .filter(|k| *k > 0)
+}
+
+/// Inputs to [`snapshot`]
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotRequest {
+    /// Agent state, as JSON text
+    pub state: String,
+    pub message: String,
+    pub author: String,
+    /// Branch to commit to; created at the new commit if missing
+    pub branch: String,
+    /// Logic hash component of the composite commit id
+    pub logic_hash: Option<String>,
+    /// Environment hash component of the composite commit id
+    pub env_hash: Option<String>,
+    /// Git HEAD of the agent's code, recorded in the snapshot's metadata
+    pub git_sha: Option<String>,
+    /// Toolchain in use, recorded in the snapshot's metadata
+    pub toolchain: Option<String>,
+    /// Don't commit when the state matches the branch head
+    pub skip_if_unchanged: bool,
+    /// Delta-encode against the parent snapshot with this keyframe interval
+    pub keyframe_interval: Option<u32>,
+}
+
+/// What [`snapshot`] did with the state it was given
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SnapshotOutcome {
+    /// Commit created, or `None` when skipped via `skip_if_unchanged`
+    pub commit: Option<String>,
+    /// CAS digest of the state blob
+    pub cas_digest: String,
+    /// Parents of the new commit (the branch head it was claimed against)
+    pub parent_ids: Vec<String>,
+    /// The state blob was already in CAS, so no new blob was written
+    pub deduplicated: bool,
+    /// Branch head whose state is identical to the snapshotted state
+    pub unchanged_from: Option<String>,
+}
+
+/// Commit `request.state` to `request.branch`, storing the blob in `cas`
+///
+/// The state is validated as JSON before anything is written. The commit is
+/// stored first and the branch head then claimed with a compare-and-swap
+/// (or created if absent), so concurrent snapshots on one branch are
+/// re-parented rather than lost.
+#[instrument(skip(handle, cas, request, signer), fields(branch = %request.branch))]
+pub async fn snapshot(
+    handle: &SurrealHandle,
+    cas: &dyn CasStore,
+    request: &SnapshotRequest,
+    signer: Option<&CommitSigner>,
+) -> Result<SnapshotOutcome> {
+    let branch = request.branch.as_str();
+    let state: Value =
+        serde_json::from_str(&request.state).context("Failed to parse state as JSON")?;
+
+    let deduplicated = cas
+        .exists(&Digest::compute(request.state.as_bytes()))
+        .map_err(|e| anyhow!("CAS lookup failed: {e}"))?;
+    let cas_digest = cas
+        .put(request.state.as_bytes())
+        .map_err(|e| anyhow!("CAS put failed: {e}"))?
+        .to_hex();
+
+    let mut parent_ids = branch_parents(handle, branch).await?;
+
+    // A fresh blob can't match the head's state, so only look it up on dedup
+    let mut unchanged_from = None;
+    if deduplicated {
+        if let Some(head) = parent_ids.first() {
+            let head_state_hash = handle
+                .get_commit(head)
+                .await?
+                .map(|c| c.commit_id.state_hash);
+            if head_state_hash.as_deref() == Some(cas_digest.as_str()) {
+                unchanged_from = Some(head.clone());
+            }
+        }
+    }
+    if unchanged_from.is_some() && request.skip_if_unchanged {
+        return Ok(SnapshotOutcome {
+            commit: None,
+            cas_digest,
+            parent_ids,
+            deduplicated,
+            unchanged_from,
+        });
+    }
+
+    let commit_id = CommitId::new(
+        request.logic_hash.as_deref(),
+        &cas_digest,
+        request.env_hash.as_deref(),
+    );
+
+    // Store the commit's rows before claiming the branch head, so the head
+    // never points at a commit that isn't there yet. A concurrent snapshot
+    // may move the head between our read and the compare-and-swap; the
+    // stored commit is then re-parented onto the new head and we retry. A
+    // delta snapshot keeps its original base, which stays a valid commit.
+    match request.keyframe_interval {
+        Some(interval) => {
+            handle
+                .save_snapshot_delta(
+                    &commit_id,
+                    state,
+                    parent_ids.first().map(String::as_str),
+                    interval,
+                )
+                .await?
+        }
+        None => handle.save_snapshot(&commit_id, state).await?,
+    }
+
+    let mut commit = CommitRecord::new(
+        commit_id.clone(),
+        parent_ids.clone(),
+        &request.message,
+        &request.author,
+    )
+    .with_branch(branch);
+    if let Some(signer) = signer {
+        signer.sign(&mut commit);
+    }
+    handle.save_commit(&commit).await?;
+
+    let meta = SnapshotMeta::new(
+        cas_digest.clone(),
+        request.git_sha.clone().unwrap_or_default(),
+        request.message.clone(),
+        request.author.clone(),
+        branch.to_string(),
+    )
+    .with_hashes(request.logic_hash.clone(), request.env_hash.clone())
+    .with_toolchain(request.toolchain.clone());
+    handle.save_snapshot_meta(&commit_id.hash, &meta).await?;
+
+    let mut attempts = 0;
+    loop {
+        let claimed = match parent_ids.first() {
+            Some(head) => {
+                handle
+                    .update_branch_head_cas(branch, head, &commit_id.hash)
+                    .await?
+            }
+            None => {
+                let branch_record = BranchRecord::new(branch, &commit_id.hash, branch == "main");
+                handle.create_branch_if_absent(&branch_record).await?
+            }
+        };
+        if claimed {
+            break;
+        }
+        attempts += 1;
+        if attempts >= BRANCH_HEAD_CAS_RETRIES {
+            return Err(CommandConflict::BranchMoving {
+                branch: branch.to_string(),
+                attempts,
+            }
+            .into());
+        }
+        parent_ids = branch_parents(handle, branch).await?;
+        commit.parent_ids = parent_ids.clone();
+        if let Some(signer) = signer {
+            signer.sign(&mut commit);
+        }
+        handle.reparent_commit(&commit).await?;
+    }
+
+    for pid in &parent_ids {
+        handle.save_commit_graph_edge(&commit_id.hash, pid).await?;
+    }
+
+    info!(
+        "snapshot {} on '{}' ({})",
+        commit_id.short(),
+        branch,
+        cas_digest
+    );
+    Ok(SnapshotOutcome {
+        commit: Some(commit_id.hash),
+        cas_digest,
+        parent_ids,
+        deduplicated,
+        unchanged_from,
+    })
+}
+
+/// Current head of `branch` as a parent list; empty if the branch is missing
+async fn branch_parents(handle: &SurrealHandle, branch: &str) -> Result<Vec<String>> {
+    Ok(handle
+        .get_branch(branch)
+        .await?
+        .map(|b| vec![b.head_commit_id])
+        .unwrap_or_default())
+}
+
+/// Agent state restored from a commit
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RestoredState {
+    /// Commit the state was read from
+    pub commit: String,
+    pub state: Value,
+}
+
+/// Load the state at `reference`, a branch name or commit id
+///
+/// With `verify` set, the loaded state is checked against the commit's
+/// state hash with [`verify_state`] before it is returned.
+#[instrument(skip(handle, verify))]
+pub async fn restore(
+    handle: &SurrealHandle,
+    reference: &str,
+    verify: Option<&dyn CasStore>,
+) -> Result<RestoredState> {
+    let commit = resolve_commit_ref(handle, reference).await;
+    let snapshot = handle
+        .load_snapshot(&commit)
+        .await
+        .with_context(|| format!("Commit not found: {}", reference))?;
+    if let Some(cas) = verify {
+        let record = handle
+            .get_commit(&commit)
+            .await?
+            .ok_or_else(|| StateError::CommitNotFound(reference.to_string()))?;
+        verify_state(cas, &record.commit_id.state_hash, &snapshot.state)
+            .with_context(|| format!("Snapshot of {} failed verification", commit))?;
+    }
+    Ok(RestoredState {
+        commit,
+        state: snapshot.state,
+    })
+}
+
+/// Check a loaded `state` against the `state_hash` recorded at commit time
+///
+/// [`snapshot`] records the CAS digest of the state exactly as it was
+/// submitted, so the blob is fetched from `cas`, re-hashed, and compared to
+/// `state` as JSON values; formatting and key order of the original input
+/// don't matter. Commits made without CAS (`init`, merges, forks) hash the
+/// compact encoding instead, which is checked when `cas` has no blob.
+/// A mismatch fails with [`StateError::IntegrityError`].
+///
+/// [`StateError::IntegrityError`]: oxidized_state::StateError::IntegrityError
+pub fn verify_state(cas: &dyn CasStore, state_hash: &str, state: &Value) -> Result<()> {
+    let expected: Digest = state_hash
+        .parse()
+        .map_err(|e| anyhow!("Unrecognized state hash {state_hash}: {e}"))?;
+    let compact = Digest::compute_with(expected.algo(), &serde_json::to_vec(state)?);
+    let integrity_error = |actual: Digest| StateError::IntegrityError {
+        expected: state_hash.to_string(),
+        actual: actual.to_hex(),
+    };
+
+    let blob = match cas.get(&expected) {
+        Ok(blob) => blob,
+        Err(CasError::NotFound(_)) if compact == expected => return Ok(()),
+        Err(CasError::NotFound(_)) => bail!(
+            "Cannot verify state {}: its blob is not in CAS and the stored state hashes to {}",
+            state_hash,
+            compact
+        ),
+        Err(e) => bail!("CAS get failed: {e}"),
+    };
+    let actual = Digest::compute_with(expected.algo(), &blob);
+    if actual != expected {
+        return Err(integrity_error(actual).into());
+    }
+    let committed: Value =
+        serde_json::from_slice(&blob).context("Committed state blob is not valid JSON")?;
+    if &committed != state {
+        return Err(integrity_error(compact).into());
+    }
+    Ok(())
+}
+
+/// Up to `limit` commits of history, newest first
+///
+/// `reference` is a branch name or commit id, or a `<from>..<to>` range of
+/// the commits reachable from `to` but not from `from`.
+#[instrument(skip(handle))]
+pub async fn log(
+    handle: &SurrealHandle,
+    reference: &str,
+    limit: usize,
+) -> Result<Vec<CommitRecord>> {
+    if let Some((from, to)) = parse_log_range(reference) {
+        let from_commit = resolve_commit_ref(handle, from).await;
+        let to_commit = resolve_commit_ref(handle, to).await;
+        Ok(handle
+            .get_commit_range(&from_commit, &to_commit, limit)
+            .await?)
+    } else {
+        let start_commit = resolve_commit_ref(handle, reference).await;
+        Ok(handle.get_commit_history(&start_commit, limit).await?)
+    }
+}
+
+/// Commit time bounds for [`log_window`], both inclusive
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LogWindow {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl LogWindow {
+    pub fn is_unbounded(&self) -> bool {
+        self.since.is_none() && self.until.is_none()
+    }
+
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        !matches!(self.since, Some(since) if at < since)
+            && !matches!(self.until, Some(until) if at > until)
+    }
+}
+
+/// [`log`] restricted to commits created within `window`
+///
+/// The window applies before `limit`, so up to `limit` matching commits
+/// come back even when newer commits fall outside it. Parents predate
+/// their children, so the walk stops following a line of history at its
+/// first commit older than `since` instead of loading all of it.
+#[instrument(skip(handle))]
+pub async fn log_window(
+    handle: &SurrealHandle,
+    reference: &str,
+    limit: usize,
+    window: LogWindow,
+) -> Result<Vec<CommitRecord>> {
+    if window.is_unbounded() {
+        return log(handle, reference, limit).await;
+    }
+    // A range walks every parent, a single reference only the first
+    let (start, excluded, all_parents) = match parse_log_range(reference) {
+        Some((from, to)) => {
+            let from_commit = resolve_commit_ref(handle, from).await;
+            let to_commit = resolve_commit_ref(handle, to).await;
+            for endpoint in [&from_commit, &to_commit] {
+                if handle.get_commit(endpoint).await?.is_none() {
+                    return Err(StateError::CommitNotFound(endpoint.clone()).into());
+                }
+            }
+            let excluded = handle.get_ancestors(&from_commit).await?;
+            (to_commit, excluded, true)
+        }
+        None => (
+            resolve_commit_ref(handle, reference).await,
+            HashSet::new(),
+            false,
+        ),
+    };
+
+    let mut history = Vec::new();
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(hash) = queue.pop_front() {
+        // Breadth-first order across merges isn't newest first, so a range
+        // is walked in full and cut to `limit` once sorted
+        if !all_parents && history.len() >= limit {
+            break;
+        }
+        if excluded.contains(&hash) || !seen.insert(hash.clone()) {
+            continue;
+        }
+        let Some(commit) = handle.get_commit(&hash).await? else {
+            continue;
+        };
+        if matches!(window.since, Some(since) if commit.created_at < since) {
+            continue;
+        }
+        let parents = if all_parents {
+            &commit.parent_ids[..]
+        } else {
+            &commit.parent_ids[..commit.parent_ids.len().min(1)]
+        };
+        queue.extend(parents.iter().cloned());
+        if window.contains(commit.created_at) {
+            history.push(commit);
+        }
+    }
+    if all_parents {
+        history.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        history.truncate(limit);
+    }
+    Ok(history)
+}
+
+/// Parse a `--since`/`--until` value: an RFC 3339 timestamp, or a duration
+/// before `now` such as `30m`, `24h`, `7d`, or `2w`
+pub fn parse_log_time(text: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(text) {
+        return Ok(at.with_timezone(&Utc));
+    }
+    let unit_at = text.len().saturating_sub(1);
+    let (amount, unit) = (text.get(..unit_at), text.get(unit_at..));
+    let amount: i64 = match amount.and_then(|a| a.parse().ok()) {
+        Some(amount) if amount >= 0 => amount,
+        _ => bail!(
+            "invalid time '{}': expected RFC 3339 (2024-05-01T00:00:00Z) or a duration like 7d",
+            text
+        ),
+    };
+    let ago = match unit {
+        Some("s") => Duration::try_seconds(amount),
+        Some("m") => Duration::try_minutes(amount),
+        Some("h") => Duration::try_hours(amount),
+        Some("d") => Duration::try_days(amount),
+        Some("w") => Duration::try_weeks(amount),
+        _ => bail!(
+            "invalid time '{}': duration unit must be s, m, h, d, or w",
+            text
+        ),
+    };
+    ago.and_then(|ago| now.checked_sub_signed(ago))
+        .ok_or_else(|| anyhow!("invalid time '{}': out of range", text))
+}
+
+/// Split a `<from>..<to>` log range. Returns `None` for a single reference.
+pub fn parse_log_range(reference: &str) -> Option<(&str, &str)> {
+    let (from, to) = reference.split_once("..")?;
+    if from.is_empty() || to.is_empty() {
+        return None;
+    }
+    Some((from, to))
+}
+
+/// Resolve a branch name to its head commit, otherwise treat it as a commit ID.
+pub async fn resolve_commit_ref(handle: &SurrealHandle, reference: &str) -> String {
+    if let Ok(Some(branch)) = handle.get_branch(reference).await {
+        branch.head_commit_id
+    } else {
+        reference.to_string()
+    }
+}
+
+/// `log` output: commits newest first.
+///
+/// Shared by `aivcs log --json` and the `aivcsd` API; add fields rather than
+/// renaming or removing.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LogOutput {
+    pub commits: Vec<CommitOutput>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CommitOutput {
+    pub commit_id: String,
+    pub parent_ids: Vec<String>,
+    pub author: String,
+    pub message: String,
+    /// RFC 3339, UTC
+    pub created_at: String,
+    pub branch: Option<String>,
+}
+
+/// `show` output: a commit's metadata and the size of its stored snapshot.
+///
+/// Add fields rather than renaming or removing.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ShowOutput {
+    pub commit_id: String,
+    pub logic_hash: Option<String>,
+    /// CAS digest of the state blob
+    pub state_hash: String,
+    pub env_hash: Option<String>,
+    pub parent_ids: Vec<String>,
+    pub author: String,
+    pub message: String,
+    /// RFC 3339, UTC
+    pub created_at: String,
+    pub branch: Option<String>,
+    pub signed: bool,
+    pub snapshot: SnapshotInfo,
+    /// Environment recorded at snapshot time; `None` for commits made
+    /// before it was recorded, or not made by [`snapshot`]
+    pub meta: Option<SnapshotMeta>,
+}
+
+/// The stored snapshot row of a commit, without its state
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SnapshotInfo {
+    /// Bytes stored: the full state, or the patch for a delta
+    pub size_bytes: u64,
+    /// Commit the stored patch applies to, for delta snapshots
+    pub delta_parent: Option<String>,
+    /// Deltas since the last full snapshot (0 for a keyframe)
+    pub chain_depth: u32,
+}
+
+/// Branch listing: branches sorted by name.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BranchListOutput {
+    pub branches: Vec<BranchOutput>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BranchOutput {
+    pub name: String,
+    pub head_commit_id: String,
+    pub is_default: bool,
+}
+
+pub fn commit_output(commit: &CommitRecord) -> CommitOutput {
+    CommitOutput {
+        commit_id: commit.commit_id.hash.clone(),
+        parent_ids: commit.parent_ids.clone(),
+        author: commit.author.clone(),
+        message: commit.message.clone(),
+        created_at: commit
+            .created_at
+            .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        branch: commit.branch.clone(),
+    }
+}
+
+pub fn log_output(history: &[CommitRecord]) -> LogOutput {
+    LogOutput {
+        commits: history.iter().map(commit_output).collect(),
+    }
+}
+
+pub fn branch_list_output(branches: &[BranchRecord]) -> BranchListOutput {
+    let mut branches: Vec<BranchOutput> = branches
+        .iter()
+        .map(|b| BranchOutput {
+            name: b.name.clone(),
+            head_commit_id: b.head_commit_id.clone(),
+            is_default: b.is_default,
+        })
+        .collect();
+    branches.sort_by(|a, b| a.name.cmp(&b.name));
+    BranchListOutput { branches }
+}
+
+/// Metadata of the commit at `reference`, a branch name or commit id
+///
+/// Reads the commit and its stored snapshot row; delta snapshots are not
+/// reconstructed.
+#[instrument(skip(handle))]
+pub async fn show(handle: &SurrealHandle, reference: &str) -> Result<ShowOutput> {
+    let commit_hash = resolve_commit_ref(handle, reference).await;
+    let commit = handle
+        .get_commit(&commit_hash)
+        .await?
+        .ok_or_else(|| StateError::CommitNotFound(reference.to_string()))?;
+    let snapshot = handle
+        .fetch_snapshot_record(&commit_hash)
+        .await
+        .with_context(|| format!("Snapshot not found for commit {}", commit_hash))?;
+    let meta = handle.get_snapshot_meta(&commit_hash).await?;
+
+    let CommitOutput {
+        commit_id,
+        parent_ids,
+        author,
+        message,
+        created_at,
+        branch,
+    } = commit_output(&commit);
+    Ok(ShowOutput {
+        commit_id,
+        logic_hash: commit.commit_id.logic_hash,
+        state_hash: commit.commit_id.state_hash,
+        env_hash: commit.commit_id.env_hash,
+        parent_ids,
+        author,
+        message,
+        created_at,
+        branch,
+        signed: commit.signature.is_some(),
+        snapshot: SnapshotInfo {
+            size_bytes: snapshot.size_bytes,
+            delta_parent: snapshot.delta_parent,
+            chain_depth: snapshot.chain_depth,
+        },
+        meta,
+    })
+}
+
+/// Environment of a commit, pulled from the binary cache
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ReproducedEnvironment {
+    pub commit_id: String,
+    pub env_hash: String,
+    pub store_path: PathBuf,
+}
+
+/// Pull the Nix environment recorded for the commit at `reference`
+///
+/// The env hash comes from the commit's [`SnapshotMeta`], or from its
+/// commit id for commits made before that was recorded. An environment
+/// that isn't in `cache` is an error: the flake it was built from isn't
+/// stored, so it can't be rebuilt here.
+#[instrument(skip(handle, cache))]
+pub async fn reproduce_environment(
+    handle: &SurrealHandle,
+    cache: &dyn EnvironmentCache,
+    reference: &str,
+) -> Result<ReproducedEnvironment> {
+    let commit_hash = resolve_commit_ref(handle, reference).await;
+    let commit = handle
+        .get_commit(&commit_hash)
+        .await?
+        .ok_or_else(|| StateError::CommitNotFound(reference.to_string()))?;
+    let meta: Option<SnapshotMeta> = handle.get_snapshot_meta(&commit_hash).await?;
+    let Some(env_hash) = meta.and_then(|m| m.env_hash).or(commit.commit_id.env_hash) else {
+        bail!(
+            "Commit {} has no environment hash; it was snapshotted outside a Nix flake",
+            commit_hash
+        );
+    };
+
+    let nix_hash = NixHash::new(env_hash.clone(), HashSource::FlakeLock);
+    if !cache.is_environment_cached(&nix_hash).await {
+        bail!(
+            "Environment {} of commit {} is not in the binary cache and can't be rebuilt without its flake; push it from a machine that has it",
+            nix_hash.short(),
+            commit_hash
+        );
+    }
+    let store_path = cache
+        .pull_environment(&nix_hash)
+        .await
+        .with_context(|| format!("Failed to pull environment {}", nix_hash.short()))?;
+    info!(
+        "reproduced environment {} for {}",
+        nix_hash.short(),
+        commit_hash
+    );
+
+    Ok(ReproducedEnvironment {
+        commit_id: commit_hash,
+        env_hash,
+        store_path,
+    })
+}
+
+/// Create the initial commit and the default `main` branch
+#[instrument(skip(handle))]
+pub async fn init(handle: &SurrealHandle) -> Result<CommitId> {
+    let initial_state = serde_json::json!({
+        "initialized": true,
+        "version": env!("CARGO_PKG_VERSION"),
+    });
+    let commit_id = CommitId::from_state(serde_json::to_vec(&initial_state)?.as_slice());
+
+    handle.save_snapshot(&commit_id, initial_state).await?;
+    let commit = CommitRecord::new(commit_id.clone(), vec![], "Initial commit", "system");
+    handle.save_commit(&commit).await?;
+    let main_branch = BranchRecord::new("main", &commit_id.hash, true);
+    handle.save_branch(&main_branch).await?;
+
+    info!("initialized repository at {}", commit_id.short());
+    Ok(commit_id)
+}
+
+/// Create branch `name` at `from`, a branch name or commit id
+#[instrument(skip(handle))]
+pub async fn create_branch(handle: &SurrealHandle, name: &str, from: &str) -> Result<BranchRecord> {
+    let head_commit = resolve_commit_ref(handle, from).await;
+    let branch = BranchRecord::new(name, &head_commit, false);
+    handle.save_branch(&branch).await?;
+    Ok(branch)
+}
+
+/// Delete branch `name`, subject to its protection rules
+#[instrument(skip(handle))]
+pub async fn delete_branch(handle: &SurrealHandle, name: &str) -> Result<()> {
+    handle
+        .delete_branch(name)
+        .await
+        .with_context(|| format!("Failed to delete branch '{}'", name))
+}
+
+/// How [`merge`] integrated the source branch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergePath {
+    /// Target already contained the source head; nothing changed
+    UpToDate,
+    /// Target pointer moved to the source head without a new commit
+    FastForward,
+    /// A merge commit was synthesized
+    MergeCommit,
+}
+
+/// Result of [`merge`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MergeOutcome {
+    pub path: MergePath,
+    /// Target head before the merge
+    pub previous_head: String,
+    /// Target head after the merge
+    pub head: String,
+    /// Semantic merge summary, for merge commits
+    pub summary: Option<String>,
+    /// Memory keys the semantic merge left for manual resolution
+    pub conflicts: Vec<String>,
+    /// Arbiter confidence per 0.1 bucket over every conflict; all zero
+    /// without a merge commit
+    pub confidence_histogram: [usize; 10],
+}
+
+/// Merge branch `source` into branch `target`
+///
+/// Fast-forwards when possible unless `no_ff` is set; otherwise synthesizes
+/// a semantic merge commit, which a `require-linear` target refuses.
+pub async fn merge(
+    handle: &SurrealHandle,
+    source: &str,
+    target: &str,
+    message: Option<&str>,
+    no_ff: bool,
+    author: &str,
+) -> Result<MergeOutcome> {
+    merge_with_progress(
+        handle,
+        source,
+        target,
+        message,
+        no_ff,
+        author,
+        None,
+        &NoProgress,
+    )
+    .await
+}
+
+/// [`merge`], reporting each memory conflict reviewed by the semantic merge
+/// to `progress`. A merge performed by a run passes the run event as
+/// `merge_run`, the recorded source of every memory the merge writes.
+#[allow(clippy::too_many_arguments)]
+#[instrument(skip(handle, progress))]
+pub async fn merge_with_progress(
+    handle: &SurrealHandle,
+    source: &str,
+    target: &str,
+    message: Option<&str>,
+    no_ff: bool,
+    author: &str,
+    merge_run: Option<&semantic_rag_merge::MergeRun>,
+    progress: &dyn Progress,
+) -> Result<MergeOutcome> {
+    let (source_commit, target_commit, path) = merge_path(handle, source, target, no_ff).await?;
+    let outcome = |path, head: &str| MergeOutcome {
+        path,
+        previous_head: target_commit.clone(),
+        head: head.to_string(),
+        summary: None,
+        conflicts: Vec::new(),
+        confidence_histogram: [0; 10],
+    };
+
+    match path {
+        MergePath::UpToDate => return Ok(outcome(path, &target_commit)),
+        MergePath::FastForward => {
+            let branch = BranchRecord::new(target, &source_commit, target == "main");
+            handle.save_branch(&branch).await?;
+            info!("fast-forwarded '{}' to '{}'", target, source);
+            return Ok(outcome(path, &source_commit));
+        }
+        MergePath::MergeCommit => {}
+    }
+
+    let merge_message = message
+        .map(String::from)
+        .unwrap_or_else(|| format!("Merge branch '{}' into '{}'", source, target));
+    let mut report = |done: usize, total: usize| match done {
+        0 => progress.start("merge", total),
+        _ => progress.inc(1),
+    };
+    let result = semantic_rag_merge::semantic_merge_with_progress(
+        handle,
+        &source_commit,
+        &target_commit,
+        &merge_message,
+        author,
+        merge_run,
+        &mut report,
+    )
+    .await;
+    progress.finish();
+    let result = result?;
+
+    // Record why each conflict was resolved the way it was
+    let recorder = DecisionRecorder::with_default_config(Arc::new(handle.clone()));
+    for decision in result.decisions {
+        recorder
+            .record(decision, DecisionCaptureSource::Event)
+            .await?;
+    }
+
+    let branch = BranchRecord::new(target, &result.merge_commit_id.hash, target == "main");
+    handle.save_branch(&branch).await?;
+
+    info!("merged '{}' into '{}'", source, target);
+    Ok(MergeOutcome {
+        summary: Some(result.summary),
+        conflicts: result
+            .manual_conflicts
+            .into_iter()
+            .map(|conflict| conflict.key)
+            .collect(),
+        confidence_histogram: result.confidence_histogram,
+        ..outcome(MergePath::MergeCommit, &result.merge_commit_id.hash)
+    })
+}
+
+/// What [`merge`] would do, computed without writing anything
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MergePreview {
+    pub path: MergePath,
+    /// Current target head
+    pub previous_head: String,
+    /// Target head after the merge: the source head for a fast-forward, or
+    /// the id the merge commit would get
+    pub head: String,
+    /// Conflicts the arbiter would resolve on its own
+    pub auto_resolved: usize,
+    /// Memory keys that would be left for manual resolution
+    pub conflicts: Vec<String>,
+    /// Arbiter confidence per 0.1 bucket over every conflict
+    pub confidence_histogram: [usize; 10],
+    /// Summary the merge commit would report
+    pub summary: Option<String>,
+}
+
+/// Dry run of [`merge`]: the same path, arbiter decisions, and summary,
+/// with no commit, memories, graph edges, or branch move written
+#[instrument(skip(handle))]
+pub async fn preview_merge(
+    handle: &SurrealHandle,
+    source: &str,
+    target: &str,
+    no_ff: bool,
+) -> Result<MergePreview> {
+    let (source_commit, target_commit, path) = merge_path(handle, source, target, no_ff).await?;
+    let mut preview = MergePreview {
+        path,
+        previous_head: target_commit.clone(),
+        head: target_commit.clone(),
+        auto_resolved: 0,
+        conflicts: Vec::new(),
+        confidence_histogram: [0; 10],
+        summary: None,
+    };
+
+    match path {
+        MergePath::UpToDate => {}
+        MergePath::FastForward => preview.head = source_commit,
+        MergePath::MergeCommit => {
+            let plan = semantic_rag_merge::plan_semantic_merge(
+                handle,
+                &source_commit,
+                &target_commit,
+                &mut |_, _| {},
+            )
+            .await?;
+            preview.head = plan.merge_commit_id.hash;
+            preview.auto_resolved = plan.auto_resolved;
+            preview.conflicts = plan
+                .manual_conflicts
+                .into_iter()
+                .map(|conflict| conflict.key)
+                .collect();
+            preview.confidence_histogram = plan.confidence_histogram;
+            preview.summary = Some(plan.summary);
+        }
+    }
+    Ok(preview)
+}
+
+/// Resolve both branch heads and decide how `source` merges into `target`
+///
+/// Returns `(source head, target head, path)`. A merge commit into a
+/// `require-linear` target is refused here, so dry runs refuse it too.
+async fn merge_path(
+    handle: &SurrealHandle,
+    source: &str,
+    target: &str,
+    no_ff: bool,
+) -> Result<(String, String, MergePath)> {
+    let source_commit = handle
+        .get_branch_head(source)
+        .await
+        .with_context(|| format!("Source branch not found: {}", source))?;
+    let target_commit = handle
+        .get_branch_head(target)
+        .await
+        .with_context(|| format!("Target branch not found: {}", target))?;
+
+    let merge_base = handle
+        .find_merge_base(&source_commit, &target_commit)
+        .await?;
+    let path = if merge_base.as_deref() == Some(source_commit.as_str()) {
+        MergePath::UpToDate
+    } else if merge_base.as_deref() == Some(target_commit.as_str()) && !no_ff {
+        MergePath::FastForward
+    } else if handle.get_protection(target).await?.require_linear {
+        return Err(CommandConflict::LinearHistoryRequired {
+            from: source.to_string(),
+            into: target.to_string(),
+        }
+        .into());
+    } else {
+        MergePath::MergeCommit
+    };
+    Ok((source_commit, target_commit, path))
+}
@@ -2,9 +2,12 @@
 //!
 //! Evaluates a candidate [`Release`] against a [`CompatRuleSet`] to produce a
 //! [`CompatVerdict`] — the pass/fail decision that blocks or allows a promote.
+//! Warnings in the verdict flag deprecations consumers should act on but never
+//! block the promote.
 
 use serde::{Deserialize, Serialize};
 
+use crate::domain::eval::EvalRunReport;
 use crate::domain::release::Release;
 
 /// A single compatibility rule that can block a promotion.
@@ -17,10 +20,25 @@ pub enum CompatRule {
     RequireToolsDigest,
     /// `graph_digest` must be non-empty.
     RequireGraphDigest,
-    /// `tools_digest` must not change vs. the current release (if one exists).
+    /// `tools_digest` must not change vs. the current release (if one exists
+    /// and recorded its digest).
     NoToolsChange,
-    /// `graph_digest` must not change vs. the current release (if one exists).
+    /// `graph_digest` must not change vs. the current release (if one exists
+    /// and recorded its digest).
     NoGraphChange,
+    /// The candidate's eval run must reach `threshold` pass rate; a missing
+    /// report counts as a failure.
+    MinEvalPassRate { threshold: f32 },
+    /// The candidate must not remove tool or config `field`, listed under
+    /// `metadata.fields`: it is removed when the current release exposes it
+    /// and the candidate doesn't, or, with no current release, when the
+    /// candidate doesn't expose it. Once `deprecated_since` is set, removing
+    /// it only produces a warning.
+    RequireField {
+        field: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        deprecated_since: Option<String>,
+    },
 }
 
 /// A set of compatibility rules to evaluate before promoting.
@@ -54,6 +72,10 @@ pub struct PromoteContext<'a> {
     pub candidate: &'a Release,
     /// The existing release at the target environment (if any).
     pub current: Option<&'a Release>,
+    /// Eval run report for the candidate's spec (if one was produced).
+    pub eval_report: Option<&'a EvalRunReport>,
+    /// CAS digest `eval_report` was loaded from, quoted in violations.
+    pub eval_report_digest: Option<&'a str>,
 }
 
 /// A single rule violation.
@@ -65,11 +87,23 @@ pub struct CompatViolation {
     pub reason: String,
 }
 
+/// A non-blocking finding, such as a deprecated field being removed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CompatWarning {
+    /// Which rule raised the warning.
+    pub rule: CompatRule,
+    /// Human-readable explanation.
+    pub reason: String,
+}
+
 /// The outcome of evaluating a compat rule set against a promote context.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct CompatVerdict {
     /// Violations found (empty when passed).
     pub violations: Vec<CompatViolation>,
+    /// Warnings found; these never fail the verdict.
+    #[serde(default)]
+    pub warnings: Vec<CompatWarning>,
 }
 
 impl CompatVerdict {
@@ -82,14 +116,51 @@ impl CompatVerdict {
 /// Evaluate a [`PromoteContext`] against a [`CompatRuleSet`], returning a [`CompatVerdict`].
 pub fn evaluate_compat(rule_set: &CompatRuleSet, ctx: &PromoteContext) -> CompatVerdict {
     let mut violations = Vec::new();
+    let mut warnings = Vec::new();
 
     for rule in &rule_set.rules {
         if let Some(v) = check_rule(rule, ctx) {
             violations.push(v);
         }
+        if let Some(w) = check_deprecation(rule, ctx) {
+            warnings.push(w);
+        }
+    }
+
+    CompatVerdict {
+        violations,
+        warnings,
     }
+}
+
+/// Whether `release` lists `field` under `metadata.fields`.
+fn exposes_field(release: &Release, field: &str) -> bool {
+    release
+        .metadata
+        .get("fields")
+        .and_then(|v| v.as_array())
+        .is_some_and(|fields| fields.iter().any(|f| f.as_str() == Some(field)))
+}
 
-    CompatVerdict { violations }
+/// Whether the candidate drops `field` relative to the current release.
+fn field_removed(ctx: &PromoteContext, field: &str) -> bool {
+    !exposes_field(ctx.candidate, field)
+        && ctx
+            .current
+            .is_none_or(|current| exposes_field(current, field))
+}
+
+fn check_deprecation(rule: &CompatRule, ctx: &PromoteContext) -> Option<CompatWarning> {
+    match rule {
+        CompatRule::RequireField {
+            field,
+            deprecated_since: Some(since),
+        } if field_removed(ctx, field) => Some(CompatWarning {
+            rule: rule.clone(),
+            reason: format!("field '{}' was removed (deprecated since {})", field, since,),
+        }),
+        _ => None,
+    }
 }
 
 fn is_valid_hex_digest(s: &str) -> bool {
@@ -134,7 +205,7 @@ fn check_rule(rule: &CompatRule, ctx: &PromoteContext) -> Option<CompatViolation
             }
         }
         CompatRule::NoToolsChange => {
-            if let Some(current) = ctx.current {
+            if let Some(current) = ctx.current.filter(|c| !c.tools_digest.is_empty()) {
                 if ctx.candidate.tools_digest != current.tools_digest {
                     Some(CompatViolation {
                         rule: rule.clone(),
@@ -151,7 +222,7 @@ fn check_rule(rule: &CompatRule, ctx: &PromoteContext) -> Option<CompatViolation
             }
         }
         CompatRule::NoGraphChange => {
-            if let Some(current) = ctx.current {
+            if let Some(current) = ctx.current.filter(|c| !c.graph_digest.is_empty()) {
                 if ctx.candidate.graph_digest != current.graph_digest {
                     Some(CompatViolation {
                         rule: rule.clone(),
@@ -167,5 +238,37 @@ fn check_rule(rule: &CompatRule, ctx: &PromoteContext) -> Option<CompatViolation
                 None
             }
         }
+        CompatRule::MinEvalPassRate { threshold } => match ctx.eval_report {
+            None => Some(CompatViolation {
+                rule: rule.clone(),
+                reason: "no eval run report for the candidate".to_string(),
+            }),
+            Some(report) if report.pass_rate < *threshold => Some(CompatViolation {
+                rule: rule.clone(),
+                reason: format!(
+                    "eval pass rate {:.2} of suite '{}'{} is below the required {:.2}",
+                    report.pass_rate,
+                    report.suite_digest,
+                    ctx.eval_report_digest
+                        .map(|d| format!(" (report {d})"))
+                        .unwrap_or_default(),
+                    threshold,
+                ),
+            }),
+            Some(_) => None,
+        },
+        CompatRule::RequireField {
+            field,
+            deprecated_since,
+        } => {
+            if deprecated_since.is_none() && field_removed(ctx, field) {
+                Some(CompatViolation {
+                    rule: rule.clone(),
+                    reason: format!("required field '{}' was removed", field),
+                })
+            } else {
+                None
+            }
+        }
     }
 }
@@ -0,0 +1,299 @@
+//! Repository configuration: database connection, CAS location and model
+//! pricing
+//!
+//! Each setting resolves with the precedence command-line flag >
+//! environment > `aivcs.toml` > built-in default. The config file is found
+//! by searching upward from the working directory, so each repository can
+//! carry its own.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use oxidized_state::CloudConfig;
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::cost::PricingTable;
+use crate::SurrealHandle;
+
+/// Name of the per-repository config file
+pub const CONFIG_FILE_NAME: &str = "aivcs.toml";
+
+/// Database used when nothing names one: local SurrealKV under the cwd
+pub const DEFAULT_DB_URL: &str = "surrealkv://.aivcs/db";
+
+const DEFAULT_NAMESPACE: &str = "aivcs";
+const DEFAULT_DATABASE: &str = "main";
+const DEFAULT_CAS_DIR: &str = ".aivcs/cas";
+
+/// Variables [`CloudConfig::from_env`] needs to connect to SurrealDB Cloud
+const CLOUD_ENV_VARS: [&str; 3] = [
+    "SURREALDB_ENDPOINT",
+    "SURREALDB_USERNAME",
+    "SURREALDB_PASSWORD",
+];
+
+/// Contents of an `aivcs.toml`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    /// SurrealDB endpoint URL, e.g. `ws://localhost:8000`
+    pub db_url: Option<String>,
+    pub namespace: Option<String>,
+    pub database: Option<String>,
+    /// CAS directory; a relative path is taken from the file's directory
+    pub cas_dir: Option<PathBuf>,
+    /// Model prices used to cost runs, one `[pricing.<model>]` table each
+    #[serde(default)]
+    pub pricing: PricingTable,
+}
+
+impl ConfigFile {
+    /// Nearest `aivcs.toml` in `start` or one of its ancestors
+    pub fn find(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .map(|dir| dir.join(CONFIG_FILE_NAME))
+            .find(|path| path.is_file())
+    }
+
+    /// Parse the config file at `path`
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read config {}", path.display()))?;
+        toml::from_str(&text).with_context(|| format!("invalid config {}", path.display()))
+    }
+}
+
+/// Settings given on the command line
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOverrides {
+    /// `--db-url`
+    pub db_url: Option<String>,
+}
+
+/// Resolved repository configuration
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// SurrealDB URL. `None` means [`Config::connect`] uses the cloud
+    /// credentials in the environment, or [`DEFAULT_DB_URL`] if there are
+    /// none.
+    pub db_url: Option<String>,
+    pub namespace: String,
+    pub database: String,
+    pub cas_dir: PathBuf,
+    /// Model prices from the config file; empty when none are configured
+    pub pricing: PricingTable,
+    /// Config file the settings were read from, if one was found
+    pub file: Option<PathBuf>,
+}
+
+impl Config {
+    /// Resolve from `overrides`, the process environment, and the nearest
+    /// `aivcs.toml` above the current directory
+    pub fn resolve(overrides: &ConfigOverrides) -> Result<Self> {
+        let cwd = std::env::current_dir().context("Failed to get current directory")?;
+        Self::resolve_from(overrides, |name| std::env::var(name).ok(), &cwd)
+    }
+
+    /// [`Config::resolve`] with an explicit environment lookup and starting
+    /// directory
+    ///
+    /// Reads `SURREALDB_URL`, `SURREALDB_NAMESPACE`, `SURREALDB_DATABASE`,
+    /// and `AIVCS_CAS_DIR`. As in [`SurrealHandle::setup_from_env`], cloud
+    /// credentials (`SURREALDB_ENDPOINT`, `SURREALDB_USERNAME`,
+    /// `SURREALDB_PASSWORD`) in the environment take precedence over
+    /// `SURREALDB_URL` and the file's `db_url`; only `--db-url` outranks them.
+    pub fn resolve_from(
+        overrides: &ConfigOverrides,
+        env: impl Fn(&str) -> Option<String>,
+        cwd: &Path,
+    ) -> Result<Self> {
+        let path = ConfigFile::find(cwd);
+        let file = match &path {
+            Some(path) => ConfigFile::load(path)?,
+            None => ConfigFile::default(),
+        };
+        let file_dir = path.as_deref().and_then(Path::parent);
+
+        let cloud = CLOUD_ENV_VARS.iter().all(|name| env(name).is_some());
+        let db_url = match overrides.db_url.clone() {
+            Some(url) => Some(url),
+            None if cloud => None,
+            None => env("SURREALDB_URL").or(file.db_url),
+        };
+        let namespace = env("SURREALDB_NAMESPACE")
+            .or(file.namespace)
+            .unwrap_or_else(|| DEFAULT_NAMESPACE.to_string());
+        let database = env("SURREALDB_DATABASE")
+            .or(file.database)
+            .unwrap_or_else(|| DEFAULT_DATABASE.to_string());
+        let cas_dir = match (env("AIVCS_CAS_DIR"), file.cas_dir) {
+            (Some(dir), _) => PathBuf::from(dir),
+            (None, Some(dir)) => match file_dir {
+                Some(base) if dir.is_relative() => base.join(dir),
+                _ => dir,
+            },
+            (None, None) => PathBuf::from(DEFAULT_CAS_DIR),
+        };
+
+        let config = Self {
+            db_url,
+            namespace,
+            database,
+            cas_dir,
+            pricing: file.pricing,
+            file: path,
+        };
+        debug!(?config, "resolved configuration");
+        Ok(config)
+    }
+
+    /// Connect to the configured database
+    pub async fn connect(&self) -> Result<SurrealHandle> {
+        let handle = match &self.db_url {
+            Some(url) => {
+                SurrealHandle::setup_with_url(url, &self.namespace, &self.database).await?
+            }
+            None => match CloudConfig::from_env() {
+                Ok(cloud) => {
+                    let cloud = cloud
+                        .with_namespace(&self.namespace)
+                        .with_database(&self.database);
+                    SurrealHandle::setup_cloud(cloud).await?
+                }
+                Err(_) => {
+                    SurrealHandle::setup_with_url(DEFAULT_DB_URL, &self.namespace, &self.database)
+                        .await?
+                }
+            },
+        };
+        Ok(handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env_of(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    fn flag(url: &str) -> ConfigOverrides {
+        ConfigOverrides {
+            db_url: Some(url.to_string()),
+        }
+    }
+
+    fn repo_with_config(contents: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(CONFIG_FILE_NAME), contents).unwrap();
+        dir
+    }
+
+    const FILE: &str = r#"
+db_url = "ws://file:8000"
+namespace = "file-ns"
+database = "file-db"
+cas_dir = "store"
+"#;
+
+    #[test]
+    fn test_flag_beats_env_beats_file_beats_default() {
+        let repo = repo_with_config(FILE);
+        let env = env_of(&[
+            ("SURREALDB_URL", "ws://env:8000"),
+            ("SURREALDB_NAMESPACE", "env-ns"),
+        ]);
+
+        let config = Config::resolve_from(&flag("mem://"), &env, repo.path()).unwrap();
+        assert_eq!(config.db_url.as_deref(), Some("mem://"));
+        assert_eq!(config.namespace, "env-ns");
+        assert_eq!(config.database, "file-db");
+        assert_eq!(config.cas_dir, repo.path().join("store"));
+
+        let config = Config::resolve_from(&ConfigOverrides::default(), &env, repo.path()).unwrap();
+        assert_eq!(config.db_url.as_deref(), Some("ws://env:8000"));
+
+        let config =
+            Config::resolve_from(&ConfigOverrides::default(), env_of(&[]), repo.path()).unwrap();
+        assert_eq!(config.db_url.as_deref(), Some("ws://file:8000"));
+        assert_eq!(config.namespace, "file-ns");
+        assert_eq!(
+            config.file.as_deref(),
+            Some(repo.path().join(CONFIG_FILE_NAME).as_path())
+        );
+
+        let bare = tempfile::tempdir().unwrap();
+        let config =
+            Config::resolve_from(&ConfigOverrides::default(), env_of(&[]), bare.path()).unwrap();
+        assert_eq!(config.db_url, None);
+        assert_eq!(config.namespace, DEFAULT_NAMESPACE);
+        assert_eq!(config.database, DEFAULT_DATABASE);
+        assert_eq!(config.cas_dir, PathBuf::from(DEFAULT_CAS_DIR));
+        assert_eq!(config.file, None);
+    }
+
+    #[test]
+    fn test_config_file_is_found_from_a_subdirectory() {
+        let repo = repo_with_config(FILE);
+        let nested = repo.path().join("agents").join("planner");
+        std::fs::create_dir_all(&nested).unwrap();
+
+        let env = env_of(&[("AIVCS_CAS_DIR", "/srv/cas")]);
+        let config = Config::resolve_from(&ConfigOverrides::default(), env, &nested).unwrap();
+        assert_eq!(config.db_url.as_deref(), Some("ws://file:8000"));
+        assert_eq!(config.cas_dir, PathBuf::from("/srv/cas"));
+    }
+
+    #[test]
+    fn test_cloud_credentials_outrank_env_and_file_url() {
+        let repo = repo_with_config(FILE);
+        let env = env_of(&[
+            ("SURREALDB_ENDPOINT", "wss://cloud"),
+            ("SURREALDB_USERNAME", "user"),
+            ("SURREALDB_PASSWORD", "secret"),
+            ("SURREALDB_URL", "ws://env:8000"),
+        ]);
+
+        let config = Config::resolve_from(&ConfigOverrides::default(), &env, repo.path()).unwrap();
+        assert_eq!(config.db_url, None);
+
+        // Without full credentials cloud is not configured
+        let partial = env_of(&[
+            ("SURREALDB_ENDPOINT", "wss://cloud"),
+            ("SURREALDB_URL", "ws://env:8000"),
+        ]);
+        let config =
+            Config::resolve_from(&ConfigOverrides::default(), partial, repo.path()).unwrap();
+        assert_eq!(config.db_url.as_deref(), Some("ws://env:8000"));
+
+        let config = Config::resolve_from(&flag("mem://"), &env, repo.path()).unwrap();
+        assert_eq!(config.db_url.as_deref(), Some("mem://"));
+    }
+
+    #[test]
+    fn test_pricing_table_is_read_from_file() {
+        let repo =
+            repo_with_config("[pricing.gpt-4o]\ninput_per_mtok = 2.5\noutput_per_mtok = 10.0\n");
+        let config =
+            Config::resolve_from(&ConfigOverrides::default(), env_of(&[]), repo.path()).unwrap();
+        let price = config.pricing.price("gpt-4o-2024-08-06").unwrap();
+        assert_eq!(price.input_per_mtok, 2.5);
+        assert_eq!(price.output_per_mtok, 10.0);
+    }
+
+    #[test]
+    fn test_unknown_config_keys_are_rejected() {
+        let repo = repo_with_config("db_uri = \"ws://typo\"\n");
+        let err = Config::resolve_from(&ConfigOverrides::default(), env_of(&[]), repo.path())
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("invalid config"), "{err:#}");
+    }
+}
@@ -0,0 +1,278 @@
+//! Token and cost accounting for runs
+//!
+//! Events that call a model report what it consumed in a `usage` object in
+//! their payload:
+//!
+//! ```json
+//! {"tool_name": "llm", "usage": {"model": "gpt-4o", "input_tokens": 1200, "output_tokens": 300}}
+//! ```
+//!
+//! `prompt_tokens`/`completion_tokens` are accepted as aliases, and the model
+//! may be given next to `usage` instead of inside it. [`usage_from_events`]
+//! sums these per model; the recorder stores the sums in the run summary.
+//! Cost is computed from token counts with a [`PricingTable`], configured
+//! under `[pricing]` in `aivcs.toml`.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use oxidized_state::{RunEvent, RunId, RunLedger, RunRecord, RunUsage, TokenUsage};
+use serde::{Deserialize, Serialize};
+
+use crate::{AivcsError, Result};
+
+/// Model name used when a usage report names none
+pub const UNKNOWN_MODEL: &str = "unknown";
+
+/// Price of one model in USD per million tokens
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModelPrice {
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+}
+
+impl ModelPrice {
+    /// Cost of `usage` at this price
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        (usage.input_tokens as f64 * self.input_per_mtok
+            + usage.output_tokens as f64 * self.output_per_mtok)
+            / 1_000_000.0
+    }
+}
+
+/// Model prices, keyed by model name
+///
+/// ```toml
+/// [pricing.gpt-4o]
+/// input_per_mtok = 2.5
+/// output_per_mtok = 10.0
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PricingTable {
+    pub models: BTreeMap<String, ModelPrice>,
+}
+
+impl PricingTable {
+    /// Set the price of `model`
+    pub fn with_model(mut self, model: impl Into<String>, price: ModelPrice) -> Self {
+        self.models.insert(model.into(), price);
+        self
+    }
+
+    /// Price of `model`: an exact entry, otherwise the longest entry the
+    /// name starts with, so `gpt-4o` also prices `gpt-4o-2024-08-06`
+    pub fn price(&self, model: &str) -> Option<&ModelPrice> {
+        self.models.get(model).or_else(|| {
+            self.models
+                .iter()
+                .filter(|(name, _)| model.starts_with(name.as_str()))
+                .max_by_key(|(name, _)| name.len())
+                .map(|(_, price)| price)
+        })
+    }
+}
+
+/// Model and tokens of one event's `usage` report, if it has one
+pub fn event_usage(payload: &serde_json::Value) -> Option<(String, TokenUsage)> {
+    let usage = payload.get("usage")?.as_object()?;
+    let tokens = |names: [&str; 2]| {
+        names
+            .iter()
+            .find_map(|name| usage.get(*name).and_then(|v| v.as_u64()))
+            .unwrap_or(0)
+    };
+    let model = usage
+        .get("model")
+        .or_else(|| payload.get("model"))
+        .and_then(|m| m.as_str())
+        .unwrap_or(UNKNOWN_MODEL);
+    Some((
+        model.to_string(),
+        TokenUsage {
+            input_tokens: tokens(["input_tokens", "prompt_tokens"]),
+            output_tokens: tokens(["output_tokens", "completion_tokens"]),
+            calls: 1,
+        },
+    ))
+}
+
+/// Token usage reported by `events`, summed per model
+pub fn usage_from_events(events: &[RunEvent]) -> RunUsage {
+    let mut usage = RunUsage::default();
+    for (model, tokens) in events.iter().filter_map(|e| event_usage(&e.payload)) {
+        usage.add(&model, &tokens);
+    }
+    usage
+}
+
+/// Tokens and cost of one model
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModelCost {
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub calls: u64,
+    /// `None` when the pricing table has no price for the model
+    pub cost_usd: Option<f64>,
+}
+
+/// Tokens and cost of a set of runs
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CostSummary {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Cost of the priced models
+    pub cost_usd: f64,
+    /// Per-model breakdown, sorted by model name
+    pub models: Vec<ModelCost>,
+    /// Models with usage but no price; their tokens are not in `cost_usd`
+    pub unpriced_models: Vec<String>,
+}
+
+/// Price `usage` with `pricing`
+pub fn price_usage(usage: &RunUsage, pricing: &PricingTable) -> CostSummary {
+    let total = usage.total();
+    let models: Vec<ModelCost> = usage
+        .models
+        .iter()
+        .map(|(model, tokens)| ModelCost {
+            model: model.clone(),
+            input_tokens: tokens.input_tokens,
+            output_tokens: tokens.output_tokens,
+            calls: tokens.calls,
+            cost_usd: pricing.price(model).map(|price| price.cost(tokens)),
+        })
+        .collect();
+    CostSummary {
+        input_tokens: total.input_tokens,
+        output_tokens: total.output_tokens,
+        cost_usd: models.iter().filter_map(|m| m.cost_usd).sum(),
+        unpriced_models: models
+            .iter()
+            .filter(|m| m.cost_usd.is_none())
+            .map(|m| m.model.clone())
+            .collect(),
+        models,
+    }
+}
+
+/// Token usage of `run`: its summary's when it finished with one,
+/// otherwise summed from its events
+async fn usage_of(ledger: &dyn RunLedger, run: &RunRecord) -> Result<RunUsage> {
+    if let Some(usage) = run.summary.as_ref().and_then(|s| s.usage.clone()) {
+        return Ok(usage);
+    }
+    let events = ledger.get_events(&run.run_id).await.map_err(|e| {
+        AivcsError::StorageError(format!(
+            "Failed to load events of run {}: {}",
+            run.run_id, e
+        ))
+    })?;
+    Ok(usage_from_events(&events))
+}
+
+/// Tokens and cost of one run
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunCost {
+    pub run_id: String,
+    pub agent_name: String,
+    #[serde(flatten)]
+    pub cost: CostSummary,
+}
+
+/// Cost of run `run_id` at `pricing`
+pub async fn run_cost(
+    ledger: &dyn RunLedger,
+    run_id: &str,
+    pricing: &PricingTable,
+) -> Result<RunCost> {
+    let run = ledger
+        .get_run(&RunId(run_id.to_string()))
+        .await
+        .map_err(|e| AivcsError::StorageError(format!("Failed to load run {}: {}", run_id, e)))?;
+    let usage = usage_of(ledger, &run).await?;
+    Ok(RunCost {
+        run_id: run_id.to_string(),
+        agent_name: run.metadata.agent_name,
+        cost: price_usage(&usage, pricing),
+    })
+}
+
+/// Cost of the runs matched by [`cost_report`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CostReport {
+    /// Agent the report is limited to, if any
+    pub agent: Option<String>,
+    /// Earliest run start included, if limited
+    pub since: Option<DateTime<Utc>>,
+    /// Number of runs included
+    pub runs: usize,
+    #[serde(flatten)]
+    pub cost: CostSummary,
+}
+
+/// Sum the cost of every run of `agent` (or of all agents) started at or
+/// after `since`
+pub async fn cost_report(
+    ledger: &dyn RunLedger,
+    agent: Option<&str>,
+    since: Option<DateTime<Utc>>,
+    pricing: &PricingTable,
+) -> Result<CostReport> {
+    let runs = ledger
+        .list_agent_runs(agent, since)
+        .await
+        .map_err(|e| AivcsError::StorageError(format!("Failed to list runs: {}", e)))?;
+
+    let mut usage = RunUsage::default();
+    for run in &runs {
+        usage.merge(&usage_of(ledger, run).await?);
+    }
+
+    Ok(CostReport {
+        agent: agent.map(str::to_string),
+        since,
+        runs: runs.len(),
+        cost: price_usage(&usage, pricing),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_usage_reads_aliases_and_outer_model() {
+        let payload = serde_json::json!({
+            "model": "gpt-4o-mini",
+            "usage": {"prompt_tokens": 10, "completion_tokens": 4},
+        });
+        let (model, tokens) = event_usage(&payload).unwrap();
+        assert_eq!(model, "gpt-4o-mini");
+        assert_eq!(tokens.input_tokens, 10);
+        assert_eq!(tokens.output_tokens, 4);
+
+        assert!(event_usage(&serde_json::json!({"tool_name": "grep"})).is_none());
+    }
+
+    #[test]
+    fn test_price_prefers_exact_then_longest_prefix() {
+        let cheap = ModelPrice {
+            input_per_mtok: 1.0,
+            output_per_mtok: 2.0,
+        };
+        let dear = ModelPrice {
+            input_per_mtok: 10.0,
+            output_per_mtok: 20.0,
+        };
+        let pricing = PricingTable::default()
+            .with_model("gpt-4o", dear)
+            .with_model("gpt-4o-mini", cheap);
+
+        assert_eq!(pricing.price("gpt-4o-mini-2024-07-18"), Some(&cheap));
+        assert_eq!(pricing.price("gpt-4o-2024-08-06"), Some(&dear));
+        assert_eq!(pricing.price("claude"), None);
+    }
+}
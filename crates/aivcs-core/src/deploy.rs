@@ -8,9 +8,10 @@
 use chrono::{DateTime, Utc};
 use oxidized_state::storage_traits::{ReleaseRegistry, RunId, RunLedger};
 
+use crate::cas::CasStore;
 use crate::deploy_runner::DeployByDigestRunner;
 use crate::domain::{AivcsError, Result};
-use crate::replay::{replay_run, ReplaySummary};
+use crate::replay::{replay_run_with_archive, ReplaySummary};
 
 /// Result of a deploy-by-digest invocation.
 #[derive(Debug, Clone)]
@@ -31,9 +32,11 @@ pub struct DeployResult {
 /// equality testing.
 ///
 /// Pass a fixed `timestamp` to get deterministic digests across invocations.
+/// Runs already archived out of `ledger` are replayed from `cas`.
 pub async fn deploy_by_digest(
     registry: &dyn ReleaseRegistry,
     ledger: &dyn RunLedger,
+    cas: &dyn CasStore,
     agent_name: &str,
     timestamp: Option<DateTime<Utc>>,
 ) -> Result<DeployResult> {
@@ -57,7 +60,7 @@ pub async fn deploy_by_digest(
     };
 
     // 3. Replay to get the golden digest
-    let (_events, replay_summary) = replay_run(ledger, &output.run_id.0).await?;
+    let (_events, replay_summary) = replay_run_with_archive(ledger, cas, &output.run_id.0).await?;
 
     Ok(DeployResult {
         run_id: output.run_id,
@@ -96,6 +96,7 @@ impl DeployByDigestRunner {
             final_state_digest: None,
             duration_ms: started.elapsed().as_millis() as u64,
             success: true,
+            usage: None,
         };
         ledger
             .complete_run(&run_id, summary)
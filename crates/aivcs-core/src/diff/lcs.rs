@@ -0,0 +1,225 @@
+//! Longest Common Subsequence (LCS) alignment shared by the tool-call differs.
+//!
+//! Short sequences use the full `m×n` DP table. When either sequence is longer
+//! than [`LINEAR_SPACE_THRESHOLD`], Hirschberg's divide-and-conquer computes
+//! the alignment in `O(m + n)` space (plus one bounded DP table per leaf).
+//!
+//! Both paths resolve ties identically: walking back from the end, a call in
+//! `a` is skipped whenever that keeps the LCS length, equal calls are matched
+//! next, and a call in `b` is skipped last. Hirschberg reproduces that
+//! alignment by splitting at the rightmost optimal column, so which path runs
+//! never changes the resulting diff.
+
+/// Sequences longer than this are aligned in linear space.
+pub(crate) const LINEAR_SPACE_THRESHOLD: usize = 1024;
+
+/// Align `a` and `b`, returning matched `(index_a, index_b)` pairs in order.
+pub(crate) fn lcs_alignment<T: PartialEq>(a: &[T], b: &[T]) -> Vec<(usize, usize)> {
+    if a.len() <= LINEAR_SPACE_THRESHOLD && b.len() <= LINEAR_SPACE_THRESHOLD {
+        quadratic_alignment(a, b)
+    } else {
+        linear_space_alignment(a, b)
+    }
+}
+
+/// LCS alignment from the full DP table; `O(m·n)` time and space.
+pub(crate) fn quadratic_alignment<T: PartialEq>(a: &[T], b: &[T]) -> Vec<(usize, usize)> {
+    let m = a.len();
+    let n = b.len();
+
+    if m == 0 || n == 0 {
+        return Vec::new();
+    }
+
+    // DP table: dp[i][j] = length of LCS of a[0..i] and b[0..j]
+    let mut dp = vec![vec![0usize; n + 1]; m + 1];
+
+    for i in 1..=m {
+        for j in 1..=n {
+            if a[i - 1] == b[j - 1] {
+                dp[i][j] = dp[i - 1][j - 1] + 1;
+            } else {
+                dp[i][j] = dp[i][j - 1].max(dp[i - 1][j]);
+            }
+        }
+    }
+
+    // Backtrack to find the LCS indices
+    let mut alignment = Vec::new();
+    let mut i = m;
+    let mut j = n;
+
+    while i > 0 && j > 0 {
+        if dp[i - 1][j] == dp[i][j] {
+            i -= 1;
+        } else if a[i - 1] == b[j - 1] {
+            alignment.push((i - 1, j - 1));
+            i -= 1;
+            j -= 1;
+        } else {
+            j -= 1;
+        }
+    }
+
+    alignment.reverse();
+    alignment
+}
+
+/// LCS alignment by Hirschberg's algorithm; `O(m·n)` time, linear space.
+///
+/// Yields exactly the alignment of [`quadratic_alignment`].
+pub(crate) fn linear_space_alignment<T: PartialEq>(a: &[T], b: &[T]) -> Vec<(usize, usize)> {
+    let mut alignment = Vec::new();
+    hirschberg(a, b, (0, 0), LINEAR_SPACE_THRESHOLD, &mut alignment);
+    alignment
+}
+
+/// Recursive step: sub-problems no longer than `leaf` on both sides are
+/// solved with the quadratic table.
+fn hirschberg<T: PartialEq>(
+    a: &[T],
+    b: &[T],
+    (offset_a, offset_b): (usize, usize),
+    leaf: usize,
+    out: &mut Vec<(usize, usize)>,
+) {
+    let m = a.len();
+    let n = b.len();
+
+    if m == 0 || n == 0 {
+        return;
+    }
+    if m <= leaf && n <= leaf {
+        out.extend(
+            quadratic_alignment(a, b)
+                .into_iter()
+                .map(|(i, j)| (i + offset_a, j + offset_b)),
+        );
+        return;
+    }
+    if m == 1 {
+        if let Some(j) = b.iter().rposition(|item| *item == a[0]) {
+            out.push((offset_a, offset_b + j));
+        }
+        return;
+    }
+
+    // Split `a` in half and find the column where an optimal path crosses:
+    // forward[j] + backward[n - j] is the LCS length through (mid, j).
+    let mid = m / 2;
+    let forward = lcs_last_row(&a[..mid], b, false);
+    let backward = lcs_last_row(&a[mid..], b, true);
+
+    let mut split = 0;
+    let mut best = 0;
+    for j in 0..=n {
+        let len = forward[j] + backward[n - j];
+        if len >= best {
+            best = len;
+            split = j;
+        }
+    }
+
+    hirschberg(&a[..mid], &b[..split], (offset_a, offset_b), leaf, out);
+    hirschberg(
+        &a[mid..],
+        &b[split..],
+        (offset_a + mid, offset_b + split),
+        leaf,
+        out,
+    );
+}
+
+/// Last row of the LCS table of `a` against `b`, in two rows of memory.
+///
+/// `row[j]` is the LCS length of `a` and `b[..j]`; with `reverse`, both are
+/// scanned from the end and `row[j]` is the LCS length of `a` and `b[n - j..]`.
+fn lcs_last_row<T: PartialEq>(a: &[T], b: &[T], reverse: bool) -> Vec<usize> {
+    fn at<T>(items: &[T], k: usize, reverse: bool) -> &T {
+        if reverse {
+            &items[items.len() - 1 - k]
+        } else {
+            &items[k]
+        }
+    }
+
+    let n = b.len();
+    let mut prev = vec![0usize; n + 1];
+    let mut cur = vec![0usize; n + 1];
+
+    for i in 0..a.len() {
+        let x = at(a, i, reverse);
+        for j in 1..=n {
+            let y = at(b, j - 1, reverse);
+            cur[j] = if x == y {
+                prev[j - 1] + 1
+            } else {
+                cur[j - 1].max(prev[j])
+            };
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic pseudo-random tool names from a small alphabet, so
+    /// sequences share many names and ties between alignments are common.
+    fn names(len: usize, seed: u64) -> Vec<&'static str> {
+        const TOOLS: [&str; 4] = ["search", "fetch", "parse", "write"];
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                TOOLS[(state >> 33) as usize % TOOLS.len()]
+            })
+            .collect()
+    }
+
+    fn is_valid_alignment(a: &[&str], b: &[&str], alignment: &[(usize, usize)]) -> bool {
+        alignment.iter().all(|&(i, j)| a[i] == b[j])
+            && alignment
+                .windows(2)
+                .all(|w| w[0].0 < w[1].0 && w[0].1 < w[1].1)
+    }
+
+    #[test]
+    fn linear_space_matches_quadratic_on_medium_inputs() {
+        for seed in 0..20 {
+            let a = names(150 + seed as usize * 7, seed);
+            let b = names(120 + seed as usize * 11, seed + 100);
+            let quadratic = quadratic_alignment(&a, &b);
+            assert!(is_valid_alignment(&a, &b, &quadratic));
+
+            // Leaf sizes below the threshold force real splits at every level.
+            for leaf in [0, 1, 16] {
+                let mut linear = Vec::new();
+                hirschberg(&a, &b, (0, 0), leaf, &mut linear);
+                assert_eq!(linear, quadratic, "seed {seed}, leaf {leaf}");
+            }
+        }
+    }
+
+    #[test]
+    fn linear_space_matches_quadratic_above_threshold() {
+        let a = names(LINEAR_SPACE_THRESHOLD + 300, 7);
+        let b = names(LINEAR_SPACE_THRESHOLD + 200, 8);
+        assert_eq!(lcs_alignment(&a, &b), quadratic_alignment(&a, &b));
+    }
+
+    #[test]
+    fn duplicate_call_aligns_to_the_latest_match() {
+        let a = ["search"];
+        let b = ["search", "search"];
+        assert_eq!(quadratic_alignment(&a, &b), vec![(0, 1)]);
+
+        let mut linear = Vec::new();
+        hirschberg(&a, &b, (0, 0), 0, &mut linear);
+        assert_eq!(linear, vec![(0, 1)]);
+    }
+}
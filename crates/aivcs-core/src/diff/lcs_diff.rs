@@ -1,9 +1,13 @@
 //! Tool-call sequence diffing for run comparison.
 //!
 //! This module provides structural diffing of tool-call sequences between two runs.
-//! It detects added/removed/reordered calls and parameter changes using
+//! It detects added/removed/reordered calls (grouping contiguous moves into
+//! blocks) and parameter changes using
 //! Longest Common Subsequence (LCS) alignment.
 
+use std::collections::HashSet;
+use std::ops::Range;
+
 use oxidized_state::storage_traits::RunEvent;
 use serde_json::Value;
 
@@ -42,6 +46,15 @@ pub enum ToolCallChange {
         seq_a: u64,
         seq_b: u64,
     },
+    /// Contiguous tool calls that moved together as a unit
+    MovedBlock {
+        /// Tool names of the moved calls, in order
+        tool_names: Vec<String>,
+        /// Positions of the block in A's tool-call sequence
+        from_range: Range<usize>,
+        /// Positions of the block in B's tool-call sequence
+        to_range: Range<usize>,
+    },
     /// Tool call exists in both, but parameters differ
     ParamDelta {
         tool_name: String,
@@ -51,6 +64,44 @@ pub enum ToolCallChange {
     },
 }
 
+/// How [`diff_tool_calls_with`] decides which calls of A and B correspond.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AlignMode {
+    /// Calls with the same tool name correspond
+    #[default]
+    Name,
+    /// Calls align on `(tool_name, canonical_args_digest)` first; the calls
+    /// left over fall back to name-only alignment only when their arguments
+    /// are similar, so unrelated calls to one tool show up as add + remove.
+    Arguments,
+}
+
+/// Options for [`diff_tool_calls_with`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffOptions {
+    /// Coalesce contiguous moved calls into a single
+    /// [`ToolCallChange::MovedBlock`]; when false, every moved call is
+    /// reported as its own [`ToolCallChange::Reordered`] entry.
+    pub group_moves: bool,
+    /// How calls of A and B are matched up
+    pub align: AlignMode,
+    /// RFC 6901 pointers whose changes are left out of `ParamDelta`s, e.g.
+    /// timestamps or request ids that differ on every run. A `*` segment
+    /// matches any single segment (`/context/*/ts`); ignoring a pointer
+    /// also ignores everything beneath it.
+    pub ignore_pointers: Vec<String>,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            group_moves: true,
+            align: AlignMode::Name,
+            ignore_pointers: Vec::new(),
+        }
+    }
+}
+
 /// Summary of differences between two runs' tool-call sequences.
 #[derive(Debug, Clone)]
 pub struct DiffSummary {
@@ -82,57 +133,96 @@ fn extract_tool_calls(events: &[RunEvent]) -> Vec<ToolCallEntry> {
         .collect()
 }
 
-/// Compute the Longest Common Subsequence (LCS) of tool names.
-///
-/// Returns a list of (index_a, index_b) pairs indicating matching positions
-/// in the two sequences.
+/// Align two call sequences by tool name (see [`super::lcs`]).
 fn lcs_alignment(calls_a: &[ToolCallEntry], calls_b: &[ToolCallEntry]) -> Vec<(usize, usize)> {
-    let m = calls_a.len();
-    let n = calls_b.len();
+    let names_a: Vec<&str> = calls_a.iter().map(|c| c.tool_name.as_str()).collect();
+    let names_b: Vec<&str> = calls_b.iter().map(|c| c.tool_name.as_str()).collect();
+    super::lcs::lcs_alignment(&names_a, &names_b)
+}
 
-    if m == 0 || n == 0 {
-        return Vec::new();
+/// Align two call sequences on tool name and argument digest, then align the
+/// calls between those anchors by name where their arguments are similar.
+fn argument_alignment(calls_a: &[ToolCallEntry], calls_b: &[ToolCallEntry]) -> Vec<(usize, usize)> {
+    fn keys(calls: &[ToolCallEntry]) -> Vec<(&str, String)> {
+        calls
+            .iter()
+            .map(|c| (c.tool_name.as_str(), args_digest(c)))
+            .collect()
     }
 
-    // DP table: dp[i][j] = length of LCS of calls_a[0..i] and calls_b[0..j]
-    let mut dp = vec![vec![0usize; n + 1]; m + 1];
+    /// Equal when the calls correspond under [`AlignMode::Arguments`].
+    struct SimilarCall<'a>(&'a ToolCallEntry);
 
-    for i in 1..=m {
-        for j in 1..=n {
-            if calls_a[i - 1].tool_name == calls_b[j - 1].tool_name {
-                dp[i][j] = dp[i - 1][j - 1] + 1;
-            } else {
-                dp[i][j] = dp[i][j - 1].max(dp[i - 1][j]);
-            }
+    impl PartialEq for SimilarCall<'_> {
+        fn eq(&self, other: &Self) -> bool {
+            calls_correspond(self.0, other.0, AlignMode::Arguments)
         }
     }
 
-    // Backtrack to find the LCS indices
+    let anchors = super::lcs::lcs_alignment(&keys(calls_a), &keys(calls_b));
+
     let mut alignment = Vec::new();
-    let mut i = m;
-    let mut j = n;
-
-    while i > 0 && j > 0 {
-        if calls_a[i - 1].tool_name == calls_b[j - 1].tool_name {
-            alignment.push((i - 1, j - 1));
-            i -= 1;
-            j -= 1;
-        } else if dp[i][j - 1] > dp[i - 1][j] {
-            j -= 1;
-        } else {
-            i -= 1;
+    let (mut start_a, mut start_b) = (0, 0);
+    let ends = anchors
+        .iter()
+        .copied()
+        .chain([(calls_a.len(), calls_b.len())]);
+    for (end_a, end_b) in ends {
+        let gap_a: Vec<SimilarCall> = calls_a[start_a..end_a].iter().map(SimilarCall).collect();
+        let gap_b: Vec<SimilarCall> = calls_b[start_b..end_b].iter().map(SimilarCall).collect();
+        alignment.extend(
+            super::lcs::lcs_alignment(&gap_a, &gap_b)
+                .into_iter()
+                .map(|(i, j)| (start_a + i, start_b + j)),
+        );
+        if end_a < calls_a.len() {
+            alignment.push((end_a, end_b));
         }
+        (start_a, start_b) = (end_a + 1, end_b + 1);
     }
-
-    alignment.reverse();
     alignment
 }
 
+/// SHA-256 of a call's canonical arguments: its payload minus `tool_name`.
+fn args_digest(call: &ToolCallEntry) -> String {
+    let mut args = call.payload.clone();
+    if let Value::Object(obj) = &mut args {
+        obj.remove("tool_name");
+    }
+    crate::domain::digest::compute_digest(&args).expect("JSON values always digest")
+}
+
+/// True if `a` and `b` may be reported as the same call under `mode`.
+///
+/// Under [`AlignMode::Arguments`], arguments are similar when at least half
+/// of the top-level fields present on either side hold equal values.
+fn calls_correspond(a: &ToolCallEntry, b: &ToolCallEntry, mode: AlignMode) -> bool {
+    if a.tool_name != b.tool_name {
+        return false;
+    }
+    if mode == AlignMode::Name {
+        return true;
+    }
+    let (Some(obj_a), Some(obj_b)) = (a.payload.as_object(), b.payload.as_object()) else {
+        return a.payload == b.payload;
+    };
+    let fields: HashSet<&String> = obj_a
+        .keys()
+        .chain(obj_b.keys())
+        .filter(|key| *key != "tool_name")
+        .collect();
+    let equal = fields
+        .iter()
+        .filter(|key| obj_a.get(**key) == obj_b.get(**key))
+        .count();
+    equal * 2 >= fields.len()
+}
+
 /// Recursively compute JSON differences.
 ///
 /// Returns a list of pointer paths and their differing values.
-fn json_diff(prefix: &str, val_a: &Value, val_b: &Value) -> Vec<ParamChange> {
-    if val_a == val_b {
+fn json_diff(prefix: &str, val_a: &Value, val_b: &Value, ignore: &[String]) -> Vec<ParamChange> {
+    if val_a == val_b || is_ignored(prefix, ignore) {
         return Vec::new();
     }
 
@@ -151,7 +241,7 @@ fn json_diff(prefix: &str, val_a: &Value, val_b: &Value) -> Vec<ParamChange> {
                 } else {
                     format!("{}/{}", prefix, key)
                 };
-                changes.extend(json_diff(&path, val_a_inner, val_b_inner));
+                changes.extend(json_diff(&path, val_a_inner, val_b_inner, ignore));
             }
             changes
         }
@@ -163,7 +253,7 @@ fn json_diff(prefix: &str, val_a: &Value, val_b: &Value) -> Vec<ParamChange> {
                 let val_a_inner = arr_a.get(i).unwrap_or(&Value::Null);
                 let val_b_inner = arr_b.get(i).unwrap_or(&Value::Null);
                 let path = format!("{}/{}", prefix, i);
-                changes.extend(json_diff(&path, val_a_inner, val_b_inner));
+                changes.extend(json_diff(&path, val_a_inner, val_b_inner, ignore));
             }
             changes
         }
@@ -181,14 +271,35 @@ fn json_diff(prefix: &str, val_a: &Value, val_b: &Value) -> Vec<ParamChange> {
     }
 }
 
+/// True if `pointer` matches one of the `ignore` patterns, segment by segment.
+fn is_ignored(pointer: &str, ignore: &[String]) -> bool {
+    if pointer.is_empty() {
+        return false;
+    }
+    ignore.iter().any(|pattern| {
+        let mut segments = pointer.split('/');
+        let mut pattern_segments = pattern.split('/');
+        loop {
+            match (pattern_segments.next(), segments.next()) {
+                (None, None) => return true,
+                (Some(p), Some(s)) if p == "*" || p == s => continue,
+                _ => return false,
+            }
+        }
+    })
+}
+
 /// Diff the tool-call sequences of two runs.
 ///
 /// # Algorithm
 ///
 /// 1. Extract tool calls (kind="tool_called") from both event sequences
-/// 2. Compute LCS alignment on tool names
+/// 2. Compute LCS alignment on tool names (or on names and arguments, see
+///    [`AlignMode`])
 /// 3. For each index:
-///    - Not in LCS → Added or Removed
+///    - Not in LCS, same tool name on both sides → moved (Reordered, or
+///      MovedBlock for a contiguous run)
+///    - Otherwise not in LCS → Added or Removed
 ///    - In LCS with seq mismatch → Reordered
 ///    - In LCS with payload difference → ParamDelta
 ///
@@ -198,25 +309,67 @@ pub fn diff_tool_calls(
     events_a: &[RunEvent],
     run_id_b: &str,
     events_b: &[RunEvent],
+) -> DiffSummary {
+    diff_tool_calls_with(
+        run_id_a,
+        events_a,
+        run_id_b,
+        events_b,
+        &DiffOptions::default(),
+    )
+}
+
+/// [`diff_tool_calls`] with explicit [`DiffOptions`].
+pub fn diff_tool_calls_with(
+    run_id_a: &str,
+    events_a: &[RunEvent],
+    run_id_b: &str,
+    events_b: &[RunEvent],
+    options: &DiffOptions,
 ) -> DiffSummary {
     let calls_a = extract_tool_calls(events_a);
     let calls_b = extract_tool_calls(events_b);
 
-    let alignment = lcs_alignment(&calls_a, &calls_b);
+    let alignment = match options.align {
+        AlignMode::Name => lcs_alignment(&calls_a, &calls_b),
+        AlignMode::Arguments => argument_alignment(&calls_a, &calls_b),
+    };
 
     // Build a set of aligned indices for quick lookup
-    let mut aligned_a: std::collections::HashSet<usize> = std::collections::HashSet::new();
-    let mut aligned_b: std::collections::HashSet<usize> = std::collections::HashSet::new();
+    let mut aligned_a: HashSet<usize> = HashSet::new();
+    let mut aligned_b: HashSet<usize> = HashSet::new();
     for (i_a, i_b) in &alignment {
         aligned_a.insert(*i_a);
         aligned_b.insert(*i_b);
     }
 
+    // Pair unaligned calls that reappear (same tool name, and similar
+    // arguments when aligning on them) on the other side: those moved rather
+    // than being removed and re-added.
+    let mut moves = Vec::new();
+    let mut moved_a: HashSet<usize> = HashSet::new();
+    let mut moved_b: HashSet<usize> = HashSet::new();
+    for (i_a, call_a) in calls_a.iter().enumerate() {
+        if aligned_a.contains(&i_a) {
+            continue;
+        }
+        let target = (0..calls_b.len()).find(|i_b| {
+            !aligned_b.contains(i_b)
+                && !moved_b.contains(i_b)
+                && calls_correspond(call_a, &calls_b[*i_b], options.align)
+        });
+        if let Some(i_b) = target {
+            moved_a.insert(i_a);
+            moved_b.insert(i_b);
+            moves.push((i_a, i_b));
+        }
+    }
+
     let mut changes = Vec::new();
 
     // Handle removed calls (in A but not aligned in B)
     for (i, call) in calls_a.iter().enumerate() {
-        if !aligned_a.contains(&i) {
+        if !aligned_a.contains(&i) && !moved_a.contains(&i) {
             changes.push(ToolCallChange::Removed {
                 entry: call.clone(),
             });
@@ -252,7 +405,12 @@ pub fn diff_tool_calls(
             });
         } else {
             // Check for parameter changes
-            let param_changes = json_diff("", &call_a.payload, &call_b.payload);
+            let param_changes = json_diff(
+                "",
+                &call_a.payload,
+                &call_b.payload,
+                &options.ignore_pointers,
+            );
             if !param_changes.is_empty() {
                 changes.push(ToolCallChange::ParamDelta {
                     tool_name: call_a.tool_name.clone(),
@@ -264,9 +422,29 @@ pub fn diff_tool_calls(
         }
     }
 
+    // Handle moved calls, then any parameter changes they carry
+    changes.extend(moved_changes(&calls_a, &calls_b, &moves, options));
+    for &(i_a, i_b) in &moves {
+        let (call_a, call_b) = (&calls_a[i_a], &calls_b[i_b]);
+        let param_changes = json_diff(
+            "",
+            &call_a.payload,
+            &call_b.payload,
+            &options.ignore_pointers,
+        );
+        if !param_changes.is_empty() {
+            changes.push(ToolCallChange::ParamDelta {
+                tool_name: call_a.tool_name.clone(),
+                seq_a: call_a.seq,
+                seq_b: call_b.seq,
+                changes: param_changes,
+            });
+        }
+    }
+
     // Handle added calls (in B but not aligned in A)
     for (i, call) in calls_b.iter().enumerate() {
-        if !aligned_b.contains(&i) {
+        if !aligned_b.contains(&i) && !moved_b.contains(&i) {
             changes.push(ToolCallChange::Added {
                 entry: call.clone(),
             });
@@ -283,6 +461,55 @@ pub fn diff_tool_calls(
     }
 }
 
+/// Report moved `(index_a, index_b)` pairs, ordered by their position in A.
+///
+/// With `options.group_moves`, each run of pairs adjacent on both sides
+/// becomes one `MovedBlock`; single moved calls stay `Reordered`.
+fn moved_changes(
+    calls_a: &[ToolCallEntry],
+    calls_b: &[ToolCallEntry],
+    moves: &[(usize, usize)],
+    options: &DiffOptions,
+) -> Vec<ToolCallChange> {
+    let reordered = |&(i_a, i_b): &(usize, usize)| ToolCallChange::Reordered {
+        tool_name: calls_a[i_a].tool_name.clone(),
+        seq_a: calls_a[i_a].seq,
+        seq_b: calls_b[i_b].seq,
+    };
+    if !options.group_moves {
+        return moves.iter().map(reordered).collect();
+    }
+
+    let mut changes = Vec::new();
+    let mut start = 0;
+    while start < moves.len() {
+        let mut end = start + 1;
+        while end < moves.len()
+            && moves[end].0 == moves[end - 1].0 + 1
+            && moves[end].1 == moves[end - 1].1 + 1
+        {
+            end += 1;
+        }
+
+        let block = &moves[start..end];
+        if block.len() == 1 {
+            changes.push(reordered(&block[0]));
+        } else {
+            let (first_a, first_b) = block[0];
+            changes.push(ToolCallChange::MovedBlock {
+                tool_names: block
+                    .iter()
+                    .map(|&(i_a, _)| calls_a[i_a].tool_name.clone())
+                    .collect(),
+                from_range: first_a..first_a + block.len(),
+                to_range: first_b..first_b + block.len(),
+            });
+        }
+        start = end;
+    }
+    changes
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -448,4 +675,174 @@ mod tests {
             assert!(matches!(change, ToolCallChange::Added { .. }));
         }
     }
+
+    #[test]
+    fn test_moved_block_is_grouped() {
+        // `lint, test, build` moves from the end of A to the front of B; the
+        // four anchor calls form the LCS.
+        let names_a = [
+            "plan",
+            "search",
+            "fetch",
+            "summarize",
+            "lint",
+            "test",
+            "build",
+        ];
+        let names_b = [
+            "lint",
+            "test",
+            "build",
+            "plan",
+            "search",
+            "fetch",
+            "summarize",
+        ];
+        let events = |names: &[&str]| -> Vec<RunEvent> {
+            names
+                .iter()
+                .enumerate()
+                .map(|(i, name)| make_tool_event(i as u64 + 1, name, None))
+                .collect()
+        };
+        let (events_a, events_b) = (events(&names_a), events(&names_b));
+
+        let diff = diff_tool_calls("run_a", &events_a, "run_b", &events_b);
+        assert!(!diff.identical);
+        assert_eq!(diff.changes.len(), 1, "{:?}", diff.changes);
+        match &diff.changes[0] {
+            ToolCallChange::MovedBlock {
+                tool_names,
+                from_range,
+                to_range,
+            } => {
+                assert_eq!(tool_names, &["lint", "test", "build"]);
+                assert_eq!(from_range, &(4..7));
+                assert_eq!(to_range, &(0..3));
+            }
+            other => panic!("Expected MovedBlock, got {:?}", other),
+        }
+
+        let ungrouped = diff_tool_calls_with(
+            "run_a",
+            &events_a,
+            "run_b",
+            &events_b,
+            &DiffOptions {
+                group_moves: false,
+                ..DiffOptions::default()
+            },
+        );
+        let reordered: Vec<(&str, u64, u64)> = ungrouped
+            .changes
+            .iter()
+            .map(|change| match change {
+                ToolCallChange::Reordered {
+                    tool_name,
+                    seq_a,
+                    seq_b,
+                } => (tool_name.as_str(), *seq_a, *seq_b),
+                other => panic!("Expected Reordered, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            reordered,
+            vec![("lint", 5, 1), ("test", 6, 2), ("build", 7, 3)]
+        );
+    }
+
+    #[test]
+    fn test_ignored_pointers_are_skipped() {
+        let event = |seq: u64, ts: u64, query: &str| {
+            make_tool_event(
+                seq,
+                "search",
+                Some(serde_json::json!({
+                    "request_id": format!("req-{seq}"),
+                    "context": [{ "ts": ts, "doc": "a" }, { "ts": ts + 1, "doc": "b" }],
+                    "query": query,
+                })),
+            )
+        };
+        let events_a = vec![event(1, 100, "rust")];
+        let events_b = vec![event(2, 200, "rust")];
+        let options = DiffOptions {
+            ignore_pointers: vec!["/request_id".to_string(), "/context/*/ts".to_string()],
+            ..DiffOptions::default()
+        };
+
+        let diff = diff_tool_calls_with("run_a", &events_a, "run_b", &events_b, &options);
+        assert!(diff.identical, "{:?}", diff.changes);
+
+        // A sibling of an ignored path is still reported
+        let events_b = vec![event(2, 200, "go")];
+        let diff = diff_tool_calls_with("run_a", &events_a, "run_b", &events_b, &options);
+        assert_eq!(diff.changes.len(), 1);
+        match &diff.changes[0] {
+            ToolCallChange::ParamDelta { changes, .. } => {
+                let pointers: Vec<&str> = changes.iter().map(|c| c.pointer.as_str()).collect();
+                assert_eq!(pointers, vec!["/query"]);
+            }
+            other => panic!("Expected ParamDelta, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_argument_alignment_splits_dissimilar_calls() {
+        let events_a = vec![
+            make_tool_event(1, "plan", None),
+            make_tool_event(
+                2,
+                "search",
+                Some(serde_json::json!({ "query": "rust async runtimes" })),
+            ),
+        ];
+        let events_b = vec![
+            make_tool_event(1, "plan", None),
+            make_tool_event(
+                2,
+                "search",
+                Some(serde_json::json!({ "query": "pasta recipes" })),
+            ),
+        ];
+
+        // Name-only alignment pairs the two searches into a param delta
+        let by_name = diff_tool_calls("run_a", &events_a, "run_b", &events_b);
+        assert_eq!(by_name.changes.len(), 1);
+        assert!(matches!(
+            &by_name.changes[0],
+            ToolCallChange::ParamDelta { .. }
+        ));
+
+        let options = DiffOptions {
+            align: AlignMode::Arguments,
+            ..DiffOptions::default()
+        };
+        let by_args = diff_tool_calls_with("run_a", &events_a, "run_b", &events_b, &options);
+        assert_eq!(by_args.changes.len(), 2, "{:?}", by_args.changes);
+        assert!(matches!(
+            &by_args.changes[0],
+            ToolCallChange::Removed { entry } if entry.payload["query"] == "rust async runtimes"
+        ));
+        assert!(matches!(
+            &by_args.changes[1],
+            ToolCallChange::Added { entry } if entry.payload["query"] == "pasta recipes"
+        ));
+
+        // Similar arguments still fall back to a name alignment
+        let events_b = vec![
+            make_tool_event(1, "plan", None),
+            make_tool_event(
+                2,
+                "search",
+                Some(serde_json::json!({ "query": "rust async runtimes", "limit": 5 })),
+            ),
+        ];
+        let similar = diff_tool_calls_with("run_a", &events_a, "run_b", &events_b, &options);
+        assert_eq!(similar.changes.len(), 1);
+        assert!(matches!(
+            &similar.changes[0],
+            ToolCallChange::ParamDelta { .. }
+        ));
+    }
 }
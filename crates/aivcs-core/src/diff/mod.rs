@@ -1,5 +1,7 @@
+mod lcs;
 pub mod lcs_diff;
 pub mod node_paths;
 pub mod semantic_graph;
+pub mod spec_behavior;
 pub mod state_diff;
 pub mod tool_calls;
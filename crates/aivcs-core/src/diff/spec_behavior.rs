@@ -0,0 +1,103 @@
+//! Behavioral comparison of two agent specs through their recorded runs.
+//!
+//! Answers "did the new spec change behavior?": the latest finished run of
+//! each spec is replayed and their tool-call sequences and node paths are
+//! diffed.
+
+use oxidized_state::storage_traits::{ContentDigest, RunLedger, RunRecord, RunStatus};
+
+use super::node_paths::{diff_node_paths, NodePathDiff};
+use super::tool_calls::{diff_tool_calls, ToolCallDiff};
+use crate::domain::{AivcsError, Result};
+use crate::replay::replay_run;
+
+/// How the runs chosen for two specs differ.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BehaviorDiff {
+    /// Run compared on behalf of spec A.
+    pub run_a: String,
+    /// Run compared on behalf of spec B.
+    pub run_b: String,
+    /// Tool-call changes from run A to run B.
+    pub tool_calls: ToolCallDiff,
+    /// Where the node traversal paths diverge, if they do.
+    pub node_paths: NodePathDiff,
+}
+
+impl BehaviorDiff {
+    /// True if both runs made the same tool calls along the same node path.
+    pub fn is_empty(&self) -> bool {
+        self.tool_calls.is_empty() && self.node_paths.is_empty()
+    }
+}
+
+/// Compare the behavior of `spec_a` and `spec_b` on their latest runs.
+///
+/// Only finished (non-running) runs are considered. With `input_tag`, the
+/// runs must also carry equal values for that key in their metadata tags,
+/// so both specs are compared on the same input; the newest such pair wins.
+///
+/// # Errors
+///
+/// - `AivcsError::NoComparableRuns` if either spec has no finished run, or
+///   no pair of runs shares a value for `input_tag`.
+/// - `AivcsError::StorageError` if the ledger cannot be read.
+pub async fn compare_specs_behavior(
+    ledger: &dyn RunLedger,
+    spec_a: &ContentDigest,
+    spec_b: &ContentDigest,
+    input_tag: Option<&str>,
+) -> Result<BehaviorDiff> {
+    let runs_a = finished_runs(ledger, spec_a).await?;
+    let runs_b = finished_runs(ledger, spec_b).await?;
+
+    let (run_a, run_b) = match input_tag {
+        None => (&runs_a[0], &runs_b[0]),
+        Some(tag) => runs_a
+            .iter()
+            .find_map(|a| {
+                let input = a.metadata.tags.get(tag)?;
+                let b = runs_b
+                    .iter()
+                    .find(|b| b.metadata.tags.get(tag) == Some(input))?;
+                Some((a, b))
+            })
+            .ok_or_else(|| {
+                AivcsError::NoComparableRuns(format!(
+                    "no runs of specs {} and {} share input tag '{}'",
+                    spec_a.short(),
+                    spec_b.short(),
+                    tag
+                ))
+            })?,
+    };
+
+    let (events_a, _) = replay_run(ledger, &run_a.run_id.0).await?;
+    let (events_b, _) = replay_run(ledger, &run_b.run_id.0).await?;
+
+    Ok(BehaviorDiff {
+        run_a: run_a.run_id.0.clone(),
+        run_b: run_b.run_id.0.clone(),
+        tool_calls: diff_tool_calls(&events_a, &events_b),
+        node_paths: diff_node_paths(&events_a, &events_b),
+    })
+}
+
+/// Finished runs of `spec`, newest first; never empty.
+async fn finished_runs(ledger: &dyn RunLedger, spec: &ContentDigest) -> Result<Vec<RunRecord>> {
+    let mut runs: Vec<RunRecord> = ledger
+        .list_runs(Some(spec))
+        .await
+        .map_err(|e| AivcsError::StorageError(e.to_string()))?
+        .into_iter()
+        .filter(|run| run.status != RunStatus::Running)
+        .collect();
+    if runs.is_empty() {
+        return Err(AivcsError::NoComparableRuns(format!(
+            "no finished run for spec {}",
+            spec.short()
+        )));
+    }
+    runs.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(runs)
+}
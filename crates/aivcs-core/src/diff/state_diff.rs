@@ -1,8 +1,11 @@
+use std::collections::BTreeSet;
+
 use oxidized_state::RunEvent;
+use serde::Serialize;
 use serde_json::Value;
 
 /// A single delta at an RFC 6901 JSON pointer path between two states.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct StateDelta {
     /// RFC 6901 JSON pointer, e.g. `"/memory/0/context"`.
     pub pointer: String,
@@ -13,7 +16,7 @@ pub struct StateDelta {
 }
 
 /// The result of diffing two states at scoped JSON pointer paths.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct ScopedStateDiff {
     pub deltas: Vec<StateDelta>,
 }
@@ -73,6 +76,42 @@ pub fn diff_scoped_state(a: &Value, b: &Value, pointers: &[&str]) -> ScopedState
     ScopedStateDiff { deltas }
 }
 
+/// Diff every leaf below the RFC 6901 pointer `scope` in `a` and `b`.
+///
+/// Descends objects and arrays under `scope` in both values and runs
+/// [`diff_scoped_state`] over the union of their leaf pointers, so changes
+/// outside the subtree are never reported. A scope missing from one side
+/// reports each leaf of the other side against `Null`; `""` scopes the
+/// whole document.
+pub fn diff_state_subtree(a: &Value, b: &Value, scope: &str) -> ScopedStateDiff {
+    let mut pointers = BTreeSet::new();
+    for root in [a.pointer(scope), b.pointer(scope)].into_iter().flatten() {
+        collect_leaf_pointers(root, scope.to_string(), &mut pointers);
+    }
+    let pointers: Vec<&str> = pointers.iter().map(String::as_str).collect();
+    diff_scoped_state(a, b, &pointers)
+}
+
+/// Add the pointer of every scalar or empty container below `value`
+fn collect_leaf_pointers(value: &Value, pointer: String, out: &mut BTreeSet<String>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                let escaped = key.replace('~', "~0").replace('/', "~1");
+                collect_leaf_pointers(child, format!("{}/{}", pointer, escaped), out);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for (i, child) in items.iter().enumerate() {
+                collect_leaf_pointers(child, format!("{}/{}", pointer, i), out);
+            }
+        }
+        _ => {
+            out.insert(pointer);
+        }
+    }
+}
+
 /// Convenience: extract last checkpoint state from two event streams and diff
 /// at the given JSON pointer paths.
 ///
@@ -78,44 +78,11 @@ fn extract_tool_calls(events: &[RunEvent]) -> Vec<ToolCall> {
 // Alignment (LCS)
 // ---------------------------------------------------------------------------
 
+/// Align two call sequences by tool name (see [`super::lcs`]).
 fn lcs_alignment(calls_a: &[ToolCall], calls_b: &[ToolCall]) -> Vec<(usize, usize)> {
-    let m = calls_a.len();
-    let n = calls_b.len();
-
-    if m == 0 || n == 0 {
-        return Vec::new();
-    }
-
-    let mut dp = vec![vec![0usize; n + 1]; m + 1];
-
-    for i in 1..=m {
-        for j in 1..=n {
-            if calls_a[i - 1].tool_name == calls_b[j - 1].tool_name {
-                dp[i][j] = dp[i - 1][j - 1] + 1;
-            } else {
-                dp[i][j] = dp[i][j - 1].max(dp[i - 1][j]);
-            }
-        }
-    }
-
-    let mut alignment = Vec::new();
-    let mut i = m;
-    let mut j = n;
-
-    while i > 0 && j > 0 {
-        if calls_a[i - 1].tool_name == calls_b[j - 1].tool_name {
-            alignment.push((i - 1, j - 1));
-            i -= 1;
-            j -= 1;
-        } else if dp[i][j - 1] > dp[i - 1][j] {
-            j -= 1;
-        } else {
-            i -= 1;
-        }
-    }
-
-    alignment.reverse();
-    alignment
+    let names_a: Vec<&str> = calls_a.iter().map(|c| c.tool_name.as_str()).collect();
+    let names_b: Vec<&str> = calls_b.iter().map(|c| c.tool_name.as_str()).collect();
+    super::lcs::lcs_alignment(&names_a, &names_b)
 }
 
 // ---------------------------------------------------------------------------
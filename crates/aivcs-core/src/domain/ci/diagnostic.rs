@@ -1,5 +1,7 @@
 //! Normalized CI diagnostic types.
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 /// Severity level for a diagnostic.
@@ -86,6 +88,75 @@ impl Diagnostic {
     }
 }
 
+/// A deduplicated diagnostic and how many times it was reported.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RankedDiagnostic {
+    /// First report of the issue, at the highest severity seen for it.
+    pub diagnostic: Diagnostic,
+
+    /// Number of reports merged into this entry.
+    pub occurrences: u32,
+}
+
+/// Output of [`normalize_diagnostics`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct NormalizedDiagnostics {
+    /// One entry per distinct issue, most important first.
+    pub ranked: Vec<RankedDiagnostic>,
+
+    /// Every input diagnostic in its original order, when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub all: Option<Vec<Diagnostic>>,
+}
+
+/// Deduplicate diagnostics by `(code, primary span)` and rank them.
+///
+/// The primary span is the file, line and column; diagnostics without a
+/// code are keyed by their message instead. Entries are ordered by
+/// severity, then by occurrence count, then by first appearance. With
+/// `keep_all`, the unmodified input is returned alongside.
+pub fn normalize_diagnostics(diagnostics: &[Diagnostic], keep_all: bool) -> NormalizedDiagnostics {
+    type Key<'a> = (&'a str, Option<&'a str>, Option<u32>, Option<u32>);
+
+    let mut index: HashMap<Key<'_>, usize> = HashMap::new();
+    let mut ranked: Vec<RankedDiagnostic> = Vec::new();
+    for diag in diagnostics {
+        let key = (
+            diag.code.as_deref().unwrap_or(&diag.message),
+            diag.file.as_deref(),
+            diag.line,
+            diag.column,
+        );
+        match index.get(&key) {
+            Some(&i) => {
+                let entry = &mut ranked[i];
+                entry.occurrences += 1;
+                entry.diagnostic.severity = entry.diagnostic.severity.max(diag.severity);
+            }
+            None => {
+                index.insert(key, ranked.len());
+                ranked.push(RankedDiagnostic {
+                    diagnostic: diag.clone(),
+                    occurrences: 1,
+                });
+            }
+        }
+    }
+
+    // Stable, so ties keep first-appearance order
+    ranked.sort_by(|a, b| {
+        b.diagnostic
+            .severity
+            .cmp(&a.diagnostic.severity)
+            .then(b.occurrences.cmp(&a.occurrences))
+    });
+
+    NormalizedDiagnostics {
+        ranked,
+        all: keep_all.then(|| diagnostics.to_vec()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -151,4 +222,42 @@ mod tests {
         assert!(diag.column.is_none());
         assert!(diag.evidence.is_none());
     }
+
+    #[test]
+    fn test_normalize_diagnostics_dedups_and_ranks() {
+        let missing_import = || {
+            Diagnostic::new(
+                Severity::Error,
+                "cannot find type `HashMap` in this scope".to_string(),
+                DiagnosticSource::Rustc,
+            )
+            .with_code("E0412".to_string())
+            .with_location("src/lib.rs".to_string(), 3, 12)
+        };
+        let lint = Diagnostic::new(
+            Severity::Warning,
+            "unneeded `return` statement".to_string(),
+            DiagnosticSource::Clippy,
+        )
+        .with_code("clippy::needless_return".to_string())
+        .with_location("src/lib.rs".to_string(), 9, 5);
+        let input = vec![
+            lint.clone(),
+            missing_import(),
+            missing_import(),
+            missing_import(),
+        ];
+
+        let normalized = normalize_diagnostics(&input, false);
+        assert_eq!(normalized.ranked.len(), 2);
+        assert_eq!(normalized.ranked[0].diagnostic, missing_import());
+        assert_eq!(normalized.ranked[0].occurrences, 3);
+        assert_eq!(normalized.ranked[1].diagnostic, lint);
+        assert_eq!(normalized.ranked[1].occurrences, 1);
+        assert!(normalized.all.is_none());
+
+        let full = normalize_diagnostics(&input, true);
+        assert_eq!(full.ranked, normalized.ranked);
+        assert_eq!(full.all, Some(input));
+    }
 }
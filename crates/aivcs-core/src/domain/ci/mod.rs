@@ -13,8 +13,11 @@ pub mod result;
 pub mod run_spec;
 pub mod verification;
 
-pub use diagnostic::{Diagnostic, DiagnosticSource, Severity};
-pub use repair::{PatchCommit, RepairPlan, RepairStrategy};
+pub use diagnostic::{
+    normalize_diagnostics, Diagnostic, DiagnosticSource, NormalizedDiagnostics, RankedDiagnostic,
+    Severity,
+};
+pub use repair::{PatchCommit, RepairAction, RepairPlan, RepairPolicy, RepairStrategy};
 pub use result::{CIResult, CIStageResult, CIStatus};
 pub use run_spec::{CIRunSpec, CIRunSpecFields, CITrigger};
 pub use verification::VerificationLink;
@@ -3,6 +3,8 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::diagnostic::Severity;
+
 /// Strategy for automated repair.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -28,6 +30,58 @@ pub struct PatchCommit {
     pub description: String,
 }
 
+/// One proposed repair, addressing one deduplicated diagnostic.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RepairAction {
+    /// File to change (relative to workspace root), if the diagnostic has one.
+    pub file: Option<String>,
+
+    /// Line of the diagnostic's primary span (1-indexed).
+    pub line: Option<u32>,
+
+    /// Column of the diagnostic's primary span (1-indexed).
+    pub column: Option<u32>,
+
+    /// Diagnostic code being addressed.
+    pub code: Option<String>,
+
+    /// Why this change is proposed.
+    pub rationale: String,
+
+    /// Command that applies the repair, when it can be automated.
+    pub fix_command: Option<Vec<String>>,
+
+    /// Number of reports of the diagnostic this action covers.
+    pub occurrences: u32,
+}
+
+/// Bounds applied when planning a repair.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RepairPolicy {
+    /// Most actions a plan may propose.
+    pub max_actions: usize,
+
+    /// Diagnostics below this severity get no action.
+    pub min_severity: Severity,
+
+    /// Whether actions with a fix command may be applied automatically.
+    pub allow_auto_fix: bool,
+
+    /// Maximum allowed repair attempts.
+    pub max_attempts: u32,
+}
+
+impl Default for RepairPolicy {
+    fn default() -> Self {
+        Self {
+            max_actions: 20,
+            min_severity: Severity::Warning,
+            allow_auto_fix: true,
+            max_attempts: 3,
+        }
+    }
+}
+
 /// A bounded repair plan for a CI run.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct RepairPlan {
@@ -40,6 +94,10 @@ pub struct RepairPlan {
     /// Proposed file patches.
     pub patches: Vec<PatchCommit>,
 
+    /// Proposed repair actions, most important first.
+    #[serde(default)]
+    pub actions: Vec<RepairAction>,
+
     /// Maximum allowed repair attempts.
     pub max_attempts: u32,
 
@@ -54,6 +112,7 @@ impl RepairPlan {
             run_id,
             strategy,
             patches: Vec::new(),
+            actions: Vec::new(),
             max_attempts,
             current_attempt: 0,
         }
@@ -64,6 +123,12 @@ impl RepairPlan {
         self.patches.push(patch);
         self
     }
+
+    /// Add a repair action to the plan.
+    pub fn with_action(mut self, action: RepairAction) -> Self {
+        self.actions.push(action);
+        self
+    }
 }
 
 #[cfg(test)]
@@ -2,89 +2,19 @@
 //!
 //! This module implements RFC 8785-compliant canonical JSON serialization with:
 //! - UTF-16 code unit ordering for object keys (§3.2.3)
-//! - Number normalization (integer-valued floats → integers; reject NaN/Infinity)
+//! - Number normalization (integer-valued floats → integers)
 //! - SHA256 hex digest computation
 
-use crate::domain::error::{AivcsError, Result};
+use crate::domain::error::Result;
 use sha2::{Digest, Sha256};
 
-/// Recursively sort JSON object keys using UTF-16 code unit ordering (RFC 8785 §3.2.3).
-fn sort_keys_utf16(value: &serde_json::Value) -> serde_json::Value {
-    match value {
-        serde_json::Value::Object(map) => {
-            let mut sorted = serde_json::Map::new();
-            let mut keys: Vec<_> = map.keys().collect();
-
-            // Sort by UTF-16 code unit order (RFC 8785)
-            keys.sort_by(|a, b| {
-                let a_utf16: Vec<u16> = a.encode_utf16().collect();
-                let b_utf16: Vec<u16> = b.encode_utf16().collect();
-                a_utf16.cmp(&b_utf16)
-            });
-
-            for key in keys {
-                if let Some(v) = map.get(key) {
-                    sorted.insert(key.to_string(), sort_keys_utf16(v));
-                }
-            }
-            serde_json::Value::Object(sorted)
-        }
-        serde_json::Value::Array(arr) => {
-            serde_json::Value::Array(arr.iter().map(sort_keys_utf16).collect())
-        }
-        other => other.clone(),
-    }
-}
-
-/// Normalize numbers: integer-valued floats → integer repr; reject NaN/Infinity.
-fn normalize_value(value: &serde_json::Value) -> Result<serde_json::Value> {
-    match value {
-        serde_json::Value::Object(map) => {
-            let mut normalized = serde_json::Map::new();
-            for (k, v) in map.iter() {
-                normalized.insert(k.clone(), normalize_value(v)?);
-            }
-            Ok(serde_json::Value::Object(normalized))
-        }
-        serde_json::Value::Array(arr) => {
-            let normalized = arr
-                .iter()
-                .map(normalize_value)
-                .collect::<Result<Vec<_>>>()?;
-            Ok(serde_json::Value::Array(normalized))
-        }
-        serde_json::Value::Number(n) => {
-            // If already an integer (via serde_json), pass through
-            if n.is_i64() || n.is_u64() {
-                Ok(serde_json::Value::Number(n.clone()))
-            } else if let Some(f) = n.as_f64() {
-                // Check for NaN or Infinity
-                if !f.is_finite() {
-                    return Err(AivcsError::InvalidAgentSpec(
-                        "NaN/Infinity not permitted in canonical JSON".to_string(),
-                    ));
-                }
-                // If integer-valued, convert to integer representation
-                if f.fract() == 0.0 && f >= i64::MIN as f64 && f <= i64::MAX as f64 {
-                    Ok(serde_json::Value::Number(serde_json::Number::from(
-                        f as i64,
-                    )))
-                } else {
-                    Ok(serde_json::Value::Number(n.clone()))
-                }
-            } else {
-                Ok(serde_json::Value::Number(n.clone()))
-            }
-        }
-        other => Ok(other.clone()),
-    }
-}
-
 /// Convert JSON value to canonical form: normalize numbers → sort keys → compact JSON.
+///
+/// Delegates to [`oxidized_state::canonical_json`], the single encoder shared
+/// by commit ids, spec digests, and CI object digests.
 pub fn canonical_json(value: &serde_json::Value) -> Result<String> {
-    let normalized = normalize_value(value)?;
-    let sorted = sort_keys_utf16(&normalized);
-    Ok(serde_json::to_string(&sorted)?)
+    let bytes = oxidized_state::canonical_json(value);
+    Ok(String::from_utf8(bytes).expect("canonical JSON is valid UTF-8"))
 }
 
 /// Compute SHA256 hex digest of canonical JSON.
@@ -22,6 +22,9 @@ pub enum AivcsError {
     #[error("invalid CI run spec: {0}")]
     InvalidCIRunSpec(String),
 
+    #[error("invalid scorer '{scorer}': {reason}")]
+    InvalidScorer { scorer: String, reason: String },
+
     #[error("run not found: {0}")]
     RunNotFound(uuid::Uuid),
 
@@ -54,6 +57,12 @@ pub enum AivcsError {
 
     #[error("multi-repo error: {0}")]
     MultiRepo(String),
+
+    #[error("run events must be contiguous from seq 1: expected seq {expected}, got {actual}")]
+    NonContiguousEvents { expected: u64, actual: u64 },
+
+    #[error("no comparable runs: {0}")]
+    NoComparableRuns(String),
 }
 
 /// Result type for AIVCS domain operations.
@@ -3,10 +3,11 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use uuid::Uuid;
 
 use super::digest;
-use super::error::Result;
+use super::error::{AivcsError, Result};
 
 /// Enumeration of available scorer types.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -21,6 +22,14 @@ pub enum ScorerType {
     /// Tool call sequence matching.
     ToolCallSequence,
 
+    /// Output must validate against the JSON Schema in `params.schema`.
+    /// Only the keywords [`ScorerConfig::validate`] accepts are supported.
+    JsonSchema,
+
+    /// Like exact match, but numbers may differ by `params.abs` or by
+    /// `params.rel` relative to the larger magnitude.
+    NumericTolerance,
+
     /// Custom scorer extension.
     Custom(String),
 }
@@ -38,6 +47,29 @@ pub struct ScorerConfig {
     pub params: serde_json::Value,
 }
 
+impl ScorerConfig {
+    /// Check that the parameters can be scored as configured.
+    ///
+    /// A `JsonSchema` scorer needs a `params.schema` that uses only the
+    /// supported keywords; anything else (`anyOf`, `$ref`, `pattern`, ...)
+    /// is rejected rather than ignored, so an unenforced constraint can't
+    /// let invalid output score 1.0.
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason: String| AivcsError::InvalidScorer {
+            scorer: self.name.clone(),
+            reason,
+        };
+        if self.scorer_type == ScorerType::JsonSchema {
+            let schema = self
+                .params
+                .get("schema")
+                .ok_or_else(|| invalid("missing params.schema".to_string()))?;
+            check_schema(schema, "#").map_err(invalid)?;
+        }
+        Ok(())
+    }
+}
+
 /// Thresholds for evaluation pass/fail criteria.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct EvalThresholds {
@@ -184,8 +216,12 @@ impl EvalSuite {
         digest::compute_digest(&json)
     }
 
-    /// Finalize the suite: compute and set suite_digest from current fields.
+    /// Finalize the suite: validate its scorers, then compute and set
+    /// suite_digest from current fields.
     pub fn finalize(mut self) -> Result<Self> {
+        for scorer in &self.scorers {
+            scorer.validate()?;
+        }
         let fields = EvalSuiteFields {
             name: self.name.clone(),
             version: self.version.clone(),
@@ -234,46 +270,133 @@ impl DeterministicEvalRunner {
     ///
     /// `actual_outputs` maps test case IDs to the concrete run output for that case.
     ///
-    /// Returns `Err` if `suite.suite_digest` is empty (call `finalize()` first).
+    /// Returns `Err` if `suite.suite_digest` is empty (call `finalize()` first)
+    /// or a scorer is invalid.
     pub fn run_with_outputs(
         &self,
         suite: &EvalSuite,
         actual_outputs: &HashMap<Uuid, serde_json::Value>,
     ) -> Result<EvalRunReport> {
-        if suite.suite_digest.is_empty() {
-            return Err(super::error::AivcsError::DigestMismatch {
-                expected: "<non-empty>".to_string(),
-                actual: "<empty>".to_string(),
-            });
-        }
+        Self::check_runnable(suite)?;
 
         let mut case_results = Vec::with_capacity(suite.test_cases.len());
 
         for case in &suite.test_cases {
-            let actual = actual_outputs
-                .get(&case.case_id)
-                .cloned()
-                .unwrap_or(serde_json::Value::Null);
-
-            let score = self.score_case(suite, case, &actual);
-            let passed = if case.expected.is_some() {
-                score >= 1.0
-            } else {
-                score > 0.0
-            };
+            let result = self.evaluate_case(suite, case, actual_outputs);
+            let passed = result.passed;
+            case_results.push(result);
 
-            case_results.push(EvalCaseResult {
-                case_id: case.case_id,
-                score,
-                passed,
-                actual,
-            });
+            if suite.thresholds.fail_fast && !passed {
+                break;
+            }
+        }
 
+        Ok(self.build_report(suite, case_results))
+    }
+
+    /// Like [`run_with_outputs`](Self::run_with_outputs), but scores cases
+    /// on up to `max_concurrency` threads.
+    ///
+    /// Results are assembled in suite order, so the report is identical to
+    /// the sequential one whatever order cases finish in. With `fail_fast`,
+    /// the report stops at the first failing case by suite index; cases
+    /// after it that were already scored are discarded.
+    pub fn run_with_outputs_parallel(
+        &self,
+        suite: &EvalSuite,
+        actual_outputs: &HashMap<Uuid, serde_json::Value>,
+        max_concurrency: usize,
+    ) -> Result<EvalRunReport> {
+        Self::check_runnable(suite)?;
+
+        let cases = &suite.test_cases;
+        let next = AtomicUsize::new(0);
+        // Lowest failing index seen so far; later cases needn't be scored
+        let first_failure = AtomicUsize::new(usize::MAX);
+        let mut slots: Vec<Option<EvalCaseResult>> = vec![None; cases.len()];
+
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..max_concurrency.clamp(1, cases.len().max(1)))
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut scored = Vec::new();
+                        loop {
+                            let i = next.fetch_add(1, Ordering::Relaxed);
+                            if i >= cases.len() || i > first_failure.load(Ordering::Relaxed) {
+                                break;
+                            }
+                            let result = self.evaluate_case(suite, &cases[i], actual_outputs);
+                            if suite.thresholds.fail_fast && !result.passed {
+                                first_failure.fetch_min(i, Ordering::Relaxed);
+                            }
+                            scored.push((i, result));
+                        }
+                        scored
+                    })
+                })
+                .collect();
+            for worker in workers {
+                for (i, result) in worker.join().expect("eval worker panicked") {
+                    slots[i] = Some(result);
+                }
+            }
+        });
+
+        let mut case_results = Vec::with_capacity(cases.len());
+        for slot in slots {
+            let Some(result) = slot else { break };
+            let passed = result.passed;
+            case_results.push(result);
             if suite.thresholds.fail_fast && !passed {
                 break;
             }
         }
 
+        Ok(self.build_report(suite, case_results))
+    }
+
+    /// A suite loaded from JSON may never have been finalized, so scorers
+    /// are checked again here.
+    fn check_runnable(suite: &EvalSuite) -> Result<()> {
+        if suite.suite_digest.is_empty() {
+            return Err(AivcsError::DigestMismatch {
+                expected: "<non-empty>".to_string(),
+                actual: "<empty>".to_string(),
+            });
+        }
+        for scorer in &suite.scorers {
+            scorer.validate()?;
+        }
+        Ok(())
+    }
+
+    fn evaluate_case(
+        &self,
+        suite: &EvalSuite,
+        case: &EvalTestCase,
+        actual_outputs: &HashMap<Uuid, serde_json::Value>,
+    ) -> EvalCaseResult {
+        let actual = actual_outputs
+            .get(&case.case_id)
+            .cloned()
+            .unwrap_or(serde_json::Value::Null);
+
+        let score = self.score_case(suite, case, &actual);
+        let passed = if case.expected.is_some() {
+            score >= 1.0
+        } else {
+            score > 0.0
+        };
+
+        EvalCaseResult {
+            case_id: case.case_id,
+            score,
+            passed,
+            actual,
+        }
+    }
+
+    fn build_report(&self, suite: &EvalSuite, case_results: Vec<EvalCaseResult>) -> EvalRunReport {
         let passed_cases = case_results.iter().filter(|c| c.passed).count();
         let total_cases = case_results.len();
         // An empty suite validated nothing, so it must not vacuously pass the
@@ -286,7 +409,7 @@ impl DeterministicEvalRunner {
         };
         let overall_pass = total_cases > 0 && pass_rate >= suite.thresholds.min_pass_rate;
 
-        Ok(EvalRunReport {
+        EvalRunReport {
             suite_digest: suite.suite_digest.clone(),
             seed: self.seed,
             total_cases,
@@ -294,7 +417,7 @@ impl DeterministicEvalRunner {
             pass_rate,
             overall_pass,
             case_results,
-        })
+        }
     }
 
     fn score_case(
@@ -332,6 +455,34 @@ impl DeterministicEvalRunner {
                     };
                     scores.push(s);
                 }
+                ScorerType::JsonSchema => {
+                    let valid = scorer
+                        .params
+                        .get("schema")
+                        .is_some_and(|schema| matches_schema(schema, actual));
+                    scores.push(if valid { 1.0 } else { 0.0 });
+                }
+                ScorerType::NumericTolerance => {
+                    let tolerance = |key: &str| {
+                        scorer
+                            .params
+                            .get(key)
+                            .and_then(serde_json::Value::as_f64)
+                            .unwrap_or(0.0)
+                    };
+                    let (abs, rel) = (tolerance("abs"), tolerance("rel"));
+                    let s = match &case.expected {
+                        Some(expected) => {
+                            if approx_equal(expected, actual, abs, rel) {
+                                1.0
+                            } else {
+                                0.0
+                            }
+                        }
+                        None => 1.0,
+                    };
+                    scores.push(s);
+                }
                 // Unimplemented scorers are skipped to avoid silently dragging
                 // down scores. Once real implementations land, add arms here.
                 ScorerType::SemanticSimilarity
@@ -358,6 +509,241 @@ impl DeterministicEvalRunner {
     }
 }
 
+/// Compare `expected` and `actual` structurally, letting numeric leaves
+/// differ within `abs` or `rel` tolerance; all other leaves must be equal.
+fn approx_equal(
+    expected: &serde_json::Value,
+    actual: &serde_json::Value,
+    abs: f64,
+    rel: f64,
+) -> bool {
+    use serde_json::Value;
+
+    match (expected, actual) {
+        (Value::Number(e), Value::Number(a)) => match (e.as_f64(), a.as_f64()) {
+            (Some(e), Some(a)) => {
+                let diff = (e - a).abs();
+                diff <= abs || diff <= rel * e.abs().max(a.abs())
+            }
+            _ => e == a,
+        },
+        (Value::Array(e), Value::Array(a)) => {
+            e.len() == a.len() && e.iter().zip(a).all(|(e, a)| approx_equal(e, a, abs, rel))
+        }
+        (Value::Object(e), Value::Object(a)) => {
+            e.len() == a.len()
+                && e.iter()
+                    .all(|(k, e)| a.get(k).is_some_and(|a| approx_equal(e, a, abs, rel)))
+        }
+        _ => expected == actual,
+    }
+}
+
+/// Schema keywords [`matches_schema`] enforces.
+const SUPPORTED_SCHEMA_KEYWORDS: &[&str] = &[
+    "type",
+    "enum",
+    "const",
+    "required",
+    "properties",
+    "additionalProperties",
+    "items",
+    "minimum",
+    "maximum",
+    "minLength",
+    "maxLength",
+];
+
+/// Keywords that only annotate a schema and never constrain a value.
+const ANNOTATION_SCHEMA_KEYWORDS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$comment",
+    "title",
+    "description",
+    "default",
+    "examples",
+];
+
+/// Reject schemas [`matches_schema`] can't fully enforce, naming the
+/// offending keyword by its JSON pointer below `path`.
+fn check_schema(schema: &serde_json::Value, path: &str) -> std::result::Result<(), String> {
+    use serde_json::Value;
+
+    let object = match schema {
+        Value::Bool(_) => return Ok(()),
+        Value::Object(object) => object,
+        _ => return Err(format!("{path} must be an object or a boolean")),
+    };
+    for (keyword, value) in object {
+        let at = format!("{path}/{keyword}");
+        if ANNOTATION_SCHEMA_KEYWORDS.contains(&keyword.as_str()) {
+            continue;
+        }
+        if !SUPPORTED_SCHEMA_KEYWORDS.contains(&keyword.as_str()) {
+            return Err(format!("unsupported schema keyword at {at}"));
+        }
+        match keyword.as_str() {
+            "type" => {
+                let known = |t: &Value| {
+                    matches!(
+                        t.as_str(),
+                        Some(
+                            "object"
+                                | "array"
+                                | "string"
+                                | "boolean"
+                                | "null"
+                                | "number"
+                                | "integer"
+                        )
+                    )
+                };
+                let ok = match value {
+                    Value::Array(types) => types.iter().all(known),
+                    t => known(t),
+                };
+                if !ok {
+                    return Err(format!("unknown type at {at}"));
+                }
+            }
+            "enum" | "required" if !value.is_array() => {
+                return Err(format!("{at} must be an array"));
+            }
+            "properties" => {
+                let properties = value
+                    .as_object()
+                    .ok_or_else(|| format!("{at} must be an object"))?;
+                for (name, field) in properties {
+                    check_schema(field, &format!("{at}/{name}"))?;
+                }
+            }
+            "additionalProperties" if !value.is_boolean() => {
+                return Err(format!("{at} must be a boolean"));
+            }
+            "items" => check_schema(value, &at)?,
+            "minimum" | "maximum" if !value.is_number() => {
+                return Err(format!("{at} must be a number"));
+            }
+            "minLength" | "maxLength" if !value.is_u64() => {
+                return Err(format!("{at} must be a non-negative integer"));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Check `value` against a JSON Schema.
+///
+/// Supports the keywords structured agent output typically relies on:
+/// `type`, `enum`, `const`, `required`, `properties`,
+/// `additionalProperties` (as a boolean), `items`, `minimum`/`maximum` and
+/// `minLength`/`maxLength`. [`check_schema`] rejects schemas using anything
+/// else before a suite is scored.
+fn matches_schema(schema: &serde_json::Value, value: &serde_json::Value) -> bool {
+    use serde_json::Value;
+
+    let Some(schema) = schema.as_object() else {
+        // `true`/`false` are valid schemas accepting everything/nothing
+        return schema.as_bool().unwrap_or(true);
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let type_matches = |t: &Value| match t.as_str() {
+            Some("object") => value.is_object(),
+            Some("array") => value.is_array(),
+            Some("string") => value.is_string(),
+            Some("boolean") => value.is_boolean(),
+            Some("null") => value.is_null(),
+            Some("number") => value.is_number(),
+            Some("integer") => {
+                value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|f| f.fract() == 0.0)
+            }
+            _ => false,
+        };
+        let ok = match expected {
+            Value::Array(types) => types.iter().any(type_matches),
+            t => type_matches(t),
+        };
+        if !ok {
+            return false;
+        }
+    }
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        if !options.contains(value) {
+            return false;
+        }
+    }
+    if let Some(constant) = schema.get("const") {
+        if constant != value {
+            return false;
+        }
+    }
+
+    if let Some(n) = value.as_f64() {
+        if schema
+            .get("minimum")
+            .and_then(Value::as_f64)
+            .is_some_and(|min| n < min)
+            || schema
+                .get("maximum")
+                .and_then(Value::as_f64)
+                .is_some_and(|max| n > max)
+        {
+            return false;
+        }
+    }
+    if let Some(text) = value.as_str() {
+        let len = text.chars().count() as u64;
+        if schema
+            .get("minLength")
+            .and_then(Value::as_u64)
+            .is_some_and(|min| len < min)
+            || schema
+                .get("maxLength")
+                .and_then(Value::as_u64)
+                .is_some_and(|max| len > max)
+        {
+            return false;
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        let properties = schema.get("properties").and_then(Value::as_object);
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            if !required
+                .iter()
+                .filter_map(Value::as_str)
+                .all(|key| object.contains_key(key))
+            {
+                return false;
+            }
+        }
+        for (key, field) in object {
+            match properties.and_then(|p| p.get(key)) {
+                Some(field_schema) => {
+                    if !matches_schema(field_schema, field) {
+                        return false;
+                    }
+                }
+                None => {
+                    if schema.get("additionalProperties") == Some(&Value::Bool(false)) {
+                        return false;
+                    }
+                }
+            }
+        }
+    }
+    if let (Some(items), Some(elements)) = (schema.get("items"), value.as_array()) {
+        if !elements.iter().all(|e| matches_schema(items, e)) {
+            return false;
+        }
+    }
+
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -698,4 +1084,251 @@ mod tests {
         });
         assert_eq!(actual, expected);
     }
+
+    fn json_schema_suite(case: &EvalTestCase) -> EvalSuite {
+        EvalSuite::new("structured".to_string(), "1.0.0".to_string())
+            .add_test_case(case.clone())
+            .add_scorer(ScorerConfig {
+                name: "schema".to_string(),
+                scorer_type: ScorerType::JsonSchema,
+                params: serde_json::json!({
+                    "schema": {
+                        "type": "object",
+                        "required": ["answer", "confidence"],
+                        "properties": {
+                            "answer": { "type": "string", "minLength": 1 },
+                            "confidence": { "type": "number", "minimum": 0, "maximum": 1 },
+                            "sources": { "type": "array", "items": { "type": "string" } }
+                        }
+                    }
+                }),
+            })
+            .finalize()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_json_schema_scorer_accepts_valid_output() {
+        let case = EvalTestCase::new(serde_json::json!({"q": "capital of France"}), None);
+        let suite = json_schema_suite(&case);
+        let outputs = HashMap::from([(
+            case.case_id,
+            serde_json::json!({"answer": "Paris", "confidence": 0.9, "sources": ["atlas"]}),
+        )]);
+
+        let report = DeterministicEvalRunner::new(1)
+            .run_with_outputs(&suite, &outputs)
+            .unwrap();
+        assert_eq!(report.case_results[0].score, 1.0);
+        assert!(report.case_results[0].passed);
+    }
+
+    #[test]
+    fn test_json_schema_scorer_rejects_missing_required_field() {
+        let case = EvalTestCase::new(serde_json::json!({"q": "capital of France"}), None);
+        let suite = json_schema_suite(&case);
+        let outputs = HashMap::from([(case.case_id, serde_json::json!({"answer": "Paris"}))]);
+
+        let report = DeterministicEvalRunner::new(1)
+            .run_with_outputs(&suite, &outputs)
+            .unwrap();
+        assert_eq!(report.case_results[0].score, 0.0);
+        assert!(!report.case_results[0].passed);
+    }
+
+    #[test]
+    fn test_matches_schema_checks_nested_types_and_enums() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "additionalProperties": false,
+            "properties": {
+                "status": { "enum": ["ok", "error"] },
+                "count": { "type": "integer" }
+            }
+        });
+        assert!(matches_schema(
+            &schema,
+            &serde_json::json!({"status": "ok", "count": 3})
+        ));
+        assert!(!matches_schema(
+            &schema,
+            &serde_json::json!({"status": "maybe"})
+        ));
+        assert!(!matches_schema(&schema, &serde_json::json!({"count": 1.5})));
+        assert!(!matches_schema(
+            &schema,
+            &serde_json::json!({"extra": true})
+        ));
+        assert!(!matches_schema(
+            &schema,
+            &serde_json::json!("not an object")
+        ));
+    }
+
+    fn schema_scorer(params: serde_json::Value) -> ScorerConfig {
+        ScorerConfig {
+            name: "schema".to_string(),
+            scorer_type: ScorerType::JsonSchema,
+            params,
+        }
+    }
+
+    #[test]
+    fn test_json_schema_scorer_rejects_unsupported_keywords_and_missing_schema() {
+        let suite = |params| {
+            EvalSuite::new("structured".to_string(), "1.0.0".to_string())
+                .add_scorer(schema_scorer(params))
+                .finalize()
+        };
+
+        for params in [
+            serde_json::json!({"schema": {"anyOf": [{"type": "string"}]}}),
+            serde_json::json!({"schema": {"properties": {"id": {"pattern": "^a"}}}}),
+            serde_json::json!({"schema": {"items": {"$ref": "#/defs/item"}}}),
+            serde_json::json!({"schema": {"additionalProperties": {"type": "string"}}}),
+            serde_json::json!({"schema": {"type": "text"}}),
+            serde_json::json!({"type": "object", "required": ["answer"]}),
+        ] {
+            let err = suite(params.clone()).unwrap_err();
+            assert!(
+                matches!(err, AivcsError::InvalidScorer { .. }),
+                "{params}: {err}"
+            );
+        }
+
+        let ok = suite(serde_json::json!({"schema": {
+            "title": "answer",
+            "type": ["object", "null"],
+            "properties": {"answer": {"type": "string", "description": "final answer"}}
+        }}));
+        assert!(ok.is_ok());
+    }
+
+    #[test]
+    fn test_run_rejects_unchecked_schema_scorer() {
+        // A suite deserialized from JSON may carry a digest without having
+        // gone through `finalize`
+        let mut suite = EvalSuite::new("structured".to_string(), "1.0.0".to_string()).add_scorer(
+            schema_scorer(serde_json::json!({"schema": {"oneOf": [{"type": "string"}]}})),
+        );
+        suite.suite_digest = "d".repeat(64);
+
+        let err = DeterministicEvalRunner::new(1)
+            .run_with_outputs(&suite, &HashMap::new())
+            .unwrap_err();
+        assert!(err.to_string().contains("oneOf"), "{err}");
+    }
+
+    fn numeric_score(expected: serde_json::Value, actual: serde_json::Value) -> f32 {
+        let case = EvalTestCase::new(serde_json::json!({"q": "measure"}), Some(expected));
+        let suite = EvalSuite::new("numeric".to_string(), "1.0.0".to_string())
+            .add_test_case(case.clone())
+            .add_scorer(ScorerConfig {
+                name: "tolerance".to_string(),
+                scorer_type: ScorerType::NumericTolerance,
+                params: serde_json::json!({"abs": 1e-3, "rel": 0.0}),
+            })
+            .finalize()
+            .unwrap();
+        let outputs = HashMap::from([(case.case_id, actual)]);
+        DeterministicEvalRunner::new(1)
+            .run_with_outputs(&suite, &outputs)
+            .unwrap()
+            .case_results[0]
+            .score
+    }
+
+    #[test]
+    fn test_numeric_tolerance_scorer_accepts_close_numbers() {
+        assert_eq!(
+            numeric_score(serde_json::json!(4.0), serde_json::json!(4.00001)),
+            1.0
+        );
+        assert_eq!(
+            numeric_score(
+                serde_json::json!({"unit": "m", "values": [1.5, {"depth": 2}]}),
+                serde_json::json!({"unit": "m", "values": [1.5004, {"depth": 2.0001}]}),
+            ),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_numeric_tolerance_scorer_rejects_distant_numbers() {
+        assert_eq!(
+            numeric_score(serde_json::json!(4.0), serde_json::json!(4.1)),
+            0.0
+        );
+        assert_eq!(
+            numeric_score(
+                serde_json::json!({"unit": "m", "values": [1.5, {"depth": 2}]}),
+                serde_json::json!({"unit": "m", "values": [1.5, {"depth": 2.5}]}),
+            ),
+            0.0
+        );
+        // Non-numeric leaves still need an exact match
+        assert_eq!(
+            numeric_score(
+                serde_json::json!({"unit": "m", "value": 1.0}),
+                serde_json::json!({"unit": "cm", "value": 1.0}),
+            ),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_approx_equal_relative_tolerance() {
+        let (e, a) = (serde_json::json!(1000.0), serde_json::json!(1004.0));
+        assert!(approx_equal(&e, &a, 0.0, 0.01));
+        assert!(!approx_equal(&e, &a, 0.0, 0.001));
+    }
+
+    #[test]
+    fn test_parallel_run_matches_sequential_byte_for_byte() {
+        let mut suite = EvalSuite::new("parallel".to_string(), "1.0.0".to_string());
+        let mut outputs = HashMap::new();
+        for i in 0..40 {
+            let case = EvalTestCase::new(serde_json::json!({ "i": i }), Some(serde_json::json!(i)));
+            // Every third case answers wrong
+            let answer = if i % 3 == 0 { i + 1 } else { i };
+            outputs.insert(case.case_id, serde_json::json!(answer));
+            suite = suite.add_test_case(case);
+        }
+        let suite = suite
+            .add_scorer(ScorerConfig {
+                name: "exact".to_string(),
+                scorer_type: ScorerType::ExactMatch,
+                params: serde_json::json!({}),
+            })
+            .finalize()
+            .unwrap();
+        let runner = DeterministicEvalRunner::new(9);
+
+        let sequential =
+            serde_json::to_vec(&runner.run_with_outputs(&suite, &outputs).unwrap()).unwrap();
+        for workers in [1, 4, 64] {
+            let parallel = runner
+                .run_with_outputs_parallel(&suite, &outputs, workers)
+                .unwrap();
+            assert_eq!(serde_json::to_vec(&parallel).unwrap(), sequential);
+        }
+
+        let fail_fast = suite
+            .clone()
+            .with_thresholds(EvalThresholds {
+                fail_fast: true,
+                ..EvalThresholds::default()
+            })
+            .finalize()
+            .unwrap();
+        let sequential = runner.run_with_outputs(&fail_fast, &outputs).unwrap();
+        let parallel = runner
+            .run_with_outputs_parallel(&fail_fast, &outputs, 8)
+            .unwrap();
+        assert_eq!(sequential.total_cases, 1);
+        assert_eq!(
+            serde_json::to_vec(&parallel).unwrap(),
+            serde_json::to_vec(&sequential).unwrap()
+        );
+    }
 }
@@ -13,6 +13,33 @@ pub enum ReleaseEnvironment {
     Production,
 }
 
+impl ReleaseEnvironment {
+    /// Name as serialized, e.g. `PRODUCTION`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReleaseEnvironment::Dev => "DEV",
+            ReleaseEnvironment::Staging => "STAGING",
+            ReleaseEnvironment::Production => "PRODUCTION",
+        }
+    }
+}
+
+impl std::str::FromStr for ReleaseEnvironment {
+    type Err = String;
+
+    /// Parse an environment name, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "DEV" => Ok(ReleaseEnvironment::Dev),
+            "STAGING" => Ok(ReleaseEnvironment::Staging),
+            "PRODUCTION" => Ok(ReleaseEnvironment::Production),
+            _ => Err(format!(
+                "unknown environment '{s}' (expected dev, staging or production)"
+            )),
+        }
+    }
+}
+
 /// A release of an agent into a specific environment.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Release {
@@ -179,6 +206,23 @@ mod tests {
         assert_eq!(pointer, deserialized);
     }
 
+    #[test]
+    fn test_release_environment_parses_its_name() {
+        for env in [
+            ReleaseEnvironment::Dev,
+            ReleaseEnvironment::Staging,
+            ReleaseEnvironment::Production,
+        ] {
+            assert_eq!(env.as_str().parse::<ReleaseEnvironment>(), Ok(env));
+            assert_eq!(
+                serde_json::to_string(&env).unwrap(),
+                format!("\"{}\"", env.as_str())
+            );
+        }
+        assert_eq!("staging".parse(), Ok(ReleaseEnvironment::Staging));
+        assert!("prod".parse::<ReleaseEnvironment>().is_err());
+    }
+
     #[test]
     fn test_release_environment_all_variants() {
         let dev_json = serde_json::to_string(&ReleaseEnvironment::Dev).expect("serialize");
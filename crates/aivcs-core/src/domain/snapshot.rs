@@ -23,6 +23,19 @@ pub struct SnapshotMeta {
 
     /// When the snapshot was created.
     pub created_at: DateTime<Utc>,
+
+    /// Logic hash folded into the commit id, if one was computed.
+    #[serde(default)]
+    pub logic_hash: Option<String>,
+
+    /// Environment (Nix flake) hash folded into the commit id, if one was
+    /// computed.
+    #[serde(default)]
+    pub env_hash: Option<String>,
+
+    /// Toolchain in use, e.g. the `rustc --version` line.
+    #[serde(default)]
+    pub toolchain: Option<String>,
 }
 
 impl SnapshotMeta {
@@ -40,8 +53,24 @@ impl SnapshotMeta {
             author,
             branch,
             created_at: Utc::now(),
+            logic_hash: None,
+            env_hash: None,
+            toolchain: None,
         }
     }
+
+    /// Record the logic and environment hashes of the commit id.
+    pub fn with_hashes(mut self, logic_hash: Option<String>, env_hash: Option<String>) -> Self {
+        self.logic_hash = logic_hash;
+        self.env_hash = env_hash;
+        self
+    }
+
+    /// Record the toolchain in use.
+    pub fn with_toolchain(mut self, toolchain: Option<String>) -> Self {
+        self.toolchain = toolchain;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -74,4 +103,13 @@ mod tests {
         );
         assert_eq!(meta.git_sha, sha);
     }
+
+    #[test]
+    fn snapshot_meta_without_environment_fields_still_parses() {
+        let json = r#"{"cas_digest":"d","git_sha":"s","message":"m","author":"a","branch":"main","created_at":"2024-05-01T00:00:00Z"}"#;
+        let meta: SnapshotMeta = serde_json::from_str(json).unwrap();
+        assert_eq!(meta.logic_hash, None);
+        assert_eq!(meta.env_hash, None);
+        assert_eq!(meta.toolchain, None);
+    }
 }
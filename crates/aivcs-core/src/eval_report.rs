@@ -0,0 +1,127 @@
+//! Persisted eval run reports and regression checks against a baseline
+//!
+//! A report is stored as a JSON blob in CAS; the `eval_reports` index maps
+//! `<suite_digest>:<seed>` to the latest report that did not regress, so the
+//! known-good report for a suite can be fetched without remembering its
+//! digest.
+
+use std::collections::HashMap;
+
+use oxidized_state::SurrealHandle;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::cas::{CasStore, Digest};
+use crate::domain::{AivcsError, EvalRunReport, Result};
+
+/// Index key of `report`: its suite digest and seed
+pub fn eval_report_key(suite_digest: &str, seed: u64) -> String {
+    format!("{suite_digest}:{seed}")
+}
+
+/// Store `report` in CAS and index it under its suite digest and seed,
+/// replacing the earlier report for the same pair unless `report` regresses
+/// against it; a regressed run stays in CAS, reachable by its digest, and
+/// does not become the next baseline
+pub async fn persist_eval_report(
+    handle: &SurrealHandle,
+    cas: &dyn CasStore,
+    report: &EvalRunReport,
+) -> Result<Digest> {
+    let blob = serde_json::to_vec(report)?;
+    let digest = cas.put(&blob).map_err(cas_err)?;
+    let key = eval_report_key(&report.suite_digest, report.seed);
+    if let Some(baseline) = load_eval_report(handle, cas, &report.suite_digest, report.seed).await?
+    {
+        let regression = compare_eval_reports(&baseline, report);
+        if regression.has_regression() {
+            warn!(
+                "eval report {} ({}) regresses {} case(s); keeping the indexed baseline",
+                key,
+                digest,
+                regression.newly_failing.len()
+            );
+            return Ok(digest);
+        }
+    }
+    handle
+        .save_eval_report_digest(&key, &digest.to_hex())
+        .await
+        .map_err(storage_err)?;
+    info!("stored eval report {} ({})", key, digest);
+    Ok(digest)
+}
+
+/// Report persisted for `suite_digest` and `seed`, or `None` if there is none
+pub async fn load_eval_report(
+    handle: &SurrealHandle,
+    cas: &dyn CasStore,
+    suite_digest: &str,
+    seed: u64,
+) -> Result<Option<EvalRunReport>> {
+    let Some(hex) = handle
+        .get_eval_report_digest(&eval_report_key(suite_digest, seed))
+        .await
+        .map_err(storage_err)?
+    else {
+        return Ok(None);
+    };
+    let digest: Digest = hex.parse().map_err(cas_err)?;
+    load_eval_report_by_digest(cas, &digest).map(Some)
+}
+
+/// Report stored in CAS under `digest`, as returned by [`persist_eval_report`]
+pub fn load_eval_report_by_digest(cas: &dyn CasStore, digest: &Digest) -> Result<EvalRunReport> {
+    let blob = cas.get(digest).map_err(cas_err)?;
+    Ok(serde_json::from_slice(&blob)?)
+}
+
+/// How a run's case outcomes moved relative to a baseline run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvalRegression {
+    /// Cases that failed in the baseline and pass now
+    pub newly_passing: Vec<Uuid>,
+    /// Cases that passed in the baseline and fail now, or were not run
+    pub newly_failing: Vec<Uuid>,
+}
+
+impl EvalRegression {
+    /// Whether any baseline-passing case no longer passes
+    pub fn has_regression(&self) -> bool {
+        !self.newly_failing.is_empty()
+    }
+}
+
+/// Compare `current` against `baseline` case by case
+///
+/// Cases are matched by id. A case that passed in the baseline but is
+/// missing from `current` (for example after a `fail_fast` stop) counts as
+/// newly failing; cases new to `current` are ignored. Both lists follow the
+/// baseline's case order.
+pub fn compare_eval_reports(baseline: &EvalRunReport, current: &EvalRunReport) -> EvalRegression {
+    let now: HashMap<Uuid, bool> = current
+        .case_results
+        .iter()
+        .map(|c| (c.case_id, c.passed))
+        .collect();
+
+    let mut regression = EvalRegression::default();
+    for case in &baseline.case_results {
+        let passes_now = now.get(&case.case_id).copied().unwrap_or(false);
+        if case.passed && !passes_now {
+            regression.newly_failing.push(case.case_id);
+        } else if !case.passed && passes_now {
+            regression.newly_passing.push(case.case_id);
+        }
+    }
+    regression
+}
+
+fn storage_err(e: oxidized_state::StateError) -> AivcsError {
+    AivcsError::StorageError(e.to_string())
+}
+
+fn cas_err(e: crate::cas::CasError) -> AivcsError {
+    AivcsError::StorageError(format!("eval report: {e}"))
+}
@@ -19,15 +19,20 @@
 //!     metadata,
 //! ).await;
 //! ```
+//!
+//! [`replay_to_bus`] goes the other way: it re-publishes a recorded run's
+//! events onto a [`RunEventBus`] so late subscribers can catch up on history.
 
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use async_trait::async_trait;
 use serde_json::json;
 use tokio::sync::RwLock;
-use tracing::{instrument, warn};
+use tracing::{debug, instrument, warn};
 
+use crate::cost::event_usage;
+use crate::domain::{AivcsError, Result};
 use crate::metrics::METRICS;
 
 use oxidizedgraph::events::{
@@ -36,8 +41,9 @@ use oxidizedgraph::events::{
 };
 
 use oxidized_state::storage_traits::{
-    ContentDigest, RunEvent, RunId, RunLedger, RunMetadata, RunSummary,
+    ContentDigest, RunEvent, RunId, RunLedger, RunMetadata, RunSummary, RunUsage, StorageResult,
 };
+use oxidized_state::StorageError;
 
 /// Implements oxidizedgraph's `EventHandler` to persist graph lifecycle
 /// events into an AIVCS `RunLedger`.
@@ -46,6 +52,11 @@ use oxidized_state::storage_traits::{
 /// - Creates a run on `on_start()`
 /// - Maps each `Event` to a `RunEvent` and appends it on `handle()`
 /// - Completes or fails the run on `on_stop()` based on whether errors occurred
+///
+/// Events are appended through [`append_event_idempotent`], so an append the
+/// ledger already holds for `(run_id, seq)` is dropped instead of failing.
+/// Token usage reported by appended events (see [`crate::cost`]) is summed
+/// into the run summary, as [`crate::recording::GraphRunRecorder`] does.
 pub struct LedgerHandler<L: RunLedger> {
     ledger: Arc<L>,
     run_id: RwLock<Option<RunId>>,
@@ -54,6 +65,7 @@ pub struct LedgerHandler<L: RunLedger> {
     metadata: RunMetadata,
     saw_error: AtomicBool,
     start_time: RwLock<Option<std::time::Instant>>,
+    usage: Mutex<RunUsage>,
 }
 
 impl<L: RunLedger> LedgerHandler<L> {
@@ -67,6 +79,7 @@ impl<L: RunLedger> LedgerHandler<L> {
             metadata,
             saw_error: AtomicBool::new(false),
             start_time: RwLock::new(None),
+            usage: Mutex::new(RunUsage::default()),
         }
     }
 
@@ -84,10 +97,27 @@ impl<L: RunLedger> LedgerHandler<L> {
     pub fn seq(&self) -> u64 {
         self.seq.load(Ordering::SeqCst)
     }
+}
 
-    /// Bump the sequence counter and return the new value.
-    fn next_seq(&self) -> u64 {
-        self.seq.fetch_add(1, Ordering::SeqCst)
+/// Append `event`, treating a redelivery of an already-stored `(run_id, seq)`
+/// as a successful no-op.
+///
+/// Returns `true` if the event was written and `false` if it was a duplicate.
+/// Buses with at-least-once delivery can hand the same event over twice; the
+/// ledger's unique `(run_id, seq)` constraint makes the second write a no-op.
+pub async fn append_event_idempotent<L: RunLedger + ?Sized>(
+    ledger: &L,
+    run_id: &RunId,
+    event: RunEvent,
+) -> StorageResult<bool> {
+    match ledger.append_event(run_id, event).await {
+        Ok(()) => Ok(true),
+        Err(StorageError::DuplicateEvent { run_id, seq }) => {
+            METRICS.inc_events_deduplicated();
+            debug!(run_id = %run_id, seq, "duplicate event ignored");
+            Ok(false)
+        }
+        Err(e) => Err(e),
     }
 }
 
@@ -214,6 +244,7 @@ impl<L: RunLedger + 'static> EventHandler for LedgerHandler<L> {
         METRICS.inc_events_processed();
 
         let (kind, payload) = map_event(event);
+        let seq = self.seq.fetch_add(1, Ordering::SeqCst);
 
         // Track errors
         if matches!(
@@ -223,15 +254,27 @@ impl<L: RunLedger + 'static> EventHandler for LedgerHandler<L> {
             self.saw_error.store(true, Ordering::SeqCst);
         }
 
+        let usage = event_usage(&payload);
         let run_event = RunEvent {
-            seq: self.next_seq(),
+            seq,
             kind,
             payload,
             timestamp: event.timestamp,
         };
 
-        if let Err(e) = self.ledger.append_event(&run_id, run_event).await {
-            warn!(error = %e, run_id = %run_id, "LedgerHandler: failed to append event");
+        match append_event_idempotent(self.ledger.as_ref(), &run_id, run_event).await {
+            Ok(true) => {
+                if let Some((model, tokens)) = usage {
+                    self.usage
+                        .lock()
+                        .expect("usage poisoned")
+                        .add(&model, &tokens);
+                }
+            }
+            Ok(false) => {}
+            Err(e) => {
+                warn!(error = %e, run_id = %run_id, "LedgerHandler: failed to append event");
+            }
         }
     }
 
@@ -253,12 +296,14 @@ impl<L: RunLedger + 'static> EventHandler for LedgerHandler<L> {
             .map(|t| t.elapsed().as_millis() as u64)
             .unwrap_or(0);
         let success = !self.saw_error.load(Ordering::SeqCst);
+        let usage = self.usage.lock().expect("usage poisoned").clone();
 
         let summary = RunSummary {
             total_events,
             final_state_digest: None,
             duration_ms,
             success,
+            usage: Some(usage).filter(|u| !u.is_empty()),
         };
 
         let result = if success {
@@ -290,6 +335,46 @@ pub fn subscribe_ledger_to_bus<L: RunLedger + 'static>(
     handler
 }
 
+/// A destination for recorded [`RunEvent`]s re-driven by [`replay_to_bus`].
+#[async_trait]
+pub trait RunEventBus: Send + Sync {
+    /// Publish a single event belonging to `run_id`.
+    async fn publish(&self, run_id: &RunId, event: RunEvent) -> Result<()>;
+}
+
+#[async_trait]
+impl RunEventBus for tokio::sync::broadcast::Sender<(RunId, RunEvent)> {
+    async fn publish(&self, run_id: &RunId, event: RunEvent) -> Result<()> {
+        // No live receivers is not an error: there is simply nobody to catch up.
+        let _ = self.send((run_id.clone(), event));
+        Ok(())
+    }
+}
+
+/// Re-publish every event of a recorded run onto `bus`, in `seq` order.
+///
+/// Events are forwarded unchanged, so `seq` and `timestamp` match the ledger.
+/// Returns the number of events published.
+#[instrument(skip(ledger, bus))]
+pub async fn replay_to_bus(
+    ledger: &dyn RunLedger,
+    run_id: &str,
+    bus: &dyn RunEventBus,
+) -> Result<usize> {
+    let run_id = RunId(run_id.to_string());
+    let mut events = ledger
+        .get_events(&run_id)
+        .await
+        .map_err(|e| AivcsError::StorageError(e.to_string()))?;
+    events.sort_by_key(|e| e.seq);
+
+    let count = events.len();
+    for event in events {
+        bus.publish(&run_id, event).await?;
+    }
+    Ok(count)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -403,4 +488,159 @@ mod tests {
         assert_eq!(kind, "Custom:MyCustom");
         assert_eq!(payload, json!({"foo": "bar"}));
     }
+
+    #[derive(Default)]
+    struct RecordingBus {
+        seen: std::sync::Mutex<Vec<(RunId, RunEvent)>>,
+    }
+
+    #[async_trait]
+    impl RunEventBus for RecordingBus {
+        async fn publish(&self, run_id: &RunId, event: RunEvent) -> Result<()> {
+            self.seen.lock().unwrap().push((run_id.clone(), event));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn replay_to_bus_reemits_events_in_seq_order() {
+        let ledger = Arc::new(MemoryRunLedger::new());
+        let handler = LedgerHandler::new(ledger.clone(), test_digest(), test_metadata());
+
+        handler.on_start().await;
+        let run_id = handler.run_id().await.unwrap();
+        handler
+            .handle(&Event::graph_started("t", Some("g".into()), "entry".into()))
+            .await;
+        handler
+            .handle(&Event::node_entered("t", "n".into(), 1))
+            .await;
+        handler
+            .handle(&Event::graph_completed("t", 1, Duration::from_millis(5)))
+            .await;
+        handler.on_stop().await;
+
+        let recorded = ledger.get_events(&run_id).await.unwrap();
+        let bus = RecordingBus::default();
+        let count = replay_to_bus(ledger.as_ref(), &run_id.0, &bus)
+            .await
+            .unwrap();
+
+        let seen = bus.seen.into_inner().unwrap();
+        assert_eq!(count, 3);
+        assert!(seen.iter().all(|(id, _)| *id == run_id));
+        let seqs: Vec<u64> = seen.iter().map(|(_, e)| e.seq).collect();
+        assert_eq!(seqs, vec![1, 2, 3]);
+        let kinds: Vec<&str> = seen.iter().map(|(_, e)| e.kind.as_str()).collect();
+        assert_eq!(
+            kinds,
+            vec!["graph_started", "node_entered", "graph_completed"]
+        );
+        for ((_, replayed), original) in seen.iter().zip(&recorded) {
+            assert_eq!(replayed.timestamp, original.timestamp);
+        }
+    }
+
+    #[tokio::test]
+    async fn redelivered_event_is_deduplicated() {
+        let ledger = MemoryRunLedger::new();
+        let run_id = ledger
+            .create_run(&test_digest(), test_metadata())
+            .await
+            .unwrap();
+        let event = RunEvent {
+            seq: 1,
+            kind: "node_entered".into(),
+            payload: json!({ "node_id": "n" }),
+            timestamp: chrono::Utc::now(),
+        };
+
+        let before = METRICS.events_deduplicated();
+        assert!(append_event_idempotent(&ledger, &run_id, event.clone())
+            .await
+            .unwrap());
+        assert!(!append_event_idempotent(&ledger, &run_id, event)
+            .await
+            .unwrap());
+
+        assert_eq!(ledger.get_events(&run_id).await.unwrap().len(), 1);
+        assert!(METRICS.events_deduplicated() > before);
+    }
+
+    #[tokio::test]
+    async fn handler_treats_already_stored_seq_as_success() {
+        let ledger = Arc::new(MemoryRunLedger::new());
+        let handler = LedgerHandler::new(ledger.clone(), test_digest(), test_metadata());
+
+        handler.on_start().await;
+        let run_id = handler.run_id().await.unwrap();
+        // Seq 1 already written, as by an earlier delivery
+        ledger
+            .append_event(
+                &run_id,
+                RunEvent {
+                    seq: 1,
+                    kind: "node_entered".into(),
+                    payload: json!({ "node_id": "n", "iteration": 1 }),
+                    timestamp: chrono::Utc::now(),
+                },
+            )
+            .await
+            .unwrap();
+
+        let before = METRICS.events_deduplicated();
+        handler
+            .handle(&Event::node_entered("t", "n".into(), 1))
+            .await;
+        handler
+            .handle(&Event::node_entered("t", "m".into(), 2))
+            .await;
+        handler.on_stop().await;
+
+        let seqs: Vec<u64> = ledger
+            .get_events(&run_id)
+            .await
+            .unwrap()
+            .iter()
+            .map(|e| e.seq)
+            .collect();
+        assert_eq!(seqs, vec![1, 2]);
+        assert!(METRICS.events_deduplicated() > before);
+        let record = ledger.get_run(&run_id).await.unwrap();
+        assert_eq!(record.status, RunStatus::Completed);
+    }
+
+    #[tokio::test]
+    async fn handler_sums_reported_usage_into_summary() {
+        let ledger = Arc::new(MemoryRunLedger::new());
+        let handler = LedgerHandler::new(ledger.clone(), test_digest(), test_metadata());
+
+        handler.on_start().await;
+        let run_id = handler.run_id().await.unwrap();
+        let llm_call = |input: u64| {
+            Event::new(
+                "t",
+                EventKind::Custom {
+                    name: "llm".into(),
+                    payload: json!({"usage": {"model": "m", "input_tokens": input}}),
+                },
+            )
+        };
+        handler.handle(&llm_call(100)).await;
+        handler.handle(&llm_call(20)).await;
+        handler.on_stop().await;
+
+        let record = ledger.get_run(&run_id).await.unwrap();
+        let usage = record.summary.unwrap().usage.expect("usage in summary");
+        assert_eq!(usage.models["m"].input_tokens, 120);
+        assert_eq!(usage.models["m"].calls, 2);
+    }
+
+    #[tokio::test]
+    async fn replay_to_bus_unknown_run_is_storage_error() {
+        let ledger = MemoryRunLedger::new();
+        let bus = RecordingBus::default();
+        let err = replay_to_bus(&ledger, "missing", &bus).await.unwrap_err();
+        assert!(matches!(err, AivcsError::StorageError(_)));
+    }
 }
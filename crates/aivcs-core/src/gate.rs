@@ -8,6 +8,8 @@
 use serde::{Deserialize, Serialize};
 
 use crate::domain::eval::EvalThresholds;
+use crate::memory::DecisionRecorder;
+use crate::Result;
 
 // ---------------------------------------------------------------------------
 // Eval result types (input to the gate)
@@ -156,6 +158,24 @@ pub fn evaluate_gate(rule_set: &GateRuleSet, report: &EvalReport) -> GateVerdict
     }
 }
 
+/// [`evaluate_gate`], recording the verdict on `subject` at `commit_id`
+/// through `recorder` when one is given.
+pub async fn evaluate_gate_recorded(
+    rule_set: &GateRuleSet,
+    report: &EvalReport,
+    recorder: Option<&DecisionRecorder>,
+    commit_id: &str,
+    subject: &str,
+) -> Result<GateVerdict> {
+    let verdict = evaluate_gate(rule_set, report);
+    if let Some(recorder) = recorder {
+        recorder
+            .record_gate_verdict(commit_id, subject, &verdict)
+            .await?;
+    }
+    Ok(verdict)
+}
+
 fn check_rule(
     rule: &GateRule,
     thresholds: &EvalThresholds,
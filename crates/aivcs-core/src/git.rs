@@ -131,6 +131,51 @@ pub fn is_valid_github_name(value: &str) -> bool {
         .all(|b| b.is_ascii_alphanumeric() || matches!(*b, b'.' | b'_' | b'-'))
 }
 
+/// Author recorded when no identity can be resolved.
+pub const DEFAULT_AUTHOR: &str = "agent";
+
+/// Resolve the author to record on commits made from `repo_dir`.
+///
+/// Precedence: `explicit` (e.g. `--author`), then `AIVCS_AUTHOR`, then the
+/// git identity (`user.name <user.email>`), falling back to
+/// [`DEFAULT_AUTHOR`].
+pub fn resolve_author(explicit: Option<&str>, repo_dir: &Path) -> String {
+    let env = std::env::var("AIVCS_AUTHOR").ok();
+    resolve_author_from(explicit, env.as_deref(), repo_dir)
+}
+
+fn resolve_author_from(explicit: Option<&str>, env: Option<&str>, repo_dir: &Path) -> String {
+    let non_empty = |value: &str| {
+        let value = value.trim();
+        (!value.is_empty()).then(|| value.to_string())
+    };
+    explicit
+        .and_then(non_empty)
+        .or_else(|| env.and_then(non_empty))
+        .or_else(|| git_identity(repo_dir))
+        .unwrap_or_else(|| DEFAULT_AUTHOR.to_string())
+}
+
+/// `user.name <user.email>` from git config, or whichever half is set.
+fn git_identity(repo_dir: &Path) -> Option<String> {
+    let config = |key: &str| {
+        let output = Command::new("git")
+            .args(["config", "--get", key])
+            .current_dir(repo_dir)
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (!value.is_empty()).then_some(value)
+    };
+    match (config("user.name"), config("user.email")) {
+        (Some(name), Some(email)) => Some(format!("{name} <{email}>")),
+        (name, email) => name.or(email),
+    }
+}
+
 /// Detect the current local git branch name.
 ///
 /// Runs `git rev-parse --abbrev-ref HEAD` in the given directory.
@@ -411,4 +456,31 @@ mod tests {
         assert!(!branch.is_empty());
         assert!(branch == "master" || branch == "main");
     }
+
+    #[test]
+    fn resolve_author_prefers_explicit_then_env_then_git() {
+        let repo = make_git_repo();
+        let git_author = "test-user <test@example.com>";
+
+        assert_eq!(
+            resolve_author_from(Some("alice"), Some("env-bot"), repo.path()),
+            "alice"
+        );
+        assert_eq!(
+            resolve_author_from(None, Some("env-bot"), repo.path()),
+            "env-bot"
+        );
+        assert_eq!(resolve_author_from(None, None, repo.path()), git_author);
+        // Blank values are treated as unset
+        assert_eq!(
+            resolve_author_from(Some("  "), Some(""), repo.path()),
+            git_author
+        );
+    }
+
+    #[test]
+    fn resolve_author_falls_back_to_default() {
+        let missing = std::path::PathBuf::from("/nonexistent/aivcs-author-test");
+        assert_eq!(resolve_author_from(None, None, &missing), DEFAULT_AUTHOR);
+    }
 }
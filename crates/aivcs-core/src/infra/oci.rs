@@ -160,12 +160,12 @@ pub fn resolve_push_tags(
 
     let mut tags = Vec::new();
     if let Ok(sha) = std::env::var("CI_COMMIT_SHA") {
-        if sha.len() >= 7 {
-            tags.push(format!("sha-{}", &sha[..7]));
+        if let Some(short) = sha.get(..7) {
+            tags.push(format!("sha-{short}"));
         }
     } else if let Ok(sha) = std::env::var("GITHUB_SHA") {
-        if sha.len() >= 7 {
-            tags.push(format!("sha-{}", &sha[..7]));
+        if let Some(short) = sha.get(..7) {
+            tags.push(format!("sha-{short}"));
         }
     }
 
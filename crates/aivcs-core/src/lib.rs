@@ -3,13 +3,19 @@
 //! Re-exports core components for programmatic access to AIVCS functionality.
 
 pub mod a2a;
+pub mod archive;
+pub mod bundle;
 pub mod cas;
 pub mod ci_snapshot;
+pub mod commands;
 pub mod compat;
+pub mod config;
+pub mod cost;
 pub mod deploy;
 pub mod deploy_runner;
 pub mod diff;
 pub mod domain;
+pub mod eval_report;
 pub mod event_adapter;
 pub mod forge;
 pub mod gate;
@@ -27,15 +33,20 @@ pub mod obs;
 pub mod orchestration;
 pub mod parallel;
 pub mod planning_autonomy;
+pub mod progress;
 pub mod publish_gate;
 pub mod quality_guardrails;
 pub mod recording;
 pub mod release_registry;
+pub mod remote;
 pub mod replay;
 pub mod reporting;
 pub mod role_orchestration;
 pub mod sandbox;
 pub mod self_healing;
+pub mod signing;
+pub mod snapshot_retention;
+pub mod stash;
 pub mod telemetry;
 pub mod tooling;
 pub mod trace_artifact;
@@ -47,7 +58,7 @@ pub use domain::{
     SnapshotMeta, ValidationError,
 };
 
-pub use event_adapter::{subscribe_ledger_to_bus, LedgerHandler};
+pub use event_adapter::{replay_to_bus, subscribe_ledger_to_bus, LedgerHandler, RunEventBus};
 
 pub use a2a::{
     emit_code_committed_best_effort, maybe_emit_code_committed_from_env, A2aRetryPolicy,
@@ -57,7 +68,7 @@ pub use a2a::{
 pub use forge::ForgeClient;
 pub use git::{
     capture_head_sha, detect_current_branch, detect_github_repository, is_git_repo, is_owner_repo,
-    is_valid_github_name, parse_github_remote,
+    is_valid_github_name, parse_github_remote, resolve_author, DEFAULT_AUTHOR,
 };
 pub use git_host::{detect_forge_repository, parse_git_remote, GitHost};
 pub use infra::cloudflare_lb::{
@@ -66,43 +77,59 @@ pub use infra::cloudflare_lb::{
 };
 pub use infra::flux::{build_reconcile_command, run_reconcile};
 
-pub use memory::{DecisionRecorder, DecisionRecorderConfig};
+pub use memory::{
+    DecisionCaptureSource, DecisionRecorder, DecisionRecorderConfig, GATE_TASK_PREFIX,
+    REPLAN_TASK_PREFIX,
+};
 
-pub use ci_snapshot::{build_ci_snapshot, compute_workspace_hash, find_repo_root, run_local_ci};
+pub use ci_snapshot::{
+    build_ci_snapshot, compute_workspace_hash, find_repo_root, run_local_ci, WorkspaceHasher,
+};
 
 pub use oxidized_state::{
-    BranchRecord, CommitId, CommitRecord, DecisionRecord, MemoryProvenanceRecord, MemoryRecord,
-    ProvenanceSourceType, SnapshotRecord, SurrealHandle,
+    short_hash, BranchProtection, BranchRecord, CommitId, CommitRecord, DecisionRecord,
+    MemoryProvenanceRecord, MemoryRecord, ProvenanceSourceType, SnapshotRecord, StashRecord,
+    SurrealHandle,
 };
 
 pub use nix_env_manager::{
     generate_environment_hash, generate_logic_hash, is_attic_available, is_nix_available,
-    AtticClient, AtticConfig, FlakeMetadata, HashSource, NixHash,
+    AtticClient, AtticConfig, EnvironmentCache, FlakeMetadata, HashSource, NixHash,
 };
 
 pub use semantic_rag_merge::{
-    diff_memory_vectors, resolve_conflict_state, semantic_merge, synthesize_memory,
-    AutoResolvedValue, MemoryConflict, MergeResult, VectorStoreDelta,
+    diff_memory_vectors, plan_semantic_merge, resolve_conflict_state, resolve_merge_conflict,
+    semantic_merge, semantic_merge_with_progress, synthesize_memory, unresolved_conflicts,
+    AutoResolvedValue, ConflictChoice, ConflictResolution, MemoryConflict, MergePlan, MergeResult,
+    MergeRun, VectorStoreDelta,
 };
 
-pub use cas::fs::FsCasStore;
+pub use bundle::{
+    export_bundle, export_bundle_since, export_bundle_with_progress, import_bundle, BundleSummary,
+    BUNDLE_FORMAT_VERSION,
+};
+pub use cas::fs::{CasStats, FsCasStore, DEFAULT_SHARD_DEPTH, MAX_SHARD_DEPTH};
+pub use cas::memory::MemoryCasStore;
 pub use cas::{CasError, CasStore, Digest};
 pub use compat::{
-    evaluate_compat, CompatRule, CompatRuleSet, CompatVerdict, CompatViolation, PromoteContext,
+    evaluate_compat, CompatRule, CompatRuleSet, CompatVerdict, CompatViolation, CompatWarning,
+    PromoteContext,
 };
 pub use deploy::{deploy_by_digest, DeployResult};
 pub use deploy_runner::{DeployByDigestRunner, DeployRunOutput};
 pub use diff::lcs_diff::{
-    diff_tool_calls as diff_tool_calls_lcs, DiffSummary, ParamChange,
-    ToolCallChange as LcsToolCallChange, ToolCallEntry,
+    diff_tool_calls as diff_tool_calls_lcs, diff_tool_calls_with as diff_tool_calls_lcs_with,
+    AlignMode, DiffOptions, DiffSummary, ParamChange, ToolCallChange as LcsToolCallChange,
+    ToolCallEntry,
 };
 pub use diff::semantic_graph::{
     diff_graph_snapshots, extract_graph_snapshot, format_semantic_diff_markdown, GraphSnapshot,
     SemanticGraphDiff,
 };
+pub use diff::spec_behavior::{compare_specs_behavior, BehaviorDiff};
 pub use diff::state_diff::{
-    diff_run_states, diff_scoped_state, extract_last_checkpoint, ScopedStateDiff, StateDelta,
-    CHECKPOINT_SAVED_KIND,
+    diff_run_states, diff_scoped_state, diff_state_subtree, extract_last_checkpoint,
+    ScopedStateDiff, StateDelta, CHECKPOINT_SAVED_KIND,
 };
 pub use orchestration::{
     default_role_templates, deterministic_role_order, merge_role_outputs, validate_handoff,
@@ -110,28 +137,55 @@ pub use orchestration::{
     MergeConflictStrategy, MergeOutcome, ParallelPlanError, RoleHandoff, RoleOutput, RoleTemplate,
 };
 pub use parallel::{
-    fork_agent_parallel, BranchStatus, ForkResult, ParallelConfig, ParallelManager,
+    fork_agent_parallel, fork_agent_parallel_with_progress, BranchStatus, ForkResult,
+    ParallelConfig, ParallelManager,
 };
 pub use planning_autonomy::{
-    compute_progress, decompose_goal_to_dag, evaluate_replan, schedule_next_ready_tasks, EpicPlan,
-    ExecutionDag, GoalPlan, PlanTask, PlanTaskStatus, PlanningError, ProgressReport,
-    ReplanDecision, ReplanPolicy, ReplanReason, SchedulerConstraints, TaskPlan,
+    build_dag_from_plan, compute_progress, decompose_goal_to_dag, evaluate_replan,
+    evaluate_replan_recorded, evaluate_replan_with_controls, schedule_next_ready_tasks,
+    ControlledReplanDecision, EpicPlan, ExecutionDag, GoalDecomposer, GoalPlan,
+    HeuristicDecomposer, LlmDecomposer, MockDecomposer, PlanTask, PlanTaskStatus, PlannerModel,
+    PlanningError, ProgressReport, RecoveryControls, ReplanControlState, ReplanDecision,
+    ReplanPolicy, ReplanReason, ReplanSuppressionReason, SchedulerConstraints, TaskPlan,
 };
+pub use progress::{NoProgress, Progress, TermProgress};
 
+pub use archive::{archive_runs, load_archived_run, ArchiveReport, ArchivedRun, RunArchive};
+pub use cost::{
+    cost_report, price_usage, run_cost, usage_from_events, CostReport, CostSummary, ModelCost,
+    ModelPrice, PricingTable, RunCost,
+};
 pub use diff::node_paths::{
     diff_node_paths, extract_node_path, NodeDivergence, NodePathDiff, NodeStep,
 };
 pub use diff::tool_calls::{diff_tool_calls, ParamDelta, ToolCall, ToolCallChange, ToolCallDiff};
+pub use eval_report::{
+    compare_eval_reports, eval_report_key, load_eval_report, load_eval_report_by_digest,
+    persist_eval_report, EvalRegression,
+};
 pub use gate::{
-    evaluate_gate, CaseResult, EvalReport, GateRule, GateRuleSet, GateVerdict, Violation,
+    evaluate_gate, evaluate_gate_recorded, CaseResult, EvalReport, GateRule, GateRuleSet,
+    GateVerdict, Violation,
 };
-pub use recording::GraphRunRecorder;
+pub use recording::{record_run, GraphRunRecorder, NodeGuard};
 pub use release_registry::ReleaseRegistryApi;
-pub use replay::{find_resume_point, replay_run, verify_spec_digest, ReplaySummary, ResumePoint};
+pub use remote::{pull_branch, push_branch, SyncSummary};
+pub use replay::{
+    find_resume_point, replay_run, replay_run_with_archive, verify_spec_digest, ReplaySummary,
+    ResumePoint,
+};
 pub use reporting::{
-    render_diff_summary_md, write_diff_summary_md, write_eval_results_json, DiffSummaryArtifact,
-    EvalCaseResultArtifact, EvalResultsArtifact, EvalSummaryArtifact,
+    render_commit_graph_ascii, render_diff_summary_md, write_diff_summary_md,
+    write_eval_results_json, CommitGraphNode, DiffSummaryArtifact, EvalCaseResultArtifact,
+    EvalResultsArtifact, EvalSummaryArtifact, TermStyle,
+};
+pub use signing::{
+    commit_signing_bytes, verify_commit, CommitSigner, SignatureStatus, TrustedKeys,
+};
+pub use snapshot_retention::{
+    apply_snapshot_retention, SnapshotRetentionPolicy, SnapshotRetentionReport,
 };
+pub use stash::{stash_pop, stash_save};
 
 pub use trace_artifact::{
     read_trace_artifact, write_trace_artifact, RetentionPolicy, RunTraceArtifact,
@@ -162,10 +216,15 @@ pub use sandbox::{
     SandboxError, SandboxResult, ToolExecutionResult, ToolPolicyRule, ToolPolicySet, ToolRequest,
 };
 
+#[cfg(feature = "openai")]
+pub use memory::OpenAiEmbedder;
 pub use memory::{
-    assemble_context, compact_index, CompactionPolicy, CompactionResult, ContextBudget,
-    ContextItem, ContextWindow, DecisionRationale, IndexQuery, IndexResult, MemoryEntry,
-    MemoryEntryKind, MemoryError, MemoryIndex, MemoryResult, RationaleEntry, RationaleOutcome,
+    assemble_context, blame_memory, cluster_memories, compact_commit_memories, compact_index,
+    cosine_similarity, embed_commit_memories, find_duplicate_memories, memory_provenance,
+    save_memory_from_run, BlameEntry, CommitCompaction, CompactionPolicy, CompactionResult,
+    ContextBudget, ContextItem, ContextWindow, DecisionRationale, Embedder, HashEmbedder,
+    IndexQuery, IndexResult, MemoryEntry, MemoryEntryKind, MemoryError, MemoryIndex, MemoryOrigin,
+    MemoryResult, MemorySource, RationaleEntry, RationaleOutcome, SummaryArbiter,
 };
 
 pub use memory_context::{
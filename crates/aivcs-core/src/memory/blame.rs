@@ -0,0 +1,81 @@
+//! Blame: attribute a memory key to the commit that last changed it.
+
+use chrono::{DateTime, Utc};
+use oxidized_state::{CommitRecord, SurrealHandle};
+use serde::Serialize;
+
+use crate::{AivcsError, Result};
+
+/// The commit that last changed a memory key.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlameEntry {
+    /// Hash of the commit that introduced the current content
+    pub commit_id: String,
+    /// Author of that commit
+    pub author: String,
+    /// When that commit was created
+    pub created_at: DateTime<Utc>,
+    /// The memory content as of the blamed commit
+    pub content: String,
+}
+
+/// Find the most recent ancestor of `commit` (inclusive) whose memory
+/// content for `key` differs from its parent's.
+///
+/// Follows first parents only, like `aivcs log`. Returns `None` when `key`
+/// is absent at `commit`.
+pub async fn blame_memory(
+    handle: &SurrealHandle,
+    commit: &str,
+    key: &str,
+) -> Result<Option<BlameEntry>> {
+    let Some(mut current) = load_commit(handle, commit).await? else {
+        return Err(AivcsError::StorageError(format!(
+            "commit not found: {commit}"
+        )));
+    };
+    let Some(content) = memory_content(handle, &current.commit_id.hash, key).await? else {
+        return Ok(None);
+    };
+
+    while let Some(parent_id) = current.parent_ids.first() {
+        if memory_content(handle, parent_id, key).await?.as_deref() != Some(content.as_str()) {
+            break;
+        }
+        match load_commit(handle, parent_id).await? {
+            Some(parent) => current = parent,
+            None => break,
+        }
+    }
+
+    Ok(Some(BlameEntry {
+        commit_id: current.commit_id.hash,
+        author: current.author,
+        created_at: current.created_at,
+        content,
+    }))
+}
+
+async fn load_commit(handle: &SurrealHandle, commit: &str) -> Result<Option<CommitRecord>> {
+    handle
+        .get_commit(commit)
+        .await
+        .map_err(|e| AivcsError::StorageError(format!("Failed to load commit: {}", e)))
+}
+
+/// Content of `key` at `commit_id`; the newest record wins if the key repeats.
+async fn memory_content(
+    handle: &SurrealHandle,
+    commit_id: &str,
+    key: &str,
+) -> Result<Option<String>> {
+    let memories = handle
+        .get_memories(commit_id)
+        .await
+        .map_err(|e| AivcsError::StorageError(format!("Failed to load memories: {}", e)))?;
+    Ok(memories
+        .into_iter()
+        .rev()
+        .find(|m| m.key == key)
+        .map(|m| m.content))
+}
@@ -0,0 +1,101 @@
+//! Grouping of related memories ahead of summarization or compaction.
+
+use oxidized_state::MemoryRecord;
+
+use super::dedup::cosine_similarity;
+
+/// Group `memories` by single-linkage clustering over embedding cosine
+/// similarity.
+///
+/// Two memories share a cluster when a chain of pairs, each with
+/// similarity of at least `threshold`, connects them. Memories without an
+/// embedding, or whose embedding can't be compared, form singleton
+/// clusters. Clusters are ordered by their first member, and members keep
+/// their input order.
+pub fn cluster_memories(memories: Vec<MemoryRecord>, threshold: f32) -> Vec<Vec<MemoryRecord>> {
+    let mut parent: Vec<usize> = (0..memories.len()).collect();
+
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    for i in 0..memories.len() {
+        let Some(emb_a) = memories[i].embedding.as_deref() else {
+            continue;
+        };
+        for j in i + 1..memories.len() {
+            let Some(emb_b) = memories[j].embedding.as_deref() else {
+                continue;
+            };
+            if cosine_similarity(emb_a, emb_b).is_some_and(|s| s >= threshold) {
+                let (ra, rb) = (root(&mut parent, i), root(&mut parent, j));
+                // Keep the earlier member as root so cluster order is stable
+                parent[ra.max(rb)] = ra.min(rb);
+            }
+        }
+    }
+
+    let mut clusters: Vec<Vec<MemoryRecord>> = Vec::new();
+    let mut slot_of_root = vec![usize::MAX; memories.len()];
+    for (i, memory) in memories.into_iter().enumerate() {
+        let r = root(&mut parent, i);
+        if slot_of_root[r] == usize::MAX {
+            slot_of_root[r] = clusters.len();
+            clusters.push(Vec::new());
+        }
+        clusters[slot_of_root[r]].push(memory);
+    }
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory(key: &str, embedding: Option<Vec<f32>>) -> MemoryRecord {
+        let record = MemoryRecord::new("c1", key, key);
+        match embedding {
+            Some(e) => record.with_embedding(e),
+            None => record,
+        }
+    }
+
+    fn keys(clusters: &[Vec<MemoryRecord>]) -> Vec<Vec<&str>> {
+        clusters
+            .iter()
+            .map(|c| c.iter().map(|m| m.key.as_str()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn similar_memories_share_a_cluster() {
+        let clusters = cluster_memories(
+            vec![
+                memory("a", Some(vec![1.0, 0.0, 0.0])),
+                memory("b", Some(vec![0.0, 0.0, 1.0])),
+                memory("c", Some(vec![0.98, 0.2, 0.0])),
+            ],
+            0.9,
+        );
+        assert_eq!(keys(&clusters), vec![vec!["a", "c"], vec!["b"]]);
+    }
+
+    #[test]
+    fn linkage_is_transitive_and_unembedded_memories_stay_alone() {
+        let clusters = cluster_memories(
+            vec![
+                memory("a", Some(vec![1.0, 0.0])),
+                memory("bare", None),
+                memory("b", Some(vec![0.8, 0.6])),
+                memory("c", Some(vec![0.28, 0.96])),
+            ],
+            0.75,
+        );
+        // a~b and b~c (0.8) pass the threshold; a~c (0.28) does not
+        assert_eq!(keys(&clusters), vec![vec!["a", "b", "c"], vec!["bare"]]);
+    }
+}
@@ -2,13 +2,21 @@
 //!
 //! Captures agent decisions with rationale and outcomes for learning and analysis.
 
-use oxidized_state::{DecisionRecord, MemoryProvenanceRecord, SurrealHandle};
+use oxidized_state::{DecisionRecord, MemoryProvenanceRecord, MemoryRecord, SurrealHandle};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::gate::GateVerdict;
+use crate::planning_autonomy::{ControlledReplanDecision, ReplanSuppressionReason};
 use crate::{AivcsError, Result};
 
+/// Decision task prefix for replan verdicts, followed by the plan ID.
+pub const REPLAN_TASK_PREFIX: &str = "replan";
+
+/// Decision task prefix for gate verdicts, followed by the gated subject.
+pub const GATE_TASK_PREFIX: &str = "gate";
+
 /// Configuration for decision recording
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DecisionRecorderConfig {
@@ -91,39 +99,150 @@ impl DecisionRecorder {
         confidence: f32,
         source: DecisionCaptureSource,
     ) -> Result<String> {
-        if !self.config.enabled {
-            return Ok("decision_recording_disabled".to_string());
-        }
-        if !self.should_capture(source) {
-            return Ok("decision_capture_disabled_for_source".to_string());
-        }
-
-        // Validate confidence is in range [0.0, 1.0]
-        if !(0.0..=1.0).contains(&confidence) {
-            return Err(AivcsError::StorageError(
-                "confidence must be between 0.0 and 1.0".to_string(),
-            ));
-        }
-        self.validate_payload_size(&commit_id, &task, &action, &rationale)?;
-
-        let decision_id = Uuid::new_v4().to_string();
-
         let decision = DecisionRecord::new(
-            decision_id.clone(),
+            Uuid::new_v4().to_string(),
             commit_id,
             task,
             action,
             rationale,
             confidence,
         );
+        self.record(decision, source).await
+    }
 
-        // Insert into database using SurrealHandle
-        self.handle
-            .save_decision(&decision)
+    /// Record a decision built elsewhere, such as a merge's conflict
+    /// resolutions, under the same policy as the other `record_*` methods.
+    pub async fn record(
+        &self,
+        decision: DecisionRecord,
+        source: DecisionCaptureSource,
+    ) -> Result<String> {
+        self.save_decision(decision, source).await
+    }
+
+    /// Record a replan verdict for `plan_id` under the task `replan:<plan_id>`.
+    ///
+    /// The action is `replan`, `suppressed` or `continue`; the rationale
+    /// lists the policy's reasons and, when suppressed, why. The full
+    /// decision is kept as the record's inputs.
+    pub async fn record_replan(
+        &self,
+        commit_id: &str,
+        plan_id: &str,
+        decision: &ControlledReplanDecision,
+    ) -> Result<String> {
+        let action = if decision.should_replan {
+            "replan"
+        } else if decision.suppression.is_some() {
+            "suppressed"
+        } else {
+            "continue"
+        };
+
+        let mut rationale: Vec<String> = decision
+            .policy_decision
+            .reasons
+            .iter()
+            .map(|r| serde_json::to_value(r).map_or_else(|_| format!("{r:?}"), |v| v.to_string()))
+            .collect();
+        if rationale.is_empty() {
+            rationale.push("no replan trigger fired".to_string());
+        }
+        match &decision.suppression {
+            Some(ReplanSuppressionReason::WithinMinInterval {
+                elapsed_minutes,
+                min_interval_minutes,
+            }) => rationale.push(format!(
+                "suppressed: {elapsed_minutes} min since last replan, minimum {min_interval_minutes}"
+            )),
+            Some(ReplanSuppressionReason::ConfidenceDeltaTooSmall {
+                observed_delta,
+                min_delta,
+            }) => rationale.push(format!(
+                "suppressed: confidence moved {observed_delta:.2}, minimum {min_delta:.2}"
+            )),
+            None => {}
+        }
+
+        let record = DecisionRecord::new(
+            Uuid::new_v4().to_string(),
+            commit_id.to_string(),
+            format!("{REPLAN_TASK_PREFIX}:{plan_id}"),
+            action.to_string(),
+            rationale.join("; "),
+            1.0,
+        )
+        .with_alternatives(
+            ["replan", "continue"]
+                .into_iter()
+                .filter(|a| *a != action)
+                .map(String::from)
+                .collect(),
+        )
+        .with_inputs(to_inputs(decision)?);
+        self.save_decision(record, DecisionCaptureSource::Event)
             .await
-            .map_err(|e| AivcsError::StorageError(format!("Failed to record decision: {}", e)))?;
+    }
+
+    /// Record a gate verdict on `subject` under the task `gate:<subject>`.
+    ///
+    /// The action is `pass` or `fail`; the rationale lists the violations.
+    pub async fn record_gate_verdict(
+        &self,
+        commit_id: &str,
+        subject: &str,
+        verdict: &GateVerdict,
+    ) -> Result<String> {
+        let violations: Vec<&str> = verdict
+            .violations
+            .iter()
+            .map(|v| v.reason.as_str())
+            .collect();
+        self.record_gate_outcome(
+            commit_id,
+            subject,
+            verdict.passed(),
+            &violations,
+            to_inputs(verdict)?,
+        )
+        .await
+    }
+
+    /// Record the outcome of a gate that is not a [`GateVerdict`], such as
+    /// the CI gate, the same way as [`Self::record_gate_verdict`].
+    pub async fn record_gate_outcome(
+        &self,
+        commit_id: &str,
+        subject: &str,
+        passed: bool,
+        violations: &[&str],
+        inputs: serde_json::Value,
+    ) -> Result<String> {
+        let (action, alternative) = if passed {
+            ("pass", "fail")
+        } else {
+            ("fail", "pass")
+        };
+        let rationale = if passed {
+            "all gate rules satisfied".to_string()
+        } else if violations.is_empty() {
+            "gate failed without reporting a violation".to_string()
+        } else {
+            violations.join("; ")
+        };
 
-        Ok(decision_id)
+        let record = DecisionRecord::new(
+            Uuid::new_v4().to_string(),
+            commit_id.to_string(),
+            format!("{GATE_TASK_PREFIX}:{subject}"),
+            action.to_string(),
+            rationale,
+            1.0,
+        )
+        .with_alternatives(vec![alternative.to_string()])
+        .with_inputs(inputs);
+        self.save_decision(record, DecisionCaptureSource::Event)
+            .await
     }
 
     /// Record decision outcome
@@ -145,21 +264,19 @@ impl DecisionRecorder {
         Ok(())
     }
 
-    /// Record memory provenance for a memory
-    pub async fn record_provenance(&self, provenance: MemoryProvenanceRecord) -> Result<String> {
-        if !self.config.enabled {
-            return Ok("provenance_recording_disabled".to_string());
-        }
-
-        let memory_id = provenance.memory_id.clone();
-
-        // Insert into database using SurrealHandle
+    /// Save `memories` with the provenance rows that describe them, in one
+    /// transaction so a failure cannot leave a memory without its
+    /// provenance. With recording disabled only the memories are saved.
+    pub async fn record_provenance(
+        &self,
+        memories: &[MemoryRecord],
+        provenance: &[MemoryProvenanceRecord],
+    ) -> Result<()> {
+        let provenance = if self.config.enabled { provenance } else { &[] };
         self.handle
-            .save_provenance(&provenance)
+            .save_memories_with_provenance(memories, provenance)
             .await
-            .map_err(|e| AivcsError::StorageError(format!("Failed to record provenance: {}", e)))?;
-
-        Ok(memory_id)
+            .map_err(|e| AivcsError::StorageError(format!("Failed to record provenance: {}", e)))
     }
 
     /// Get decision history for a task
@@ -203,6 +320,41 @@ impl DecisionRecorder {
         Ok(())
     }
 
+    /// Validate `decision` against the config and persist it.
+    async fn save_decision(
+        &self,
+        decision: DecisionRecord,
+        source: DecisionCaptureSource,
+    ) -> Result<String> {
+        if !self.config.enabled {
+            return Ok("decision_recording_disabled".to_string());
+        }
+        if !self.should_capture(source) {
+            return Ok("decision_capture_disabled_for_source".to_string());
+        }
+
+        // Validate confidence is in range [0.0, 1.0]
+        if !(0.0..=1.0).contains(&decision.confidence) {
+            return Err(AivcsError::StorageError(
+                "confidence must be between 0.0 and 1.0".to_string(),
+            ));
+        }
+        self.validate_payload_size(
+            &decision.commit_id,
+            &decision.task,
+            &decision.action,
+            &decision.rationale,
+        )?;
+
+        // Insert into database using SurrealHandle
+        self.handle
+            .save_decision(&decision)
+            .await
+            .map_err(|e| AivcsError::StorageError(format!("Failed to record decision: {}", e)))?;
+
+        Ok(decision.decision_id)
+    }
+
     fn should_capture(&self, source: DecisionCaptureSource) -> bool {
         should_capture_source(&self.config, source)
     }
@@ -233,6 +385,10 @@ fn should_capture_source(config: &DecisionRecorderConfig, source: DecisionCaptur
     }
 }
 
+fn to_inputs(value: &impl Serialize) -> Result<serde_json::Value> {
+    serde_json::to_value(value).map_err(AivcsError::Serialization)
+}
+
 fn decision_payload_size(commit_id: &str, task: &str, action: &str, rationale: &str) -> usize {
     commit_id.len() + task.len() + action.len() + rationale.len()
 }
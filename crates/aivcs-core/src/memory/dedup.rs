@@ -0,0 +1,79 @@
+//! Near-duplicate detection over a commit's embedded memories.
+
+use oxidized_state::SurrealHandle;
+
+use crate::{AivcsError, Result};
+
+/// Cosine similarity of two vectors.
+///
+/// Returns `None` when the lengths differ or either vector has zero norm.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> Option<f32> {
+    if a.len() != b.len() || a.is_empty() {
+        return None;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return None;
+    }
+    Some(dot / (norm_a * norm_b))
+}
+
+/// Find pairs of memories in `commit_id` whose embeddings have a cosine
+/// similarity of at least `threshold`.
+///
+/// Returns `(older_key, newer_key, similarity)` tuples, ordered by the
+/// memories' creation time. Memories without an embedding, or with an
+/// embedding of a different dimension, are never paired.
+pub async fn find_duplicate_memories(
+    handle: &SurrealHandle,
+    commit_id: &str,
+    threshold: f32,
+) -> Result<Vec<(String, String, f32)>> {
+    let memories = handle
+        .get_memories(commit_id)
+        .await
+        .map_err(|e| AivcsError::StorageError(format!("Failed to load memories: {}", e)))?;
+
+    let embedded: Vec<(&str, &[f32])> = memories
+        .iter()
+        .filter_map(|m| m.embedding.as_deref().map(|e| (m.key.as_str(), e)))
+        .collect();
+
+    let mut pairs = Vec::new();
+    for (i, (key_a, emb_a)) in embedded.iter().enumerate() {
+        for (key_b, emb_b) in &embedded[i + 1..] {
+            if let Some(similarity) = cosine_similarity(emb_a, emb_b) {
+                if similarity >= threshold {
+                    pairs.push((key_a.to_string(), key_b.to_string(), similarity));
+                }
+            }
+        }
+    }
+    Ok(pairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cosine_of_parallel_vectors_is_one() {
+        let sim = cosine_similarity(&[1.0, 2.0, 3.0], &[2.0, 4.0, 6.0]).unwrap();
+        assert!((sim - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn cosine_of_orthogonal_vectors_is_zero() {
+        let sim = cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]).unwrap();
+        assert!(sim.abs() < 1e-6);
+    }
+
+    #[test]
+    fn cosine_rejects_mismatched_or_zero_vectors() {
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 0.0]), None);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), None);
+        assert_eq!(cosine_similarity(&[], &[]), None);
+    }
+}
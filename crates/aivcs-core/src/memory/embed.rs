@@ -0,0 +1,242 @@
+//! Embedding providers and backfill of missing memory embeddings.
+//!
+//! Memories are saved without embeddings; [`embed_commit_memories`] fills
+//! them in through whichever [`Embedder`] is configured. [`HashEmbedder`]
+//! needs no network and is deterministic, which makes it the choice for
+//! tests and offline use. `OpenAiEmbedder` is available with the `openai`
+//! feature.
+
+use async_trait::async_trait;
+use oxidized_state::SurrealHandle;
+use sha2::{Digest, Sha256};
+use tracing::instrument;
+
+use crate::{AivcsError, Result};
+
+/// Turns memory content into an embedding vector
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>>;
+}
+
+/// Deterministic, offline embedder built from SHA-256 of word tokens
+///
+/// Each lowercase whitespace-separated token adds a hashed ±1 to one
+/// dimension; the result is L2-normalized. Texts sharing words get
+/// positive cosine similarity, and the same text always embeds the same.
+#[derive(Debug, Clone, Copy)]
+pub struct HashEmbedder {
+    dims: usize,
+}
+
+impl HashEmbedder {
+    pub const DEFAULT_DIMS: usize = 256;
+
+    pub fn new(dims: usize) -> Self {
+        assert!(dims > 0, "embedding dimension must be positive");
+        Self { dims }
+    }
+
+    pub fn dims(&self) -> usize {
+        self.dims
+    }
+}
+
+impl Default for HashEmbedder {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_DIMS)
+    }
+}
+
+#[async_trait]
+impl Embedder for HashEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let mut vector = vec![0.0f32; self.dims];
+        for token in text.split_whitespace() {
+            let hash = Sha256::digest(token.to_lowercase().as_bytes());
+            let bucket = u64::from_le_bytes(hash[..8].try_into().expect("8 bytes"));
+            let sign = if hash[8] & 1 == 0 { 1.0 } else { -1.0 };
+            vector[(bucket % self.dims as u64) as usize] += sign;
+        }
+        let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|v| *v /= norm);
+        }
+        Ok(vector)
+    }
+}
+
+#[cfg(feature = "openai")]
+pub use openai::OpenAiEmbedder;
+
+#[cfg(feature = "openai")]
+mod openai {
+    use async_trait::async_trait;
+    use serde::Deserialize;
+
+    use super::Embedder;
+    use crate::{AivcsError, Result};
+
+    const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+    const DEFAULT_MODEL: &str = "text-embedding-3-small";
+
+    /// Embedder backed by the OpenAI embeddings API
+    #[derive(Debug, Clone)]
+    pub struct OpenAiEmbedder {
+        client: reqwest::Client,
+        api_key: String,
+        model: String,
+        base_url: String,
+    }
+
+    #[derive(Deserialize)]
+    struct EmbeddingResponse {
+        data: Vec<EmbeddingData>,
+    }
+
+    #[derive(Deserialize)]
+    struct EmbeddingData {
+        embedding: Vec<f32>,
+    }
+
+    impl OpenAiEmbedder {
+        pub fn new(api_key: impl Into<String>) -> Self {
+            Self {
+                client: reqwest::Client::new(),
+                api_key: api_key.into(),
+                model: DEFAULT_MODEL.to_string(),
+                base_url: DEFAULT_BASE_URL.to_string(),
+            }
+        }
+
+        /// Read the key from `OPENAI_API_KEY`, and the endpoint from
+        /// `OPENAI_BASE_URL` if set
+        pub fn from_env() -> Result<Self> {
+            let api_key = std::env::var("OPENAI_API_KEY")
+                .map_err(|_| AivcsError::Memory("OPENAI_API_KEY is not set".to_string()))?;
+            let mut embedder = Self::new(api_key);
+            if let Ok(base_url) = std::env::var("OPENAI_BASE_URL") {
+                embedder.base_url = base_url;
+            }
+            Ok(embedder)
+        }
+
+        pub fn with_model(mut self, model: impl Into<String>) -> Self {
+            self.model = model.into();
+            self
+        }
+    }
+
+    #[async_trait]
+    impl Embedder for OpenAiEmbedder {
+        async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            let url = format!("{}/embeddings", self.base_url.trim_end_matches('/'));
+            let response = self
+                .client
+                .post(url)
+                .bearer_auth(&self.api_key)
+                .json(&serde_json::json!({ "model": self.model, "input": text }))
+                .send()
+                .await
+                .and_then(|r| r.error_for_status())
+                .map_err(|e| AivcsError::Memory(format!("embedding request failed: {}", e)))?;
+            let body: EmbeddingResponse = response
+                .json()
+                .await
+                .map_err(|e| AivcsError::Memory(format!("invalid embedding response: {}", e)))?;
+            body.data
+                .into_iter()
+                .next()
+                .map(|d| d.embedding)
+                .ok_or_else(|| AivcsError::Memory("embedding response was empty".to_string()))
+        }
+    }
+}
+
+/// Embed every memory of `commit_id` that has no embedding yet
+///
+/// Memories that already carry one are left alone, so running this twice
+/// embeds nothing the second time. Returns the number of memories filled.
+#[instrument(skip(handle, embedder))]
+pub async fn embed_commit_memories(
+    handle: &SurrealHandle,
+    embedder: &dyn Embedder,
+    commit_id: &str,
+) -> Result<usize> {
+    let memories = handle
+        .get_memories(commit_id)
+        .await
+        .map_err(|e| AivcsError::StorageError(format!("Failed to load memories: {}", e)))?;
+
+    let mut filled = 0;
+    for memory in memories.iter().filter(|m| m.embedding.is_none()) {
+        let embedding = embedder.embed(&memory.content).await?;
+        handle
+            .set_memory_embedding(commit_id, &memory.key, &embedding)
+            .await
+            .map_err(|e| AivcsError::StorageError(format!("Failed to save embedding: {}", e)))?;
+        filled += 1;
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::dedup::cosine_similarity;
+    use oxidized_state::MemoryRecord;
+
+    #[tokio::test]
+    async fn test_hash_embedder_is_stable_and_normalized() {
+        let embedder = HashEmbedder::new(64);
+        let a = embedder.embed("retry the flaky step").await.unwrap();
+        let b = embedder.embed("retry the flaky step").await.unwrap();
+        let c = embedder.embed("deploy to production").await.unwrap();
+
+        assert_eq!(a, b);
+        assert_eq!(a.len(), 64);
+        let norm: f32 = a.iter().map(|v| v * v).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-5);
+        assert!(cosine_similarity(&a, &b).unwrap() > cosine_similarity(&a, &c).unwrap());
+        assert!(embedder.embed("").await.unwrap().iter().all(|v| *v == 0.0));
+    }
+
+    #[tokio::test]
+    async fn test_embed_commit_memories_fills_only_missing_embeddings() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+        let embedder = HashEmbedder::default();
+        let preset = vec![1.0, 0.0];
+        for record in [
+            MemoryRecord::new("c1", "plan", "split the job into stages"),
+            MemoryRecord::new("c1", "note", "the cache is cold on monday"),
+            MemoryRecord::new("c1", "given", "already embedded").with_embedding(preset.clone()),
+        ] {
+            handle.save_memory(&record).await.unwrap();
+        }
+
+        let filled = embed_commit_memories(&handle, &embedder, "c1")
+            .await
+            .unwrap();
+        assert_eq!(filled, 2);
+
+        let memories = handle.get_memories("c1").await.unwrap();
+        for memory in &memories {
+            let embedding = memory.embedding.as_ref().expect("embedding populated");
+            if memory.key == "given" {
+                assert_eq!(embedding, &preset);
+            } else {
+                assert_eq!(embedding, &embedder.embed(&memory.content).await.unwrap());
+            }
+        }
+
+        let again = embed_commit_memories(&handle, &embedder, "c1")
+            .await
+            .unwrap();
+        assert_eq!(again, 0);
+        let embeddings = |ms: &[MemoryRecord]| -> Vec<Option<Vec<f32>>> {
+            ms.iter().map(|m| m.embedding.clone()).collect()
+        };
+        let rerun = handle.get_memories("c1").await.unwrap();
+        assert_eq!(embeddings(&rerun), embeddings(&memories));
+    }
+}
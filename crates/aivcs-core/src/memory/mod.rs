@@ -1,19 +1,41 @@
 //! Memory subsystem for agent context, indexing, and retention.
 //!
 //! Provides in-memory indexing of run traces, rationales, diffs, and snapshots
-//! with tag/kind/time filtering, token-budgeted context assembly, and
-//! configurable compaction policies.
+//! with tag/kind/time filtering, token-budgeted context assembly,
+//! configurable compaction policies including cluster summarization,
+//! pluggable embedding providers, embedding-based duplicate detection and
+//! clustering, per-key blame over commit history, and provenance linking
+//! memories to the run events that produced them.
 
+pub mod blame;
+pub mod cluster;
 pub mod context;
 pub mod decision;
+pub mod dedup;
+pub mod embed;
 pub mod error;
 pub mod index;
+pub mod provenance;
 pub mod rationale;
 pub mod retention;
+pub mod summarize;
 
+pub use blame::{blame_memory, BlameEntry};
+pub use cluster::cluster_memories;
 pub use context::{assemble_context, ContextBudget, ContextItem, ContextWindow};
-pub use decision::{DecisionRecorder, DecisionRecorderConfig};
+pub use decision::{
+    DecisionCaptureSource, DecisionRecorder, DecisionRecorderConfig, GATE_TASK_PREFIX,
+    REPLAN_TASK_PREFIX,
+};
+pub use dedup::{cosine_similarity, find_duplicate_memories};
+#[cfg(feature = "openai")]
+pub use embed::OpenAiEmbedder;
+pub use embed::{embed_commit_memories, Embedder, HashEmbedder};
 pub use error::{MemoryError, MemoryResult};
 pub use index::{IndexQuery, IndexResult, MemoryEntry, MemoryEntryKind, MemoryIndex};
+pub use provenance::{
+    memory_id, memory_provenance, save_memory_from_run, MemoryOrigin, MemorySource,
+};
 pub use rationale::{DecisionRationale, RationaleEntry, RationaleOutcome};
 pub use retention::{compact_index, CompactionPolicy, CompactionResult};
+pub use summarize::{compact_commit_memories, CommitCompaction, SummaryArbiter};
@@ -0,0 +1,112 @@
+//! Provenance: link a memory to the run event that produced it.
+//!
+//! A memory written during a run gets a `memory_provenances` row naming the
+//! run, the seq of the producing event, and how the memory was produced
+//! (tool output, LLM generation, merge). Memories are identified by
+//! [`memory_id`], since a key alone repeats across commits.
+
+use chrono::{DateTime, Utc};
+pub use oxidized_state::memory_id;
+use oxidized_state::{MemoryProvenanceRecord, MemoryRecord, ProvenanceSourceType, SurrealHandle};
+use serde::Serialize;
+
+use crate::memory::DecisionRecorder;
+use crate::recording::GraphRunRecorder;
+use crate::{AivcsError, Result};
+
+/// The run event a memory is being produced by.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemorySource {
+    /// Run that produced the memory
+    pub run_id: String,
+    /// Seq of the producing event within the run
+    pub seq: u64,
+    /// How the memory was produced
+    pub source_type: ProvenanceSourceType,
+}
+
+impl MemorySource {
+    /// Source at event `seq` of `run_id`.
+    pub fn new(run_id: impl Into<String>, seq: u64, source_type: ProvenanceSourceType) -> Self {
+        Self {
+            run_id: run_id.into(),
+            seq,
+            source_type,
+        }
+    }
+
+    /// Source at the latest event `recorder` has appended.
+    pub fn from_recorder(recorder: &GraphRunRecorder, source_type: ProvenanceSourceType) -> Self {
+        Self::new(
+            recorder.run_id().to_string(),
+            recorder.last_seq(),
+            source_type,
+        )
+    }
+}
+
+/// Save `record` and a provenance row tying it to `source`, together
+/// through `recorder`.
+pub async fn save_memory_from_run(
+    recorder: &DecisionRecorder,
+    record: &MemoryRecord,
+    source: &MemorySource,
+) -> Result<()> {
+    let provenance = MemoryProvenanceRecord::from_run_event(
+        memory_id(&record.commit_id, &record.key),
+        source.run_id.clone(),
+        source.seq,
+        source.source_type.clone(),
+    );
+    recorder
+        .record_provenance(std::slice::from_ref(record), &[provenance])
+        .await
+}
+
+/// A run event recorded as the source of a memory.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MemoryOrigin {
+    /// Run that produced the memory
+    pub run_id: String,
+    /// Seq of the producing event within the run
+    pub seq: u64,
+    /// How the memory was produced, e.g. `tool_output`
+    pub source_type: String,
+    /// When the provenance was recorded
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Runs that produced the memory `key` at `commit_id`, oldest first.
+///
+/// Invalidated provenance and rows not tied to a run event (snapshots,
+/// user annotations) are left out.
+pub async fn memory_provenance(
+    handle: &SurrealHandle,
+    commit_id: &str,
+    key: &str,
+) -> Result<Vec<MemoryOrigin>> {
+    let records = handle
+        .get_provenance(&memory_id(commit_id, key))
+        .await
+        .map_err(|e| AivcsError::StorageError(format!("Failed to load provenance: {}", e)))?;
+
+    let mut origins: Vec<MemoryOrigin> = records
+        .into_iter()
+        .filter(|p| p.invalidated_at.is_none())
+        .filter_map(|p| {
+            let data = &p.source_data;
+            // Run-trace rows predate `seq` and record an event index instead
+            let seq = data["seq"]
+                .as_u64()
+                .or_else(|| data["event_idx"].as_u64())?;
+            Some(MemoryOrigin {
+                run_id: data["run_id"].as_str()?.to_string(),
+                seq,
+                source_type: p.source_type,
+                recorded_at: p.created_at,
+            })
+        })
+        .collect();
+    origins.sort_by_key(|o| o.recorded_at);
+    Ok(origins)
+}
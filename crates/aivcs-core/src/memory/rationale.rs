@@ -13,6 +13,37 @@ pub enum RationaleOutcome {
     Skipped,
 }
 
+impl RationaleOutcome {
+    /// Lowercase name, as serialized and as classified by
+    /// [`oxidized_state::decision_outcome_status`]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RationaleOutcome::Success => "success",
+            RationaleOutcome::Failure => "failure",
+            RationaleOutcome::Partial => "partial",
+            RationaleOutcome::Skipped => "skipped",
+        }
+    }
+}
+
+impl std::str::FromStr for RationaleOutcome {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            RationaleOutcome::Success,
+            RationaleOutcome::Failure,
+            RationaleOutcome::Partial,
+            RationaleOutcome::Skipped,
+        ]
+        .into_iter()
+        .find(|outcome| outcome.as_str().eq_ignore_ascii_case(s.trim()))
+        .ok_or_else(|| {
+            format!("unknown outcome '{s}' (expected success, failure, partial or skipped)")
+        })
+    }
+}
+
 /// A captured decision rationale with reasoning and alternatives.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DecisionRationale {
@@ -107,6 +138,18 @@ impl RationaleEntry {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_outcome_parses_its_serialized_name_and_rejects_typos() {
+        for outcome in [RationaleOutcome::Failure, RationaleOutcome::Skipped] {
+            let serialized = serde_json::to_value(&outcome).unwrap();
+            assert_eq!(serialized, outcome.as_str());
+            assert_eq!(outcome.as_str().parse::<RationaleOutcome>(), Ok(outcome));
+        }
+        assert_eq!("Failure".parse(), Ok(RationaleOutcome::Failure));
+        let err = "fail".parse::<RationaleOutcome>().unwrap_err();
+        assert!(err.contains("failure"), "{err}");
+    }
+
     #[test]
     fn test_rationale_builder() {
         let r = DecisionRationale::new("do X", "because Y")
@@ -0,0 +1,136 @@
+//! Compaction that replaces clusters of related memories with summaries.
+//!
+//! [`compact_commit_memories`] groups a commit's memories with
+//! [`cluster_memories`], asks a [`SummaryArbiter`] for one summary per
+//! cluster, and records the result as a new child commit. The source
+//! commit is left untouched.
+
+use async_trait::async_trait;
+use oxidized_state::{CommitId, CommitRecord, MemoryRecord, SurrealHandle};
+use tracing::instrument;
+
+use super::cluster::cluster_memories;
+use crate::memory_context::{CompactionPolicy, CompactionStrategy};
+use crate::{AivcsError, Result};
+
+/// Writes the content of one memory standing in for a cluster
+#[async_trait]
+pub trait SummaryArbiter: Send + Sync {
+    async fn summarize(&self, cluster: &[MemoryRecord]) -> Result<String>;
+}
+
+/// Outcome of [`compact_commit_memories`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitCompaction {
+    /// New commit carrying the compacted memories
+    pub commit_id: CommitId,
+    /// Memories in the source commit
+    pub source_count: usize,
+    /// Memories in the compacted commit
+    pub memory_count: usize,
+    /// Clusters replaced by a summary
+    pub summarized_clusters: usize,
+}
+
+/// Compact the memories of `commit_id` into a new child commit
+///
+/// `policy` must use [`CompactionStrategy::Summarize`] and set no age or
+/// per-key limit. Memories are clustered at `threshold` cosine similarity
+/// and each cluster of two or more is replaced with a summary, which takes
+/// the key of the cluster's first member and records the cluster's keys
+/// under `summarized_from` in its metadata. Memories that cluster with
+/// nothing are carried over unchanged. The new commit reuses the source
+/// commit's snapshot; no branch is moved.
+#[instrument(skip(handle, arbiter))]
+pub async fn compact_commit_memories(
+    handle: &SurrealHandle,
+    commit_id: &str,
+    policy: &CompactionPolicy,
+    threshold: f32,
+    arbiter: &dyn SummaryArbiter,
+    author: &str,
+) -> Result<CommitCompaction> {
+    if policy.strategy != CompactionStrategy::Summarize {
+        return Err(AivcsError::Memory(format!(
+            "commit compaction needs the summarize strategy, got {:?}",
+            policy.strategy
+        )));
+    }
+    if policy.max_age_days.is_some() || policy.max_entries_per_key.is_some() {
+        return Err(AivcsError::Memory(
+            "commit compaction does not apply max_age_days or max_entries_per_key; unset them"
+                .to_string(),
+        ));
+    }
+    let storage = |e: oxidized_state::StateError| AivcsError::StorageError(e.to_string());
+    let memories = handle.get_memories(commit_id).await.map_err(storage)?;
+    let source_count = memories.len();
+
+    let mut compacted = Vec::new();
+    let mut summarized_clusters = 0;
+    for cluster in cluster_memories(memories, threshold) {
+        if let [memory] = cluster.as_slice() {
+            compacted.push(memory.clone());
+            continue;
+        }
+        let content = arbiter.summarize(&cluster).await?;
+        let keys: Vec<&str> = cluster.iter().map(|m| m.key.as_str()).collect();
+        compacted.push(
+            MemoryRecord::new(commit_id, &cluster[0].key, &content).with_metadata(
+                serde_json::json!({
+                    "summarized_from": keys,
+                    "source_commit": commit_id,
+                }),
+            ),
+        );
+        summarized_clusters += 1;
+    }
+
+    // The id covers the compacted content, so a different summary of the
+    // same commit gets its own commit
+    let mut state = format!("compact:{}", commit_id);
+    for memory in &compacted {
+        state.push_str(&format!("\n{}={}", memory.key, memory.content));
+    }
+    let new_id = CommitId::from_state(state.as_bytes());
+    for memory in &mut compacted {
+        memory.id = None;
+        memory.commit_id = new_id.hash.clone();
+    }
+
+    let snapshot = handle.load_snapshot(commit_id).await.map_err(storage)?;
+    handle
+        .save_snapshot(&new_id, snapshot.state)
+        .await
+        .map_err(storage)?;
+    handle
+        .save_memories_batch(&compacted)
+        .await
+        .map_err(storage)?;
+    let message = format!(
+        "Compact memories: {} -> {} ({} cluster(s) summarized)",
+        source_count,
+        compacted.len(),
+        summarized_clusters
+    );
+    handle
+        .save_commit(&CommitRecord::new(
+            new_id.clone(),
+            vec![commit_id.to_string()],
+            &message,
+            author,
+        ))
+        .await
+        .map_err(storage)?;
+    handle
+        .save_commit_graph_edge(&new_id.hash, commit_id)
+        .await
+        .map_err(storage)?;
+
+    Ok(CommitCompaction {
+        commit_id: new_id,
+        source_count,
+        memory_count: compacted.len(),
+        summarized_clusters,
+    })
+}
@@ -389,7 +389,8 @@ pub enum CompactionStrategy {
     DeleteOld,
     /// Keep only the most recent N per key.
     KeepRecentPerKey,
-    /// Merge old entries into summary records.
+    /// Merge old entries into summary records; see
+    /// [`crate::memory::compact_commit_memories`].
     Summarize,
 }
 
@@ -418,6 +419,14 @@ impl CompactionPolicy {
         }
     }
 
+    pub fn summarize() -> Self {
+        Self {
+            max_age_days: None,
+            max_entries_per_key: None,
+            strategy: CompactionStrategy::Summarize,
+        }
+    }
+
     /// Apply compaction to a list of memory entries, returning retained entries
     /// and the count of compacted entries.
     pub fn compact(&self, entries: &[MemoryEntry]) -> CompactionResult {
@@ -14,6 +14,7 @@ pub struct Metrics {
     events_processed: AtomicU64,
     replays_executed: AtomicU64,
     forks_created: AtomicU64,
+    events_deduplicated: AtomicU64,
 }
 
 impl Default for Metrics {
@@ -28,6 +29,7 @@ impl Metrics {
             events_processed: AtomicU64::new(0),
             replays_executed: AtomicU64::new(0),
             forks_created: AtomicU64::new(0),
+            events_deduplicated: AtomicU64::new(0),
         }
     }
 
@@ -49,6 +51,12 @@ impl Metrics {
         tracing::trace!(metric = "forks_created", "counter incremented");
     }
 
+    /// Increment the events-deduplicated counter by one.
+    pub fn inc_events_deduplicated(&self) {
+        self.events_deduplicated.fetch_add(1, Ordering::Relaxed);
+        tracing::trace!(metric = "events_deduplicated", "counter incremented");
+    }
+
     /// Emit all current counter values as a single `info!` event.
     ///
     /// Call this at natural boundaries (end of a run, daemon tick, etc.)
@@ -59,6 +67,7 @@ impl Metrics {
             events_processed = self.events_processed(),
             replays_executed = self.replays_executed(),
             forks_created = self.forks_created(),
+            events_deduplicated = self.events_deduplicated(),
         );
     }
 
@@ -77,11 +86,17 @@ impl Metrics {
         self.forks_created.load(Ordering::Relaxed)
     }
 
+    /// Read the current events-deduplicated count.
+    pub fn events_deduplicated(&self) -> u64 {
+        self.events_deduplicated.load(Ordering::Relaxed)
+    }
+
     /// Reset all counters to zero (useful in tests).
     pub fn reset(&self) {
         self.events_processed.store(0, Ordering::Relaxed);
         self.replays_executed.store(0, Ordering::Relaxed);
         self.forks_created.store(0, Ordering::Relaxed);
+        self.events_deduplicated.store(0, Ordering::Relaxed);
     }
 }
 
@@ -104,6 +119,9 @@ mod tests {
         m.inc_forks();
         m.inc_forks();
         assert_eq!(m.forks_created(), 3);
+
+        m.inc_events_deduplicated();
+        assert_eq!(m.events_deduplicated(), 1);
     }
 
     #[test]
@@ -112,9 +130,11 @@ mod tests {
         m.inc_events_processed();
         m.inc_replays();
         m.inc_forks();
+        m.inc_events_deduplicated();
         m.reset();
         assert_eq!(m.events_processed(), 0);
         assert_eq!(m.replays_executed(), 0);
         assert_eq!(m.forks_created(), 0);
+        assert_eq!(m.events_deduplicated(), 0);
     }
 }
@@ -204,6 +204,7 @@ impl BackportExecutor {
                     final_state_digest: None,
                     duration_ms: 0,
                     success: true,
+                    usage: None,
                 })
                 .await
                 .map_err(|e| MultiRepoError::Storage(e.to_string()))?;
@@ -214,6 +215,7 @@ impl BackportExecutor {
                     final_state_digest: None,
                     duration_ms: 0,
                     success: false,
+                    usage: None,
                 })
                 .await
                 .map_err(|e| MultiRepoError::Storage(e.to_string()))?;
@@ -274,6 +274,7 @@ impl ReleaseSequencer {
                     final_state_digest: None,
                     duration_ms: 0,
                     success: true,
+                    usage: None,
                 })
                 .await
                 .map_err(|e| MultiRepoError::Storage(e.to_string()))?;
@@ -284,6 +285,7 @@ impl ReleaseSequencer {
                     final_state_digest: None,
                     duration_ms: 0,
                     success: false,
+                    usage: None,
                 })
                 .await
                 .map_err(|e| MultiRepoError::Storage(e.to_string()))?;
@@ -18,6 +18,7 @@ use tokio::task::JoinHandle;
 use tracing::{debug, info, instrument, warn};
 
 use crate::metrics::METRICS;
+use crate::progress::{NoProgress, Progress};
 
 /// Result of forking multiple branches
 #[derive(Debug, Clone)]
@@ -86,12 +87,23 @@ impl Default for ParallelConfig {
 ///
 /// # Returns
 /// * `ForkResult` containing the created branch names and commit IDs
-#[instrument(skip(handle), fields(parent = %&parent_commit[..8.min(parent_commit.len())]))]
 pub async fn fork_agent_parallel(
     handle: Arc<SurrealHandle>,
     parent_commit: &str,
     count: u8,
     prefix: &str,
+) -> Result<ForkResult> {
+    fork_agent_parallel_with_progress(handle, parent_commit, count, prefix, &NoProgress).await
+}
+
+/// [`fork_agent_parallel`], reporting each created branch to `progress`
+#[instrument(skip(handle, progress), fields(parent = %&parent_commit[..8.min(parent_commit.len())]))]
+pub async fn fork_agent_parallel_with_progress(
+    handle: Arc<SurrealHandle>,
+    parent_commit: &str,
+    count: u8,
+    prefix: &str,
+    progress: &dyn Progress,
 ) -> Result<ForkResult> {
     METRICS.inc_forks();
     info!(
@@ -153,11 +165,23 @@ pub async fn fork_agent_parallel(
     let mut branches = Vec::new();
     let mut commit_ids = Vec::new();
 
+    progress.start("fork", tasks.len());
+    let mut joined = Ok(());
     for task in tasks {
-        let (name, id) = task.await??;
-        branches.push(name);
-        commit_ids.push(id);
+        match task.await.map_err(anyhow::Error::from).and_then(|r| r) {
+            Ok((name, id)) => {
+                progress.inc(1);
+                branches.push(name);
+                commit_ids.push(id);
+            }
+            Err(e) => {
+                joined = Err(e);
+                break;
+            }
+        }
     }
+    progress.finish();
+    joined?;
 
     info!("Created {} parallel branches", branches.len());
 
@@ -386,4 +410,50 @@ mod tests {
         assert_eq!(statuses[0].score, 0.75);
         assert!(statuses[0].active);
     }
+
+    #[derive(Default)]
+    struct CountingProgress {
+        total: std::sync::Mutex<Option<usize>>,
+        incs: std::sync::atomic::AtomicUsize,
+        finished: std::sync::atomic::AtomicBool,
+    }
+
+    impl Progress for CountingProgress {
+        fn start(&self, _label: &str, total: usize) {
+            *self.total.lock().unwrap() = Some(total);
+        }
+        fn inc(&self, n: usize) {
+            self.incs.fetch_add(n, std::sync::atomic::Ordering::SeqCst);
+        }
+        fn finish(&self) {
+            self.finished
+                .store(true, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fork_reports_one_increment_per_branch() {
+        let handle = Arc::new(SurrealHandle::setup_db().await.unwrap());
+        let parent_id = CommitId::from_state(b"progress-parent");
+        handle
+            .save_snapshot(&parent_id, serde_json::json!({ "step": 0 }))
+            .await
+            .unwrap();
+
+        let progress = CountingProgress::default();
+        let result = fork_agent_parallel_with_progress(
+            Arc::clone(&handle),
+            &parent_id.hash,
+            7,
+            "progress",
+            &progress,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.branches.len(), 7);
+        assert_eq!(*progress.total.lock().unwrap(), Some(7));
+        assert_eq!(progress.incs.load(std::sync::atomic::Ordering::SeqCst), 7);
+        assert!(progress.finished.load(std::sync::atomic::Ordering::SeqCst));
+    }
 }
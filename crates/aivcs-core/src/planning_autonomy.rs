@@ -3,8 +3,12 @@
 use std::collections::{BTreeMap, BTreeSet, VecDeque};
 
 use chrono::{DateTime, Utc};
+use oxidized_state::{PlanRecord, PlanTaskRecord};
 use serde::{Deserialize, Serialize};
 
+use crate::memory::DecisionRecorder;
+use crate::AivcsError;
+
 /// A high-level goal containing epics and tasks.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct GoalPlan {
@@ -51,6 +55,16 @@ pub struct PlanTask {
     pub status: PlanTaskStatus,
     pub confidence: f32,
     pub updated_at: DateTime<Utc>,
+    /// Declared resource cost charged against `SchedulerConstraints::max_total_cost`.
+    #[serde(default)]
+    pub cost: u32,
+    /// Scheduling priority; higher values are scheduled first.
+    #[serde(default)]
+    pub priority: i32,
+    /// Relative share of the plan's total work; unset tasks count as 1.0
+    /// once any task in the DAG declares a weight.
+    #[serde(default)]
+    pub weight: Option<f32>,
 }
 
 impl PlanTask {
@@ -63,6 +77,9 @@ impl PlanTask {
             status: PlanTaskStatus::Pending,
             confidence: 1.0,
             updated_at,
+            cost: 0,
+            priority: 0,
+            weight: None,
         }
     }
 }
@@ -123,13 +140,67 @@ impl ExecutionDag {
         }
         Ok(())
     }
+
+    /// Convert into persistence records for `SurrealHandle::save_plan`.
+    pub fn to_plan_records(&self) -> Result<(PlanRecord, Vec<PlanTaskRecord>), PlanningError> {
+        let plan = PlanRecord::new(&self.goal_id, &self.objective);
+        let tasks = self
+            .tasks
+            .values()
+            .map(|task| {
+                Ok(PlanTaskRecord::new(
+                    &self.goal_id,
+                    &task.id,
+                    task.depends_on.clone(),
+                    serde_json::to_value(&task.status)?,
+                    serde_json::to_value(task)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, serde_json::Error>>()
+            .map_err(|e| PlanningError::Persistence(e.to_string()))?;
+        Ok((plan, tasks))
+    }
+
+    /// Rebuild a DAG from records returned by `SurrealHandle::load_plan`.
+    ///
+    /// The record's `status` and `depends_on` columns win over the copies in
+    /// the task payload, since those are what `update_task_status` touches.
+    pub fn from_plan_records(
+        plan: PlanRecord,
+        records: Vec<PlanTaskRecord>,
+    ) -> Result<Self, PlanningError> {
+        let mut tasks = BTreeMap::new();
+        for record in records {
+            let mut task: PlanTask = serde_json::from_value(record.task)
+                .map_err(|e| PlanningError::Persistence(e.to_string()))?;
+            task.status = serde_json::from_value(record.status)
+                .map_err(|e| PlanningError::Persistence(e.to_string()))?;
+            task.depends_on = record.depends_on;
+            task.updated_at = task.updated_at.max(record.updated_at);
+            tasks.insert(record.task_id, task);
+        }
+
+        let dag = Self {
+            goal_id: plan.plan_id,
+            objective: plan.objective,
+            tasks,
+        };
+        dag.validate()?;
+        Ok(dag)
+    }
 }
 
 /// Scheduling controls.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// `max_parallel` and `max_total_cost` are budgets over everything running at
+/// once, so tasks already `InProgress` consume part of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SchedulerConstraints {
     pub max_parallel: usize,
     pub blocked_tasks: BTreeSet<String>,
+    /// Upper bound on the summed `cost` of running tasks; `None` is unbounded.
+    #[serde(default)]
+    pub max_total_cost: Option<u32>,
 }
 
 /// Progress report over task execution state.
@@ -141,7 +212,11 @@ pub struct ProgressReport {
     pub blocked_tasks: usize,
     pub failed_tasks: usize,
     pub pending_tasks: usize,
+    /// Fraction of tasks done, counting every task equally.
     pub completion_ratio: f32,
+    /// Fraction of task weight done; equals `completion_ratio` when no task
+    /// declares a weight.
+    pub weighted_completion_ratio: f32,
     pub confidence: f32,
     pub blockers: Vec<String>,
 }
@@ -183,6 +258,56 @@ pub struct ReplanDecision {
     pub reasons: Vec<ReplanReason>,
 }
 
+/// Hysteresis controls that damp replan oscillation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecoveryControls {
+    /// Minimum time between two approved replans.
+    pub min_replan_interval_minutes: i64,
+    /// Minimum absolute change in plan confidence since the last approved
+    /// replan before another one is allowed.
+    pub min_confidence_delta: f32,
+}
+
+impl Default for RecoveryControls {
+    fn default() -> Self {
+        Self {
+            min_replan_interval_minutes: 30,
+            min_confidence_delta: 0.1,
+        }
+    }
+}
+
+/// Replan history carried between evaluations.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReplanControlState {
+    pub last_replan_at: Option<DateTime<Utc>>,
+    pub last_replan_confidence: Option<f32>,
+    pub suppressed_count: u32,
+}
+
+/// Why a policy-triggered replan was held back.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum ReplanSuppressionReason {
+    WithinMinInterval {
+        elapsed_minutes: i64,
+        min_interval_minutes: i64,
+    },
+    ConfidenceDeltaTooSmall {
+        observed_delta: f32,
+        min_delta: f32,
+    },
+}
+
+/// Replan decision after hysteresis controls are applied.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ControlledReplanDecision {
+    pub should_replan: bool,
+    /// The raw policy decision before controls were applied.
+    pub policy_decision: ReplanDecision,
+    pub suppression: Option<ReplanSuppressionReason>,
+}
+
 #[derive(Debug, thiserror::Error, PartialEq, Eq)]
 pub enum PlanningError {
     #[error("task '{task_id}' has missing dependency '{missing_dependency}'")]
@@ -192,14 +317,27 @@ pub enum PlanningError {
     },
     #[error("dependency cycle detected in execution DAG")]
     CycleDetected,
+    #[error("goal text is empty")]
+    EmptyGoal,
+    #[error("duplicate task id '{0}' in decomposition")]
+    DuplicateTask(String),
+    #[error("planner model failed: {0}")]
+    Model(String),
+    #[error("invalid planner output: {0}")]
+    InvalidPlannerOutput(String),
+    #[error("plan persistence failed: {0}")]
+    Persistence(String),
 }
 
-/// Decompose a goal into executable DAG tasks.
-pub fn decompose_goal_to_dag(goal: &GoalPlan) -> Result<ExecutionDag, PlanningError> {
+/// Build an executable DAG from an already structured goal plan.
+pub fn build_dag_from_plan(goal: &GoalPlan) -> Result<ExecutionDag, PlanningError> {
     let mut tasks = BTreeMap::new();
     let now = Utc::now();
     for epic in &goal.epics {
         for t in &epic.tasks {
+            if tasks.contains_key(&t.id) {
+                return Err(PlanningError::DuplicateTask(t.id.clone()));
+            }
             tasks.insert(
                 t.id.clone(),
                 PlanTask {
@@ -210,6 +348,9 @@ pub fn decompose_goal_to_dag(goal: &GoalPlan) -> Result<ExecutionDag, PlanningEr
                     status: PlanTaskStatus::Pending,
                     confidence: 1.0,
                     updated_at: now,
+                    cost: 0,
+                    priority: 0,
+                    weight: None,
                 },
             );
         }
@@ -224,17 +365,206 @@ pub fn decompose_goal_to_dag(goal: &GoalPlan) -> Result<ExecutionDag, PlanningEr
     Ok(dag)
 }
 
+/// Strategy for turning a natural-language goal into an execution DAG.
+pub trait GoalDecomposer {
+    fn decompose(&self, goal: &str) -> Result<ExecutionDag, PlanningError>;
+}
+
+/// Decompose a natural-language goal with the given planner.
+///
+/// The returned DAG is always re-validated, so planners cannot hand back
+/// cycles or dangling dependencies.
+pub fn decompose_goal_to_dag(
+    decomposer: &dyn GoalDecomposer,
+    goal: &str,
+) -> Result<ExecutionDag, PlanningError> {
+    if goal.trim().is_empty() {
+        return Err(PlanningError::EmptyGoal);
+    }
+    let dag = decomposer.decompose(goal)?;
+    dag.validate()?;
+    Ok(dag)
+}
+
+/// Stable goal id derived from the goal text.
+fn goal_id_for(goal: &str) -> String {
+    use sha2::{Digest, Sha256};
+    let hash = hex::encode(Sha256::digest(goal.trim().as_bytes()));
+    format!("goal-{}", &hash[..12])
+}
+
+fn plan_from_tasks(goal: &str, tasks: Vec<TaskPlan>) -> GoalPlan {
+    GoalPlan {
+        id: goal_id_for(goal),
+        objective: goal.trim().to_string(),
+        epics: vec![EpicPlan {
+            id: "epic-1".to_string(),
+            title: goal.trim().to_string(),
+            tasks,
+        }],
+    }
+}
+
+/// Rule-based decomposer: splits the goal into steps on newlines, `;` and
+/// `then`, and chains each step on the one before it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeuristicDecomposer;
+
+impl HeuristicDecomposer {
+    fn split_steps(goal: &str) -> Vec<String> {
+        goal.split(['\n', ';'])
+            .flat_map(|part| part.split(" then "))
+            .map(|step| Self::strip_list_marker(step.trim()).to_string())
+            .filter(|step| !step.is_empty())
+            .collect()
+    }
+
+    /// Drop a leading `-`, `*`, `1.` or `1)` list marker.
+    fn strip_list_marker(step: &str) -> &str {
+        if let Some(rest) = step.strip_prefix(['-', '*']) {
+            return rest.trim_start();
+        }
+        let digits = step.len() - step.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if digits > 0 {
+            if let Some(rest) = step[digits..].strip_prefix(['.', ')']) {
+                return rest.trim_start();
+            }
+        }
+        step
+    }
+}
+
+impl GoalDecomposer for HeuristicDecomposer {
+    fn decompose(&self, goal: &str) -> Result<ExecutionDag, PlanningError> {
+        let steps = Self::split_steps(goal);
+        if steps.is_empty() {
+            return Err(PlanningError::EmptyGoal);
+        }
+
+        let tasks = steps
+            .into_iter()
+            .enumerate()
+            .map(|(i, title)| TaskPlan {
+                id: format!("t{}", i + 1),
+                title,
+                depends_on: if i == 0 {
+                    Vec::new()
+                } else {
+                    vec![format!("t{i}")]
+                },
+                estimate_hours: 1,
+            })
+            .collect();
+
+        build_dag_from_plan(&plan_from_tasks(goal, tasks))
+    }
+}
+
+/// Text-completion backend used by [`LlmDecomposer`].
+pub trait PlannerModel {
+    fn complete(&self, prompt: &str) -> Result<String, PlanningError>;
+}
+
+#[derive(Debug, Deserialize)]
+struct LlmPlanOutput {
+    tasks: Vec<TaskPlan>,
+}
+
+/// Decomposer that prompts a model for tasks with dependencies and validates
+/// the answer into a DAG.
+///
+/// The model must reply with JSON of the form
+/// `{"tasks": [{"id", "title", "depends_on", "estimate_hours"}]}`; a fenced
+/// code block around the JSON is tolerated.
+pub struct LlmDecomposer<M: PlannerModel> {
+    model: M,
+}
+
+impl<M: PlannerModel> LlmDecomposer<M> {
+    pub fn new(model: M) -> Self {
+        Self { model }
+    }
+
+    pub fn prompt_for(goal: &str) -> String {
+        format!(
+            "Decompose the following goal into concrete tasks.\n\
+             Reply with JSON only, shaped as \
+             {{\"tasks\": [{{\"id\": string, \"title\": string, \
+             \"depends_on\": [task ids], \"estimate_hours\": integer}}]}}.\n\
+             Dependencies must reference ids in the same list and must not form cycles.\n\n\
+             Goal: {}",
+            goal.trim()
+        )
+    }
+
+    fn parse_output(raw: &str) -> Result<Vec<TaskPlan>, PlanningError> {
+        let trimmed = raw.trim();
+        let body = trimmed
+            .strip_prefix("```json")
+            .or_else(|| trimmed.strip_prefix("```"))
+            .and_then(|rest| rest.strip_suffix("```"))
+            .unwrap_or(trimmed);
+        let parsed: LlmPlanOutput = serde_json::from_str(body.trim())
+            .map_err(|e| PlanningError::InvalidPlannerOutput(e.to_string()))?;
+        if parsed.tasks.is_empty() {
+            return Err(PlanningError::InvalidPlannerOutput(
+                "model returned no tasks".to_string(),
+            ));
+        }
+        Ok(parsed.tasks)
+    }
+}
+
+impl<M: PlannerModel> GoalDecomposer for LlmDecomposer<M> {
+    fn decompose(&self, goal: &str) -> Result<ExecutionDag, PlanningError> {
+        let raw = self.model.complete(&Self::prompt_for(goal))?;
+        let tasks = Self::parse_output(&raw)?;
+        build_dag_from_plan(&plan_from_tasks(goal, tasks))
+    }
+}
+
+/// Deterministic decomposer returning a fixed task list, for tests.
+#[derive(Debug, Clone, Default)]
+pub struct MockDecomposer {
+    pub tasks: Vec<TaskPlan>,
+}
+
+impl MockDecomposer {
+    pub fn new(tasks: Vec<TaskPlan>) -> Self {
+        Self { tasks }
+    }
+}
+
+impl GoalDecomposer for MockDecomposer {
+    fn decompose(&self, goal: &str) -> Result<ExecutionDag, PlanningError> {
+        build_dag_from_plan(&plan_from_tasks(goal, self.tasks.clone()))
+    }
+}
+
 /// Return dependency-ready tasks respecting constraints.
+///
+/// Ready tasks are ordered by descending priority, then id, and admitted
+/// greedily while both the concurrency and cost budgets have room.
 pub fn schedule_next_ready_tasks(
     dag: &ExecutionDag,
     constraints: &SchedulerConstraints,
 ) -> Result<Vec<String>, PlanningError> {
     dag.validate()?;
-    if constraints.max_parallel == 0 {
+
+    let running: Vec<&PlanTask> = dag
+        .tasks
+        .values()
+        .filter(|t| t.status == PlanTaskStatus::InProgress)
+        .collect();
+    let mut slots = constraints.max_parallel.saturating_sub(running.len());
+    if slots == 0 {
         return Ok(Vec::new());
     }
+    let mut remaining_cost = constraints
+        .max_total_cost
+        .map(|max| max.saturating_sub(running.iter().map(|t| t.cost).sum()));
 
-    let mut ready: Vec<String> = dag
+    let mut ready: Vec<&PlanTask> = dag
         .tasks
         .iter()
         .filter_map(|(id, task)| match task.status {
@@ -250,12 +580,26 @@ pub fn schedule_next_ready_tasks(
                 )
             })
         })
-        .map(|(id, _)| id.clone())
+        .map(|(_, task)| task)
         .collect();
 
-    ready.sort();
-    ready.truncate(constraints.max_parallel);
-    Ok(ready)
+    ready.sort_by(|a, b| b.priority.cmp(&a.priority).then_with(|| a.id.cmp(&b.id)));
+
+    let mut selected = Vec::new();
+    for task in ready {
+        if slots == 0 {
+            break;
+        }
+        if let Some(budget) = remaining_cost.as_mut() {
+            if task.cost > *budget {
+                continue;
+            }
+            *budget -= task.cost;
+        }
+        selected.push(task.id.clone());
+        slots -= 1;
+    }
+    Ok(selected)
 }
 
 /// Compute progress and confidence.
@@ -268,11 +612,19 @@ pub fn compute_progress(dag: &ExecutionDag) -> ProgressReport {
     let mut pending = 0usize;
     let mut blockers = Vec::new();
     let mut confidence_sum = 0.0f32;
+    let weighted = dag.tasks.values().any(|t| t.weight.is_some());
+    let mut weight_total = 0.0f32;
+    let mut weight_done = 0.0f32;
 
     for task in dag.tasks.values() {
         confidence_sum += task.confidence;
+        let weight = task.weight.unwrap_or(1.0).max(0.0);
+        weight_total += weight;
         match &task.status {
-            PlanTaskStatus::Done => done += 1,
+            PlanTaskStatus::Done => {
+                done += 1;
+                weight_done += weight;
+            }
             PlanTaskStatus::InProgress => in_progress += 1,
             PlanTaskStatus::Blocked { reason } => {
                 blocked += 1;
@@ -288,6 +640,13 @@ pub fn compute_progress(dag: &ExecutionDag) -> ProgressReport {
     } else {
         done as f32 / total as f32
     };
+    let weighted_completion_ratio = if !weighted {
+        completion_ratio
+    } else if weight_total > 0.0 {
+        weight_done / weight_total
+    } else {
+        0.0
+    };
     let confidence = if total == 0 {
         0.0
     } else {
@@ -302,6 +661,7 @@ pub fn compute_progress(dag: &ExecutionDag) -> ProgressReport {
         failed_tasks: failed,
         pending_tasks: pending,
         completion_ratio,
+        weighted_completion_ratio,
         confidence,
         blockers,
     }
@@ -356,3 +716,86 @@ pub fn evaluate_replan(
         reasons,
     }
 }
+
+/// Evaluate replan triggers, suppressing replans that would thrash.
+///
+/// A triggered replan is held back when the previous approved replan was
+/// less than `min_replan_interval_minutes` ago, or when plan confidence has
+/// moved by less than `min_confidence_delta` since then. `state` is updated
+/// with the outcome so it can be fed into the next evaluation.
+pub fn evaluate_replan_with_controls(
+    dag: &ExecutionDag,
+    policy: &ReplanPolicy,
+    controls: &RecoveryControls,
+    state: &mut ReplanControlState,
+    now: DateTime<Utc>,
+) -> ControlledReplanDecision {
+    let policy_decision = evaluate_replan(dag, policy, now);
+    if !policy_decision.should_replan {
+        return ControlledReplanDecision {
+            should_replan: false,
+            policy_decision,
+            suppression: None,
+        };
+    }
+
+    let confidence = compute_progress(dag).confidence;
+    let mut suppression = None;
+
+    if let Some(last_at) = state.last_replan_at {
+        let elapsed_minutes = (now - last_at).num_minutes();
+        if elapsed_minutes < controls.min_replan_interval_minutes {
+            suppression = Some(ReplanSuppressionReason::WithinMinInterval {
+                elapsed_minutes,
+                min_interval_minutes: controls.min_replan_interval_minutes,
+            });
+        }
+    }
+
+    if suppression.is_none() {
+        if let Some(last_confidence) = state.last_replan_confidence {
+            let observed_delta = (confidence - last_confidence).abs();
+            if observed_delta < controls.min_confidence_delta {
+                suppression = Some(ReplanSuppressionReason::ConfidenceDeltaTooSmall {
+                    observed_delta,
+                    min_delta: controls.min_confidence_delta,
+                });
+            }
+        }
+    }
+
+    if suppression.is_some() {
+        state.suppressed_count += 1;
+    } else {
+        state.last_replan_at = Some(now);
+        state.last_replan_confidence = Some(confidence);
+    }
+
+    ControlledReplanDecision {
+        should_replan: suppression.is_none(),
+        policy_decision,
+        suppression,
+    }
+}
+
+/// [`evaluate_replan_with_controls`], recording the outcome for `plan_id`
+/// at `commit_id` through `recorder` when one is given.
+#[allow(clippy::too_many_arguments)]
+pub async fn evaluate_replan_recorded(
+    dag: &ExecutionDag,
+    policy: &ReplanPolicy,
+    controls: &RecoveryControls,
+    state: &mut ReplanControlState,
+    now: DateTime<Utc>,
+    recorder: Option<&DecisionRecorder>,
+    commit_id: &str,
+    plan_id: &str,
+) -> Result<ControlledReplanDecision, AivcsError> {
+    let decision = evaluate_replan_with_controls(dag, policy, controls, state, now);
+    if let Some(recorder) = recorder {
+        recorder
+            .record_replan(commit_id, plan_id, &decision)
+            .await?;
+    }
+    Ok(decision)
+}
@@ -0,0 +1,144 @@
+//! Progress reporting for long-running operations
+//!
+//! Library calls that walk many items (forking branches, merging memories,
+//! writing bundles) report through a [`Progress`] sink. Pass [`NoProgress`]
+//! to stay silent, [`TermProgress`] for a terminal bar, or your own sink.
+
+use std::io::Write;
+use std::sync::Mutex;
+
+/// Receives item counts from a long-running operation
+pub trait Progress: Send + Sync {
+    /// Work on `total` items named `label` is starting
+    fn start(&self, label: &str, total: usize);
+    /// `n` more items are done
+    fn inc(&self, n: usize);
+    /// The operation is over, successfully or not
+    fn finish(&self);
+}
+
+/// Sink that ignores every report
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProgress;
+
+impl Progress for NoProgress {
+    fn start(&self, _label: &str, _total: usize) {}
+    fn inc(&self, _n: usize) {}
+    fn finish(&self) {}
+}
+
+const BAR_WIDTH: usize = 30;
+
+#[derive(Default)]
+struct BarState {
+    label: String,
+    done: usize,
+    total: usize,
+}
+
+/// Single-line `label [####    ] done/total` bar, redrawn in place
+pub struct TermProgress {
+    out: Mutex<Box<dyn Write + Send>>,
+    state: Mutex<BarState>,
+}
+
+impl TermProgress {
+    /// Bar drawn on stderr, leaving stdout for command output
+    pub fn stderr() -> Self {
+        Self::new(Box::new(std::io::stderr()))
+    }
+
+    /// Bar drawn on `out`
+    pub fn new(out: Box<dyn Write + Send>) -> Self {
+        Self {
+            out: Mutex::new(out),
+            state: Mutex::new(BarState::default()),
+        }
+    }
+
+    fn draw(&self, state: &BarState) {
+        let filled = if state.total == 0 {
+            BAR_WIDTH
+        } else {
+            BAR_WIDTH * state.done.min(state.total) / state.total
+        };
+        let mut out = self.out.lock().unwrap_or_else(|e| e.into_inner());
+        // Progress is best-effort; a closed stderr must not fail the operation
+        let _ = write!(
+            out,
+            "\r{} [{}{}] {}/{}",
+            state.label,
+            "#".repeat(filled),
+            " ".repeat(BAR_WIDTH - filled),
+            state.done,
+            state.total
+        );
+        let _ = out.flush();
+    }
+}
+
+impl Progress for TermProgress {
+    fn start(&self, label: &str, total: usize) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        *state = BarState {
+            label: label.to_string(),
+            done: 0,
+            total,
+        };
+        self.draw(&state);
+    }
+
+    fn inc(&self, n: usize) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.done += n;
+        self.draw(&state);
+    }
+
+    fn finish(&self) {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.label.is_empty() {
+            return;
+        }
+        let mut out = self.out.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = writeln!(out);
+        let _ = out.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_term_progress_redraws_counts_in_place() {
+        let buf = SharedBuf::default();
+        let bar = TermProgress::new(Box::new(buf.clone()));
+
+        bar.start("fork", 4);
+        bar.inc(1);
+        bar.inc(3);
+        bar.finish();
+
+        let text = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+        let frames: Vec<&str> = text.trim_end().split('\r').skip(1).collect();
+        assert_eq!(frames.len(), 3);
+        assert!(frames[0].ends_with("] 0/4"), "{text:?}");
+        assert!(frames[1].starts_with("fork [#######"), "{text:?}");
+        assert!(frames[2].ends_with(&format!("[{}] 4/4", "#".repeat(BAR_WIDTH))));
+        assert!(text.ends_with('\n'));
+    }
+}
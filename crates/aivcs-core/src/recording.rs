@@ -1,12 +1,19 @@
 //! Graph lifecycle adapter: bridges domain `Event` types to `RunLedger` persistence.
 
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use tracing::warn;
 
 use oxidized_state::{
-    ContentDigest, RunEvent, RunId, RunLedger, RunMetadata, RunSummary, StorageResult,
+    ContentDigest, RunEvent, RunId, RunLedger, RunMetadata, RunSummary, RunUsage, StorageResult,
 };
 
+use crate::cost::{event_usage, usage_from_events};
 use crate::domain::run::{Event, EventKind};
+use crate::domain::{AivcsError, Result};
 
 /// Extract the snake_case kind string from an `EventKind` via its serde tag.
 fn event_kind_str(kind: &EventKind) -> String {
@@ -16,15 +23,55 @@ fn event_kind_str(kind: &EventKind) -> String {
         .unwrap_or_else(|| "unknown".to_string())
 }
 
+/// Split an `EventKind` into its snake_case kind string and its fields as a payload object.
+fn kind_and_payload(kind: &EventKind) -> (String, serde_json::Value) {
+    let mut fields = serde_json::to_value(kind).unwrap_or_else(|_| serde_json::json!({}));
+    if let serde_json::Value::Object(ref mut map) = fields {
+        map.remove("type");
+    }
+    (event_kind_str(kind), fields)
+}
+
+/// Append `kind` as the next event in the run, using and advancing `seq`.
+async fn append_kind(
+    ledger: &dyn RunLedger,
+    run_id: &RunId,
+    seq: &AtomicU64,
+    kind: EventKind,
+) -> StorageResult<()> {
+    let (kind_str, payload) = kind_and_payload(&kind);
+    let next = seq.fetch_add(1, Ordering::SeqCst) + 1;
+    let run_event = RunEvent {
+        seq: next,
+        kind: kind_str.clone(),
+        payload,
+        timestamp: chrono::Utc::now(),
+    };
+    ledger.append_event(run_id, run_event).await?;
+    crate::obs::emit_event_appended(&run_id.to_string(), &kind_str, next);
+    Ok(())
+}
+
 /// Adapter that records graph lifecycle [`Event`]s into a [`RunLedger`].
 ///
 /// Usage:
 /// 1. Call [`GraphRunRecorder::start`] to create a new run.
 /// 2. Call [`GraphRunRecorder::record`] for each domain event.
 /// 3. Call [`GraphRunRecorder::finish_ok`] or [`GraphRunRecorder::finish_err`] to finalize.
+///
+/// Node boundaries can be recorded with [`GraphRunRecorder::enter_node`], which
+/// emits `node_entered` and returns a [`NodeGuard`] that emits `node_exited`
+/// with the measured duration when exited or dropped.
+///
+/// Token usage reported by recorded events (see [`crate::cost`]) is summed
+/// and stored in the run summary unless the summary already carries usage.
 pub struct GraphRunRecorder {
     ledger: Arc<dyn RunLedger>,
     run_id: RunId,
+    /// Highest seq appended so far; auto-generated events continue from here.
+    seq: Arc<AtomicU64>,
+    node_iterations: Mutex<HashMap<String, u32>>,
+    usage: Mutex<RunUsage>,
 }
 
 impl GraphRunRecorder {
@@ -36,7 +83,13 @@ impl GraphRunRecorder {
     ) -> StorageResult<Self> {
         let run_id = ledger.create_run(spec_digest, metadata.clone()).await?;
         crate::obs::emit_run_started(run_id.to_string().as_str(), &metadata.agent_name);
-        Ok(Self { ledger, run_id })
+        Ok(Self {
+            ledger,
+            run_id,
+            seq: Arc::new(AtomicU64::new(0)),
+            node_iterations: Mutex::new(HashMap::new()),
+            usage: Mutex::new(RunUsage::default()),
+        })
     }
 
     /// Record a single domain event into the ledger.
@@ -62,13 +115,54 @@ impl GraphRunRecorder {
             payload,
             timestamp: event.timestamp,
         };
+        let usage = event_usage(&run_event.payload);
         self.ledger.append_event(&self.run_id, run_event).await?;
+        self.seq.fetch_max(event.seq, Ordering::SeqCst);
+        if let Some((model, tokens)) = usage {
+            self.usage.lock().unwrap().add(&model, &tokens);
+        }
         crate::obs::emit_event_appended(&self.run_id.to_string(), &kind_str, event.seq);
         Ok(())
     }
 
+    /// Record entry into `node_id` and return a guard that records the exit.
+    ///
+    /// The `node_exited` event carries `duration_ms` measured from this call.
+    /// Prefer [`NodeGuard::exit`] to observe append errors; dropping the guard
+    /// records the exit on a background task instead.
+    pub async fn enter_node(&self, node_id: &str) -> StorageResult<NodeGuard> {
+        let iteration = {
+            let mut iterations = self.node_iterations.lock().unwrap();
+            let count = iterations.entry(node_id.to_string()).or_insert(0);
+            *count += 1;
+            *count
+        };
+
+        append_kind(
+            self.ledger.as_ref(),
+            &self.run_id,
+            &self.seq,
+            EventKind::NodeEntered {
+                node_id: node_id.to_string(),
+                iteration,
+            },
+        )
+        .await?;
+
+        Ok(NodeGuard {
+            ledger: self.ledger.clone(),
+            run_id: self.run_id.clone(),
+            seq: self.seq.clone(),
+            node_id: node_id.to_string(),
+            next_node: None,
+            started: Instant::now(),
+            exited: false,
+        })
+    }
+
     /// Finalize the run as completed.
     pub async fn finish_ok(self, summary: RunSummary) -> StorageResult<()> {
+        let summary = self.with_recorded_usage(summary);
         let duration_ms = summary.duration_ms;
         let total_events = summary.total_events;
         self.ledger.complete_run(&self.run_id, summary).await?;
@@ -78,6 +172,7 @@ impl GraphRunRecorder {
 
     /// Finalize the run as failed.
     pub async fn finish_err(self, summary: RunSummary) -> StorageResult<()> {
+        let summary = self.with_recorded_usage(summary);
         let duration_ms = summary.duration_ms;
         let total_events = summary.total_events;
         self.ledger.fail_run(&self.run_id, summary).await?;
@@ -89,6 +184,160 @@ impl GraphRunRecorder {
     pub fn run_id(&self) -> &RunId {
         &self.run_id
     }
+
+    /// Highest event seq appended so far (0 before the first event).
+    pub fn last_seq(&self) -> u64 {
+        self.seq.load(Ordering::SeqCst)
+    }
+
+    /// `summary` with the usage recorded so far, unless it already has some.
+    fn with_recorded_usage(&self, mut summary: RunSummary) -> RunSummary {
+        let usage = self.usage.lock().unwrap();
+        if summary.usage.is_none() && !usage.is_empty() {
+            summary.usage = Some(usage.clone());
+        }
+        summary
+    }
+}
+
+/// Persist a complete run from its events in one call.
+///
+/// Creates the run, appends `events` in order, and finalizes it with a
+/// summary derived from the events: the run is failed if it contains a
+/// `graph_failed` event and completed otherwise. Events must be numbered
+/// contiguously from seq 1; this is checked before anything is written.
+///
+/// # Errors
+///
+/// - `AivcsError::NonContiguousEvents` if the event seqs are not `1, 2, 3, ...`.
+/// - `AivcsError::StorageError` if the ledger rejects any step.
+pub async fn record_run(
+    ledger: &dyn RunLedger,
+    spec_digest: &ContentDigest,
+    metadata: RunMetadata,
+    events: Vec<RunEvent>,
+) -> Result<RunId> {
+    for (expected, event) in (1..).zip(&events) {
+        if event.seq != expected {
+            return Err(AivcsError::NonContiguousEvents {
+                expected,
+                actual: event.seq,
+            });
+        }
+    }
+    let summary = summarize_events(&events);
+    let storage_err = |e: oxidized_state::StorageError| AivcsError::StorageError(e.to_string());
+
+    let agent_name = metadata.agent_name.clone();
+    let run_id = ledger
+        .create_run(spec_digest, metadata)
+        .await
+        .map_err(storage_err)?;
+    crate::obs::emit_run_started(&run_id.to_string(), &agent_name);
+
+    for event in events {
+        let (kind, seq) = (event.kind.clone(), event.seq);
+        ledger
+            .append_event(&run_id, event)
+            .await
+            .map_err(storage_err)?;
+        crate::obs::emit_event_appended(&run_id.to_string(), &kind, seq);
+    }
+
+    let (duration_ms, total_events, success) =
+        (summary.duration_ms, summary.total_events, summary.success);
+    let finished = if success {
+        ledger.complete_run(&run_id, summary).await
+    } else {
+        ledger.fail_run(&run_id, summary).await
+    };
+    finished.map_err(storage_err)?;
+    crate::obs::emit_run_finished(&run_id.to_string(), duration_ms, total_events, success);
+
+    Ok(run_id)
+}
+
+/// Summary of a finished run's events: its count, the span between the first
+/// and last timestamps, whether any `graph_failed` event was recorded, and
+/// the token usage the events reported.
+fn summarize_events(events: &[RunEvent]) -> RunSummary {
+    let duration_ms = match (events.first(), events.last()) {
+        (Some(first), Some(last)) => {
+            (last.timestamp - first.timestamp).num_milliseconds().max(0) as u64
+        }
+        _ => 0,
+    };
+    RunSummary {
+        total_events: events.len() as u64,
+        final_state_digest: None,
+        duration_ms,
+        success: !events.iter().any(|e| e.kind == "graph_failed"),
+        usage: Some(usage_from_events(events)).filter(|u| !u.is_empty()),
+    }
+}
+
+/// Scope guard returned by [`GraphRunRecorder::enter_node`].
+///
+/// Emits `node_exited` with the elapsed time on [`NodeGuard::exit`] or on drop.
+pub struct NodeGuard {
+    ledger: Arc<dyn RunLedger>,
+    run_id: RunId,
+    seq: Arc<AtomicU64>,
+    node_id: String,
+    next_node: Option<String>,
+    started: Instant,
+    exited: bool,
+}
+
+impl NodeGuard {
+    /// Record which node execution moves to next.
+    pub fn set_next_node(&mut self, next_node: impl Into<String>) {
+        self.next_node = Some(next_node.into());
+    }
+
+    /// Node this guard was created for.
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    /// Record the exit now and surface any ledger error.
+    pub async fn exit(mut self) -> StorageResult<()> {
+        self.exited = true;
+        let kind = self.exit_kind();
+        append_kind(self.ledger.as_ref(), &self.run_id, &self.seq, kind).await
+    }
+
+    fn exit_kind(&self) -> EventKind {
+        EventKind::NodeExited {
+            node_id: self.node_id.clone(),
+            next_node: self.next_node.clone(),
+            duration_ms: self.started.elapsed().as_millis() as u64,
+        }
+    }
+}
+
+impl Drop for NodeGuard {
+    fn drop(&mut self) {
+        if self.exited {
+            return;
+        }
+        let kind = self.exit_kind();
+        let ledger = self.ledger.clone();
+        let run_id = self.run_id.clone();
+        let seq = self.seq.clone();
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(async move {
+                    if let Err(e) = append_kind(ledger.as_ref(), &run_id, &seq, kind).await {
+                        warn!(error = %e, run_id = %run_id, "NodeGuard: failed to record node exit");
+                    }
+                });
+            }
+            Err(_) => {
+                warn!(node_id = %self.node_id, "NodeGuard dropped outside a runtime; node exit not recorded");
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -143,4 +392,144 @@ mod tests {
             "tool_name missing from recorded payload"
         );
     }
+
+    fn test_metadata() -> oxidized_state::RunMetadata {
+        oxidized_state::RunMetadata {
+            git_sha: None,
+            agent_name: "test".to_string(),
+            tags: json!({}),
+            evaluation: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn enter_node_records_enter_and_exit_with_duration() {
+        let ledger = Arc::new(SurrealRunLedger::in_memory().await.unwrap());
+        let spec_digest = oxidized_state::ContentDigest::from_bytes(b"spec");
+        let recorder = GraphRunRecorder::start(ledger.clone(), &spec_digest, test_metadata())
+            .await
+            .unwrap();
+        let run_id = recorder.run_id().clone();
+
+        let mut guard = recorder.enter_node("planner").await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        guard.set_next_node("executor");
+        guard.exit().await.unwrap();
+
+        let events = ledger.get_events(&run_id).await.unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind, "node_entered");
+        assert_eq!(events[0].payload["node_id"], "planner");
+        assert_eq!(events[0].payload["iteration"], 1);
+        assert_eq!(events[1].kind, "node_exited");
+        assert_eq!(events[1].payload["node_id"], "planner");
+        assert_eq!(events[1].payload["next_node"], "executor");
+        assert!(events[1].payload["duration_ms"].as_u64().unwrap() > 0);
+        assert_eq!(events[1].seq, events[0].seq + 1);
+    }
+
+    #[tokio::test]
+    async fn dropped_node_guard_records_exit() {
+        let ledger = Arc::new(oxidized_state::fakes::MemoryRunLedger::new());
+        let spec_digest = oxidized_state::ContentDigest::from_bytes(b"spec");
+        let recorder = GraphRunRecorder::start(ledger.clone(), &spec_digest, test_metadata())
+            .await
+            .unwrap();
+        let run_id = recorder.run_id().clone();
+
+        {
+            let _guard = recorder.enter_node("critic").await.unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+
+        let mut events = Vec::new();
+        for _ in 0..100 {
+            events = ledger.get_events(&run_id).await.unwrap();
+            if events.len() == 2 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].kind, "node_exited");
+        assert_eq!(events[1].payload["node_id"], "critic");
+        assert!(events[1].payload["duration_ms"].as_u64().unwrap() > 0);
+    }
+
+    fn fixed_events() -> Vec<RunEvent> {
+        let ts = chrono::DateTime::parse_from_rfc3339("2024-06-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        [
+            (
+                "graph_started",
+                json!({ "graph_name": "g", "entry_point": "start" }),
+            ),
+            (
+                "tool_called",
+                json!({ "tool_name": "search", "query": "rust" }),
+            ),
+            ("tool_returned", json!({ "tool_name": "search" })),
+            (
+                "graph_completed",
+                json!({ "iterations": 1, "duration_ms": 250 }),
+            ),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (kind, payload))| RunEvent {
+            seq: i as u64 + 1,
+            kind: kind.to_string(),
+            payload,
+            timestamp: ts + chrono::Duration::milliseconds(100 * i as i64),
+        })
+        .collect()
+    }
+
+    #[tokio::test]
+    async fn record_run_replays_to_equal_events() {
+        let ledger = oxidized_state::fakes::MemoryRunLedger::new();
+        let spec_digest = oxidized_state::ContentDigest::from_bytes(b"spec");
+        let events = fixed_events();
+
+        let run_id = record_run(&ledger, &spec_digest, test_metadata(), events.clone())
+            .await
+            .unwrap();
+
+        let (replayed, summary) = crate::replay_run(&ledger, &run_id.to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&replayed).unwrap(),
+            serde_json::to_value(&events).unwrap()
+        );
+        assert_eq!(summary.status, oxidized_state::RunStatus::Completed);
+
+        let record = ledger.get_run(&run_id).await.unwrap();
+        let run_summary = record.summary.unwrap();
+        assert_eq!(run_summary.total_events, 4);
+        assert_eq!(run_summary.duration_ms, 300);
+        assert!(run_summary.success);
+    }
+
+    #[tokio::test]
+    async fn record_run_rejects_non_contiguous_events() {
+        let ledger = oxidized_state::fakes::MemoryRunLedger::new();
+        let spec_digest = oxidized_state::ContentDigest::from_bytes(b"spec");
+        let mut events = fixed_events();
+        events.remove(1);
+
+        let err = record_run(&ledger, &spec_digest, test_metadata(), events)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            AivcsError::NonContiguousEvents {
+                expected: 2,
+                actual: 3
+            }
+        ));
+        assert!(ledger.list_runs(None).await.unwrap().is_empty());
+    }
 }
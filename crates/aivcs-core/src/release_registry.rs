@@ -1,5 +1,6 @@
 use crate::domain::agent_spec::AgentSpec;
 use crate::domain::error::{AivcsError, Result};
+use crate::domain::release::{Release, ReleaseEnvironment};
 use oxidized_state::{
     ContentDigest, ReleaseMetadata, ReleaseRecord, ReleaseRegistry, StorageResult,
 };
@@ -42,6 +43,25 @@ fn validate_spec_for_promote(spec: &AgentSpec) -> Result<ContentDigest> {
         .map_err(|e| AivcsError::InvalidAgentSpec(format!("spec_digest is not valid hex: {}", e)))
 }
 
+/// Rebuild the domain [`Release`] a registry record describes.
+fn release_from_record(record: ReleaseRecord, environment: ReleaseEnvironment) -> Release {
+    let metadata = record.metadata;
+    let mut release = Release::new(
+        record.name,
+        record.spec_digest.as_str().to_string(),
+        metadata.tools_digest.unwrap_or_default(),
+        metadata.graph_digest.unwrap_or_default(),
+        metadata.version_label.unwrap_or_default(),
+        environment,
+        metadata.promoted_by,
+    );
+    release.promoted_at = record.created_at;
+    if let Some(fields) = metadata.fields {
+        release.metadata = serde_json::json!({ "fields": fields });
+    }
+    release
+}
+
 /// Thin API layer over a release registry backend.
 pub struct ReleaseRegistryApi<R> {
     registry: R,
@@ -63,19 +83,83 @@ where
         version_label: Option<String>,
         notes: Option<String>,
     ) -> Result<ReleaseRecord> {
-        let content_digest = validate_spec_for_promote(spec)?;
-
         let metadata = ReleaseMetadata {
             version_label,
             promoted_by: promoted_by.to_string(),
             notes,
+            ..Default::default()
+        };
+        self.promote_with_metadata(name, spec, metadata).await
+    }
+
+    /// Promote `spec` as `release`, recording its environment and the
+    /// fields it lists under `metadata.fields` so later promotions can be
+    /// checked against it.
+    pub async fn promote_release(
+        &self,
+        spec: &AgentSpec,
+        release: &Release,
+        notes: Option<String>,
+    ) -> Result<ReleaseRecord> {
+        let fields = release
+            .metadata
+            .get("fields")
+            .and_then(|v| serde_json::from_value(v.clone()).ok());
+        let metadata = ReleaseMetadata {
+            version_label: Some(release.version.clone()).filter(|v| !v.is_empty()),
+            promoted_by: release.promoted_by.clone(),
+            notes,
+            environment: Some(release.environment.as_str().to_string()),
+            fields,
+            ..Default::default()
         };
+        self.promote_with_metadata(&release.agent_name, spec, metadata)
+            .await
+    }
+
+    async fn promote_with_metadata(
+        &self,
+        name: &str,
+        spec: &AgentSpec,
+        mut metadata: ReleaseMetadata,
+    ) -> Result<ReleaseRecord> {
+        let content_digest = validate_spec_for_promote(spec)?;
+        metadata.tools_digest = Some(spec.tools_digest.clone());
+        metadata.graph_digest = Some(spec.graph_digest.clone());
         self.registry
             .promote(name, &content_digest, metadata)
             .await
             .map_err(|e| AivcsError::StorageError(e.to_string()))
     }
 
+    /// Newest release of `name` in `environment`, if any
+    ///
+    /// Releases recorded without an environment count as production.
+    /// Digests and fields that weren't recorded are left empty, which the
+    /// compat rules treat as unknown.
+    pub async fn current_release(
+        &self,
+        name: &str,
+        environment: ReleaseEnvironment,
+    ) -> Result<Option<Release>> {
+        let history = self
+            .registry
+            .history(name)
+            .await
+            .map_err(|e| AivcsError::StorageError(e.to_string()))?;
+        Ok(history
+            .into_iter()
+            .find(|record| {
+                record
+                    .metadata
+                    .environment
+                    .as_deref()
+                    .unwrap_or(ReleaseEnvironment::Production.as_str())
+                    == environment.as_str()
+            })
+            .map(|record| release_from_record(record, environment)))
+    }
+
     pub async fn rollback(&self, name: &str) -> StorageResult<ReleaseRecord> {
         self.registry.rollback(name).await
     }
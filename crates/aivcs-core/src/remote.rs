@@ -0,0 +1,201 @@
+//! Push/pull of branches between two AIVCS repositories
+//!
+//! Transfers go through a branch-scoped incremental bundle (see
+//! [`crate::bundle`]): the source exports the commits the destination is
+//! missing, the destination imports them, and the branch ref is then moved.
+//! Non-fast-forward updates are rejected unless forced, and the ref only
+//! moves if the destination head is still the one the sync started from.
+
+use anyhow::{bail, Context, Result};
+use oxidized_state::{short_hash, BranchRecord};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument, warn};
+
+use crate::bundle::{export_branch_bundle, import_bundle, BundleSummary};
+use crate::cas::CasStore;
+use crate::commands::CommandConflict;
+use crate::SurrealHandle;
+
+/// Result of a push or pull
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncSummary {
+    /// Branch that was synced
+    pub branch: String,
+    /// Destination head before the sync (`None` if the branch was created)
+    pub old_head: Option<String>,
+    /// Destination head after the sync
+    pub new_head: String,
+    /// Whether a non-fast-forward update was forced
+    pub forced: bool,
+    /// Records newly written to the destination
+    pub transferred: BundleSummary,
+}
+
+impl SyncSummary {
+    /// True when the destination already had the source head
+    pub fn is_up_to_date(&self) -> bool {
+        self.old_head.as_deref() == Some(self.new_head.as_str())
+    }
+}
+
+/// Push `branch` from `local` to `remote`
+#[instrument(skip(local, local_cas, remote, remote_cas))]
+pub async fn push_branch(
+    local: &SurrealHandle,
+    local_cas: &dyn CasStore,
+    remote: &SurrealHandle,
+    remote_cas: &dyn CasStore,
+    branch: &str,
+    force: bool,
+) -> Result<SyncSummary> {
+    sync_branch(local, local_cas, remote, remote_cas, branch, force).await
+}
+
+/// Pull `branch` from `remote` into `local`
+#[instrument(skip(local, local_cas, remote, remote_cas))]
+pub async fn pull_branch(
+    local: &SurrealHandle,
+    local_cas: &dyn CasStore,
+    remote: &SurrealHandle,
+    remote_cas: &dyn CasStore,
+    branch: &str,
+    force: bool,
+) -> Result<SyncSummary> {
+    sync_branch(remote, remote_cas, local, local_cas, branch, force).await
+}
+
+async fn sync_branch(
+    src: &SurrealHandle,
+    src_cas: &dyn CasStore,
+    dst: &SurrealHandle,
+    dst_cas: &dyn CasStore,
+    branch: &str,
+    force: bool,
+) -> Result<SyncSummary> {
+    let src_branch = src
+        .get_branch(branch)
+        .await?
+        .with_context(|| format!("branch '{branch}' not found in source"))?;
+    let new_head = src_branch.head_commit_id.clone();
+    let old_head = dst.get_branch(branch).await?.map(|b| b.head_commit_id);
+
+    let mut summary = SyncSummary {
+        branch: branch.to_string(),
+        old_head: old_head.clone(),
+        new_head: new_head.clone(),
+        forced: false,
+        transferred: BundleSummary::default(),
+    };
+    if summary.is_up_to_date() {
+        return Ok(summary);
+    }
+
+    // Only ship what the destination is missing: everything past the merge
+    // base of the two heads, when the source knows the destination head.
+    let mut since = None;
+    if let Some(old) = &old_head {
+        let fast_forward = src.get_ancestors(&new_head).await?.contains(old);
+        if !fast_forward {
+            if dst.get_protection(branch).await?.no_force_push {
+                bail!(
+                    "non-fast-forward update of '{branch}' rejected: branch is protected (no-force-push)"
+                );
+            }
+            if !force {
+                bail!(
+                    "non-fast-forward update of '{branch}' rejected ({} is not an ancestor of {}); use --force to overwrite",
+                    short_hash(old),
+                    short_hash(&new_head)
+                );
+            }
+            warn!("forcing non-fast-forward update of '{}'", branch);
+            summary.forced = true;
+        }
+        if src.get_commit(old).await?.is_some() {
+            since = src.find_merge_base(&new_head, old).await?;
+        }
+    }
+
+    let dir = tempfile::tempdir().context("failed to create bundle staging directory")?;
+    let path = dir.path().join("sync.bundle");
+    export_branch_bundle(src, src_cas, &path, branch, since.as_deref()).await?;
+    summary.transferred = import_bundle(dst, dst_cas, &path).await?;
+
+    move_branch_head(
+        dst,
+        branch,
+        old_head.as_deref(),
+        &new_head,
+        src_branch.is_default,
+    )
+    .await?;
+
+    info!(
+        "synced '{}': {} -> {}",
+        branch,
+        old_head.as_deref().map(short_hash).unwrap_or("(new)"),
+        short_hash(&new_head)
+    );
+    Ok(summary)
+}
+
+/// Move `branch` on `dst` from `old_head` to `new_head`, creating it when
+/// `old_head` is `None`
+///
+/// Fails with [`CommandConflict::BranchMovedDuringSync`] when the branch no
+/// longer matches `old_head`, so a concurrent push is not overwritten.
+/// `is_default` only applies to a newly created branch.
+async fn move_branch_head(
+    dst: &SurrealHandle,
+    branch: &str,
+    old_head: Option<&str>,
+    new_head: &str,
+    is_default: bool,
+) -> Result<()> {
+    let moved = match old_head {
+        Some(old) => dst.update_branch_head_cas(branch, old, new_head).await?,
+        None => {
+            dst.create_branch_if_absent(&BranchRecord::new(branch, new_head, is_default))
+                .await?
+        }
+    };
+    if !moved {
+        return Err(CommandConflict::BranchMovedDuringSync {
+            branch: branch.to_string(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_branch_moved_since_the_check_is_a_conflict() {
+        let dst = SurrealHandle::setup_db().await.unwrap();
+        // A concurrent push moved main from "old" to "theirs"
+        dst.save_branch(&BranchRecord::new("main", "theirs", true))
+            .await
+            .unwrap();
+
+        let err = move_branch_head(&dst, "main", Some("old"), "ours", true)
+            .await
+            .unwrap_err();
+        assert!(err.downcast_ref::<CommandConflict>().is_some());
+        assert_eq!(dst.get_branch_head("main").await.unwrap(), "theirs");
+
+        // A concurrent push created the branch this sync expected to create
+        let err = move_branch_head(&dst, "main", None, "ours", true)
+            .await
+            .unwrap_err();
+        assert!(err.downcast_ref::<CommandConflict>().is_some());
+        assert_eq!(dst.get_branch_head("main").await.unwrap(), "theirs");
+
+        move_branch_head(&dst, "main", Some("theirs"), "ours", true)
+            .await
+            .unwrap();
+        assert_eq!(dst.get_branch_head("main").await.unwrap(), "ours");
+    }
+}
@@ -7,9 +7,11 @@
 use tracing::instrument;
 
 use oxidized_state::storage_traits::{
-    ContentDigest, RunEvent, RunId, RunLedger, RunStatus as StorageRunStatus,
+    ContentDigest, RunEvent, RunId, RunLedger, RunRecord, RunStatus as StorageRunStatus,
 };
 
+use crate::archive::load_archived_run;
+use crate::cas::CasStore;
 use crate::diff::state_diff::CHECKPOINT_SAVED_KIND;
 use crate::domain::{AivcsError, Result};
 use crate::metrics::METRICS;
@@ -182,20 +184,45 @@ pub async fn replay_run(
         .await
         .map_err(|e| AivcsError::StorageError(e.to_string()))?;
 
+    let summary = replay_summary(record, &events)?;
+    Ok((events, summary))
+}
+
+/// [`replay_run`], falling back to the CAS archive for runs that
+/// [`crate::archive::archive_runs`] moved out of the ledger.
+///
+/// An archived run replays to the same events and `replay_digest` it had
+/// before archival.
+pub async fn replay_run_with_archive(
+    ledger: &dyn RunLedger,
+    cas: &dyn CasStore,
+    run_id_str: &str,
+) -> Result<(Vec<RunEvent>, ReplaySummary)> {
+    let run_id = RunId(run_id_str.to_string());
+    match load_archived_run(ledger, cas, &run_id).await? {
+        Some(archive) => {
+            METRICS.inc_replays();
+            let summary = replay_summary(archive.record, &archive.events)?;
+            Ok((archive.events, summary))
+        }
+        None => replay_run(ledger, run_id_str).await,
+    }
+}
+
+/// Build the [`ReplaySummary`] for a run record and its events.
+fn replay_summary(record: RunRecord, events: &[RunEvent]) -> Result<ReplaySummary> {
     // Compute deterministic digest: SHA-256 over serde_json::to_vec(&events)
-    let events_json = serde_json::to_vec(&events).map_err(AivcsError::Serialization)?;
+    let events_json = serde_json::to_vec(events).map_err(AivcsError::Serialization)?;
     let replay_digest = ContentDigest::from_bytes(&events_json).as_str().to_string();
 
-    let summary = ReplaySummary {
+    Ok(ReplaySummary {
         run_id: record.run_id.to_string(),
-        agent_name: record.metadata.agent_name.clone(),
+        agent_name: record.metadata.agent_name,
         status: record.status,
         event_count: events.len(),
         replay_digest,
         spec_digest: record.spec_digest,
-    };
-
-    Ok((events, summary))
+    })
 }
 
 #[cfg(test)]
@@ -287,6 +314,7 @@ mod tests {
             final_state_digest: None,
             duration_ms: 1000,
             success: true,
+            usage: None,
         };
         ledger
             .complete_run(&run_id, summary)
@@ -396,6 +424,7 @@ mod tests {
             final_state_digest: None,
             duration_ms: 100,
             success: true,
+            usage: None,
         };
         ledger
             .complete_run(&run_id, summary)
@@ -718,6 +747,7 @@ mod tests {
             final_state_digest: None,
             duration_ms: 0,
             success: true,
+            usage: None,
         };
         ledger
             .complete_run(&run_id, summary)
@@ -1,6 +1,8 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
+use oxidized_state::short_hash;
 use serde::{Deserialize, Serialize};
+use std::io::IsTerminal;
 use std::path::Path;
 use uuid::Uuid;
 
@@ -166,7 +168,7 @@ pub fn render_cross_org_audit_md(artifact: &CrossOrgAuditArtifact) -> String {
                 let run_id = h
                     .last_run
                     .as_ref()
-                    .map(|r| r.run_id.chars().take(8).collect::<String>())
+                    .map(|r| short_hash(&r.run_id).to_string())
                     .unwrap_or_else(|| "N/A".to_string());
                 out.push_str(&format!(
                     "| `{}` | {} {} | `{}` | {} |\n",
@@ -197,6 +199,244 @@ pub fn write_diff_summary_md(path: &Path, artifact: &DiffSummaryArtifact) -> Res
     Ok(())
 }
 
+/// A commit to draw with [`render_commit_graph_ascii`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitGraphNode {
+    /// Commit identifier, matched against other nodes' `parents`.
+    pub id: String,
+    /// Parent commit identifiers; the first is the mainline parent.
+    pub parents: Vec<String>,
+    /// Text printed after the graph columns (e.g. short hash and message).
+    pub label: String,
+}
+
+/// Render commits as `git log --graph`-style ASCII.
+///
+/// `commits` must be ordered children-before-parents (newest first). Each
+/// commit gets a `*` row; forks that rejoin are drawn with `/` and merges
+/// with `\`. Parents absent from `commits` simply end their lane.
+pub fn render_commit_graph_ascii(commits: &[CommitGraphNode]) -> String {
+    // Lanes sit in even columns; connectors go in the odd columns between them.
+    fn push_line(out: &mut String, buf: &[char], label: Option<&str>) {
+        let line: String = buf.iter().collect();
+        out.push_str(line.trim_end());
+        if let Some(label) = label {
+            out.push(' ');
+            out.push_str(label);
+        }
+        out.push('\n');
+    }
+
+    // Each lane holds the commit id it is waiting to reach.
+    let mut lanes: Vec<String> = Vec::new();
+    let mut out = String::new();
+
+    for commit in commits {
+        let col = match lanes.iter().position(|l| *l == commit.id) {
+            Some(col) => col,
+            None => {
+                lanes.push(commit.id.clone());
+                lanes.len() - 1
+            }
+        };
+
+        // Other lanes converging on this commit fold into the lane to their left.
+        while let Some(j) = lanes
+            .iter()
+            .skip(col + 1)
+            .position(|l| *l == commit.id)
+            .map(|p| p + col + 1)
+        {
+            let mut buf = vec![' '; 2 * lanes.len() - 1];
+            for k in 0..lanes.len() {
+                if k < j {
+                    buf[2 * k] = '|';
+                } else {
+                    buf[2 * k - 1] = '/';
+                }
+            }
+            push_line(&mut out, &buf, None);
+            lanes.remove(j);
+        }
+
+        let mut buf = vec![' '; 2 * lanes.len() - 1];
+        for k in 0..lanes.len() {
+            buf[2 * k] = if k == col { '*' } else { '|' };
+        }
+        push_line(&mut out, &buf, Some(&commit.label));
+
+        match commit.parents.split_first() {
+            None => {
+                lanes.remove(col);
+            }
+            Some((first, rest)) => {
+                lanes[col] = first.clone();
+                for parent in rest {
+                    if lanes.contains(parent) {
+                        continue;
+                    }
+                    lanes.insert(col + 1, parent.clone());
+                    let mut buf = vec![' '; 2 * lanes.len() - 1];
+                    for k in 0..lanes.len() {
+                        if k <= col {
+                            buf[2 * k] = '|';
+                        } else {
+                            buf[2 * k - 1] = '\\';
+                        }
+                    }
+                    push_line(&mut out, &buf, None);
+                }
+            }
+        }
+    }
+
+    out
+}
+
+const ANSI_GREEN: &str = "\x1b[32m";
+const ANSI_RED: &str = "\x1b[31m";
+const ANSI_YELLOW: &str = "\x1b[33m";
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// Width assumed for a terminal whose size can't be queried and that
+/// doesn't report one via `COLUMNS`.
+const DEFAULT_TERM_WIDTH: usize = 80;
+
+/// Styling for human-readable command output: ANSI colour, and the width
+/// lines are fitted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TermStyle {
+    pub color: bool,
+    /// Columns available, or `None` to leave lines at full length.
+    pub width: Option<usize>,
+}
+
+impl TermStyle {
+    /// No colour and no width limit, as for pipes and files.
+    pub const PLAIN: Self = Self {
+        color: false,
+        width: None,
+    };
+
+    /// Style for stdout in the current process.
+    pub fn stdout() -> Self {
+        let stdout = std::io::stdout();
+        let size = terminal_size::terminal_size_of(&stdout);
+        Self::detect(
+            stdout.is_terminal(),
+            size.map(|(terminal_size::Width(w), _)| usize::from(w)),
+            |name| std::env::var(name).ok(),
+        )
+    }
+
+    /// Style for a stream that is (`is_tty`) or isn't a terminal, and
+    /// reports `size` columns when queried.
+    ///
+    /// Anything but a terminal gets [`TermStyle::PLAIN`]. On a terminal,
+    /// colour is on unless `NO_COLOR` is set to a non-empty value, and the
+    /// width is `size`, else `COLUMNS`, else 80.
+    pub fn detect(is_tty: bool, size: Option<usize>, env: impl Fn(&str) -> Option<String>) -> Self {
+        if !is_tty {
+            return Self::PLAIN;
+        }
+        let no_color = env("NO_COLOR").is_some_and(|v| !v.is_empty());
+        let width = size
+            .filter(|&w| w > 0)
+            .or_else(|| {
+                env("COLUMNS")
+                    .and_then(|c| c.trim().parse().ok())
+                    .filter(|&w: &usize| w > 0)
+            })
+            .unwrap_or(DEFAULT_TERM_WIDTH);
+        Self {
+            color: !no_color,
+            width: Some(width),
+        }
+    }
+
+    /// Something present only on the newer side (green).
+    pub fn added(&self, text: &str) -> String {
+        self.paint(ANSI_GREEN, text)
+    }
+
+    /// Something present only on the older side (red).
+    pub fn removed(&self, text: &str) -> String {
+        self.paint(ANSI_RED, text)
+    }
+
+    /// Something changed on both sides, or otherwise needing attention (yellow).
+    pub fn conflict(&self, text: &str) -> String {
+        self.paint(ANSI_YELLOW, text)
+    }
+
+    /// Cut `line` to the terminal width, marking the cut with `…`.
+    ///
+    /// Fit plain text before colouring it: escape codes would count as columns.
+    pub fn fit(&self, line: &str) -> String {
+        match self.width {
+            Some(width) if line.chars().count() > width => {
+                let mut cut: String = line.chars().take(width.saturating_sub(1)).collect();
+                cut.push('…');
+                cut
+            }
+            _ => line.to_string(),
+        }
+    }
+
+    /// Break `line` at spaces into lines that fit the terminal width,
+    /// indenting each continuation like `line` itself.
+    ///
+    /// Words wider than a line are split. For text that must be read in
+    /// full, where [`TermStyle::fit`] would cut it.
+    pub fn wrap(&self, line: &str) -> String {
+        let Some(width) = self.width else {
+            return line.to_string();
+        };
+        if line.chars().count() <= width {
+            return line.to_string();
+        }
+        let body = line.trim_start();
+        let indent = &line[..line.len() - body.len()];
+        // Keep at least a few columns for text under a deep indent
+        let room = width.saturating_sub(indent.chars().count()).max(8);
+
+        let mut lines: Vec<String> = Vec::new();
+        let mut current = String::new();
+        for word in body.split(' ') {
+            let mut word: Vec<char> = word.chars().collect();
+            let used = current.chars().count();
+            // A word wider than a line starts on this one if there's room
+            if used > 0 && used + 1 + word.len() > room && (word.len() <= room || used + 1 >= room)
+            {
+                lines.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            while current.chars().count() + word.len() > room {
+                let take = room - current.chars().count();
+                current.extend(word.drain(..take));
+                lines.push(std::mem::take(&mut current));
+            }
+            current.extend(word);
+        }
+        lines.push(current);
+        lines
+            .iter()
+            .map(|l| format!("{indent}{l}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn paint(&self, code: &str, text: &str) -> String {
+        if self.color {
+            format!("{code}{text}{ANSI_RESET}")
+        } else {
+            text.to_string()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -310,4 +550,111 @@ mod tests {
         assert!(!rendered.contains("Dependency Matrix"));
         assert!(!rendered.contains("Dependency graph visualization"));
     }
+
+    fn node(id: &str, parents: &[&str]) -> CommitGraphNode {
+        CommitGraphNode {
+            id: id.to_string(),
+            parents: parents.iter().map(|p| p.to_string()).collect(),
+            label: id.to_string(),
+        }
+    }
+
+    #[test]
+    fn commit_graph_linear_history() {
+        let commits = vec![node("c", &["b"]), node("b", &["a"]), node("a", &[])];
+        assert_eq!(render_commit_graph_ascii(&commits), "* c\n* b\n* a\n");
+    }
+
+    #[test]
+    fn commit_graph_merge_golden() {
+        let commits = vec![
+            node("merge", &["main1", "feat1"]),
+            node("feat1", &["base"]),
+            node("main1", &["base"]),
+            node("base", &[]),
+        ];
+        let expected = "\
+* merge
+|\\
+| * feat1
+* | main1
+|/
+* base
+";
+        assert_eq!(render_commit_graph_ascii(&commits), expected);
+    }
+
+    fn env_of(vars: &'static [(&'static str, &'static str)]) -> impl Fn(&str) -> Option<String> {
+        |name| {
+            vars.iter()
+                .find(|(k, _)| *k == name)
+                .map(|(_, v)| v.to_string())
+        }
+    }
+
+    #[test]
+    fn term_style_emits_color_codes_when_forced_on() {
+        let style = TermStyle {
+            color: true,
+            width: None,
+        };
+        assert_eq!(style.added("+ a"), "\x1b[32m+ a\x1b[0m");
+        assert_eq!(style.removed("- b"), "\x1b[31m- b\x1b[0m");
+        assert_eq!(style.conflict("~ c"), "\x1b[33m~ c\x1b[0m");
+
+        let tty = TermStyle::detect(true, None, env_of(&[]));
+        assert!(tty.color);
+        assert!(tty.added("x").contains('\x1b'));
+    }
+
+    #[test]
+    fn term_style_omits_color_under_no_color_and_off_a_tty() {
+        let no_color = TermStyle::detect(true, None, env_of(&[("NO_COLOR", "1")]));
+        assert!(!no_color.color);
+        assert_eq!(no_color.added("+ a"), "+ a");
+        assert_eq!(no_color.conflict("~ c"), "~ c");
+
+        assert_eq!(
+            TermStyle::detect(false, Some(100), env_of(&[])),
+            TermStyle::PLAIN
+        );
+        assert_eq!(TermStyle::PLAIN.removed("- b"), "- b");
+    }
+
+    #[test]
+    fn term_style_fits_lines_to_detected_width() {
+        let style = TermStyle::detect(true, None, env_of(&[("COLUMNS", "10")]));
+        assert_eq!(style.width, Some(10));
+        assert_eq!(style.fit("0123456789"), "0123456789");
+        assert_eq!(style.fit("0123456789abc"), "012345678…");
+
+        // The queried terminal size wins over COLUMNS
+        let sized = TermStyle::detect(true, Some(120), env_of(&[("COLUMNS", "10")]));
+        assert_eq!(sized.width, Some(120));
+
+        let default = TermStyle::detect(true, None, env_of(&[("COLUMNS", "junk")]));
+        assert_eq!(default.width, Some(DEFAULT_TERM_WIDTH));
+        let long = "x".repeat(200);
+        assert_eq!(TermStyle::PLAIN.fit(&long), long);
+    }
+
+    #[test]
+    fn term_style_wraps_long_lines_keeping_their_indent() {
+        let style = TermStyle {
+            color: false,
+            width: Some(20),
+        };
+        assert_eq!(style.wrap("    short message"), "    short message");
+        assert_eq!(
+            style.wrap("    fix the retry loop in the fetcher"),
+            "    fix the retry\n    loop in the\n    fetcher"
+        );
+        // A word wider than the line is split across lines
+        assert_eq!(
+            style.wrap("    see 0123456789abcdefghij"),
+            "    see 0123456789ab\n    cdefghij"
+        );
+        let long = "word ".repeat(40);
+        assert_eq!(TermStyle::PLAIN.wrap(&long), long);
+    }
 }
@@ -144,6 +144,7 @@ where
                                 final_state_digest: None,
                                 duration_ms: 0,
                                 success: true,
+                                usage: None,
                             },
                         )
                         .await;
@@ -165,6 +166,7 @@ where
                                 final_state_digest: None,
                                 duration_ms: 0,
                                 success: false,
+                                usage: None,
                             },
                         )
                         .await;
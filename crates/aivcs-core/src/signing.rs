@@ -0,0 +1,214 @@
+//! Ed25519 commit signing and verification
+//!
+//! A signature covers the commit's canonical bytes: its composite id,
+//! parents, message, author, and timestamp. The signature and the signer's
+//! public key are stored hex-encoded on the [`CommitRecord`]. Signing is
+//! optional; unsigned commits stay valid and verify as
+//! [`SignatureStatus::Unsigned`].
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use chrono::SecondsFormat;
+use oxidized_state::CommitRecord;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde::Serialize;
+
+/// Bytes a commit signature is computed over.
+///
+/// Excludes the advisory `branch` field and the signature fields themselves.
+pub fn commit_signing_bytes(commit: &CommitRecord) -> Vec<u8> {
+    #[derive(Serialize)]
+    struct SignedFields<'a> {
+        hash: &'a str,
+        logic_hash: Option<&'a str>,
+        state_hash: &'a str,
+        env_hash: Option<&'a str>,
+        parent_ids: &'a [String],
+        message: &'a str,
+        author: &'a str,
+        created_at: String,
+    }
+
+    let fields = SignedFields {
+        hash: &commit.commit_id.hash,
+        logic_hash: commit.commit_id.logic_hash.as_deref(),
+        state_hash: &commit.commit_id.state_hash,
+        env_hash: commit.commit_id.env_hash.as_deref(),
+        parent_ids: &commit.parent_ids,
+        message: &commit.message,
+        author: &commit.author,
+        created_at: commit
+            .created_at
+            .to_rfc3339_opts(SecondsFormat::Nanos, true),
+    };
+    serde_json::to_vec(&fields).expect("signed commit fields serialize to JSON")
+}
+
+/// An Ed25519 key that signs commits
+pub struct CommitSigner {
+    key: Ed25519KeyPair,
+}
+
+impl CommitSigner {
+    /// Build a signer from a hex-encoded 32-byte Ed25519 seed
+    pub fn from_seed_hex(seed_hex: &str) -> Result<Self> {
+        let seed = hex::decode(seed_hex.trim()).context("signing key is not valid hex")?;
+        let key = Ed25519KeyPair::from_seed_unchecked(&seed)
+            .map_err(|_| anyhow::anyhow!("signing key must be a 32-byte Ed25519 seed"))?;
+        Ok(Self { key })
+    }
+
+    /// Load a signer from a file holding a hex-encoded Ed25519 seed
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let seed = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read signing key {}", path.display()))?;
+        Self::from_seed_hex(&seed)
+    }
+
+    /// Hex-encoded public key, as recorded in `CommitRecord::signer`
+    pub fn public_key_hex(&self) -> String {
+        hex::encode(self.key.public_key().as_ref())
+    }
+
+    /// Sign `commit` in place, setting its `signature` and `signer`
+    pub fn sign(&self, commit: &mut CommitRecord) {
+        let signature = self.key.sign(&commit_signing_bytes(commit));
+        commit.signature = Some(hex::encode(signature.as_ref()));
+        commit.signer = Some(self.public_key_hex());
+    }
+}
+
+/// Public keys whose commit signatures are trusted, with display names
+#[derive(Debug, Clone, Default)]
+pub struct TrustedKeys {
+    keys: BTreeMap<String, String>,
+}
+
+impl TrustedKeys {
+    /// Create an empty key set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trust the hex-encoded public key `public_key_hex` under `name`
+    pub fn add(&mut self, public_key_hex: &str, name: &str) {
+        self.keys
+            .insert(public_key_hex.to_ascii_lowercase(), name.to_string());
+    }
+
+    /// Parse a key set: one `<hex public key> [name]` per line; blank lines
+    /// and lines starting with `#` are ignored.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut trusted = Self::new();
+        for (lineno, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, name) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let bytes = hex::decode(key)
+                .with_context(|| format!("line {}: public key is not valid hex", lineno + 1))?;
+            if bytes.len() != 32 {
+                bail!("line {}: public key must be 32 bytes", lineno + 1);
+            }
+            let name = name.trim();
+            trusted.add(key, if name.is_empty() { key } else { name });
+        }
+        Ok(trusted)
+    }
+
+    /// Load a key set from a file in the [`TrustedKeys::parse`] format
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read trusted keys {}", path.display()))?;
+        Self::parse(&text)
+    }
+
+    /// Display name of a trusted key
+    pub fn name_of(&self, public_key_hex: &str) -> Option<&str> {
+        self.keys
+            .get(&public_key_hex.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+
+    /// Number of trusted keys
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// True if no keys are trusted
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+/// Outcome of verifying a commit's signature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureStatus {
+    /// The commit carries no signature
+    Unsigned,
+    /// Signed by a trusted key, and the signature matches the commit
+    Valid {
+        /// Hex public key of the signer
+        signer: String,
+        /// Display name of the signer in the trusted key set
+        name: String,
+    },
+    /// The signature matches, but the signer is not in the trusted key set
+    UntrustedSigner {
+        /// Hex public key of the signer
+        signer: String,
+    },
+    /// The signature is malformed or does not match the commit contents
+    Invalid {
+        /// Why verification failed
+        reason: String,
+    },
+}
+
+impl SignatureStatus {
+    /// True only for [`SignatureStatus::Valid`]
+    pub fn is_valid(&self) -> bool {
+        matches!(self, SignatureStatus::Valid { .. })
+    }
+}
+
+/// Verify `commit`'s signature against `trusted`
+pub fn verify_commit(commit: &CommitRecord, trusted: &TrustedKeys) -> SignatureStatus {
+    let (signature, signer) = match (&commit.signature, &commit.signer) {
+        (None, None) => return SignatureStatus::Unsigned,
+        (Some(signature), Some(signer)) => (signature, signer),
+        _ => {
+            return SignatureStatus::Invalid {
+                reason: "signature and signer must be set together".to_string(),
+            }
+        }
+    };
+    let (Ok(signature), Ok(public_key)) = (hex::decode(signature), hex::decode(signer)) else {
+        return SignatureStatus::Invalid {
+            reason: "signature or signer is not valid hex".to_string(),
+        };
+    };
+
+    let bytes = commit_signing_bytes(commit);
+    if UnparsedPublicKey::new(&ED25519, &public_key)
+        .verify(&bytes, &signature)
+        .is_err()
+    {
+        return SignatureStatus::Invalid {
+            reason: "signature does not match commit contents".to_string(),
+        };
+    }
+
+    match trusted.name_of(signer) {
+        Some(name) => SignatureStatus::Valid {
+            signer: signer.clone(),
+            name: name.to_string(),
+        },
+        None => SignatureStatus::UntrustedSigner {
+            signer: signer.clone(),
+        },
+    }
+}
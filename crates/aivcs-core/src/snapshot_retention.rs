@@ -0,0 +1,173 @@
+//! Snapshot retention: pruning old commit states while keeping history
+//!
+//! [`apply_snapshot_retention`] drops the snapshots of commits that have
+//! fallen out of every branch's retention window. Commit records stay, marked
+//! `snapshot_pruned`, so `log`, ancestry, and merges are unaffected; loading
+//! a pruned commit's state fails with
+//! [`StateError::SnapshotPruned`](oxidized_state::StateError::SnapshotPruned).
+
+use std::collections::{BTreeSet, HashSet};
+
+use oxidized_state::SurrealHandle;
+use tracing::info;
+
+use crate::cas::{CasStore, Digest};
+use crate::domain::{AivcsError, Result};
+
+/// Which snapshots [`apply_snapshot_retention`] keeps
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotRetentionPolicy {
+    /// Snapshots kept per branch, newest first along its first-parent
+    /// history. The branch head is always kept.
+    pub keep_last: usize,
+    /// Commits kept regardless of age, e.g. tagged or released ones
+    pub pinned: BTreeSet<String>,
+}
+
+impl SnapshotRetentionPolicy {
+    pub fn keep_last(keep_last: usize) -> Self {
+        Self {
+            keep_last,
+            pinned: BTreeSet::new(),
+        }
+    }
+
+    /// Also keep `commit_id`
+    pub fn pin(mut self, commit_id: impl Into<String>) -> Self {
+        self.pinned.insert(commit_id.into());
+        self
+    }
+}
+
+/// Outcome of [`apply_snapshot_retention`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotRetentionReport {
+    /// Commits whose snapshot was pruned, oldest first
+    pub pruned: Vec<String>,
+    /// Kept commits whose delta snapshot was rewritten as a full state
+    /// because its delta chain ran through a pruned commit
+    pub rewritten_as_keyframes: Vec<String>,
+    /// State blobs removed from CAS
+    pub cas_blobs_removed: usize,
+}
+
+/// Prune the snapshots of commits outside `policy` on every branch
+///
+/// A commit is pruned when no branch keeps it and it isn't pinned. Commits
+/// only reachable through second parents of merges, or from no branch,
+/// are left alone. A pruned commit's state blob is removed from `cas`
+/// unless a remaining commit has the same state.
+pub async fn apply_snapshot_retention(
+    handle: &SurrealHandle,
+    cas: &dyn CasStore,
+    policy: &SnapshotRetentionPolicy,
+) -> Result<SnapshotRetentionReport> {
+    let mut kept: HashSet<String> = policy.pinned.iter().cloned().collect();
+    let mut candidates: HashSet<String> = HashSet::new();
+    for branch in handle.list_branches().await.map_err(storage_err)? {
+        let history = handle
+            .get_commit_history(&branch.head_commit_id, usize::MAX)
+            .await
+            .map_err(storage_err)?;
+        let keep = policy.keep_last.max(1);
+        for (i, commit) in history.into_iter().enumerate() {
+            if commit.snapshot_pruned {
+                continue;
+            }
+            if i < keep {
+                kept.insert(commit.commit_id.hash);
+            } else {
+                candidates.insert(commit.commit_id.hash);
+            }
+        }
+    }
+
+    // Oldest first, so the report and log read chronologically
+    let commits = handle.list_commits().await.map_err(storage_err)?;
+    let prune: Vec<_> = commits
+        .iter()
+        .filter(|c| candidates.contains(&c.commit_id.hash) && !kept.contains(&c.commit_id.hash))
+        .collect();
+    let prune_ids: HashSet<&str> = prune.iter().map(|c| c.commit_id.hash.as_str()).collect();
+
+    let mut report = SnapshotRetentionReport::default();
+
+    // Rewrite surviving deltas first, while the states they patch still exist
+    for commit in &commits {
+        let id = commit.commit_id.hash.as_str();
+        if commit.snapshot_pruned || prune_ids.contains(id) {
+            continue;
+        }
+        if delta_chain_touches(handle, id, &prune_ids).await? {
+            handle
+                .rewrite_snapshot_as_keyframe(id)
+                .await
+                .map_err(storage_err)?;
+            report.rewritten_as_keyframes.push(id.to_string());
+        }
+    }
+
+    let surviving_states: HashSet<&str> = commits
+        .iter()
+        .filter(|c| !c.snapshot_pruned && !prune_ids.contains(c.commit_id.hash.as_str()))
+        .map(|c| c.commit_id.state_hash.as_str())
+        .collect();
+    let mut removed_states = HashSet::new();
+    for commit in prune {
+        let id = &commit.commit_id.hash;
+        handle.prune_snapshot(id).await.map_err(storage_err)?;
+        report.pruned.push(id.clone());
+
+        let state = commit.commit_id.state_hash.as_str();
+        if surviving_states.contains(state) || !removed_states.insert(state) {
+            continue;
+        }
+        // Older commits may carry a state hash that isn't a CAS digest
+        if let Ok(digest) = state.parse::<Digest>() {
+            if cas.delete(&digest).map_err(cas_err)? {
+                report.cas_blobs_removed += 1;
+            }
+        }
+    }
+
+    info!(
+        "snapshot retention: pruned {}, rewrote {} as keyframes, removed {} blobs",
+        report.pruned.len(),
+        report.rewritten_as_keyframes.len(),
+        report.cas_blobs_removed
+    );
+    Ok(report)
+}
+
+/// Whether `commit_id`'s snapshot is a delta whose chain passes through a
+/// commit in `prune`
+async fn delta_chain_touches(
+    handle: &SurrealHandle,
+    commit_id: &str,
+    prune: &HashSet<&str>,
+) -> Result<bool> {
+    let mut record = match handle.fetch_snapshot_record(commit_id).await {
+        Ok(record) => record,
+        // Commits without a snapshot (e.g. merge bookkeeping) have no chain
+        Err(oxidized_state::StateError::CommitNotFound(_)) => return Ok(false),
+        Err(e) => return Err(storage_err(e)),
+    };
+    while let Some(parent) = record.delta_parent.take() {
+        if prune.contains(parent.as_str()) {
+            return Ok(true);
+        }
+        record = handle
+            .fetch_snapshot_record(&parent)
+            .await
+            .map_err(storage_err)?;
+    }
+    Ok(false)
+}
+
+fn storage_err(e: oxidized_state::StateError) -> AivcsError {
+    AivcsError::StorageError(e.to_string())
+}
+
+fn cas_err(e: crate::cas::CasError) -> AivcsError {
+    AivcsError::StorageError(format!("snapshot retention: {e}"))
+}
@@ -0,0 +1,72 @@
+//! Stashes: work-in-progress agent state set aside without a commit
+//!
+//! The state blob goes to CAS and a named row in the `stashes` table points
+//! at it, so stashes never appear in branch history. Popping a stash returns
+//! the state and removes the row; the blob stays in CAS, where it may be
+//! shared with commits holding the same content.
+
+use anyhow::{Context, Result};
+use oxidized_state::StashRecord;
+use tracing::{info, instrument};
+
+use crate::cas::{CasStore, Digest};
+use crate::SurrealHandle;
+
+/// Store `state_content` in CAS and record it as the stash `name`
+///
+/// The content must be valid JSON; a stash with the same name must not
+/// already exist.
+#[instrument(skip(handle, cas, state_content))]
+pub async fn stash_save(
+    handle: &SurrealHandle,
+    cas: &dyn CasStore,
+    name: &str,
+    state_content: &str,
+    message: &str,
+) -> Result<StashRecord> {
+    serde_json::from_str::<serde_json::Value>(state_content)
+        .context("Failed to parse state as JSON")?;
+    if handle.get_stash(name).await?.is_some() {
+        anyhow::bail!("stash '{name}' already exists");
+    }
+
+    let digest = cas
+        .put(state_content.as_bytes())
+        .map_err(|e| anyhow::anyhow!("CAS put failed: {e}"))?;
+    let record = StashRecord::new(name, &digest.to_hex(), message, state_content.len() as u64);
+    let saved = handle.save_stash(&record).await?;
+
+    info!("stashed '{}' ({})", name, digest);
+    Ok(saved)
+}
+
+/// Remove the stash `name` and return it together with its state
+///
+/// The stash is only deleted once its state has been read back from CAS,
+/// so a missing or corrupt blob leaves the stash in place.
+#[instrument(skip(handle, cas))]
+pub async fn stash_pop(
+    handle: &SurrealHandle,
+    cas: &dyn CasStore,
+    name: &str,
+) -> Result<(StashRecord, serde_json::Value)> {
+    let record = handle
+        .get_stash(name)
+        .await?
+        .with_context(|| format!("stash '{name}' not found"))?;
+
+    let digest: Digest = record
+        .cas_digest
+        .parse()
+        .map_err(|e| anyhow::anyhow!("stash '{name}' has an invalid digest: {e}"))?;
+    let bytes = cas
+        .get(&digest)
+        .map_err(|e| anyhow::anyhow!("failed to read stash '{name}' from CAS: {e}"))?;
+    let state: serde_json::Value = serde_json::from_slice(&bytes)
+        .with_context(|| format!("stash '{name}' does not hold valid JSON"))?;
+
+    handle.delete_stash(name).await?;
+
+    info!("popped stash '{}'", name);
+    Ok((record, state))
+}
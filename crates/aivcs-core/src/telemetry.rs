@@ -17,6 +17,9 @@ use tracing_subscriber::{fmt, EnvFilter};
 ///   (useful for log aggregation pipelines).
 /// * `level` — default verbosity when `RUST_LOG` is not set.
 ///
+/// Log lines go to stderr so that stdout stays clean for command output
+/// (e.g. `aivcs log --json`).
+///
 /// Respects the `RUST_LOG` environment variable for fine-grained filtering.
 /// If `RUST_LOG` is not set, falls back to the supplied `level`.
 ///
@@ -28,13 +31,18 @@ pub fn init_tracing(json: bool, level: Level) {
     if json {
         tracing_subscriber::registry()
             .with(env_filter)
-            .with(fmt::layer().with_target(false).json())
+            .with(
+                fmt::layer()
+                    .with_target(false)
+                    .with_writer(std::io::stderr)
+                    .json(),
+            )
             .try_init()
             .ok();
     } else {
         tracing_subscriber::registry()
             .with(env_filter)
-            .with(fmt::layer().with_target(false))
+            .with(fmt::layer().with_target(false).with_writer(std::io::stderr))
             .try_init()
             .ok();
     }
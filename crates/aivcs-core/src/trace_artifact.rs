@@ -221,6 +221,7 @@ mod tests {
                 final_state_digest: None,
                 duration_ms: 10,
                 success: true,
+                usage: None,
             }),
             created_at,
             completed_at: Some(created_at),
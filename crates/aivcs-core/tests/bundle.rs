@@ -0,0 +1,164 @@
+//! Roundtrip tests for repository bundles
+
+use aivcs_core::{
+    export_bundle, export_bundle_since, import_bundle, BranchRecord, CasStore, CommitId,
+    CommitRecord, FsCasStore, MemoryRecord, SurrealHandle,
+};
+use serde_json::json;
+
+/// Commit `state` on top of `parent`, storing its bytes in CAS like the CLI does
+async fn commit(
+    handle: &SurrealHandle,
+    cas: &FsCasStore,
+    state: serde_json::Value,
+    parent: Option<&str>,
+) -> String {
+    let bytes = serde_json::to_vec(&state).unwrap();
+    let digest = cas.put(&bytes).unwrap();
+    let id = CommitId::new(None, &digest.to_hex(), None);
+    handle.save_snapshot(&id, state).await.unwrap();
+    let parents = parent.map(|p| vec![p.to_string()]).unwrap_or_default();
+    handle
+        .save_commit(&CommitRecord::new(id.clone(), parents, "step", "test"))
+        .await
+        .unwrap();
+    if let Some(p) = parent {
+        handle.save_commit_graph_edge(&id.hash, p).await.unwrap();
+    }
+    id.hash
+}
+
+#[tokio::test]
+async fn test_bundle_roundtrip_into_fresh_repo() {
+    let dir = tempfile::tempdir().unwrap();
+    let src = SurrealHandle::setup_db().await.unwrap();
+    let src_cas = FsCasStore::new(dir.path().join("src-cas")).unwrap();
+
+    let c1 = commit(&src, &src_cas, json!({ "step": 1 }), None).await;
+    let c2 = commit(
+        &src,
+        &src_cas,
+        json!({ "step": 2, "notes": ["a"] }),
+        Some(&c1),
+    )
+    .await;
+    let c3 = commit(&src, &src_cas, json!({ "step": 3 }), Some(&c1)).await;
+    src.save_branch(&BranchRecord::new("main", &c2, true))
+        .await
+        .unwrap();
+    src.save_branch(&BranchRecord::new("experiment", &c3, false))
+        .await
+        .unwrap();
+    src.save_memory(&MemoryRecord::new(&c2, "goal", "ship it"))
+        .await
+        .unwrap();
+
+    let bundle = dir.path().join("repo.tar");
+    let exported = export_bundle(&src, &src_cas, &bundle).await.unwrap();
+    assert_eq!(exported.commits, 3);
+    assert_eq!(exported.blobs, 3);
+
+    let dst = SurrealHandle::setup_db().await.unwrap();
+    let dst_cas = FsCasStore::new(dir.path().join("dst-cas")).unwrap();
+    let imported = import_bundle(&dst, &dst_cas, &bundle).await.unwrap();
+    assert_eq!(imported, exported);
+
+    for name in ["main", "experiment"] {
+        assert_eq!(
+            dst.get_branch_head(name).await.unwrap(),
+            src.get_branch_head(name).await.unwrap()
+        );
+    }
+    for id in [&c1, &c2, &c3] {
+        assert_eq!(
+            dst.load_snapshot(id).await.unwrap().state,
+            src.load_snapshot(id).await.unwrap().state
+        );
+        let commit = dst.get_commit(id).await.unwrap().unwrap();
+        let digest = commit.commit_id.state_hash.parse().unwrap();
+        assert!(dst_cas.exists(&digest).unwrap());
+    }
+    assert_eq!(dst.get_children(&c1).await.unwrap().len(), 2);
+    assert_eq!(dst.get_memories(&c2).await.unwrap()[0].content, "ship it");
+
+    // Importing again skips everything that already exists
+    let again = import_bundle(&dst, &dst_cas, &bundle).await.unwrap();
+    assert_eq!(again, Default::default());
+}
+
+#[tokio::test]
+async fn test_incremental_bundle_applies_onto_base() {
+    let dir = tempfile::tempdir().unwrap();
+    let src = SurrealHandle::setup_db().await.unwrap();
+    let src_cas = FsCasStore::new(dir.path().join("src-cas")).unwrap();
+
+    let c1 = commit(&src, &src_cas, json!({ "step": 1 }), None).await;
+    let c2 = commit(&src, &src_cas, json!({ "step": 2 }), Some(&c1)).await;
+    src.save_branch(&BranchRecord::new("main", &c2, true))
+        .await
+        .unwrap();
+
+    // Seed the target with the base via a full bundle
+    let base_bundle = dir.path().join("base.tar");
+    export_bundle(&src, &src_cas, &base_bundle).await.unwrap();
+    let dst = SurrealHandle::setup_db().await.unwrap();
+    let dst_cas = FsCasStore::new(dir.path().join("dst-cas")).unwrap();
+    import_bundle(&dst, &dst_cas, &base_bundle).await.unwrap();
+
+    // New work on top of the base
+    let c3 = commit(&src, &src_cas, json!({ "step": 3 }), Some(&c2)).await;
+    let c4 = commit(&src, &src_cas, json!({ "step": 4 }), Some(&c3)).await;
+    src.save_branch(&BranchRecord::new("main", &c4, true))
+        .await
+        .unwrap();
+    src.save_memory(&MemoryRecord::new(&c4, "note", "incremental"))
+        .await
+        .unwrap();
+    src.save_memory(&MemoryRecord::new(&c1, "old", "already synced"))
+        .await
+        .unwrap();
+
+    let delta = dir.path().join("delta.tar");
+    let exported = export_bundle_since(&src, &src_cas, &delta, &c2)
+        .await
+        .unwrap();
+    assert_eq!(exported.commits, 2);
+    assert_eq!(exported.snapshots, 2);
+    assert_eq!(exported.blobs, 2);
+    assert_eq!(exported.memories, 1);
+    assert_eq!(exported.graph_edges, 2);
+
+    let imported = import_bundle(&dst, &dst_cas, &delta).await.unwrap();
+    assert_eq!(imported, exported);
+    assert_eq!(dst.get_branch_head("main").await.unwrap(), c4);
+    assert_eq!(
+        dst.load_snapshot(&c4).await.unwrap().state,
+        json!({ "step": 4 })
+    );
+    assert_eq!(dst.get_parent(&c3).await.unwrap(), Some(c2));
+}
+
+#[tokio::test]
+async fn test_incremental_bundle_requires_base_in_target() {
+    let dir = tempfile::tempdir().unwrap();
+    let src = SurrealHandle::setup_db().await.unwrap();
+    let src_cas = FsCasStore::new(dir.path().join("src-cas")).unwrap();
+
+    let c1 = commit(&src, &src_cas, json!({ "step": 1 }), None).await;
+    let c2 = commit(&src, &src_cas, json!({ "step": 2 }), Some(&c1)).await;
+    src.save_branch(&BranchRecord::new("main", &c2, true))
+        .await
+        .unwrap();
+
+    let delta = dir.path().join("delta.tar");
+    export_bundle_since(&src, &src_cas, &delta, &c1)
+        .await
+        .unwrap();
+
+    let dst = SurrealHandle::setup_db().await.unwrap();
+    let dst_cas = FsCasStore::new(dir.path().join("dst-cas")).unwrap();
+    let err = import_bundle(&dst, &dst_cas, &delta).await.unwrap_err();
+    assert!(err.to_string().contains("missing from the target"), "{err}");
+    assert!(dst.get_commit(&c2).await.unwrap().is_none());
+    assert!(dst.get_branch("main").await.unwrap().is_none());
+}
@@ -0,0 +1,68 @@
+//! Every JSON digest site shares one canonical encoding
+
+use std::collections::BTreeMap;
+
+use aivcs_core::domain::digest;
+use aivcs_core::domain::eval::{EvalSuite, EvalSuiteFields, EvalTestCase, EvalThresholds};
+use aivcs_core::CommitId;
+use oxidized_state::{canonical_json, CiCommand, CiStepSpec};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+#[test]
+fn test_entry_points_agree_on_bytes_and_digest() {
+    let value = json!({ "z": [1.0, { "b": 2, "a": 1 }], "a": "x" });
+    let bytes = canonical_json(&value);
+    assert_eq!(bytes, br#"{"a":"x","z":[1,{"a":1,"b":2}]}"#);
+
+    let encoded = digest::canonical_json(&value).unwrap();
+    assert_eq!(encoded.as_bytes(), bytes.as_slice());
+
+    let expected = sha256_hex(&bytes);
+    assert_eq!(digest::compute_digest(&value).unwrap(), expected);
+    assert_eq!(CommitId::from_json(&value).state_hash, expected);
+}
+
+#[test]
+fn test_eval_suite_digest_uses_shared_encoding() {
+    let fields = EvalSuiteFields {
+        name: "suite".to_string(),
+        version: "1.0.0".to_string(),
+        test_cases: vec![EvalTestCase::new(json!({ "q": 1, "p": 2.0 }), None)],
+        scorers: Vec::new(),
+        thresholds: EvalThresholds::default(),
+    };
+    let value = serde_json::to_value(&fields).unwrap();
+
+    assert_eq!(
+        EvalSuite::compute_digest(&fields).unwrap(),
+        sha256_hex(&canonical_json(&value))
+    );
+    assert_eq!(
+        EvalSuite::compute_digest(&fields).unwrap(),
+        CommitId::from_json(&value).state_hash
+    );
+}
+
+#[test]
+fn test_ci_object_digest_uses_shared_encoding() {
+    let step = CiStepSpec {
+        name: "check".to_string(),
+        command: CiCommand {
+            program: "cargo".to_string(),
+            args: vec!["check".to_string()],
+            env: BTreeMap::from([("RUSTFLAGS".to_string(), "-D warnings".to_string())]),
+            cwd: None,
+        },
+        timeout_secs: Some(600),
+        allow_failure: false,
+    };
+    let value = serde_json::to_value(&step).unwrap();
+
+    assert_eq!(step.digest(), digest::compute_digest(&value).unwrap());
+    assert_eq!(step.digest(), CommitId::from_json(&value).state_hash);
+}
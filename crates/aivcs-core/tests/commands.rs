@@ -0,0 +1,702 @@
+//! Repository commands driven through the library, without the CLI binary
+
+use aivcs_core::commands::{
+    self, log_output, parse_log_time, CommandConflict, LogWindow, MergePath, SnapshotRequest,
+};
+use aivcs_core::{
+    memory_provenance, CasStore, Digest, EnvironmentCache, MemoryCasStore, MergeRun, NixHash,
+    NoProgress, SnapshotMeta, SurrealHandle,
+};
+use oxidized_state::{
+    BranchProtection, BranchRecord, CommitId, CommitRecord, ContentDigest, MemoryRecord, StateError,
+};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+fn request(state: &Value, branch: &str) -> SnapshotRequest {
+    SnapshotRequest {
+        state: state.to_string(),
+        message: "snap".to_string(),
+        author: "agent".to_string(),
+        branch: branch.to_string(),
+        ..Default::default()
+    }
+}
+
+async fn commit_on(handle: &SurrealHandle, label: &str, parent: Option<&str>) -> String {
+    let id = CommitId::from_state(label.as_bytes());
+    let parents = parent.map(|p| vec![p.to_string()]).unwrap_or_default();
+    handle
+        .save_commit(&CommitRecord::new(id.clone(), parents, label, "agent"))
+        .await
+        .unwrap();
+    handle
+        .save_snapshot(&id, json!({ "label": label }))
+        .await
+        .unwrap();
+    id.hash
+}
+
+#[tokio::test]
+async fn test_init_creates_main_at_initial_commit() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+
+    let commit_id = commands::init(&handle).await.unwrap();
+
+    let main = handle.get_branch("main").await.unwrap().unwrap();
+    assert!(main.is_default);
+    assert_eq!(main.head_commit_id, commit_id.hash);
+    let restored = commands::restore(&handle, "main", None).await.unwrap();
+    assert_eq!(restored.state["initialized"], true);
+}
+
+#[tokio::test]
+async fn test_snapshot_then_restore_by_branch_and_commit() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let cas = MemoryCasStore::new();
+    let first = json!({ "step": 1 });
+    let second = json!({ "step": 2 });
+
+    let a = commands::snapshot(&handle, &cas, &request(&first, "main"), None)
+        .await
+        .unwrap();
+    let b = commands::snapshot(&handle, &cas, &request(&second, "main"), None)
+        .await
+        .unwrap();
+    let first_commit = a.commit.unwrap();
+    assert_eq!(b.parent_ids, vec![first_commit.clone()]);
+
+    let digest: Digest = b.cas_digest.parse().unwrap();
+    assert_eq!(cas.get(&digest).unwrap(), second.to_string().as_bytes());
+
+    let restored = commands::restore(&handle, "main", None).await.unwrap();
+    assert_eq!(Some(restored.commit), b.commit);
+    assert_eq!(restored.state, second);
+    let restored = commands::restore(&handle, &first_commit, None)
+        .await
+        .unwrap();
+    assert_eq!(restored.state, first);
+}
+
+#[tokio::test]
+async fn test_snapshot_skips_unchanged_state_and_rejects_invalid_json() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let cas = MemoryCasStore::new();
+    let state = json!({ "step": 1 });
+
+    let first = commands::snapshot(&handle, &cas, &request(&state, "main"), None)
+        .await
+        .unwrap();
+    let head = first.commit.unwrap();
+
+    let skip = SnapshotRequest {
+        skip_if_unchanged: true,
+        ..request(&state, "main")
+    };
+    let second = commands::snapshot(&handle, &cas, &skip, None)
+        .await
+        .unwrap();
+    assert!(second.deduplicated);
+    assert_eq!(second.unchanged_from.as_deref(), Some(head.as_str()));
+    assert_eq!(second.commit, None);
+    assert_eq!(handle.list_commits().await.unwrap().len(), 1);
+
+    let invalid = SnapshotRequest {
+        state: "not json".to_string(),
+        ..request(&state, "other")
+    };
+    assert!(commands::snapshot(&handle, &cas, &invalid, None)
+        .await
+        .is_err());
+    assert!(handle.get_branch("other").await.unwrap().is_none());
+}
+
+/// CAS that serves `blob` in place of whatever is stored
+struct TamperedCas {
+    inner: MemoryCasStore,
+    blob: Vec<u8>,
+}
+
+impl CasStore for TamperedCas {
+    fn put(&self, data: &[u8]) -> aivcs_core::cas::Result<Digest> {
+        self.inner.put(data)
+    }
+
+    fn get(&self, digest: &Digest) -> aivcs_core::cas::Result<Vec<u8>> {
+        self.inner.get(digest).map(|_| self.blob.clone())
+    }
+
+    fn exists(&self, digest: &Digest) -> aivcs_core::cas::Result<bool> {
+        self.inner.exists(digest)
+    }
+
+    fn delete(&self, digest: &Digest) -> aivcs_core::cas::Result<bool> {
+        self.inner.delete(digest)
+    }
+}
+
+#[tokio::test]
+async fn test_verified_restore_checks_the_committed_cas_blob() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let cas = MemoryCasStore::new();
+    let state = json!({ "step": 1, "memory": ["a", "b"] });
+
+    // Commits made without CAS verify against their compact encoding
+    let init = commands::init(&handle).await.unwrap();
+    commands::restore(&handle, &init.hash, Some(&cas))
+        .await
+        .unwrap();
+
+    // Pretty-printed, key-reordered input still verifies: the blob is
+    // compared to the stored state as JSON, not byte for byte
+    let pretty = SnapshotRequest {
+        state: "{\n  \"memory\": [\"a\", \"b\"],\n  \"step\": 1\n}".to_string(),
+        ..request(&state, "main")
+    };
+    let outcome = commands::snapshot(&handle, &cas, &pretty, None)
+        .await
+        .unwrap();
+    let commit = outcome.commit.unwrap();
+    let restored = commands::restore(&handle, "main", Some(&cas))
+        .await
+        .unwrap();
+    assert_eq!(restored.state, state);
+
+    // A blob that no longer matches its digest fails verification, while
+    // an unverified restore still returns the stored state
+    let tampered = TamperedCas {
+        inner: cas,
+        blob: json!({ "step": 999 }).to_string().into_bytes(),
+    };
+    let err = commands::restore(&handle, &commit, Some(&tampered))
+        .await
+        .unwrap_err();
+    match err.downcast_ref::<StateError>() {
+        Some(StateError::IntegrityError { expected, .. }) => {
+            assert_eq!(expected, &outcome.cas_digest)
+        }
+        other => panic!("expected IntegrityError, got {other:?}: {err:#}"),
+    }
+    let unverified = commands::restore(&handle, &commit, None).await.unwrap();
+    assert_eq!(unverified.state, state);
+
+    // Without the blob, a non-compact input can't be verified
+    let err = commands::restore(&handle, &commit, Some(&MemoryCasStore::new()))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:#}").contains("not in CAS"), "{err:#}");
+}
+
+#[tokio::test]
+async fn test_log_follows_history_and_ranges() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let root = commit_on(&handle, "log-root", None).await;
+    let mid = commit_on(&handle, "log-mid", Some(&root)).await;
+    let tip = commit_on(&handle, "log-tip", Some(&mid)).await;
+    handle
+        .save_branch(&BranchRecord::new("main", &root, true))
+        .await
+        .unwrap();
+    commands::create_branch(&handle, "feature", &tip)
+        .await
+        .unwrap();
+
+    let history = commands::log(&handle, "feature", 10).await.unwrap();
+    let ids: Vec<&str> = history.iter().map(|c| c.commit_id.hash.as_str()).collect();
+    assert_eq!(ids, [tip.as_str(), mid.as_str(), root.as_str()]);
+
+    let range = commands::log(&handle, "main..feature", 10).await.unwrap();
+    let output = log_output(&range);
+    let ids: Vec<&str> = output
+        .commits
+        .iter()
+        .map(|c| c.commit_id.as_str())
+        .collect();
+    assert_eq!(ids, [tip.as_str(), mid.as_str()]);
+}
+
+#[tokio::test]
+async fn test_log_range_rejects_unknown_endpoints() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let root = commit_on(&handle, "range-root", None).await;
+    handle
+        .save_branch(&BranchRecord::new("main", &root, true))
+        .await
+        .unwrap();
+
+    let window = LogWindow {
+        since: Some("2000-01-01T00:00:00Z".parse().unwrap()),
+        until: None,
+    };
+    for reference in ["v1..main", "main..typo"] {
+        let missing = if reference.starts_with("v1") {
+            "v1"
+        } else {
+            "typo"
+        };
+        let err = commands::log(&handle, reference, 10).await.unwrap_err();
+        assert!(
+            matches!(err.downcast_ref::<StateError>(), Some(StateError::CommitNotFound(r)) if r == missing),
+            "{err:#}"
+        );
+        let err = commands::log_window(&handle, reference, 10, window)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err.downcast_ref::<StateError>(), Some(StateError::CommitNotFound(r)) if r == missing),
+            "{err:#}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_show_reports_metadata_of_fresh_snapshot() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let cas = MemoryCasStore::new();
+    let state = json!({ "step": 1, "notes": "warm cache" });
+
+    let parent = commit_on(&handle, "base", None).await;
+    handle
+        .save_branch(&BranchRecord::new("main", &parent, true))
+        .await
+        .unwrap();
+    let snap = SnapshotRequest {
+        logic_hash: Some("logic-abc".to_string()),
+        ..request(&state, "main")
+    };
+    let outcome = commands::snapshot(&handle, &cas, &snap, None)
+        .await
+        .unwrap();
+    let commit = outcome.commit.unwrap();
+
+    let shown = commands::show(&handle, "main").await.unwrap();
+    assert_eq!(shown.commit_id, commit);
+    assert_eq!(shown.logic_hash.as_deref(), Some("logic-abc"));
+    assert_eq!(shown.state_hash, outcome.cas_digest);
+    assert_eq!(shown.env_hash, None);
+    assert_eq!(shown.parent_ids, vec![parent]);
+    assert_eq!(shown.author, "agent");
+    assert_eq!(shown.message, "snap");
+    assert_eq!(shown.branch.as_deref(), Some("main"));
+    assert!(!shown.signed);
+    assert_eq!(
+        shown.snapshot.size_bytes,
+        serde_json::to_string(&state).unwrap().len() as u64
+    );
+    assert_eq!(shown.snapshot.delta_parent, None);
+
+    let by_id = commands::show(&handle, &commit).await.unwrap();
+    assert_eq!(by_id, shown);
+    assert!(commands::show(&handle, "no-such-commit").await.is_err());
+}
+
+#[tokio::test]
+async fn test_snapshot_persists_meta_with_commit_hashes() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let cas = MemoryCasStore::new();
+    let snap = SnapshotRequest {
+        logic_hash: Some("logic-abc".to_string()),
+        env_hash: Some("env-123".to_string()),
+        git_sha: Some("deadbeef".repeat(5)),
+        toolchain: Some("rustc 1.80.0".to_string()),
+        ..request(&json!({ "step": 1 }), "main")
+    };
+
+    let outcome = commands::snapshot(&handle, &cas, &snap, None)
+        .await
+        .unwrap();
+    let commit = outcome.commit.unwrap();
+
+    let meta: SnapshotMeta = handle.get_snapshot_meta(&commit).await.unwrap().unwrap();
+    let recorded = handle.get_commit(&commit).await.unwrap().unwrap().commit_id;
+    assert_eq!(meta.logic_hash, recorded.logic_hash);
+    assert_eq!(meta.env_hash, recorded.env_hash);
+    assert_eq!(meta.env_hash.as_deref(), Some("env-123"));
+    assert_eq!(meta.cas_digest, outcome.cas_digest);
+    assert_eq!(meta.git_sha, "deadbeef".repeat(5));
+    assert_eq!(meta.toolchain.as_deref(), Some("rustc 1.80.0"));
+    assert_eq!(meta.branch, "main");
+
+    let shown = commands::show(&handle, &commit).await.unwrap();
+    assert_eq!(shown.meta, Some(meta));
+}
+
+/// Binary cache stand-in that records which environments were pulled
+struct RecordingCache {
+    cached: bool,
+    pulled: std::sync::Mutex<Vec<String>>,
+}
+
+#[async_trait::async_trait]
+impl EnvironmentCache for RecordingCache {
+    async fn is_environment_cached(&self, _hash: &NixHash) -> bool {
+        self.cached
+    }
+
+    async fn pull_environment(&self, hash: &NixHash) -> nix_env_manager::Result<PathBuf> {
+        self.pulled.lock().unwrap().push(hash.hash.clone());
+        Ok(PathBuf::from(format!(
+            "/nix/store/{}-aivcs-env",
+            hash.short()
+        )))
+    }
+}
+
+#[tokio::test]
+async fn test_reproduce_environment_pulls_commit_env_hash() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let cas = MemoryCasStore::new();
+    let env_hash = "e".repeat(64);
+    let snap = SnapshotRequest {
+        env_hash: Some(env_hash.clone()),
+        ..request(&json!({ "step": 1 }), "main")
+    };
+    let commit = commands::snapshot(&handle, &cas, &snap, None)
+        .await
+        .unwrap()
+        .commit
+        .unwrap();
+
+    let cache = RecordingCache {
+        cached: true,
+        pulled: Default::default(),
+    };
+    let reproduced = commands::reproduce_environment(&handle, &cache, "main")
+        .await
+        .unwrap();
+    assert_eq!(reproduced.commit_id, commit);
+    assert_eq!(reproduced.env_hash, env_hash);
+    assert_eq!(
+        reproduced.store_path,
+        PathBuf::from("/nix/store/eeeeeeeeeeee-aivcs-env")
+    );
+    assert_eq!(*cache.pulled.lock().unwrap(), vec![env_hash.clone()]);
+
+    let missing = RecordingCache {
+        cached: false,
+        pulled: Default::default(),
+    };
+    let err = commands::reproduce_environment(&handle, &missing, &commit)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("not in the binary cache"), "{err}");
+    assert!(missing.pulled.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_reproduce_environment_requires_env_hash() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let cas = MemoryCasStore::new();
+    commands::snapshot(&handle, &cas, &request(&json!({ "step": 1 }), "main"), None)
+        .await
+        .unwrap();
+
+    let cache = RecordingCache {
+        cached: true,
+        pulled: Default::default(),
+    };
+    let err = commands::reproduce_environment(&handle, &cache, "main")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("no environment hash"), "{err}");
+    assert!(cache.pulled.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_log_window_filters_by_commit_time_before_limit() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let day = |d: u32| format!("2024-05-{:02}T12:00:00Z", d).parse().unwrap();
+    // A root stamped inside the window but behind an older commit: the walk
+    // stops at that older commit and never reaches it
+    let skewed = CommitId::from_state(b"window-skewed-root");
+    let mut root = CommitRecord::new(skewed.clone(), vec![], "skewed", "agent");
+    root.created_at = day(3);
+    handle.save_commit(&root).await.unwrap();
+    let mut parent = Some(skewed.hash);
+    let mut ids = Vec::new();
+    for d in 1..=5 {
+        let label = format!("window-{d}");
+        let id = CommitId::from_state(label.as_bytes());
+        let mut record = CommitRecord::new(
+            id.clone(),
+            parent.iter().cloned().collect(),
+            &label,
+            "agent",
+        );
+        record.created_at = day(d);
+        handle.save_commit(&record).await.unwrap();
+        parent = Some(id.hash.clone());
+        ids.push(id.hash);
+    }
+    commands::create_branch(&handle, "main", &ids[4])
+        .await
+        .unwrap();
+
+    let window = LogWindow {
+        since: Some(day(2)),
+        until: Some(day(4)),
+    };
+    let history = commands::log_window(&handle, "main", 10, window)
+        .await
+        .unwrap();
+    let got: Vec<&str> = history.iter().map(|c| c.commit_id.hash.as_str()).collect();
+    assert_eq!(got, [ids[3].as_str(), ids[2].as_str(), ids[1].as_str()]);
+
+    // The limit counts commits inside the window, not the newer ones skipped
+    let history = commands::log_window(&handle, "main", 2, window)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].commit_id.hash, ids[3]);
+
+    let since_only = LogWindow {
+        since: Some(day(4)),
+        until: None,
+    };
+    let history = commands::log_window(&handle, "main", 10, since_only)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 2);
+}
+
+#[test]
+fn test_parse_log_time_accepts_rfc3339_and_relative_durations() {
+    let now = "2024-05-08T00:00:00Z".parse().unwrap();
+    let at = |text: &str| parse_log_time(text, now).unwrap().to_rfc3339();
+
+    assert_eq!(at("7d"), "2024-05-01T00:00:00+00:00");
+    assert_eq!(at("24h"), "2024-05-07T00:00:00+00:00");
+    assert_eq!(at("1w"), "2024-05-01T00:00:00+00:00");
+    assert_eq!(at("2024-05-03T10:00:00+02:00"), "2024-05-03T08:00:00+00:00");
+    for bad in ["", "7", "d", "-1d", "7y", "last week"] {
+        assert!(parse_log_time(bad, now).is_err(), "{bad}");
+    }
+}
+
+#[tokio::test]
+async fn test_merge_fast_forwards_then_reports_up_to_date() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let root = commit_on(&handle, "ff-root", None).await;
+    let ahead = commit_on(&handle, "ff-ahead", Some(&root)).await;
+    handle
+        .save_branch(&BranchRecord::new("main", &root, true))
+        .await
+        .unwrap();
+    commands::create_branch(&handle, "feature", &ahead)
+        .await
+        .unwrap();
+
+    let outcome = commands::merge(&handle, "feature", "main", None, false, "agent")
+        .await
+        .unwrap();
+    assert_eq!(outcome.path, MergePath::FastForward);
+    assert_eq!(outcome.previous_head, root);
+    assert_eq!(outcome.head, ahead);
+    assert_eq!(handle.get_branch_head("main").await.unwrap(), ahead);
+
+    let outcome = commands::merge(&handle, "feature", "main", None, false, "agent")
+        .await
+        .unwrap();
+    assert_eq!(outcome.path, MergePath::UpToDate);
+}
+
+#[tokio::test]
+async fn test_merge_into_linear_branch_is_a_typed_conflict() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let root = commit_on(&handle, "lin-root", None).await;
+    let left = commit_on(&handle, "lin-left", Some(&root)).await;
+    let right = commit_on(&handle, "lin-right", Some(&root)).await;
+    handle
+        .save_branch(&BranchRecord::new("main", &left, true))
+        .await
+        .unwrap();
+    commands::create_branch(&handle, "feature", &right)
+        .await
+        .unwrap();
+    handle
+        .set_protection(
+            "main",
+            BranchProtection {
+                require_linear: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    let err = commands::merge(&handle, "feature", "main", None, false, "agent")
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<CommandConflict>(),
+        Some(CommandConflict::LinearHistoryRequired { .. })
+    ));
+    assert_eq!(handle.get_branch_head("main").await.unwrap(), left);
+}
+
+async fn db_counts(handle: &SurrealHandle) -> (usize, usize, usize, String) {
+    (
+        handle.list_commits().await.unwrap().len(),
+        handle.list_all_memories().await.unwrap().len(),
+        handle.list_graph_edges().await.unwrap().len(),
+        handle.get_branch_head("main").await.unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn test_merge_dry_run_writes_nothing_and_matches_real_merge() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let root = commit_on(&handle, "dry-root", None).await;
+    let left = commit_on(&handle, "dry-left", Some(&root)).await;
+    let right = commit_on(&handle, "dry-right", Some(&root)).await;
+    handle
+        .save_branch(&BranchRecord::new("main", &left, true))
+        .await
+        .unwrap();
+    commands::create_branch(&handle, "feature", &right)
+        .await
+        .unwrap();
+    for (commit, key, content) in [
+        (&left, "shared", "same"),
+        (&left, "plan", "short"),
+        (&left, "only-left", "x"),
+        (&right, "shared", "same"),
+        (&right, "plan", "a much longer plan with more detail"),
+        (&right, "only-right", "y"),
+    ] {
+        handle
+            .save_memory(&MemoryRecord::new(commit, key, content))
+            .await
+            .unwrap();
+    }
+
+    let before = db_counts(&handle).await;
+    let preview = commands::preview_merge(&handle, "feature", "main", false)
+        .await
+        .unwrap();
+    assert_eq!(db_counts(&handle).await, before);
+    assert_eq!(preview.path, MergePath::MergeCommit);
+    assert_eq!(preview.previous_head, left);
+    assert_eq!(preview.auto_resolved + preview.conflicts.len(), 1);
+
+    let outcome = commands::merge(&handle, "feature", "main", None, false, "agent")
+        .await
+        .unwrap();
+    assert_eq!(outcome.head, preview.head);
+    assert_eq!(outcome.summary, preview.summary);
+    assert_eq!(outcome.conflicts, preview.conflicts);
+    assert_ne!(db_counts(&handle).await, before);
+}
+
+#[tokio::test]
+async fn test_merge_records_conflict_decisions_by_key_and_digest() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let root = commit_on(&handle, "audit-root", None).await;
+    let left = commit_on(&handle, "audit-left", Some(&root)).await;
+    let right = commit_on(&handle, "audit-right", Some(&root)).await;
+    handle
+        .save_branch(&BranchRecord::new("main", &left, true))
+        .await
+        .unwrap();
+    commands::create_branch(&handle, "feature", &right)
+        .await
+        .unwrap();
+    let long_plan = "a much longer plan with more detail";
+    for (commit, content) in [(&left, "short"), (&right, long_plan)] {
+        handle
+            .save_memory(&MemoryRecord::new(commit, "plan", content))
+            .await
+            .unwrap();
+    }
+
+    let outcome = commands::merge(&handle, "feature", "main", None, false, "agent")
+        .await
+        .unwrap();
+
+    let audit = handle
+        .get_decision_history(
+            &format!(
+                "{}:{}",
+                semantic_rag_merge::MERGE_AUTO_RESOLUTION_TASK_PREFIX,
+                outcome.head
+            ),
+            10,
+        )
+        .await
+        .unwrap();
+    assert_eq!(audit.len(), 1);
+    assert!(audit[0].action.starts_with("plan: chose side"));
+    assert_eq!(audit[0].alternatives.len(), 1);
+    assert!(audit[0].alternatives[0].starts_with("plan: chose side"));
+    let inputs = audit[0].inputs.as_ref().expect("inputs recorded");
+    assert_eq!(inputs["key"], "plan");
+    assert_eq!(
+        inputs["content_a"],
+        ContentDigest::from_bytes(long_plan.as_bytes()).to_string()
+    );
+    assert_eq!(
+        inputs["content_b"],
+        ContentDigest::from_bytes(b"short").to_string()
+    );
+}
+
+#[tokio::test]
+async fn test_merge_by_a_run_records_merged_provenance() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let root = commit_on(&handle, "prov-root", None).await;
+    let left = commit_on(&handle, "prov-left", Some(&root)).await;
+    let right = commit_on(&handle, "prov-right", Some(&root)).await;
+    handle
+        .save_branch(&BranchRecord::new("main", &left, true))
+        .await
+        .unwrap();
+    commands::create_branch(&handle, "feature", &right)
+        .await
+        .unwrap();
+    for (commit, key) in [(&left, "notes"), (&right, "todo")] {
+        handle
+            .save_memory(&MemoryRecord::new(commit, key, "content"))
+            .await
+            .unwrap();
+    }
+
+    let run = MergeRun {
+        run_id: "merge-run".to_string(),
+        seq: 4,
+    };
+    let outcome = commands::merge_with_progress(
+        &handle,
+        "feature",
+        "main",
+        None,
+        false,
+        "agent",
+        Some(&run),
+        &NoProgress,
+    )
+    .await
+    .unwrap();
+
+    for key in ["notes", "todo"] {
+        let origins = memory_provenance(&handle, &outcome.head, key)
+            .await
+            .unwrap();
+        assert_eq!(origins.len(), 1, "{key}");
+        assert_eq!(origins[0].run_id, "merge-run");
+        assert_eq!(origins[0].seq, 4);
+        assert_eq!(origins[0].source_type, "merged");
+    }
+}
+
+#[tokio::test]
+async fn test_delete_branch_removes_it() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let root = commit_on(&handle, "del-root", None).await;
+    commands::create_branch(&handle, "scratch", &root)
+        .await
+        .unwrap();
+
+    commands::delete_branch(&handle, "scratch").await.unwrap();
+
+    assert!(handle.get_branch("scratch").await.unwrap().is_none());
+}
@@ -0,0 +1,83 @@
+//! Ed25519 commit signing and verification
+
+use aivcs_core::{
+    verify_commit, CommitId, CommitRecord, CommitSigner, SignatureStatus, SurrealHandle,
+    TrustedKeys,
+};
+
+const SEED: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+
+fn trusted(signer: &CommitSigner) -> TrustedKeys {
+    TrustedKeys::parse(&format!(
+        "# release signers\n{} alice\n",
+        signer.public_key_hex()
+    ))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_signed_commit_verifies_after_roundtrip() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let signer = CommitSigner::from_seed_hex(SEED).unwrap();
+
+    let mut commit = CommitRecord::new(CommitId::from_state(b"signed"), vec![], "ship", "alice");
+    signer.sign(&mut commit);
+    handle.save_commit(&commit).await.unwrap();
+
+    let loaded = handle
+        .get_commit(&commit.commit_id.hash)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        loaded.signer.as_deref(),
+        Some(signer.public_key_hex().as_str())
+    );
+    assert_eq!(
+        verify_commit(&loaded, &trusted(&signer)),
+        SignatureStatus::Valid {
+            signer: signer.public_key_hex(),
+            name: "alice".to_string(),
+        }
+    );
+    assert_eq!(
+        verify_commit(&loaded, &TrustedKeys::new()),
+        SignatureStatus::UntrustedSigner {
+            signer: signer.public_key_hex(),
+        }
+    );
+}
+
+#[test]
+fn test_tampered_commit_fails_verification() {
+    let signer = CommitSigner::from_seed_hex(SEED).unwrap();
+    let trusted = trusted(&signer);
+
+    let mut commit = CommitRecord::new(CommitId::from_state(b"tamper"), vec![], "ship", "alice");
+    signer.sign(&mut commit);
+    assert!(verify_commit(&commit, &trusted).is_valid());
+
+    let mut reworded = commit.clone();
+    reworded.message = "ship (edited)".to_string();
+    assert!(matches!(
+        verify_commit(&reworded, &trusted),
+        SignatureStatus::Invalid { .. }
+    ));
+
+    let mut reparented = commit.clone();
+    reparented.parent_ids = vec!["forged-parent".to_string()];
+    assert!(!verify_commit(&reparented, &trusted).is_valid());
+
+    // Moving the advisory branch label doesn't invalidate the signature
+    let relabeled = commit.with_branch("elsewhere");
+    assert!(verify_commit(&relabeled, &trusted).is_valid());
+}
+
+#[test]
+fn test_unsigned_commit_reports_unsigned() {
+    let commit = CommitRecord::new(CommitId::from_state(b"plain"), vec![], "wip", "bob");
+    assert_eq!(
+        verify_commit(&commit, &TrustedKeys::new()),
+        SignatureStatus::Unsigned
+    );
+}
@@ -1,6 +1,8 @@
+use aivcs_core::domain::eval::EvalRunReport;
 use aivcs_core::domain::release::{Release, ReleaseEnvironment};
 use aivcs_core::{
-    evaluate_compat, CompatRule, CompatRuleSet, CompatVerdict, CompatViolation, PromoteContext,
+    evaluate_compat, CompatRule, CompatRuleSet, CompatVerdict, CompatViolation, CompatWarning,
+    PromoteContext,
 };
 
 /// Valid 64-char lowercase hex digest for tests.
@@ -28,6 +30,8 @@ fn spec_digest_valid_passes_well_formed() {
     let ctx = PromoteContext {
         candidate: &candidate,
         current: None,
+        eval_report: None,
+        eval_report_digest: None,
     };
     let rules = CompatRuleSet {
         rules: vec![CompatRule::SpecDigestValid],
@@ -42,6 +46,8 @@ fn spec_digest_valid_fails_empty() {
     let ctx = PromoteContext {
         candidate: &candidate,
         current: None,
+        eval_report: None,
+        eval_report_digest: None,
     };
     let rules = CompatRuleSet {
         rules: vec![CompatRule::SpecDigestValid],
@@ -58,6 +64,8 @@ fn spec_digest_valid_fails_wrong_length() {
     let ctx = PromoteContext {
         candidate: &candidate,
         current: None,
+        eval_report: None,
+        eval_report_digest: None,
     };
     let rules = CompatRuleSet {
         rules: vec![CompatRule::SpecDigestValid],
@@ -75,6 +83,8 @@ fn spec_digest_valid_fails_non_hex() {
     let ctx = PromoteContext {
         candidate: &candidate,
         current: None,
+        eval_report: None,
+        eval_report_digest: None,
     };
     let rules = CompatRuleSet {
         rules: vec![CompatRule::SpecDigestValid],
@@ -91,6 +101,8 @@ fn require_tools_digest_fails_empty() {
     let ctx = PromoteContext {
         candidate: &candidate,
         current: None,
+        eval_report: None,
+        eval_report_digest: None,
     };
     let rules = CompatRuleSet {
         rules: vec![CompatRule::RequireToolsDigest],
@@ -106,6 +118,8 @@ fn require_graph_digest_fails_empty() {
     let ctx = PromoteContext {
         candidate: &candidate,
         current: None,
+        eval_report: None,
+        eval_report_digest: None,
     };
     let rules = CompatRuleSet {
         rules: vec![CompatRule::RequireGraphDigest],
@@ -124,6 +138,8 @@ fn no_tools_change_passes_same_digest() {
     let ctx = PromoteContext {
         candidate: &candidate,
         current: Some(&current),
+        eval_report: None,
+        eval_report_digest: None,
     };
     let rules = CompatRuleSet {
         rules: vec![CompatRule::NoToolsChange],
@@ -140,6 +156,8 @@ fn no_tools_change_fails_different_digest() {
     let ctx = PromoteContext {
         candidate: &candidate,
         current: Some(&current),
+        eval_report: None,
+        eval_report_digest: None,
     };
     let rules = CompatRuleSet {
         rules: vec![CompatRule::NoToolsChange],
@@ -157,6 +175,8 @@ fn no_graph_change_fails_different_digest() {
     let ctx = PromoteContext {
         candidate: &candidate,
         current: Some(&current),
+        eval_report: None,
+        eval_report_digest: None,
     };
     let rules = CompatRuleSet {
         rules: vec![CompatRule::NoGraphChange],
@@ -172,6 +192,8 @@ fn no_tools_change_passes_when_no_current_release() {
     let ctx = PromoteContext {
         candidate: &candidate,
         current: None,
+        eval_report: None,
+        eval_report_digest: None,
     };
     let rules = CompatRuleSet {
         rules: vec![CompatRule::NoToolsChange, CompatRule::NoGraphChange],
@@ -180,6 +202,193 @@ fn no_tools_change_passes_when_no_current_release() {
     assert!(verdict.passed());
 }
 
+// ── MinEvalPassRate ─────────────────────────────────────────────────────
+
+fn make_report(passed_cases: usize, total_cases: usize) -> EvalRunReport {
+    EvalRunReport {
+        suite_digest: VALID_DIGEST.to_string(),
+        seed: 0,
+        total_cases,
+        passed_cases,
+        pass_rate: passed_cases as f32 / total_cases as f32,
+        overall_pass: true,
+        case_results: vec![],
+    }
+}
+
+#[test]
+fn min_eval_pass_rate_allows_report_above_threshold() {
+    let candidate = make_release(VALID_DIGEST, VALID_TOOLS, VALID_GRAPH);
+    let report = make_report(19, 20);
+    let ctx = PromoteContext {
+        candidate: &candidate,
+        current: None,
+        eval_report: Some(&report),
+        eval_report_digest: None,
+    };
+    let rules = CompatRuleSet::standard().with_rule(CompatRule::MinEvalPassRate { threshold: 0.9 });
+    let verdict = evaluate_compat(&rules, &ctx);
+    assert!(verdict.passed());
+}
+
+#[test]
+fn min_eval_pass_rate_blocks_report_below_threshold() {
+    let candidate = make_release(VALID_DIGEST, VALID_TOOLS, VALID_GRAPH);
+    let report = make_report(7, 10);
+    let ctx = PromoteContext {
+        candidate: &candidate,
+        current: None,
+        eval_report: Some(&report),
+        eval_report_digest: None,
+    };
+    let rule = CompatRule::MinEvalPassRate { threshold: 0.9 };
+    let rules = CompatRuleSet::standard().with_rule(rule.clone());
+    let verdict = evaluate_compat(&rules, &ctx);
+    assert!(!verdict.passed());
+    assert_eq!(verdict.violations.len(), 1);
+    assert_eq!(verdict.violations[0].rule, rule);
+    assert!(verdict.violations[0].reason.contains("0.70"));
+}
+
+#[test]
+fn min_eval_pass_rate_blocks_missing_report() {
+    let candidate = make_release(VALID_DIGEST, VALID_TOOLS, VALID_GRAPH);
+    let ctx = PromoteContext {
+        candidate: &candidate,
+        current: None,
+        eval_report: None,
+        eval_report_digest: None,
+    };
+    let rules = CompatRuleSet {
+        rules: vec![CompatRule::MinEvalPassRate { threshold: 0.5 }],
+    };
+    let verdict = evaluate_compat(&rules, &ctx);
+    assert!(!verdict.passed());
+}
+
+// ── RequireField ────────────────────────────────────────────────────────
+
+fn with_fields(mut release: Release, fields: &[&str]) -> Release {
+    release.metadata = serde_json::json!({ "fields": fields });
+    release
+}
+
+#[test]
+fn removed_deprecated_field_warns_without_blocking() {
+    let candidate = with_fields(
+        make_release(VALID_DIGEST, VALID_TOOLS, VALID_GRAPH),
+        &["model", "max_tokens"],
+    );
+    let ctx = PromoteContext {
+        candidate: &candidate,
+        current: None,
+        eval_report: None,
+        eval_report_digest: None,
+    };
+    let rule = CompatRule::RequireField {
+        field: "temperature".to_string(),
+        deprecated_since: Some("1.4.0".to_string()),
+    };
+    let rules = CompatRuleSet::standard().with_rule(rule.clone());
+    let verdict = evaluate_compat(&rules, &ctx);
+    assert!(verdict.passed());
+    assert_eq!(verdict.warnings.len(), 1);
+    assert_eq!(verdict.warnings[0].rule, rule);
+    assert!(verdict.warnings[0].reason.contains("1.4.0"));
+}
+
+#[test]
+fn removed_required_field_is_a_violation() {
+    let candidate = with_fields(
+        make_release(VALID_DIGEST, VALID_TOOLS, VALID_GRAPH),
+        &["max_tokens"],
+    );
+    let ctx = PromoteContext {
+        candidate: &candidate,
+        current: None,
+        eval_report: None,
+        eval_report_digest: None,
+    };
+    let rule = CompatRule::RequireField {
+        field: "model".to_string(),
+        deprecated_since: None,
+    };
+    let rules = CompatRuleSet::standard().with_rule(rule.clone());
+    let verdict = evaluate_compat(&rules, &ctx);
+    assert!(!verdict.passed());
+    assert_eq!(verdict.violations.len(), 1);
+    assert_eq!(verdict.violations[0].rule, rule);
+    assert!(verdict.warnings.is_empty());
+}
+
+#[test]
+fn field_dropped_from_current_release_is_a_violation() {
+    let current = with_fields(
+        make_release(VALID_DIGEST, VALID_TOOLS, VALID_GRAPH),
+        &["model", "max_tokens"],
+    );
+    let rule = CompatRule::RequireField {
+        field: "model".to_string(),
+        deprecated_since: None,
+    };
+    let rules = CompatRuleSet::standard().with_rule(rule.clone());
+
+    let dropped = with_fields(
+        make_release(VALID_DIGEST, VALID_TOOLS, VALID_GRAPH),
+        &["max_tokens"],
+    );
+    let verdict = evaluate_compat(
+        &rules,
+        &PromoteContext {
+            candidate: &dropped,
+            current: Some(&current),
+            eval_report: None,
+            eval_report_digest: None,
+        },
+    );
+    assert_eq!(verdict.violations.len(), 1);
+    assert_eq!(verdict.violations[0].rule, rule);
+
+    // A field the live release never exposed can't be removed
+    let older = with_fields(
+        make_release(VALID_DIGEST, VALID_TOOLS, VALID_GRAPH),
+        &["max_tokens"],
+    );
+    let verdict = evaluate_compat(
+        &rules,
+        &PromoteContext {
+            candidate: &dropped,
+            current: Some(&older),
+            eval_report: None,
+            eval_report_digest: None,
+        },
+    );
+    assert!(verdict.passed());
+}
+
+#[test]
+fn present_deprecated_field_is_silent() {
+    let candidate = with_fields(
+        make_release(VALID_DIGEST, VALID_TOOLS, VALID_GRAPH),
+        &["temperature"],
+    );
+    let ctx = PromoteContext {
+        candidate: &candidate,
+        current: None,
+        eval_report: None,
+        eval_report_digest: None,
+    };
+    let rules = CompatRuleSet {
+        rules: vec![CompatRule::RequireField {
+            field: "temperature".to_string(),
+            deprecated_since: Some("1.4.0".to_string()),
+        }],
+    };
+    let verdict = evaluate_compat(&rules, &ctx);
+    assert!(verdict.passed());
+    assert!(verdict.warnings.is_empty());
+}
+
 // ── Edge cases ──────────────────────────────────────────────────────────
 
 #[test]
@@ -188,6 +397,8 @@ fn empty_rules_always_passes() {
     let ctx = PromoteContext {
         candidate: &candidate,
         current: None,
+        eval_report: None,
+        eval_report_digest: None,
     };
     let rules = CompatRuleSet { rules: vec![] };
     let verdict = evaluate_compat(&rules, &ctx);
@@ -202,6 +413,13 @@ fn compat_verdict_serde_roundtrip() {
             rule: CompatRule::SpecDigestValid,
             reason: "bad digest".to_string(),
         }],
+        warnings: vec![CompatWarning {
+            rule: CompatRule::RequireField {
+                field: "temperature".to_string(),
+                deprecated_since: Some("1.4.0".to_string()),
+            },
+            reason: "field 'temperature' was removed (deprecated since 1.4.0)".to_string(),
+        }],
     };
     let json = serde_json::to_string(&verdict).expect("serialize");
     let deserialized: CompatVerdict = serde_json::from_str(&json).expect("deserialize");
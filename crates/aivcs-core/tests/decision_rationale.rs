@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use aivcs_core::{
+    evaluate_gate_recorded, ControlledReplanDecision, DecisionRecorder, EvalReport, GateRule,
+    GateRuleSet, GateVerdict, ReplanDecision, ReplanReason, ReplanSuppressionReason, SurrealHandle,
+    Violation,
+};
+
+#[tokio::test]
+async fn replan_decision_is_recorded_with_inputs_and_rationale() {
+    let handle = Arc::new(SurrealHandle::setup_db().await.unwrap());
+    let recorder = DecisionRecorder::with_default_config(handle);
+
+    let approved = ControlledReplanDecision {
+        should_replan: true,
+        policy_decision: ReplanDecision {
+            should_replan: true,
+            reasons: vec![ReplanReason::FailedTasks { count: 2 }],
+        },
+        suppression: None,
+    };
+    let suppressed = ControlledReplanDecision {
+        should_replan: false,
+        policy_decision: approved.policy_decision.clone(),
+        suppression: Some(ReplanSuppressionReason::WithinMinInterval {
+            elapsed_minutes: 3,
+            min_interval_minutes: 30,
+        }),
+    };
+    recorder
+        .record_replan("commit-1", "plan-a", &approved)
+        .await
+        .unwrap();
+    recorder
+        .record_replan("commit-2", "plan-a", &suppressed)
+        .await
+        .unwrap();
+
+    let history = recorder
+        .get_decision_history("replan:plan-a", 10)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 2);
+
+    let replan = history.iter().find(|d| d.action == "replan").unwrap();
+    assert_eq!(replan.commit_id, "commit-1");
+    assert!(replan.rationale.contains("failed_tasks"));
+    assert_eq!(replan.alternatives, vec!["continue".to_string()]);
+    let inputs = replan.inputs.as_ref().expect("inputs recorded");
+    assert_eq!(inputs["policy_decision"]["reasons"][0]["count"], 2);
+
+    let held = history.iter().find(|d| d.action == "suppressed").unwrap();
+    assert!(held.rationale.contains("3 min since last replan"));
+}
+
+#[tokio::test]
+async fn gate_verdict_is_recorded_under_its_subject() {
+    let handle = Arc::new(SurrealHandle::setup_db().await.unwrap());
+    let recorder = DecisionRecorder::with_default_config(handle);
+
+    let verdict = GateVerdict {
+        violations: vec![Violation {
+            rule: GateRule::MinPassRate,
+            reason: "pass rate 0.50 below 0.95".to_string(),
+        }],
+    };
+    recorder
+        .record_gate_verdict("commit-1", "release-v1", &verdict)
+        .await
+        .unwrap();
+
+    let history = recorder
+        .get_decision_history("gate:release-v1", 10)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].action, "fail");
+    assert_eq!(history[0].rationale, "pass rate 0.50 below 0.95");
+    assert!(history[0].inputs.is_some());
+}
+
+#[tokio::test]
+async fn gate_evaluation_records_its_verdict_when_given_a_recorder() {
+    let handle = Arc::new(SurrealHandle::setup_db().await.unwrap());
+    let recorder = DecisionRecorder::with_default_config(handle);
+    let report = EvalReport {
+        case_results: vec![],
+        pass_rate: 0.5,
+        baseline_pass_rate: None,
+    };
+    let rule_set = GateRuleSet::standard();
+
+    let unrecorded = evaluate_gate_recorded(&rule_set, &report, None, "commit-1", "merge")
+        .await
+        .unwrap();
+    let recorded = evaluate_gate_recorded(&rule_set, &report, Some(&recorder), "commit-1", "merge")
+        .await
+        .unwrap();
+    assert_eq!(unrecorded, recorded);
+    assert!(!recorded.passed());
+
+    let history = recorder
+        .get_decision_history("gate:merge", 10)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].action, "fail");
+    assert_eq!(history[0].alternatives, vec!["pass".to_string()]);
+}
@@ -1,7 +1,7 @@
 use aivcs_core::deploy::deploy_by_digest;
 use aivcs_core::domain::agent_spec::AgentSpec;
 use aivcs_core::domain::error::AivcsError;
-use aivcs_core::{replay_run, DeployByDigestRunner};
+use aivcs_core::{replay_run, DeployByDigestRunner, MemoryCasStore};
 use chrono::{DateTime, Utc};
 use oxidized_state::fakes::{MemoryReleaseRegistry, MemoryRunLedger};
 use oxidized_state::storage_traits::{ReleaseRegistry, RunLedger, RunStatus};
@@ -37,6 +37,7 @@ async fn setup_registry(agent_name: &str, seed: &str) -> MemoryReleaseRegistry {
         version_label: None,
         promoted_by: "ci".to_string(),
         notes: None,
+        ..Default::default()
     };
     registry
         .promote(agent_name, &digest, metadata)
@@ -56,10 +57,10 @@ async fn deployed_digest_matches_replay_golden() {
     let ledger_b = MemoryRunLedger::new();
     let ts = Some(fixed_timestamp());
 
-    let result_a = deploy_by_digest(&registry, &ledger_a, "agent-a", ts)
+    let result_a = deploy_by_digest(&registry, &ledger_a, &MemoryCasStore::new(), "agent-a", ts)
         .await
         .expect("deploy_a");
-    let result_b = deploy_by_digest(&registry, &ledger_b, "agent-a", ts)
+    let result_b = deploy_by_digest(&registry, &ledger_b, &MemoryCasStore::new(), "agent-a", ts)
         .await
         .expect("deploy_b");
 
@@ -75,9 +76,15 @@ async fn deploy_returns_correct_spec_digest() {
     let registry = setup_registry("agent-b", "v1").await;
     let ledger = MemoryRunLedger::new();
 
-    let result = deploy_by_digest(&registry, &ledger, "agent-b", Some(fixed_timestamp()))
-        .await
-        .expect("deploy");
+    let result = deploy_by_digest(
+        &registry,
+        &ledger,
+        &MemoryCasStore::new(),
+        "agent-b",
+        Some(fixed_timestamp()),
+    )
+    .await
+    .expect("deploy");
 
     assert_eq!(result.spec_digest, spec.spec_digest);
 }
@@ -87,9 +94,15 @@ async fn deploy_run_status_completed() {
     let registry = setup_registry("agent-c", "v1").await;
     let ledger = MemoryRunLedger::new();
 
-    let result = deploy_by_digest(&registry, &ledger, "agent-c", Some(fixed_timestamp()))
-        .await
-        .expect("deploy");
+    let result = deploy_by_digest(
+        &registry,
+        &ledger,
+        &MemoryCasStore::new(),
+        "agent-c",
+        Some(fixed_timestamp()),
+    )
+    .await
+    .expect("deploy");
 
     assert_eq!(result.summary.status, RunStatus::Completed);
 }
@@ -99,9 +112,15 @@ async fn deploy_emits_three_events() {
     let registry = setup_registry("agent-d", "v1").await;
     let ledger = MemoryRunLedger::new();
 
-    let result = deploy_by_digest(&registry, &ledger, "agent-d", Some(fixed_timestamp()))
-        .await
-        .expect("deploy");
+    let result = deploy_by_digest(
+        &registry,
+        &ledger,
+        &MemoryCasStore::new(),
+        "agent-d",
+        Some(fixed_timestamp()),
+    )
+    .await
+    .expect("deploy");
 
     // DeployByDigestRunner emits 3 events
     assert_eq!(result.summary.event_count, 3);
@@ -112,9 +131,15 @@ async fn deploy_no_release_returns_error() {
     let registry = MemoryReleaseRegistry::new(); // no releases
     let ledger = MemoryRunLedger::new();
 
-    let err = deploy_by_digest(&registry, &ledger, "nonexistent-agent", None)
-        .await
-        .unwrap_err();
+    let err = deploy_by_digest(
+        &registry,
+        &ledger,
+        &MemoryCasStore::new(),
+        "nonexistent-agent",
+        None,
+    )
+    .await
+    .unwrap_err();
 
     match err {
         AivcsError::ReleaseConflict(msg) => {
@@ -129,9 +154,15 @@ async fn deploy_events_contain_spec_digest() {
     let registry = setup_registry("agent-f", "v1").await;
     let ledger = MemoryRunLedger::new();
 
-    let result = deploy_by_digest(&registry, &ledger, "agent-f", Some(fixed_timestamp()))
-        .await
-        .expect("deploy");
+    let result = deploy_by_digest(
+        &registry,
+        &ledger,
+        &MemoryCasStore::new(),
+        "agent-f",
+        Some(fixed_timestamp()),
+    )
+    .await
+    .expect("deploy");
 
     let events = ledger.get_events(&result.run_id).await.expect("get_events");
     assert_eq!(events[0].kind, "deploy_started");
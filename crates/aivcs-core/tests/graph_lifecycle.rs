@@ -89,6 +89,7 @@ async fn happy_path_two_node_lifecycle() {
             final_state_digest: None,
             duration_ms: 100,
             success: true,
+            usage: None,
         })
         .await
         .expect("finish_ok");
@@ -163,6 +164,7 @@ async fn failure_path_node_failed() {
             final_state_digest: None,
             duration_ms: 50,
             success: false,
+            usage: None,
         })
         .await
         .expect("finish_err");
@@ -0,0 +1,77 @@
+//! Blame attributes a memory key to the commit that last changed it
+
+use aivcs_core::{blame_memory, CommitId, CommitRecord, MemoryRecord, SurrealHandle};
+
+/// Save a linear chain of commits, one per `(author, content)` entry, each
+/// holding the memory key `plan` with that content. Returns the hashes.
+async fn save_chain(handle: &SurrealHandle, entries: &[(&str, &str)]) -> Vec<String> {
+    let mut hashes: Vec<String> = Vec::new();
+    for (i, (author, content)) in entries.iter().enumerate() {
+        let id = CommitId::from_state(format!("blame-{i}").as_bytes());
+        let parents = hashes.last().cloned().into_iter().collect();
+        let commit = CommitRecord::new(id.clone(), parents, &format!("commit {i}"), author);
+        handle.save_commit(&commit).await.unwrap();
+        handle
+            .save_memory(&MemoryRecord::new(&id.hash, "plan", content))
+            .await
+            .unwrap();
+        handle
+            .save_memory(&MemoryRecord::new(&id.hash, "noise", &format!("n{i}")))
+            .await
+            .unwrap();
+        hashes.push(id.hash);
+    }
+    hashes
+}
+
+#[tokio::test]
+async fn test_blame_points_at_commit_that_changed_the_key() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let chain = save_chain(
+        &handle,
+        &[
+            ("alice", "draft plan"),
+            ("bob", "draft plan"),
+            ("carol", "final plan"),
+            ("dave", "final plan"),
+        ],
+    )
+    .await;
+
+    let entry = blame_memory(&handle, &chain[3], "plan")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(entry.commit_id, chain[2]);
+    assert_eq!(entry.author, "carol");
+    assert_eq!(entry.content, "final plan");
+
+    // Earlier in history the key traces back to the root commit
+    let entry = blame_memory(&handle, &chain[1], "plan")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(entry.commit_id, chain[0]);
+    assert_eq!(entry.author, "alice");
+
+    // A key that changes on every commit blames the starting commit
+    let entry = blame_memory(&handle, &chain[3], "noise")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(entry.commit_id, chain[3]);
+}
+
+#[tokio::test]
+async fn test_blame_missing_key_returns_none() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let chain = save_chain(&handle, &[("alice", "draft plan")]).await;
+
+    assert_eq!(
+        blame_memory(&handle, &chain[0], "absent").await.unwrap(),
+        None
+    );
+    assert!(blame_memory(&handle, "no-such-commit", "plan")
+        .await
+        .is_err());
+}
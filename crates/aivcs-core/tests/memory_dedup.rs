@@ -0,0 +1,39 @@
+//! Near-duplicate memory detection within a single commit
+
+use aivcs_core::{find_duplicate_memories, MemoryRecord, SurrealHandle};
+
+#[tokio::test]
+async fn test_near_identical_embedded_memories_are_flagged() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+
+    let memories = [
+        MemoryRecord::new("c1", "retry-policy", "retry three times with backoff")
+            .with_embedding(vec![0.9, 0.1, 0.4]),
+        MemoryRecord::new("c1", "retry-policy-copy", "retry 3x with backoff")
+            .with_embedding(vec![0.91, 0.1, 0.39]),
+        MemoryRecord::new("c1", "cache", "cache tool results").with_embedding(vec![0.0, 1.0, 0.0]),
+        MemoryRecord::new("c1", "unembedded", "retry three times with backoff"),
+        // Same vector on another commit must not be paired
+        MemoryRecord::new("c2", "retry-policy", "retry three times with backoff")
+            .with_embedding(vec![0.9, 0.1, 0.4]),
+    ];
+    for memory in &memories {
+        handle.save_memory(memory).await.unwrap();
+    }
+
+    let pairs = find_duplicate_memories(&handle, "c1", 0.95).await.unwrap();
+    assert_eq!(pairs.len(), 1, "{pairs:?}");
+    let (a, b, similarity) = &pairs[0];
+    assert_eq!(
+        (a.as_str(), b.as_str()),
+        ("retry-policy", "retry-policy-copy")
+    );
+    assert!(*similarity >= 0.95 && *similarity <= 1.0);
+
+    // A loose threshold also pairs the distinct memory, never the unembedded one
+    let loose = find_duplicate_memories(&handle, "c1", 0.0).await.unwrap();
+    assert_eq!(loose.len(), 3);
+    assert!(loose
+        .iter()
+        .all(|(a, b, _)| a != "unembedded" && b != "unembedded"));
+}
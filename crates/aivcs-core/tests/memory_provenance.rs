@@ -0,0 +1,83 @@
+//! Memories written during a run are traced back to the producing event
+
+use std::sync::Arc;
+
+use aivcs_core::domain::run::{Event, EventKind};
+use aivcs_core::recording::GraphRunRecorder;
+use aivcs_core::{
+    memory_provenance, save_memory_from_run, DecisionRecorder, MemoryRecord, MemorySource,
+    ProvenanceSourceType, SurrealHandle,
+};
+use oxidized_state::{fakes::MemoryRunLedger, ContentDigest, RunLedger, RunMetadata};
+use uuid::Uuid;
+
+#[tokio::test]
+async fn test_memory_written_during_run_records_source_run_and_seq() {
+    let handle = Arc::new(SurrealHandle::setup_db().await.unwrap());
+    let recorder_db = DecisionRecorder::with_default_config(handle.clone());
+    let ledger: Arc<dyn RunLedger> = Arc::new(MemoryRunLedger::new());
+    let metadata = RunMetadata {
+        git_sha: None,
+        agent_name: "provenance-agent".to_string(),
+        tags: serde_json::json!({}),
+        evaluation: Default::default(),
+    };
+    let recorder = GraphRunRecorder::start(ledger, &ContentDigest::from_bytes(b"spec"), metadata)
+        .await
+        .unwrap();
+
+    let run_uuid = Uuid::new_v4();
+    for (seq, kind) in [
+        (1, EventKind::GraphStarted),
+        (
+            2,
+            EventKind::ToolCalled {
+                tool_name: "search".to_string(),
+            },
+        ),
+        (
+            3,
+            EventKind::ToolReturned {
+                tool_name: "search".to_string(),
+            },
+        ),
+    ] {
+        recorder
+            .record(&Event::new(run_uuid, seq, kind, serde_json::json!({})))
+            .await
+            .unwrap();
+    }
+
+    let source = MemorySource::from_recorder(&recorder, ProvenanceSourceType::ToolOutput);
+    assert_eq!(source.seq, 3);
+    save_memory_from_run(
+        &recorder_db,
+        &MemoryRecord::new("commit-1", "findings", "three results"),
+        &source,
+    )
+    .await
+    .unwrap();
+    save_memory_from_run(
+        &recorder_db,
+        &MemoryRecord::new("commit-1", "summary", "one line"),
+        &MemorySource::new("other-run", 7, ProvenanceSourceType::LlmGenerated),
+    )
+    .await
+    .unwrap();
+
+    let origins = memory_provenance(&handle, "commit-1", "findings")
+        .await
+        .unwrap();
+    assert_eq!(origins.len(), 1);
+    assert_eq!(origins[0].run_id, recorder.run_id().to_string());
+    assert_eq!(origins[0].seq, 3);
+    assert_eq!(origins[0].source_type, "tool_output");
+    let saved = handle.get_memories("commit-1").await.unwrap();
+    assert_eq!(saved.len(), 2);
+
+    // Same key at another commit has no provenance of its own
+    let elsewhere = memory_provenance(&handle, "commit-2", "findings")
+        .await
+        .unwrap();
+    assert!(elsewhere.is_empty());
+}
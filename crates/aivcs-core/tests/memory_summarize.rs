@@ -0,0 +1,121 @@
+//! Summarizing compaction of a commit's memories into a new commit
+
+use aivcs_core::memory::{compact_commit_memories, SummaryArbiter};
+use aivcs_core::memory_context::CompactionPolicy;
+use aivcs_core::{CommitId, CommitRecord, MemoryRecord, SurrealHandle};
+use async_trait::async_trait;
+use serde_json::json;
+
+/// Joins the cluster's contents, so summaries are predictable
+struct JoiningArbiter;
+
+#[async_trait]
+impl SummaryArbiter for JoiningArbiter {
+    async fn summarize(&self, cluster: &[MemoryRecord]) -> aivcs_core::Result<String> {
+        let contents: Vec<&str> = cluster.iter().map(|m| m.content.as_str()).collect();
+        Ok(contents.join(" | "))
+    }
+}
+
+#[tokio::test]
+async fn test_two_clusters_compact_to_two_summaries() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let source = CommitId::from_state(b"summarize-source");
+    handle
+        .save_commit(&CommitRecord::new(source.clone(), vec![], "six", "agent"))
+        .await
+        .unwrap();
+    handle
+        .save_snapshot(&source, json!({ "step": 6 }))
+        .await
+        .unwrap();
+    for (key, embedding) in [
+        ("retry-a", vec![1.0, 0.0]),
+        ("cache-a", vec![0.0, 1.0]),
+        ("retry-b", vec![0.99, 0.1]),
+        ("cache-b", vec![0.1, 0.99]),
+        ("retry-c", vec![0.97, 0.2]),
+        ("cache-c", vec![0.2, 0.97]),
+    ] {
+        handle
+            .save_memory(&MemoryRecord::new(&source.hash, key, key).with_embedding(embedding))
+            .await
+            .unwrap();
+    }
+
+    let compaction = compact_commit_memories(
+        &handle,
+        &source.hash,
+        &CompactionPolicy::summarize(),
+        0.9,
+        &JoiningArbiter,
+        "agent",
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(compaction.source_count, 6);
+    assert_eq!(compaction.memory_count, 2);
+    assert_eq!(compaction.summarized_clusters, 2);
+
+    let mut summaries = handle
+        .get_memories(&compaction.commit_id.hash)
+        .await
+        .unwrap();
+    summaries.sort_by(|a, b| a.key.cmp(&b.key));
+    let sources: Vec<_> = summaries
+        .iter()
+        .map(|m| (m.key.as_str(), m.metadata["summarized_from"].clone()))
+        .collect();
+    assert_eq!(
+        sources,
+        [
+            ("cache-a", json!(["cache-a", "cache-b", "cache-c"])),
+            ("retry-a", json!(["retry-a", "retry-b", "retry-c"])),
+        ]
+    );
+    assert_eq!(summaries[1].content, "retry-a | retry-b | retry-c");
+
+    let commit = handle
+        .get_commit(&compaction.commit_id.hash)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(commit.parent_ids, vec![source.hash.clone()]);
+    let snapshot = handle
+        .load_snapshot(&compaction.commit_id.hash)
+        .await
+        .unwrap();
+    assert_eq!(snapshot.state, json!({ "step": 6 }));
+    assert_eq!(handle.get_memories(&source.hash).await.unwrap().len(), 6);
+
+    // Only the summarize strategy compacts a commit
+    let err = compact_commit_memories(
+        &handle,
+        &source.hash,
+        &CompactionPolicy::keep_recent(1),
+        0.9,
+        &JoiningArbiter,
+        "agent",
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("summarize strategy"), "{err}");
+
+    // Limits the summarize strategy would ignore are rejected
+    let limited = CompactionPolicy {
+        max_age_days: Some(30),
+        ..CompactionPolicy::summarize()
+    };
+    let err = compact_commit_memories(
+        &handle,
+        &source.hash,
+        &limited,
+        0.9,
+        &JoiningArbiter,
+        "agent",
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("max_age_days"), "{err}");
+}
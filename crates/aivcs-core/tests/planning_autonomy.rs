@@ -1,8 +1,11 @@
 use std::collections::{BTreeMap, BTreeSet};
 
 use aivcs_core::{
-    compute_progress, decompose_goal_to_dag, evaluate_replan, schedule_next_ready_tasks, EpicPlan,
-    GoalPlan, PlanTask, PlanTaskStatus, ReplanPolicy, SchedulerConstraints, TaskPlan,
+    build_dag_from_plan, compute_progress, decompose_goal_to_dag, evaluate_replan,
+    evaluate_replan_with_controls, schedule_next_ready_tasks, EpicPlan, GoalPlan,
+    HeuristicDecomposer, LlmDecomposer, MockDecomposer, PlanTask, PlanTaskStatus, PlannerModel,
+    PlanningError, RecoveryControls, ReplanControlState, ReplanPolicy, ReplanSuppressionReason,
+    SchedulerConstraints, SurrealHandle, TaskPlan,
 };
 use chrono::{Duration, Utc};
 
@@ -34,7 +37,7 @@ fn complex_objective_decomposes_into_executable_dag() {
         ],
     };
 
-    let dag = decompose_goal_to_dag(&goal).expect("decompose");
+    let dag = build_dag_from_plan(&goal).expect("decompose");
     dag.validate().expect("valid dag");
 
     assert_eq!(dag.tasks.len(), 4);
@@ -46,6 +49,103 @@ fn complex_objective_decomposes_into_executable_dag() {
         .contains(&"t1".to_string()));
 }
 
+struct CannedModel(&'static str);
+
+impl PlannerModel for CannedModel {
+    fn complete(&self, _prompt: &str) -> Result<String, PlanningError> {
+        Ok(self.0.to_string())
+    }
+}
+
+#[test]
+fn mock_decomposer_yields_acyclic_dag() {
+    let mock = MockDecomposer::new(vec![
+        mk_task("t1", &[]),
+        mk_task("t2", &["t1"]),
+        mk_task("t3", &["t1", "t2"]),
+    ]);
+
+    let dag = decompose_goal_to_dag(&mock, "ship the thing").expect("decompose");
+    assert!(dag.validate().is_ok());
+    assert_eq!(dag.objective, "ship the thing");
+    assert_eq!(dag.tasks.len(), 3);
+
+    let again = decompose_goal_to_dag(&mock, "ship the thing").expect("decompose");
+    assert_eq!(dag.goal_id, again.goal_id);
+}
+
+#[test]
+fn heuristic_decomposer_chains_goal_steps() {
+    let goal = "1. scaffold crate\n2. add storage layer; wire CLI then write docs";
+    let dag = decompose_goal_to_dag(&HeuristicDecomposer, goal).expect("decompose");
+    assert!(dag.validate().is_ok());
+
+    let titles: Vec<&str> = dag.tasks.values().map(|t| t.title.as_str()).collect();
+    assert_eq!(
+        titles,
+        vec![
+            "scaffold crate",
+            "add storage layer",
+            "wire CLI",
+            "write docs"
+        ]
+    );
+    assert!(dag.tasks["t1"].depends_on.is_empty());
+    assert_eq!(dag.tasks["t4"].depends_on, vec!["t3".to_string()]);
+
+    assert_eq!(
+        decompose_goal_to_dag(&HeuristicDecomposer, "   "),
+        Err(PlanningError::EmptyGoal)
+    );
+}
+
+#[test]
+fn llm_decomposer_parses_model_tasks() {
+    let model = CannedModel(
+        r#"```json
+{"tasks": [
+  {"id": "design", "title": "design api", "depends_on": [], "estimate_hours": 2},
+  {"id": "impl", "title": "implement", "depends_on": ["design"], "estimate_hours": 6}
+]}
+```"#,
+    );
+    let dag = decompose_goal_to_dag(&LlmDecomposer::new(model), "build an api").expect("dag");
+    assert!(dag.validate().is_ok());
+    assert_eq!(dag.tasks["impl"].depends_on, vec!["design".to_string()]);
+    assert_eq!(dag.tasks["impl"].estimate_hours, 6);
+}
+
+#[test]
+fn llm_decomposer_rejects_cycles_and_dangling_deps() {
+    let cyclic = CannedModel(
+        r#"{"tasks": [
+  {"id": "a", "title": "a", "depends_on": ["b"], "estimate_hours": 1},
+  {"id": "b", "title": "b", "depends_on": ["a"], "estimate_hours": 1}
+]}"#,
+    );
+    assert_eq!(
+        decompose_goal_to_dag(&LlmDecomposer::new(cyclic), "loop"),
+        Err(PlanningError::CycleDetected)
+    );
+
+    let dangling = CannedModel(
+        r#"{"tasks": [{"id": "a", "title": "a", "depends_on": ["ghost"], "estimate_hours": 1}]}"#,
+    );
+    assert_eq!(
+        decompose_goal_to_dag(&LlmDecomposer::new(dangling), "dangling"),
+        Err(PlanningError::MissingDependency {
+            task_id: "a".to_string(),
+            missing_dependency: "ghost".to_string(),
+        })
+    );
+
+    let garbage = CannedModel("sure, here is a plan!");
+    assert!(matches!(
+        decompose_goal_to_dag(&LlmDecomposer::new(garbage), "garbage"),
+        Err(PlanningError::InvalidPlannerOutput(_))
+    ));
+}
+
 #[test]
 fn scheduler_respects_dependencies_and_constraints() {
     let now = Utc::now();
@@ -69,6 +169,7 @@ fn scheduler_respects_dependencies_and_constraints() {
     let c = SchedulerConstraints {
         max_parallel: 1,
         blocked_tasks: BTreeSet::new(),
+        max_total_cost: None,
     };
     let ready = schedule_next_ready_tasks(&dag, &c).expect("schedule");
     assert_eq!(ready, vec!["t1".to_string()]);
@@ -78,11 +179,77 @@ fn scheduler_respects_dependencies_and_constraints() {
     let c2 = SchedulerConstraints {
         max_parallel: 2,
         blocked_tasks: BTreeSet::from(["t3".to_string()]),
+        max_total_cost: None,
     };
     let ready2 = schedule_next_ready_tasks(&dag2, &c2).expect("schedule2");
     assert_eq!(ready2, vec!["t2".to_string()]);
 }
 
+fn costed_task(id: &str, deps: &[&str], cost: u32, priority: i32) -> PlanTask {
+    let mut task = PlanTask::pending(id, deps.iter().map(|s| s.to_string()).collect(), Utc::now());
+    task.cost = cost;
+    task.priority = priority;
+    task
+}
+
+fn dag_of(tasks: Vec<PlanTask>) -> aivcs_core::ExecutionDag {
+    aivcs_core::ExecutionDag {
+        goal_id: "g".to_string(),
+        objective: "obj".to_string(),
+        tasks: tasks.into_iter().map(|t| (t.id.clone(), t)).collect(),
+    }
+}
+
+#[test]
+fn scheduler_enforces_cost_budget_and_running_tasks() {
+    let mut running = costed_task("run", &[], 4, 0);
+    running.status = PlanTaskStatus::InProgress;
+    let dag = dag_of(vec![
+        running,
+        costed_task("big", &[], 7, 10),
+        costed_task("mid", &[], 3, 5),
+        costed_task("small", &[], 2, 1),
+        costed_task("waits", &["run"], 0, 100),
+    ]);
+
+    let c = SchedulerConstraints {
+        max_parallel: 3,
+        max_total_cost: Some(10),
+        ..Default::default()
+    };
+    // 6 units of cost and 2 slots are left once "run" is accounted for; "big"
+    // does not fit, and "waits" is blocked on an unfinished dependency.
+    let ready = schedule_next_ready_tasks(&dag, &c).expect("schedule");
+    assert_eq!(ready, vec!["mid".to_string(), "small".to_string()]);
+
+    let unbounded = SchedulerConstraints {
+        max_parallel: 3,
+        ..Default::default()
+    };
+    let ready = schedule_next_ready_tasks(&dag, &unbounded).expect("schedule");
+    assert_eq!(ready, vec!["big".to_string(), "mid".to_string()]);
+}
+
+#[test]
+fn scheduler_prefers_priority_then_id() {
+    let dag = dag_of(vec![
+        costed_task("a", &[], 1, 0),
+        costed_task("b", &[], 1, 2),
+        costed_task("c", &[], 1, 2),
+        costed_task("d", &[], 1, 1),
+    ]);
+
+    let c = SchedulerConstraints {
+        max_parallel: 3,
+        ..Default::default()
+    };
+    let ready = schedule_next_ready_tasks(&dag, &c).expect("schedule");
+    assert_eq!(
+        ready,
+        vec!["b".to_string(), "c".to_string(), "d".to_string()]
+    );
+}
+
 #[test]
 fn progress_reporting_matches_execution_reality() {
     let now = Utc::now();
@@ -115,9 +282,35 @@ fn progress_reporting_matches_execution_reality() {
     assert_eq!(report.in_progress_tasks, 1);
     assert_eq!(report.blocked_tasks, 1);
     assert_eq!(report.completion_ratio, 1.0 / 3.0);
+    assert_eq!(report.weighted_completion_ratio, report.completion_ratio);
     assert_eq!(report.blockers, vec!["waiting on API key".to_string()]);
 }
 
+#[test]
+fn weighted_progress_reflects_skewed_task_sizes() {
+    let mut tasks: Vec<PlanTask> = (1..=9)
+        .map(|i| {
+            let mut t = costed_task(&format!("small{i}"), &[], 0, 0);
+            t.status = PlanTaskStatus::Done;
+            t
+        })
+        .collect();
+    let mut huge = costed_task("huge", &[], 0, 0);
+    huge.status = PlanTaskStatus::InProgress;
+    tasks.push(huge);
+
+    let unweighted = compute_progress(&dag_of(tasks.clone()));
+    assert_eq!(unweighted.completion_ratio, 0.9);
+    assert_eq!(unweighted.weighted_completion_ratio, 0.9);
+
+    for t in tasks.iter_mut() {
+        t.weight = Some(if t.id == "huge" { 91.0 } else { 1.0 });
+    }
+    let weighted = compute_progress(&dag_of(tasks));
+    assert_eq!(weighted.completion_ratio, 0.9);
+    assert!((weighted.weighted_completion_ratio - 0.09).abs() < 1e-6);
+}
+
 #[test]
 fn replans_trigger_automatically_on_drift_failure_and_blockers() {
     let now = Utc::now();
@@ -158,3 +351,114 @@ fn replans_trigger_automatically_on_drift_failure_and_blockers() {
     assert!(decision.should_replan);
     assert!(!decision.reasons.is_empty());
 }
+
+#[test]
+fn replan_hysteresis_suppresses_oscillation() {
+    let start = Utc::now();
+    let policy = ReplanPolicy {
+        min_confidence: 0.6,
+        max_blocked_ratio: 1.0,
+        trigger_on_failure: false,
+        max_stale_hours: 1_000,
+    };
+    let controls = RecoveryControls {
+        min_replan_interval_minutes: 30,
+        min_confidence_delta: 0.1,
+    };
+    let mut state = ReplanControlState::default();
+
+    let dag_at = |confidence: f32| {
+        let mut task = PlanTask::pending("t1", vec![], start);
+        task.confidence = confidence;
+        dag_of(vec![task])
+    };
+
+    // First dip below the policy threshold replans.
+    let d = evaluate_replan_with_controls(&dag_at(0.5), &policy, &controls, &mut state, start);
+    assert!(d.should_replan);
+    assert_eq!(d.suppression, None);
+
+    // Recovery above threshold: nothing to do.
+    let t = start + Duration::minutes(5);
+    let d = evaluate_replan_with_controls(&dag_at(0.65), &policy, &controls, &mut state, t);
+    assert!(!d.should_replan);
+    assert_eq!(d.suppression, None);
+
+    // Dips again inside the interval: policy wants a replan, controls block it.
+    let t = start + Duration::minutes(10);
+    let d = evaluate_replan_with_controls(&dag_at(0.55), &policy, &controls, &mut state, t);
+    assert!(d.policy_decision.should_replan);
+    assert!(!d.should_replan);
+    assert!(matches!(
+        d.suppression,
+        Some(ReplanSuppressionReason::WithinMinInterval {
+            elapsed_minutes: 10,
+            ..
+        })
+    ));
+
+    // Past the interval but confidence barely moved since the last replan.
+    let t = start + Duration::minutes(45);
+    let d = evaluate_replan_with_controls(&dag_at(0.52), &policy, &controls, &mut state, t);
+    assert!(!d.should_replan);
+    assert!(matches!(
+        d.suppression,
+        Some(ReplanSuppressionReason::ConfidenceDeltaTooSmall { .. })
+    ));
+    assert_eq!(state.suppressed_count, 2);
+
+    // A real drop gets through.
+    let t = start + Duration::minutes(50);
+    let d = evaluate_replan_with_controls(&dag_at(0.3), &policy, &controls, &mut state, t);
+    assert!(d.should_replan);
+    assert_eq!(state.last_replan_at, Some(t));
+}
+
+#[tokio::test]
+async fn plan_roundtrips_through_surreal_with_status_updates() {
+    let handle = SurrealHandle::setup_db().await.expect("db");
+    let mock = MockDecomposer::new(vec![
+        mk_task("t1", &[]),
+        mk_task("t2", &["t1"]),
+        mk_task("t3", &["t1", "t2"]),
+    ]);
+    let dag = decompose_goal_to_dag(&mock, "persist me").expect("dag");
+
+    let (plan, tasks) = dag.to_plan_records().expect("records");
+    handle.save_plan(&plan, &tasks).await.expect("save");
+
+    handle
+        .update_task_status(
+            &dag.goal_id,
+            "t1",
+            serde_json::to_value(PlanTaskStatus::Done).unwrap(),
+        )
+        .await
+        .expect("update");
+
+    let (plan, tasks) = handle
+        .load_plan(&dag.goal_id)
+        .await
+        .expect("load")
+        .expect("plan exists");
+    let reloaded = aivcs_core::ExecutionDag::from_plan_records(plan, tasks).expect("rebuild");
+
+    assert_eq!(reloaded.objective, "persist me");
+    assert_eq!(reloaded.tasks["t1"].status, PlanTaskStatus::Done);
+    assert_eq!(reloaded.tasks["t2"].status, PlanTaskStatus::Pending);
+    assert_eq!(
+        reloaded.tasks["t3"].depends_on,
+        vec!["t1".to_string(), "t2".to_string()]
+    );
+
+    let c = SchedulerConstraints {
+        max_parallel: 4,
+        ..Default::default()
+    };
+    assert_eq!(
+        schedule_next_ready_tasks(&reloaded, &c).expect("schedule"),
+        vec!["t2".to_string()]
+    );
+
+    assert!(handle.load_plan("missing").await.expect("load").is_none());
+}
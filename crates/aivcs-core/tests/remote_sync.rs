@@ -0,0 +1,268 @@
+//! Push/pull between two in-memory repositories acting as local and remote
+
+use aivcs_core::{
+    pull_branch, push_branch, BranchProtection, BranchRecord, CasStore, CommitId, CommitRecord,
+    FsCasStore, SurrealHandle,
+};
+use serde_json::json;
+
+async fn commit(
+    handle: &SurrealHandle,
+    cas: &FsCasStore,
+    state: serde_json::Value,
+    parent: Option<&str>,
+    branch: &str,
+) -> String {
+    let bytes = serde_json::to_vec(&state).unwrap();
+    let digest = cas.put(&bytes).unwrap();
+    let id = CommitId::new(None, &digest.to_hex(), None);
+    handle.save_snapshot(&id, state).await.unwrap();
+    let parents = parent.map(|p| vec![p.to_string()]).unwrap_or_default();
+    handle
+        .save_commit(&CommitRecord::new(id.clone(), parents, "step", "test"))
+        .await
+        .unwrap();
+    if let Some(p) = parent {
+        handle.save_commit_graph_edge(&id.hash, p).await.unwrap();
+    }
+    handle
+        .save_branch(&BranchRecord::new(branch, &id.hash, branch == "main"))
+        .await
+        .unwrap();
+    id.hash
+}
+
+struct Repo {
+    handle: SurrealHandle,
+    cas: FsCasStore,
+}
+
+async fn repo(dir: &tempfile::TempDir, name: &str) -> Repo {
+    Repo {
+        handle: SurrealHandle::setup_db().await.unwrap(),
+        cas: FsCasStore::new(dir.path().join(name)).unwrap(),
+    }
+}
+
+#[tokio::test]
+async fn test_push_then_pull_fast_forwards() {
+    let dir = tempfile::tempdir().unwrap();
+    let local = repo(&dir, "local").await;
+    let remote = repo(&dir, "remote").await;
+
+    let c1 = commit(
+        &local.handle,
+        &local.cas,
+        json!({ "step": 1 }),
+        None,
+        "main",
+    )
+    .await;
+    let first = push_branch(
+        &local.handle,
+        &local.cas,
+        &remote.handle,
+        &remote.cas,
+        "main",
+        false,
+    )
+    .await
+    .unwrap();
+    assert_eq!(first.old_head, None);
+    assert_eq!(first.transferred.commits, 1);
+    assert_eq!(remote.handle.get_branch_head("main").await.unwrap(), c1);
+
+    // Only the new commit is shipped on the next push
+    let c2 = commit(
+        &local.handle,
+        &local.cas,
+        json!({ "step": 2 }),
+        Some(&c1),
+        "main",
+    )
+    .await;
+    let second = push_branch(
+        &local.handle,
+        &local.cas,
+        &remote.handle,
+        &remote.cas,
+        "main",
+        false,
+    )
+    .await
+    .unwrap();
+    assert_eq!(second.old_head.as_deref(), Some(c1.as_str()));
+    assert_eq!(second.transferred.commits, 1);
+    assert_eq!(second.transferred.blobs, 1);
+    assert!(!second.forced);
+    assert_eq!(remote.handle.get_branch_head("main").await.unwrap(), c2);
+    assert_eq!(
+        remote.handle.load_snapshot(&c2).await.unwrap().state,
+        json!({ "step": 2 })
+    );
+
+    // A fresh clone pulls everything; pulling again is a no-op
+    let clone = repo(&dir, "clone").await;
+    let pulled = pull_branch(
+        &clone.handle,
+        &clone.cas,
+        &remote.handle,
+        &remote.cas,
+        "main",
+        false,
+    )
+    .await
+    .unwrap();
+    assert_eq!(pulled.transferred.commits, 2);
+    assert_eq!(clone.handle.get_branch_head("main").await.unwrap(), c2);
+    let again = pull_branch(
+        &clone.handle,
+        &clone.cas,
+        &remote.handle,
+        &remote.cas,
+        "main",
+        false,
+    )
+    .await
+    .unwrap();
+    assert!(again.is_up_to_date());
+}
+
+#[tokio::test]
+async fn test_divergent_push_is_rejected_unless_forced() {
+    let dir = tempfile::tempdir().unwrap();
+    let local = repo(&dir, "local").await;
+    let remote = repo(&dir, "remote").await;
+
+    let base = commit(
+        &local.handle,
+        &local.cas,
+        json!({ "step": 0 }),
+        None,
+        "main",
+    )
+    .await;
+    push_branch(
+        &local.handle,
+        &local.cas,
+        &remote.handle,
+        &remote.cas,
+        "main",
+        false,
+    )
+    .await
+    .unwrap();
+
+    // Both sides commit on top of the shared base
+    let theirs = commit(
+        &remote.handle,
+        &remote.cas,
+        json!({ "side": "remote" }),
+        Some(&base),
+        "main",
+    )
+    .await;
+    let ours = commit(
+        &local.handle,
+        &local.cas,
+        json!({ "side": "local" }),
+        Some(&base),
+        "main",
+    )
+    .await;
+
+    let err = push_branch(
+        &local.handle,
+        &local.cas,
+        &remote.handle,
+        &remote.cas,
+        "main",
+        false,
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("non-fast-forward"), "{err}");
+    assert_eq!(remote.handle.get_branch_head("main").await.unwrap(), theirs);
+    assert!(remote.handle.get_commit(&ours).await.unwrap().is_none());
+
+    let forced = push_branch(
+        &local.handle,
+        &local.cas,
+        &remote.handle,
+        &remote.cas,
+        "main",
+        true,
+    )
+    .await
+    .unwrap();
+    assert!(forced.forced);
+    assert_eq!(forced.transferred.commits, 1);
+    assert_eq!(remote.handle.get_branch_head("main").await.unwrap(), ours);
+}
+
+#[tokio::test]
+async fn test_protected_branch_rejects_force_push() {
+    let dir = tempfile::tempdir().unwrap();
+    let local = repo(&dir, "local").await;
+    let remote = repo(&dir, "remote").await;
+
+    let base = commit(
+        &local.handle,
+        &local.cas,
+        json!({ "step": 0 }),
+        None,
+        "main",
+    )
+    .await;
+    push_branch(
+        &local.handle,
+        &local.cas,
+        &remote.handle,
+        &remote.cas,
+        "main",
+        false,
+    )
+    .await
+    .unwrap();
+    remote
+        .handle
+        .set_protection(
+            "main",
+            BranchProtection {
+                no_force_push: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    let theirs = commit(
+        &remote.handle,
+        &remote.cas,
+        json!({ "side": "remote" }),
+        Some(&base),
+        "main",
+    )
+    .await;
+    commit(
+        &local.handle,
+        &local.cas,
+        json!({ "side": "local" }),
+        Some(&base),
+        "main",
+    )
+    .await;
+
+    let err = push_branch(
+        &local.handle,
+        &local.cas,
+        &remote.handle,
+        &remote.cas,
+        "main",
+        true,
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("protected"), "{err}");
+    assert_eq!(remote.handle.get_branch_head("main").await.unwrap(), theirs);
+}
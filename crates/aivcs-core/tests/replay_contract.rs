@@ -67,6 +67,7 @@ async fn build_seeded_run(ledger: &dyn RunLedger) -> RunId {
         final_state_digest: None,
         duration_ms: 100,
         success: true,
+        usage: None,
     };
     ledger
         .complete_run(&run_id, summary)
@@ -149,6 +150,7 @@ async fn test_golden_digest_identical_inputs_match() {
                     final_state_digest: None,
                     duration_ms: 0,
                     success: true,
+                    usage: None,
                 },
             )
             .await
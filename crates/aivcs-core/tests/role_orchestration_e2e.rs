@@ -207,6 +207,7 @@ async fn test_e2e_replay_digest_is_deterministic() {
                     final_state_digest: None,
                     duration_ms: 50,
                     success: true,
+                    usage: None,
                 },
             )
             .await
@@ -55,6 +55,17 @@ impl RunLedger for FlakyCreateRunLedger {
         self.inner.create_run(spec_digest, metadata).await
     }
 
+    async fn create_run_with_id(
+        &self,
+        run_id: RunId,
+        spec_digest: &ContentDigest,
+        metadata: RunMetadata,
+    ) -> Result<RunId, StorageError> {
+        self.inner
+            .create_run_with_id(run_id, spec_digest, metadata)
+            .await
+    }
+
     async fn append_event(&self, run_id: &RunId, event: RunEvent) -> Result<(), StorageError> {
         self.inner.append_event(run_id, event).await
     }
@@ -85,6 +96,22 @@ impl RunLedger for FlakyCreateRunLedger {
     ) -> Result<Vec<RunRecord>, StorageError> {
         self.inner.list_runs(spec_digest).await
     }
+
+    async fn list_agent_runs(
+        &self,
+        agent_name: Option<&str>,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<Vec<RunRecord>, StorageError> {
+        self.inner.list_agent_runs(agent_name, since).await
+    }
+
+    async fn archive_run(&self, run_id: &RunId, archive_digest: &str) -> Result<(), StorageError> {
+        self.inner.archive_run(run_id, archive_digest).await
+    }
+
+    async fn archived_run_digest(&self, run_id: &RunId) -> Result<Option<String>, StorageError> {
+        self.inner.archived_run_digest(run_id).await
+    }
 }
 
 /// Stub executor: always succeeds with a canned output per role.
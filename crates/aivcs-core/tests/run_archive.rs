@@ -0,0 +1,94 @@
+//! Archiving old runs into CAS and replaying them from the archive
+
+use std::time::Duration;
+
+use aivcs_core::cas::memory::MemoryCasStore;
+use aivcs_core::{archive_runs, record_run, replay_run, replay_run_with_archive};
+use chrono::Utc;
+use oxidized_state::fakes::MemoryRunLedger;
+use oxidized_state::{ContentDigest, RunEvent, RunLedger, RunMetadata, RunStatus};
+use serde_json::json;
+
+fn metadata() -> RunMetadata {
+    RunMetadata {
+        git_sha: None,
+        agent_name: "archiver".to_string(),
+        tags: json!({}),
+        evaluation: Default::default(),
+    }
+}
+
+fn events(tool: &str) -> Vec<RunEvent> {
+    vec![
+        RunEvent {
+            seq: 1,
+            kind: "graph_started".to_string(),
+            payload: json!({ "graph_name": "g", "entry_point": "start" }),
+            timestamp: Utc::now(),
+        },
+        RunEvent {
+            seq: 2,
+            kind: "tool_called".to_string(),
+            payload: json!({ "tool_name": tool }),
+            timestamp: Utc::now(),
+        },
+    ]
+}
+
+#[tokio::test]
+async fn archived_run_leaves_ledger_and_replays_from_cas() {
+    let ledger = MemoryRunLedger::new();
+    let cas = MemoryCasStore::new();
+    let spec = ContentDigest::from_bytes(b"spec");
+
+    let old = record_run(&ledger, &spec, metadata(), events("search"))
+        .await
+        .unwrap();
+    let running = ledger.create_run(&spec, metadata()).await.unwrap();
+    let (events_before, summary_before) = replay_run(&ledger, &old.0).await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    let cutoff = Utc::now();
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    let recent = record_run(&ledger, &spec, metadata(), events("fetch"))
+        .await
+        .unwrap();
+
+    let report = archive_runs(&ledger, &cas, cutoff).await.unwrap();
+    assert_eq!(report.archived.len(), 1);
+    assert_eq!(report.archived[0].run_id, old.0);
+    assert_eq!(report.skipped_running, 1);
+
+    // Gone from the hot ledger; the others are untouched
+    assert!(ledger.get_run(&old).await.is_err());
+    assert!(replay_run(&ledger, &old.0).await.is_err());
+    assert_eq!(
+        ledger.get_run(&running).await.unwrap().status,
+        RunStatus::Running
+    );
+    assert!(ledger.get_run(&recent).await.is_ok());
+    assert_eq!(
+        ledger.archived_run_digest(&old).await.unwrap(),
+        Some(report.archived[0].digest.clone())
+    );
+
+    let (events_after, summary_after) = replay_run_with_archive(&ledger, &cas, &old.0)
+        .await
+        .unwrap();
+    assert_eq!(
+        serde_json::to_value(&events_after).unwrap(),
+        serde_json::to_value(&events_before).unwrap()
+    );
+    assert_eq!(summary_after.replay_digest, summary_before.replay_digest);
+    assert_eq!(summary_after.status, RunStatus::Completed);
+
+    // Runs still in the ledger replay as before
+    let (recent_events, _) = replay_run_with_archive(&ledger, &cas, &recent.0)
+        .await
+        .unwrap();
+    assert_eq!(recent_events[1].payload["tool_name"], "fetch");
+
+    // A second pass finds nothing new to archive
+    let again = archive_runs(&ledger, &cas, cutoff).await.unwrap();
+    assert!(again.archived.is_empty());
+}
@@ -0,0 +1,151 @@
+//! Token usage reported by run events is summed into the run summary and
+//! priced per run and per agent
+
+use std::sync::Arc;
+
+use aivcs_core::domain::run::{Event, EventKind};
+use aivcs_core::recording::GraphRunRecorder;
+use aivcs_core::{cost_report, record_run, run_cost, ModelPrice, PricingTable};
+use oxidized_state::{
+    fakes::MemoryRunLedger, ContentDigest, RunEvent, RunId, RunLedger, RunMetadata, RunSummary,
+};
+use uuid::Uuid;
+
+fn metadata(agent: &str) -> RunMetadata {
+    RunMetadata {
+        git_sha: None,
+        agent_name: agent.to_string(),
+        tags: serde_json::json!({}),
+        evaluation: Default::default(),
+    }
+}
+
+fn pricing() -> PricingTable {
+    PricingTable::default()
+        .with_model(
+            "big-model",
+            ModelPrice {
+                input_per_mtok: 3.0,
+                output_per_mtok: 15.0,
+            },
+        )
+        .with_model(
+            "small-model",
+            ModelPrice {
+                input_per_mtok: 0.5,
+                output_per_mtok: 1.5,
+            },
+        )
+}
+
+fn llm_call(model: &str, input: u64, output: u64) -> serde_json::Value {
+    serde_json::json!({
+        "usage": {"model": model, "input_tokens": input, "output_tokens": output},
+    })
+}
+
+#[tokio::test]
+async fn test_run_cost_from_recorded_usage_events() {
+    let ledger: Arc<dyn RunLedger> = Arc::new(MemoryRunLedger::new());
+    let recorder = GraphRunRecorder::start(
+        ledger.clone(),
+        &ContentDigest::from_bytes(b"spec"),
+        metadata("planner"),
+    )
+    .await
+    .unwrap();
+
+    let run_uuid = Uuid::new_v4();
+    let tool = |name: &str| EventKind::ToolCalled {
+        tool_name: name.to_string(),
+    };
+    for (seq, kind, payload) in [
+        (1, EventKind::GraphStarted, serde_json::json!({})),
+        (2, tool("llm"), llm_call("big-model", 1_000_000, 200_000)),
+        (
+            3,
+            tool("llm"),
+            llm_call("small-model", 2_000_000, 1_000_000),
+        ),
+        (4, tool("llm"), llm_call("big-model", 500_000, 0)),
+        (5, tool("llm"), llm_call("mystery-model", 10, 10)),
+        (6, tool("search"), serde_json::json!({})),
+    ] {
+        recorder
+            .record(&Event::new(run_uuid, seq, kind, payload))
+            .await
+            .unwrap();
+    }
+    let run_id = recorder.run_id().to_string();
+    recorder
+        .finish_ok(RunSummary {
+            total_events: 6,
+            final_state_digest: None,
+            duration_ms: 10,
+            success: true,
+            usage: None,
+        })
+        .await
+        .unwrap();
+
+    // Usage is stored with the run
+    let run = ledger.get_run(&RunId(run_id.clone())).await.unwrap();
+    let usage = run.summary.unwrap().usage.expect("usage stored in summary");
+    assert_eq!(usage.models["big-model"].input_tokens, 1_500_000);
+    assert_eq!(usage.models["big-model"].calls, 2);
+
+    let cost = run_cost(ledger.as_ref(), &run_id, &pricing())
+        .await
+        .unwrap();
+    assert_eq!(cost.agent_name, "planner");
+    assert_eq!(cost.cost.input_tokens, 3_500_010);
+    assert_eq!(cost.cost.output_tokens, 1_200_010);
+    // big: 1.5M in * $3 + 0.2M out * $15 = 4.5 + 3.0
+    // small: 2M in * $0.5 + 1M out * $1.5 = 1.0 + 1.5
+    assert!(
+        (cost.cost.cost_usd - 10.0).abs() < 1e-9,
+        "{}",
+        cost.cost.cost_usd
+    );
+    assert_eq!(cost.cost.unpriced_models, vec!["mystery-model".to_string()]);
+}
+
+#[tokio::test]
+async fn test_cost_report_sums_runs_of_one_agent() {
+    let ledger = MemoryRunLedger::new();
+    let spec = ContentDigest::from_bytes(b"spec");
+    let events = |model: &str, input: u64, output: u64| {
+        vec![RunEvent {
+            seq: 1,
+            kind: "tool_called".to_string(),
+            payload: llm_call(model, input, output),
+            timestamp: chrono::Utc::now(),
+        }]
+    };
+
+    for (agent, input) in [("planner", 1_000_000), ("planner", 3_000_000), ("coder", 7)] {
+        record_run(
+            &ledger,
+            &spec,
+            metadata(agent),
+            events("small-model", input, 0),
+        )
+        .await
+        .unwrap();
+    }
+
+    let since = chrono::Utc::now() - chrono::Duration::days(30);
+    let report = cost_report(&ledger, Some("planner"), Some(since), &pricing())
+        .await
+        .unwrap();
+    assert_eq!(report.runs, 2);
+    assert_eq!(report.cost.input_tokens, 4_000_000);
+    assert!((report.cost.cost_usd - 2.0).abs() < 1e-9);
+
+    let future = chrono::Utc::now() + chrono::Duration::days(1);
+    let none = cost_report(&ledger, None, Some(future), &pricing())
+        .await
+        .unwrap();
+    assert_eq!(none.runs, 0);
+    assert_eq!(none.cost.cost_usd, 0.0);
+}
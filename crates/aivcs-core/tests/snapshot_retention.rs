@@ -0,0 +1,99 @@
+//! Pruning old snapshots while keeping commit history
+
+use aivcs_core::cas::{CasStore, Digest};
+use aivcs_core::commands::{self, SnapshotRequest};
+use aivcs_core::{
+    apply_snapshot_retention, MemoryCasStore, SnapshotRetentionPolicy, SurrealHandle,
+};
+use oxidized_state::StateError;
+use serde_json::json;
+
+/// Snapshot `count` states onto `main`, returning commit ids oldest first
+async fn snapshots(
+    handle: &SurrealHandle,
+    cas: &MemoryCasStore,
+    count: usize,
+    keyframe_interval: Option<u32>,
+) -> Vec<String> {
+    let mut commits = Vec::new();
+    for step in 0..count {
+        let request = SnapshotRequest {
+            state: json!({ "step": step, "notes": ["warm", "cache"] }).to_string(),
+            message: format!("step {step}"),
+            author: "agent".to_string(),
+            branch: "main".to_string(),
+            keyframe_interval,
+            ..Default::default()
+        };
+        let outcome = commands::snapshot(handle, cas, &request, None)
+            .await
+            .unwrap();
+        commits.push(outcome.commit.unwrap());
+    }
+    commits
+}
+
+#[tokio::test]
+async fn keeps_last_three_and_pinned_snapshots() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let cas = MemoryCasStore::new();
+    let commits = snapshots(&handle, &cas, 6, None).await;
+    let tagged = commits[1].clone();
+
+    let policy = SnapshotRetentionPolicy::keep_last(3).pin(tagged.clone());
+    let report = apply_snapshot_retention(&handle, &cas, &policy)
+        .await
+        .unwrap();
+
+    assert_eq!(report.pruned, vec![commits[0].clone(), commits[2].clone()]);
+    assert_eq!(report.cas_blobs_removed, 2);
+    for pruned in &report.pruned {
+        let err = handle.load_snapshot(pruned).await.unwrap_err();
+        assert!(
+            matches!(err, StateError::SnapshotPruned(ref id) if id == pruned),
+            "{err}"
+        );
+        let commit = handle.get_commit(pruned).await.unwrap().unwrap();
+        assert!(commit.snapshot_pruned);
+        let digest: Digest = commit.commit_id.state_hash.parse().unwrap();
+        assert!(!cas.exists(&digest).unwrap());
+    }
+    for kept in [&commits[1], &commits[3], &commits[4], &commits[5]] {
+        let commit = handle.get_commit(kept).await.unwrap().unwrap();
+        assert!(!commit.snapshot_pruned);
+        assert!(handle.load_snapshot(kept).await.is_ok());
+        let digest: Digest = commit.commit_id.state_hash.parse().unwrap();
+        assert!(cas.exists(&digest).unwrap());
+    }
+
+    // History is untouched, and a second pass finds nothing to do
+    let history = commands::log(&handle, "main", 10).await.unwrap();
+    assert_eq!(history.len(), 6);
+    let again = apply_snapshot_retention(&handle, &cas, &policy)
+        .await
+        .unwrap();
+    assert!(again.pruned.is_empty());
+}
+
+#[tokio::test]
+async fn kept_delta_snapshots_survive_pruning_their_base() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let cas = MemoryCasStore::new();
+    let commits = snapshots(&handle, &cas, 5, Some(10)).await;
+    let expected = handle.load_snapshot(&commits[4]).await.unwrap().state;
+
+    let report = apply_snapshot_retention(&handle, &cas, &SnapshotRetentionPolicy::keep_last(2))
+        .await
+        .unwrap();
+
+    assert_eq!(report.pruned, commits[..3].to_vec());
+    assert_eq!(report.rewritten_as_keyframes, vec![commits[3].clone()]);
+    assert_eq!(
+        handle.load_snapshot(&commits[4]).await.unwrap().state,
+        expected
+    );
+    assert_eq!(
+        handle.load_snapshot(&commits[3]).await.unwrap().state["step"],
+        3
+    );
+}
@@ -0,0 +1,150 @@
+use aivcs_core::{compare_specs_behavior, record_run, AivcsError, ToolCallChange};
+use chrono::Utc;
+use oxidized_state::fakes::MemoryRunLedger;
+use oxidized_state::{ContentDigest, RunEvent, RunMetadata};
+use serde_json::{json, Value};
+
+fn metadata(tags: Value) -> RunMetadata {
+    RunMetadata {
+        git_sha: None,
+        agent_name: "agent".to_string(),
+        tags,
+        evaluation: Default::default(),
+    }
+}
+
+/// A run that plans, then calls each tool in `tools` from the `act` node.
+fn run_events(tools: &[&str]) -> Vec<RunEvent> {
+    let mut kinds = vec![
+        ("node_entered", json!({ "node_id": "plan" })),
+        ("node_entered", json!({ "node_id": "act" })),
+    ];
+    kinds.extend(
+        tools
+            .iter()
+            .map(|tool| ("tool_called", json!({ "tool_name": tool }))),
+    );
+    kinds
+        .into_iter()
+        .enumerate()
+        .map(|(i, (kind, payload))| RunEvent {
+            seq: i as u64 + 1,
+            kind: kind.to_string(),
+            payload,
+            timestamp: Utc::now(),
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn reports_the_diverging_tool_call() {
+    let ledger = MemoryRunLedger::new();
+    let spec_a = ContentDigest::from_bytes(b"spec-v1");
+    let spec_b = ContentDigest::from_bytes(b"spec-v2");
+
+    let run_a = record_run(
+        &ledger,
+        &spec_a,
+        metadata(json!({})),
+        run_events(&["search", "fetch", "summarize"]),
+    )
+    .await
+    .unwrap();
+    let run_b = record_run(
+        &ledger,
+        &spec_b,
+        metadata(json!({})),
+        run_events(&["search", "translate", "summarize"]),
+    )
+    .await
+    .unwrap();
+
+    let diff = compare_specs_behavior(&ledger, &spec_a, &spec_b, None)
+        .await
+        .unwrap();
+    assert_eq!(diff.run_a, run_a.0);
+    assert_eq!(diff.run_b, run_b.0);
+    assert!(!diff.is_empty());
+    assert!(diff.node_paths.is_empty());
+
+    let mut changes: Vec<String> = diff
+        .tool_calls
+        .changes
+        .iter()
+        .map(|change| match change {
+            ToolCallChange::Added(call) => format!("+{}", call.tool_name),
+            ToolCallChange::Removed(call) => format!("-{}", call.tool_name),
+            other => panic!("unexpected change {:?}", other),
+        })
+        .collect();
+    changes.sort();
+    assert_eq!(changes, vec!["+translate", "-fetch"]);
+}
+
+#[tokio::test]
+async fn input_tag_pairs_runs_on_the_same_input() {
+    let ledger = MemoryRunLedger::new();
+    let spec_a = ContentDigest::from_bytes(b"spec-v1");
+    let spec_b = ContentDigest::from_bytes(b"spec-v2");
+    let tools = ["search", "summarize"];
+
+    let run_a = record_run(
+        &ledger,
+        &spec_a,
+        metadata(json!({ "input": "q1" })),
+        run_events(&tools),
+    )
+    .await
+    .unwrap();
+    record_run(
+        &ledger,
+        &spec_b,
+        metadata(json!({ "input": "q2" })),
+        run_events(&["fetch"]),
+    )
+    .await
+    .unwrap();
+    let run_b = record_run(
+        &ledger,
+        &spec_b,
+        metadata(json!({ "input": "q1" })),
+        run_events(&tools),
+    )
+    .await
+    .unwrap();
+
+    let diff = compare_specs_behavior(&ledger, &spec_a, &spec_b, Some("input"))
+        .await
+        .unwrap();
+    assert_eq!((diff.run_a, diff.run_b), (run_a.0, run_b.0));
+    assert!(diff.is_empty());
+
+    let err = compare_specs_behavior(&ledger, &spec_a, &spec_b, Some("dataset"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AivcsError::NoComparableRuns(_)));
+}
+
+#[tokio::test]
+async fn spec_without_finished_runs_is_an_error() {
+    let ledger = MemoryRunLedger::new();
+    let spec_a = ContentDigest::from_bytes(b"spec-v1");
+    record_run(
+        &ledger,
+        &spec_a,
+        metadata(json!({})),
+        run_events(&["search"]),
+    )
+    .await
+    .unwrap();
+
+    let err = compare_specs_behavior(
+        &ledger,
+        &spec_a,
+        &ContentDigest::from_bytes(b"spec-v2"),
+        None,
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, AivcsError::NoComparableRuns(_)));
+}
@@ -0,0 +1,72 @@
+//! Stash save/list/pop against an in-memory repository
+
+use aivcs_core::{stash_pop, stash_save, CasStore, Digest, MemoryCasStore, SurrealHandle};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_stash_save_list_pop_roundtrip() {
+    let cas = MemoryCasStore::new();
+    let handle = SurrealHandle::setup_db().await.unwrap();
+
+    let state = json!({ "step": 3, "scratch": ["a", "b"] });
+    let content = serde_json::to_string(&state).unwrap();
+    let saved = stash_save(&handle, &cas, "wip", &content, "half-done search")
+        .await
+        .unwrap();
+    assert_eq!(saved.name, "wip");
+    assert_eq!(saved.size_bytes, content.len() as u64);
+
+    let digest: Digest = saved.cas_digest.parse().unwrap();
+    assert!(cas.exists(&digest).unwrap());
+
+    let listed = handle.list_stashes().await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].message, "half-done search");
+
+    let (record, popped) = stash_pop(&handle, &cas, "wip").await.unwrap();
+    assert_eq!(record.cas_digest, saved.cas_digest);
+    assert_eq!(popped, state);
+
+    // Stashes never touch branch history
+    assert!(handle.list_branches().await.unwrap().is_empty());
+    assert!(handle.list_commits().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_popped_stash_is_removed() {
+    let cas = MemoryCasStore::new();
+    let handle = SurrealHandle::setup_db().await.unwrap();
+
+    stash_save(&handle, &cas, "a", r#"{"n":1}"#, "first")
+        .await
+        .unwrap();
+    stash_save(&handle, &cas, "b", r#"{"n":2}"#, "second")
+        .await
+        .unwrap();
+
+    stash_pop(&handle, &cas, "a").await.unwrap();
+
+    let names: Vec<String> = handle
+        .list_stashes()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|s| s.name)
+        .collect();
+    assert_eq!(names, vec!["b".to_string()]);
+    assert!(handle.get_stash("a").await.unwrap().is_none());
+    assert!(stash_pop(&handle, &cas, "a").await.is_err());
+}
+
+#[tokio::test]
+async fn test_stash_rejects_duplicate_names_and_invalid_json() {
+    let cas = MemoryCasStore::new();
+    let handle = SurrealHandle::setup_db().await.unwrap();
+
+    stash_save(&handle, &cas, "wip", "{}", "").await.unwrap();
+    assert!(stash_save(&handle, &cas, "wip", "{}", "").await.is_err());
+    assert!(stash_save(&handle, &cas, "bad", "not json", "")
+        .await
+        .is_err());
+    assert_eq!(handle.list_stashes().await.unwrap().len(), 1);
+}
@@ -1,5 +1,6 @@
 use aivcs_core::{
-    diff_run_states, diff_scoped_state, extract_last_checkpoint, StateDelta, CHECKPOINT_SAVED_KIND,
+    diff_run_states, diff_scoped_state, diff_state_subtree, extract_last_checkpoint, StateDelta,
+    CHECKPOINT_SAVED_KIND,
 };
 use chrono::Utc;
 use oxidized_state::RunEvent;
@@ -169,3 +170,38 @@ fn diff_run_states_no_checkpoints_produces_empty_diff() {
         "no checkpoints in either stream should produce empty diff"
     );
 }
+
+#[test]
+fn subtree_diff_reports_only_leaves_under_the_scope() {
+    let a = json!({
+        "model": "gpt-4",
+        "routing": {"default": "planner", "rules": [{"match": "sql", "to": "db"}]}
+    });
+    let b = json!({
+        "model": "gpt-5",
+        "routing": {"default": "planner", "rules": [{"match": "sql", "to": "sql-agent"}], "a/b": 1}
+    });
+
+    let diff = diff_state_subtree(&a, &b, "/routing");
+    let pointers: Vec<&str> = diff.deltas.iter().map(|d| d.pointer.as_str()).collect();
+    assert_eq!(pointers, ["/routing/a~1b", "/routing/rules/0/to"]);
+    assert_eq!(diff.deltas[0].before, Value::Null);
+    assert_eq!(diff.deltas[1].after, json!("sql-agent"));
+}
+
+#[test]
+fn subtree_diff_with_scope_missing_on_one_side() {
+    let a = json!({"model": "gpt-4"});
+    let b = json!({"model": "gpt-4", "routing": {"default": "planner"}});
+
+    let diff = diff_state_subtree(&a, &b, "/routing");
+    assert_eq!(
+        diff.deltas,
+        [StateDelta {
+            pointer: "/routing/default".to_string(),
+            before: Value::Null,
+            after: json!("planner"),
+        }]
+    );
+    assert!(diff_state_subtree(&a, &a, "/routing").is_empty());
+}
@@ -0,0 +1,80 @@
+//! Tool-call diffing of very long runs stays within a memory bound.
+//!
+//! Kept in its own test binary: the peak-tracking global allocator below
+//! observes every allocation in the process.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use aivcs_core::{diff_tool_calls, ToolCallChange};
+use chrono::Utc;
+use oxidized_state::RunEvent;
+use serde_json::json;
+
+struct PeakAlloc;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let now = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(now, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAlloc = PeakAlloc;
+
+const TOOLS: [&str; 4] = ["search", "fetch", "parse", "write"];
+
+fn tool_events(names: &[&str]) -> Vec<RunEvent> {
+    names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| RunEvent {
+            seq: i as u64 + 1,
+            kind: "tool_called".to_string(),
+            payload: json!({ "tool_name": name }),
+            timestamp: Utc::now(),
+        })
+        .collect()
+}
+
+#[test]
+fn long_runs_diff_in_bounded_memory() {
+    const CALLS: usize = 5_000;
+    let names_a: Vec<&str> = (0..CALLS).map(|i| TOOLS[(i * 7 + i / 3) % 4]).collect();
+    let mut names_b = names_a.clone();
+    names_b.insert(CALLS / 2, "translate");
+
+    let a = tool_events(&names_a);
+    let b = tool_events(&names_b);
+
+    let baseline = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    let diff = diff_tool_calls(&a, &b);
+    let peak = PEAK.load(Ordering::Relaxed) - baseline;
+
+    assert_eq!(diff.changes.len(), 1, "{:?}", diff.changes);
+    assert!(matches!(
+        &diff.changes[0],
+        ToolCallChange::Added(call) if call.tool_name == "translate"
+    ));
+
+    // A full DP table would need (CALLS + 1)^2 usizes (~200 MB) here.
+    let bound = 32 * 1024 * 1024;
+    assert!(
+        peak < bound,
+        "diff of {CALLS} calls peaked at {peak} bytes (bound {bound})"
+    );
+}
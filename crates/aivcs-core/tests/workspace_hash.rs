@@ -0,0 +1,84 @@
+//! Incremental workspace hashing behind the CI snapshot's `workspace_hash`
+
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+use aivcs_core::{compute_workspace_hash, WorkspaceHasher};
+
+/// Write `content` to `path` with an mtime old enough to be cacheable
+fn write_settled(path: &Path, content: &str, age_secs: u64) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, content).unwrap();
+    let file = std::fs::File::options().write(true).open(path).unwrap();
+    file.set_modified(SystemTime::now() - Duration::from_secs(age_secs))
+        .unwrap();
+}
+
+#[test]
+fn test_hash_is_stable_across_noop_runs_and_reuses_digests() {
+    let dir = tempfile::tempdir().unwrap();
+    write_settled(&dir.path().join("src/lib.rs"), "pub fn a() {}\n", 60);
+    write_settled(&dir.path().join("Cargo.toml"), "[package]\n", 60);
+
+    let mut hasher = WorkspaceHasher::new();
+    let first = hasher.hash(dir.path()).unwrap();
+    assert_eq!(hasher.files_read(), 2);
+
+    let second = hasher.hash(dir.path()).unwrap();
+    assert_eq!(second, first);
+    assert_eq!(hasher.files_read(), 0, "unchanged files are not re-read");
+
+    // A saved and reloaded cache gives the same hash without reading either
+    let cache = dir.path().join(".aivcs/workspace-hashes.json");
+    hasher.save(&cache).unwrap();
+    let mut reloaded = WorkspaceHasher::load(&cache);
+    assert_eq!(reloaded.hash(dir.path()).unwrap(), first);
+    assert_eq!(reloaded.files_read(), 0);
+
+    assert_eq!(compute_workspace_hash(dir.path()).unwrap(), first);
+}
+
+#[test]
+fn test_hash_changes_when_a_file_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let lib = dir.path().join("src/lib.rs");
+    write_settled(&lib, "pub fn a() {}\n", 60);
+
+    let mut hasher = WorkspaceHasher::new();
+    let before = hasher.hash(dir.path()).unwrap();
+
+    // Same size, different content and mtime
+    write_settled(&lib, "pub fn b() {}\n", 30);
+    let after = hasher.hash(dir.path()).unwrap();
+    assert_ne!(after, before);
+    assert_eq!(hasher.files_read(), 1);
+
+    // Build output doesn't count
+    write_settled(&dir.path().join("target/debug/out.rs"), "x", 30);
+    assert_eq!(hasher.hash(dir.path()).unwrap(), after);
+}
+
+#[test]
+fn test_git_ignored_paths_are_skipped() {
+    let dir = tempfile::tempdir().unwrap();
+    let git_init = Command::new("git")
+        .args(["init", "-q"])
+        .current_dir(dir.path())
+        .status();
+    if !git_init.is_ok_and(|s| s.success()) {
+        eprintln!("git unavailable; skipping");
+        return;
+    }
+    write_settled(&dir.path().join(".gitignore"), "*.log\n", 60);
+    write_settled(&dir.path().join("src/lib.rs"), "pub fn a() {}\n", 60);
+
+    let mut hasher = WorkspaceHasher::new();
+    let before = hasher.hash(dir.path()).unwrap();
+
+    write_settled(&dir.path().join("debug.log"), "noise", 30);
+    assert_eq!(hasher.hash(dir.path()).unwrap(), before);
+
+    write_settled(&dir.path().join("src/new.rs"), "pub fn n() {}\n", 30);
+    assert_ne!(hasher.hash(dir.path()).unwrap(), before);
+}
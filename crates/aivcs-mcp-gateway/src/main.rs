@@ -115,6 +115,12 @@ struct MemoryWriteRequest {
     key: Option<String>, // Required for session_vector
     #[serde(default)]
     ttl_ms: Option<i64>,
+    /// Run writing the memory; with `seq`, recorded as its provenance
+    #[serde(default)]
+    run_id: Option<String>,
+    /// Seq of the run event producing the memory
+    #[serde(default)]
+    seq: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -356,10 +362,28 @@ async fn memory_write_handler(
         created_at: Utc::now(),
     };
 
-    let saved = db
-        .save_memory(&memory_record)
-        .await
-        .map_err(|e| format!("Failed to save memory: {}", e))?;
+    // Memories written by a run are saved with provenance naming the event
+    let saved = match (req.run_id, req.seq) {
+        (Some(run_id), Some(seq)) => {
+            let source_type = match req.kind.as_str() {
+                "summary" => aivcs_core::ProvenanceSourceType::LlmGenerated,
+                _ => aivcs_core::ProvenanceSourceType::ToolOutput,
+            };
+            let recorder = aivcs_core::DecisionRecorder::with_default_config(Arc::new(db.clone()));
+            aivcs_core::save_memory_from_run(
+                &recorder,
+                &memory_record,
+                &aivcs_core::MemorySource::new(run_id, seq, source_type),
+            )
+            .await
+            .map_err(|e| format!("Failed to save memory: {}", e))?;
+            memory_record
+        }
+        _ => db
+            .save_memory(&memory_record)
+            .await
+            .map_err(|e| format!("Failed to save memory: {}", e))?,
+    };
 
     Ok(json!({
         "memory_id": format!("aivcs:mem-{}", saved.id.as_ref().map(|id| id.to_string()).unwrap_or_else(|| Uuid::new_v4().to_string())),
@@ -5,13 +5,17 @@ use anyhow::{Context, Result};
 use axum::{
     body::Bytes,
     extract::State,
+    http::StatusCode,
     routing::{get, post},
     Json, Router,
 };
+use oxidized_state::migrations::{applied_migrations, migrations};
+use oxidized_state::SurrealHandle;
 use serde_json::{json, Value};
+use std::future::Future;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use surrealdb::engine::any::connect;
+use surrealdb::engine::any::{connect, Any};
 use surrealdb::opt::auth::Root;
 use surrealdb::Surreal;
 use tracing::{info, warn, Level};
@@ -20,6 +24,8 @@ use tracing::{info, warn, Level};
 pub struct AppState {
     pub db: Surreal<surrealdb::engine::any::Any>,
     pub cas: Arc<aivcs_core::cas::fs::FsCasStore>,
+    /// AIVCS state store (commits, branches, run ledger)
+    pub handle: SurrealHandle,
 }
 
 #[tokio::main]
@@ -73,9 +79,28 @@ async fn main() -> Result<()> {
     );
     info!("📦 Initialized CAS store");
 
-    let state = AppState { db, cas };
+    let handle = SurrealHandle::setup_from_env()
+        .await
+        .context("Failed to connect to the AIVCS state store")?;
+    info!("✅ Connected to the AIVCS state store");
+
+    let state = AppState { db, cas, handle };
+
+    let addr = SocketAddr::from(([0, 0, 0, 0], 8080));
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("📡 listening on {}", addr);
+
+    serve(listener, state, shutdown_signal()).await?;
+    info!("👋 aivcsd stopped");
 
-    let app = Router::new()
+    Ok(())
+}
+
+/// Build the HTTP router over `state`
+fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
         .route("/health", get(health_check))
         .route("/version", get(version_info))
         .route("/api/v1/push", post(push_state))
@@ -88,15 +113,94 @@ async fn main() -> Result<()> {
             "/api/v1/ci/checks/:pr_number",
             get(routes::ci::get_ci_checks),
         )
-        .with_state(state);
+        .route("/api/v1/repo/snapshot", post(routes::repo::snapshot))
+        .route("/api/v1/repo/restore", post(routes::repo::restore))
+        .route("/api/v1/repo/log", post(routes::repo::log))
+        .route("/api/v1/repo/branch", post(routes::repo::branch))
+        .route("/api/v1/repo/merge", post(routes::repo::merge))
+        .with_state(state)
+}
 
-    let addr = SocketAddr::from(([0, 0, 0, 0], 8080));
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    info!("📡 listening on {}", addr);
+/// Serve the router on `listener` until `shutdown` resolves, then finish
+/// in-flight requests and return
+async fn serve(
+    listener: tokio::net::TcpListener,
+    state: AppState,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> std::io::Result<()> {
+    axum::serve(listener, router(state))
+        .with_graceful_shutdown(shutdown)
+        .await
+}
 
-    axum::serve(listener, app).await?;
+/// Resolve on SIGTERM or Ctrl-C
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
 
-    Ok(())
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    info!("🛑 shutdown signal received, draining connections");
+}
+
+/// Liveness: the process is up and serving requests
+async fn healthz() -> Json<Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+/// Readiness: the state store is reachable and fully migrated
+async fn readyz(State(state): State<AppState>) -> (StatusCode, Json<Value>) {
+    match readiness(state.handle.db()).await {
+        Ok(schema_version) => (
+            StatusCode::OK,
+            Json(json!({ "status": "ready", "schema_version": schema_version })),
+        ),
+        Err(reason) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "status": "not_ready", "reason": reason })),
+        ),
+    }
+}
+
+/// Latest applied migration id, or why the store is not ready to serve
+async fn readiness(db: &Surreal<Any>) -> Result<u32, String> {
+    db.query("RETURN true")
+        .await
+        .and_then(|res| res.check())
+        .map_err(|e| format!("database unreachable: {e}"))?;
+
+    let expected = migrations().iter().map(|m| m.id).max().unwrap_or(0);
+    let applied = applied_migrations(db)
+        .await
+        .map_err(|e| format!("cannot read schema version: {e}"))?;
+    let current = applied.into_iter().max().unwrap_or(0);
+    if current != expected {
+        return Err(format!(
+            "schema at migration {current}, expected {expected}"
+        ));
+    }
+    Ok(current)
 }
 
 async fn health_check(State(state): State<AppState>) -> Json<Value> {
@@ -189,10 +293,76 @@ async fn version_info() -> Json<Value> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
     #[tokio::test]
     async fn test_version_info() {
         let res = version_info().await;
         assert_eq!(res.0["name"], "aivcsd");
     }
+
+    async fn test_state(cas_dir: &std::path::Path) -> AppState {
+        let db = connect("mem://").await.unwrap();
+        db.use_ns("ci").use_db("fft").await.unwrap();
+        AppState {
+            db,
+            cas: Arc::new(aivcs_core::cas::fs::FsCasStore::new(cas_dir.to_path_buf()).unwrap()),
+            handle: SurrealHandle::setup_db().await.unwrap(),
+        }
+    }
+
+    /// GET `path` with a bare HTTP/1.1 request; returns status and JSON body
+    async fn get_json(addr: SocketAddr, path: &str) -> (u16, Value) {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let request = format!("GET {path} HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\r\n");
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        let status = response
+            .split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse().ok())
+            .unwrap();
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_health_and_ready_endpoints() {
+        let cas_dir = tempfile::tempdir().unwrap();
+        let state = test_state(cas_dir.path()).await;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(serve(listener, state, async {
+            let _ = stopped.await;
+        }));
+
+        let (status, body) = get_json(addr, "/healthz").await;
+        assert_eq!(status, 200);
+        assert_eq!(body["status"], "ok");
+
+        let (status, body) = get_json(addr, "/readyz").await;
+        assert_eq!(status, 200);
+        assert_eq!(body["status"], "ready");
+        let latest = migrations().iter().map(|m| m.id).max().unwrap();
+        assert_eq!(body["schema_version"], latest);
+
+        stop.send(()).unwrap();
+        server.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_readiness_reports_unreachable_and_unmigrated_db() {
+        let disconnected = Surreal::<Any>::init();
+        let reason = readiness(&disconnected).await.unwrap_err();
+        assert!(reason.contains("database unreachable"), "{reason}");
+
+        let fresh = connect("mem://").await.unwrap();
+        fresh.use_ns("aivcs").use_db("main").await.unwrap();
+        let reason = readiness(&fresh).await.unwrap_err();
+        assert!(reason.contains("schema at migration 0"), "{reason}");
+    }
 }
@@ -1 +1,2 @@
 pub mod ci;
+pub mod repo;
@@ -0,0 +1,501 @@
+//! Repository API: snapshot, restore, log, branch, and merge over HTTP+JSON
+//!
+//! Each handler validates its request, then calls the shared
+//! [`aivcs_core::commands`] implementation the CLI uses. Failures are
+//! reported as `{"status": "error", "code": ..., "message": ...}` with a
+//! matching HTTP status.
+
+use crate::AppState;
+use aivcs_core::cas::CasStore;
+use aivcs_core::commands::{self, CommandConflict, SnapshotRequest};
+use axum::{
+    extract::{rejection::JsonRejection, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use oxidized_state::StateError;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::error;
+
+/// Longest branch name or commit reference accepted
+const MAX_REF_LEN: usize = 256;
+
+/// Most commits a single `log` call returns
+const MAX_LOG_LIMIT: usize = 1000;
+
+/// A failed API call, rendered as a structured error body
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+impl ApiError {
+    fn invalid(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            code: "invalid_request",
+            message: message.into(),
+        }
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::invalid(rejection.body_text())
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(err: anyhow::Error) -> Self {
+        let (status, code) = if err.downcast_ref::<CommandConflict>().is_some() {
+            (StatusCode::CONFLICT, "conflict")
+        } else {
+            match err.downcast_ref::<StateError>() {
+                Some(StateError::CommitNotFound(_) | StateError::BranchNotFound(_)) => {
+                    (StatusCode::NOT_FOUND, "not_found")
+                }
+                Some(StateError::BranchProtected { .. }) => (StatusCode::CONFLICT, "conflict"),
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, "internal"),
+            }
+        };
+        Self {
+            status,
+            code,
+            message: format!("{err:#}"),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        if self.status.is_server_error() {
+            error!("repository API call failed: {}", self.message);
+        }
+        (
+            self.status,
+            Json(json!({
+                "status": "error",
+                "code": self.code,
+                "message": self.message,
+            })),
+        )
+            .into_response()
+    }
+}
+
+type ApiResult = Result<Json<Value>, ApiError>;
+
+/// Reject empty, oversized, or whitespace-bearing branch names and references
+fn validate_ref(field: &str, value: &str) -> Result<(), ApiError> {
+    if value.is_empty() {
+        return Err(ApiError::invalid(format!("{field} must not be empty")));
+    }
+    if value.len() > MAX_REF_LEN {
+        return Err(ApiError::invalid(format!(
+            "{field} must be at most {MAX_REF_LEN} bytes"
+        )));
+    }
+    if value.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(ApiError::invalid(format!(
+            "{field} must not contain whitespace or control characters"
+        )));
+    }
+    Ok(())
+}
+
+fn validate_author(author: &str) -> Result<(), ApiError> {
+    if author.trim().is_empty() {
+        return Err(ApiError::invalid("author must not be empty"));
+    }
+    Ok(())
+}
+
+fn default_branch() -> String {
+    "main".to_string()
+}
+
+fn default_message() -> String {
+    "Auto-snapshot".to_string()
+}
+
+fn default_log_limit() -> usize {
+    10
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SnapshotParams {
+    /// Agent state to commit
+    pub state: Value,
+    #[serde(default = "default_message")]
+    pub message: String,
+    pub author: String,
+    #[serde(default = "default_branch")]
+    pub branch: String,
+    #[serde(default)]
+    pub skip_if_unchanged: bool,
+}
+
+/// `POST /api/v1/repo/snapshot`: commit agent state to a branch
+pub async fn snapshot(
+    State(state): State<AppState>,
+    payload: Result<Json<SnapshotParams>, JsonRejection>,
+) -> ApiResult {
+    let Json(params) = payload?;
+    validate_ref("branch", &params.branch)?;
+    validate_author(&params.author)?;
+
+    let request = SnapshotRequest {
+        state: params.state.to_string(),
+        message: params.message,
+        author: params.author,
+        branch: params.branch,
+        skip_if_unchanged: params.skip_if_unchanged,
+        keyframe_interval: commands::snapshot_keyframe_interval(),
+        ..Default::default()
+    };
+    let outcome = commands::snapshot(&state.handle, state.cas.as_ref(), &request, None).await?;
+    Ok(Json(json!(outcome)))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RestoreParams {
+    /// Branch name or commit id
+    #[serde(rename = "ref")]
+    pub reference: String,
+    /// Check the state against its commit's state hash before returning it
+    #[serde(default)]
+    pub verify: bool,
+}
+
+/// `POST /api/v1/repo/restore`: read the agent state at a branch or commit
+pub async fn restore(
+    State(state): State<AppState>,
+    payload: Result<Json<RestoreParams>, JsonRejection>,
+) -> ApiResult {
+    let Json(params) = payload?;
+    validate_ref("ref", &params.reference)?;
+
+    let cas: &dyn CasStore = state.cas.as_ref();
+    let restored = commands::restore(
+        &state.handle,
+        &params.reference,
+        params.verify.then_some(cas),
+    )
+    .await?;
+    Ok(Json(json!(restored)))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LogParams {
+    /// Branch name, commit id, or `<from>..<to>` range
+    #[serde(rename = "ref", default = "default_branch")]
+    pub reference: String,
+    #[serde(default = "default_log_limit")]
+    pub limit: usize,
+}
+
+/// `POST /api/v1/repo/log`: commit history, newest first
+pub async fn log(
+    State(state): State<AppState>,
+    payload: Result<Json<LogParams>, JsonRejection>,
+) -> ApiResult {
+    let Json(params) = payload?;
+    validate_ref("ref", &params.reference)?;
+    if !(1..=MAX_LOG_LIMIT).contains(&params.limit) {
+        return Err(ApiError::invalid(format!(
+            "limit must be between 1 and {MAX_LOG_LIMIT}"
+        )));
+    }
+
+    let history = commands::log(&state.handle, &params.reference, params.limit).await?;
+    Ok(Json(json!(commands::log_output(&history))))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case", deny_unknown_fields)]
+pub enum BranchParams {
+    /// List all branches
+    List,
+    /// Create a branch at another branch's head or a commit
+    Create {
+        name: String,
+        #[serde(default = "default_branch")]
+        from: String,
+    },
+}
+
+/// `POST /api/v1/repo/branch`: list or create branches
+pub async fn branch(
+    State(state): State<AppState>,
+    payload: Result<Json<BranchParams>, JsonRejection>,
+) -> ApiResult {
+    let Json(params) = payload?;
+    match params {
+        BranchParams::List => {
+            let branches = state
+                .handle
+                .list_branches()
+                .await
+                .map_err(anyhow::Error::from)?;
+            Ok(Json(json!(commands::branch_list_output(&branches))))
+        }
+        BranchParams::Create { name, from } => {
+            validate_ref("name", &name)?;
+            validate_ref("from", &from)?;
+            let branch = commands::create_branch(&state.handle, &name, &from).await?;
+            Ok(Json(json!({
+                "name": branch.name,
+                "head_commit_id": branch.head_commit_id,
+            })))
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MergeParams {
+    pub source: String,
+    #[serde(default = "default_branch")]
+    pub target: String,
+    pub message: Option<String>,
+    #[serde(default)]
+    pub no_ff: bool,
+    pub author: String,
+}
+
+/// `POST /api/v1/repo/merge`: merge one branch into another
+pub async fn merge(
+    State(state): State<AppState>,
+    payload: Result<Json<MergeParams>, JsonRejection>,
+) -> ApiResult {
+    let Json(params) = payload?;
+    validate_ref("source", &params.source)?;
+    validate_ref("target", &params.target)?;
+    validate_author(&params.author)?;
+    if params.source == params.target {
+        return Err(ApiError::invalid("source and target must differ"));
+    }
+
+    let outcome = commands::merge(
+        &state.handle,
+        &params.source,
+        &params.target,
+        params.message.as_deref(),
+        params.no_ff,
+        &params.author,
+    )
+    .await?;
+    Ok(Json(json!(outcome)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxidized_state::SurrealHandle;
+    use std::sync::Arc;
+    use surrealdb::engine::any::connect;
+
+    async fn test_state(cas_dir: &std::path::Path) -> AppState {
+        AppState {
+            db: connect("mem://").await.unwrap(),
+            cas: Arc::new(aivcs_core::cas::fs::FsCasStore::new(cas_dir).unwrap()),
+            handle: SurrealHandle::setup_db().await.unwrap(),
+        }
+    }
+
+    /// Status and JSON body of a handler result
+    async fn respond(result: ApiResult) -> (StatusCode, Value) {
+        let response = result.into_response();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn snapshot_params(state: Value, branch: &str) -> Json<SnapshotParams> {
+        Json(SnapshotParams {
+            state,
+            message: "snap".to_string(),
+            author: "agent".to_string(),
+            branch: branch.to_string(),
+            skip_if_unchanged: false,
+        })
+    }
+
+    fn restore_params(reference: &str) -> Json<RestoreParams> {
+        Json(RestoreParams {
+            reference: reference.to_string(),
+            verify: true,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_then_restore_round_trips_state() {
+        let cas_dir = tempfile::tempdir().unwrap();
+        let state = test_state(cas_dir.path()).await;
+        let first_state = json!({ "step": 1, "goal": "ship" });
+        let second_state = json!({ "step": 2, "goal": "ship", "done": true });
+
+        let (status, first) = respond(
+            snapshot(
+                State(state.clone()),
+                Ok(snapshot_params(first_state.clone(), "main")),
+            )
+            .await,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{first}");
+        let first_commit = first["commit"].as_str().unwrap().to_string();
+
+        let (status, restored) =
+            respond(restore(State(state.clone()), Ok(restore_params("main"))).await).await;
+        assert_eq!(status, StatusCode::OK, "{restored}");
+        assert_eq!(restored["commit"], first_commit);
+        assert_eq!(restored["state"], first_state);
+
+        let (_, second) = respond(
+            snapshot(
+                State(state.clone()),
+                Ok(snapshot_params(second_state.clone(), "main")),
+            )
+            .await,
+        )
+        .await;
+        assert_eq!(second["parent_ids"], json!([first_commit]));
+
+        let (_, restored) =
+            respond(restore(State(state.clone()), Ok(restore_params("main"))).await).await;
+        assert_eq!(restored["state"], second_state);
+        let (_, restored) =
+            respond(restore(State(state.clone()), Ok(restore_params(&first_commit))).await).await;
+        assert_eq!(restored["state"], first_state);
+
+        let (status, history) = respond(
+            log(
+                State(state),
+                Ok(Json(LogParams {
+                    reference: "main".to_string(),
+                    limit: 10,
+                })),
+            )
+            .await,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(history["commits"].as_array().unwrap().len(), 2);
+        assert_eq!(history["commits"][1]["commit_id"], first_commit);
+    }
+
+    #[tokio::test]
+    async fn test_branch_and_merge_fast_forward() {
+        let cas_dir = tempfile::tempdir().unwrap();
+        let state = test_state(cas_dir.path()).await;
+        snapshot(
+            State(state.clone()),
+            Ok(snapshot_params(json!({ "step": 0 }), "main")),
+        )
+        .await
+        .unwrap();
+
+        let (status, created) = respond(
+            branch(
+                State(state.clone()),
+                Ok(Json(BranchParams::Create {
+                    name: "feature".to_string(),
+                    from: "main".to_string(),
+                })),
+            )
+            .await,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{created}");
+
+        let feature_state = json!({ "step": 1, "feature": true });
+        snapshot(
+            State(state.clone()),
+            Ok(snapshot_params(feature_state.clone(), "feature")),
+        )
+        .await
+        .unwrap();
+
+        let (status, merged) = respond(
+            merge(
+                State(state.clone()),
+                Ok(Json(MergeParams {
+                    source: "feature".to_string(),
+                    target: "main".to_string(),
+                    message: None,
+                    no_ff: false,
+                    author: "agent".to_string(),
+                })),
+            )
+            .await,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{merged}");
+        assert_eq!(merged["path"], "fast_forward");
+
+        let (_, restored) =
+            respond(restore(State(state.clone()), Ok(restore_params("main"))).await).await;
+        assert_eq!(restored["state"], feature_state);
+
+        let (_, listed) = respond(branch(State(state), Ok(Json(BranchParams::List))).await).await;
+        let names: Vec<&str> = listed["branches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|b| b["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["feature", "main"]);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_and_failed_calls_return_structured_errors() {
+        let cas_dir = tempfile::tempdir().unwrap();
+        let state = test_state(cas_dir.path()).await;
+
+        let mut params = snapshot_params(json!({ "step": 1 }), "main");
+        params.author = " ".to_string();
+        let (status, body) = respond(snapshot(State(state.clone()), Ok(params)).await).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["status"], "error");
+        assert_eq!(body["code"], "invalid_request");
+
+        let (status, body) =
+            respond(restore(State(state.clone()), Ok(restore_params("main branch"))).await).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["message"].as_str().unwrap().contains("whitespace"));
+
+        let (status, body) =
+            respond(restore(State(state.clone()), Ok(restore_params("missing"))).await).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+        assert_eq!(body["code"], "not_found");
+
+        let (status, body) = respond(
+            merge(
+                State(state),
+                Ok(Json(MergeParams {
+                    source: "ghost".to_string(),
+                    target: "main".to_string(),
+                    message: None,
+                    no_ff: false,
+                    author: "agent".to_string(),
+                })),
+            )
+            .await,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+        assert!(body["message"]
+            .as_str()
+            .unwrap()
+            .contains("Source branch not found"));
+    }
+}
@@ -6,9 +6,12 @@
 use crate::error::NixError;
 use crate::flake::NixHash;
 use crate::Result;
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::Duration;
 use tracing::{debug, info, warn};
 
 /// Attic configuration
@@ -22,6 +25,49 @@ pub struct AtticConfig {
     pub token: Option<String>,
     /// Whether to use CLI or HTTP API
     pub use_cli: bool,
+    /// Retries for push and pull
+    #[serde(default)]
+    pub retry: AtticRetryPolicy,
+    /// HTTP timeouts for the server
+    #[serde(default)]
+    pub timeouts: AtticTimeouts,
+}
+
+/// Retry settings for Attic push and pull
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AtticRetryPolicy {
+    /// Total attempts, including the first
+    pub max_attempts: u32,
+    /// Delay before the first retry (milliseconds); doubles for each retry
+    pub backoff_base_ms: u64,
+}
+
+impl Default for AtticRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff_base_ms: 500,
+        }
+    }
+}
+
+/// HTTP timeouts for the Attic server, so an unreachable cache fails
+/// instead of hanging
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AtticTimeouts {
+    /// Limit on establishing a connection (milliseconds)
+    pub connect_ms: u64,
+    /// Limit on a whole request, including reading the response (milliseconds)
+    pub request_ms: u64,
+}
+
+impl Default for AtticTimeouts {
+    fn default() -> Self {
+        Self {
+            connect_ms: 5_000,
+            request_ms: 30_000,
+        }
+    }
 }
 
 impl Default for AtticConfig {
@@ -32,6 +78,8 @@ impl Default for AtticConfig {
             cache_name: std::env::var("ATTIC_CACHE").unwrap_or_else(|_| "aivcs".to_string()),
             token: std::env::var("ATTIC_TOKEN").ok(),
             use_cli: true,
+            retry: AtticRetryPolicy::default(),
+            timeouts: AtticTimeouts::default(),
         }
     }
 }
@@ -49,6 +97,8 @@ impl AtticConfig {
             cache_name: cache_name.to_string(),
             token: None,
             use_cli: true,
+            retry: AtticRetryPolicy::default(),
+            timeouts: AtticTimeouts::default(),
         }
     }
 
@@ -57,6 +107,45 @@ impl AtticConfig {
         self.token = Some(token.to_string());
         self
     }
+
+    /// Set the retry policy for push and pull
+    pub fn with_retry(mut self, retry: AtticRetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Set the HTTP timeouts
+    pub fn with_timeouts(mut self, timeouts: AtticTimeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Check the settings are usable before talking to the server
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason: String| Err(NixError::InvalidAtticConfig(reason));
+        let url = self.server_url.trim();
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            return invalid(format!(
+                "server URL '{}' must start with http:// or https://",
+                self.server_url
+            ));
+        }
+        if self.cache_name.is_empty()
+            || !self
+                .cache_name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        {
+            return invalid(format!(
+                "cache name '{}' must be non-empty and use only letters, digits, '-', '_', or '.'",
+                self.cache_name
+            ));
+        }
+        if self.token.as_deref().is_some_and(|t| t.trim().is_empty()) {
+            return invalid("token is set but empty".to_string());
+        }
+        Ok(())
+    }
 }
 
 /// Attic client for binary cache operations
@@ -70,6 +159,8 @@ impl AtticClient {
     pub fn new(config: AtticConfig) -> Self {
         let http_client = reqwest::Client::builder()
             .user_agent("aivcs-nix-env-manager/0.1.0")
+            .connect_timeout(Duration::from_millis(config.timeouts.connect_ms))
+            .timeout(Duration::from_millis(config.timeouts.request_ms))
             .build()
             .expect("Failed to create HTTP client");
 
@@ -127,15 +218,19 @@ impl AtticClient {
 
     /// Pull environment from cache
     ///
-    /// Returns the path to the cached environment
+    /// Returns the path to the cached environment. Transient failures are
+    /// retried per the config's [`AtticRetryPolicy`].
     pub async fn pull_environment(&self, hash: &NixHash) -> Result<PathBuf> {
         info!("Pulling environment {} from Attic", hash.short());
 
-        if self.config.use_cli {
-            self.pull_cli(hash).await
-        } else {
-            self.pull_http(hash).await
-        }
+        retry_with_backoff(self.config.retry, "pull", || async move {
+            if self.config.use_cli {
+                self.pull_cli(hash).await
+            } else {
+                self.pull_http(hash).await
+            }
+        })
+        .await
     }
 
     /// Pull using Attic CLI
@@ -153,7 +248,10 @@ impl AtticClient {
         } else {
             let stderr = String::from_utf8_lossy(&output.stderr);
             warn!("Failed to pull from cache: {}", stderr);
-            Err(NixError::EnvironmentNotCached(hash.hash.clone()))
+            if is_missing_path(&stderr) {
+                return Err(NixError::EnvironmentNotCached(hash.hash.clone()));
+            }
+            Err(transfer_failure(&stderr, NixError::NixCommandFailed))
         }
     }
 
@@ -167,15 +265,20 @@ impl AtticClient {
 
     /// Push environment to cache
     ///
-    /// Takes the store path of a built environment and pushes it to the cache
+    /// Takes the store path of a built environment and pushes it to the
+    /// cache. Transient failures are retried per the config's
+    /// [`AtticRetryPolicy`].
     pub async fn push_environment(&self, hash: &NixHash, store_path: &Path) -> Result<()> {
         info!("Pushing environment {} to Attic", hash.short());
 
-        if self.config.use_cli {
-            self.push_cli(hash, store_path).await
-        } else {
-            self.push_http(hash, store_path).await
-        }
+        retry_with_backoff(self.config.retry, "push", || async move {
+            if self.config.use_cli {
+                self.push_cli(hash, store_path).await
+            } else {
+                self.push_http(hash, store_path).await
+            }
+        })
+        .await
     }
 
     /// Push using Attic CLI
@@ -202,7 +305,7 @@ impl AtticClient {
                 Ok(())
             } else {
                 let stderr = String::from_utf8_lossy(&output.stderr);
-                Err(NixError::AtticCommandFailed(stderr.to_string()))
+                Err(transfer_failure(&stderr, NixError::AtticCommandFailed))
             }
         } else {
             // Fall back to nix copy
@@ -220,7 +323,7 @@ impl AtticClient {
                 Ok(())
             } else {
                 let stderr = String::from_utf8_lossy(&output.stderr);
-                Err(NixError::NixCommandFailed(stderr.to_string()))
+                Err(transfer_failure(&stderr, NixError::NixCommandFailed))
             }
         }
     }
@@ -270,6 +373,64 @@ impl AtticClient {
         Ok((hash, store_path))
     }
 
+    /// Preflight the configured cache: validate the settings, then fetch
+    /// the cache's `nix-cache-info` with the token
+    ///
+    /// Unlike [`get_cache_info`](Self::get_cache_info), failures are
+    /// errors: an unreachable server, rejected credentials, a missing cache,
+    /// or a response that isn't a Nix binary cache.
+    pub async fn check_connectivity(&self) -> Result<CacheInfo> {
+        self.config.validate()?;
+        let url = format!(
+            "{}/{}/nix-cache-info",
+            self.config.server_url.trim().trim_end_matches('/'),
+            self.config.cache_name
+        );
+
+        let mut request = self.http_client.get(&url);
+        if let Some(token) = &self.config.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+            return Err(NixError::AtticUnauthorized(format!(
+                "{} returned {}",
+                url, status
+            )));
+        }
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(NixError::HttpStatus {
+                status: status.as_u16(),
+                message: format!(
+                    "cache '{}' not found on {}",
+                    self.config.cache_name, self.config.server_url
+                ),
+            });
+        }
+        if !status.is_success() {
+            return Err(NixError::HttpStatus {
+                status: status.as_u16(),
+                message: format!("{} returned {}", url, status),
+            });
+        }
+
+        let body = response.text().await?;
+        if !body.lines().any(|line| line.starts_with("StoreDir:")) {
+            return Err(NixError::Http(format!(
+                "{} did not return a Nix cache info document",
+                url
+            )));
+        }
+        debug!("Attic cache {} is reachable", self.config.cache_name);
+        Ok(CacheInfo {
+            name: self.config.cache_name.clone(),
+            server: self.config.server_url.clone(),
+            available: true,
+            info: Some(body),
+        })
+    }
+
     /// Get cache statistics
     pub async fn get_cache_info(&self) -> Result<CacheInfo> {
         if self.config.use_cli {
@@ -319,6 +480,99 @@ impl AtticClient {
     }
 }
 
+/// Run `operation` until it succeeds, fails with a non-retryable error, or
+/// uses up `policy.max_attempts`, doubling the delay between attempts
+async fn retry_with_backoff<T, F, Fut>(
+    policy: AtticRetryPolicy,
+    operation: &str,
+    mut attempt_once: F,
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let max_attempts = policy.max_attempts.max(1);
+    let mut attempt = 1;
+    loop {
+        match attempt_once().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt >= max_attempts || !e.is_retryable() => return Err(e),
+            Err(e) => {
+                let delay = policy
+                    .backoff_base_ms
+                    .saturating_mul(2u64.saturating_pow(attempt - 1));
+                warn!(
+                    "Attic {} failed (attempt {}/{}): {}; retrying in {}ms",
+                    operation, attempt, max_attempts, e, delay
+                );
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Status code of an `HTTP error NNN` message in `stderr`, if any
+fn http_error_status(stderr: &str) -> Option<u16> {
+    const MARKER: &str = "http error ";
+    let lower = stderr.to_lowercase();
+    let start = lower.find(MARKER)? + MARKER.len();
+    lower.get(start..start + 3)?.parse().ok()
+}
+
+/// Error for a failed `nix copy` or `attic push`: rejected credentials are
+/// reported as such and other HTTP errors by status code, so only 5xx
+/// responses are retried; anything else via `other`
+fn transfer_failure(stderr: &str, other: fn(String) -> NixError) -> NixError {
+    let lower = stderr.to_lowercase();
+    let status = http_error_status(stderr);
+    if matches!(status, Some(401 | 403))
+        || lower.contains("unauthorized")
+        || lower.contains("forbidden")
+    {
+        return NixError::AtticUnauthorized(stderr.trim().to_string());
+    }
+    match status {
+        Some(status) => NixError::HttpStatus {
+            status,
+            message: stderr.trim().to_string(),
+        },
+        None => other(stderr.to_string()),
+    }
+}
+
+/// Whether a failed `nix copy --from` says the path isn't in the cache
+fn is_missing_path(stderr: &str) -> bool {
+    let lower = stderr.to_lowercase();
+    ["does not exist", "is not valid", "http error 404"]
+        .iter()
+        .any(|marker| lower.contains(marker))
+}
+
+/// A binary cache environments can be pulled from
+///
+/// Implemented by [`AtticClient`]; callers take `&dyn EnvironmentCache` so
+/// they can be exercised without Nix or a cache server.
+#[async_trait]
+pub trait EnvironmentCache: Send + Sync {
+    /// Whether the environment with `hash` is in the cache
+    async fn is_environment_cached(&self, hash: &NixHash) -> bool;
+
+    /// Fetch the environment with `hash`, returning its store path
+    async fn pull_environment(&self, hash: &NixHash) -> Result<PathBuf>;
+}
+
+#[async_trait]
+impl EnvironmentCache for AtticClient {
+    async fn is_environment_cached(&self, hash: &NixHash) -> bool {
+        AtticClient::is_environment_cached(self, hash).await
+    }
+
+    async fn pull_environment(&self, hash: &NixHash) -> Result<PathBuf> {
+        AtticClient::pull_environment(self, hash).await
+    }
+}
+
 /// Cache information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CacheInfo {
@@ -335,6 +589,7 @@ pub struct CacheInfo {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
 
     #[test]
     fn test_attic_config_default() {
@@ -357,6 +612,208 @@ mod tests {
         assert_eq!(config.token, Some("secret-token".to_string()));
     }
 
+    #[test]
+    fn test_attic_config_validate() {
+        let valid = AtticConfig::new("https://cache.example.com", "aivcs");
+        assert!(valid.validate().is_ok());
+
+        for config in [
+            AtticConfig::new("cache.example.com", "aivcs"),
+            AtticConfig::new("https://cache.example.com", ""),
+            AtticConfig::new("https://cache.example.com", "has space"),
+            AtticConfig::new("https://cache.example.com", "aivcs").with_token(" "),
+        ] {
+            assert!(
+                matches!(config.validate(), Err(NixError::InvalidAtticConfig(_))),
+                "{:?}",
+                config
+            );
+        }
+    }
+
+    fn fast_retry(max_attempts: u32) -> AtticRetryPolicy {
+        AtticRetryPolicy {
+            max_attempts,
+            backoff_base_ms: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_succeeds_after_transient_failures() {
+        let calls = AtomicU32::new(0);
+        let counter = &calls;
+        let result = retry_with_backoff(fast_retry(3), "pull", || async move {
+            match counter.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => Err(NixError::NixCommandFailed(
+                    "error: unable to download: HTTP error 503".to_string(),
+                )),
+                _ => Ok(PathBuf::from("/nix/store/abc-aivcs-env")),
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), PathBuf::from("/nix/store/abc-aivcs-env"));
+        assert_eq!(calls.into_inner(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_gives_up_after_max_attempts() {
+        let calls = AtomicU32::new(0);
+        let counter = &calls;
+        let result: Result<()> = retry_with_backoff(fast_retry(2), "push", || async move {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Err(NixError::Http("connection reset".to_string()))
+        })
+        .await;
+
+        assert!(matches!(result, Err(NixError::Http(_))));
+        assert_eq!(calls.into_inner(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized_fails_without_retry() {
+        let calls = AtomicU32::new(0);
+        let counter = &calls;
+        let result: Result<()> = retry_with_backoff(fast_retry(5), "push", || async move {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Err(transfer_failure(
+                "error: unable to upload: HTTP error 401 (Unauthorized)",
+                NixError::AtticCommandFailed,
+            ))
+        })
+        .await;
+
+        assert!(matches!(result, Err(NixError::AtticUnauthorized(_))));
+        assert_eq!(calls.into_inner(), 1);
+    }
+
+    #[test]
+    fn test_transfer_failures_retry_on_5xx_but_not_4xx() {
+        let classify =
+            |stderr: &str| transfer_failure(stderr, NixError::NixCommandFailed).is_retryable();
+        assert!(classify("error: unable to download: HTTP error 503"));
+        assert!(classify(
+            "error: unable to download: HTTP error 500 (Internal Server Error)"
+        ));
+        assert!(!classify(
+            "error: unable to upload: HTTP error 413 (Payload Too Large)"
+        ));
+        assert!(!classify("error: unable to download: HTTP error 400"));
+        assert!(!classify("error: unable to upload: HTTP error 403"));
+        // No status: network trouble, retried
+        assert!(classify("error: connection timed out"));
+        assert!(matches!(
+            transfer_failure("HTTP error 429", NixError::NixCommandFailed),
+            NixError::HttpStatus { status: 429, .. }
+        ));
+    }
+
+    #[test]
+    fn test_missing_path_is_not_cached_rather_than_retryable() {
+        assert!(is_missing_path(
+            "error: path '/nix/store/abc-aivcs-env' does not exist in binary cache"
+        ));
+        assert!(!is_missing_path("error: connection timed out"));
+        assert!(!NixError::EnvironmentNotCached("abc".to_string()).is_retryable());
+    }
+
+    /// Serve one HTTP request with `status` and `body`; the task resolves
+    /// to the raw request text
+    async fn serve_once(
+        status: &'static str,
+        body: &'static str,
+    ) -> (String, tokio::task::JoinHandle<String>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 4096];
+            let n = socket.read(&mut buf).await.unwrap();
+            let response = format!(
+                "HTTP/1.1 {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&buf[..n]).to_string()
+        });
+        (url, server)
+    }
+
+    #[tokio::test]
+    async fn test_check_connectivity_accepts_cache_info_with_token() {
+        let (url, server) = serve_once(
+            "200 OK",
+            "StoreDir: /nix/store\nWantMassQuery: 1\nPriority: 41\n",
+        )
+        .await;
+        let client = AtticClient::new(AtticConfig::new(&url, "aivcs").with_token("secret"));
+
+        let info = client.check_connectivity().await.unwrap();
+        assert!(info.available);
+        assert!(info.info.unwrap().contains("StoreDir: /nix/store"));
+
+        let request = server.await.unwrap();
+        assert!(
+            request.starts_with("GET /aivcs/nix-cache-info "),
+            "{request}"
+        );
+        assert!(
+            request
+                .to_ascii_lowercase()
+                .contains("authorization: bearer secret"),
+            "{request}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_connectivity_reports_rejected_token() {
+        let (url, server) = serve_once("401 Unauthorized", "").await;
+        let client = AtticClient::new(AtticConfig::new(&url, "aivcs").with_token("stale"));
+
+        let err = client.check_connectivity().await.unwrap_err();
+        assert!(matches!(err, NixError::AtticUnauthorized(_)), "{err}");
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_check_connectivity_times_out_on_silent_server() {
+        // Accepts the connection but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            drop(socket);
+        });
+        let client = AtticClient::new(AtticConfig::new(&url, "aivcs").with_timeouts(
+            AtticTimeouts {
+                connect_ms: 1_000,
+                request_ms: 200,
+            },
+        ));
+
+        let err = tokio::time::timeout(Duration::from_secs(5), client.check_connectivity())
+            .await
+            .expect("request should time out before the guard")
+            .unwrap_err();
+        assert!(matches!(err, NixError::Http(_)), "{err}");
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_check_connectivity_rejects_non_cache_response() {
+        let (url, server) = serve_once("200 OK", "<html>login</html>").await;
+        let client = AtticClient::new(AtticConfig::new(&url, "aivcs"));
+
+        let err = client.check_connectivity().await.unwrap_err();
+        assert!(err.to_string().contains("Nix cache info"), "{err}");
+        server.await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_pull_nonexistent_hash_fails_gracefully() {
         let client = AtticClient::from_env();
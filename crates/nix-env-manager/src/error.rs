@@ -29,6 +29,14 @@ pub enum NixError {
     #[error("Attic command failed: {0}")]
     AtticCommandFailed(String),
 
+    /// Attic settings are malformed
+    #[error("Invalid Attic configuration: {0}")]
+    InvalidAtticConfig(String),
+
+    /// The Attic server rejected the token
+    #[error("Attic server rejected the credentials: {0}")]
+    AtticUnauthorized(String),
+
     /// Environment not found in cache
     #[error("Environment not found in cache: {0}")]
     EnvironmentNotCached(String),
@@ -45,13 +53,48 @@ pub enum NixError {
     #[error("HTTP error: {0}")]
     Http(String),
 
+    /// HTTP request answered with an error status (for Attic API)
+    #[error("HTTP {status}: {message}")]
+    HttpStatus { status: u16, message: String },
+
     /// Hash computation error
     #[error("Hash computation failed: {0}")]
     HashError(String),
 }
 
+impl NixError {
+    /// Whether another attempt might succeed
+    ///
+    /// Failed transfers, network trouble and 5xx responses are retryable;
+    /// 4xx responses, rejected credentials, missing paths or tools, and bad
+    /// input are not.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            NixError::NixCommandFailed(_) | NixError::AtticCommandFailed(_) | NixError::Http(_) => {
+                true
+            }
+            NixError::HttpStatus { status, .. } => *status >= 500,
+            NixError::Io(e) => matches!(
+                e.kind(),
+                std::io::ErrorKind::TimedOut
+                    | std::io::ErrorKind::Interrupted
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::ConnectionRefused
+            ),
+            _ => false,
+        }
+    }
+}
+
 impl From<reqwest::Error> for NixError {
     fn from(err: reqwest::Error) -> Self {
-        NixError::Http(err.to_string())
+        match err.status() {
+            Some(status) => NixError::HttpStatus {
+                status: status.as_u16(),
+                message: err.to_string(),
+            },
+            None => NixError::Http(err.to_string()),
+        }
     }
 }
@@ -19,12 +19,15 @@ mod error;
 mod flake;
 mod logic;
 
-pub use attic::{AtticClient, AtticConfig};
+pub use attic::{AtticClient, AtticConfig, AtticRetryPolicy, AtticTimeouts, EnvironmentCache};
 pub use error::NixError;
 pub use flake::{
     generate_environment_hash, get_flake_metadata, FlakeMetadata, HashSource, NixHash,
 };
-pub use logic::generate_logic_hash;
+pub use logic::{
+    generate_logic_hash, generate_logic_hash_with, logic_hash_breakdown, LogicFileDigest,
+    LogicHashConfig,
+};
 
 /// Result type for nix-env-manager operations
 pub type Result<T> = std::result::Result<T, NixError>;
@@ -47,6 +50,17 @@ pub fn is_attic_available() -> bool {
         .unwrap_or(false)
 }
 
+/// The `rustc --version` line of the toolchain on `PATH`, if rustc runs
+pub fn rust_toolchain_version() -> Option<String> {
+    let output = std::process::Command::new("rustc")
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!version.is_empty()).then_some(version)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
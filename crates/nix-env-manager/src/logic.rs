@@ -2,13 +2,59 @@
 //!
 //! Generates content-addressable hashes from Rust source code,
 //! enabling versioning of agent logic alongside state and environment.
+//! Prompts, configs, and other files that shape behavior can be folded in
+//! through [`LogicHashConfig`].
 
 use crate::error::NixError;
 use crate::Result;
+use serde::Serialize;
 use sha2::{Digest, Sha256};
 use std::path::Path;
 use tracing::debug;
 
+/// Which files count as agent logic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogicHashConfig {
+    /// File extensions to hash when walking a directory, without the dot
+    /// and matched case-insensitively
+    pub include_extensions: Vec<String>,
+}
+
+impl Default for LogicHashConfig {
+    fn default() -> Self {
+        Self {
+            include_extensions: vec!["rs".to_string()],
+        }
+    }
+}
+
+impl LogicHashConfig {
+    /// Also hash files with these extensions (e.g. `md`, `toml`, `py`)
+    pub fn with_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for ext in extensions {
+            let ext = ext.as_ref().trim_start_matches('.').to_ascii_lowercase();
+            if !ext.is_empty() && !self.include_extensions.contains(&ext) {
+                self.include_extensions.push(ext);
+            }
+        }
+        self
+    }
+
+    fn includes(&self, path: &Path) -> bool {
+        let Some(ext) = path.extension() else {
+            return false;
+        };
+        let ext = ext.to_string_lossy();
+        self.include_extensions
+            .iter()
+            .any(|wanted| wanted.trim_start_matches('.').eq_ignore_ascii_case(&ext))
+    }
+}
+
 /// Generate a hash of Rust source code
 ///
 /// This function recursively walks a directory and hashes all .rs files,
@@ -16,14 +62,23 @@ use tracing::debug;
 ///
 /// # TDD: test_changing_rust_source_changes_logic_hash
 pub fn generate_logic_hash(source_path: &Path) -> Result<String> {
+    generate_logic_hash_with(source_path, &LogicHashConfig::default())
+}
+
+/// [`generate_logic_hash`] over the files `config` selects
+///
+/// With the default config the hash is the same as
+/// [`generate_logic_hash`]'s. A single file is hashed whatever its
+/// extension.
+pub fn generate_logic_hash_with(source_path: &Path, config: &LogicHashConfig) -> Result<String> {
     let mut hasher = Sha256::new();
 
     if source_path.is_file() {
         // Single file
         hash_rust_file(source_path, &mut hasher)?;
     } else if source_path.is_dir() {
-        // Directory - hash all .rs files
-        hash_rust_directory(source_path, &mut hasher)?;
+        // Directory - hash all selected files
+        hash_source_directory(source_path, config, &mut hasher)?;
     } else {
         return Err(NixError::Io(std::io::Error::new(
             std::io::ErrorKind::NotFound,
@@ -36,6 +91,51 @@ pub fn generate_logic_hash(source_path: &Path) -> Result<String> {
     Ok(hash)
 }
 
+/// One file's contribution to a logic hash
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LogicFileDigest {
+    /// Path relative to the hashed directory, or the file name when a
+    /// single file was hashed
+    pub path: String,
+    /// SHA-256 of the normalized content, as hashed
+    pub digest: String,
+    /// Size on disk in bytes
+    pub size_bytes: u64,
+}
+
+/// The files [`generate_logic_hash_with`] would hash, in hashing order,
+/// each with its own digest
+pub fn logic_hash_breakdown(
+    source_path: &Path,
+    config: &LogicHashConfig,
+) -> Result<Vec<LogicFileDigest>> {
+    let (root, mut files) = if source_path.is_file() {
+        let root = source_path.parent().unwrap_or(Path::new(""));
+        (root, vec![source_path.to_path_buf()])
+    } else if source_path.is_dir() {
+        (source_path, collect_source_files(source_path, config)?)
+    } else {
+        return Err(NixError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("Source path not found: {:?}", source_path),
+        )));
+    };
+    files.sort();
+
+    files
+        .into_iter()
+        .map(|path| {
+            let content = std::fs::read(&path)?;
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            Ok(LogicFileDigest {
+                path: relative.to_string_lossy().into_owned(),
+                digest: hex::encode(Sha256::digest(normalize_source(&content))),
+                size_bytes: content.len() as u64,
+            })
+        })
+        .collect()
+}
+
 /// Hash a single Rust file
 fn hash_rust_file(path: &Path, hasher: &mut Sha256) -> Result<()> {
     let content = std::fs::read(path)?;
@@ -54,9 +154,9 @@ fn hash_rust_file(path: &Path, hasher: &mut Sha256) -> Result<()> {
     Ok(())
 }
 
-/// Recursively hash all Rust files in a directory
-fn hash_rust_directory(dir: &Path, hasher: &mut Sha256) -> Result<()> {
-    let mut entries = collect_rust_files(dir)?;
+/// Recursively hash all files `config` selects in a directory
+fn hash_source_directory(dir: &Path, config: &LogicHashConfig, hasher: &mut Sha256) -> Result<()> {
+    let mut entries = collect_source_files(dir, config)?;
 
     // Sort for deterministic ordering
     entries.sort();
@@ -78,12 +178,21 @@ fn hash_rust_directory(dir: &Path, hasher: &mut Sha256) -> Result<()> {
 
 /// Collect all Rust files in a directory recursively
 fn collect_rust_files(dir: &Path) -> Result<Vec<std::path::PathBuf>> {
+    collect_source_files(dir, &LogicHashConfig::default())
+}
+
+/// Collect all files `config` selects in a directory recursively
+fn collect_source_files(dir: &Path, config: &LogicHashConfig) -> Result<Vec<std::path::PathBuf>> {
     let mut files = Vec::new();
-    collect_rust_files_recursive(dir, &mut files)?;
+    collect_source_files_recursive(dir, config, &mut files)?;
     Ok(files)
 }
 
-fn collect_rust_files_recursive(dir: &Path, files: &mut Vec<std::path::PathBuf>) -> Result<()> {
+fn collect_source_files_recursive(
+    dir: &Path,
+    config: &LogicHashConfig,
+    files: &mut Vec<std::path::PathBuf>,
+) -> Result<()> {
     if !dir.is_dir() {
         return Ok(());
     }
@@ -99,13 +208,11 @@ fn collect_rust_files_recursive(dir: &Path, files: &mut Vec<std::path::PathBuf>)
         }
 
         if path.is_file() {
-            if let Some(ext) = path.extension() {
-                if ext == "rs" {
-                    files.push(path);
-                }
+            if config.includes(&path) {
+                files.push(path);
             }
         } else if path.is_dir() {
-            collect_rust_files_recursive(&path, files)?;
+            collect_source_files_recursive(&path, config, files)?;
         }
     }
 
@@ -271,6 +378,70 @@ mod tests {
         assert!(!hash.is_empty());
     }
 
+    #[test]
+    fn test_included_prompt_changes_logic_hash() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+        let prompts = dir.path().join("prompts");
+        std::fs::create_dir(&prompts).unwrap();
+        std::fs::write(prompts.join("system.md"), "You are terse.").unwrap();
+
+        let with_md = LogicHashConfig::default().with_extensions([".MD"]);
+        assert_eq!(with_md.include_extensions, ["rs", "md"]);
+        let rust_only_1 = generate_logic_hash(dir.path()).unwrap();
+        let with_md_1 = generate_logic_hash_with(dir.path(), &with_md).unwrap();
+        assert_ne!(rust_only_1, with_md_1);
+        assert_eq!(
+            generate_logic_hash_with(dir.path(), &LogicHashConfig::default()).unwrap(),
+            rust_only_1
+        );
+
+        // Only the prompt changes
+        std::fs::write(prompts.join("system.md"), "You are verbose.").unwrap();
+        let rust_only_2 = generate_logic_hash(dir.path()).unwrap();
+        let with_md_2 = generate_logic_hash_with(dir.path(), &with_md).unwrap();
+        assert_eq!(rust_only_1, rust_only_2);
+        assert_ne!(with_md_1, with_md_2);
+    }
+
+    #[test]
+    fn test_breakdown_lists_exactly_the_hashed_files() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("prompts")).unwrap();
+        std::fs::create_dir(dir.path().join("target")).unwrap();
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}\r\n").unwrap();
+        std::fs::write(dir.path().join("prompts/system.md"), "Be terse.").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not logic").unwrap();
+        std::fs::write(dir.path().join("target/gen.rs"), "// generated").unwrap();
+
+        let config = LogicHashConfig::default().with_extensions(["md"]);
+        let breakdown = logic_hash_breakdown(dir.path(), &config).unwrap();
+        let paths: Vec<&str> = breakdown.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "main.rs",
+                &*format!("prompts{}system.md", std::path::MAIN_SEPARATOR)
+            ]
+        );
+
+        let main = &breakdown[0];
+        assert_eq!(main.size_bytes, 14);
+        assert_eq!(
+            main.digest,
+            hex::encode(Sha256::digest(b"fn main() {}\n")),
+            "digest covers normalized content"
+        );
+        assert_eq!(
+            logic_hash_breakdown(dir.path(), &config).unwrap(),
+            breakdown
+        );
+
+        let single = logic_hash_breakdown(&dir.path().join("notes.txt"), &config).unwrap();
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].path, "notes.txt");
+    }
+
     #[test]
     fn test_skips_target_directory() {
         let dir = tempdir().unwrap();
@@ -0,0 +1,101 @@
+//! Canonical JSON encoding for content hashing
+//!
+//! Object keys are sorted by UTF-16 code units at every depth (RFC 8785
+//! §3.2.3), integer-valued floats are written as integers, and no
+//! insignificant whitespace is emitted, so semantically equal values encode
+//! to identical bytes regardless of key insertion order.
+
+use serde_json::{Number, Value};
+
+/// Encode `value` as canonical JSON bytes
+pub fn canonical_json(value: &Value) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_value(value, &mut out);
+    out
+}
+
+fn write_value(value: &Value, out: &mut Vec<u8>) {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push(b'{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_scalar(&Value::String(key.clone()), out);
+                out.push(b':');
+                write_value(item, out);
+            }
+            out.push(b'}');
+        }
+        Value::Array(items) => {
+            out.push(b'[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_value(item, out);
+            }
+            out.push(b']');
+        }
+        Value::Number(n) => write_scalar(&Value::Number(normalize_number(n)), out),
+        scalar => write_scalar(scalar, out),
+    }
+}
+
+fn write_scalar(value: &Value, out: &mut Vec<u8>) {
+    serde_json::to_writer(out, value).expect("writing JSON to a Vec cannot fail");
+}
+
+/// Integer-valued floats within `i64` range become integers (`1.0` → `1`)
+fn normalize_number(n: &Number) -> Number {
+    match n.as_f64() {
+        Some(f)
+            if n.is_f64() && f.fract() == 0.0 && f >= i64::MIN as f64 && f <= i64::MAX as f64 =>
+        {
+            Number::from(f as i64)
+        }
+        _ => n.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn encode(value: &Value) -> String {
+        String::from_utf8(canonical_json(value)).unwrap()
+    }
+
+    #[test]
+    fn sorts_keys_at_every_depth() {
+        let value = json!({ "b": { "z": [ { "y": 1, "x": 2 } ], "a": null }, "a": true });
+        assert_eq!(
+            encode(&value),
+            r#"{"a":true,"b":{"a":null,"z":[{"x":2,"y":1}]}}"#
+        );
+    }
+
+    #[test]
+    fn orders_keys_by_utf16_code_units() {
+        // U+1F600 sorts after U+FF61 by UTF-8 bytes, but its leading
+        // surrogate (0xD83D) sorts before 0xFF61 in UTF-16
+        let value = json!({ "\u{FF61}": 2, "\u{1F600}": 1 });
+        assert_eq!(encode(&value), "{\"\u{1F600}\":1,\"\u{FF61}\":2}");
+    }
+
+    #[test]
+    fn normalizes_integer_valued_floats() {
+        let value = json!([1.0, -0.0, 1.5, 1e10, 7]);
+        assert_eq!(encode(&value), "[1,0,1.5,10000000000,7]");
+    }
+
+    #[test]
+    fn escapes_strings_like_serde_json() {
+        let value = json!({ "k\"ey": "line\nbreak" });
+        assert_eq!(encode(&value), r#"{"k\"ey":"line\nbreak"}"#);
+    }
+}
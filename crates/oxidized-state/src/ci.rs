@@ -186,10 +186,10 @@ impl CiRunRecord {
 }
 
 fn digest_json<T: Serialize>(value: &T) -> String {
-    let bytes =
-        serde_json::to_vec(value).expect("CI domain objects must be serializable for hashing");
+    let value =
+        serde_json::to_value(value).expect("CI domain objects must be serializable for hashing");
     let mut hasher = Sha256::new();
-    hasher.update(bytes);
+    hasher.update(crate::canonical_json(&value));
     hex::encode(hasher.finalize())
 }
 
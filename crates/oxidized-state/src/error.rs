@@ -40,6 +40,26 @@ pub enum StateError {
     /// Schema setup error
     #[error("Schema setup failed: {0}")]
     SchemaSetup(String),
+
+    /// Operation blocked by a branch protection rule
+    #[error("Branch '{branch}' is protected: {rule}")]
+    BranchProtected { branch: String, rule: String },
+
+    /// The commit exists but retention removed its snapshot
+    #[error("Snapshot of commit {0} was pruned by retention; only its metadata remains")]
+    SnapshotPruned(String),
+
+    /// Loaded data does not match its recorded digest
+    #[error("Integrity check failed: expected {expected}, got {actual}")]
+    IntegrityError { expected: String, actual: String },
+
+    /// An advisory lock stayed held by another process for too long
+    #[error("Timed out after {waited:?} waiting for lock '{name}' held by {holder}")]
+    LockTimeout {
+        name: String,
+        holder: String,
+        waited: std::time::Duration,
+    },
 }
 
 /// Errors for the storage trait abstractions (CasStore, RunLedger, ReleaseRegistry)
@@ -53,6 +73,10 @@ pub enum StorageError {
     #[error("run not found: {run_id}")]
     RunNotFound { run_id: String },
 
+    /// A run with this id already exists in the ledger
+    #[error("run already exists: {run_id}")]
+    RunAlreadyExists { run_id: String },
+
     /// Run is not in a valid state for the requested operation
     #[error("run {run_id} is {status}, expected {expected}")]
     InvalidRunState {
@@ -61,6 +85,10 @@ pub enum StorageError {
         expected: String,
     },
 
+    /// An event with this `(run_id, seq)` has already been appended
+    #[error("duplicate event: run {run_id} already has seq {seq}")]
+    DuplicateEvent { run_id: String, seq: u64 },
+
     /// Release not found in registry
     #[error("release not found: {name}")]
     ReleaseNotFound { name: String },
@@ -86,6 +114,18 @@ pub enum StorageError {
     Serialization(String),
 }
 
+/// Whether `err` is a write rejected by the unique index `index`.
+///
+/// Embedded engines report this as a typed error. Remote engines only pass
+/// the server's message along, so callers confirm a conflict by reading the
+/// existing row back instead of parsing that message.
+pub(crate) fn violates_unique_index(err: &surrealdb::Error, index: &str) -> bool {
+    matches!(
+        err,
+        surrealdb::Error::Db(surrealdb::error::Db::IndexExists { index: name, .. }) if name == index
+    )
+}
+
 impl From<surrealdb::Error> for StateError {
     fn from(err: surrealdb::Error) -> Self {
         StateError::Query(err.to_string())
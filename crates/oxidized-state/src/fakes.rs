@@ -7,7 +7,7 @@ use std::collections::HashMap;
 use std::sync::Mutex;
 
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 
 use crate::error::StorageError;
 use crate::storage_traits::*;
@@ -73,6 +73,7 @@ struct RunState {
 #[derive(Debug, Default)]
 pub struct MemoryRunLedger {
     runs: Mutex<HashMap<String, RunState>>,
+    archived: Mutex<HashMap<String, String>>,
 }
 
 impl MemoryRunLedger {
@@ -88,7 +89,16 @@ impl RunLedger for MemoryRunLedger {
         spec_digest: &ContentDigest,
         metadata: RunMetadata,
     ) -> StorageResult<RunId> {
-        let run_id = RunId::new();
+        self.create_run_with_id(RunId::new(), spec_digest, metadata)
+            .await
+    }
+
+    async fn create_run_with_id(
+        &self,
+        run_id: RunId,
+        spec_digest: &ContentDigest,
+        metadata: RunMetadata,
+    ) -> StorageResult<RunId> {
         let record = RunRecord {
             run_id: run_id.clone(),
             spec_digest: spec_digest.clone(),
@@ -99,6 +109,9 @@ impl RunLedger for MemoryRunLedger {
             completed_at: None,
         };
         let mut runs = self.runs.lock().unwrap();
+        if runs.contains_key(&run_id.0) {
+            return Err(StorageError::RunAlreadyExists { run_id: run_id.0 });
+        }
         runs.insert(
             run_id.0.clone(),
             RunState {
@@ -123,6 +136,12 @@ impl RunLedger for MemoryRunLedger {
                 expected: "Running".to_string(),
             });
         }
+        if state.events.iter().any(|e| e.seq == event.seq) {
+            return Err(StorageError::DuplicateEvent {
+                run_id: run_id.0.clone(),
+                seq: event.seq,
+            });
+        }
         state.events.push(event);
         Ok(())
     }
@@ -224,6 +243,52 @@ impl RunLedger for MemoryRunLedger {
             .collect();
         Ok(records)
     }
+
+    async fn list_agent_runs(
+        &self,
+        agent_name: Option<&str>,
+        since: Option<DateTime<Utc>>,
+    ) -> StorageResult<Vec<RunRecord>> {
+        let runs = self.runs.lock().unwrap();
+        let mut records: Vec<RunRecord> = runs
+            .values()
+            .filter(|s| {
+                agent_name
+                    .map(|a| s.record.metadata.agent_name == a)
+                    .unwrap_or(true)
+            })
+            .filter(|s| since.map(|t| s.record.created_at >= t).unwrap_or(true))
+            .map(|s| s.record.clone())
+            .collect();
+        records.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(records)
+    }
+
+    async fn archive_run(&self, run_id: &RunId, archive_digest: &str) -> StorageResult<()> {
+        let mut runs = self.runs.lock().unwrap();
+        let state = runs
+            .get(&run_id.0)
+            .ok_or_else(|| StorageError::RunNotFound {
+                run_id: run_id.0.clone(),
+            })?;
+        if state.record.status == RunStatus::Running {
+            return Err(StorageError::InvalidRunState {
+                run_id: run_id.0.clone(),
+                status: format!("{:?}", state.record.status),
+                expected: "terminal".to_string(),
+            });
+        }
+        runs.remove(&run_id.0);
+        self.archived
+            .lock()
+            .unwrap()
+            .insert(run_id.0.clone(), archive_digest.to_string());
+        Ok(())
+    }
+
+    async fn archived_run_digest(&self, run_id: &RunId) -> StorageResult<Option<String>> {
+        Ok(self.archived.lock().unwrap().get(&run_id.0).cloned())
+    }
 }
 
 // ---------------------------------------------------------------------------
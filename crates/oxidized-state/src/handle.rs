@@ -4,21 +4,23 @@
 //! - save_snapshot / load_snapshot
 //! - save_commit_graph_edge
 //! - get_branch_head
-//! - CRUD for commits, branches, agents, memories, and CI records
+//! - CRUD for commits, branches, agents, memories, stashes, CI records, and plans
 //!
 //! Supports both local (in-memory) and cloud (WebSocket) connections.
 
 use crate::ci::{CiPipelineSpec, CiRunRecord, CiSnapshot};
 use crate::error::StateError;
 use crate::schema::{
-    AgentRecord, BranchRecord, CommitId, CommitRecord, DecisionRecord, GraphEdge,
-    MemoryProvenanceRecord, MemoryRecord, SnapshotRecord,
+    decision_outcome_status, AgentRecord, BranchProtection, BranchProtectionRecord, BranchRecord,
+    CommitId, CommitRecord, DecisionFilter, DecisionRecord, GraphEdge, MemoryProvenanceRecord,
+    MemoryRecord, PlanRecord, PlanTaskRecord, SnapshotRecord, StashRecord,
 };
 use crate::storage_traits::{ContentDigest, ReleaseMetadata, ReleaseRecord, StorageResult};
 use crate::Result;
 use crate::StorageError;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
 use surrealdb::engine::any::Any;
 use surrealdb::opt::auth::{Database, Root};
 use surrealdb::sql::Datetime as SurrealDatetime;
@@ -103,16 +105,19 @@ impl CloudConfig {
     /// - SURREALDB_DATABASE (optional, default: "main")
     /// - SURREALDB_ROOT (optional, default: "false") - set to "true" for root users
     pub fn from_env() -> std::result::Result<Self, String> {
-        let endpoint =
-            std::env::var("SURREALDB_ENDPOINT").map_err(|_| "SURREALDB_ENDPOINT not set")?;
-        let username =
-            std::env::var("SURREALDB_USERNAME").map_err(|_| "SURREALDB_USERNAME not set")?;
-        let password =
-            std::env::var("SURREALDB_PASSWORD").map_err(|_| "SURREALDB_PASSWORD not set")?;
-        let namespace =
-            std::env::var("SURREALDB_NAMESPACE").unwrap_or_else(|_| "aivcs".to_string());
-        let database = std::env::var("SURREALDB_DATABASE").unwrap_or_else(|_| "main".to_string());
-        let is_root = std::env::var("SURREALDB_ROOT")
+        Self::from_env_with_prefix("SURREALDB")
+    }
+
+    /// Create from environment variables named `{prefix}_ENDPOINT`,
+    /// `{prefix}_USERNAME`, etc. (same set and defaults as [`from_env`](Self::from_env))
+    pub fn from_env_with_prefix(prefix: &str) -> std::result::Result<Self, String> {
+        let var = |name: &str| std::env::var(format!("{prefix}_{name}"));
+        let endpoint = var("ENDPOINT").map_err(|_| format!("{prefix}_ENDPOINT not set"))?;
+        let username = var("USERNAME").map_err(|_| format!("{prefix}_USERNAME not set"))?;
+        let password = var("PASSWORD").map_err(|_| format!("{prefix}_PASSWORD not set"))?;
+        let namespace = var("NAMESPACE").unwrap_or_else(|_| "aivcs".to_string());
+        let database = var("DATABASE").unwrap_or_else(|_| "main".to_string());
+        let is_root = var("ROOT")
             .map(|v| v.to_lowercase() == "true")
             .unwrap_or(false);
 
@@ -261,14 +266,7 @@ impl SurrealHandle {
             url
         } else {
             // Default to local persistence in .aivcs/db
-            let path = ".aivcs/db";
-            std::fs::create_dir_all(path).map_err(|e| {
-                StateError::Connection(format!(
-                    "Failed to create database directory {}: {}",
-                    path, e
-                ))
-            })?;
-            let url = format!("surrealkv://{}", path);
+            let url = "surrealkv://.aivcs/db".to_string();
             info!(
                 "No cloud config or SURREALDB_URL found, using local persistence: {}",
                 url
@@ -276,12 +274,28 @@ impl SurrealHandle {
             url
         };
 
-        let db = surrealdb::engine::any::connect(&url)
+        Self::setup_with_url(&url, "aivcs", "main").await
+    }
+
+    /// Connect to `url` (any SurrealDB engine URL, e.g. `surrealkv://path`,
+    /// `ws://host:8000`, `mem://`) and select `namespace` / `database`
+    #[instrument(skip_all, fields(url = %url))]
+    pub async fn setup_with_url(url: &str, namespace: &str, database: &str) -> Result<Self> {
+        if let Some(path) = url.strip_prefix("surrealkv://") {
+            std::fs::create_dir_all(path).map_err(|e| {
+                StateError::Connection(format!(
+                    "Failed to create database directory {}: {}",
+                    path, e
+                ))
+            })?;
+        }
+
+        let db = surrealdb::engine::any::connect(url)
             .await
             .map_err(|e| StateError::Connection(format!("Failed to connect to {}: {}", url, e)))?;
 
-        db.use_ns("aivcs")
-            .use_db("main")
+        db.use_ns(namespace)
+            .use_db(database)
             .await
             .map_err(|e| StateError::Connection(e.to_string()))?;
 
@@ -311,6 +325,29 @@ impl SurrealHandle {
         created.ok_or_else(|| StateError::Transaction("Failed to create commit".to_string()))
     }
 
+    /// Replace the parents of the stored commit `record.commit_id`, along
+    /// with its signature, which covers the parents
+    #[instrument(skip(self, record), fields(commit = %record.commit_id.short()))]
+    pub async fn reparent_commit(&self, record: &CommitRecord) -> Result<()> {
+        let mut result = self
+            .db
+            .query(
+                "UPDATE commits SET parent_ids = $parents, signature = $signature, \
+                 signer = $signer WHERE commit_id.hash = $hash",
+            )
+            .bind(("parents", record.parent_ids.clone()))
+            .bind(("signature", record.signature.clone()))
+            .bind(("signer", record.signer.clone()))
+            .bind(("hash", record.commit_id.hash.clone()))
+            .await?;
+
+        let updated: Vec<CommitRecord> = result.take(0)?;
+        if updated.is_empty() {
+            return Err(StateError::CommitNotFound(record.commit_id.hash.clone()));
+        }
+        Ok(())
+    }
+
     /// Get a commit by its hash
     #[instrument(skip(self))]
     pub async fn get_commit(&self, commit_hash: &str) -> Result<Option<CommitRecord>> {
@@ -328,6 +365,22 @@ impl SurrealHandle {
         Ok(commits.into_iter().next())
     }
 
+    /// List commits recorded as created on `branch`, oldest first
+    ///
+    /// Uses the advisory `branch` field, so commits that only became
+    /// reachable from the branch later (e.g. via fast-forward) are not
+    /// included.
+    #[instrument(skip(self))]
+    pub async fn list_commits_on_branch(&self, branch: &str) -> Result<Vec<CommitRecord>> {
+        let mut result = self
+            .db
+            .query("SELECT * FROM commits WHERE branch = $branch ORDER BY created_at ASC")
+            .bind(("branch", branch.to_string()))
+            .await?;
+        let commits: Vec<CommitRecord> = result.take(0)?;
+        Ok(commits)
+    }
+
     // ========== Snapshot Operations ==========
 
     /// Save a snapshot (agent state)
@@ -354,11 +407,91 @@ impl SurrealHandle {
         Ok(())
     }
 
+    /// Save a snapshot as a JSON Patch against its parent commit's state
+    ///
+    /// Falls back to a full snapshot ("keyframe") when there is no parent
+    /// snapshot or the parent's delta chain would reach `keyframe_interval`,
+    /// which bounds how many patches [`load_snapshot`](Self::load_snapshot)
+    /// has to replay.
+    #[instrument(skip(self, state))]
+    pub async fn save_snapshot_delta(
+        &self,
+        commit_id: &CommitId,
+        state: serde_json::Value,
+        parent_commit: Option<&str>,
+        keyframe_interval: u32,
+    ) -> Result<()> {
+        let base = match parent_commit {
+            Some(parent) => match self.fetch_snapshot_record(parent).await {
+                Ok(record) => Some((parent, record.chain_depth)),
+                Err(StateError::CommitNotFound(_) | StateError::SnapshotPruned(_)) => None,
+                Err(e) => return Err(e),
+            },
+            None => None,
+        };
+
+        let record = match base {
+            Some((parent, depth)) if depth + 1 < keyframe_interval => {
+                let parent_state = self.load_snapshot(parent).await?.state;
+                let patch = crate::json_patch::diff(&parent_state, &state);
+                SnapshotRecord::delta(&commit_id.hash, parent, patch, depth + 1)
+            }
+            _ => SnapshotRecord::new(&commit_id.hash, state),
+        };
+
+        debug!(
+            "Saving {} snapshot for commit {} (depth {})",
+            if record.is_delta() { "delta" } else { "full" },
+            commit_id.short(),
+            record.chain_depth
+        );
+
+        let _created: Option<SnapshotRecord> = self.db.create("snapshots").content(record).await?;
+        Ok(())
+    }
+
     /// Load a snapshot by commit ID
+    ///
+    /// Delta snapshots are reconstructed by replaying patches from the
+    /// nearest full snapshot, so the returned `state` is always complete.
     #[instrument(skip(self))]
     pub async fn load_snapshot(&self, commit_id: &str) -> Result<SnapshotRecord> {
         debug!("Loading snapshot");
 
+        let mut record = self.fetch_snapshot_record(commit_id).await?;
+        if !record.is_delta() {
+            return Ok(record);
+        }
+
+        // Walk back to the keyframe, collecting patches newest first.
+        let mut patches = Vec::new();
+        let mut current = record.clone();
+        while let Some(parent) = current.delta_parent.clone() {
+            patches.push(current.patch.take().unwrap_or_default());
+            current = self.fetch_snapshot_record(&parent).await?;
+        }
+
+        let mut state = current.state;
+        for patch in patches.iter().rev() {
+            crate::json_patch::apply(&mut state, patch).map_err(|e| {
+                StateError::Deserialization(format!(
+                    "failed to apply snapshot delta for {}: {}",
+                    commit_id, e
+                ))
+            })?;
+        }
+
+        record.state = state;
+        record.patch = None;
+        record.delta_parent = None;
+        Ok(record)
+    }
+
+    /// Fetch the stored snapshot row without reconstructing deltas
+    ///
+    /// For a delta snapshot `state` is empty and `size_bytes` is the size of
+    /// the patch; use [`load_snapshot`](Self::load_snapshot) for the state.
+    pub async fn fetch_snapshot_record(&self, commit_id: &str) -> Result<SnapshotRecord> {
         let id_owned = commit_id.to_string();
 
         let mut result = self
@@ -368,10 +501,150 @@ impl SurrealHandle {
             .await?;
 
         let snapshots: Vec<SnapshotRecord> = result.take(0)?;
-        snapshots
+        if let Some(record) = snapshots.into_iter().next() {
+            return Ok(record);
+        }
+        match self.get_commit(commit_id).await? {
+            Some(commit) if commit.snapshot_pruned => {
+                Err(StateError::SnapshotPruned(commit_id.to_string()))
+            }
+            _ => Err(StateError::CommitNotFound(commit_id.to_string())),
+        }
+    }
+
+    /// Store `commit_id`'s snapshot as a full state, replacing a delta
+    ///
+    /// Lets the commits a delta chain runs through be pruned without
+    /// losing this one.
+    #[instrument(skip(self))]
+    pub async fn rewrite_snapshot_as_keyframe(&self, commit_id: &str) -> Result<()> {
+        let full = self.load_snapshot(commit_id).await?;
+        let size = serde_json::to_string(&full.state)?.len() as u64;
+        self.db
+            .query(
+                "UPDATE snapshots SET state = $state, size_bytes = $size, delta_parent = NONE, \
+                 patch = NONE, chain_depth = 0 WHERE commit_id = $id",
+            )
+            .bind(("state", full.state))
+            .bind(("size", size))
+            .bind(("id", commit_id.to_string()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Delete `commit_id`'s snapshot and mark the commit as pruned
+    ///
+    /// The commit record stays, so history and ancestry are unchanged;
+    /// loading the snapshot fails with [`StateError::SnapshotPruned`].
+    /// Returns whether a snapshot was deleted.
+    #[instrument(skip(self))]
+    pub async fn prune_snapshot(&self, commit_id: &str) -> Result<bool> {
+        let mut existing = self
+            .db
+            .query("SELECT VALUE commit_id FROM snapshots WHERE commit_id = $id")
+            .bind(("id", commit_id.to_string()))
+            .await?;
+        let existing: Vec<String> = existing.take(0)?;
+
+        // Deletion and marker commit together, so a commit never loses its
+        // snapshot without being marked
+        self.db
+            .query(
+                "BEGIN TRANSACTION;\n\
+                 DELETE snapshots WHERE commit_id = $id;\n\
+                 UPDATE commits SET snapshot_pruned = true WHERE commit_id.hash = $id;\n\
+                 COMMIT TRANSACTION;",
+            )
+            .bind(("id", commit_id.to_string()))
+            .await?
+            .check()?;
+        Ok(!existing.is_empty())
+    }
+
+    /// Save the metadata recorded when `commit_id`'s snapshot was taken
+    ///
+    /// `meta` is stored as JSON; the caller owns its shape.
+    #[instrument(skip(self, meta))]
+    pub async fn save_snapshot_meta<T: serde::Serialize>(
+        &self,
+        commit_id: &str,
+        meta: &T,
+    ) -> Result<()> {
+        #[derive(serde::Serialize, serde::Deserialize)]
+        struct SnapshotMetaStore {
+            commit_id: String,
+            meta_json: String,
+        }
+
+        let payload = SnapshotMetaStore {
+            commit_id: commit_id.to_string(),
+            meta_json: serde_json::to_string(meta)?,
+        };
+        let _created: Option<SnapshotMetaStore> =
+            self.db.create("snapshot_meta").content(payload).await?;
+        Ok(())
+    }
+
+    /// Load the metadata saved by [`save_snapshot_meta`](Self::save_snapshot_meta)
+    #[instrument(skip(self))]
+    pub async fn get_snapshot_meta<T: serde::de::DeserializeOwned>(
+        &self,
+        commit_id: &str,
+    ) -> Result<Option<T>> {
+        #[derive(serde::Deserialize)]
+        struct SnapshotMetaStore {
+            meta_json: String,
+        }
+
+        let mut result = self
+            .db
+            .query("SELECT meta_json FROM snapshot_meta WHERE commit_id = $id")
+            .bind(("id", commit_id.to_string()))
+            .await?;
+        let metas: Vec<SnapshotMetaStore> = result.take(0)?;
+        metas
             .into_iter()
             .next()
-            .ok_or_else(|| StateError::CommitNotFound(commit_id.to_string()))
+            .map(|m| serde_json::from_str(&m.meta_json))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    // ========== Eval Report Index ==========
+
+    /// Point `report_key` at the CAS blob `report_digest`, replacing any
+    /// earlier report under the same key
+    #[instrument(skip(self))]
+    pub async fn save_eval_report_digest(
+        &self,
+        report_key: &str,
+        report_digest: &str,
+    ) -> Result<()> {
+        self.db
+            .query(
+                "BEGIN TRANSACTION;\n\
+                 DELETE eval_reports WHERE report_key = $key;\n\
+                 CREATE eval_reports CONTENT { report_key: $key, report_digest: $digest, recorded_at: time::now() };\n\
+                 COMMIT TRANSACTION;",
+            )
+            .bind(("key", report_key.to_string()))
+            .bind(("digest", report_digest.to_string()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// CAS digest of the report saved under `report_key`, if any
+    #[instrument(skip(self))]
+    pub async fn get_eval_report_digest(&self, report_key: &str) -> Result<Option<String>> {
+        let mut result = self
+            .db
+            .query("SELECT VALUE report_digest FROM eval_reports WHERE report_key = $key")
+            .bind(("key", report_key.to_string()))
+            .await?;
+        let digests: Vec<String> = result.take(0)?;
+        Ok(digests.into_iter().next())
     }
 
     // ========== Graph Edge Operations ==========
@@ -489,6 +762,59 @@ impl SurrealHandle {
         }
     }
 
+    /// Create branch `record` unless one of that name exists
+    ///
+    /// Returns `Ok(false)` without writing when the branch exists, including
+    /// when a concurrent writer creates it first and the unique
+    /// `idx_branch_name` index rejects this create.
+    #[instrument(skip(self))]
+    pub async fn create_branch_if_absent(&self, record: &BranchRecord) -> Result<bool> {
+        if self.get_branch(&record.name).await?.is_some() {
+            return Ok(false);
+        }
+
+        let created: std::result::Result<Option<BranchRecord>, _> =
+            self.db.create("branches").content(record.clone()).await;
+        match created {
+            Ok(Some(_)) => Ok(true),
+            Ok(None) => Err(StateError::Transaction(
+                "Failed to create branch".to_string(),
+            )),
+            Err(_) if self.get_branch(&record.name).await?.is_some() => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Move branch `name` from `expected_head` to `new_head` atomically
+    ///
+    /// Returns `Ok(false)` without writing when the branch is missing or its
+    /// head is no longer `expected_head`, so the caller can re-read and retry
+    /// instead of overwriting a concurrent update.
+    #[instrument(skip(self))]
+    pub async fn update_branch_head_cas(
+        &self,
+        name: &str,
+        expected_head: &str,
+        new_head: &str,
+    ) -> Result<bool> {
+        let now = SurrealDatetime::from(chrono::Utc::now());
+
+        let mut result = self
+            .db
+            .query(
+                "UPDATE branches SET head_commit_id = $new, updated_at = $now \
+                 WHERE name = $name AND head_commit_id = $expected",
+            )
+            .bind(("new", new_head.to_string()))
+            .bind(("now", now))
+            .bind(("name", name.to_string()))
+            .bind(("expected", expected_head.to_string()))
+            .await?;
+
+        let updated: Vec<BranchRecord> = result.take(0)?;
+        Ok(!updated.is_empty())
+    }
+
     /// Get a branch by name
     #[instrument(skip(self))]
     pub async fn get_branch(&self, name: &str) -> Result<Option<BranchRecord>> {
@@ -542,6 +868,12 @@ impl SurrealHandle {
                 "Cannot delete the default branch".to_string(),
             ));
         }
+        if self.get_protection(name).await?.no_delete {
+            return Err(StateError::BranchProtected {
+                branch: name.to_string(),
+                rule: "no-delete".to_string(),
+            });
+        }
 
         let name_owned = name.to_string();
 
@@ -554,6 +886,66 @@ impl SurrealHandle {
         Ok(())
     }
 
+    /// Set the protection rules of branch `name`, replacing any existing ones
+    ///
+    /// The branch itself need not exist yet.
+    #[instrument(skip(self))]
+    pub async fn set_protection(
+        &self,
+        name: &str,
+        protection: BranchProtection,
+    ) -> Result<BranchProtectionRecord> {
+        let record = BranchProtectionRecord::new(name, protection);
+
+        if self.get_protection_record(name).await?.is_some() {
+            let now = SurrealDatetime::from(record.updated_at);
+            let mut result = self
+                .db
+                .query(
+                    "UPDATE branch_protections SET no_force_push = $no_force_push, \
+                     no_delete = $no_delete, require_linear = $require_linear, \
+                     updated_at = $now WHERE name = $name",
+                )
+                .bind(("no_force_push", protection.no_force_push))
+                .bind(("no_delete", protection.no_delete))
+                .bind(("require_linear", protection.require_linear))
+                .bind(("now", now))
+                .bind(("name", name.to_string()))
+                .await?;
+
+            let updated: Vec<BranchProtectionRecord> = result.take(0)?;
+            updated.into_iter().next().ok_or_else(|| {
+                StateError::Transaction("Failed to update branch protection".to_string())
+            })
+        } else {
+            let created: Option<BranchProtectionRecord> =
+                self.db.create("branch_protections").content(record).await?;
+            created.ok_or_else(|| {
+                StateError::Transaction("Failed to create branch protection".to_string())
+            })
+        }
+    }
+
+    /// Protection rules of branch `name`; all rules are off if none were set
+    #[instrument(skip(self))]
+    pub async fn get_protection(&self, name: &str) -> Result<BranchProtection> {
+        Ok(self
+            .get_protection_record(name)
+            .await?
+            .map(|r| r.protection())
+            .unwrap_or_default())
+    }
+
+    async fn get_protection_record(&self, name: &str) -> Result<Option<BranchProtectionRecord>> {
+        let mut result = self
+            .db
+            .query("SELECT * FROM branch_protections WHERE name = $name")
+            .bind(("name", name.to_string()))
+            .await?;
+        let records: Vec<BranchProtectionRecord> = result.take(0)?;
+        Ok(records.into_iter().next())
+    }
+
     // ========== Agent Operations ==========
 
     /// Register an agent
@@ -598,6 +990,57 @@ impl SurrealHandle {
         created.ok_or_else(|| StateError::Transaction("Failed to save memory".to_string()))
     }
 
+    /// Save many memory records with a single INSERT statement
+    ///
+    /// Either every record is stored or, if the statement fails, none is.
+    #[instrument(skip(self, records), fields(count = records.len()))]
+    pub async fn save_memories_batch(&self, records: &[MemoryRecord]) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+        debug!("Saving {} memories in one batch", records.len());
+
+        let created: Vec<MemoryRecord> =
+            self.db.insert("memories").content(records.to_vec()).await?;
+
+        if created.len() != records.len() {
+            return Err(StateError::Transaction(format!(
+                "Batch memory insert stored {} of {} records",
+                created.len(),
+                records.len()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Save `memories` and the `provenance` rows describing them in one
+    /// transaction, so no memory is stored without its provenance
+    #[instrument(skip(self, memories, provenance), fields(count = memories.len()))]
+    pub async fn save_memories_with_provenance(
+        &self,
+        memories: &[MemoryRecord],
+        provenance: &[MemoryProvenanceRecord],
+    ) -> Result<()> {
+        debug!(
+            "Saving {} memories with {} provenance rows",
+            memories.len(),
+            provenance.len()
+        );
+
+        self.db
+            .query(
+                "BEGIN TRANSACTION;\n\
+                 FOR $memory IN $memories { CREATE memories CONTENT $memory; };\n\
+                 FOR $row IN $provenance { CREATE memory_provenances CONTENT $row; };\n\
+                 COMMIT TRANSACTION;",
+            )
+            .bind(("memories", memories.to_vec()))
+            .bind(("provenance", provenance.to_vec()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
     /// Get all memories for a commit
     #[instrument(skip(self))]
     pub async fn get_memories(&self, commit_id: &str) -> Result<Vec<MemoryRecord>> {
@@ -647,6 +1090,50 @@ impl SurrealHandle {
         Ok(was_deleted)
     }
 
+    /// Delete the memory with `key` belonging to `commit_id`
+    ///
+    /// Unlike [`delete_memory`](Self::delete_memory), memories with the same
+    /// key on other commits are left untouched.
+    #[instrument(skip(self))]
+    pub async fn delete_commit_memory(&self, commit_id: &str, key: &str) -> Result<bool> {
+        let id_owned = commit_id.to_string();
+        let key_owned = key.to_string();
+
+        let mut result = self
+            .db
+            .query("DELETE FROM memories WHERE commit_id = $id AND key = $key RETURN BEFORE")
+            .bind(("id", id_owned))
+            .bind(("key", key_owned))
+            .await?;
+
+        let deleted_records: Vec<MemoryRecord> = result.take(0)?;
+        Ok(!deleted_records.is_empty())
+    }
+
+    /// Set the embedding of the memory with `key` belonging to `commit_id`
+    ///
+    /// Returns `false` if no such memory exists.
+    #[instrument(skip(self, embedding), fields(dims = embedding.len()))]
+    pub async fn set_memory_embedding(
+        &self,
+        commit_id: &str,
+        key: &str,
+        embedding: &[f32],
+    ) -> Result<bool> {
+        let mut result = self
+            .db
+            .query(
+                "UPDATE memories SET embedding = $embedding WHERE commit_id = $id AND key = $key",
+            )
+            .bind(("embedding", embedding.to_vec()))
+            .bind(("id", commit_id.to_string()))
+            .bind(("key", key.to_string()))
+            .await?;
+
+        let updated: Vec<MemoryRecord> = result.take(0)?;
+        Ok(!updated.is_empty())
+    }
+
     // ========== Release Registry Operations ==========
 
     /// Promote a new release for an agent.
@@ -952,14 +1439,17 @@ impl SurrealHandle {
     ) -> Result<DecisionRecord> {
         let id_owned = decision_id.to_string();
         let now = SurrealDatetime::from(Utc::now());
+        let status = decision_outcome_status(&outcome_json);
 
         let mut result = self
             .db
             .query(
-                "UPDATE decisions SET outcome = $outcome, outcome_at = $outcome_at WHERE decision_id = $id RETURN AFTER",
+                "UPDATE decisions SET outcome = $outcome, outcome_status = $status, \
+                 outcome_at = $outcome_at WHERE decision_id = $id RETURN AFTER",
             )
             .bind(("id", id_owned))
             .bind(("outcome", outcome_json))
+            .bind(("status", status))
             .bind(("outcome_at", now))
             .await?;
 
@@ -992,6 +1482,47 @@ impl SurrealHandle {
         Ok(decisions)
     }
 
+    /// Decisions matching `filter`, newest first
+    #[instrument(skip(self))]
+    pub async fn query_decisions(&self, filter: DecisionFilter) -> Result<Vec<DecisionRecord>> {
+        let mut conditions = Vec::new();
+        if filter.task.is_some() {
+            conditions.push("task = $task");
+        }
+        if filter.outcome.is_some() {
+            conditions.push("outcome_status = $outcome");
+        }
+        if filter.since.is_some() {
+            conditions.push("timestamp >= $since");
+        }
+        if filter.until.is_some() {
+            conditions.push("timestamp <= $until");
+        }
+
+        let mut sql = "SELECT * FROM decisions".to_string();
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY timestamp DESC");
+        if filter.limit.is_some() {
+            sql.push_str(" LIMIT $limit");
+        }
+
+        let mut result = self
+            .db
+            .query(sql)
+            .bind(("task", filter.task))
+            .bind(("outcome", filter.outcome))
+            .bind(("since", filter.since.map(SurrealDatetime::from)))
+            .bind(("until", filter.until.map(SurrealDatetime::from)))
+            .bind(("limit", filter.limit.map(|l| l as i64)))
+            .await?;
+
+        let decisions: Vec<DecisionRecord> = result.take(0)?;
+        Ok(decisions)
+    }
+
     /// Save a memory provenance record
     #[instrument(skip(self, record))]
     pub async fn save_provenance(
@@ -1026,6 +1557,262 @@ impl SurrealHandle {
         Ok(provenances)
     }
 
+    // ========== Plan Operations ==========
+
+    /// Save a plan and its tasks, replacing any previously saved copy
+    #[instrument(skip(self, plan, tasks), fields(plan_id = %plan.plan_id))]
+    pub async fn save_plan(&self, plan: &PlanRecord, tasks: &[PlanTaskRecord]) -> Result<()> {
+        debug!("Saving plan with {} tasks", tasks.len());
+
+        let plan_id = plan.plan_id.clone();
+        let _result = self
+            .db
+            .query(
+                "DELETE FROM plan_tasks WHERE plan_id = $id; DELETE FROM plans WHERE plan_id = $id",
+            )
+            .bind(("id", plan_id))
+            .await?;
+
+        let created: Option<PlanRecord> = self.db.create("plans").content(plan.clone()).await?;
+        if created.is_none() {
+            return Err(StateError::Transaction("Failed to create plan".to_string()));
+        }
+
+        for task in tasks {
+            let mut task_owned = task.clone();
+            task_owned.plan_id = plan.plan_id.clone();
+            let created: Option<PlanTaskRecord> =
+                self.db.create("plan_tasks").content(task_owned).await?;
+            if created.is_none() {
+                return Err(StateError::Transaction(format!(
+                    "Failed to create plan task {}",
+                    task.task_id
+                )));
+            }
+        }
+
+        info!("Plan saved: {} ({} tasks)", plan.plan_id, tasks.len());
+        Ok(())
+    }
+
+    /// Load a plan and its tasks (ordered by task ID)
+    #[instrument(skip(self))]
+    pub async fn load_plan(
+        &self,
+        plan_id: &str,
+    ) -> Result<Option<(PlanRecord, Vec<PlanTaskRecord>)>> {
+        let id_owned = plan_id.to_string();
+
+        let mut result = self
+            .db
+            .query("SELECT * FROM plans WHERE plan_id = $id")
+            .bind(("id", id_owned.clone()))
+            .await?;
+        let plans: Vec<PlanRecord> = result.take(0)?;
+        let Some(plan) = plans.into_iter().next() else {
+            return Ok(None);
+        };
+
+        let mut result = self
+            .db
+            .query("SELECT * FROM plan_tasks WHERE plan_id = $id ORDER BY task_id")
+            .bind(("id", id_owned))
+            .await?;
+        let tasks: Vec<PlanTaskRecord> = result.take(0)?;
+
+        Ok(Some((plan, tasks)))
+    }
+
+    /// Update the status of a single plan task
+    #[instrument(skip(self, status))]
+    pub async fn update_task_status(
+        &self,
+        plan_id: &str,
+        task_id: &str,
+        status: serde_json::Value,
+    ) -> Result<PlanTaskRecord> {
+        let plan_owned = plan_id.to_string();
+        let task_owned = task_id.to_string();
+        let now = SurrealDatetime::from(Utc::now());
+
+        let mut result = self
+            .db
+            .query(
+                "UPDATE plan_tasks SET status = $status, updated_at = $now WHERE plan_id = $plan AND task_id = $task RETURN AFTER; \
+                 UPDATE plans SET updated_at = $now WHERE plan_id = $plan",
+            )
+            .bind(("plan", plan_owned))
+            .bind(("task", task_owned))
+            .bind(("status", status))
+            .bind(("now", now))
+            .await?;
+
+        let updated: Vec<PlanTaskRecord> = result.take(0)?;
+        updated.into_iter().next().ok_or_else(|| {
+            StateError::Transaction(format!("Plan task not found: {plan_id}/{task_id}"))
+        })
+    }
+
+    // ========== Stash Operations ==========
+
+    /// Save a stash; fails if a stash with the same name exists
+    #[instrument(skip(self, record), fields(name = %record.name))]
+    pub async fn save_stash(&self, record: &StashRecord) -> Result<StashRecord> {
+        if self.get_stash(&record.name).await?.is_some() {
+            return Err(StateError::Transaction(format!(
+                "Stash already exists: {}",
+                record.name
+            )));
+        }
+
+        let created: Option<StashRecord> =
+            self.db.create("stashes").content(record.clone()).await?;
+
+        created.ok_or_else(|| StateError::Transaction("Failed to create stash".to_string()))
+    }
+
+    /// Get a stash by name
+    #[instrument(skip(self))]
+    pub async fn get_stash(&self, name: &str) -> Result<Option<StashRecord>> {
+        let name_owned = name.to_string();
+
+        let mut result = self
+            .db
+            .query("SELECT * FROM stashes WHERE name = $name")
+            .bind(("name", name_owned))
+            .await?;
+
+        let stashes: Vec<StashRecord> = result.take(0)?;
+        Ok(stashes.into_iter().next())
+    }
+
+    /// List all stashes, newest first
+    #[instrument(skip(self))]
+    pub async fn list_stashes(&self) -> Result<Vec<StashRecord>> {
+        let mut result = self
+            .db
+            .query("SELECT * FROM stashes ORDER BY created_at DESC")
+            .await?;
+
+        let stashes: Vec<StashRecord> = result.take(0)?;
+        Ok(stashes)
+    }
+
+    /// Delete a stash by name, returning the removed record if there was one
+    #[instrument(skip(self))]
+    pub async fn delete_stash(&self, name: &str) -> Result<Option<StashRecord>> {
+        let name_owned = name.to_string();
+
+        let mut result = self
+            .db
+            .query("DELETE FROM stashes WHERE name = $name RETURN BEFORE")
+            .bind(("name", name_owned))
+            .await?;
+
+        let deleted: Vec<StashRecord> = result.take(0)?;
+        Ok(deleted.into_iter().next())
+    }
+
+    // ========== Bundle Operations ==========
+
+    /// List every commit, oldest first
+    #[instrument(skip(self))]
+    pub async fn list_commits(&self) -> Result<Vec<CommitRecord>> {
+        let mut result = self
+            .db
+            .query("SELECT * FROM commits ORDER BY created_at ASC")
+            .await?;
+        let commits: Vec<CommitRecord> = result.take(0)?;
+        Ok(commits)
+    }
+
+    /// List every stored snapshot row as-is (delta snapshots are not expanded)
+    #[instrument(skip(self))]
+    pub async fn list_snapshot_records(&self) -> Result<Vec<SnapshotRecord>> {
+        let mut result = self
+            .db
+            .query("SELECT * FROM snapshots ORDER BY created_at ASC")
+            .await?;
+        let snapshots: Vec<SnapshotRecord> = result.take(0)?;
+        Ok(snapshots)
+    }
+
+    /// List every memory record across all commits
+    #[instrument(skip(self))]
+    pub async fn list_all_memories(&self) -> Result<Vec<MemoryRecord>> {
+        let mut result = self
+            .db
+            .query("SELECT * FROM memories ORDER BY created_at ASC")
+            .await?;
+        let memories: Vec<MemoryRecord> = result.take(0)?;
+        Ok(memories)
+    }
+
+    /// List every commit graph edge
+    #[instrument(skip(self))]
+    pub async fn list_graph_edges(&self) -> Result<Vec<GraphEdge>> {
+        let mut result = self
+            .db
+            .query("SELECT * FROM graph_edges ORDER BY created_at ASC")
+            .await?;
+        let edges: Vec<GraphEdge> = result.take(0)?;
+        Ok(edges)
+    }
+
+    /// Insert a commit unless one with the same hash exists; returns whether it was inserted
+    #[instrument(skip(self, record), fields(commit_id = %record.commit_id))]
+    pub async fn import_commit(&self, record: &CommitRecord) -> Result<bool> {
+        if self.get_commit(&record.commit_id.hash).await?.is_some() {
+            return Ok(false);
+        }
+        let mut record = record.clone();
+        record.id = None;
+        self.save_commit(&record).await?;
+        Ok(true)
+    }
+
+    /// Insert a raw snapshot row unless the commit already has one
+    #[instrument(skip(self, record), fields(commit_id = %record.commit_id))]
+    pub async fn import_snapshot_record(&self, record: &SnapshotRecord) -> Result<bool> {
+        match self.fetch_snapshot_record(&record.commit_id).await {
+            Ok(_) => return Ok(false),
+            Err(StateError::CommitNotFound(_)) => {}
+            Err(e) => return Err(e),
+        }
+        let mut record = record.clone();
+        record.id = None;
+        let _created: Option<SnapshotRecord> = self.db.create("snapshots").content(record).await?;
+        Ok(true)
+    }
+
+    /// Insert a memory unless the commit already has one with the same key
+    #[instrument(skip(self, record), fields(key = %record.key))]
+    pub async fn import_memory(&self, record: &MemoryRecord) -> Result<bool> {
+        let existing = self.get_memories(&record.commit_id).await?;
+        if existing.iter().any(|m| m.key == record.key) {
+            return Ok(false);
+        }
+        let mut record = record.clone();
+        record.id = None;
+        self.save_memory(&record).await?;
+        Ok(true)
+    }
+
+    /// Insert a graph edge unless the same parent -> child edge exists
+    #[instrument(skip(self, edge))]
+    pub async fn import_graph_edge(&self, edge: &GraphEdge) -> Result<bool> {
+        if self
+            .get_children(&edge.parent_id)
+            .await?
+            .contains(&edge.child_id)
+        {
+            return Ok(false);
+        }
+        let _created: Option<GraphEdge> =
+            self.db.create("graph_edges").content(edge.clone()).await?;
+        Ok(true)
+    }
+
     // ========== History Operations ==========
 
     /// Get commit history (walk back from a commit)
@@ -1055,6 +1842,120 @@ impl SurrealHandle {
         Ok(history)
     }
 
+    /// Collect every commit reachable from `commit_id` (inclusive), following all parents
+    #[instrument(skip(self))]
+    pub async fn get_ancestors(&self, commit_id: &str) -> Result<HashSet<String>> {
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::from([commit_id.to_string()]);
+
+        while let Some(hash) = queue.pop_front() {
+            if !seen.insert(hash.clone()) {
+                continue;
+            }
+            if let Some(commit) = self.get_commit(&hash).await? {
+                queue.extend(commit.parent_ids);
+            }
+        }
+
+        Ok(seen)
+    }
+
+    /// Find the nearest common ancestor of two commits, if any
+    #[instrument(skip(self))]
+    pub async fn find_merge_base(&self, a: &str, b: &str) -> Result<Option<String>> {
+        let ancestors_a = self.get_ancestors(a).await?;
+
+        // Breadth-first from `b` so the first hit is the closest to `b`.
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::from([b.to_string()]);
+        while let Some(hash) = queue.pop_front() {
+            if ancestors_a.contains(&hash) {
+                return Ok(Some(hash));
+            }
+            if !seen.insert(hash.clone()) {
+                continue;
+            }
+            if let Some(commit) = self.get_commit(&hash).await? {
+                queue.extend(commit.parent_ids);
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Get commits reachable from `to` but not from `from` (git's `from..to`), newest first
+    ///
+    /// Fails with [`StateError::CommitNotFound`] if either endpoint is not
+    /// a stored commit.
+    #[instrument(skip(self))]
+    pub async fn get_commit_range(
+        &self,
+        from: &str,
+        to: &str,
+        limit: usize,
+    ) -> Result<Vec<CommitRecord>> {
+        for endpoint in [from, to] {
+            if self.get_commit(endpoint).await?.is_none() {
+                return Err(StateError::CommitNotFound(endpoint.to_string()));
+            }
+        }
+        let excluded = self.get_ancestors(from).await?;
+
+        let mut range = Vec::new();
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::from([to.to_string()]);
+        while let Some(hash) = queue.pop_front() {
+            if excluded.contains(&hash) || !seen.insert(hash.clone()) {
+                continue;
+            }
+            if let Some(commit) = self.get_commit(&hash).await? {
+                queue.extend(commit.parent_ids.iter().cloned());
+                range.push(commit);
+            }
+        }
+
+        // Breadth-first order interleaves the sides of a merge
+        range.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        range.truncate(limit);
+        Ok(range)
+    }
+
+    /// History of memory `key` along `branch`'s first-parent chain, oldest first
+    ///
+    /// Each entry pairs a commit hash with the key's memory at that commit.
+    /// Commits where the key is absent or its content is unchanged from the
+    /// previous entry are skipped.
+    #[instrument(skip(self))]
+    pub async fn get_memory_history(
+        &self,
+        branch: &str,
+        key: &str,
+    ) -> Result<Vec<(String, MemoryRecord)>> {
+        let head = self.get_branch_head(branch).await?;
+        let history = self.get_commit_history(&head, usize::MAX).await?;
+
+        let mut entries: Vec<(String, MemoryRecord)> = Vec::new();
+        for commit in history.into_iter().rev() {
+            let hash = commit.commit_id.hash;
+            let Some(memory) = self
+                .get_memories(&hash)
+                .await?
+                .into_iter()
+                .rev()
+                .find(|m| m.key == key)
+            else {
+                continue;
+            };
+            let changed = entries
+                .last()
+                .is_none_or(|(_, prev)| prev.content != memory.content);
+            if changed {
+                entries.push((hash, memory));
+            }
+        }
+        Ok(entries)
+    }
+
     /// Get the reasoning trace (CoT) for time-travel debugging
     ///
     /// # TDD: test_get_trace_for_commit_id_returns_correct_CoT
@@ -1156,9 +2057,124 @@ mod tests {
             updated.outcome,
             Some(r#"{"status":"success","duration_ms":123}"#.to_string())
         );
+        assert_eq!(updated.outcome_status.as_deref(), Some("success"));
         assert!(updated.outcome_at.is_some());
     }
 
+    #[tokio::test]
+    async fn test_query_decisions_filters_by_outcome_task_and_time() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+
+        let outcomes = [
+            ("dec-q-1", "deploy", Some(r#"{"status":"failure"}"#)),
+            ("dec-q-2", "deploy", Some(r#""success""#)),
+            ("dec-q-3", "retry", Some(r#"{"failure":{"code":1}}"#)),
+            ("dec-q-4", "retry", Some("partial")),
+            ("dec-q-5", "deploy", None),
+        ];
+        for (id, task, outcome) in outcomes {
+            let decision = DecisionRecord::new(
+                id.to_string(),
+                "commit-q".to_string(),
+                task.to_string(),
+                "act".to_string(),
+                "because".to_string(),
+                0.5,
+            );
+            handle.save_decision(&decision).await.unwrap();
+            if let Some(outcome) = outcome {
+                handle
+                    .update_decision_outcome(id, outcome.to_string())
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let failures = handle
+            .query_decisions(DecisionFilter::default().with_outcome("Failure"))
+            .await
+            .unwrap();
+        let mut ids: Vec<_> = failures.iter().map(|d| d.decision_id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["dec-q-1", "dec-q-3"]);
+
+        let deploy_failures = handle
+            .query_decisions(
+                DecisionFilter::default()
+                    .with_task("deploy")
+                    .with_outcome("failure"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(deploy_failures.len(), 1);
+        assert_eq!(deploy_failures[0].decision_id, "dec-q-1");
+
+        let all = handle
+            .query_decisions(DecisionFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 5);
+
+        let future = handle
+            .query_decisions(
+                DecisionFilter::default().with_since(Utc::now() + chrono::Duration::hours(1)),
+            )
+            .await
+            .unwrap();
+        assert!(future.is_empty());
+
+        let limited = handle
+            .query_decisions(DecisionFilter::default().with_limit(2))
+            .await
+            .unwrap();
+        assert_eq!(limited.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_outcomes_recorded_before_status_are_backfilled_by_migration() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+        let decision = DecisionRecord::new(
+            "dec-old".to_string(),
+            "commit-old".to_string(),
+            "deploy".to_string(),
+            "act".to_string(),
+            "because".to_string(),
+            0.5,
+        );
+        handle.save_decision(&decision).await.unwrap();
+        // As written before `outcome_status` was introduced
+        handle
+            .db()
+            .query("UPDATE decisions SET outcome = $outcome WHERE decision_id = 'dec-old'")
+            .bind(("outcome", r#"{"status":"failure"}"#.to_string()))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        let failures = handle
+            .query_decisions(DecisionFilter::default().with_outcome("failure"))
+            .await
+            .unwrap();
+        assert!(failures.is_empty());
+
+        // Re-apply the `outcome_status` migration as a database that predates it would
+        handle
+            .db()
+            .query("DELETE schema_migrations WHERE migration_id = 10")
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        handle.init_schema().await.unwrap();
+
+        let failures = handle
+            .query_decisions(DecisionFilter::default().with_outcome("failure"))
+            .await
+            .unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].outcome_status.as_deref(), Some("failure"));
+    }
+
     #[tokio::test]
     async fn test_snapshot_is_atomic_and_retrievable() {
         let handle = SurrealHandle::setup_db().await.unwrap();
@@ -1351,6 +2367,7 @@ mod tests {
             version_label: Some("v1.2.3".to_string()),
             promoted_by: "test-user".to_string(),
             notes: Some("Release notes here".to_string()),
+            ..Default::default()
         };
         let digest = ContentDigest::from_bytes(b"spec-data");
 
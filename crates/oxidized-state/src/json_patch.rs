@@ -0,0 +1,189 @@
+//! Minimal RFC 6902 JSON Patch support for delta-encoded snapshots.
+//!
+//! [`diff`] produces `add` / `remove` / `replace` operations only; [`apply`]
+//! understands the same subset. Arrays of equal length are diffed element by
+//! element, otherwise they are replaced wholesale.
+
+use serde_json::{json, Value};
+
+/// Compute a patch that turns `from` into `to`.
+pub fn diff(from: &Value, to: &Value) -> Vec<Value> {
+    let mut ops = Vec::new();
+    diff_at("", from, to, &mut ops);
+    ops
+}
+
+fn diff_at(path: &str, from: &Value, to: &Value, ops: &mut Vec<Value>) {
+    match (from, to) {
+        (Value::Object(a), Value::Object(b)) => {
+            for key in a.keys() {
+                if !b.contains_key(key) {
+                    ops.push(json!({ "op": "remove", "path": child_path(path, key) }));
+                }
+            }
+            for (key, b_val) in b {
+                let child = child_path(path, key);
+                match a.get(key) {
+                    Some(a_val) => diff_at(&child, a_val, b_val, ops),
+                    None => ops.push(json!({ "op": "add", "path": child, "value": b_val })),
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(b)) if a.len() == b.len() => {
+            for (i, (a_val, b_val)) in a.iter().zip(b).enumerate() {
+                diff_at(&format!("{path}/{i}"), a_val, b_val, ops);
+            }
+        }
+        _ if from != to => {
+            ops.push(json!({ "op": "replace", "path": path, "value": to }));
+        }
+        _ => {}
+    }
+}
+
+fn child_path(parent: &str, key: &str) -> String {
+    format!("{parent}/{}", key.replace('~', "~0").replace('/', "~1"))
+}
+
+/// Apply `patch` to `doc` in place.
+pub fn apply(doc: &mut Value, patch: &[Value]) -> Result<(), String> {
+    for op in patch {
+        let kind = op["op"].as_str().ok_or("patch op missing 'op'")?;
+        let path = op["path"].as_str().ok_or("patch op missing 'path'")?;
+        match kind {
+            "add" | "replace" => {
+                let value = op.get("value").ok_or("patch op missing 'value'")?.clone();
+                set(doc, path, value, kind == "add")?;
+            }
+            "remove" => remove(doc, path)?,
+            other => return Err(format!("unsupported patch op '{other}'")),
+        }
+    }
+    Ok(())
+}
+
+fn tokens(path: &str) -> Result<Vec<String>, String> {
+    if path.is_empty() {
+        return Ok(Vec::new());
+    }
+    let rest = path
+        .strip_prefix('/')
+        .ok_or_else(|| format!("invalid JSON pointer '{path}'"))?;
+    Ok(rest
+        .split('/')
+        .map(|t| t.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+/// Resolve the container holding the last token of `path`.
+fn parent_mut<'a>(doc: &'a mut Value, toks: &[String]) -> Result<&'a mut Value, String> {
+    let mut cur = doc;
+    for tok in toks {
+        cur = match cur {
+            Value::Object(map) => map
+                .get_mut(tok)
+                .ok_or_else(|| format!("path segment '{tok}' not found"))?,
+            Value::Array(arr) => {
+                let i: usize = tok
+                    .parse()
+                    .map_err(|_| format!("invalid array index '{tok}'"))?;
+                arr.get_mut(i)
+                    .ok_or_else(|| format!("array index {i} out of bounds"))?
+            }
+            _ => return Err(format!("cannot descend into scalar at '{tok}'")),
+        };
+    }
+    Ok(cur)
+}
+
+fn set(doc: &mut Value, path: &str, value: Value, insert: bool) -> Result<(), String> {
+    let toks = tokens(path)?;
+    let Some((last, parents)) = toks.split_last() else {
+        *doc = value;
+        return Ok(());
+    };
+    match parent_mut(doc, parents)? {
+        Value::Object(map) => {
+            map.insert(last.clone(), value);
+        }
+        Value::Array(arr) => {
+            if insert && last == "-" {
+                arr.push(value);
+            } else {
+                let i: usize = last
+                    .parse()
+                    .map_err(|_| format!("invalid array index '{last}'"))?;
+                if insert && i <= arr.len() {
+                    arr.insert(i, value);
+                } else if !insert && i < arr.len() {
+                    arr[i] = value;
+                } else {
+                    return Err(format!("array index {i} out of bounds"));
+                }
+            }
+        }
+        _ => return Err(format!("cannot set '{path}' on a scalar")),
+    }
+    Ok(())
+}
+
+fn remove(doc: &mut Value, path: &str) -> Result<(), String> {
+    let toks = tokens(path)?;
+    let (last, parents) = toks.split_last().ok_or("cannot remove the document root")?;
+    match parent_mut(doc, parents)? {
+        Value::Object(map) => {
+            map.remove(last)
+                .ok_or_else(|| format!("key '{last}' not found"))?;
+        }
+        Value::Array(arr) => {
+            let i: usize = last
+                .parse()
+                .map_err(|_| format!("invalid array index '{last}'"))?;
+            if i >= arr.len() {
+                return Err(format!("array index {i} out of bounds"));
+            }
+            arr.remove(i);
+        }
+        _ => return Err(format!("cannot remove '{path}' from a scalar")),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_then_apply_roundtrips() {
+        let from = json!({
+            "step": 1,
+            "goal": "plan",
+            "memory": { "facts": ["a", "b"], "a/b~c": 1 },
+            "dropped": true
+        });
+        let to = json!({
+            "step": 2,
+            "goal": "plan",
+            "memory": { "facts": ["a", "c"], "a/b~c": 2, "new": null },
+            "tags": [1, 2, 3]
+        });
+
+        let patch = diff(&from, &to);
+        let mut doc = from.clone();
+        apply(&mut doc, &patch).unwrap();
+        assert_eq!(doc, to);
+    }
+
+    #[test]
+    fn identical_documents_produce_empty_patch() {
+        let v = json!({ "x": [1, { "y": 2 }] });
+        assert!(diff(&v, &v).is_empty());
+    }
+
+    #[test]
+    fn apply_rejects_unknown_op() {
+        let mut doc = json!({});
+        let err = apply(&mut doc, &[json!({ "op": "move", "path": "/a" })]).unwrap_err();
+        assert!(err.contains("unsupported"));
+    }
+}
@@ -15,35 +15,46 @@
 //! - `GraphEdge`: Schema mapping to the Graph Layer (Commit -> Parent)
 //! - `RunRecord`, `RunEventRecord`: Schema for execution run ledger
 //! - `ReleaseRecordSchema`: Schema for release management
+//! - `StashRecord`: Schema for stashed work-in-progress state
+//! - `BranchProtection`: Rules blocking destructive operations on a branch
 //! - `init_schema`: Initialize all tables with constraints and indexes
+//! - `run_migrations`: Apply versioned, forward-only schema migrations
+//! - `DbLock`: Advisory lock serializing schema initialization across processes
 
+mod canonical;
 mod ci;
 mod error;
 pub mod fakes;
 mod handle;
+pub mod json_patch;
+pub mod lock;
 pub mod migrations;
 mod schema;
 pub mod storage_traits;
 pub mod surreal_ledger;
 pub mod surreal_release_registry;
 
+pub use canonical::canonical_json;
 pub use ci::{
     CiArtifact, CiCommand, CiPipelineSpec, CiRunRecord, CiRunStatus, CiSnapshot, CiStepResult,
     CiStepSpec,
 };
 pub use error::{StateError, StorageError};
 pub use handle::{CloudConfig, SurrealHandle};
-pub use migrations::init_schema;
+pub use lock::{DbLock, LockOptions};
+pub use migrations::{init_schema, init_schema_with_lock, run_migrations, Migration};
 pub use schema::{
-    AgentRecord, BranchRecord, CommitId, CommitRecord, DecisionRecord, EdgeType, GraphEdge,
-    MemoryProvenanceRecord, MemoryRecord, ProvenanceSourceType, ReleaseRecordSchema,
-    RunEventRecord as DbRunEventRecord, RunRecord as DbRunRecord, SnapshotRecord,
+    decision_outcome_status, memory_id, short_hash, AgentRecord, BranchProtection,
+    BranchProtectionRecord, BranchRecord, CommitId, CommitRecord, DecisionFilter, DecisionRecord,
+    EdgeType, GraphEdge, MemoryProvenanceRecord, MemoryRecord, PlanRecord, PlanTaskRecord,
+    ProvenanceSourceType, ReleaseRecordSchema, RunEventRecord as DbRunEventRecord,
+    RunRecord as DbRunRecord, SnapshotRecord, StashRecord,
 };
 pub use storage_traits::{
     CasStore, ContentDigest, ReleaseMetadata, ReleaseRecord, ReleaseRegistry, RunEvent, RunId,
-    RunLedger, RunMetadata, RunRecord, RunStatus, RunSummary, StorageResult,
+    RunLedger, RunMetadata, RunRecord, RunStatus, RunSummary, RunUsage, StorageResult, TokenUsage,
 };
-pub use surreal_ledger::SurrealRunLedger;
+pub use surreal_ledger::{SurrealRunLedger, DEFAULT_TAIL_POLL_INTERVAL};
 pub use surreal_release_registry::SurrealDbReleaseRegistry;
 
 /// Result type for oxidized-state operations
@@ -0,0 +1,179 @@
+//! Advisory locks shared by every process using a database
+//!
+//! A lock is a row in the `locks` table; a unique index on its name means
+//! only one holder can insert it. Holders poll until the row is gone or the
+//! timeout passes. Rows older than the lease are treated as abandoned by a
+//! crashed process and removed, so a lost holder can't block forever.
+
+use std::time::{Duration, Instant};
+
+use surrealdb::engine::any::Any;
+use surrealdb::Surreal;
+use tracing::{debug, instrument, warn};
+
+use crate::error::violates_unique_index;
+use crate::{Result, StateError};
+
+/// DDL for the `locks` table
+const LOCKS_TABLE_SQL: &str = r#"
+        DEFINE TABLE IF NOT EXISTS locks SCHEMALESS;
+        DEFINE INDEX IF NOT EXISTS idx_lock_name ON TABLE locks COLUMNS name UNIQUE;
+"#;
+
+/// Unique index that lets only one holder insert a lock row
+const LOCK_NAME_INDEX: &str = "idx_lock_name";
+
+/// How long [`DbLock::acquire`] waits and how long a lock is honoured
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockOptions {
+    /// Give up with [`StateError::LockTimeout`] after waiting this long
+    pub timeout: Duration,
+    /// Pause between attempts while another process holds the lock
+    pub poll_interval: Duration,
+    /// Age after which a held lock counts as abandoned
+    pub lease: Duration,
+}
+
+impl Default for LockOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            poll_interval: Duration::from_millis(100),
+            lease: Duration::from_secs(300),
+        }
+    }
+}
+
+impl LockOptions {
+    /// Defaults, with the timeout taken from `AIVCS_LOCK_TIMEOUT_SECS` if
+    /// it is set to a number
+    pub fn from_env() -> Self {
+        let mut options = Self::default();
+        if let Some(secs) = std::env::var("AIVCS_LOCK_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+        {
+            options.timeout = Duration::from_secs(secs);
+        }
+        options
+    }
+}
+
+/// A held advisory lock; released by [`DbLock::release`] or, failing that,
+/// in the background when dropped
+pub struct DbLock {
+    db: Surreal<Any>,
+    name: String,
+    holder: String,
+    released: bool,
+}
+
+impl DbLock {
+    /// Acquire the lock `name`, waiting while another holder has it
+    #[instrument(skip(db, options))]
+    pub async fn acquire(db: &Surreal<Any>, name: &str, options: &LockOptions) -> Result<Self> {
+        db.query(LOCKS_TABLE_SQL).await?.check()?;
+        let holder = format!("pid {} ({})", std::process::id(), uuid::Uuid::new_v4());
+        let started = Instant::now();
+
+        loop {
+            db.query(
+                "DELETE locks WHERE name = $name AND acquired_at < time::now() - <duration>$lease",
+            )
+            .bind(("name", name.to_string()))
+            .bind(("lease", format!("{}ms", options.lease.as_millis())))
+            .await?
+            .check()?;
+
+            let created = db
+                .query("CREATE locks CONTENT { name: $name, holder: $holder, acquired_at: time::now() }")
+                .bind(("name", name.to_string()))
+                .bind(("holder", holder.clone()))
+                .await?
+                .check();
+            let err = match created {
+                Ok(_) => {
+                    debug!("Acquired lock");
+                    return Ok(Self {
+                        db: db.clone(),
+                        name: name.to_string(),
+                        holder,
+                        released: false,
+                    });
+                }
+                Err(err) => err,
+            };
+
+            // A failed insert only means contention if someone holds the lock,
+            // or held it until it was released after the insert failed
+            let current = match Self::holder_of(db, name).await? {
+                Some(current) => Some(current),
+                None if violates_unique_index(&err, LOCK_NAME_INDEX) => None,
+                None => return Err(err.into()),
+            };
+            if started.elapsed() >= options.timeout {
+                return Err(StateError::LockTimeout {
+                    name: name.to_string(),
+                    holder: current.unwrap_or_else(|| "a released holder".to_string()),
+                    waited: started.elapsed(),
+                });
+            }
+            match current {
+                Some(current) => {
+                    debug!(%current, "Lock held elsewhere; waiting");
+                    tokio::time::sleep(options.poll_interval).await;
+                }
+                None => debug!("Lock released while acquiring; retrying"),
+            }
+        }
+    }
+
+    /// Current holder of the lock `name`, if any
+    pub async fn holder_of(db: &Surreal<Any>, name: &str) -> Result<Option<String>> {
+        db.query(LOCKS_TABLE_SQL).await?.check()?;
+        let mut result = db
+            .query("SELECT VALUE holder FROM locks WHERE name = $name")
+            .bind(("name", name.to_string()))
+            .await?;
+        let holders: Vec<String> = result.take(0)?;
+        Ok(holders.into_iter().next())
+    }
+
+    /// Release the lock now
+    pub async fn release(mut self) -> Result<()> {
+        self.released = true;
+        delete_lock(&self.db, &self.name, &self.holder).await
+    }
+}
+
+impl Drop for DbLock {
+    fn drop(&mut self) {
+        if self.released {
+            return;
+        }
+        let (db, name, holder) = (self.db.clone(), self.name.clone(), self.holder.clone());
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => {
+                runtime.spawn(async move {
+                    if let Err(e) = delete_lock(&db, &name, &holder).await {
+                        warn!("Failed to release lock {}: {}", name, e);
+                    }
+                });
+            }
+            // The lease expires it eventually
+            Err(_) => warn!(
+                "Lock {} dropped outside a runtime; left to expire",
+                self.name
+            ),
+        }
+    }
+}
+
+async fn delete_lock(db: &Surreal<Any>, name: &str, holder: &str) -> Result<()> {
+    db.query("DELETE locks WHERE name = $name AND holder = $holder")
+        .bind(("name", name.to_string()))
+        .bind(("holder", holder.to_string()))
+        .await?
+        .check()?;
+    Ok(())
+}
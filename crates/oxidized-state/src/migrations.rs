@@ -1,47 +1,175 @@
 //! SurrealDB schema migrations and initialization
 //!
 //! This module provides initialization functions to set up all tables
-//! with proper constraints, indexes, and ACID guarantees.
+//! with proper constraints, indexes, and ACID guarantees. Schema changes are
+//! expressed as ordered, forward-only [`Migration`]s whose application is
+//! tracked in the `schema_migrations` table.
 
+use crate::lock::{DbLock, LockOptions};
 use crate::Result;
 use surrealdb::engine::any::Any;
 use surrealdb::Surreal;
 use tracing::{debug, info};
 
+/// Name of the advisory lock held while migrations run
+pub const SCHEMA_LOCK: &str = "schema_migrations";
+
 /// Initialize all AIVCS tables in SurrealDB
 ///
-/// This should be called once on first connection to set up the schema.
-/// Safe to call multiple times (idempotent).
+/// Applies the built-in [`migrations`] through [`run_migrations`]. Safe to
+/// call multiple times: already-applied migrations are skipped. Lock
+/// options come from the environment, see [`LockOptions::from_env`].
 pub async fn init_schema(db: &Surreal<Any>) -> Result<()> {
-    info!("Initializing AIVCS SurrealDB schema");
+    init_schema_with_lock(db, &LockOptions::from_env()).await
+}
 
-    // Core VCS tables
-    init_commits_table(db).await?;
-    init_snapshots_table(db).await?;
-    init_branches_table(db).await?;
-    init_graph_edges_table(db).await?;
-    init_memories_table(db).await?;
-    init_agents_table(db).await?;
+/// [`init_schema`] under the [`SCHEMA_LOCK`] advisory lock
+///
+/// Processes starting against the same database take turns: one applies
+/// pending migrations while the rest wait, then find nothing left to do.
+pub async fn init_schema_with_lock(db: &Surreal<Any>, options: &LockOptions) -> Result<()> {
+    info!("Initializing AIVCS SurrealDB schema");
+    let lock = DbLock::acquire(db, SCHEMA_LOCK, options).await?;
+    let applied = match run_migrations(db, &migrations()).await {
+        Ok(ids) if ids.contains(&DECISION_OUTCOME_STATUS_MIGRATION) => {
+            backfill_decision_outcome_status(db).await.map(|_| ids)
+        }
+        other => other,
+    };
+    lock.release().await?;
+    let applied = applied?;
+    info!(
+        "AIVCS schema initialization complete ({} migration(s) applied)",
+        applied.len()
+    );
+    Ok(())
+}
 
-    // Run Ledger tables
-    init_runs_table(db).await?;
-    init_run_events_table(db).await?;
+/// A forward-only schema migration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    /// Monotonic migration id; recorded in `schema_migrations` once applied
+    pub id: u32,
+    /// Human-readable name
+    pub name: String,
+    /// SurrealQL to execute
+    pub sql: String,
+}
 
-    // Release Registry tables
-    init_releases_table(db).await?;
+impl Migration {
+    /// Create a new migration
+    pub fn new(id: u32, name: impl Into<String>, sql: impl Into<String>) -> Self {
+        Migration {
+            id,
+            name: name.into(),
+            sql: sql.into(),
+        }
+    }
+}
 
-    // CI tables
-    init_ci_tables(db).await?;
+/// Built-in AIVCS migrations, in order
+///
+/// Append new migrations here with the next id; never edit one that has
+/// shipped.
+pub fn migrations() -> Vec<Migration> {
+    vec![
+        Migration::new(
+            1,
+            "initial_schema",
+            [
+                // Core VCS tables
+                COMMITS_TABLE_SQL,
+                SNAPSHOTS_TABLE_SQL,
+                BRANCHES_TABLE_SQL,
+                GRAPH_EDGES_TABLE_SQL,
+                MEMORIES_TABLE_SQL,
+                AGENTS_TABLE_SQL,
+                // Run Ledger tables
+                RUNS_TABLE_SQL,
+                RUN_EVENTS_TABLE_SQL,
+                // Release Registry tables
+                RELEASES_TABLE_SQL,
+                // CI tables
+                CI_TABLES_SQL,
+                // Memory and Decision tables (EPIC5)
+                DECISIONS_TABLE_SQL,
+                MEMORY_PROVENANCES_TABLE_SQL,
+                // Planning tables
+                PLAN_TABLES_SQL,
+            ]
+            .concat(),
+        ),
+        Migration::new(2, "stashes", STASHES_TABLE_SQL),
+        Migration::new(3, "branch_protections", BRANCH_PROTECTIONS_TABLE_SQL),
+        Migration::new(4, "commit_signatures", COMMIT_SIGNATURES_SQL),
+        Migration::new(5, "archived_runs", ARCHIVED_RUNS_TABLE_SQL),
+        Migration::new(6, "snapshot_meta", SNAPSHOT_META_TABLE_SQL),
+        Migration::new(7, "snapshot_retention", SNAPSHOT_RETENTION_SQL),
+        Migration::new(8, "eval_reports", EVAL_REPORTS_TABLE_SQL),
+        Migration::new(9, "decision_inputs", DECISION_INPUTS_SQL),
+        Migration::new(
+            DECISION_OUTCOME_STATUS_MIGRATION,
+            "decision_outcome_status",
+            DECISION_OUTCOME_STATUS_SQL,
+        ),
+    ]
+}
 
-    // Memory and Decision tables (EPIC5)
-    init_decisions_table(db).await?;
-    init_memory_provenances_table(db).await?;
+/// DDL for the `schema_migrations` bookkeeping table
+const SCHEMA_MIGRATIONS_SQL: &str = r#"
+        DEFINE TABLE IF NOT EXISTS schema_migrations SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS migration_id ON schema_migrations TYPE int;
+        DEFINE FIELD IF NOT EXISTS name ON schema_migrations TYPE string;
+        DEFINE FIELD IF NOT EXISTS applied_at ON schema_migrations TYPE datetime;
+        DEFINE INDEX IF NOT EXISTS idx_migration_id ON schema_migrations FIELDS migration_id UNIQUE;
+"#;
+
+/// Ids of migrations already recorded in `schema_migrations`, ascending
+pub async fn applied_migrations(db: &Surreal<Any>) -> Result<Vec<u32>> {
+    db.query(SCHEMA_MIGRATIONS_SQL).await?.check()?;
+    let mut result = db
+        .query("SELECT VALUE migration_id FROM schema_migrations")
+        .await?;
+    let ids: Vec<i64> = result.take(0)?;
+    let mut ids: Vec<u32> = ids.into_iter().map(|id| id as u32).collect();
+    ids.sort_unstable();
+    Ok(ids)
+}
 
-    info!("AIVCS schema initialization complete");
-    Ok(())
+/// Apply every migration not yet recorded in `schema_migrations`
+///
+/// Migrations run in ascending id order, each in its own transaction
+/// together with the row that records it, so a failing migration leaves
+/// neither partial schema changes nor a bookkeeping entry. Returns the ids
+/// applied by this call.
+pub async fn run_migrations(db: &Surreal<Any>, migrations: &[Migration]) -> Result<Vec<u32>> {
+    let done = applied_migrations(db).await?;
+
+    let mut pending: Vec<&Migration> = migrations
+        .iter()
+        .filter(|m| !done.contains(&m.id))
+        .collect();
+    pending.sort_by_key(|m| m.id);
+
+    let mut applied = Vec::with_capacity(pending.len());
+    for migration in pending {
+        debug!("Applying migration {} ({})", migration.id, migration.name);
+        let sql = format!(
+            "BEGIN TRANSACTION;\n{}\nCREATE schema_migrations CONTENT {{ migration_id: $id, name: $name, applied_at: time::now() }};\nCOMMIT TRANSACTION;",
+            migration.sql
+        );
+        db.query(sql)
+            .bind(("id", migration.id as i64))
+            .bind(("name", migration.name.clone()))
+            .await?
+            .check()?;
+        info!("✓ migration {} ({}) applied", migration.id, migration.name);
+        applied.push(migration.id);
+    }
+    Ok(applied)
 }
 
-/// Initialize `runs` table with constraints and indexes
+/// DDL for `runs` table with constraints and indexes
 ///
 /// Schema:
 /// ```text
@@ -66,12 +194,8 @@ pub async fn init_schema(db: &Surreal<Any>) -> Result<()> {
 /// - `status` must be one of: "RUNNING", "COMPLETED", "FAILED", "CANCELLED"
 /// - `status` transitions: RUNNING → COMPLETED | FAILED | CANCELLED (enforced via app logic)
 /// - Completed runs are immutable (enforced via app logic)
-async fn init_runs_table(db: &Surreal<Any>) -> Result<()> {
-    debug!("Initializing runs table");
-
-    // Create table with constraints
-    let sql = r#"
-        DEFINE TABLE runs SCHEMALESS
+const RUNS_TABLE_SQL: &str = r#"
+        DEFINE TABLE IF NOT EXISTS runs SCHEMALESS
             PERMISSIONS
                 FOR create FULL
                 FOR select FULL
@@ -79,33 +203,28 @@ async fn init_runs_table(db: &Surreal<Any>) -> Result<()> {
                 FOR delete NONE;
 
         -- Ensure run_id is unique
-        DEFINE INDEX idx_run_id ON TABLE runs COLUMNS run_id UNIQUE;
+        DEFINE INDEX IF NOT EXISTS idx_run_id ON TABLE runs COLUMNS run_id UNIQUE;
 
         -- Index spec_digest for listing runs by agent version
-        DEFINE INDEX idx_spec_digest ON TABLE runs COLUMNS spec_digest;
+        DEFINE INDEX IF NOT EXISTS idx_spec_digest ON TABLE runs COLUMNS spec_digest;
 
         -- Index agent_name for finding runs by agent
-        DEFINE INDEX idx_agent_name ON TABLE runs COLUMNS agent_name;
+        DEFINE INDEX IF NOT EXISTS idx_agent_name ON TABLE runs COLUMNS agent_name;
 
         -- Index git_sha for correlating runs with git commits
-        DEFINE INDEX idx_git_sha ON TABLE runs COLUMNS git_sha;
+        DEFINE INDEX IF NOT EXISTS idx_git_sha ON TABLE runs COLUMNS git_sha;
 
         -- Index created_at for time-range queries
-        DEFINE INDEX idx_created_at ON TABLE runs COLUMNS created_at;
+        DEFINE INDEX IF NOT EXISTS idx_created_at ON TABLE runs COLUMNS created_at;
 
         -- Composite index (spec_digest, created_at) for fast agent version history
-        DEFINE INDEX idx_spec_digest_created_at ON TABLE runs COLUMNS spec_digest, created_at;
+        DEFINE INDEX IF NOT EXISTS idx_spec_digest_created_at ON TABLE runs COLUMNS spec_digest, created_at;
 
         -- Composite index (run_id, status) for state queries
-        DEFINE INDEX idx_run_id_status ON TABLE runs COLUMNS run_id, status;
-    "#;
-
-    db.query(sql).await?;
-    info!("✓ runs table initialized");
-    Ok(())
-}
+        DEFINE INDEX IF NOT EXISTS idx_run_id_status ON TABLE runs COLUMNS run_id, status;
+"#;
 
-/// Initialize `run_events` table with constraints and indexes
+/// DDL for `run_events` table with constraints and indexes
 ///
 /// Schema:
 /// ```text
@@ -122,11 +241,8 @@ async fn init_runs_table(db: &Surreal<Any>) -> Result<()> {
 /// - `(run_id, seq)` is unique and clustered (prevents duplicate seq)
 /// - `seq` is 1-indexed and monotonically increasing within a run
 /// - Enforced via application logic during append_event()
-async fn init_run_events_table(db: &Surreal<Any>) -> Result<()> {
-    debug!("Initializing run_events table");
-
-    let sql = r#"
-        DEFINE TABLE run_events SCHEMALESS
+const RUN_EVENTS_TABLE_SQL: &str = r#"
+        DEFINE TABLE IF NOT EXISTS run_events SCHEMALESS
             PERMISSIONS
                 FOR create FULL
                 FOR select FULL
@@ -135,27 +251,22 @@ async fn init_run_events_table(db: &Surreal<Any>) -> Result<()> {
 
         -- Composite unique index: (run_id, seq) ensures no duplicate sequences per run
         -- This is the most critical constraint for event ordering
-        DEFINE INDEX idx_run_id_seq ON TABLE run_events COLUMNS run_id, seq UNIQUE;
+        DEFINE INDEX IF NOT EXISTS idx_run_id_seq ON TABLE run_events COLUMNS run_id, seq UNIQUE;
 
         -- Index run_id for fast event retrieval by run
-        DEFINE INDEX idx_run_id ON TABLE run_events COLUMNS run_id;
+        DEFINE INDEX IF NOT EXISTS idx_run_id ON TABLE run_events COLUMNS run_id;
 
         -- Index (run_id, timestamp) for time-ordered queries
-        DEFINE INDEX idx_run_id_timestamp ON TABLE run_events COLUMNS run_id, timestamp;
+        DEFINE INDEX IF NOT EXISTS idx_run_id_timestamp ON TABLE run_events COLUMNS run_id, timestamp;
 
         -- Index event kind for filtering by event type
-        DEFINE INDEX idx_kind ON TABLE run_events COLUMNS kind;
+        DEFINE INDEX IF NOT EXISTS idx_kind ON TABLE run_events COLUMNS kind;
 
         -- Composite index (run_id, seq, timestamp) for sorted event retrieval
-        DEFINE INDEX idx_run_id_seq_timestamp ON TABLE run_events COLUMNS run_id, seq, timestamp;
-    "#;
-
-    db.query(sql).await?;
-    info!("✓ run_events table initialized");
-    Ok(())
-}
+        DEFINE INDEX IF NOT EXISTS idx_run_id_seq_timestamp ON TABLE run_events COLUMNS run_id, seq, timestamp;
+"#;
 
-/// Initialize `releases` table with constraints and indexes
+/// DDL for `releases` table with constraints and indexes
 ///
 /// Schema:
 /// ```text
@@ -173,186 +284,126 @@ async fn init_run_events_table(db: &Surreal<Any>) -> Result<()> {
 /// - Release history is append-only (new release entry for rollback)
 /// - Most recent release (by created_at) is "current"
 /// - Uniqueness enforced at application layer (can have same spec_digest multiple times)
-async fn init_releases_table(db: &Surreal<Any>) -> Result<()> {
-    debug!("Initializing releases table");
-
-    let sql = r#"
-        DEFINE TABLE releases SCHEMAFULL;
-        DEFINE FIELD name ON releases TYPE string;
-        DEFINE FIELD spec_digest ON releases TYPE string;
-        DEFINE FIELD metadata ON releases FLEXIBLE TYPE object;
-        DEFINE FIELD version_label ON releases TYPE option<string>;
-        DEFINE FIELD promoted_by ON releases TYPE option<string>;
-        DEFINE FIELD notes ON releases TYPE option<string>;
-        DEFINE FIELD created_at ON releases TYPE datetime;
-
-        DEFINE INDEX idx_release_name ON releases FIELDS name;
-        DEFINE INDEX idx_release_name_created_at ON releases FIELDS name, created_at;
-        DEFINE INDEX idx_spec_digest ON releases FIELDS spec_digest;
-    "#;
-
-    db.query(sql).await?;
-    info!("✓ releases table initialized");
-    Ok(())
-}
-
-/// Initialize `commits` table
-async fn init_commits_table(db: &Surreal<Any>) -> Result<()> {
-    debug!("Initializing commits table");
-
-    let sql = r#"
-        DEFINE TABLE commits SCHEMAFULL;
-        DEFINE FIELD commit_id ON commits TYPE object;
-        DEFINE FIELD commit_id.hash ON commits TYPE string;
-        DEFINE FIELD commit_id.logic_hash ON commits TYPE option<string>;
-        DEFINE FIELD commit_id.state_hash ON commits TYPE string;
-        DEFINE FIELD commit_id.env_hash ON commits TYPE option<string>;
-        DEFINE FIELD parent_ids ON commits TYPE array<string>;
-        DEFINE FIELD message ON commits TYPE string;
-        DEFINE FIELD author ON commits TYPE string;
-        DEFINE FIELD created_at ON commits TYPE datetime;
-        DEFINE FIELD branch ON commits TYPE option<string>;
-        DEFINE INDEX idx_commit_hash ON commits FIELDS commit_id.hash UNIQUE;
-        DEFINE INDEX idx_author ON commits FIELDS author;
-        DEFINE INDEX idx_branch ON commits FIELDS branch;
-    "#;
-
-    db.query(sql).await?;
-    info!("✓ commits table initialized");
-    Ok(())
-}
-
-/// Initialize `snapshots` table
-async fn init_snapshots_table(db: &Surreal<Any>) -> Result<()> {
-    debug!("Initializing snapshots table");
-
-    let sql = r#"
-        DEFINE TABLE snapshots SCHEMAFULL;
-        DEFINE FIELD commit_id ON snapshots TYPE string;
-        DEFINE FIELD state ON snapshots FLEXIBLE TYPE object;
-        DEFINE FIELD size_bytes ON snapshots TYPE int;
-        DEFINE FIELD created_at ON snapshots TYPE datetime;
-        DEFINE INDEX idx_snapshot_commit ON snapshots FIELDS commit_id UNIQUE;
-    "#;
-
-    db.query(sql).await?;
-    info!("✓ snapshots table initialized");
-    Ok(())
-}
-
-/// Initialize `branches` table
-async fn init_branches_table(db: &Surreal<Any>) -> Result<()> {
-    debug!("Initializing branches table");
-
-    let sql = r#"
-        DEFINE TABLE branches SCHEMAFULL;
-        DEFINE FIELD name ON branches TYPE string;
-        DEFINE FIELD head_commit_id ON branches TYPE string;
-        DEFINE FIELD is_default ON branches TYPE bool;
-        DEFINE FIELD created_at ON branches TYPE datetime;
-        DEFINE FIELD updated_at ON branches TYPE datetime;
-        DEFINE INDEX idx_branch_name ON branches FIELDS name UNIQUE;
-    "#;
-
-    db.query(sql).await?;
-    info!("✓ branches table initialized");
-    Ok(())
-}
-
-/// Initialize `graph_edges` table
-async fn init_graph_edges_table(db: &Surreal<Any>) -> Result<()> {
-    debug!("Initializing graph_edges table");
-
-    let sql = r#"
-        DEFINE TABLE graph_edges SCHEMAFULL;
-        DEFINE FIELD child_id ON graph_edges TYPE string;
-        DEFINE FIELD parent_id ON graph_edges TYPE string;
-        DEFINE FIELD edge_type ON graph_edges TYPE string;
-        DEFINE FIELD created_at ON graph_edges TYPE datetime;
-        DEFINE INDEX idx_edge_child ON graph_edges FIELDS child_id;
-        DEFINE INDEX idx_edge_parent ON graph_edges FIELDS parent_id;
-    "#;
-
-    db.query(sql).await?;
-    info!("✓ graph_edges table initialized");
-    Ok(())
-}
-
-/// Initialize `memories` table
-async fn init_memories_table(db: &Surreal<Any>) -> Result<()> {
-    debug!("Initializing memories table");
-
-    let sql = r#"
-        DEFINE TABLE memories SCHEMAFULL;
-        DEFINE FIELD commit_id ON memories TYPE string;
-        DEFINE FIELD key ON memories TYPE string;
-        DEFINE FIELD content ON memories TYPE string;
-        DEFINE FIELD embedding ON memories TYPE option<array>;
-        DEFINE FIELD metadata ON memories FLEXIBLE TYPE object;
-        DEFINE FIELD created_at ON memories TYPE datetime;
-        DEFINE INDEX idx_memory_commit ON memories FIELDS commit_id;
-    "#;
-
-    db.query(sql).await?;
-    info!("✓ memories table initialized");
-    Ok(())
-}
-
-/// Initialize `agents` table
-async fn init_agents_table(db: &Surreal<Any>) -> Result<()> {
-    debug!("Initializing agents table");
-
-    let sql = r#"
-        DEFINE TABLE agents SCHEMAFULL;
-        DEFINE FIELD agent_id ON agents TYPE string;
-        DEFINE FIELD name ON agents TYPE string;
-        DEFINE FIELD agent_type ON agents TYPE string;
-        DEFINE FIELD config ON agents FLEXIBLE TYPE object;
-        DEFINE FIELD created_at ON agents TYPE datetime;
-        DEFINE INDEX idx_agent_id ON agents FIELDS agent_id UNIQUE;
-    "#;
-
-    db.query(sql).await?;
-    info!("✓ agents table initialized");
-    Ok(())
-}
-
-/// Initialize CI related tables
-async fn init_ci_tables(db: &Surreal<Any>) -> Result<()> {
-    debug!("Initializing CI tables");
-
-    let sql = r#"
+const RELEASES_TABLE_SQL: &str = r#"
+        DEFINE TABLE IF NOT EXISTS releases SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS name ON releases TYPE string;
+        DEFINE FIELD IF NOT EXISTS spec_digest ON releases TYPE string;
+        DEFINE FIELD IF NOT EXISTS metadata ON releases FLEXIBLE TYPE object;
+        DEFINE FIELD IF NOT EXISTS version_label ON releases TYPE option<string>;
+        DEFINE FIELD IF NOT EXISTS promoted_by ON releases TYPE option<string>;
+        DEFINE FIELD IF NOT EXISTS notes ON releases TYPE option<string>;
+        DEFINE FIELD IF NOT EXISTS created_at ON releases TYPE datetime;
+
+        DEFINE INDEX IF NOT EXISTS idx_release_name ON releases FIELDS name;
+        DEFINE INDEX IF NOT EXISTS idx_release_name_created_at ON releases FIELDS name, created_at;
+        DEFINE INDEX IF NOT EXISTS idx_spec_digest ON releases FIELDS spec_digest;
+"#;
+
+/// DDL for `commits` table
+const COMMITS_TABLE_SQL: &str = r#"
+        DEFINE TABLE IF NOT EXISTS commits SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS commit_id ON commits TYPE object;
+        DEFINE FIELD IF NOT EXISTS commit_id.hash ON commits TYPE string;
+        DEFINE FIELD IF NOT EXISTS commit_id.logic_hash ON commits TYPE option<string>;
+        DEFINE FIELD IF NOT EXISTS commit_id.state_hash ON commits TYPE string;
+        DEFINE FIELD IF NOT EXISTS commit_id.env_hash ON commits TYPE option<string>;
+        DEFINE FIELD IF NOT EXISTS parent_ids ON commits TYPE array<string>;
+        DEFINE FIELD IF NOT EXISTS message ON commits TYPE string;
+        DEFINE FIELD IF NOT EXISTS author ON commits TYPE string;
+        DEFINE FIELD IF NOT EXISTS created_at ON commits TYPE datetime;
+        DEFINE FIELD IF NOT EXISTS branch ON commits TYPE option<string>;
+        DEFINE INDEX IF NOT EXISTS idx_commit_hash ON commits FIELDS commit_id.hash UNIQUE;
+        DEFINE INDEX IF NOT EXISTS idx_author ON commits FIELDS author;
+        DEFINE INDEX IF NOT EXISTS idx_branch ON commits FIELDS branch;
+"#;
+
+/// DDL for `snapshots` table
+const SNAPSHOTS_TABLE_SQL: &str = r#"
+        DEFINE TABLE IF NOT EXISTS snapshots SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS commit_id ON snapshots TYPE string;
+        DEFINE FIELD IF NOT EXISTS state ON snapshots FLEXIBLE TYPE object;
+        DEFINE FIELD IF NOT EXISTS size_bytes ON snapshots TYPE int;
+        DEFINE FIELD IF NOT EXISTS created_at ON snapshots TYPE datetime;
+        DEFINE FIELD IF NOT EXISTS delta_parent ON snapshots TYPE option<string>;
+        DEFINE FIELD IF NOT EXISTS patch ON snapshots TYPE option<array>;
+        DEFINE FIELD IF NOT EXISTS patch[*] ON snapshots FLEXIBLE TYPE object;
+        DEFINE FIELD IF NOT EXISTS chain_depth ON snapshots TYPE int DEFAULT 0;
+        DEFINE INDEX IF NOT EXISTS idx_snapshot_commit ON snapshots FIELDS commit_id UNIQUE;
+"#;
+
+/// DDL for `branches` table
+const BRANCHES_TABLE_SQL: &str = r#"
+        DEFINE TABLE IF NOT EXISTS branches SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS name ON branches TYPE string;
+        DEFINE FIELD IF NOT EXISTS head_commit_id ON branches TYPE string;
+        DEFINE FIELD IF NOT EXISTS is_default ON branches TYPE bool;
+        DEFINE FIELD IF NOT EXISTS created_at ON branches TYPE datetime;
+        DEFINE FIELD IF NOT EXISTS updated_at ON branches TYPE datetime;
+        DEFINE INDEX IF NOT EXISTS idx_branch_name ON branches FIELDS name UNIQUE;
+"#;
+
+/// DDL for `graph_edges` table
+const GRAPH_EDGES_TABLE_SQL: &str = r#"
+        DEFINE TABLE IF NOT EXISTS graph_edges SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS child_id ON graph_edges TYPE string;
+        DEFINE FIELD IF NOT EXISTS parent_id ON graph_edges TYPE string;
+        DEFINE FIELD IF NOT EXISTS edge_type ON graph_edges TYPE string;
+        DEFINE FIELD IF NOT EXISTS created_at ON graph_edges TYPE datetime;
+        DEFINE INDEX IF NOT EXISTS idx_edge_child ON graph_edges FIELDS child_id;
+        DEFINE INDEX IF NOT EXISTS idx_edge_parent ON graph_edges FIELDS parent_id;
+"#;
+
+/// DDL for `memories` table
+const MEMORIES_TABLE_SQL: &str = r#"
+        DEFINE TABLE IF NOT EXISTS memories SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS commit_id ON memories TYPE string;
+        DEFINE FIELD IF NOT EXISTS key ON memories TYPE string;
+        DEFINE FIELD IF NOT EXISTS content ON memories TYPE string;
+        DEFINE FIELD IF NOT EXISTS embedding ON memories TYPE option<array>;
+        DEFINE FIELD IF NOT EXISTS metadata ON memories FLEXIBLE TYPE object;
+        DEFINE FIELD IF NOT EXISTS created_at ON memories TYPE datetime;
+        DEFINE INDEX IF NOT EXISTS idx_memory_commit ON memories FIELDS commit_id;
+"#;
+
+/// DDL for `agents` table
+const AGENTS_TABLE_SQL: &str = r#"
+        DEFINE TABLE IF NOT EXISTS agents SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS agent_id ON agents TYPE string;
+        DEFINE FIELD IF NOT EXISTS name ON agents TYPE string;
+        DEFINE FIELD IF NOT EXISTS agent_type ON agents TYPE string;
+        DEFINE FIELD IF NOT EXISTS config ON agents FLEXIBLE TYPE object;
+        DEFINE FIELD IF NOT EXISTS created_at ON agents TYPE datetime;
+        DEFINE INDEX IF NOT EXISTS idx_agent_id ON agents FIELDS agent_id UNIQUE;
+"#;
+
+/// DDL for CI related tables
+const CI_TABLES_SQL: &str = r#"
         -- CI snapshot table (content-addressed by digest)
-        DEFINE TABLE ci_snapshots SCHEMAFULL;
-        DEFINE FIELD digest ON ci_snapshots TYPE string;
-        DEFINE FIELD snapshot_json ON ci_snapshots TYPE string;
-        DEFINE INDEX idx_ci_snapshot_digest ON ci_snapshots FIELDS digest UNIQUE;
+        DEFINE TABLE IF NOT EXISTS ci_snapshots SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS digest ON ci_snapshots TYPE string;
+        DEFINE FIELD IF NOT EXISTS snapshot_json ON ci_snapshots TYPE string;
+        DEFINE INDEX IF NOT EXISTS idx_ci_snapshot_digest ON ci_snapshots FIELDS digest UNIQUE;
 
         -- CI pipeline table (content-addressed by digest)
-        DEFINE TABLE ci_pipelines SCHEMAFULL;
-        DEFINE FIELD digest ON ci_pipelines TYPE string;
-        DEFINE FIELD pipeline_json ON ci_pipelines TYPE string;
-        DEFINE INDEX idx_ci_pipeline_digest ON ci_pipelines FIELDS digest UNIQUE;
+        DEFINE TABLE IF NOT EXISTS ci_pipelines SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS digest ON ci_pipelines TYPE string;
+        DEFINE FIELD IF NOT EXISTS pipeline_json ON ci_pipelines TYPE string;
+        DEFINE INDEX IF NOT EXISTS idx_ci_pipeline_digest ON ci_pipelines FIELDS digest UNIQUE;
 
         -- CI run table (linked by run_id and digests)
-        DEFINE TABLE ci_runs SCHEMAFULL;
-        DEFINE FIELD run_id ON ci_runs TYPE string;
-        DEFINE FIELD snapshot_digest ON ci_runs TYPE string;
-        DEFINE FIELD pipeline_digest ON ci_runs TYPE string;
-        DEFINE FIELD status ON ci_runs TYPE string;
-        DEFINE FIELD run_json ON ci_runs TYPE string;
-        DEFINE FIELD started_at ON ci_runs TYPE option<string>;
-        DEFINE FIELD finished_at ON ci_runs TYPE option<string>;
-        DEFINE INDEX idx_ci_run_id ON ci_runs FIELDS run_id UNIQUE;
-        DEFINE INDEX idx_ci_run_snapshot ON ci_runs FIELDS snapshot_digest;
-    "#;
-
-    db.query(sql).await?;
-    info!("✓ CI tables initialized");
-    Ok(())
-}
-
-/// Initialize `decisions` table (EPIC5)
+        DEFINE TABLE IF NOT EXISTS ci_runs SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS run_id ON ci_runs TYPE string;
+        DEFINE FIELD IF NOT EXISTS snapshot_digest ON ci_runs TYPE string;
+        DEFINE FIELD IF NOT EXISTS pipeline_digest ON ci_runs TYPE string;
+        DEFINE FIELD IF NOT EXISTS status ON ci_runs TYPE string;
+        DEFINE FIELD IF NOT EXISTS run_json ON ci_runs TYPE string;
+        DEFINE FIELD IF NOT EXISTS started_at ON ci_runs TYPE option<string>;
+        DEFINE FIELD IF NOT EXISTS finished_at ON ci_runs TYPE option<string>;
+        DEFINE INDEX IF NOT EXISTS idx_ci_run_id ON ci_runs FIELDS run_id UNIQUE;
+        DEFINE INDEX IF NOT EXISTS idx_ci_run_snapshot ON ci_runs FIELDS snapshot_digest;
+"#;
+
+/// DDL for `decisions` table (EPIC5)
 ///
 /// Schema:
 /// ```text
@@ -369,35 +420,27 @@ async fn init_ci_tables(db: &Surreal<Any>) -> Result<()> {
 ///   outcome_at:     DATETIME?
 /// }
 /// ```
-async fn init_decisions_table(db: &Surreal<Any>) -> Result<()> {
-    debug!("Initializing decisions table");
-
-    let sql = r#"
-        DEFINE TABLE decisions SCHEMAFULL;
-        DEFINE FIELD decision_id ON decisions TYPE string;
-        DEFINE FIELD commit_id ON decisions TYPE string;
-        DEFINE FIELD task ON decisions TYPE string;
-        DEFINE FIELD action ON decisions TYPE string;
-        DEFINE FIELD rationale ON decisions TYPE string;
-        DEFINE FIELD alternatives ON decisions TYPE array<string>;
-        DEFINE FIELD confidence ON decisions TYPE float;
-        DEFINE FIELD outcome ON decisions TYPE option<string>;
-        DEFINE FIELD timestamp ON decisions TYPE datetime;
-        DEFINE FIELD outcome_at ON decisions TYPE option<datetime>;
-
-        DEFINE INDEX idx_decision_id ON decisions FIELDS decision_id UNIQUE;
-        DEFINE INDEX idx_decision_commit ON decisions FIELDS commit_id;
-        DEFINE INDEX idx_decision_task ON decisions FIELDS task;
-        DEFINE INDEX idx_decision_timestamp ON decisions FIELDS timestamp;
-        DEFINE INDEX idx_decision_commit_task ON decisions FIELDS commit_id, task;
-    "#;
-
-    db.query(sql).await?;
-    info!("✓ decisions table initialized");
-    Ok(())
-}
-
-/// Initialize `memory_provenances` table (EPIC5)
+const DECISIONS_TABLE_SQL: &str = r#"
+        DEFINE TABLE IF NOT EXISTS decisions SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS decision_id ON decisions TYPE string;
+        DEFINE FIELD IF NOT EXISTS commit_id ON decisions TYPE string;
+        DEFINE FIELD IF NOT EXISTS task ON decisions TYPE string;
+        DEFINE FIELD IF NOT EXISTS action ON decisions TYPE string;
+        DEFINE FIELD IF NOT EXISTS rationale ON decisions TYPE string;
+        DEFINE FIELD IF NOT EXISTS alternatives ON decisions TYPE array<string>;
+        DEFINE FIELD IF NOT EXISTS confidence ON decisions TYPE float;
+        DEFINE FIELD IF NOT EXISTS outcome ON decisions TYPE option<string>;
+        DEFINE FIELD IF NOT EXISTS timestamp ON decisions TYPE datetime;
+        DEFINE FIELD IF NOT EXISTS outcome_at ON decisions TYPE option<datetime>;
+
+        DEFINE INDEX IF NOT EXISTS idx_decision_id ON decisions FIELDS decision_id UNIQUE;
+        DEFINE INDEX IF NOT EXISTS idx_decision_commit ON decisions FIELDS commit_id;
+        DEFINE INDEX IF NOT EXISTS idx_decision_task ON decisions FIELDS task;
+        DEFINE INDEX IF NOT EXISTS idx_decision_timestamp ON decisions FIELDS timestamp;
+        DEFINE INDEX IF NOT EXISTS idx_decision_commit_task ON decisions FIELDS commit_id, task;
+"#;
+
+/// DDL for `memory_provenances` table (EPIC5)
 ///
 /// Schema:
 /// ```text
@@ -410,28 +453,197 @@ async fn init_decisions_table(db: &Surreal<Any>) -> Result<()> {
 ///   invalidated_at:  DATETIME?
 /// }
 /// ```
-async fn init_memory_provenances_table(db: &Surreal<Any>) -> Result<()> {
-    debug!("Initializing memory_provenances table");
-
-    let sql = r#"
-        DEFINE TABLE memory_provenances SCHEMAFULL;
-        DEFINE FIELD memory_id ON memory_provenances TYPE string;
-        DEFINE FIELD source_type ON memory_provenances TYPE string;
-        DEFINE FIELD source_data ON memory_provenances FLEXIBLE TYPE object;
-        DEFINE FIELD derived_from ON memory_provenances TYPE option<string>;
-        DEFINE FIELD created_at ON memory_provenances TYPE datetime;
-        DEFINE FIELD invalidated_at ON memory_provenances TYPE option<datetime>;
-
-        DEFINE INDEX idx_provenance_memory_id ON memory_provenances FIELDS memory_id;
-        DEFINE INDEX idx_provenance_created_at ON memory_provenances FIELDS created_at;
-        DEFINE INDEX idx_provenance_derived_from ON memory_provenances FIELDS derived_from;
-        DEFINE INDEX idx_provenance_source_type ON memory_provenances FIELDS source_type;
-        DEFINE INDEX idx_provenance_invalidated ON memory_provenances FIELDS invalidated_at;
-    "#;
-
-    db.query(sql).await?;
-    info!("✓ memory_provenances table initialized");
-    Ok(())
+const MEMORY_PROVENANCES_TABLE_SQL: &str = r#"
+        DEFINE TABLE IF NOT EXISTS memory_provenances SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS memory_id ON memory_provenances TYPE string;
+        DEFINE FIELD IF NOT EXISTS source_type ON memory_provenances TYPE string;
+        DEFINE FIELD IF NOT EXISTS source_data ON memory_provenances FLEXIBLE TYPE object;
+        DEFINE FIELD IF NOT EXISTS derived_from ON memory_provenances TYPE option<string>;
+        DEFINE FIELD IF NOT EXISTS created_at ON memory_provenances TYPE datetime;
+        DEFINE FIELD IF NOT EXISTS invalidated_at ON memory_provenances TYPE option<datetime>;
+
+        DEFINE INDEX IF NOT EXISTS idx_provenance_memory_id ON memory_provenances FIELDS memory_id;
+        DEFINE INDEX IF NOT EXISTS idx_provenance_created_at ON memory_provenances FIELDS created_at;
+        DEFINE INDEX IF NOT EXISTS idx_provenance_derived_from ON memory_provenances FIELDS derived_from;
+        DEFINE INDEX IF NOT EXISTS idx_provenance_source_type ON memory_provenances FIELDS source_type;
+        DEFINE INDEX IF NOT EXISTS idx_provenance_invalidated ON memory_provenances FIELDS invalidated_at;
+"#;
+
+/// DDL for `plans` and `plan_tasks` tables
+///
+/// Schema:
+/// ```text
+/// TABLE plans {
+///   plan_id:     STRING (unique)
+///   objective:   STRING
+///   created_at:  DATETIME
+///   updated_at:  DATETIME
+/// }
+///
+/// TABLE plan_tasks {
+///   plan_id:     STRING (indexed)
+///   task_id:     STRING ((plan_id, task_id) unique)
+///   depends_on:  ARRAY<STRING> (DAG edges)
+///   status:      OBJECT (serialized task status)
+///   task:        OBJECT (full task payload)
+///   updated_at:  DATETIME
+/// }
+/// ```
+const PLAN_TABLES_SQL: &str = r#"
+        DEFINE TABLE IF NOT EXISTS plans SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS plan_id ON plans TYPE string;
+        DEFINE FIELD IF NOT EXISTS objective ON plans TYPE string;
+        DEFINE FIELD IF NOT EXISTS created_at ON plans TYPE datetime;
+        DEFINE FIELD IF NOT EXISTS updated_at ON plans TYPE datetime;
+        DEFINE INDEX IF NOT EXISTS idx_plan_id ON plans FIELDS plan_id UNIQUE;
+
+        DEFINE TABLE IF NOT EXISTS plan_tasks SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS plan_id ON plan_tasks TYPE string;
+        DEFINE FIELD IF NOT EXISTS task_id ON plan_tasks TYPE string;
+        DEFINE FIELD IF NOT EXISTS depends_on ON plan_tasks TYPE array<string>;
+        DEFINE FIELD IF NOT EXISTS status ON plan_tasks FLEXIBLE TYPE object;
+        DEFINE FIELD IF NOT EXISTS task ON plan_tasks FLEXIBLE TYPE object;
+        DEFINE FIELD IF NOT EXISTS updated_at ON plan_tasks TYPE datetime;
+        DEFINE INDEX IF NOT EXISTS idx_plan_task_plan ON plan_tasks FIELDS plan_id;
+        DEFINE INDEX IF NOT EXISTS idx_plan_task_id ON plan_tasks FIELDS plan_id, task_id UNIQUE;
+"#;
+
+/// DDL for `stashes` table
+///
+/// Schema:
+/// ```text
+/// TABLE stashes {
+///   name:        STRING (unique)
+///   cas_digest:  STRING (CAS blob holding the stashed state)
+///   message:     STRING
+///   size_bytes:  INT
+///   created_at:  DATETIME
+/// }
+/// ```
+const STASHES_TABLE_SQL: &str = r#"
+        DEFINE TABLE IF NOT EXISTS stashes SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS name ON stashes TYPE string;
+        DEFINE FIELD IF NOT EXISTS cas_digest ON stashes TYPE string;
+        DEFINE FIELD IF NOT EXISTS message ON stashes TYPE string;
+        DEFINE FIELD IF NOT EXISTS size_bytes ON stashes TYPE int;
+        DEFINE FIELD IF NOT EXISTS created_at ON stashes TYPE datetime;
+        DEFINE INDEX IF NOT EXISTS idx_stash_name ON stashes FIELDS name UNIQUE;
+"#;
+
+/// DDL for `branch_protections` table
+const BRANCH_PROTECTIONS_TABLE_SQL: &str = r#"
+        DEFINE TABLE IF NOT EXISTS branch_protections SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS name ON branch_protections TYPE string;
+        DEFINE FIELD IF NOT EXISTS no_force_push ON branch_protections TYPE bool;
+        DEFINE FIELD IF NOT EXISTS no_delete ON branch_protections TYPE bool;
+        DEFINE FIELD IF NOT EXISTS require_linear ON branch_protections TYPE bool;
+        DEFINE FIELD IF NOT EXISTS updated_at ON branch_protections TYPE datetime;
+        DEFINE INDEX IF NOT EXISTS idx_branch_protection_name ON branch_protections FIELDS name UNIQUE;
+"#;
+
+/// DDL adding optional signature fields to `commits`
+const COMMIT_SIGNATURES_SQL: &str = r#"
+        DEFINE FIELD IF NOT EXISTS signature ON commits TYPE option<string>;
+        DEFINE FIELD IF NOT EXISTS signer ON commits TYPE option<string>;
+"#;
+
+/// DDL for the `archived_runs` index: runs moved out of `runs`/`run_events`
+/// into CAS, keyed by run id
+const ARCHIVED_RUNS_TABLE_SQL: &str = r#"
+        DEFINE TABLE IF NOT EXISTS archived_runs SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS run_id ON archived_runs TYPE string;
+        DEFINE FIELD IF NOT EXISTS archive_digest ON archived_runs TYPE string;
+        DEFINE FIELD IF NOT EXISTS archived_at ON archived_runs TYPE datetime;
+        DEFINE INDEX IF NOT EXISTS idx_archived_run_id ON archived_runs FIELDS run_id UNIQUE;
+"#;
+
+/// DDL for `snapshot_meta`: the environment a snapshot was taken in, as
+/// JSON, keyed by commit id
+const SNAPSHOT_META_TABLE_SQL: &str = r#"
+        DEFINE TABLE IF NOT EXISTS snapshot_meta SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS commit_id ON snapshot_meta TYPE string;
+        DEFINE FIELD IF NOT EXISTS meta_json ON snapshot_meta TYPE string;
+        DEFINE INDEX IF NOT EXISTS idx_snapshot_meta_commit ON snapshot_meta FIELDS commit_id UNIQUE;
+"#;
+
+/// DDL marking commits whose snapshot retention removed
+const SNAPSHOT_RETENTION_SQL: &str = r#"
+        DEFINE FIELD IF NOT EXISTS snapshot_pruned ON commits TYPE bool DEFAULT false;
+"#;
+
+/// DDL for the `eval_reports` index: the CAS digest of the latest eval run
+/// report per `<suite_digest>:<seed>` key
+const EVAL_REPORTS_TABLE_SQL: &str = r#"
+        DEFINE TABLE IF NOT EXISTS eval_reports SCHEMAFULL;
+        DEFINE FIELD IF NOT EXISTS report_key ON eval_reports TYPE string;
+        DEFINE FIELD IF NOT EXISTS report_digest ON eval_reports TYPE string;
+        DEFINE FIELD IF NOT EXISTS recorded_at ON eval_reports TYPE datetime;
+        DEFINE INDEX IF NOT EXISTS idx_eval_report_key ON eval_reports FIELDS report_key UNIQUE;
+"#;
+
+/// DDL for the inputs an autonomous decision was made from
+const DECISION_INPUTS_SQL: &str = r#"
+        DEFINE FIELD IF NOT EXISTS inputs ON decisions FLEXIBLE TYPE option<object>;
+"#;
+
+/// DDL for querying decisions by outcome
+///
+/// `outcome_status` is filled in when an outcome is recorded. Decisions
+/// given an outcome before this migration are classified once, right after
+/// it is applied, by [`backfill_decision_outcome_status`].
+const DECISION_OUTCOME_STATUS_SQL: &str = r#"
+        DEFINE FIELD IF NOT EXISTS outcome_status ON decisions TYPE option<string>;
+
+        DEFINE INDEX IF NOT EXISTS idx_decision_outcome_time ON decisions FIELDS outcome_status, timestamp;
+        DEFINE INDEX IF NOT EXISTS idx_decision_task_time ON decisions FIELDS task, timestamp;
+"#;
+
+/// Id of the migration that introduces `decisions.outcome_status`
+const DECISION_OUTCOME_STATUS_MIGRATION: u32 = 10;
+
+/// Classify outcomes recorded before `outcome_status` existed
+///
+/// The classification is done in Rust by [`crate::decision_outcome_status`],
+/// so it cannot be part of the migration's SurrealQL. Runs once, under the
+/// [`SCHEMA_LOCK`], in the same call that applies the migration. Returns the
+/// number of decisions classified.
+async fn backfill_decision_outcome_status(db: &Surreal<Any>) -> Result<usize> {
+    #[derive(serde::Deserialize)]
+    struct Unclassified {
+        decision_id: String,
+        outcome: String,
+    }
+
+    let mut result = db
+        .query(
+            "SELECT decision_id, outcome FROM decisions \
+             WHERE outcome_status IS NONE AND outcome IS NOT NONE",
+        )
+        .await?;
+    let unclassified: Vec<Unclassified> = result.take(0)?;
+    if unclassified.is_empty() {
+        return Ok(0);
+    }
+
+    let rows: Vec<serde_json::Value> = unclassified
+        .iter()
+        .map(|d| {
+            serde_json::json!({
+                "id": d.decision_id,
+                "status": crate::decision_outcome_status(&d.outcome),
+            })
+        })
+        .collect();
+    db.query(
+        "FOR $row IN $rows { \
+         UPDATE decisions SET outcome_status = $row.status WHERE decision_id = $row.id; \
+         };",
+    )
+    .bind(("rows", rows))
+    .await?
+    .check()?;
+    info!("✓ classified {} decision outcome(s)", unclassified.len());
+    Ok(unclassified.len())
 }
 
 #[cfg(test)]
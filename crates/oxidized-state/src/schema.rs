@@ -5,6 +5,7 @@
 //! - branches: Branch pointers to commit IDs
 //! - agents: Registered agent metadata
 //! - memories: Agent memory/context snapshots
+//! - plans / plan_tasks: Persisted execution DAGs and task statuses
 
 use chrono::{DateTime, Utc};
 
@@ -91,6 +92,14 @@ impl CommitId {
         Self::new(None, &state_hash, None)
     }
 
+    /// Create a CommitId from a JSON state, hashing its canonical encoding
+    ///
+    /// Keys are sorted at every depth and numbers normalized first, so
+    /// semantically equal states get the same id regardless of key order.
+    pub fn from_json(state: &serde_json::Value) -> Self {
+        Self::from_state(&crate::canonical_json(state))
+    }
+
     /// Create a full composite CommitId (Phase 2+)
     pub fn new(logic_hash: Option<&str>, state_hash: &str, env_hash: Option<&str>) -> Self {
         let mut hasher = Sha256::new();
@@ -132,7 +141,16 @@ impl CommitId {
 
     /// Get short hash (first 8 characters)
     pub fn short(&self) -> String {
-        self.hash.chars().take(8).collect()
+        short_hash(&self.hash).to_string()
+    }
+}
+
+/// Short form of a hash or digest for display: the first 8 characters, or
+/// the whole hash if it is shorter.
+pub fn short_hash(hash: &str) -> &str {
+    match hash.char_indices().nth(8) {
+        Some((end, _)) => &hash[..end],
+        None => hash,
     }
 }
 
@@ -158,8 +176,18 @@ pub struct CommitRecord {
     /// Timestamp of commit creation
     #[serde(with = "surreal_datetime")]
     pub created_at: DateTime<Utc>,
-    /// Branch name (if this is a branch head)
+    /// Branch the commit was created on. Advisory only: a commit can later
+    /// become reachable from several branches.
     pub branch: Option<String>,
+    /// Hex-encoded Ed25519 signature over the commit's canonical bytes
+    #[serde(default)]
+    pub signature: Option<String>,
+    /// Hex-encoded Ed25519 public key of the signer
+    #[serde(default)]
+    pub signer: Option<String>,
+    /// The snapshot was removed by retention; the commit stays as history
+    #[serde(default)]
+    pub snapshot_pruned: bool,
 }
 
 impl CommitRecord {
@@ -173,8 +201,17 @@ impl CommitRecord {
             author: author.to_string(),
             created_at: Utc::now(),
             branch: None,
+            signature: None,
+            signer: None,
+            snapshot_pruned: false,
         }
     }
+
+    /// Record the branch the commit was created on
+    pub fn with_branch(mut self, branch: &str) -> Self {
+        self.branch = Some(branch.to_string());
+        self
+    }
 }
 
 /// Snapshot record - the actual agent state data
@@ -191,6 +228,15 @@ pub struct SnapshotRecord {
     /// Timestamp
     #[serde(with = "surreal_datetime")]
     pub created_at: DateTime<Utc>,
+    /// Parent commit this snapshot is a delta against (`None` for full snapshots)
+    #[serde(default)]
+    pub delta_parent: Option<String>,
+    /// RFC 6902 patch from the parent's state; `state` is empty when set
+    #[serde(default)]
+    pub patch: Option<Vec<serde_json::Value>>,
+    /// Number of deltas since the last full snapshot (0 for a keyframe)
+    #[serde(default)]
+    pub chain_depth: u32,
 }
 
 impl SnapshotRecord {
@@ -206,8 +252,39 @@ impl SnapshotRecord {
             state,
             size_bytes: size,
             created_at: Utc::now(),
+            delta_parent: None,
+            patch: None,
+            chain_depth: 0,
         }
     }
+
+    /// Create a delta snapshot storing `patch` against `parent_commit`
+    pub fn delta(
+        commit_id: &str,
+        parent_commit: &str,
+        patch: Vec<serde_json::Value>,
+        chain_depth: u32,
+    ) -> Self {
+        let size = serde_json::to_string(&patch)
+            .map(|s| s.len() as u64)
+            .unwrap_or(0);
+
+        SnapshotRecord {
+            id: None,
+            commit_id: commit_id.to_string(),
+            state: serde_json::json!({}),
+            size_bytes: size,
+            created_at: Utc::now(),
+            delta_parent: Some(parent_commit.to_string()),
+            patch: Some(patch),
+            chain_depth,
+        }
+    }
+
+    /// Whether this record is a patch rather than a full state
+    pub fn is_delta(&self) -> bool {
+        self.delta_parent.is_some()
+    }
 }
 
 /// Branch record - pointer to a commit
@@ -400,6 +477,9 @@ pub struct RunRecord {
     pub duration_ms: u64,
     /// Whether run succeeded
     pub success: bool,
+    /// LLM token usage, set when the run finishes
+    #[serde(default)]
+    pub usage: Option<crate::storage_traits::RunUsage>,
     /// Path to the success rubric for this evaluation.
     pub rubric_path: Option<String>,
     /// Whether this run is a production-promotion gate.
@@ -441,6 +521,7 @@ impl RunRecord {
             final_state_digest: None,
             duration_ms: 0,
             success: false,
+            usage: None,
             rubric_path,
             is_promotion_gate,
             agent_phase,
@@ -580,10 +661,16 @@ pub struct DecisionRecord {
     pub rationale: String,
     /// Alternative options considered
     pub alternatives: Vec<String>,
+    /// Inputs the decision was made from (JSON object)
+    #[serde(default)]
+    pub inputs: Option<serde_json::Value>,
     /// Confidence level (0.0-1.0)
     pub confidence: f32,
     /// Decision outcome
     pub outcome: Option<String>, // JSON serialized DecisionOutcome enum
+    /// Status the outcome classifies as, see [`decision_outcome_status`]
+    #[serde(default)]
+    pub outcome_status: Option<String>,
     /// Decision timestamp
     #[serde(with = "surreal_datetime")]
     pub timestamp: DateTime<Utc>,
@@ -610,8 +697,10 @@ impl DecisionRecord {
             action,
             rationale,
             alternatives: Vec::new(),
+            inputs: None,
             confidence,
             outcome: None,
+            outcome_status: None,
             timestamp: Utc::now(),
             outcome_at: None,
         }
@@ -623,14 +712,88 @@ impl DecisionRecord {
         self
     }
 
+    /// Set the inputs the decision was made from
+    pub fn with_inputs(mut self, inputs: serde_json::Value) -> Self {
+        self.inputs = Some(inputs);
+        self
+    }
+
     /// Record the decision outcome
     pub fn with_outcome(mut self, outcome: String) -> Self {
+        self.outcome_status = Some(decision_outcome_status(&outcome));
         self.outcome = Some(outcome);
         self.outcome_at = Some(Utc::now());
         self
     }
 }
 
+/// Classify a recorded outcome as a lowercase status such as `failure`.
+///
+/// Accepts a JSON string (`"failure"`), an object with a `status` field
+/// (`{"status":"failure"}`), a single-key tagged enum (`{"failure":{..}}`),
+/// or plain text.
+pub fn decision_outcome_status(outcome: &str) -> String {
+    let status = match serde_json::from_str::<serde_json::Value>(outcome) {
+        Ok(serde_json::Value::String(s)) => s,
+        Ok(serde_json::Value::Object(map)) => match map.get("status") {
+            Some(serde_json::Value::String(s)) => s.clone(),
+            _ if map.len() == 1 => map.keys().next().cloned().unwrap_or_default(),
+            _ => outcome.to_string(),
+        },
+        _ => outcome.to_string(),
+    };
+    status.trim().to_lowercase()
+}
+
+/// Filter for [`SurrealHandle::query_decisions`](crate::SurrealHandle::query_decisions)
+///
+/// Unset fields match every decision.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DecisionFilter {
+    /// Only decisions about this task
+    pub task: Option<String>,
+    /// Only decisions whose outcome status is this, e.g. `failure`
+    pub outcome: Option<String>,
+    /// Only decisions made at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only decisions made at or before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Return at most this many decisions
+    pub limit: Option<usize>,
+}
+
+impl DecisionFilter {
+    /// Match decisions about `task`
+    pub fn with_task(mut self, task: impl Into<String>) -> Self {
+        self.task = Some(task.into());
+        self
+    }
+
+    /// Match decisions whose outcome status is `outcome`
+    pub fn with_outcome(mut self, outcome: impl Into<String>) -> Self {
+        self.outcome = Some(outcome.into().to_lowercase());
+        self
+    }
+
+    /// Match decisions made at or after `since`
+    pub fn with_since(mut self, since: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    /// Match decisions made at or before `until`
+    pub fn with_until(mut self, until: DateTime<Utc>) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    /// Return at most `limit` decisions
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
 /// Provenance source for memory records
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -643,6 +806,19 @@ pub enum ProvenanceSourceType {
     UserAnnotation,
     /// Derived from another memory
     MemoryDerivation,
+    /// Produced from a tool's output during a run
+    ToolOutput,
+    /// Generated by an LLM during a run
+    LlmGenerated,
+    /// Produced by merging memories during a run
+    Merged,
+}
+
+/// Provenance id of the memory `key` at `commit_id`, the
+/// [`MemoryProvenanceRecord::memory_id`] of its rows. A key alone repeats
+/// across commits.
+pub fn memory_id(commit_id: &str, key: &str) -> String {
+    format!("{commit_id}:{key}")
 }
 
 /// Memory provenance record - tracks lineage of memories
@@ -719,6 +895,24 @@ impl MemoryProvenanceRecord {
         }
     }
 
+    /// Create provenance for a memory produced at event `seq` of a run
+    pub fn from_run_event(
+        memory_id: String,
+        run_id: String,
+        seq: u64,
+        source_type: ProvenanceSourceType,
+    ) -> Self {
+        MemoryProvenanceRecord {
+            id: None,
+            memory_id,
+            source_type: source_type.to_string(),
+            source_data: serde_json::json!({ "run_id": run_id, "seq": seq }),
+            derived_from: None,
+            created_at: Utc::now(),
+            invalidated_at: None,
+        }
+    }
+
     /// Mark this provenance as invalidated
     pub fn invalidate(mut self) -> Self {
         self.invalidated_at = Some(Utc::now());
@@ -733,6 +927,177 @@ impl core::fmt::Display for ProvenanceSourceType {
             ProvenanceSourceType::StateSnapshot => write!(f, "state_snapshot"),
             ProvenanceSourceType::UserAnnotation => write!(f, "user_annotation"),
             ProvenanceSourceType::MemoryDerivation => write!(f, "memory_derivation"),
+            ProvenanceSourceType::ToolOutput => write!(f, "tool_output"),
+            ProvenanceSourceType::LlmGenerated => write!(f, "llm_generated"),
+            ProvenanceSourceType::Merged => write!(f, "merged"),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Planning Records — Persisted Execution DAGs
+// ---------------------------------------------------------------------------
+
+/// Plan record - header row for a persisted execution DAG
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanRecord {
+    /// SurrealDB record ID
+    pub id: Option<surrealdb::sql::Thing>,
+    /// Plan identifier (the DAG's goal ID)
+    pub plan_id: String,
+    /// Objective the plan was decomposed from
+    pub objective: String,
+    /// Created timestamp
+    #[serde(with = "surreal_datetime")]
+    pub created_at: DateTime<Utc>,
+    /// Last updated timestamp
+    #[serde(with = "surreal_datetime")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PlanRecord {
+    /// Create a new plan record
+    pub fn new(plan_id: &str, objective: &str) -> Self {
+        let now = Utc::now();
+        PlanRecord {
+            id: None,
+            plan_id: plan_id.to_string(),
+            objective: objective.to_string(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+/// Plan task record - one DAG node together with its dependency edges
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanTaskRecord {
+    /// SurrealDB record ID
+    pub id: Option<surrealdb::sql::Thing>,
+    /// Owning plan ID
+    pub plan_id: String,
+    /// Task ID, unique within the plan
+    pub task_id: String,
+    /// IDs of tasks this task depends on (the DAG edges)
+    pub depends_on: Vec<String>,
+    /// Current task status (JSON serialized status enum); authoritative over
+    /// any status embedded in `task`
+    pub status: serde_json::Value,
+    /// Full task payload (JSON)
+    pub task: serde_json::Value,
+    /// Last updated timestamp
+    #[serde(with = "surreal_datetime")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PlanTaskRecord {
+    /// Create a new plan task record
+    pub fn new(
+        plan_id: &str,
+        task_id: &str,
+        depends_on: Vec<String>,
+        status: serde_json::Value,
+        task: serde_json::Value,
+    ) -> Self {
+        PlanTaskRecord {
+            id: None,
+            plan_id: plan_id.to_string(),
+            task_id: task_id.to_string(),
+            depends_on,
+            status,
+            task,
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+/// Stash record - work-in-progress state set aside outside of any branch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StashRecord {
+    /// SurrealDB record ID
+    pub id: Option<surrealdb::sql::Thing>,
+    /// Stash name, unique across the repository
+    pub name: String,
+    /// Hex digest of the CAS blob holding the stashed state
+    pub cas_digest: String,
+    /// Description of the stashed work
+    pub message: String,
+    /// Size of the stashed state in bytes
+    pub size_bytes: u64,
+    /// Created timestamp
+    #[serde(with = "surreal_datetime")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl StashRecord {
+    /// Create a new stash record
+    pub fn new(name: &str, cas_digest: &str, message: &str, size_bytes: u64) -> Self {
+        StashRecord {
+            id: None,
+            name: name.to_string(),
+            cas_digest: cas_digest.to_string(),
+            message: message.to_string(),
+            size_bytes,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// Rules guarding a branch against destructive operations
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BranchProtection {
+    /// Reject non-fast-forward (forced) updates of the branch head
+    pub no_force_push: bool,
+    /// Reject deleting the branch
+    pub no_delete: bool,
+    /// Reject merges into the branch that would create a merge commit
+    pub require_linear: bool,
+}
+
+impl BranchProtection {
+    /// True if no rule is enabled
+    pub fn is_empty(&self) -> bool {
+        *self == BranchProtection::default()
+    }
+}
+
+/// Branch protection record - the rules set for one branch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchProtectionRecord {
+    /// SurrealDB record ID
+    pub id: Option<surrealdb::sql::Thing>,
+    /// Protected branch name
+    pub name: String,
+    /// See [`BranchProtection::no_force_push`]
+    pub no_force_push: bool,
+    /// See [`BranchProtection::no_delete`]
+    pub no_delete: bool,
+    /// See [`BranchProtection::require_linear`]
+    pub require_linear: bool,
+    /// Last time the rules were changed
+    #[serde(with = "surreal_datetime")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl BranchProtectionRecord {
+    /// Create a new branch protection record
+    pub fn new(name: &str, protection: BranchProtection) -> Self {
+        BranchProtectionRecord {
+            id: None,
+            name: name.to_string(),
+            no_force_push: protection.no_force_push,
+            no_delete: protection.no_delete,
+            require_linear: protection.require_linear,
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// The rules held by this record
+    pub fn protection(&self) -> BranchProtection {
+        BranchProtection {
+            no_force_push: self.no_force_push,
+            no_delete: self.no_delete,
+            require_linear: self.require_linear,
         }
     }
 }
@@ -761,6 +1126,22 @@ mod tests {
         assert_eq!(id1.hash, id2.hash);
     }
 
+    #[test]
+    fn test_commit_id_from_json_ignores_nested_key_order() {
+        let a = serde_json::json!({
+            "goal": "ship",
+            "plan": { "steps": [{ "tool": "grep", "args": { "q": "x", "path": "." } }], "depth": 2 }
+        });
+        let b = serde_json::json!({
+            "plan": { "depth": 2.0, "steps": [{ "args": { "path": ".", "q": "x" }, "tool": "grep" }] },
+            "goal": "ship"
+        });
+        assert_eq!(CommitId::from_json(&a), CommitId::from_json(&b));
+
+        let c = serde_json::json!({ "goal": "ship", "plan": { "depth": 3 } });
+        assert_ne!(CommitId::from_json(&a).hash, CommitId::from_json(&c).hash);
+    }
+
     #[test]
     fn test_commit_id_different_states() {
         let id1 = CommitId::from_state(b"state 1");
@@ -931,6 +1312,20 @@ mod tests {
             .contains(&"linear_backoff".to_string()));
     }
 
+    #[test]
+    fn test_decision_outcome_status_classifies_encodings() {
+        assert_eq!(decision_outcome_status(r#""Failure""#), "failure");
+        assert_eq!(
+            decision_outcome_status(r#"{"status":"success","duration_ms":5}"#),
+            "success"
+        );
+        assert_eq!(
+            decision_outcome_status(r#"{"partial":{"done":3}}"#),
+            "partial"
+        );
+        assert_eq!(decision_outcome_status("skipped "), "skipped");
+    }
+
     #[test]
     fn test_decision_record_with_outcome() {
         let outcome_json = serde_json::json!({
@@ -1028,5 +1423,11 @@ mod tests {
             ProvenanceSourceType::MemoryDerivation.to_string(),
             "memory_derivation"
         );
+        assert_eq!(ProvenanceSourceType::ToolOutput.to_string(), "tool_output");
+        assert_eq!(
+            ProvenanceSourceType::LlmGenerated.to_string(),
+            "llm_generated"
+        );
+        assert_eq!(ProvenanceSourceType::Merged.to_string(), "merged");
     }
 }
@@ -8,6 +8,8 @@
 //! All traits are async and backend-agnostic. In-memory fakes are provided
 //! for testing via the `fakes` module.
 
+use std::collections::BTreeMap;
+
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -100,6 +102,30 @@ impl RunId {
     pub fn new() -> Self {
         RunId(uuid::Uuid::new_v4().to_string())
     }
+
+    /// Derive a deterministic UUID-shaped RunId from `seed`, for tests and
+    /// golden files that need stable ids. The same seed always yields the
+    /// same id.
+    pub fn from_seed(seed: u64) -> Self {
+        // splitmix64: spreads nearby seeds across all 128 bits
+        fn next(state: &mut u64) -> u64 {
+            *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = *state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        }
+
+        let mut state = seed;
+        let mut bytes = [0u8; 16];
+        bytes[..8].copy_from_slice(&next(&mut state).to_be_bytes());
+        bytes[8..].copy_from_slice(&next(&mut state).to_be_bytes());
+        RunId(
+            uuid::Builder::from_random_bytes(bytes)
+                .into_uuid()
+                .to_string(),
+        )
+    }
 }
 
 impl Default for RunId {
@@ -164,6 +190,63 @@ pub struct RunSummary {
     pub duration_ms: u64,
     /// Whether the run succeeded
     pub success: bool,
+    /// LLM token usage recorded by the run's events, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<RunUsage>,
+}
+
+/// Tokens consumed by one model
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    /// Prompt (input) tokens
+    pub input_tokens: u64,
+    /// Completion (output) tokens
+    pub output_tokens: u64,
+    /// Number of events that reported usage
+    pub calls: u64,
+}
+
+impl TokenUsage {
+    /// Add `other` into `self`
+    pub fn add(&mut self, other: &TokenUsage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.calls += other.calls;
+    }
+}
+
+/// Token usage of a run, keyed by model name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunUsage {
+    pub models: BTreeMap<String, TokenUsage>,
+}
+
+impl RunUsage {
+    /// Whether no usage was recorded
+    pub fn is_empty(&self) -> bool {
+        self.models.is_empty()
+    }
+
+    /// Add `usage` for `model`
+    pub fn add(&mut self, model: &str, usage: &TokenUsage) {
+        self.models.entry(model.to_string()).or_default().add(usage);
+    }
+
+    /// Add every model of `other`
+    pub fn merge(&mut self, other: &RunUsage) {
+        for (model, usage) in &other.models {
+            self.add(model, usage);
+        }
+    }
+
+    /// Usage summed over all models
+    pub fn total(&self) -> TokenUsage {
+        let mut total = TokenUsage::default();
+        for usage in self.models.values() {
+            total.add(usage);
+        }
+        total
+    }
 }
 
 /// Status of a run
@@ -203,6 +286,15 @@ pub trait RunLedger: Send + Sync {
         metadata: RunMetadata,
     ) -> StorageResult<RunId>;
 
+    /// Create a new run under a caller-supplied ID (e.g. [`RunId::from_seed`]).
+    /// Fails with `RunAlreadyExists` if a run with that ID exists.
+    async fn create_run_with_id(
+        &self,
+        run_id: RunId,
+        spec_digest: &ContentDigest,
+        metadata: RunMetadata,
+    ) -> StorageResult<RunId>;
+
     /// Append an event to an active run. Fails if the run is completed/failed.
     async fn append_event(&self, run_id: &RunId, event: RunEvent) -> StorageResult<()>;
 
@@ -224,6 +316,26 @@ pub trait RunLedger: Send + Sync {
     /// List runs, optionally filtered by spec digest.
     async fn list_runs(&self, spec_digest: Option<&ContentDigest>)
         -> StorageResult<Vec<RunRecord>>;
+
+    /// List runs of `agent_name` (or of every agent) created at or after
+    /// `since`, newest first.
+    async fn list_agent_runs(
+        &self,
+        agent_name: Option<&str>,
+        since: Option<DateTime<Utc>>,
+    ) -> StorageResult<Vec<RunRecord>>;
+
+    /// Record that a terminal run was archived under `archive_digest`, and
+    /// delete the run and its events from the ledger. Fails if the run is
+    /// still running.
+    ///
+    /// The digest is kept exactly as the archive's store printed it, so any
+    /// algorithm prefix (such as `b3:`) survives the round trip.
+    async fn archive_run(&self, run_id: &RunId, archive_digest: &str) -> StorageResult<()>;
+
+    /// Digest of the archive a run was moved to, or `None` if it was never
+    /// archived.
+    async fn archived_run_digest(&self, run_id: &RunId) -> StorageResult<Option<String>>;
 }
 
 // ---------------------------------------------------------------------------
@@ -231,7 +343,11 @@ pub trait RunLedger: Send + Sync {
 // ---------------------------------------------------------------------------
 
 /// Metadata for a release
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// The optional fields describe what was released, so later promotions can
+/// be checked against it; they are `None` on releases recorded before they
+/// were tracked.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ReleaseMetadata {
     /// Human-readable version label (e.g. "v1.2.3")
     pub version_label: Option<String>,
@@ -239,6 +355,18 @@ pub struct ReleaseMetadata {
     pub promoted_by: String,
     /// Release notes
     pub notes: Option<String>,
+    /// Environment the release was promoted to (e.g. "PRODUCTION")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+    /// Tools digest of the released spec
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools_digest: Option<String>,
+    /// Graph digest of the released spec
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graph_digest: Option<String>,
+    /// Tool and config fields the release exposes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fields: Option<Vec<String>>,
 }
 
 /// A single release record (pointer from name → spec digest)
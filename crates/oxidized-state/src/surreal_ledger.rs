@@ -3,12 +3,18 @@
 //! Uses `schema::RunRecord` and `schema::RunEventRecord` for persistence,
 //! converting to/from `storage_traits` types at the boundary.
 
+use std::collections::VecDeque;
+use std::time::Duration;
+
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::Stream;
 use surrealdb::engine::any::Any;
+use surrealdb::sql::Datetime as SurrealDatetime;
 use surrealdb::Surreal;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
-use crate::error::{StateError, StorageError};
+use crate::error::{violates_unique_index, StateError, StorageError};
 use crate::migrations;
 use crate::schema::RunEventRecord as DbEvent;
 use crate::schema::RunRecord as DbRun;
@@ -17,7 +23,11 @@ use crate::storage_traits::{
     RunStatus, RunSummary, StorageResult,
 };
 
+/// Default interval between polls in [`SurrealRunLedger::subscribe`].
+pub const DEFAULT_TAIL_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
 /// SurrealDB-backed implementation of [`RunLedger`].
+#[derive(Clone)]
 pub struct SurrealRunLedger {
     db: Surreal<Any>,
 }
@@ -42,6 +52,15 @@ impl SurrealRunLedger {
         Ok(Self { db })
     }
 
+    /// Share `handle`'s connection, so the ledger reads and writes the
+    /// database the handle was configured for. The handle's setup has
+    /// already applied the schema.
+    pub fn from_handle(handle: &crate::handle::SurrealHandle) -> Self {
+        Self {
+            db: handle.db().clone(),
+        }
+    }
+
     /// Create from environment variables.
     ///
     /// Uses the same env-var chain as [`crate::handle::SurrealHandle::setup_from_env`].
@@ -124,8 +143,100 @@ impl SurrealRunLedger {
         Ok(Self { db })
     }
 
+    /// Tail a run's events as they are appended.
+    ///
+    /// Yields events already recorded, then new ones in `seq` order, and ends
+    /// once the run is terminal and every event has been delivered. Backend
+    /// errors are logged and end the stream.
+    pub fn subscribe(&self, run_id: &RunId) -> impl Stream<Item = RunEvent> + Send + 'static {
+        self.subscribe_with_interval(run_id, DEFAULT_TAIL_POLL_INTERVAL)
+    }
+
+    /// Like [`subscribe`](Self::subscribe), with a custom poll interval.
+    pub fn subscribe_with_interval(
+        &self,
+        run_id: &RunId,
+        poll_interval: Duration,
+    ) -> impl Stream<Item = RunEvent> + Send + 'static {
+        struct Tail {
+            ledger: SurrealRunLedger,
+            run_id: String,
+            last_seq: u64,
+            pending: VecDeque<RunEvent>,
+            finished: bool,
+            poll_interval: Duration,
+        }
+
+        let tail = Tail {
+            ledger: self.clone(),
+            run_id: run_id.0.clone(),
+            last_seq: 0,
+            pending: VecDeque::new(),
+            finished: false,
+            poll_interval,
+        };
+
+        futures::stream::unfold(tail, |mut tail| async move {
+            loop {
+                if let Some(event) = tail.pending.pop_front() {
+                    return Some((event, tail));
+                }
+                if tail.finished {
+                    return None;
+                }
+
+                // Read the status before the events so anything appended
+                // ahead of the terminal transition is still drained.
+                let terminal = match tail.ledger.fetch_run(&tail.run_id).await {
+                    Ok(row) => row.status != "RUNNING",
+                    Err(e) => {
+                        warn!(error = %e, run_id = %tail.run_id, "run tail: failed to read run");
+                        return None;
+                    }
+                };
+
+                match tail.ledger.events_after(&tail.run_id, tail.last_seq).await {
+                    Ok(events) => {
+                        if let Some(last) = events.last() {
+                            tail.last_seq = last.seq;
+                        }
+                        tail.pending.extend(events);
+                    }
+                    Err(e) => {
+                        warn!(error = %e, run_id = %tail.run_id, "run tail: failed to read events");
+                        return None;
+                    }
+                }
+
+                if terminal {
+                    tail.finished = true;
+                } else if tail.pending.is_empty() {
+                    tokio::time::sleep(tail.poll_interval).await;
+                }
+            }
+        })
+    }
+
     // -- private helpers -----------------------------------------------------
 
+    /// Fetch events for a run with `seq` greater than `after_seq`, ordered by seq.
+    async fn events_after(&self, rid: &str, after_seq: u64) -> StorageResult<Vec<RunEvent>> {
+        let rid_owned = rid.to_string();
+        let mut res = self
+            .db
+            .query("SELECT * FROM run_events WHERE run_id = $rid AND seq > $after ORDER BY seq ASC")
+            .bind(("rid", rid_owned))
+            .bind(("after", after_seq))
+            .await
+            .map_err(|e| StorageError::Backend(e.to_string()))?;
+
+        let rows: Vec<DbEvent> = res
+            .take(0)
+            .map_err(|e| StorageError::Backend(e.to_string()))?;
+
+        Ok(rows.into_iter().map(Self::db_event_to_event).collect())
+    }
+
     /// Fetch a run row by ID (owned string), returning the DB row or RunNotFound.
     async fn fetch_run(&self, rid: &str) -> StorageResult<DbRun> {
         let rid_owned = rid.to_string();
@@ -147,6 +258,21 @@ impl SurrealRunLedger {
             })
     }
 
+    /// Whether run `rid` already has an event with `seq`.
+    async fn has_event(&self, rid: &str, seq: u64) -> StorageResult<bool> {
+        let mut res = self
+            .db
+            .query("SELECT VALUE seq FROM run_events WHERE run_id = $rid AND seq = $seq LIMIT 1")
+            .bind(("rid", rid.to_string()))
+            .bind(("seq", seq))
+            .await
+            .map_err(|e| StorageError::Backend(e.to_string()))?;
+        let rows: Vec<u64> = res
+            .take(0)
+            .map_err(|e| StorageError::Backend(e.to_string()))?;
+        Ok(!rows.is_empty())
+    }
+
     /// Fetch a run row and verify it is in "RUNNING" state.
     async fn fetch_running(&self, rid: &str) -> StorageResult<DbRun> {
         let row = self.fetch_run(rid).await?;
@@ -184,6 +310,7 @@ impl SurrealRunLedger {
                 final_state_digest,
                 duration_ms: row.duration_ms,
                 success: row.success,
+                usage: row.usage,
             })
         } else {
             None
@@ -227,7 +354,22 @@ impl RunLedger for SurrealRunLedger {
         spec_digest: &ContentDigest,
         metadata: RunMetadata,
     ) -> StorageResult<RunId> {
-        let run_id = RunId::new();
+        self.create_run_with_id(RunId::new(), spec_digest, metadata)
+            .await
+    }
+
+    async fn create_run_with_id(
+        &self,
+        run_id: RunId,
+        spec_digest: &ContentDigest,
+        metadata: RunMetadata,
+    ) -> StorageResult<RunId> {
+        match self.fetch_run(&run_id.0).await {
+            Ok(_) => return Err(StorageError::RunAlreadyExists { run_id: run_id.0 }),
+            Err(StorageError::RunNotFound { .. }) => {}
+            Err(e) => return Err(e),
+        }
+
         let db_row = DbRun::new(
             run_id.0.clone(),
             spec_digest.as_str().to_string(),
@@ -241,12 +383,17 @@ impl RunLedger for SurrealRunLedger {
 
         debug!(run_id = %run_id, "creating run");
 
-        let _created: Option<DbRun> = self
-            .db
-            .create("runs")
-            .content(db_row)
-            .await
-            .map_err(|e| StorageError::Backend(e.to_string()))?;
+        let created: Result<Option<DbRun>, _> = self.db.create("runs").content(db_row).await;
+        if let Err(e) = created {
+            // Lost a race against a concurrent create: unique idx_run_id on runs.
+            let raced =
+                violates_unique_index(&e, "idx_run_id") || self.fetch_run(&run_id.0).await.is_ok();
+            return Err(if raced {
+                StorageError::RunAlreadyExists { run_id: run_id.0 }
+            } else {
+                StorageError::Backend(e.to_string())
+            });
+        }
 
         Ok(run_id)
     }
@@ -254,14 +401,24 @@ impl RunLedger for SurrealRunLedger {
     async fn append_event(&self, run_id: &RunId, event: RunEvent) -> StorageResult<()> {
         self.fetch_running(&run_id.0).await?;
 
-        let db_event = DbEvent::new(run_id.0.clone(), event.seq, event.kind, event.payload);
-
-        let _created: Option<DbEvent> = self
-            .db
-            .create("run_events")
-            .content(db_event)
-            .await
-            .map_err(|e| StorageError::Backend(e.to_string()))?;
+        let seq = event.seq;
+        let db_event = DbEvent::new(run_id.0.clone(), seq, event.kind, event.payload);
+
+        let created: Result<Option<DbEvent>, _> =
+            self.db.create("run_events").content(db_event).await;
+        if let Err(e) = created {
+            // Violation of the unique (run_id, seq) index on run_events.
+            let duplicate = violates_unique_index(&e, "idx_run_id_seq")
+                || self.has_event(&run_id.0, seq).await?;
+            return Err(if duplicate {
+                StorageError::DuplicateEvent {
+                    run_id: run_id.0.clone(),
+                    seq,
+                }
+            } else {
+                StorageError::Backend(e.to_string())
+            });
+        }
 
         Ok(())
     }
@@ -274,7 +431,8 @@ impl RunLedger for SurrealRunLedger {
             .as_ref()
             .map(|d| d.as_str().to_string());
 
-        let updated = row.complete(summary.total_events, final_digest_str, summary.duration_ms);
+        let mut updated = row.complete(summary.total_events, final_digest_str, summary.duration_ms);
+        updated.usage = summary.usage;
         let rid_owned = run_id.0.clone();
 
         self.db
@@ -290,7 +448,8 @@ impl RunLedger for SurrealRunLedger {
     async fn fail_run(&self, run_id: &RunId, summary: RunSummary) -> StorageResult<()> {
         let row = self.fetch_running(&run_id.0).await?;
 
-        let updated = row.fail(summary.total_events, summary.duration_ms);
+        let mut updated = row.fail(summary.total_events, summary.duration_ms);
+        updated.usage = summary.usage;
         let rid_owned = run_id.0.clone();
 
         self.db
@@ -306,7 +465,8 @@ impl RunLedger for SurrealRunLedger {
     async fn cancel_run(&self, run_id: &RunId, summary: RunSummary) -> StorageResult<()> {
         let row = self.fetch_running(&run_id.0).await?;
 
-        let updated = row.cancel(summary.total_events, summary.duration_ms);
+        let mut updated = row.cancel(summary.total_events, summary.duration_ms);
+        updated.usage = summary.usage;
         let rid_owned = run_id.0.clone();
 
         self.db
@@ -369,4 +529,82 @@ impl RunLedger for SurrealRunLedger {
 
         rows.into_iter().map(Self::db_run_to_record).collect()
     }
+
+    async fn list_agent_runs(
+        &self,
+        agent_name: Option<&str>,
+        since: Option<DateTime<Utc>>,
+    ) -> StorageResult<Vec<RunRecord>> {
+        // Both conditions are served by idx_agent_name / idx_created_at
+        let mut conditions = Vec::new();
+        if agent_name.is_some() {
+            conditions.push("agent_name = $agent");
+        }
+        if since.is_some() {
+            conditions.push("created_at >= $since");
+        }
+        let mut sql = "SELECT * FROM runs".to_string();
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY created_at DESC");
+
+        let mut res = self
+            .db
+            .query(sql)
+            .bind(("agent", agent_name.map(str::to_string)))
+            .bind(("since", since.map(SurrealDatetime::from)))
+            .await
+            .map_err(|e| StorageError::Backend(e.to_string()))?;
+        let rows: Vec<DbRun> = res
+            .take(0)
+            .map_err(|e| StorageError::Backend(e.to_string()))?;
+
+        rows.into_iter().map(Self::db_run_to_record).collect()
+    }
+
+    async fn archive_run(&self, run_id: &RunId, archive_digest: &str) -> StorageResult<()> {
+        let row = self.fetch_run(&run_id.0).await?;
+        if row.status == "RUNNING" {
+            return Err(StorageError::InvalidRunState {
+                run_id: run_id.0.clone(),
+                status: row.status,
+                expected: "terminal".to_string(),
+            });
+        }
+
+        // Index entry and hot-row deletion commit together, so a run is never
+        // both missing from the ledger and absent from the archive index.
+        self.db
+            .query(
+                "BEGIN TRANSACTION;\n\
+                 CREATE archived_runs CONTENT { run_id: $rid, archive_digest: $digest, archived_at: time::now() };\n\
+                 DELETE run_events WHERE run_id = $rid;\n\
+                 DELETE runs WHERE run_id = $rid;\n\
+                 COMMIT TRANSACTION;",
+            )
+            .bind(("rid", run_id.0.clone()))
+            .bind(("digest", archive_digest.to_string()))
+            .await
+            .and_then(|res| res.check())
+            .map_err(|e| StorageError::Backend(e.to_string()))?;
+
+        debug!(run_id = %run_id, digest = %archive_digest, "archived run");
+        Ok(())
+    }
+
+    async fn archived_run_digest(&self, run_id: &RunId) -> StorageResult<Option<String>> {
+        let mut res = self
+            .db
+            .query("SELECT VALUE archive_digest FROM archived_runs WHERE run_id = $rid")
+            .bind(("rid", run_id.0.clone()))
+            .await
+            .map_err(|e| StorageError::Backend(e.to_string()))?;
+        let digests: Vec<String> = res
+            .take(0)
+            .map_err(|e| StorageError::Backend(e.to_string()))?;
+
+        Ok(digests.into_iter().next())
+    }
 }
@@ -0,0 +1,83 @@
+//! Compare-and-swap branch head updates
+
+use oxidized_state::{BranchRecord, CommitId, CommitRecord, StateError, SurrealHandle};
+
+#[tokio::test]
+async fn test_racing_head_updates_lose_nothing() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    handle
+        .save_branch(&BranchRecord::new("main", "base", true))
+        .await
+        .unwrap();
+
+    // Both writers read the same head before either updates it
+    let seen_by_a = handle.get_branch_head("main").await.unwrap();
+    let seen_by_b = handle.get_branch_head("main").await.unwrap();
+
+    assert!(handle
+        .update_branch_head_cas("main", &seen_by_a, "commit-a")
+        .await
+        .unwrap());
+    // B's view is stale, so its update must be rejected rather than
+    // silently discarding commit-a
+    assert!(!handle
+        .update_branch_head_cas("main", &seen_by_b, "commit-b")
+        .await
+        .unwrap());
+    assert_eq!(handle.get_branch_head("main").await.unwrap(), "commit-a");
+
+    // B retries against the fresh head and succeeds
+    let fresh = handle.get_branch_head("main").await.unwrap();
+    assert_eq!(fresh, "commit-a");
+    assert!(handle
+        .update_branch_head_cas("main", &fresh, "commit-b")
+        .await
+        .unwrap());
+    let branch = handle.get_branch("main").await.unwrap().unwrap();
+    assert_eq!(branch.head_commit_id, "commit-b");
+    assert!(branch.is_default);
+}
+
+#[tokio::test]
+async fn test_cas_on_missing_branch_is_rejected() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    assert!(!handle
+        .update_branch_head_cas("ghost", "base", "commit-a")
+        .await
+        .unwrap());
+    assert!(handle.get_branch("ghost").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_creating_an_existing_branch_is_rejected() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    assert!(handle
+        .create_branch_if_absent(&BranchRecord::new("feature", "commit-a", false))
+        .await
+        .unwrap());
+    // A second writer that also saw no branch must not overwrite the first
+    assert!(!handle
+        .create_branch_if_absent(&BranchRecord::new("feature", "commit-b", false))
+        .await
+        .unwrap());
+    assert_eq!(handle.get_branch_head("feature").await.unwrap(), "commit-a");
+}
+
+#[tokio::test]
+async fn test_reparent_commit_replaces_parents() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let id = CommitId::from_state(b"reparented");
+    let mut commit = CommitRecord::new(id.clone(), vec!["old-head".to_string()], "m", "a");
+    handle.save_commit(&commit).await.unwrap();
+
+    commit.parent_ids = vec!["new-head".to_string()];
+    handle.reparent_commit(&commit).await.unwrap();
+    let stored = handle.get_commit(&id.hash).await.unwrap().unwrap();
+    assert_eq!(stored.parent_ids, vec!["new-head".to_string()]);
+
+    let missing = CommitRecord::new(CommitId::from_state(b"missing"), vec![], "m", "a");
+    assert!(matches!(
+        handle.reparent_commit(&missing).await,
+        Err(StateError::CommitNotFound(_))
+    ));
+}
@@ -0,0 +1,55 @@
+//! Branch protection rules
+
+use oxidized_state::{BranchProtection, BranchRecord, StateError, SurrealHandle};
+
+#[tokio::test]
+async fn test_protection_defaults_off_and_can_be_replaced() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    assert!(handle.get_protection("main").await.unwrap().is_empty());
+
+    let strict = BranchProtection {
+        no_force_push: true,
+        no_delete: true,
+        require_linear: true,
+    };
+    handle.set_protection("main", strict).await.unwrap();
+    assert_eq!(handle.get_protection("main").await.unwrap(), strict);
+
+    let relaxed = BranchProtection {
+        no_force_push: true,
+        ..Default::default()
+    };
+    handle.set_protection("main", relaxed).await.unwrap();
+    assert_eq!(handle.get_protection("main").await.unwrap(), relaxed);
+    assert!(handle.get_protection("feature").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_no_delete_blocks_branch_deletion() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    handle
+        .save_branch(&BranchRecord::new("release", "abc", false))
+        .await
+        .unwrap();
+    handle
+        .set_protection(
+            "release",
+            BranchProtection {
+                no_delete: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    let err = handle.delete_branch("release").await.unwrap_err();
+    assert!(matches!(err, StateError::BranchProtected { .. }), "{err}");
+    assert!(handle.get_branch("release").await.unwrap().is_some());
+
+    handle
+        .set_protection("release", BranchProtection::default())
+        .await
+        .unwrap();
+    handle.delete_branch("release").await.unwrap();
+    assert!(handle.get_branch("release").await.unwrap().is_none());
+}
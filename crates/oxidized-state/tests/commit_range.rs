@@ -0,0 +1,119 @@
+use oxidized_state::{BranchRecord, CommitId, CommitRecord, StateError, SurrealHandle};
+
+async fn commit(handle: &SurrealHandle, label: &str, parents: &[&str]) -> String {
+    let id = CommitId::from_state(label.as_bytes());
+    let parent_ids = parents.iter().map(|p| p.to_string()).collect();
+    let record = CommitRecord::new(id.clone(), parent_ids, label, "test");
+    handle.save_commit(&record).await.unwrap();
+    id.hash
+}
+
+#[tokio::test]
+async fn test_commit_range_lists_only_commits_ahead_of_base() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+
+    // main: root <- m1
+    // feature:      m1 <- f1 <- f2
+    let root = commit(&handle, "root", &[]).await;
+    let m1 = commit(&handle, "m1", &[&root]).await;
+    let f1 = commit(&handle, "f1", &[&m1]).await;
+    let f2 = commit(&handle, "f2", &[&f1]).await;
+    handle
+        .save_branch(&BranchRecord::new("main", &m1, true))
+        .await
+        .unwrap();
+    handle
+        .save_branch(&BranchRecord::new("feature", &f2, false))
+        .await
+        .unwrap();
+
+    let range = handle.get_commit_range(&m1, &f2, 100).await.unwrap();
+    let ids: Vec<&str> = range.iter().map(|c| c.commit_id.hash.as_str()).collect();
+    assert_eq!(ids, vec![f2.as_str(), f1.as_str()]);
+
+    assert!(handle
+        .get_commit_range(&f2, &m1, 100)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_find_merge_base_of_diverged_branches() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+
+    let root = commit(&handle, "root", &[]).await;
+    let base = commit(&handle, "base", &[&root]).await;
+    let a1 = commit(&handle, "a1", &[&base]).await;
+    let b1 = commit(&handle, "b1", &[&base]).await;
+    let b2 = commit(&handle, "b2", &[&b1]).await;
+
+    assert_eq!(
+        handle.find_merge_base(&a1, &b2).await.unwrap(),
+        Some(base.clone())
+    );
+
+    let range = handle.get_commit_range(&a1, &b2, 100).await.unwrap();
+    let ids: Vec<&str> = range.iter().map(|c| c.commit_id.hash.as_str()).collect();
+    assert_eq!(ids, vec![b2.as_str(), b1.as_str()]);
+}
+
+#[tokio::test]
+async fn test_commit_range_across_a_merge_is_newest_first() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let at = |label: &str, parents: &[&str], day: u32| {
+        let mut record = CommitRecord::new(
+            CommitId::from_state(label.as_bytes()),
+            parents.iter().map(|p| p.to_string()).collect(),
+            label,
+            "test",
+        );
+        record.created_at = format!("2024-05-{:02}T12:00:00Z", day).parse().unwrap();
+        record
+    };
+
+    // root <- old (day 2) <- merge (day 5)
+    // root <- new (day 4) <-/
+    let root = at("merge-root", &[], 1);
+    let old = at("merge-old", &[&root.commit_id.hash], 2);
+    let new = at("merge-new", &[&root.commit_id.hash], 4);
+    let merge = at("merge-tip", &[&old.commit_id.hash, &new.commit_id.hash], 5);
+    for record in [&root, &old, &new, &merge] {
+        handle.save_commit(record).await.unwrap();
+    }
+
+    let range = handle
+        .get_commit_range(&root.commit_id.hash, &merge.commit_id.hash, 100)
+        .await
+        .unwrap();
+    let ids: Vec<&str> = range.iter().map(|c| c.commit_id.hash.as_str()).collect();
+    assert_eq!(
+        ids,
+        [
+            merge.commit_id.hash.as_str(),
+            new.commit_id.hash.as_str(),
+            old.commit_id.hash.as_str()
+        ]
+    );
+
+    // The limit keeps the newest commits, not the first ones walked
+    let range = handle
+        .get_commit_range(&root.commit_id.hash, &merge.commit_id.hash, 2)
+        .await
+        .unwrap();
+    assert_eq!(range[1].commit_id.hash, new.commit_id.hash);
+}
+
+#[tokio::test]
+async fn test_commit_range_rejects_unknown_endpoints() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let root = commit(&handle, "known-root", &[]).await;
+
+    for (from, to) in [("nope", root.as_str()), (root.as_str(), "nope")] {
+        let err = handle.get_commit_range(from, to, 10).await.unwrap_err();
+        assert!(
+            matches!(&err, StateError::CommitNotFound(id) if id == "nope"),
+            "{err}"
+        );
+    }
+}
@@ -0,0 +1,39 @@
+//! Batch memory insertion
+
+use oxidized_state::{MemoryRecord, SurrealHandle};
+
+#[tokio::test]
+async fn test_batch_insert_persists_all_memories() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+
+    let records: Vec<MemoryRecord> = (0..500)
+        .map(|i| {
+            let commit = if i % 2 == 0 {
+                "commit-even"
+            } else {
+                "commit-odd"
+            };
+            MemoryRecord::new(commit, &format!("key-{i}"), &format!("content {i}"))
+        })
+        .collect();
+    handle.save_memories_batch(&records).await.unwrap();
+
+    let even = handle.get_memories("commit-even").await.unwrap();
+    let odd = handle.get_memories("commit-odd").await.unwrap();
+    assert_eq!(even.len(), 250);
+    assert_eq!(odd.len(), 250);
+    assert!(even.iter().all(|m| m.commit_id == "commit-even"));
+    assert!(odd.iter().all(|m| m.commit_id == "commit-odd"));
+
+    let mut keys: Vec<String> = even.into_iter().chain(odd).map(|m| m.key).collect();
+    keys.sort();
+    keys.dedup();
+    assert_eq!(keys.len(), 500);
+}
+
+#[tokio::test]
+async fn test_empty_batch_is_a_noop() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    handle.save_memories_batch(&[]).await.unwrap();
+    assert!(handle.list_all_memories().await.unwrap().is_empty());
+}
@@ -0,0 +1,55 @@
+//! Time-series history of a single memory key along a branch
+
+use oxidized_state::{BranchRecord, CommitId, CommitRecord, MemoryRecord, SurrealHandle};
+
+async fn commit(
+    handle: &SurrealHandle,
+    label: &str,
+    parents: &[&str],
+    plan: Option<&str>,
+) -> String {
+    let id = CommitId::from_state(label.as_bytes());
+    let parent_ids = parents.iter().map(|p| p.to_string()).collect();
+    let record = CommitRecord::new(id.clone(), parent_ids, label, "test");
+    handle.save_commit(&record).await.unwrap();
+    if let Some(plan) = plan {
+        handle
+            .save_memory(&MemoryRecord::new(&id.hash, "plan", plan))
+            .await
+            .unwrap();
+    }
+    id.hash
+}
+
+#[tokio::test]
+async fn test_memory_history_lists_only_changes() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+
+    // root has no plan; c1 sets it, c2 keeps it, c3 changes it, c4 keeps it
+    let root = commit(&handle, "root", &[], None).await;
+    let c1 = commit(&handle, "c1", &[&root], Some("draft")).await;
+    let c2 = commit(&handle, "c2", &[&c1], Some("draft")).await;
+    let c3 = commit(&handle, "c3", &[&c2], Some("final")).await;
+    let c4 = commit(&handle, "c4", &[&c3], Some("final")).await;
+    handle
+        .save_branch(&BranchRecord::new("main", &c4, true))
+        .await
+        .unwrap();
+
+    let history = handle.get_memory_history("main", "plan").await.unwrap();
+    let summary: Vec<(&str, &str)> = history
+        .iter()
+        .map(|(hash, memory)| (hash.as_str(), memory.content.as_str()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![(c1.as_str(), "draft"), (c3.as_str(), "final")]
+    );
+
+    assert!(handle
+        .get_memory_history("main", "absent")
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(handle.get_memory_history("missing", "plan").await.is_err());
+}
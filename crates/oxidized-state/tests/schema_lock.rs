@@ -0,0 +1,113 @@
+//! Advisory locking around schema initialization
+
+use std::time::Duration;
+
+use oxidized_state::migrations::{applied_migrations, migrations, SCHEMA_LOCK};
+use oxidized_state::{init_schema_with_lock, DbLock, LockOptions, StateError, SurrealHandle};
+
+fn quick(timeout_ms: u64) -> LockOptions {
+    LockOptions {
+        timeout: Duration::from_millis(timeout_ms),
+        poll_interval: Duration::from_millis(10),
+        ..LockOptions::default()
+    }
+}
+
+#[tokio::test]
+async fn test_second_init_waits_for_the_first() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let db = handle.db().clone();
+
+    // The first process is mid-migration
+    let first = DbLock::acquire(&db, SCHEMA_LOCK, &quick(1_000))
+        .await
+        .unwrap();
+    let second = tokio::spawn({
+        let db = db.clone();
+        async move { init_schema_with_lock(&db, &quick(5_000)).await }
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!second.is_finished(), "second init must wait for the lock");
+    assert!(DbLock::holder_of(&db, SCHEMA_LOCK).await.unwrap().is_some());
+
+    first.release().await.unwrap();
+    second.await.unwrap().unwrap();
+
+    let ids: Vec<u32> = migrations().iter().map(|m| m.id).collect();
+    assert_eq!(applied_migrations(&db).await.unwrap(), ids);
+    assert_eq!(DbLock::holder_of(&db, SCHEMA_LOCK).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_init_times_out_while_lock_is_held() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let db = handle.db();
+    let held = DbLock::acquire(db, SCHEMA_LOCK, &quick(1_000))
+        .await
+        .unwrap();
+
+    let err = init_schema_with_lock(db, &quick(50)).await.unwrap_err();
+    match err {
+        StateError::LockTimeout { name, holder, .. } => {
+            assert_eq!(name, SCHEMA_LOCK);
+            assert!(holder.starts_with("pid "), "{holder}");
+        }
+        other => panic!("expected LockTimeout, got {other:?}"),
+    }
+
+    held.release().await.unwrap();
+    init_schema_with_lock(db, &quick(50)).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_abandoned_lock_expires_after_its_lease() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let db = handle.db();
+    let stale = DbLock::acquire(db, SCHEMA_LOCK, &quick(1_000))
+        .await
+        .unwrap();
+    // Simulate a crashed holder: the row stays, nobody releases it
+    std::mem::forget(stale);
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let options = LockOptions {
+        lease: Duration::from_millis(20),
+        ..quick(1_000)
+    };
+    init_schema_with_lock(db, &options).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_waiters_acquire_as_holders_release() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+    let db = handle.db().clone();
+    let held = DbLock::acquire(&db, SCHEMA_LOCK, &quick(1_000))
+        .await
+        .unwrap();
+
+    // Poll without pausing so waiters retry right as each holder releases
+    let options = LockOptions {
+        poll_interval: Duration::ZERO,
+        ..quick(10_000)
+    };
+    let waiters: Vec<_> = (0..8)
+        .map(|_| {
+            let db = db.clone();
+            tokio::spawn(async move {
+                let lock = DbLock::acquire(&db, SCHEMA_LOCK, &options).await?;
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                lock.release().await
+            })
+        })
+        .collect();
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(waiters.iter().all(|w| !w.is_finished()));
+    held.release().await.unwrap();
+
+    for waiter in waiters {
+        waiter.await.unwrap().unwrap();
+    }
+    assert_eq!(DbLock::holder_of(&db, SCHEMA_LOCK).await.unwrap(), None);
+}
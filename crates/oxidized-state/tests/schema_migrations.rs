@@ -0,0 +1,67 @@
+//! Integration tests for the versioned migration runner
+
+use oxidized_state::migrations::{applied_migrations, migrations};
+use oxidized_state::{init_schema, run_migrations, Migration, SurrealHandle};
+
+fn builtin_ids() -> Vec<u32> {
+    migrations().iter().map(|m| m.id).collect()
+}
+
+fn next_id() -> u32 {
+    builtin_ids().into_iter().max().unwrap_or(0) + 1
+}
+
+#[tokio::test]
+async fn test_initial_schema_is_recorded_as_migration_one() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+
+    let applied = applied_migrations(handle.db()).await.unwrap();
+    assert_eq!(applied.first(), Some(&1));
+    assert_eq!(applied, builtin_ids());
+}
+
+#[tokio::test]
+async fn test_applying_migrations_twice_is_a_noop() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+
+    let again = run_migrations(handle.db(), &migrations()).await.unwrap();
+    assert!(again.is_empty());
+    init_schema(handle.db()).await.unwrap();
+    assert_eq!(applied_migrations(handle.db()).await.unwrap(), builtin_ids());
+}
+
+#[tokio::test]
+async fn test_new_migration_runs_exactly_once() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+
+    let id = next_id();
+    let mut all = migrations();
+    all.push(Migration::new(
+        id,
+        "add_counter",
+        "CREATE migration_counter CONTENT { n: 1 };",
+    ));
+
+    assert_eq!(run_migrations(handle.db(), &all).await.unwrap(), vec![id]);
+    assert!(run_migrations(handle.db(), &all).await.unwrap().is_empty());
+
+    let mut result = handle
+        .db()
+        .query("SELECT VALUE n FROM migration_counter")
+        .await
+        .unwrap();
+    let rows: Vec<i64> = result.take(0).unwrap();
+    assert_eq!(rows, vec![1]);
+    let mut expected = builtin_ids();
+    expected.push(id);
+    assert_eq!(applied_migrations(handle.db()).await.unwrap(), expected);
+}
+
+#[tokio::test]
+async fn test_failed_migration_is_not_recorded() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+
+    let broken = Migration::new(next_id(), "broken", "THROW \"boom\";");
+    assert!(run_migrations(handle.db(), &[broken]).await.is_err());
+    assert_eq!(applied_migrations(handle.db()).await.unwrap(), builtin_ids());
+}
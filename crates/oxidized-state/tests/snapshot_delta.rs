@@ -0,0 +1,64 @@
+use oxidized_state::{CommitId, SurrealHandle};
+use serde_json::json;
+
+fn states() -> Vec<serde_json::Value> {
+    (0..7)
+        .map(|i| {
+            json!({
+                "step": i,
+                "goal": "summarize",
+                "memory": { "facts": (0..=i).collect::<Vec<_>>(), "last": format!("fact-{i}") },
+                "scratch": if i % 2 == 0 { json!("even") } else { json!(null) },
+            })
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_delta_chain_reconstructs_full_state() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+
+    let mut parent: Option<String> = None;
+    let mut ids = Vec::new();
+    for state in states() {
+        let id = CommitId::from_state(state.to_string().as_bytes());
+        handle
+            .save_snapshot_delta(&id, state, parent.as_deref(), 3)
+            .await
+            .unwrap();
+        parent = Some(id.hash.clone());
+        ids.push(id.hash);
+    }
+
+    for (id, expected) in ids.iter().zip(states()) {
+        let snapshot = handle.load_snapshot(id).await.unwrap();
+        assert_eq!(snapshot.state, expected, "mismatch for {id}");
+        assert!(!snapshot.is_delta());
+    }
+
+    // Keyframe every 3 commits: depths cycle 0, 1, 2, 0, 1, 2, 0
+    let depths: Vec<u32> = {
+        let mut out = Vec::new();
+        for id in &ids {
+            out.push(handle.load_snapshot(id).await.unwrap().chain_depth);
+        }
+        out
+    };
+    assert_eq!(depths, vec![0, 1, 2, 0, 1, 2, 0]);
+}
+
+#[tokio::test]
+async fn test_delta_falls_back_to_full_snapshot_without_parent_snapshot() {
+    let handle = SurrealHandle::setup_db().await.unwrap();
+
+    let state = json!({ "k": "v" });
+    let id = CommitId::from_state(b"orphan");
+    handle
+        .save_snapshot_delta(&id, state.clone(), Some("missing-parent"), 10)
+        .await
+        .unwrap();
+
+    let snapshot = handle.load_snapshot(&id.hash).await.unwrap();
+    assert_eq!(snapshot.state, state);
+    assert_eq!(snapshot.chain_depth, 0);
+}
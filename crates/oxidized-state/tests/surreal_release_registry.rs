@@ -11,6 +11,7 @@ fn sample_release_meta(label: &str) -> ReleaseMetadata {
         version_label: Some(label.to_string()),
         promoted_by: "ci".to_string(),
         notes: None,
+        ..Default::default()
     }
 }
 
@@ -131,6 +131,7 @@ fn sample_summary(total_events: u64, success: bool) -> RunSummary {
         final_state_digest: None,
         duration_ms: 100,
         success,
+        usage: None,
     }
 }
 
@@ -145,6 +146,65 @@ async fn ledger_create_run_returns_unique_ids() {
     assert_ne!(id1, id2);
 }
 
+#[test]
+fn run_id_from_seed_is_deterministic() {
+    assert_eq!(RunId::from_seed(42), RunId::from_seed(42));
+    assert_ne!(RunId::from_seed(42), RunId::from_seed(43));
+    assert!(uuid::Uuid::parse_str(&RunId::from_seed(42).0).is_ok());
+}
+
+#[tokio::test]
+async fn ledger_create_run_with_id_rejects_duplicate() {
+    let ledger = MemoryRunLedger::new();
+    let spec = ContentDigest::from_bytes(b"spec");
+
+    let run_id = ledger
+        .create_run_with_id(RunId::from_seed(7), &spec, sample_metadata())
+        .await
+        .unwrap();
+    assert_eq!(run_id, RunId::from_seed(7));
+    assert_eq!(ledger.get_run(&run_id).await.unwrap().run_id, run_id);
+
+    let err = ledger
+        .create_run_with_id(RunId::from_seed(7), &spec, sample_metadata())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, StorageError::RunAlreadyExists { .. }));
+    assert_eq!(ledger.list_runs(None).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn ledger_archive_run_removes_terminal_run() {
+    let ledger = MemoryRunLedger::new();
+    let spec = ContentDigest::from_bytes(b"spec");
+    let archive = format!("b3:{}", ContentDigest::from_bytes(b"archive blob"));
+    let run_id = ledger.create_run(&spec, sample_metadata()).await.unwrap();
+    ledger
+        .append_event(&run_id, sample_event(1, "graph_started"))
+        .await
+        .unwrap();
+
+    let err = ledger.archive_run(&run_id, &archive).await.unwrap_err();
+    assert!(matches!(err, StorageError::InvalidRunState { .. }));
+    assert_eq!(ledger.archived_run_digest(&run_id).await.unwrap(), None);
+
+    ledger
+        .complete_run(&run_id, sample_summary(1, true))
+        .await
+        .unwrap();
+    ledger.archive_run(&run_id, &archive).await.unwrap();
+
+    assert!(matches!(
+        ledger.get_run(&run_id).await.unwrap_err(),
+        StorageError::RunNotFound { .. }
+    ));
+    assert!(ledger.list_runs(None).await.unwrap().is_empty());
+    assert_eq!(
+        ledger.archived_run_digest(&run_id).await.unwrap(),
+        Some(archive)
+    );
+}
+
 #[tokio::test]
 async fn ledger_get_run_returns_created_run() {
     let ledger = MemoryRunLedger::new();
@@ -297,6 +357,7 @@ fn sample_release_meta(label: &str) -> ReleaseMetadata {
         version_label: Some(label.to_string()),
         promoted_by: "ci".to_string(),
         notes: None,
+        ..Default::default()
     }
 }
 
@@ -481,6 +542,58 @@ mod surreal_ledger_tests {
         assert_ne!(id1, id2);
     }
 
+    #[tokio::test]
+    async fn create_run_with_id_rejects_duplicate() {
+        let ledger = ledger().await;
+        let spec = ContentDigest::from_bytes(b"spec");
+
+        let run_id = ledger
+            .create_run_with_id(RunId::from_seed(7), &spec, sample_metadata())
+            .await
+            .unwrap();
+        assert_eq!(run_id, RunId::from_seed(7));
+        assert_eq!(ledger.get_run(&run_id).await.unwrap().run_id, run_id);
+
+        let err = ledger
+            .create_run_with_id(RunId::from_seed(7), &spec, sample_metadata())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StorageError::RunAlreadyExists { .. }));
+        assert_eq!(ledger.list_runs(None).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn archive_run_removes_terminal_run() {
+        let ledger = ledger().await;
+        let spec = ContentDigest::from_bytes(b"spec");
+        let archive = format!("b3:{}", ContentDigest::from_bytes(b"archive blob"));
+        let run_id = ledger.create_run(&spec, sample_metadata()).await.unwrap();
+        ledger
+            .append_event(&run_id, sample_event(1, "graph_started"))
+            .await
+            .unwrap();
+
+        let err = ledger.archive_run(&run_id, &archive).await.unwrap_err();
+        assert!(matches!(err, StorageError::InvalidRunState { .. }));
+        assert_eq!(ledger.archived_run_digest(&run_id).await.unwrap(), None);
+
+        ledger
+            .complete_run(&run_id, sample_summary(1, true))
+            .await
+            .unwrap();
+        ledger.archive_run(&run_id, &archive).await.unwrap();
+
+        assert!(matches!(
+            ledger.get_events(&run_id).await.unwrap_err(),
+            StorageError::RunNotFound { .. }
+        ));
+        assert!(ledger.list_runs(None).await.unwrap().is_empty());
+        assert_eq!(
+            ledger.archived_run_digest(&run_id).await.unwrap(),
+            Some(archive)
+        );
+    }
+
     #[tokio::test]
     async fn get_run_returns_created_run() {
         let ledger = ledger().await;
@@ -622,4 +735,128 @@ mod surreal_ledger_tests {
         assert_eq!(filtered.len(), 2);
         assert!(filtered.iter().all(|r| r.spec_digest == spec_a));
     }
+
+    #[tokio::test]
+    async fn list_agent_runs_filters_by_agent_and_time() {
+        let ledger = ledger().await;
+        let spec = ContentDigest::from_bytes(b"spec");
+        let other = RunMetadata {
+            agent_name: "other-agent".to_string(),
+            ..sample_metadata()
+        };
+
+        ledger.create_run(&spec, sample_metadata()).await.unwrap();
+        ledger.create_run(&spec, other).await.unwrap();
+        ledger.create_run(&spec, sample_metadata()).await.unwrap();
+
+        let runs = ledger
+            .list_agent_runs(Some("test-agent"), None)
+            .await
+            .unwrap();
+        assert_eq!(runs.len(), 2);
+        assert!(runs.iter().all(|r| r.metadata.agent_name == "test-agent"));
+        assert!(runs.windows(2).all(|w| w[0].created_at >= w[1].created_at));
+
+        let past = Utc::now() - chrono::Duration::hours(1);
+        assert_eq!(
+            ledger
+                .list_agent_runs(None, Some(past))
+                .await
+                .unwrap()
+                .len(),
+            3
+        );
+        let future = Utc::now() + chrono::Duration::hours(1);
+        assert!(ledger
+            .list_agent_runs(Some("test-agent"), Some(future))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn append_duplicate_seq_is_rejected_without_extra_row() {
+        let ledger = ledger().await;
+        let spec = ContentDigest::from_bytes(b"spec");
+        let run_id = ledger.create_run(&spec, sample_metadata()).await.unwrap();
+
+        ledger
+            .append_event(&run_id, sample_event(1, "graph_started"))
+            .await
+            .unwrap();
+        let err = ledger
+            .append_event(&run_id, sample_event(1, "graph_started"))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, StorageError::DuplicateEvent { seq: 1, .. }));
+        assert_eq!(ledger.get_events(&run_id).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn ledger_from_handle_shares_the_handles_database() {
+        let handle = oxidized_state::SurrealHandle::setup_db().await.unwrap();
+        let spec = ContentDigest::from_bytes(b"spec");
+        let run_id = SurrealRunLedger::from_handle(&handle)
+            .create_run(&spec, sample_metadata())
+            .await
+            .unwrap();
+
+        // A second ledger on the same handle sees the run; a fresh
+        // in-memory ledger does not
+        let run = SurrealRunLedger::from_handle(&handle)
+            .get_run(&run_id)
+            .await
+            .unwrap();
+        assert_eq!(run.spec_digest, spec);
+        assert!(ledger().await.get_run(&run_id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn subscribe_tails_events_until_run_completes() {
+        use futures::StreamExt;
+        use std::time::Duration;
+
+        let ledger = SurrealRunLedger::in_memory()
+            .await
+            .expect("in_memory() failed");
+        let spec = ContentDigest::from_bytes(b"spec");
+        let run_id = ledger.create_run(&spec, sample_metadata()).await.unwrap();
+
+        ledger
+            .append_event(&run_id, sample_event(1, "graph_started"))
+            .await
+            .unwrap();
+
+        let appender = {
+            let ledger = ledger.clone();
+            let run_id = run_id.clone();
+            tokio::spawn(async move {
+                for seq in 2..=4 {
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    ledger
+                        .append_event(&run_id, sample_event(seq, "node_entered"))
+                        .await
+                        .unwrap();
+                }
+                ledger
+                    .complete_run(&run_id, sample_summary(4, true))
+                    .await
+                    .unwrap();
+            })
+        };
+
+        let seen: Vec<u64> = tokio::time::timeout(
+            Duration::from_secs(10),
+            ledger
+                .subscribe_with_interval(&run_id, Duration::from_millis(5))
+                .map(|e| e.seq)
+                .collect::<Vec<_>>(),
+        )
+        .await
+        .expect("stream should end once the run completes");
+
+        appender.await.unwrap();
+        assert_eq!(seen, vec![1, 2, 3, 4]);
+    }
 }
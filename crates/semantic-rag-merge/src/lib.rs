@@ -7,8 +7,11 @@
 //!
 //! Focus: Semantic conflict resolution and memory synthesis.
 
-use anyhow::Result;
-use oxidized_state::{CommitId, EdgeType, MemoryRecord, SurrealHandle};
+use anyhow::{anyhow, bail, Result};
+use oxidized_state::{
+    memory_id, CommitId, ContentDigest, DecisionRecord, EdgeType, MemoryProvenanceRecord,
+    MemoryRecord, ProvenanceSourceType, SurrealHandle,
+};
 use serde::{Deserialize, Serialize};
 
 /// Difference between two memory vector stores
@@ -57,10 +60,48 @@ pub struct MergeResult {
     pub auto_resolved: usize,
     /// Any conflicts that couldn't be auto-resolved
     pub manual_conflicts: Vec<MemoryConflict>,
+    /// Arbiter confidence over every conflict, see [`confidence_histogram`]
+    pub confidence_histogram: [usize; 10],
+    /// One decision per conflict, under the task `merge-auto:<merge_commit>`.
+    /// The merge does not save them; the caller records them.
+    pub decisions: Vec<DecisionRecord>,
     /// Summary of the merge
     pub summary: String,
 }
 
+/// Auto-resolutions below this confidence are reported as manual conflicts.
+pub const MANUAL_REVIEW_CONFIDENCE: f32 = 0.6;
+
+/// Count confidences into ten buckets of width 0.1: bucket `i` holds
+/// `[i/10, (i+1)/10)`, with 1.0 counted in the last bucket. Values outside
+/// `[0, 1]` are clamped.
+pub fn confidence_histogram(confidences: impl IntoIterator<Item = f32>) -> [usize; 10] {
+    let mut buckets = [0; 10];
+    for confidence in confidences {
+        let bucket = (confidence.clamp(0.0, 1.0) * 10.0) as usize;
+        buckets[bucket.min(9)] += 1;
+    }
+    buckets
+}
+
+/// Decision task prefix used to audit manual conflict resolutions.
+pub const MERGE_RESOLUTION_TASK_PREFIX: &str = "merge-resolve";
+
+/// Decision task prefix used to audit the arbiter's automatic resolutions.
+pub const MERGE_AUTO_RESOLUTION_TASK_PREFIX: &str = "merge-auto";
+
+/// How a user resolved a manual merge conflict.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum ConflictChoice {
+    /// Keep the memory from the first parent
+    SideA,
+    /// Keep the memory from the second parent
+    SideB,
+    /// Replace both with a user-supplied value
+    Value(String),
+}
+
 /// Diff memory vectors between two commits
 ///
 /// # TDD: test_memory_diff_shows_only_new_vectors
@@ -264,6 +305,15 @@ pub async fn synthesize_memory(
     Ok(merged_memories)
 }
 
+/// The run event a merge is performed at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeRun {
+    /// Run performing the merge
+    pub run_id: String,
+    /// Seq of the merge event within the run
+    pub seq: u64,
+}
+
 /// Perform a semantic merge of two branches
 pub async fn semantic_merge(
     handle: &SurrealHandle,
@@ -272,23 +322,66 @@ pub async fn semantic_merge(
     message: &str,
     author: &str,
 ) -> Result<MergeResult> {
-    // Create the merge commit ID
-    let state_data = format!("merge:{}:{}", commit_a, commit_b);
-    let merge_commit_id = CommitId::from_state(state_data.as_bytes());
-
-    // Synthesize memories
-    let merged_memories =
-        synthesize_memory(handle, commit_a, commit_b, &merge_commit_id.hash).await?;
+    semantic_merge_with_progress(
+        handle,
+        commit_a,
+        commit_b,
+        message,
+        author,
+        None,
+        &mut |_, _| {},
+    )
+    .await
+}
 
-    // Save merged memories
-    for mem in &merged_memories {
-        handle.save_memory(mem).await?;
-    }
+/// [`semantic_merge`], calling `on_progress(done, total)` as conflicts are
+/// reviewed: once with `done == 0` before the first, then after each one.
+///
+/// When the merge is performed by a run, every merged memory gets a
+/// [`ProvenanceSourceType::Merged`] provenance row pointing at `merge_run`.
+pub async fn semantic_merge_with_progress(
+    handle: &SurrealHandle,
+    commit_a: &str,
+    commit_b: &str,
+    message: &str,
+    author: &str,
+    merge_run: Option<&MergeRun>,
+    on_progress: &mut (dyn FnMut(usize, usize) + Send),
+) -> Result<MergeResult> {
+    let plan = plan_semantic_merge(handle, commit_a, commit_b, on_progress).await?;
+    let merge_commit_id = plan.merge_commit_id;
+
+    // Save merged memories and their provenance in one transaction
+    let provenance: Vec<MemoryProvenanceRecord> = merge_run
+        .map(|run| {
+            plan.merged_memories
+                .iter()
+                .map(|memory| {
+                    MemoryProvenanceRecord::from_run_event(
+                        memory_id(&memory.commit_id, &memory.key),
+                        run.run_id.clone(),
+                        run.seq,
+                        ProvenanceSourceType::Merged,
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    handle
+        .save_memories_with_provenance(&plan.merged_memories, &provenance)
+        .await?;
 
-    // Save merge snapshot so load_snapshot(merge_commit_id) works
+    // Save merge snapshot so load_snapshot(merge_commit_id) works. The
+    // manual conflict keys drive `resolve_merge_conflict` later on.
+    let manual_keys: Vec<&str> = plan
+        .manual_conflicts
+        .iter()
+        .map(|c| c.key.as_str())
+        .collect();
     let merge_state = serde_json::json!({
         "merged_from": [commit_a, commit_b],
-        "memory_count": merged_memories.len(),
+        "memory_count": plan.merged_memories.len(),
+        "manual_conflicts": manual_keys,
     });
     handle.save_snapshot(&merge_commit_id, merge_state).await?;
 
@@ -309,13 +402,93 @@ pub async fn semantic_merge(
         .save_commit_graph_edge_typed(&merge_commit_id.hash, commit_b, EdgeType::Merge)
         .await?;
 
-    // Get delta for summary
+    Ok(MergeResult {
+        merge_commit_id,
+        auto_resolved: plan.auto_resolved,
+        manual_conflicts: plan.manual_conflicts,
+        confidence_histogram: plan.confidence_histogram,
+        decisions: plan.decisions,
+        summary: plan.summary,
+    })
+}
+
+/// What [`semantic_merge`] would write, computed without writing anything
+#[derive(Debug, Clone)]
+pub struct MergePlan {
+    /// ID the merge commit would get
+    pub merge_commit_id: CommitId,
+    /// Memories the merge commit would carry
+    pub merged_memories: Vec<MemoryRecord>,
+    /// Number of automatic resolutions
+    pub auto_resolved: usize,
+    /// Conflicts that would be left for manual resolution
+    pub manual_conflicts: Vec<MemoryConflict>,
+    /// Arbiter confidence over every conflict, see [`confidence_histogram`]
+    pub confidence_histogram: [usize; 10],
+    /// One decision per conflict, under the task `merge-auto:<merge_commit>`
+    pub decisions: Vec<DecisionRecord>,
+    /// Summary the merge would report
+    pub summary: String,
+}
+
+/// Work out a semantic merge of `commit_a` and `commit_b` without writing
+/// to the database; [`semantic_merge_with_progress`] applies the plan.
+pub async fn plan_semantic_merge(
+    handle: &SurrealHandle,
+    commit_a: &str,
+    commit_b: &str,
+    on_progress: &mut (dyn FnMut(usize, usize) + Send),
+) -> Result<MergePlan> {
+    // Create the merge commit ID
+    let state_data = format!("merge:{}:{}", commit_a, commit_b);
+    let merge_commit_id = CommitId::from_state(state_data.as_bytes());
+
+    // Synthesize memories
+    let merged_memories =
+        synthesize_memory(handle, commit_a, commit_b, &merge_commit_id.hash).await?;
+
+    // Get delta for summary and to flag low-confidence resolutions
     let delta = diff_memory_vectors(handle, commit_a, commit_b).await?;
+    let mut manual_conflicts = Vec::new();
+    let mut confidences = Vec::with_capacity(delta.conflicts.len());
+    let mut decisions = Vec::with_capacity(delta.conflicts.len());
+    on_progress(0, delta.conflicts.len());
+    for (i, conflict) in delta.conflicts.iter().enumerate() {
+        let resolved = resolve_conflict_state(&[], &[], conflict).await?;
+        let side = resolved.favored_branch.as_deref().unwrap_or("A");
+        let other = if side == "B" { "A" } else { "B" };
+        let action = if resolved.confidence < MANUAL_REVIEW_CONFIDENCE {
+            manual_conflicts.push(conflict.clone());
+            format!("{}: chose side {side}, pending manual review", conflict.key)
+        } else {
+            format!("{}: chose side {side}", conflict.key)
+        };
+        decisions.push(
+            DecisionRecord::new(
+                uuid::Uuid::new_v4().to_string(),
+                merge_commit_id.hash.clone(),
+                format!(
+                    "{MERGE_AUTO_RESOLUTION_TASK_PREFIX}:{}",
+                    merge_commit_id.hash
+                ),
+                action,
+                resolved.reasoning.clone(),
+                resolved.confidence,
+            )
+            .with_alternatives(vec![format!("{}: chose side {other}", conflict.key)])
+            .with_inputs(conflict_inputs(conflict, commit_a, commit_b)),
+        );
+        confidences.push(resolved.confidence);
+        on_progress(i + 1, delta.conflicts.len());
+    }
 
-    Ok(MergeResult {
+    Ok(MergePlan {
         merge_commit_id,
-        auto_resolved: delta.conflicts.len(),
-        manual_conflicts: vec![], // All resolved automatically for now
+        merged_memories,
+        auto_resolved: delta.conflicts.len() - manual_conflicts.len(),
+        manual_conflicts,
+        confidence_histogram: confidence_histogram(confidences),
+        decisions,
         summary: format!(
             "Merged {} memories from A, {} from B, resolved {} conflicts",
             delta.only_in_a.len(),
@@ -325,6 +498,128 @@ pub async fn semantic_merge(
     })
 }
 
+/// List the manual conflicts of a merge commit that have not been resolved yet.
+///
+/// Conflicts are read from the merge snapshot's `manual_conflicts` keys and
+/// rebuilt from the two parent commits. A key counts as resolved once the
+/// merge commit's memory for it carries a `manual_resolution` entry.
+pub async fn unresolved_conflicts(
+    handle: &SurrealHandle,
+    merge_commit: &str,
+) -> Result<Vec<MemoryConflict>> {
+    let (parent_a, parent_b) = merge_parents(handle, merge_commit).await?;
+
+    let snapshot = handle.load_snapshot(merge_commit).await?;
+    let manual_keys: Vec<String> = snapshot
+        .state
+        .get("manual_conflicts")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+
+    let resolved: Vec<String> = handle
+        .get_memories(merge_commit)
+        .await?
+        .into_iter()
+        .filter(|m| m.metadata.get("manual_resolution").is_some())
+        .map(|m| m.key)
+        .collect();
+
+    let delta = diff_memory_vectors(handle, &parent_a, &parent_b).await?;
+    Ok(delta
+        .conflicts
+        .into_iter()
+        .filter(|c| manual_keys.contains(&c.key) && !resolved.contains(&c.key))
+        .collect())
+}
+
+/// Decision inputs for `conflict`: its key, parents and the digests of the
+/// two contents. The contents themselves stay in the memory table.
+fn conflict_inputs(conflict: &MemoryConflict, commit_a: &str, commit_b: &str) -> serde_json::Value {
+    serde_json::json!({
+        "key": conflict.key,
+        "commit_a": commit_a,
+        "commit_b": commit_b,
+        "content_a": ContentDigest::from_bytes(conflict.memory_a.content.as_bytes()),
+        "content_b": ContentDigest::from_bytes(conflict.memory_b.content.as_bytes()),
+    })
+}
+
+/// A manual conflict resolution and the decision that explains it
+#[derive(Debug, Clone)]
+pub struct ConflictResolution {
+    /// The merge commit's new memory for the conflicting key
+    pub memory: MemoryRecord,
+    /// The choice, under the task `merge-resolve:<merge_commit>`; not saved
+    pub decision: DecisionRecord,
+}
+
+/// Resolve one manual conflict on a merge commit.
+///
+/// Replaces the merge commit's memory for `conflict.key` with the chosen
+/// value and returns the choice as a [`DecisionRecord`] under the task
+/// `merge-resolve:<merge_commit>` for the caller to record. Each call is
+/// persisted on its own, so an interrupted resolution session can be resumed.
+pub async fn resolve_merge_conflict(
+    handle: &SurrealHandle,
+    merge_commit: &str,
+    conflict: &MemoryConflict,
+    choice: ConflictChoice,
+) -> Result<ConflictResolution> {
+    let (value, action) = match &choice {
+        ConflictChoice::SideA => (conflict.memory_a.content.clone(), "chose side A"),
+        ConflictChoice::SideB => (conflict.memory_b.content.clone(), "chose side B"),
+        ConflictChoice::Value(v) => (v.clone(), "supplied value"),
+    };
+
+    let memory =
+        MemoryRecord::new(merge_commit, &conflict.key, &value).with_metadata(serde_json::json!({
+            "merged_from": [conflict.memory_a.commit_id, conflict.memory_b.commit_id],
+            "manual_resolution": choice,
+        }));
+
+    handle
+        .delete_commit_memory(merge_commit, &conflict.key)
+        .await?;
+    let saved = handle.save_memory(&memory).await?;
+
+    let alternatives = ["chose side A", "chose side B", "supplied value"]
+        .into_iter()
+        .filter(|a| *a != action)
+        .map(|a| format!("{}: {}", conflict.key, a))
+        .collect();
+    let decision = DecisionRecord::new(
+        uuid::Uuid::new_v4().to_string(),
+        merge_commit.to_string(),
+        format!("{MERGE_RESOLUTION_TASK_PREFIX}:{merge_commit}"),
+        format!("{}: {}", conflict.key, action),
+        "manual merge conflict resolution".to_string(),
+        1.0,
+    )
+    .with_alternatives(alternatives)
+    .with_inputs(conflict_inputs(
+        conflict,
+        &conflict.memory_a.commit_id,
+        &conflict.memory_b.commit_id,
+    ));
+
+    Ok(ConflictResolution {
+        memory: saved,
+        decision,
+    })
+}
+
+/// Return the two parents of a merge commit.
+async fn merge_parents(handle: &SurrealHandle, merge_commit: &str) -> Result<(String, String)> {
+    let commit = handle
+        .get_commit(merge_commit)
+        .await?
+        .ok_or_else(|| anyhow!("merge commit not found: {merge_commit}"))?;
+    match commit.parent_ids.as_slice() {
+        [a, b] => Ok((a.clone(), b.clone())),
+        _ => bail!("{merge_commit} is not a merge commit"),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -416,6 +711,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_confidence_histogram_buckets_by_tenths() {
+        let histogram = confidence_histogram([0.0, 0.05, 0.31, 0.55, 0.59, 0.6, 0.99, 1.0, 1.3]);
+        assert_eq!(histogram, [2, 0, 0, 1, 0, 2, 1, 0, 0, 3]);
+        assert_eq!(confidence_histogram([]), [0; 10]);
+    }
+
+    #[tokio::test]
+    async fn test_merge_histogram_counts_every_conflict_confidence() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+        for (key, a, b) in [
+            ("k1", "short", "longer content here"),
+            ("k2", "same length", "same lengtH"),
+            ("k3", "x", "a considerably longer and more detailed memory"),
+        ] {
+            handle
+                .save_memory(&MemoryRecord::new("hist-a", key, a))
+                .await
+                .unwrap();
+            handle
+                .save_memory(&MemoryRecord::new("hist-b", key, b))
+                .await
+                .unwrap();
+        }
+        let delta = diff_memory_vectors(&handle, "hist-a", "hist-b")
+            .await
+            .unwrap();
+        let mut expected = Vec::new();
+        for conflict in &delta.conflicts {
+            let resolved = resolve_conflict_state(&[], &[], conflict).await.unwrap();
+            expected.push(resolved.confidence);
+        }
+
+        let plan = plan_semantic_merge(&handle, "hist-a", "hist-b", &mut |_, _| {})
+            .await
+            .unwrap();
+
+        assert_eq!(plan.confidence_histogram.iter().sum::<usize>(), 3);
+        assert_eq!(plan.confidence_histogram, confidence_histogram(expected));
+    }
+
     #[tokio::test]
     async fn test_arbiter_resolves_value_conflict_based_on_cot() {
         let conflict = MemoryConflict {
@@ -524,4 +860,114 @@ mod tests {
             "Summary should mention merged memories"
         );
     }
+
+    #[tokio::test]
+    async fn test_manual_conflict_resolved_with_side_b() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+
+        let commit_id_a = oxidized_state::CommitId::from_state(b"resolve-a");
+        let commit_id_b = oxidized_state::CommitId::from_state(b"resolve-b");
+        for (id, msg) in [(&commit_id_a, "A"), (&commit_id_b, "B")] {
+            let commit = oxidized_state::CommitRecord::new(id.clone(), vec![], msg, "agent");
+            handle.save_commit(&commit).await.unwrap();
+        }
+
+        // Equal-strength candidates so the arbiter is not confident.
+        let mem_a = MemoryRecord::new(&commit_id_a.hash, "plan", "value A");
+        let mem_b = MemoryRecord::new(&commit_id_b.hash, "plan", "value B");
+        handle.save_memory(&mem_a).await.unwrap();
+        handle.save_memory(&mem_b).await.unwrap();
+
+        let result = semantic_merge(
+            &handle,
+            &commit_id_a.hash,
+            &commit_id_b.hash,
+            "Merge",
+            "agent-git",
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.manual_conflicts.len(), 1);
+        let merge_commit = result.merge_commit_id.hash.clone();
+
+        let pending = unresolved_conflicts(&handle, &merge_commit).await.unwrap();
+        assert_eq!(pending.len(), 1);
+
+        let resolution =
+            resolve_merge_conflict(&handle, &merge_commit, &pending[0], ConflictChoice::SideB)
+                .await
+                .unwrap();
+
+        let memories = handle.get_memories(&merge_commit).await.unwrap();
+        let plan: Vec<_> = memories.iter().filter(|m| m.key == "plan").collect();
+        assert_eq!(plan.len(), 1);
+        assert_eq!(plan[0].content, "value B");
+
+        assert!(unresolved_conflicts(&handle, &merge_commit)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let decision = &resolution.decision;
+        assert_eq!(
+            decision.task,
+            format!("{MERGE_RESOLUTION_TASK_PREFIX}:{merge_commit}")
+        );
+        assert_eq!(decision.action, "plan: chose side B");
+        assert_eq!(
+            decision.alternatives,
+            vec!["plan: chose side A", "plan: supplied value"]
+        );
+        let inputs = decision.inputs.as_ref().expect("inputs recorded");
+        assert_eq!(
+            inputs["content_b"],
+            ContentDigest::from_bytes(b"value B").to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_auto_resolution_returns_decision_without_contents() {
+        let handle = SurrealHandle::setup_db().await.unwrap();
+
+        let commit_a = "auto-decision-a";
+        let commit_b = "auto-decision-b";
+        let mem_a = MemoryRecord::new(commit_a, "strategy", "x");
+        let mem_b = MemoryRecord::new(
+            commit_b,
+            "strategy",
+            "retry with exponential backoff and jitter",
+        )
+        .with_metadata(serde_json::json!({"source": "run", "step": 3, "tool": "planner"}));
+        handle.save_memory(&mem_a).await.unwrap();
+        handle.save_memory(&mem_b).await.unwrap();
+
+        let result = semantic_merge(&handle, commit_a, commit_b, "Merge", "agent-git")
+            .await
+            .unwrap();
+        assert_eq!(result.auto_resolved, 1);
+        let merge_commit = result.merge_commit_id.hash;
+
+        let task = format!("{MERGE_AUTO_RESOLUTION_TASK_PREFIX}:{merge_commit}");
+        let audit = &result.decisions;
+        assert_eq!(audit.len(), 1);
+        assert_eq!(audit[0].task, task);
+        assert_eq!(audit[0].action, "strategy: chose side B");
+        assert_eq!(audit[0].alternatives, vec!["strategy: chose side A"]);
+        assert!(audit[0].rationale.starts_with("Chose branch B"));
+        assert!(audit[0].confidence >= MANUAL_REVIEW_CONFIDENCE);
+        let inputs = audit[0].inputs.as_ref().expect("inputs recorded");
+        assert_eq!(inputs["key"], "strategy");
+        assert_eq!(inputs["commit_a"], commit_a);
+        assert_eq!(
+            inputs["content_a"],
+            ContentDigest::from_bytes(b"x").to_string()
+        );
+
+        // Recording is left to the caller
+        assert!(handle
+            .get_decision_history(&task, 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
 }